@@ -0,0 +1,174 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Converts StackerDB chunk events with arbitrary slot metadata and contents into signer
+//! events, as the miner's sign coordinator does with what the signers write. The conversion
+//! must not panic, and must keep exactly the chunks that decode on their own.
+//!
+//! ```text
+//! cargo +nightly fuzz run fuzz_signer_chunks_event -- -malloc_limit_mb=128
+//! ```
+
+#![no_main]
+
+use blockstack_lib::chainstate::stacks::boot::{MINERS_NAME, SIGNERS_NAME};
+use blockstack_lib::chainstate::stacks::events::StackerDBChunksEvent;
+use blockstack_lib::util_lib::boot::boot_code_id;
+use clarity::vm::ContractName;
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use libsigner::envelope::open_payload;
+use libsigner::protocol::untag_message;
+use libsigner::v1::messages::SignerMessage;
+use libsigner::{EventError, SignerEvent};
+use libstackerdb::StackerDBChunkData;
+use stacks_common::codec::{read_next, StacksMessageCodec};
+use stacks_common::types::chainstate::StacksPrivateKey;
+
+#[derive(Debug, Arbitrary)]
+enum FuzzContract {
+    Miners,
+    Signers { signer_set: u8, message_id: u32 },
+    Other { name: String },
+}
+
+#[derive(Debug, Arbitrary)]
+struct FuzzChunk {
+    slot_id: u32,
+    slot_version: u32,
+    data: Vec<u8>,
+    /// Unsigned chunks carry an empty signature, from which no key can be recovered
+    signed: bool,
+}
+
+#[derive(Debug, Arbitrary)]
+struct FuzzChunksEvent {
+    contract: FuzzContract,
+    mainnet: bool,
+    chunks: Vec<FuzzChunk>,
+}
+
+fuzz_target!(|input: FuzzChunksEvent| {
+    let contract_id = match &input.contract {
+        FuzzContract::Miners => boot_code_id(MINERS_NAME, input.mainnet),
+        FuzzContract::Signers {
+            signer_set,
+            message_id,
+        } => boot_code_id(
+            &format!("{SIGNERS_NAME}-{signer_set}-{message_id}"),
+            input.mainnet,
+        ),
+        FuzzContract::Other { name } => {
+            // names the conversion recognizes are covered by the other variants
+            if name == MINERS_NAME || name.starts_with(SIGNERS_NAME) {
+                return;
+            }
+            let Ok(contract_name) = ContractName::try_from(name.clone()) else {
+                return;
+            };
+            let mut contract_id = boot_code_id(MINERS_NAME, input.mainnet);
+            contract_id.name = contract_name;
+            contract_id
+        }
+    };
+    let signer_sk = StacksPrivateKey::from_seed(&[0x01; 32]);
+    let modified_slots: Vec<_> = input
+        .chunks
+        .iter()
+        .map(|chunk| {
+            let mut chunk_data =
+                StackerDBChunkData::new(chunk.slot_id, chunk.slot_version, chunk.data.clone());
+            if chunk.signed {
+                chunk_data.sign(&signer_sk).expect("Failed to sign chunk");
+            }
+            chunk_data
+        })
+        .collect();
+
+    // what each chunk decodes to on its own, if anything
+    let decoded: Vec<_> = input
+        .chunks
+        .iter()
+        .map(|chunk| {
+            let payload = open_payload(&chunk.data).ok()?;
+            match input.contract {
+                FuzzContract::Miners => {
+                    let (_, watermark, mut data) = untag_message(&payload).ok()?;
+                    let message = SignerMessage::consensus_deserialize(&mut data).ok()?;
+                    Some((watermark, message))
+                }
+                _ => Some((None, read_next::<SignerMessage, _>(&mut &payload[..]).ok()?)),
+            }
+        })
+        .collect();
+
+    let event = StackerDBChunksEvent {
+        contract_id,
+        modified_slots,
+    };
+    match (
+        SignerEvent::<SignerMessage>::try_from(event),
+        &input.contract,
+    ) {
+        (Ok(SignerEvent::MinerMessages(messages, _)), FuzzContract::Miners) => {
+            let expected: Vec<_> = input
+                .chunks
+                .iter()
+                .zip(decoded)
+                .filter_map(|(chunk, decoded)| Some((chunk, decoded?)))
+                .map(|(chunk, (watermark, message))| {
+                    assert!(chunk.signed, "Accepted a miner message without a signature");
+                    (chunk.slot_id, chunk.slot_version, watermark, message)
+                })
+                .collect();
+            let received: Vec<_> = messages
+                .into_iter()
+                .map(|msg| (msg.slot_id, msg.slot_version, msg.watermark, msg.message))
+                .collect();
+            assert_eq!(received, expected);
+        }
+        (Err(EventError::EmptyChunksEvent), FuzzContract::Miners) => {
+            assert!(decoded.iter().all(Option::is_none));
+        }
+        (Err(EventError::MalformedRequest(_)), FuzzContract::Miners) => {
+            // a miner message that decodes but whose signer cannot be recovered
+            assert!(input
+                .chunks
+                .iter()
+                .zip(decoded.iter())
+                .any(|(chunk, decoded)| !chunk.signed && decoded.is_some()));
+        }
+        (Ok(SignerEvent::SignerMessages(_, messages)), FuzzContract::Signers { .. }) => {
+            let expected: Vec<_> = input
+                .chunks
+                .iter()
+                .zip(decoded)
+                .filter(|(chunk, _)| chunk.signed)
+                .filter_map(|(chunk, decoded)| {
+                    let (_, message) = decoded?;
+                    Some((chunk.slot_id, chunk.slot_version, message))
+                })
+                .collect();
+            let received: Vec<_> = messages
+                .into_iter()
+                .map(|msg| (msg.slot_id, msg.slot_version, msg.message))
+                .collect();
+            assert_eq!(received, expected);
+        }
+        (Err(EventError::UnrecognizedStackerDBContract(_)), FuzzContract::Signers { .. })
+        | (Err(EventError::UnrecognizedStackerDBContract(_)), FuzzContract::Other { .. }) => {}
+        (res, contract) => panic!("Unexpected conversion of {contract:?}: {res:?}"),
+    }
+});
@@ -0,0 +1,74 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Feeds arbitrary bytes to the signer message decoders, as a miner or signer reading a
+//! StackerDB chunk would. Decoding must not panic, and whatever decodes must survive a
+//! round trip through the encoder and the payload envelope unchanged.
+//!
+//! A length prefix must never be trusted for an allocation, so run with a malloc limit well
+//! below what a forged `u32` length would ask for:
+//!
+//! ```text
+//! cargo +nightly fuzz run fuzz_signer_message regressions/fuzz_signer_message -- -malloc_limit_mb=128
+//! ```
+
+#![no_main]
+
+use std::fmt::Debug;
+
+use libfuzzer_sys::fuzz_target;
+use libsigner::envelope::{open_payload, seal_payload, EnvelopeConfig, ENVELOPE_PREFIX};
+use libsigner::{v0, v1};
+use stacks_common::codec::StacksMessageCodec;
+
+/// Decode `data` as a `T`, both bare and as the payload of a chunk, and check that both
+/// decode the same thing and that it re-encodes to something that decodes to it again
+fn check_decoder<T: StacksMessageCodec + PartialEq + Debug>(data: &[u8]) {
+    let bare = T::consensus_deserialize(&mut &data[..]);
+    let opened = open_payload(data)
+        .ok()
+        .and_then(|payload| T::consensus_deserialize(&mut &payload[..]).ok());
+    // a payload that is not enveloped is the message itself
+    if data.first() != Some(&ENVELOPE_PREFIX) {
+        assert_eq!(bare.as_ref().ok(), opened.as_ref());
+    }
+    let Some(message) = opened else {
+        return;
+    };
+
+    let reencoded = message.serialize_to_vec();
+    let redecoded = T::consensus_deserialize(&mut &reencoded[..])
+        .expect("Failed to decode a re-encoded message");
+    assert_eq!(message, redecoded);
+
+    for compression_threshold in [0, usize::MAX] {
+        let sealed = seal_payload(
+            reencoded.clone(),
+            Some(&EnvelopeConfig {
+                compression_threshold,
+            }),
+        )
+        .expect("Failed to seal a message");
+        let payload = open_payload(&sealed).expect("Failed to open a sealed message");
+        let unsealed =
+            T::consensus_deserialize(&mut &payload[..]).expect("Failed to decode a sealed message");
+        assert_eq!(message, unsealed);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    check_decoder::<v1::messages::SignerMessage>(data);
+    check_decoder::<v0::messages::SignerMessage>(data);
+});
@@ -0,0 +1,336 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The payload of a StackerDB chunk written by a miner or a signer is either bare (the
+//! serialized message itself, as every writer has always written it) or wrapped in an
+//! envelope that says how the rest of the chunk is encoded:
+//!
+//! ```text
+//! ENVELOPE_PREFIX | version: u8 | flags: u8 | payload
+//! ```
+//!
+//! With `FLAG_ZSTD` set, the payload is zstd-compressed. Readers accept both forms, so
+//! writers can start enveloping their payloads once the readers they write to have upgraded.
+
+use std::borrow::Cow;
+
+use libstackerdb::STACKERDB_MAX_CHUNK_SIZE;
+use stacks_common::codec::Error as CodecError;
+
+/// Leading byte of an enveloped payload.
+/// No `SignerMessage` type prefix, `TAGGED_MESSAGE_PREFIX` or `CAPABILITIES_MESSAGE_PREFIX`
+/// uses this value.
+pub const ENVELOPE_PREFIX: u8 = 0xfd;
+
+/// The envelope version this library writes and reads
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Set if the payload is zstd-compressed
+pub const FLAG_ZSTD: u8 = 0x01;
+
+/// Length of the header of an enveloped payload
+const ENVELOPE_HEADER_LEN: usize = 3;
+
+/// Every flag this library knows how to read
+const KNOWN_FLAGS: u8 = FLAG_ZSTD;
+
+/// Payloads longer than this are compressed, unless the writer is configured otherwise
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 16 * 1024;
+
+/// zstd compression level of enveloped payloads
+const ZSTD_LEVEL: i32 = 3;
+
+/// How a writer envelopes its payloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeConfig {
+    /// Payloads longer than this many bytes are compressed
+    pub compression_threshold: usize,
+}
+
+impl Default for EnvelopeConfig {
+    fn default() -> Self {
+        Self {
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        }
+    }
+}
+
+/// Wrap a serialized message in an envelope as configured by `envelope`, or leave it bare if
+/// there is no `envelope`. The payload is only compressed if that makes it shorter.
+pub fn seal_payload(
+    payload: Vec<u8>,
+    envelope: Option<&EnvelopeConfig>,
+) -> Result<Vec<u8>, CodecError> {
+    let Some(envelope) = envelope else {
+        return Ok(payload);
+    };
+    let mut flags = 0;
+    let mut body = payload;
+    if body.len() > envelope.compression_threshold {
+        let compressed = zstd::bulk::compress(&body, ZSTD_LEVEL)
+            .map_err(|e| CodecError::SerializeError(format!("Failed to compress payload: {e}")))?;
+        if compressed.len() < body.len() {
+            flags |= FLAG_ZSTD;
+            body = compressed;
+        }
+    }
+    body.splice(0..0, [ENVELOPE_PREFIX, ENVELOPE_VERSION, flags]);
+    Ok(body)
+}
+
+/// Write a payload with `write` into `buf`, in place of its contents, and seal it as
+/// [`seal_payload`] would. Unless it is compressed, the payload is written where it ends up
+/// in the sealed chunk, so a writer that reuses `buf` neither copies it nor reallocates.
+pub fn seal_payload_into<F>(
+    buf: &mut Vec<u8>,
+    envelope: Option<&EnvelopeConfig>,
+    write: F,
+) -> Result<(), CodecError>
+where
+    F: FnOnce(&mut Vec<u8>) -> Result<(), CodecError>,
+{
+    buf.clear();
+    let Some(envelope) = envelope else {
+        return write(buf);
+    };
+    buf.extend_from_slice(&[ENVELOPE_PREFIX, ENVELOPE_VERSION, 0]);
+    write(buf)?;
+    let body_len = buf.len() - ENVELOPE_HEADER_LEN;
+    if body_len > envelope.compression_threshold {
+        let compressed = zstd::bulk::compress(&buf[ENVELOPE_HEADER_LEN..], ZSTD_LEVEL)
+            .map_err(|e| CodecError::SerializeError(format!("Failed to compress payload: {e}")))?;
+        if compressed.len() < body_len {
+            buf.truncate(ENVELOPE_HEADER_LEN);
+            buf[ENVELOPE_HEADER_LEN - 1] = FLAG_ZSTD;
+            buf.extend_from_slice(&compressed);
+        }
+    }
+    Ok(())
+}
+
+/// The serialized message in the contents of a chunk, whether it was written bare or in an
+/// envelope. Compressed payloads may not expand beyond the largest chunk a StackerDB holds.
+pub fn open_payload(data: &[u8]) -> Result<Cow<'_, [u8]>, CodecError> {
+    let [ENVELOPE_PREFIX, rest @ ..] = data else {
+        return Ok(Cow::Borrowed(data));
+    };
+    let [version, flags, payload @ ..] = rest else {
+        return Err(CodecError::DeserializeError(
+            "Payload envelope is missing its header".into(),
+        ));
+    };
+    if *version != ENVELOPE_VERSION {
+        return Err(CodecError::DeserializeError(format!(
+            "Unknown payload envelope version {version}"
+        )));
+    }
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(CodecError::DeserializeError(format!(
+            "Unknown payload envelope flags {flags:#04x}"
+        )));
+    }
+    if flags & FLAG_ZSTD == 0 {
+        return Ok(Cow::Borrowed(payload));
+    }
+    let max_len = usize::try_from(STACKERDB_MAX_CHUNK_SIZE).expect("FATAL: u32 overflows usize");
+    zstd::bulk::decompress(payload, max_len)
+        .map(Cow::Owned)
+        .map_err(|e| CodecError::DeserializeError(format!("Failed to decompress payload: {e}")))
+}
+
+#[cfg(test)]
+mod test {
+    use blockstack_lib::chainstate::stacks::boot::MINERS_NAME;
+    use blockstack_lib::chainstate::stacks::events::StackerDBChunksEvent;
+    use blockstack_lib::util_lib::boot::boot_code_id;
+    use libstackerdb::StackerDBChunkData;
+    use rand::RngCore;
+    use stacks_common::codec::StacksMessageCodec;
+    use stacks_common::util::secp256k1::Secp256k1PrivateKey;
+
+    use super::*;
+    use crate::protocol::{tag_message, write_tagged_message, MinerWatermark, ProtocolVersion};
+    use crate::v1::messages::{MessageSlotID, SignerMessage};
+    use crate::{MinerSlotMessage, SignerEvent};
+
+    #[test]
+    fn payload_round_trip() {
+        let envelope = EnvelopeConfig {
+            compression_threshold: 64,
+        };
+        let short = vec![1, 2, 3];
+        let long = vec![7; 4096];
+        // bare payloads come back as they are
+        for payload in [&short, &long] {
+            let sealed = seal_payload(payload.clone(), None).unwrap();
+            assert_eq!(&sealed, payload);
+            assert_eq!(open_payload(&sealed).unwrap().as_ref(), &payload[..]);
+        }
+
+        let sealed = seal_payload(short.clone(), Some(&envelope)).unwrap();
+        assert_eq!(sealed[..3], [ENVELOPE_PREFIX, ENVELOPE_VERSION, 0]);
+        assert_eq!(open_payload(&sealed).unwrap().as_ref(), &short[..]);
+
+        let sealed = seal_payload(long.clone(), Some(&envelope)).unwrap();
+        assert_eq!(sealed[..3], [ENVELOPE_PREFIX, ENVELOPE_VERSION, FLAG_ZSTD]);
+        assert!(sealed.len() < long.len());
+        assert_eq!(open_payload(&sealed).unwrap().as_ref(), &long[..]);
+
+        // payloads that compression would not shorten are left uncompressed
+        let sealed = seal_payload(
+            short.clone(),
+            Some(&EnvelopeConfig {
+                compression_threshold: 0,
+            }),
+        )
+        .unwrap();
+        assert_eq!(sealed[..3], [ENVELOPE_PREFIX, ENVELOPE_VERSION, 0]);
+        assert_eq!(open_payload(&sealed).unwrap().as_ref(), &short[..]);
+    }
+
+    #[test]
+    fn sealing_in_place_matches_sealing_a_serialized_payload() {
+        let mut noise = vec![0u8; 4096];
+        rand::thread_rng().fill_bytes(&mut noise);
+        let payloads = [vec![], vec![1, 2, 3], vec![7; 4096], noise];
+        let envelopes = [
+            None,
+            Some(EnvelopeConfig::default()),
+            Some(EnvelopeConfig {
+                compression_threshold: 0,
+            }),
+            Some(EnvelopeConfig {
+                compression_threshold: 64,
+            }),
+        ];
+        // one buffer for everything, so that each payload is written over a longer one
+        let mut buf = vec![0xaa; 8192];
+        for envelope in envelopes.iter() {
+            for payload in payloads.iter().rev() {
+                seal_payload_into(&mut buf, envelope.as_ref(), |buf| {
+                    buf.extend_from_slice(payload);
+                    Ok(())
+                })
+                .unwrap();
+                assert_eq!(
+                    buf,
+                    seal_payload(payload.clone(), envelope.as_ref()).unwrap()
+                );
+            }
+        }
+
+        // and a miner message is tagged as it would be once serialized
+        let message = SignerMessage::Transactions(vec![]);
+        let watermark = MinerWatermark::new(7, 3);
+        for envelope in envelopes.iter() {
+            seal_payload_into(&mut buf, envelope.as_ref(), |buf| {
+                write_tagged_message(buf, ProtocolVersion::V1, &watermark, &message)
+            })
+            .unwrap();
+            let tagged = tag_message(ProtocolVersion::V1, watermark, message.serialize_to_vec());
+            assert_eq!(buf, seal_payload(tagged, envelope.as_ref()).unwrap());
+        }
+    }
+
+    #[test]
+    fn malformed_envelopes() {
+        assert!(open_payload(&[ENVELOPE_PREFIX]).is_err());
+        assert!(open_payload(&[ENVELOPE_PREFIX, ENVELOPE_VERSION]).is_err());
+        // a version or a flag from a newer writer
+        assert!(open_payload(&[ENVELOPE_PREFIX, ENVELOPE_VERSION + 1, 0, 1]).is_err());
+        assert!(open_payload(&[ENVELOPE_PREFIX, ENVELOPE_VERSION, 0x80, 1]).is_err());
+        // not zstd
+        assert!(open_payload(&[ENVELOPE_PREFIX, ENVELOPE_VERSION, FLAG_ZSTD, 1, 2, 3]).is_err());
+        // expands beyond the largest chunk
+        let bomb = zstd::bulk::compress(
+            &vec![0; usize::try_from(STACKERDB_MAX_CHUNK_SIZE).unwrap() + 1],
+            ZSTD_LEVEL,
+        )
+        .unwrap();
+        let mut sealed = vec![ENVELOPE_PREFIX, ENVELOPE_VERSION, FLAG_ZSTD];
+        sealed.extend(bomb);
+        assert!(open_payload(&sealed).is_err());
+    }
+
+    fn signed_chunks(contents: Vec<Vec<u8>>) -> Vec<StackerDBChunkData> {
+        let privk = Secp256k1PrivateKey::new();
+        contents
+            .into_iter()
+            .enumerate()
+            .map(|(slot_id, data)| {
+                let mut chunk = StackerDBChunkData::new(slot_id as u32, 1, data);
+                chunk.sign(&privk).unwrap();
+                chunk
+            })
+            .collect()
+    }
+
+    #[test]
+    fn events_with_bare_and_enveloped_payloads() {
+        let envelope = EnvelopeConfig {
+            compression_threshold: 0,
+        };
+        let message = SignerMessage::Transactions(vec![]);
+        let watermark = MinerWatermark::new(7, 0);
+        let tagged = tag_message(ProtocolVersion::V1, watermark, message.serialize_to_vec());
+        let event = StackerDBChunksEvent {
+            contract_id: boot_code_id(MINERS_NAME, false),
+            modified_slots: signed_chunks(vec![
+                tagged.clone(),
+                seal_payload(tagged, Some(&envelope)).unwrap(),
+                // an envelope from a newer writer
+                vec![ENVELOPE_PREFIX, ENVELOPE_VERSION + 1, 0],
+            ]),
+        };
+        let SignerEvent::MinerMessages(messages, _) =
+            SignerEvent::<SignerMessage>::try_from(event).unwrap()
+        else {
+            panic!("Expected miner messages");
+        };
+        assert_eq!(
+            messages,
+            vec![
+                MinerSlotMessage {
+                    slot_id: 0,
+                    slot_version: 1,
+                    watermark: Some(watermark),
+                    message: message.clone(),
+                },
+                MinerSlotMessage {
+                    slot_id: 1,
+                    slot_version: 1,
+                    watermark: Some(watermark),
+                    message: message.clone(),
+                },
+            ]
+        );
+
+        let event = StackerDBChunksEvent {
+            contract_id: MessageSlotID::Transactions.stacker_db_contract(false, 1),
+            modified_slots: signed_chunks(vec![
+                message.serialize_to_vec(),
+                seal_payload(message.serialize_to_vec(), Some(&envelope)).unwrap(),
+            ]),
+        };
+        let SignerEvent::SignerMessages(_, messages) =
+            SignerEvent::<SignerMessage>::try_from(event).unwrap()
+        else {
+            panic!("Expected signer messages");
+        };
+        assert_eq!(messages.len(), 2);
+        assert!(messages.iter().all(|msg| msg.message == message));
+    }
+}
@@ -39,6 +39,9 @@ pub enum RPCError {
     /// HTTP error
     #[error("HTTP code {0}")]
     HttpError(u32),
+    /// Connecting to, or awaiting a reply from, the node took too long
+    #[error("Timed out")]
+    Timeout,
 }
 
 /// Errors originating from receiving event data from the Stacks node
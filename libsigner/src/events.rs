@@ -24,7 +24,9 @@ use std::sync::Arc;
 use blockstack_lib::chainstate::nakamoto::NakamotoBlock;
 use blockstack_lib::chainstate::stacks::boot::{MINERS_NAME, SIGNERS_NAME};
 use blockstack_lib::chainstate::stacks::events::StackerDBChunksEvent;
-use blockstack_lib::chainstate::stacks::{StacksTransaction, ThresholdSignature};
+use blockstack_lib::chainstate::stacks::{
+    StacksTransaction, TenureChangeCause, TenureChangePayload, ThresholdSignature,
+};
 use blockstack_lib::net::api::postblock_proposal::{
     BlockValidateReject, BlockValidateResponse, ValidateRejectCode,
 };
@@ -39,7 +41,7 @@ use stacks_common::codec::{
     StacksMessageCodec,
 };
 pub use stacks_common::consts::SIGNER_SLOTS_PER_USER;
-use stacks_common::types::chainstate::StacksPublicKey;
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId, StacksPublicKey};
 use stacks_common::util::hash::Sha512Trunc256Sum;
 use tiny_http::{
     Method as HttpMethod, Request as HttpRequest, Response as HttpResponse, Server as HttpServer,
@@ -51,7 +53,9 @@ use wsts::net::{
 };
 use wsts::state_machine::signer;
 
+use crate::envelope::open_payload;
 use crate::http::{decode_http_body, decode_http_request};
+use crate::protocol::{read_extension_block, untag_message, write_extension_block, MinerWatermark};
 use crate::EventError;
 
 /// Define the trait for the event processor
@@ -62,6 +66,115 @@ pub trait SignerEventTrait<T: StacksMessageCodec + Clone + Debug + Send = Self>:
 
 impl<T: StacksMessageCodec + Clone + Debug + Send> SignerEventTrait for T {}
 
+/// One side's view of the tenure a proposed block belongs to. Miners send theirs with a
+/// block proposal, and signers echo theirs back when rejecting the block, so that a
+/// disagreement between the two can be seen from either side.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TenureView {
+    /// Consensus hash of the burnchain tip
+    pub burn_view_consensus_hash: ConsensusHash,
+    /// The first block of the tenure the block's tenure builds on, if known
+    pub parent_tenure_id: Option<StacksBlockId>,
+    /// Why the block's tenure changed, if the block changes tenure
+    pub tenure_change_cause: Option<TenureChangeCause>,
+}
+
+impl StacksMessageCodec for TenureView {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &self.burn_view_consensus_hash)?;
+        match &self.parent_tenure_id {
+            Some(parent_tenure_id) => {
+                write_next(fd, &1u8)?;
+                write_next(fd, parent_tenure_id)?;
+            }
+            None => write_next(fd, &0u8)?,
+        }
+        match &self.tenure_change_cause {
+            Some(cause) => {
+                write_next(fd, &1u8)?;
+                write_next(fd, cause)?;
+            }
+            None => write_next(fd, &0u8)?,
+        }
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        let burn_view_consensus_hash = read_next(fd)?;
+        let parent_tenure_id = match read_next::<u8, _>(fd)? {
+            0 => None,
+            1 => Some(read_next(fd)?),
+            x => {
+                return Err(CodecError::DeserializeError(format!(
+                    "Bad parent tenure id presence byte: {x}"
+                )))
+            }
+        };
+        let tenure_change_cause = match read_next::<u8, _>(fd)? {
+            0 => None,
+            1 => Some(read_next(fd)?),
+            x => {
+                return Err(CodecError::DeserializeError(format!(
+                    "Bad tenure change cause presence byte: {x}"
+                )))
+            }
+        };
+        Ok(Self {
+            burn_view_consensus_hash,
+            parent_tenure_id,
+            tenure_change_cause,
+        })
+    }
+}
+
+/// The tenure extension a miner proposes along with the block whose tenure change makes it,
+/// so that signers can apply the rules for extending a tenure rather than starting one
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TenureExtend {
+    /// Consensus hash of the sortition that started the tenure being extended
+    pub tenure_consensus_hash: ConsensusHash,
+    /// Consensus hash of the burnchain tip the tenure is extended to
+    pub burn_view_consensus_hash: ConsensusHash,
+    /// The last block of the tenure before it is extended
+    pub previous_tenure_end: StacksBlockId,
+    /// Number of blocks in the tenure before it is extended
+    pub previous_tenure_blocks: u32,
+}
+
+impl TenureExtend {
+    /// The tenure extension `payload` makes, if it extends a tenure
+    pub fn from_tenure_change(payload: &TenureChangePayload) -> Option<Self> {
+        if payload.cause != TenureChangeCause::Extended {
+            return None;
+        }
+        Some(Self {
+            tenure_consensus_hash: payload.tenure_consensus_hash.clone(),
+            burn_view_consensus_hash: payload.burn_view_consensus_hash.clone(),
+            previous_tenure_end: payload.previous_tenure_end.clone(),
+            previous_tenure_blocks: payload.previous_tenure_blocks,
+        })
+    }
+}
+
+impl StacksMessageCodec for TenureExtend {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &self.tenure_consensus_hash)?;
+        write_next(fd, &self.burn_view_consensus_hash)?;
+        write_next(fd, &self.previous_tenure_end)?;
+        write_next(fd, &self.previous_tenure_blocks)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        Ok(Self {
+            tenure_consensus_hash: read_next(fd)?,
+            burn_view_consensus_hash: read_next(fd)?,
+            previous_tenure_end: read_next(fd)?,
+            previous_tenure_blocks: read_next(fd)?,
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// BlockProposal sent to signers
 pub struct BlockProposal {
@@ -71,6 +184,43 @@ pub struct BlockProposal {
     pub burn_height: u64,
     /// The reward cycle the block is mined during
     pub reward_cycle: u64,
+    /// The miner's view of the block's tenure. Encoded in an extension block, so it is
+    /// `None` when read from an older miner and ignored by older signers.
+    pub miner_view: Option<TenureView>,
+    /// The tenure extension the block makes, if the miner proposes it as one. Encoded in an
+    /// extension block after the miner's view, so it is only sent along with one.
+    pub tenure_extend: Option<TenureExtend>,
+    /// The miner's signing attempt for the block, which increases every time it re-proposes
+    /// a block at the same height, so that signers can drop proposals a newer one supersedes.
+    /// Encoded at the end of the miner's view extension block, so it is only sent along with
+    /// one and is skipped by signers that only know about the view.
+    pub attempt: Option<u64>,
+}
+
+/// The contents of a block proposal's miner view extension block: the view itself, followed
+/// by the fields added to the block after it. Only ever decoded from within its extension
+/// block, whose bounds tell where these fields end.
+struct MinerViewExtension {
+    view: TenureView,
+    attempt: Option<u64>,
+}
+
+impl StacksMessageCodec for MinerViewExtension {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &self.view)?;
+        write_extension_block(fd, self.attempt.as_ref())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        let view = read_next(fd)?;
+        // whatever follows the view was added to the block later. Fields this reader does
+        // not recognize were written by a newer miner, and are skipped like the view's
+        // extension block is skipped by older readers.
+        let mut rest = vec![];
+        fd.read_to_end(&mut rest).map_err(CodecError::ReadError)?;
+        let attempt = read_extension_block(&mut &rest[..]).unwrap_or(None);
+        Ok(Self { view, attempt })
+    }
 }
 
 impl StacksMessageCodec for BlockProposal {
@@ -78,6 +228,22 @@ impl StacksMessageCodec for BlockProposal {
         self.block.consensus_serialize(fd)?;
         self.burn_height.consensus_serialize(fd)?;
         self.reward_cycle.consensus_serialize(fd)?;
+        if self.tenure_extend.is_some() && self.miner_view.is_none() {
+            return Err(CodecError::SerializeError(
+                "A tenure-extend proposal must carry the miner's view".into(),
+            ));
+        }
+        if self.attempt.is_some() && self.miner_view.is_none() {
+            return Err(CodecError::SerializeError(
+                "A proposal's attempt must be sent with the miner's view".into(),
+            ));
+        }
+        let miner_view = self.miner_view.clone().map(|view| MinerViewExtension {
+            view,
+            attempt: self.attempt,
+        });
+        write_extension_block(fd, miner_view.as_ref())?;
+        write_extension_block(fd, self.tenure_extend.as_ref())?;
         Ok(())
     }
 
@@ -85,24 +251,64 @@ impl StacksMessageCodec for BlockProposal {
         let block = NakamotoBlock::consensus_deserialize(fd)?;
         let burn_height = u64::consensus_deserialize(fd)?;
         let reward_cycle = u64::consensus_deserialize(fd)?;
+        let miner_view: Option<MinerViewExtension> = read_extension_block(fd)?;
+        let tenure_extend = read_extension_block(fd)?;
+        let (miner_view, attempt) = match miner_view {
+            Some(MinerViewExtension { view, attempt }) => (Some(view), attempt),
+            None => (None, None),
+        };
         Ok(BlockProposal {
             block,
             burn_height,
             reward_cycle,
+            miner_view,
+            tenure_extend,
+            attempt,
         })
     }
 }
 
+/// A message read out of a signer's StackerDB slot, along with the metadata of the chunk
+/// that carried it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignerSlotMessage<T: SignerEventTrait> {
+    /// The slot the chunk was written to
+    pub slot_id: u32,
+    /// The version of the chunk
+    pub slot_version: u32,
+    /// The public key recovered from the chunk's signature
+    pub signer_pubkey: StacksPublicKey,
+    /// The message in the chunk
+    pub message: T,
+}
+
+/// A message read out of a miner's StackerDB slot, along with the metadata of the chunk
+/// that carried it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MinerSlotMessage<T: SignerEventTrait> {
+    /// The slot the chunk was written to
+    pub slot_id: u32,
+    /// The version of the chunk
+    pub slot_version: u32,
+    /// The watermark the miner wrote the message with, if it was tagged with one.
+    /// Consumers should ignore messages whose watermark is not newer than the last one they
+    /// accepted from the same slot (see `MinerWatermark::is_newer_than`).
+    pub watermark: Option<MinerWatermark>,
+    /// The message in the chunk
+    pub message: T,
+}
+
 /// Event enum for newly-arrived signer subscribed events
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SignerEvent<T: SignerEventTrait> {
     /// A miner sent a message over .miners
-    /// The `Vec<T>` will contain any signer messages made by the miner.
+    /// The `Vec` will contain any signer messages made by the miner.
     /// The `StacksPublicKey` is the message sender's public key.
-    MinerMessages(Vec<T>, StacksPublicKey),
+    MinerMessages(Vec<MinerSlotMessage<T>>, StacksPublicKey),
     /// The signer messages for other signers and miners to observe
-    /// The u32 is the signer set to which the message belongs (either 0 or 1)
-    SignerMessages(u32, Vec<T>),
+    /// The u32 is the signer set to which the message belongs (either 0 or 1).
+    /// Chunks that could not be parsed or authenticated are left out.
+    SignerMessages(u32, Vec<SignerSlotMessage<T>>),
     /// A new block proposal validation response from the node
     BlockValidationResponse(BlockValidateResponse),
     /// Status endpoint request
@@ -424,7 +630,26 @@ impl<T: SignerEventTrait> TryFrom<StackerDBChunksEvent> for SignerEvent<T> {
             let mut messages = vec![];
             let mut miner_pk = None;
             for chunk in event.modified_slots {
-                let Ok(msg) = T::consensus_deserialize(&mut chunk.data.as_slice()) else {
+                let payload = match open_payload(&chunk.data) {
+                    Ok(payload) => payload,
+                    Err(e) => {
+                        warn!("Ignoring miner message with an unreadable payload envelope: {e}");
+                        continue;
+                    }
+                };
+                let (version, watermark, mut data) = match untag_message(&payload) {
+                    Ok(untagged) => untagged,
+                    Err(e) => {
+                        warn!("Ignoring miner message with unusable protocol version: {e}");
+                        continue;
+                    }
+                };
+                let Ok(msg) = T::consensus_deserialize(&mut data) else {
+                    warn!(
+                        "Failed to parse miner message. The miner may speak a protocol version this signer does not.";
+                        "protocol_version" => %version,
+                        "slot_id" => chunk.slot_id,
+                    );
                     continue;
                 };
 
@@ -433,7 +658,12 @@ impl<T: SignerEventTrait> TryFrom<StackerDBChunksEvent> for SignerEvent<T> {
                         "Failed to recover PK from StackerDB chunk: {e}"
                     ))
                 })?);
-                messages.push(msg);
+                messages.push(MinerSlotMessage {
+                    slot_id: chunk.slot_id,
+                    slot_version: chunk.slot_version,
+                    watermark,
+                    message: msg,
+                });
             }
             SignerEvent::MinerMessages(messages, miner_pk.ok_or(EventError::EmptyChunksEvent)?)
         } else if event.contract_id.name.starts_with(SIGNERS_NAME) && event.contract_id.is_boot() {
@@ -443,10 +673,28 @@ impl<T: SignerEventTrait> TryFrom<StackerDBChunksEvent> for SignerEvent<T> {
                 return Err(EventError::UnrecognizedStackerDBContract(event.contract_id));
             };
             // signer-XXX-YYY boot contract
-            let signer_messages: Vec<T> = event
+            let signer_messages: Vec<SignerSlotMessage<T>> = event
                 .modified_slots
                 .iter()
-                .filter_map(|chunk| read_next::<T, _>(&mut &chunk.data[..]).ok())
+                .filter_map(|chunk| {
+                    let payload = open_payload(&chunk.data).ok()?;
+                    let message = read_next::<T, _>(&mut &payload[..]).ok()?;
+                    let signer_pubkey = chunk
+                        .recover_pk()
+                        .map_err(|e| {
+                            warn!(
+                                "Failed to recover PK from StackerDB chunk: {e}";
+                                "slot_id" => chunk.slot_id,
+                            );
+                        })
+                        .ok()?;
+                    Some(SignerSlotMessage {
+                        slot_id: chunk.slot_id,
+                        slot_version: chunk.slot_version,
+                        signer_pubkey,
+                        message,
+                    })
+                })
                 .collect();
             SignerEvent::SignerMessages(signer_set, signer_messages)
         } else {
@@ -549,4 +797,273 @@ mod tests {
         let name = "signer--2";
         assert!(get_signers_db_signer_set_message_id(name).is_none());
     }
+
+    #[test]
+    fn test_signer_messages_keep_chunk_metadata() {
+        use blockstack_lib::chainstate::nakamoto::signer_set::NakamotoSigners;
+        use libstackerdb::StackerDBChunkData;
+        use stacks_common::util::secp256k1::Secp256k1PrivateKey;
+
+        use crate::v1::messages::SignerMessage;
+
+        let contract_id = NakamotoSigners::make_signers_db_contract_id(1, 11, false);
+        let privks: Vec<_> = (0..3).map(|_| Secp256k1PrivateKey::new()).collect();
+        let messages = [
+            SignerMessage::Transactions(vec![]),
+            SignerMessage::EncryptedSignerState(vec![1, 2, 3]),
+        ];
+        // the middle chunk does not parse
+        let contents = [
+            messages[0].serialize_to_vec(),
+            vec![0xfa, 0xce],
+            messages[1].serialize_to_vec(),
+        ];
+        let modified_slots = contents
+            .into_iter()
+            .zip(privks.iter())
+            .enumerate()
+            .map(|(i, (data, privk))| {
+                let slot_id = 5 + i as u32;
+                let mut chunk = StackerDBChunkData::new(slot_id, 10 * slot_id, data);
+                chunk.sign(privk).unwrap();
+                chunk
+            })
+            .collect();
+        let event = StackerDBChunksEvent {
+            contract_id,
+            modified_slots,
+        };
+
+        let SignerEvent::SignerMessages(signer_set, slot_messages) =
+            SignerEvent::<SignerMessage>::try_from(event).unwrap()
+        else {
+            panic!("Expected signer messages");
+        };
+        assert_eq!(signer_set, 1);
+        assert_eq!(
+            slot_messages,
+            vec![
+                SignerSlotMessage {
+                    slot_id: 5,
+                    slot_version: 50,
+                    signer_pubkey: StacksPublicKey::from_private(&privks[0]),
+                    message: messages[0].clone(),
+                },
+                SignerSlotMessage {
+                    slot_id: 7,
+                    slot_version: 70,
+                    signer_pubkey: StacksPublicKey::from_private(&privks[2]),
+                    message: messages[1].clone(),
+                },
+            ]
+        );
+    }
+
+    fn make_block_proposal(miner_view: Option<TenureView>) -> BlockProposal {
+        use blockstack_lib::chainstate::nakamoto::NakamotoBlockHeader;
+
+        BlockProposal {
+            block: NakamotoBlock {
+                header: NakamotoBlockHeader::empty(),
+                txs: vec![],
+            },
+            burn_height: 7,
+            reward_cycle: 42,
+            miner_view,
+            tenure_extend: None,
+            attempt: None,
+        }
+    }
+
+    #[test]
+    fn block_proposal_miner_view_round_trip() {
+        let views = [
+            TenureView {
+                burn_view_consensus_hash: ConsensusHash([1; 20]),
+                parent_tenure_id: Some(StacksBlockId([2; 32])),
+                tenure_change_cause: Some(TenureChangeCause::BlockFound),
+            },
+            TenureView {
+                burn_view_consensus_hash: ConsensusHash([3; 20]),
+                parent_tenure_id: None,
+                tenure_change_cause: None,
+            },
+        ];
+        for view in views {
+            let proposal = make_block_proposal(Some(view));
+            let bytes = proposal.serialize_to_vec();
+            let decoded = read_next::<BlockProposal, _>(&mut &bytes[..]).unwrap();
+            assert_eq!(decoded, proposal);
+        }
+    }
+
+    #[test]
+    fn block_proposal_old_writer_new_reader() {
+        let proposal = make_block_proposal(None);
+        // an older miner writes only the legacy fields
+        let mut bytes = proposal.block.serialize_to_vec();
+        bytes.extend(proposal.burn_height.serialize_to_vec());
+        bytes.extend(proposal.reward_cycle.serialize_to_vec());
+        assert_eq!(bytes, proposal.serialize_to_vec());
+
+        let decoded = read_next::<BlockProposal, _>(&mut &bytes[..]).unwrap();
+        assert_eq!(decoded.miner_view, None);
+        assert_eq!(decoded, proposal);
+    }
+
+    #[test]
+    fn block_proposal_new_writer_old_reader() {
+        let proposal = make_block_proposal(Some(TenureView {
+            burn_view_consensus_hash: ConsensusHash([1; 20]),
+            parent_tenure_id: Some(StacksBlockId([2; 32])),
+            tenure_change_cause: Some(TenureChangeCause::Extended),
+        }));
+        let bytes = proposal.serialize_to_vec();
+
+        // an older signer reads only the legacy fields and ignores the rest
+        let mut reader = &bytes[..];
+        let block = read_next::<NakamotoBlock, _>(&mut reader).unwrap();
+        let burn_height = read_next::<u64, _>(&mut reader).unwrap();
+        let reward_cycle = read_next::<u64, _>(&mut reader).unwrap();
+        assert_eq!(block, proposal.block);
+        assert_eq!(burn_height, proposal.burn_height);
+        assert_eq!(reward_cycle, proposal.reward_cycle);
+    }
+
+    #[test]
+    fn block_proposal_skips_unknown_extension_fields() {
+        let view = TenureView {
+            burn_view_consensus_hash: ConsensusHash([1; 20]),
+            parent_tenure_id: None,
+            tenure_change_cause: Some(TenureChangeCause::BlockFound),
+        };
+        let proposal = make_block_proposal(Some(view.clone()));
+        // a newer miner appends a field this reader does not know about
+        let mut extension = view.serialize_to_vec();
+        extension.extend([0xaa, 0xbb, 0xcc]);
+        let mut bytes = make_block_proposal(None).serialize_to_vec();
+        bytes.extend((extension.len() as u32).serialize_to_vec());
+        bytes.extend(extension);
+
+        let mut reader = &bytes[..];
+        let decoded = read_next::<BlockProposal, _>(&mut reader).unwrap();
+        assert_eq!(decoded, proposal);
+        assert!(reader.is_empty());
+
+        // but a truncated extension block is an error
+        bytes.pop();
+        assert!(read_next::<BlockProposal, _>(&mut &bytes[..]).is_err());
+    }
+
+    #[test]
+    fn block_proposal_tenure_extend_round_trip() {
+        let view = TenureView {
+            burn_view_consensus_hash: ConsensusHash([3; 20]),
+            parent_tenure_id: Some(StacksBlockId([2; 32])),
+            tenure_change_cause: Some(TenureChangeCause::Extended),
+        };
+        let mut proposal = make_block_proposal(Some(view));
+        proposal.tenure_extend = Some(TenureExtend {
+            tenure_consensus_hash: ConsensusHash([1; 20]),
+            burn_view_consensus_hash: ConsensusHash([3; 20]),
+            previous_tenure_end: StacksBlockId([4; 32]),
+            previous_tenure_blocks: 5,
+        });
+        let bytes = proposal.serialize_to_vec();
+        let decoded = read_next::<BlockProposal, _>(&mut &bytes[..]).unwrap();
+        assert_eq!(decoded, proposal);
+
+        // a signer that only knows about the miner's view still reads it
+        let mut without_extend = proposal.clone();
+        without_extend.tenure_extend = None;
+        let mut reader = &bytes[..];
+        let _ = read_next::<NakamotoBlock, _>(&mut reader).unwrap();
+        let _ = read_next::<u64, _>(&mut reader).unwrap();
+        let _ = read_next::<u64, _>(&mut reader).unwrap();
+        let miner_view = read_extension_block::<_, TenureView>(&mut reader).unwrap();
+        assert_eq!(miner_view, without_extend.miner_view);
+
+        // the extension is only sent along with the miner's view
+        proposal.miner_view = None;
+        assert!(proposal.consensus_serialize(&mut Vec::<u8>::new()).is_err());
+    }
+
+    #[test]
+    fn block_proposal_attempt_round_trip() {
+        let view = TenureView {
+            burn_view_consensus_hash: ConsensusHash([3; 20]),
+            parent_tenure_id: Some(StacksBlockId([2; 32])),
+            tenure_change_cause: Some(TenureChangeCause::Extended),
+        };
+        let mut proposal = make_block_proposal(Some(view.clone()));
+        proposal.attempt = Some(3);
+        let bytes = proposal.serialize_to_vec();
+        let decoded = read_next::<BlockProposal, _>(&mut &bytes[..]).unwrap();
+        assert_eq!(decoded, proposal);
+
+        // without an attempt, the proposal is written as before
+        let mut without_attempt = proposal.clone();
+        without_attempt.attempt = None;
+        let mut legacy_bytes = make_block_proposal(None).serialize_to_vec();
+        legacy_bytes.extend((view.serialize_to_vec().len() as u32).serialize_to_vec());
+        legacy_bytes.extend(view.serialize_to_vec());
+        assert_eq!(without_attempt.serialize_to_vec(), legacy_bytes);
+
+        // a signer that only knows about the miner's view still reads it, and the tenure
+        // extension after it
+        proposal.tenure_extend = Some(TenureExtend {
+            tenure_consensus_hash: ConsensusHash([1; 20]),
+            burn_view_consensus_hash: ConsensusHash([3; 20]),
+            previous_tenure_end: StacksBlockId([4; 32]),
+            previous_tenure_blocks: 5,
+        });
+        let bytes = proposal.serialize_to_vec();
+        let mut reader = &bytes[..];
+        let _ = read_next::<NakamotoBlock, _>(&mut reader).unwrap();
+        let _ = read_next::<u64, _>(&mut reader).unwrap();
+        let _ = read_next::<u64, _>(&mut reader).unwrap();
+        let miner_view = read_extension_block::<_, TenureView>(&mut reader).unwrap();
+        assert_eq!(miner_view, Some(view));
+        let tenure_extend = read_extension_block::<_, TenureExtend>(&mut reader).unwrap();
+        assert_eq!(tenure_extend, proposal.tenure_extend);
+        assert!(reader.is_empty());
+        assert_eq!(
+            read_next::<BlockProposal, _>(&mut &bytes[..]).unwrap(),
+            proposal
+        );
+
+        // the attempt is only sent along with the miner's view
+        let mut without_view = make_block_proposal(None);
+        without_view.attempt = Some(3);
+        assert!(without_view
+            .consensus_serialize(&mut Vec::<u8>::new())
+            .is_err());
+    }
+
+    #[test]
+    fn tenure_extend_only_from_extending_tenure_change() {
+        use stacks_common::util::hash::Hash160;
+
+        let mut payload = TenureChangePayload {
+            tenure_consensus_hash: ConsensusHash([1; 20]),
+            prev_tenure_consensus_hash: ConsensusHash([1; 20]),
+            burn_view_consensus_hash: ConsensusHash([3; 20]),
+            previous_tenure_end: StacksBlockId([4; 32]),
+            previous_tenure_blocks: 5,
+            cause: TenureChangeCause::BlockFound,
+            pubkey_hash: Hash160([6; 20]),
+        };
+        assert_eq!(TenureExtend::from_tenure_change(&payload), None);
+
+        payload.cause = TenureChangeCause::Extended;
+        assert_eq!(
+            TenureExtend::from_tenure_change(&payload),
+            Some(TenureExtend {
+                tenure_consensus_hash: ConsensusHash([1; 20]),
+                burn_view_consensus_hash: ConsensusHash([3; 20]),
+                previous_tenure_end: StacksBlockId([4; 32]),
+                previous_tenure_blocks: 5,
+            })
+        );
+    }
 }
@@ -255,6 +255,13 @@ pub fn run_http_request<S: Read + Write>(
     let mut buf = vec![];
 
     sock.read_to_end(&mut buf)?;
+    if buf.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "connection closed before HTTP response",
+        )
+        .into());
+    }
 
     let (headers, body_offset) = decode_http_response(&buf)?;
     if body_offset >= buf.len() {
@@ -36,9 +36,13 @@ extern crate libc;
 #[cfg(test)]
 mod tests;
 
+/// Versioned envelopes around StackerDB chunk payloads
+pub mod envelope;
 mod error;
 mod events;
 mod http;
+/// Protocol version negotiation between miners and signers
+pub mod protocol;
 mod runloop;
 mod session;
 mod signer_set;
@@ -49,9 +53,13 @@ pub mod v1;
 
 pub use crate::error::{EventError, RPCError};
 pub use crate::events::{
-    BlockProposal, EventReceiver, EventStopSignaler, SignerEvent, SignerEventReceiver,
-    SignerEventTrait, SignerStopSignaler,
+    BlockProposal, EventReceiver, EventStopSignaler, MinerSlotMessage, SignerEvent,
+    SignerEventReceiver, SignerEventTrait, SignerSlotMessage, SignerStopSignaler, TenureExtend,
+    TenureView,
 };
 pub use crate::runloop::{RunningSigner, Signer, SignerRunLoop};
 pub use crate::session::{SignerSession, StackerDBSession};
-pub use crate::signer_set::{Error as ParseSignerEntriesError, SignerEntries};
+pub use crate::signer_set::{
+    cycle_matches_signer_set, signer_set_for_cycle, Error as ParseSignerEntriesError,
+    RewardSetIndex, SignerEntries, SignerIndexMap, SlotId, WstsSignerId,
+};
@@ -0,0 +1,514 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Miners and signers can speak different versions of the message protocol (`v0` or `v1`).
+//! Two pieces of data let each side tell which version the other speaks:
+//!
+//! * Miners tag the messages they write to `.miners` with the protocol version they are
+//!   written in (see `tag_message`), once signers holding the signing threshold advertise
+//!   that they speak it. Untagged messages are from the legacy protocol.
+//!   Tagged messages also carry a `MinerWatermark`, so that an old message replayed at a
+//!   newer slot version can be told apart from a new one.
+//! * Signers advertise the protocol versions they speak with a `ProtocolCapabilities` message
+//!   in their slot of the `PROTOCOL_CAPABILITIES_MESSAGE_ID` signers StackerDB. Signers that
+//!   do not advertise are assumed to speak only the legacy protocol.
+
+use std::fmt::Display;
+use std::io::{Read, Write};
+
+use blockstack_lib::chainstate::nakamoto::signer_set::NakamotoSigners;
+use clarity::util::retry::BoundReader;
+use clarity::vm::types::QualifiedContractIdentifier;
+use serde::{Deserialize, Serialize};
+use stacks_common::codec::{
+    read_next, read_next_at_most, write_next, Error as CodecError, StacksMessageCodec,
+};
+
+use crate::envelope::open_payload;
+
+define_u8_enum!(
+/// Versions of the message protocol spoken between miners and signers
+ProtocolVersion {
+    /// The messages in `libsigner::v0`
+    V0 = 0,
+    /// The WSTS messages in `libsigner::v1`
+    V1 = 1
+});
+
+impl TryFrom<u8> for ProtocolVersion {
+    type Error = CodecError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Self::from_u8(value).ok_or_else(|| {
+            CodecError::DeserializeError(format!("Unknown protocol version: {value}"))
+        })
+    }
+}
+
+impl Display for ProtocolVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}", self.to_u8())
+    }
+}
+
+impl StacksMessageCodec for ProtocolVersion {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &self.to_u8())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        ProtocolVersion::try_from(read_next::<u8, _>(fd)?)
+    }
+}
+
+/// The protocol spoken by miners that don't tag their messages and signers that don't
+/// advertise their capabilities
+pub const LEGACY_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1;
+
+/// Leading byte of a miner message tagged with its protocol version.
+/// No `SignerMessage` type prefix uses this value.
+pub const TAGGED_MESSAGE_PREFIX: u8 = 0xff;
+
+/// Leading byte of a `ProtocolCapabilities` message.
+/// No `SignerMessage` type prefix uses this value.
+pub const CAPABILITIES_MESSAGE_PREFIX: u8 = 0xfe;
+
+/// The signers StackerDB (i.e., X in signers-0-X) in which signers advertise their
+/// `ProtocolCapabilities`. No other message is written there. It is beyond the StackerDBs
+/// instantiated in Epoch 2.5, so until its contract is deployed nobody can advertise, and
+/// every signer is treated as speaking the legacy protocol.
+pub const PROTOCOL_CAPABILITIES_MESSAGE_ID: u32 = 14;
+
+/// Maximum number of versions a signer can advertise
+const MAX_ADVERTISED_VERSIONS: u32 = 16;
+
+/// Orders the messages a miner writes to its slot. Each message a miner writes must have a
+/// higher watermark than the last, which lets signers ignore old messages that are replayed
+/// at a newer slot version. Watermarks compare by burn height first, then by nonce.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct MinerWatermark {
+    /// The burn block height the message was written at
+    pub burn_height: u64,
+    /// Increases with every message written at `burn_height`
+    pub nonce: u64,
+}
+
+impl MinerWatermark {
+    /// Create a new watermark
+    pub fn new(burn_height: u64, nonce: u64) -> Self {
+        Self { burn_height, nonce }
+    }
+
+    /// The lowest watermark at `burn_height` that is strictly newer than `self`
+    pub fn next_at(&self, burn_height: u64) -> Self {
+        if burn_height > self.burn_height {
+            Self::new(burn_height, 0)
+        } else {
+            Self::new(self.burn_height, self.nonce.saturating_add(1))
+        }
+    }
+
+    /// Should a message with this watermark be accepted from a slot whose newest message so
+    /// far had `last_seen`? Replays of that message or of anything older are not.
+    pub fn is_newer_than(&self, last_seen: Option<&MinerWatermark>) -> bool {
+        last_seen.map_or(true, |last_seen| self > last_seen)
+    }
+}
+
+impl Display for MinerWatermark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.burn_height, self.nonce)
+    }
+}
+
+impl StacksMessageCodec for MinerWatermark {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &self.burn_height)?;
+        write_next(fd, &self.nonce)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        let burn_height = read_next(fd)?;
+        let nonce = read_next(fd)?;
+        Ok(Self { burn_height, nonce })
+    }
+}
+
+/// Prefix a serialized miner message with the protocol version it was written in and its
+/// watermark
+pub fn tag_message(
+    version: ProtocolVersion,
+    watermark: MinerWatermark,
+    mut message: Vec<u8>,
+) -> Vec<u8> {
+    let mut header = vec![TAGGED_MESSAGE_PREFIX, version.to_u8()];
+    header.extend(watermark.serialize_to_vec());
+    message.splice(0..0, header);
+    message
+}
+
+/// Write `message` prefixed as [`tag_message`] would prefix its serialization, without
+/// serializing it on its own first
+pub fn write_tagged_message<W: Write, T: StacksMessageCodec>(
+    fd: &mut W,
+    version: ProtocolVersion,
+    watermark: &MinerWatermark,
+    message: &T,
+) -> Result<(), CodecError> {
+    fd.write_all(&[TAGGED_MESSAGE_PREFIX, version.to_u8()])
+        .map_err(CodecError::WriteError)?;
+    write_next(fd, watermark)?;
+    write_next(fd, message)
+}
+
+/// Split the contents of a miner slot into the protocol version, the watermark and the
+/// serialized message. Untagged contents are from the legacy protocol and have no watermark.
+pub fn untag_message(
+    data: &[u8],
+) -> Result<(ProtocolVersion, Option<MinerWatermark>, &[u8]), CodecError> {
+    match data {
+        [TAGGED_MESSAGE_PREFIX, version, rest @ ..] => {
+            let version = ProtocolVersion::try_from(*version)?;
+            let mut rest = rest;
+            let watermark = read_next::<MinerWatermark, _>(&mut rest)?;
+            Ok((version, Some(watermark), rest))
+        }
+        [TAGGED_MESSAGE_PREFIX] => Err(CodecError::DeserializeError(
+            "Tagged message is missing its protocol version".into(),
+        )),
+        _ => Ok((LEGACY_PROTOCOL_VERSION, None, data)),
+    }
+}
+
+/// Write an optional extension block at the end of a message.
+/// The block is the serialized `extension` prefixed with its length as a `u32`, so readers
+/// can skip fields added after they were written. Nothing is written for `None`, so messages
+/// without an extension keep their legacy encoding.
+pub fn write_extension_block<W: Write, T: StacksMessageCodec>(
+    fd: &mut W,
+    extension: Option<&T>,
+) -> Result<(), CodecError> {
+    let Some(extension) = extension else {
+        return Ok(());
+    };
+    let bytes = extension.serialize_to_vec();
+    let len = u32::try_from(bytes.len()).map_err(|_| {
+        CodecError::SerializeError("Extension block is longer than u32::MAX".into())
+    })?;
+    write_next(fd, &len)?;
+    fd.write_all(&bytes).map_err(CodecError::WriteError)
+}
+
+/// Read the optional extension block written by `write_extension_block`.
+/// A message that ends before the block was written by an older writer and has no extension.
+/// Any bytes in the block beyond those `T` reads were written by a newer writer and are skipped.
+pub fn read_extension_block<R: Read, T: StacksMessageCodec>(
+    fd: &mut R,
+) -> Result<Option<T>, CodecError> {
+    let mut len_bytes = [0u8; 4];
+    let mut num_read = 0;
+    while num_read < len_bytes.len() {
+        match fd.read(&mut len_bytes[num_read..]) {
+            Ok(0) => break,
+            Ok(n) => num_read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(CodecError::ReadError(e)),
+        }
+    }
+    if num_read == 0 {
+        return Ok(None);
+    }
+    if num_read < len_bytes.len() {
+        return Err(CodecError::DeserializeError(
+            "Truncated extension block length".into(),
+        ));
+    }
+    let len = u32::from_be_bytes(len_bytes);
+    let mut bound_reader = BoundReader::from_reader(fd, u64::from(len));
+    let extension = T::consensus_deserialize(&mut bound_reader)?;
+    std::io::copy(&mut bound_reader, &mut std::io::sink()).map_err(CodecError::ReadError)?;
+    if bound_reader.num_read() != u64::from(len) {
+        return Err(CodecError::DeserializeError(
+            "Truncated extension block".into(),
+        ));
+    }
+    Ok(Some(extension))
+}
+
+/// The protocol versions a signer speaks
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolCapabilities {
+    /// The supported protocol versions
+    pub versions: Vec<ProtocolVersion>,
+}
+
+impl ProtocolCapabilities {
+    /// Advertise support for `versions`
+    pub fn new(versions: Vec<ProtocolVersion>) -> Self {
+        Self { versions }
+    }
+
+    /// The capabilities of a signer that does not advertise any
+    pub fn legacy() -> Self {
+        Self::new(vec![LEGACY_PROTOCOL_VERSION])
+    }
+
+    /// Does the signer speak `version`?
+    pub fn supports(&self, version: ProtocolVersion) -> bool {
+        self.versions.contains(&version)
+    }
+
+    /// Interpret the contents of a signer's capabilities slot. Anything other than a valid
+    /// advertisement (including an empty slot) means the signer speaks the legacy protocol.
+    pub fn from_slot(data: Option<&[u8]>) -> Self {
+        Self::advertised(data).unwrap_or_else(Self::legacy)
+    }
+
+    /// The capabilities advertised in a signer's capabilities slot, if it holds a valid
+    /// advertisement
+    pub fn advertised(data: Option<&[u8]>) -> Option<Self> {
+        data.and_then(|data| open_payload(data).ok())
+            .and_then(|payload| read_next::<Self, _>(&mut &payload[..]).ok())
+    }
+
+    /// Return the StackerDB contract in which signers advertise their capabilities
+    pub fn stacker_db_contract(mainnet: bool, reward_cycle: u64) -> QualifiedContractIdentifier {
+        NakamotoSigners::make_signers_db_contract_id(
+            reward_cycle,
+            PROTOCOL_CAPABILITIES_MESSAGE_ID,
+            mainnet,
+        )
+    }
+}
+
+impl StacksMessageCodec for ProtocolCapabilities {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &CAPABILITIES_MESSAGE_PREFIX)?;
+        write_next(fd, &self.versions)
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        let prefix = read_next::<u8, _>(fd)?;
+        if prefix != CAPABILITIES_MESSAGE_PREFIX {
+            return Err(CodecError::DeserializeError(format!(
+                "Not a protocol capabilities message: prefix {prefix}"
+            )));
+        }
+        let versions = read_next_at_most::<_, ProtocolVersion>(fd, MAX_ADVERTISED_VERSIONS)?;
+        Ok(Self { versions })
+    }
+}
+
+/// Sum the weight of the signers that speak `version`.
+/// `weights` holds each signer's weight and `slots` the contents of its capabilities slot,
+/// both in signer slot order.
+pub fn supporting_weight(
+    weights: &[u32],
+    slots: &[Option<Vec<u8>>],
+    version: ProtocolVersion,
+) -> u64 {
+    slot_weight(weights, slots, |slot| {
+        ProtocolCapabilities::from_slot(slot).supports(version)
+    })
+}
+
+/// Sum the weight of the signers that advertise `version`, as `supporting_weight` does but
+/// without counting the signers that do not advertise at all. Only signers that advertise
+/// are known to read tagged messages.
+pub fn advertised_weight(
+    weights: &[u32],
+    slots: &[Option<Vec<u8>>],
+    version: ProtocolVersion,
+) -> u64 {
+    slot_weight(weights, slots, |slot| {
+        ProtocolCapabilities::advertised(slot)
+            .map_or(false, |capabilities| capabilities.supports(version))
+    })
+}
+
+/// Sum the weight of the signers whose capabilities slot satisfies `predicate`
+fn slot_weight<F>(weights: &[u32], slots: &[Option<Vec<u8>>], predicate: F) -> u64
+where
+    F: Fn(Option<&[u8]>) -> bool,
+{
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(slot_id, _)| predicate(slots.get(*slot_id).and_then(|slot| slot.as_deref())))
+        .map(|(_, weight)| u64::from(*weight))
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use blockstack_lib::chainstate::stacks::boot::MINERS_NAME;
+    use blockstack_lib::chainstate::stacks::events::StackerDBChunksEvent;
+    use blockstack_lib::util_lib::boot::boot_code_id;
+    use libstackerdb::StackerDBChunkData;
+    use stacks_common::util::secp256k1::Secp256k1PrivateKey;
+
+    use super::*;
+    use crate::v1::messages::SignerMessage;
+    use crate::{MinerSlotMessage, SignerEvent};
+
+    #[test]
+    fn serde_protocol_capabilities() {
+        let capabilities =
+            ProtocolCapabilities::new(vec![ProtocolVersion::V0, ProtocolVersion::V1]);
+        let bytes = capabilities.serialize_to_vec();
+        assert_eq!(bytes[0], CAPABILITIES_MESSAGE_PREFIX);
+        let parsed = read_next::<ProtocolCapabilities, _>(&mut &bytes[..]).unwrap();
+        assert_eq!(parsed, capabilities);
+        assert_eq!(ProtocolCapabilities::from_slot(Some(&bytes)), capabilities);
+
+        // unknown versions are rejected rather than silently dropped
+        let mut bytes = ProtocolCapabilities::new(vec![ProtocolVersion::V0]).serialize_to_vec();
+        *bytes.last_mut().unwrap() = 0x42;
+        assert!(read_next::<ProtocolCapabilities, _>(&mut &bytes[..]).is_err());
+    }
+
+    #[test]
+    fn non_advertising_signers_are_legacy() {
+        // a v1 signer's DKG results slot, an empty slot, and garbage all mean "legacy"
+        let v1_message = SignerMessage::Transactions(vec![]).serialize_to_vec();
+        for slot in [
+            Some(&v1_message[..]),
+            Some(&[][..]),
+            Some(&[0xfe][..]),
+            None,
+        ] {
+            assert_eq!(
+                ProtocolCapabilities::from_slot(slot),
+                ProtocolCapabilities::legacy()
+            );
+        }
+    }
+
+    #[test]
+    fn tagged_messages() {
+        let message = vec![1, 2, 3];
+        let watermark = MinerWatermark::new(100, 3);
+        let tagged = tag_message(ProtocolVersion::V0, watermark, message.clone());
+        assert_eq!(
+            untag_message(&tagged).unwrap(),
+            (ProtocolVersion::V0, Some(watermark), &message[..])
+        );
+        // untagged messages are legacy, and have no watermark
+        assert_eq!(
+            untag_message(&message).unwrap(),
+            (LEGACY_PROTOCOL_VERSION, None, &message[..])
+        );
+        assert!(untag_message(&[TAGGED_MESSAGE_PREFIX]).is_err());
+        assert!(untag_message(&[TAGGED_MESSAGE_PREFIX, 0x42, 1, 2, 3]).is_err());
+        // a truncated watermark
+        assert!(untag_message(&tagged[..10]).is_err());
+    }
+
+    #[test]
+    fn miner_watermark_ordering() {
+        let watermark = MinerWatermark::new(100, 3);
+        assert!(MinerWatermark::new(100, 4) > watermark);
+        assert!(MinerWatermark::new(101, 0) > watermark);
+        assert!(MinerWatermark::new(99, 10) < watermark);
+
+        assert_eq!(watermark.next_at(100), MinerWatermark::new(100, 4));
+        assert_eq!(watermark.next_at(101), MinerWatermark::new(101, 0));
+        // never goes backwards, even if the burn height does
+        assert_eq!(watermark.next_at(99), MinerWatermark::new(100, 4));
+
+        assert!(watermark.is_newer_than(None));
+        assert!(watermark.next_at(100).is_newer_than(Some(&watermark)));
+        assert!(!watermark.is_newer_than(Some(&watermark)));
+        assert!(!MinerWatermark::new(100, 2).is_newer_than(Some(&watermark)));
+    }
+
+    #[test]
+    fn supporting_weight_of_mixed_signer_set() {
+        let v0_only = ProtocolCapabilities::new(vec![ProtocolVersion::V0]).serialize_to_vec();
+        let both = ProtocolCapabilities::new(vec![ProtocolVersion::V0, ProtocolVersion::V1])
+            .serialize_to_vec();
+        let weights = [1, 2, 4, 8];
+        // the last signer has never written its slot and isn't even in `slots`
+        let slots = vec![Some(v0_only), Some(both), None];
+        assert_eq!(
+            supporting_weight(&weights, &slots, ProtocolVersion::V0),
+            1 + 2
+        );
+        assert_eq!(
+            supporting_weight(&weights, &slots, ProtocolVersion::V1),
+            2 + 4 + 8
+        );
+        assert_eq!(supporting_weight(&[], &[], ProtocolVersion::V1), 0);
+
+        // only the signers that advertise count as reading tagged messages
+        assert_eq!(
+            advertised_weight(&weights, &slots, ProtocolVersion::V0),
+            1 + 2
+        );
+        assert_eq!(advertised_weight(&weights, &slots, ProtocolVersion::V1), 2);
+    }
+
+    #[test]
+    fn miner_event_with_mixed_versions() {
+        let privk = Secp256k1PrivateKey::new();
+        let message = SignerMessage::Transactions(vec![]);
+        let watermark = MinerWatermark::new(7, 0);
+        let contents = [
+            tag_message(ProtocolVersion::V1, watermark, message.serialize_to_vec()),
+            message.serialize_to_vec(),
+            // tagged with a version nobody knows about yet
+            vec![TAGGED_MESSAGE_PREFIX, 0x42, 0, 0, 0, 0],
+            // a v0 message, which does not parse as v1
+            tag_message(ProtocolVersion::V0, watermark, vec![0x00, 0x01]),
+        ];
+        let modified_slots = contents
+            .into_iter()
+            .enumerate()
+            .map(|(slot_id, data)| {
+                let mut chunk = StackerDBChunkData::new(slot_id as u32, 1, data);
+                chunk.sign(&privk).unwrap();
+                chunk
+            })
+            .collect();
+        let event = StackerDBChunksEvent {
+            contract_id: boot_code_id(MINERS_NAME, false),
+            modified_slots,
+        };
+        let SignerEvent::MinerMessages(messages, _) =
+            SignerEvent::<SignerMessage>::try_from(event).unwrap()
+        else {
+            panic!("Expected miner messages");
+        };
+        assert_eq!(
+            messages,
+            vec![
+                MinerSlotMessage {
+                    slot_id: 0,
+                    slot_version: 1,
+                    watermark: Some(watermark),
+                    message: message.clone(),
+                },
+                MinerSlotMessage {
+                    slot_id: 1,
+                    slot_version: 1,
+                    watermark: None,
+                    message,
+                },
+            ]
+        );
+    }
+}
@@ -14,16 +14,19 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::net::{SocketAddr, TcpStream};
-use std::str;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use std::{io, str};
 
 use clarity::vm::types::QualifiedContractIdentifier;
 use libstackerdb::{
-    stackerdb_get_chunk_path, stackerdb_get_metadata_path, stackerdb_post_chunk_path, SlotMetadata,
-    StackerDBChunkAckData, StackerDBChunkData, SIGNERS_STACKERDB_CHUNK_SIZE,
-    STACKERDB_MAX_CHUNK_SIZE,
+    stackerdb_get_chunk_path, stackerdb_get_latest_chunks_path, stackerdb_get_metadata_path,
+    stackerdb_post_chunk_path, SlotMetadata, StackerDBChunkAckData, StackerDBChunkData,
+    SIGNERS_STACKERDB_CHUNK_SIZE, STACKERDB_MAX_CHUNK_SIZE,
+    STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST,
 };
 use stacks_common::codec::StacksMessageCodec;
+use stacks_common::util::hash::hex_bytes;
 
 use crate::error::RPCError;
 use crate::http::run_http_request;
@@ -103,6 +106,14 @@ pub struct StackerDBSession {
     pub stackerdb_contract_id: QualifiedContractIdentifier,
     /// connection to the replica
     sock: Option<TcpStream>,
+    /// how long to wait for a connection to be established (None means the OS default)
+    connect_timeout: Option<Duration>,
+    /// how long to wait on each socket read or write (None means block forever)
+    request_timeout: Option<Duration>,
+    /// how many times to reconnect and retry a request whose connection was dropped
+    max_reconnect_attempts: u32,
+    /// whether the node lacks the multi-slot latest chunks endpoint
+    latest_chunks_unsupported: bool,
 }
 
 impl StackerDBSession {
@@ -112,16 +123,120 @@ impl StackerDBSession {
             host: host.to_owned(),
             stackerdb_contract_id,
             sock: None,
+            connect_timeout: None,
+            request_timeout: None,
+            max_reconnect_attempts: 1,
+            latest_chunks_unsupported: false,
         }
     }
 
+    /// Give up on connecting to the node after `timeout`
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Give up on a request if the node stalls for longer than `timeout` on a read or write
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Reconnect and retry a request at most `attempts` times if the node drops the connection
+    pub fn max_reconnect_attempts(mut self, attempts: u32) -> Self {
+        self.max_reconnect_attempts = attempts;
+        self
+    }
+
     /// connect or reconnect to the node
     fn connect_or_reconnect(&mut self) -> Result<(), RPCError> {
         debug!("connect to {}", &self.host);
-        self.sock = Some(TcpStream::connect(&self.host)?);
+        let sock = match self.connect_timeout {
+            Some(timeout) => {
+                let addr = self.host.to_socket_addrs()?.next().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("could not resolve {}", &self.host),
+                    )
+                })?;
+                TcpStream::connect_timeout(&addr, timeout)
+            }
+            None => TcpStream::connect(&self.host),
+        }
+        .map_err(Self::classify_io_error)?;
+        sock.set_read_timeout(self.request_timeout)?;
+        sock.set_write_timeout(self.request_timeout)?;
+        self.sock = Some(sock);
         Ok(())
     }
 
+    /// The largest chunk this session will accept from the replica
+    fn chunk_size_limit(&self) -> usize {
+        if self.stackerdb_contract_id.name.starts_with("signer") {
+            SIGNERS_STACKERDB_CHUNK_SIZE
+        } else {
+            usize::try_from(STACKERDB_MAX_CHUNK_SIZE)
+                .expect("infallible: StackerDB chunk size exceeds usize::MAX")
+        }
+    }
+
+    /// Fetch the latest chunks of `slot_ids` with the multi-slot endpoint.
+    /// The node may answer with a prefix of the requested slots, in which case the rest are
+    /// requested again.
+    fn get_latest_chunks_batched(
+        &mut self,
+        slot_ids: &[u32],
+    ) -> Result<Vec<Option<Vec<u8>>>, RPCError> {
+        let limit = self.chunk_size_limit();
+        let mut payloads = Vec::with_capacity(slot_ids.len());
+        while payloads.len() < slot_ids.len() {
+            let remaining = &slot_ids[payloads.len()..];
+            let window = &remaining[..remaining.len().min(STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST)];
+            let path = stackerdb_get_latest_chunks_path(self.stackerdb_contract_id.clone(), window);
+            let bytes = self.rpc_request("GET", &path, None, &[])?;
+            let chunks: Vec<Option<String>> = serde_json::from_slice(&bytes)
+                .map_err(|e| RPCError::Deserialize(format!("{:?}", &e)))?;
+            if chunks.is_empty() || chunks.len() > window.len() {
+                return Err(RPCError::Deserialize(format!(
+                    "Expected at most {} latest chunks, got {}",
+                    window.len(),
+                    chunks.len()
+                )));
+            }
+            for chunk in chunks.into_iter() {
+                let chunk = chunk
+                    .map(|hex| hex_bytes(&hex))
+                    .transpose()
+                    .map_err(|e| RPCError::Deserialize(format!("{:?}", &e)))?;
+                // Verify that the chunk is not too large
+                payloads.push(chunk.filter(|bytes| bytes.len() <= limit));
+            }
+        }
+        Ok(payloads)
+    }
+
+    /// Report socket timeouts as `RPCError::Timeout`
+    fn classify_io_error(e: io::Error) -> RPCError {
+        match e.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => RPCError::Timeout,
+            _ => RPCError::IO(e),
+        }
+    }
+
+    /// Is this the error we get when the node drops the connection on us?
+    fn is_dropped_connection(e: &RPCError) -> bool {
+        let RPCError::IO(e) = e else {
+            return false;
+        };
+        matches!(
+            e.kind(),
+            io::ErrorKind::BrokenPipe
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::UnexpectedEof
+        )
+    }
+
     /// Do something with the connected socket
     fn with_socket<F, R>(&mut self, todo: F) -> Result<R, RPCError>
     where
@@ -153,9 +268,28 @@ impl StackerDBSession {
         content_type: Option<&str>,
         payload: &[u8],
     ) -> Result<Vec<u8>, RPCError> {
-        self.with_socket(|session, sock| {
-            run_http_request(sock, &session.host, verb, path, content_type, payload)
-        })?
+        let mut attempts = 0;
+        loop {
+            let result = self
+                .with_socket(|session, sock| {
+                    run_http_request(sock, &session.host, verb, path, content_type, payload)
+                })
+                .and_then(|res| res);
+            match result {
+                Err(e)
+                    if Self::is_dropped_connection(&e)
+                        && attempts < self.max_reconnect_attempts =>
+                {
+                    attempts += 1;
+                    debug!(
+                        "Connection to {} dropped ({e}); reconnecting (attempt {attempts})",
+                        &self.host
+                    );
+                }
+                Err(RPCError::IO(e)) => return Err(Self::classify_io_error(e)),
+                result => return result,
+            }
+        }
     }
 }
 
@@ -213,15 +347,24 @@ impl SignerSession for StackerDBSession {
         Ok(payloads)
     }
 
-    /// query the replica for zero or more latest chunks
+    /// query the replica for zero or more latest chunks.
+    /// Several slots are fetched with the node's multi-slot endpoint; a single slot, or a node
+    /// that does not serve that endpoint, falls back to one request per slot.
     fn get_latest_chunks(&mut self, slot_ids: &[u32]) -> Result<Vec<Option<Vec<u8>>>, RPCError> {
+        if slot_ids.len() > 1 && !self.latest_chunks_unsupported {
+            match self.get_latest_chunks_batched(slot_ids) {
+                Err(RPCError::HttpError(404)) => {
+                    debug!(
+                        "{} does not serve multi-slot latest chunks; fetching one slot at a time",
+                        &self.host
+                    );
+                    self.latest_chunks_unsupported = true;
+                }
+                result => return result,
+            }
+        }
         let mut payloads = vec![];
-        let limit = if self.stackerdb_contract_id.name.starts_with("signer") {
-            SIGNERS_STACKERDB_CHUNK_SIZE
-        } else {
-            usize::try_from(STACKERDB_MAX_CHUNK_SIZE)
-                .expect("infallible: StackerDB chunk size exceeds usize::MAX")
-        };
+        let limit = self.chunk_size_limit();
         for slot_id in slot_ids.iter() {
             let path = stackerdb_get_chunk_path(self.stackerdb_contract_id.clone(), *slot_id, None);
             let chunk = match self.rpc_request("GET", &path, None, &[]) {
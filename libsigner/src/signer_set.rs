@@ -13,7 +13,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use blockstack_lib::chainstate::stacks::boot::NakamotoSignerEntry;
+use blockstack_lib::chainstate::stacks::boot::{signing_threshold_of, NakamotoSignerEntry};
 use hashbrown::{HashMap, HashSet};
 use stacks_common::types::chainstate::{StacksAddress, StacksPublicKey};
 use wsts::curve::ecdsa;
@@ -50,6 +50,25 @@ impl SignerEntries {
     /// Try to parse the reward set defined by `NakamotoSignEntry` into the structures required
     ///  by WSTS party members and coordinators.
     pub fn parse(is_mainnet: bool, reward_set: &[NakamotoSignerEntry]) -> Result<Self, Error> {
+        let (parsed, _) = Self::parse_entries(is_mainnet, reward_set, false)?;
+        Ok(parsed)
+    }
+
+    /// Like [`SignerEntries::parse`], but leaves out the signers whose signing keys are
+    ///  malformed instead of failing. The other signers keep the signer IDs and key IDs they
+    ///  would have had. Returns the parsed signers and the IDs of the ones left out.
+    pub fn parse_valid(
+        is_mainnet: bool,
+        reward_set: &[NakamotoSignerEntry],
+    ) -> Result<(Self, Vec<u32>), Error> {
+        Self::parse_entries(is_mainnet, reward_set, true)
+    }
+
+    fn parse_entries(
+        is_mainnet: bool,
+        reward_set: &[NakamotoSignerEntry],
+        skip_invalid: bool,
+    ) -> Result<(Self, Vec<u32>), Error> {
         let mut weight_end = 1;
         let mut signer_key_ids = HashMap::with_capacity(reward_set.len());
         let mut signer_public_keys = HashMap::with_capacity(reward_set.len());
@@ -57,33 +76,25 @@ impl SignerEntries {
         let mut signer_ids = HashMap::with_capacity(reward_set.len());
         let mut wsts_signers = HashMap::new();
         let mut wsts_key_ids = HashMap::new();
+        let mut skipped = vec![];
         for (i, entry) in reward_set.iter().enumerate() {
             let signer_id = u32::try_from(i).map_err(|_| Error::SignerCountOverflow)?;
-            let ecdsa_pk =
-                ecdsa::PublicKey::try_from(entry.signing_key.as_slice()).map_err(|e| {
-                    Error::BadSignerPublicKey(format!(
-                        "Failed to convert signing key to ecdsa::PublicKey: {e}"
-                    ))
-                })?;
-            let signer_public_key = Point::try_from(&Compressed::from(ecdsa_pk.to_bytes()))
-                .map_err(|e| {
-                    Error::BadSignerPublicKey(format!(
-                        "Failed to convert signing key to wsts::Point: {e}"
-                    ))
-                })?;
-            let stacks_public_key = StacksPublicKey::from_slice(entry.signing_key.as_slice())
-                .map_err(|e| {
-                    Error::BadSignerPublicKey(format!(
-                        "Failed to convert signing key to StacksPublicKey: {e}"
-                    ))
-                })?;
+            let weight_start = weight_end;
+            weight_end = weight_start + entry.weight;
+            let (ecdsa_pk, signer_public_key, stacks_public_key) =
+                match Self::parse_signing_key(entry) {
+                    Ok(keys) => keys,
+                    Err(_) if skip_invalid => {
+                        skipped.push(signer_id);
+                        continue;
+                    }
+                    Err(e) => return Err(e),
+                };
 
             let stacks_address = StacksAddress::p2pkh(is_mainnet, &stacks_public_key);
             signer_ids.insert(stacks_address, signer_id);
 
             signer_public_keys.insert(signer_id, signer_public_key);
-            let weight_start = weight_end;
-            weight_end = weight_start + entry.weight;
             let key_ids: HashSet<u32> = (weight_start..weight_end).collect();
             for key_id in key_ids.iter() {
                 wsts_key_ids.insert(*key_id, ecdsa_pk);
@@ -93,7 +104,7 @@ impl SignerEntries {
             wsts_signers.insert(signer_id, ecdsa_pk);
         }
 
-        Ok(Self {
+        let parsed = Self {
             signer_ids,
             public_keys: PublicKeys {
                 signers: wsts_signers,
@@ -102,7 +113,32 @@ impl SignerEntries {
             signer_key_ids,
             signer_public_keys,
             coordinator_key_ids,
-        })
+        };
+        Ok((parsed, skipped))
+    }
+
+    /// Parse `entry`'s signing key into the forms WSTS and Stacks need
+    fn parse_signing_key(
+        entry: &NakamotoSignerEntry,
+    ) -> Result<(ecdsa::PublicKey, Point, StacksPublicKey), Error> {
+        let ecdsa_pk = ecdsa::PublicKey::try_from(entry.signing_key.as_slice()).map_err(|e| {
+            Error::BadSignerPublicKey(format!(
+                "Failed to convert signing key to ecdsa::PublicKey: {e}"
+            ))
+        })?;
+        let signer_public_key =
+            Point::try_from(&Compressed::from(ecdsa_pk.to_bytes())).map_err(|e| {
+                Error::BadSignerPublicKey(format!(
+                    "Failed to convert signing key to wsts::Point: {e}"
+                ))
+            })?;
+        let stacks_public_key =
+            StacksPublicKey::from_slice(entry.signing_key.as_slice()).map_err(|e| {
+                Error::BadSignerPublicKey(format!(
+                    "Failed to convert signing key to StacksPublicKey: {e}"
+                ))
+            })?;
+        Ok((ecdsa_pk, signer_public_key, stacks_public_key))
     }
 
     /// Return the number of Key IDs in the WSTS group signature
@@ -126,7 +162,13 @@ impl SignerEntries {
     /// Return the number of Key IDs required to sign a message with the WSTS group signature
     pub fn get_signing_threshold(&self) -> Result<u32, Error> {
         let num_keys = self.count_keys()?;
-        Ok((num_keys as f64 * 7_f64 / 10_f64).ceil() as u32)
+        Ok(Self::signing_threshold(num_keys))
+    }
+
+    /// Return the number of Key IDs required to sign a message with a WSTS group signature
+    ///  over `num_keys` keys. This is the threshold miners compute for the reward set.
+    pub fn signing_threshold(num_keys: u32) -> u32 {
+        signing_threshold_of(num_keys)
     }
 
     /// Return the number of Key IDs required to sign a message with the WSTS group signature
@@ -135,3 +177,231 @@ impl SignerEntries {
         Ok((num_keys as f64 * 9_f64 / 10_f64).ceil() as u32)
     }
 }
+
+/// The slot a signer writes to in its reward cycle's signers StackerDBs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SlotId(pub u32);
+
+/// A signer's position in its reward set's list of signers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RewardSetIndex(pub u32);
+
+/// A signer's ID in the WSTS signing group, as `SignerEntries` assigns them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WstsSignerId(pub u32);
+
+/// Maps between the three ways the signers of a reward cycle are identified: the slot they
+/// write to in StackerDB events, their position in the reward set, and their WSTS signer ID.
+/// The `.signers` contract lays out StackerDB slots in reward set order and `SignerEntries`
+/// hands out WSTS signer IDs in reward set order, so the three agree. Converting through this
+/// map keeps code that handles more than one of them from relying on that.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignerIndexMap {
+    /// Slot of each signer, by reward set index
+    slots: Vec<SlotId>,
+    /// WSTS signer ID of each signer, by reward set index
+    wsts_signer_ids: Vec<WstsSignerId>,
+    /// Reward set index of each slot
+    by_slot: HashMap<SlotId, RewardSetIndex>,
+    /// Reward set index of each WSTS signer ID
+    by_wsts_signer_id: HashMap<WstsSignerId, RewardSetIndex>,
+    /// Reward set index of each signer with a well-formed signing key, by compressed key
+    by_signing_key: HashMap<Vec<u8>, RewardSetIndex>,
+}
+
+impl SignerIndexMap {
+    /// Map the indices of the signers in `reward_set`, in reward set order
+    pub fn new(reward_set: &[NakamotoSignerEntry]) -> Self {
+        let mut map = Self::default();
+        for (i, entry) in reward_set.iter().enumerate() {
+            let i = u32::try_from(i).expect("FATAL: more than u32::MAX signers");
+            let index = RewardSetIndex(i);
+            map.slots.push(SlotId(i));
+            map.wsts_signer_ids.push(WstsSignerId(i));
+            map.by_slot.insert(SlotId(i), index);
+            map.by_wsts_signer_id.insert(WstsSignerId(i), index);
+            if let Ok(signing_key) = StacksPublicKey::from_slice(entry.signing_key.as_slice()) {
+                map.by_signing_key
+                    .insert(signing_key.to_bytes_compressed(), index);
+            }
+        }
+        map
+    }
+
+    /// The number of signers in the reward set
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Does the reward set have no signers?
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// The slot of the signer at `index`, if there is one
+    pub fn slot_of(&self, index: RewardSetIndex) -> Option<SlotId> {
+        self.slots.get(usize::try_from(index.0).ok()?).copied()
+    }
+
+    /// The WSTS signer ID of the signer at `index`, if there is one
+    pub fn wsts_signer_id_of(&self, index: RewardSetIndex) -> Option<WstsSignerId> {
+        self.wsts_signer_ids
+            .get(usize::try_from(index.0).ok()?)
+            .copied()
+    }
+
+    /// The reward set index of the signer that writes to `slot_id`, if there is one
+    pub fn index_of_slot(&self, slot_id: SlotId) -> Option<RewardSetIndex> {
+        self.by_slot.get(&slot_id).copied()
+    }
+
+    /// The reward set index of the signer with `signer_id`, if there is one
+    pub fn index_of_wsts_signer(&self, signer_id: WstsSignerId) -> Option<RewardSetIndex> {
+        self.by_wsts_signer_id.get(&signer_id).copied()
+    }
+
+    /// The reward set index of the signer whose signing key is `signer_pubkey`, if it is in
+    /// the set
+    pub fn index_of_key(&self, signer_pubkey: &StacksPublicKey) -> Option<RewardSetIndex> {
+        self.by_signing_key
+            .get(&signer_pubkey.to_bytes_compressed())
+            .copied()
+    }
+
+    /// The slot of the signer with `signer_id`, if there is one
+    pub fn slot_of_wsts_signer(&self, signer_id: WstsSignerId) -> Option<SlotId> {
+        self.slot_of(self.index_of_wsts_signer(signer_id)?)
+    }
+
+    /// The WSTS signer ID of the signer that writes to `slot_id`, if there is one
+    pub fn wsts_signer_of_slot(&self, slot_id: SlotId) -> Option<WstsSignerId> {
+        self.wsts_signer_id_of(self.index_of_slot(slot_id)?)
+    }
+
+    /// The slot of the signer whose signing key is `signer_pubkey`, if it is in the set
+    pub fn slot_of_key(&self, signer_pubkey: &StacksPublicKey) -> Option<SlotId> {
+        self.slot_of(self.index_of_key(signer_pubkey)?)
+    }
+}
+
+/// The signer set (i.e., X in signers-X-Y) whose StackerDBs carry the messages of the
+/// signers for `reward_cycle`. Consecutive reward cycles alternate between the two sets.
+pub fn signer_set_for_cycle(reward_cycle: u64) -> u32 {
+    if reward_cycle % 2 == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+/// Do messages from signer set `signer_set` belong to the signers of `reward_cycle`?
+/// Only the parity of the cycle is known from a signer set, so this is also true for
+/// `reward_cycle + 2`, and false for anything other than signer set 0 or 1.
+pub fn cycle_matches_signer_set(reward_cycle: u64, signer_set: u32) -> bool {
+    signer_set_for_cycle(reward_cycle) == signer_set
+}
+
+#[cfg(test)]
+mod test {
+    use stacks_common::types::chainstate::StacksPrivateKey;
+
+    use super::*;
+
+    fn signer_entry(private_key: &StacksPrivateKey, weight: u32) -> NakamotoSignerEntry {
+        NakamotoSignerEntry {
+            signing_key: StacksPublicKey::from_private(private_key)
+                .to_bytes_compressed()
+                .try_into()
+                .unwrap(),
+            stacked_amt: u128::from(weight),
+            weight,
+        }
+    }
+
+    #[test]
+    fn signer_indices_follow_the_reward_set_order() {
+        let private_keys: Vec<_> = (0..6).map(|_| StacksPrivateKey::new()).collect();
+        // the same signers, listed in different orders
+        for order in [
+            [0, 1, 2, 3, 4, 5],
+            [5, 4, 3, 2, 1, 0],
+            [2, 5, 0, 3, 1, 4],
+            [3, 0, 4, 1, 5, 2],
+        ] {
+            let reward_set: Vec<_> = order
+                .iter()
+                .map(|signer| signer_entry(&private_keys[*signer], *signer as u32 + 1))
+                .collect();
+            let map = SignerIndexMap::new(&reward_set);
+            let entries = SignerEntries::parse(false, &reward_set).unwrap();
+            assert_eq!(map.len(), 6);
+            for (position, signer) in order.iter().enumerate() {
+                let position = u32::try_from(position).unwrap();
+                let signer_pubkey = StacksPublicKey::from_private(&private_keys[*signer]);
+                let index = map.index_of_key(&signer_pubkey).unwrap();
+                assert_eq!(index, RewardSetIndex(position));
+                let slot_id = map.slot_of_key(&signer_pubkey).unwrap();
+                assert_eq!(slot_id, SlotId(position));
+                let signer_id = map.wsts_signer_id_of(index).unwrap();
+                assert_eq!(map.index_of_slot(slot_id), Some(index));
+                assert_eq!(map.index_of_wsts_signer(signer_id), Some(index));
+                assert_eq!(map.slot_of_wsts_signer(signer_id), Some(slot_id));
+                assert_eq!(map.wsts_signer_of_slot(slot_id), Some(signer_id));
+                // the same signer ID the WSTS structs are keyed by
+                let address = StacksAddress::p2pkh(false, &signer_pubkey);
+                assert_eq!(entries.signer_ids[&address], signer_id.0);
+                let key_ids = &entries.signer_key_ids[&signer_id.0];
+                assert_eq!(key_ids.len(), *signer + 1);
+            }
+            assert_eq!(map.slot_of(RewardSetIndex(6)), None);
+            assert_eq!(map.index_of_slot(SlotId(6)), None);
+            assert_eq!(map.slot_of_wsts_signer(WstsSignerId(6)), None);
+        }
+    }
+
+    #[test]
+    fn malformed_signing_keys_keep_their_indices() {
+        let private_keys: Vec<_> = (0..3).map(|_| StacksPrivateKey::new()).collect();
+        let mut reward_set: Vec<_> = private_keys
+            .iter()
+            .map(|private_key| signer_entry(private_key, 1))
+            .collect();
+        // not a point on the curve
+        reward_set[1].signing_key = [0x02; 33];
+        reward_set[1].signing_key[1..].copy_from_slice(&[0xff; 32]);
+        let map = SignerIndexMap::new(&reward_set);
+        assert_eq!(map.slot_of(RewardSetIndex(1)), Some(SlotId(1)));
+        assert_eq!(map.wsts_signer_of_slot(SlotId(1)), Some(WstsSignerId(1)));
+        let slot_of =
+            |signer: usize| map.slot_of_key(&StacksPublicKey::from_private(&private_keys[signer]));
+        assert_eq!(slot_of(0), Some(SlotId(0)));
+        assert_eq!(slot_of(1), None);
+        assert_eq!(slot_of(2), Some(SlotId(2)));
+    }
+
+    #[test]
+    fn signer_sets_alternate_between_cycles() {
+        assert_eq!(signer_set_for_cycle(0), 0);
+        assert_eq!(signer_set_for_cycle(1), 1);
+        assert_eq!(signer_set_for_cycle(2), 0);
+        assert_eq!(signer_set_for_cycle(u64::MAX), 1);
+    }
+
+    #[test]
+    fn signer_set_at_cycle_boundary() {
+        for cycle in [0, 1, 41, 42, u64::MAX - 2] {
+            let signer_set = signer_set_for_cycle(cycle);
+            assert!(cycle_matches_signer_set(cycle, signer_set));
+            // cycle N proposals arriving while events are tagged for cycle N+1
+            let next_signer_set = signer_set_for_cycle(cycle + 1);
+            assert!(!cycle_matches_signer_set(cycle, next_signer_set));
+            assert!(!cycle_matches_signer_set(cycle + 1, signer_set));
+            // only the parity is known
+            assert!(cycle_matches_signer_set(cycle + 2, signer_set));
+        }
+        // not signer sets at all, even if their parity matches
+        assert!(!cycle_matches_signer_set(0, 2));
+        assert!(!cycle_matches_signer_set(1, 3));
+        assert!(!cycle_matches_signer_set(1, u32::MAX));
+    }
+}
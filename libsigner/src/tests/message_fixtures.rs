@@ -0,0 +1,310 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Golden vectors for the wire format of the v0 messages miners and signers exchange.
+//! Every message shape either side writes is built from fixed values here and compared
+//! byte-for-byte against `fixtures/v0_messages.txt`, so that a release cannot change the
+//! wire format by accident.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use blockstack_lib::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
+use blockstack_lib::chainstate::stacks::TenureChangeCause;
+use blockstack_lib::net::api::postblock_proposal::ValidateRejectCode;
+use clarity::types::chainstate::{ConsensusHash, StacksBlockId, TrieHash};
+use clarity::util::secp256k1::MessageSignature;
+use stacks_common::codec::{read_next, StacksMessageCodec};
+use stacks_common::util::hash::{hex_bytes, to_hex, Sha512Trunc256Sum};
+
+use crate::protocol::{
+    tag_message, untag_message, MinerWatermark, ProtocolCapabilities, ProtocolVersion,
+};
+use crate::v0::messages::{
+    BlockRejection, BlockResponse, RejectCode, SignerMessage, UnknownRejectCode,
+};
+use crate::{BlockProposal, TenureExtend, TenureView};
+
+/// Set this environment variable to rewrite the fixtures file from the messages built here
+const REGENERATE_ENV_VAR: &str = "LIBSIGNER_REGENERATE_FIXTURES";
+
+const FIXTURES_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/tests/fixtures/v0_messages.txt"
+);
+
+const FIXTURES_HEADER: &str = "\
+# Golden vectors for the v0 messages miners and signers write to StackerDB, checked by
+# `tests::message_fixtures`. One `<name> <hex>` pair per line.
+#
+# Any change to these bytes is a wire format change. If it is intended, regenerate this file
+# with `LIBSIGNER_REGENERATE_FIXTURES=1 cargo test -p libsigner regenerate_message_fixtures`.
+";
+
+/// A message as it is written to a StackerDB slot
+#[derive(Debug, PartialEq)]
+enum Fixture {
+    /// An untagged message, as signers write them
+    Message(SignerMessage),
+    /// A message tagged with its protocol version and watermark, as miners write them
+    Tagged(ProtocolVersion, MinerWatermark, SignerMessage),
+    /// A signer's capabilities advertisement
+    Capabilities(ProtocolCapabilities),
+}
+
+impl Fixture {
+    fn serialize(&self) -> Vec<u8> {
+        match self {
+            Fixture::Message(message) => message.serialize_to_vec(),
+            Fixture::Tagged(version, watermark, message) => {
+                tag_message(*version, *watermark, message.serialize_to_vec())
+            }
+            Fixture::Capabilities(capabilities) => capabilities.serialize_to_vec(),
+        }
+    }
+
+    /// Decode `bytes` as the same kind of message as `self`, requiring all of them to be read
+    fn decode_like(&self, bytes: &[u8]) -> Fixture {
+        let mut remaining = bytes;
+        let decoded = match self {
+            Fixture::Message(_) => Fixture::Message(read_next(&mut remaining).unwrap()),
+            Fixture::Tagged(..) => {
+                let (version, watermark, mut message) = untag_message(bytes).unwrap();
+                let watermark = watermark.expect("tagged message has no watermark");
+                let decoded = Fixture::Tagged(version, watermark, read_next(&mut message).unwrap());
+                remaining = message;
+                decoded
+            }
+            Fixture::Capabilities(_) => Fixture::Capabilities(read_next(&mut remaining).unwrap()),
+        };
+        assert!(remaining.is_empty(), "{} trailing bytes", remaining.len());
+        decoded
+    }
+}
+
+fn block() -> NakamotoBlock {
+    // no transactions, so the all-zero tx merkle root of the empty header is valid
+    let mut header = NakamotoBlockHeader::empty();
+    header.chain_length = 100;
+    header.burn_spent = 5000;
+    header.consensus_hash = ConsensusHash([0x33; 20]);
+    header.parent_block_id = StacksBlockId([0x44; 32]);
+    header.state_index_root = TrieHash([0x55; 32]);
+    header.miner_signature = MessageSignature([0x66; 65]);
+    NakamotoBlock {
+        header,
+        txs: vec![],
+    }
+}
+
+fn miner_view(cause: TenureChangeCause) -> TenureView {
+    TenureView {
+        burn_view_consensus_hash: ConsensusHash([0x77; 20]),
+        parent_tenure_id: Some(StacksBlockId([0x88; 32])),
+        tenure_change_cause: Some(cause),
+    }
+}
+
+fn block_proposal(
+    miner_view: Option<TenureView>,
+    tenure_extend: Option<TenureExtend>,
+) -> BlockProposal {
+    BlockProposal {
+        block: block(),
+        burn_height: 812345,
+        reward_cycle: 98,
+        miner_view,
+        tenure_extend,
+        attempt: None,
+    }
+}
+
+fn proposal(miner_view: Option<TenureView>, tenure_extend: Option<TenureExtend>) -> SignerMessage {
+    SignerMessage::BlockProposal(block_proposal(miner_view, tenure_extend))
+}
+
+fn rejection(reason_code: RejectCode) -> SignerMessage {
+    BlockRejection::new(Sha512Trunc256Sum([0x11; 32]), reason_code).into()
+}
+
+/// Every message shape miners and signers write, by fixture name
+fn fixtures() -> Vec<(&'static str, Fixture)> {
+    let tenure_extend = TenureExtend {
+        tenure_consensus_hash: ConsensusHash([0x33; 20]),
+        burn_view_consensus_hash: ConsensusHash([0x77; 20]),
+        previous_tenure_end: StacksBlockId([0x99; 32]),
+        previous_tenure_blocks: 12,
+    };
+    let mut view_mismatch = BlockRejection::new(
+        Sha512Trunc256Sum([0x11; 32]),
+        RejectCode::SortitionViewMismatch,
+    );
+    view_mismatch.signer_view = Some(TenureView {
+        burn_view_consensus_hash: ConsensusHash([0xaa; 20]),
+        parent_tenure_id: None,
+        tenure_change_cause: None,
+    });
+    vec![
+        // written by miners
+        ("block_proposal", Fixture::Message(proposal(None, None))),
+        (
+            "block_proposal_with_miner_view",
+            Fixture::Message(proposal(
+                Some(miner_view(TenureChangeCause::BlockFound)),
+                None,
+            )),
+        ),
+        (
+            "block_proposal_with_attempt",
+            Fixture::Message(SignerMessage::BlockProposal(BlockProposal {
+                attempt: Some(2),
+                ..block_proposal(Some(miner_view(TenureChangeCause::BlockFound)), None)
+            })),
+        ),
+        (
+            "block_proposal_tenure_extend",
+            Fixture::Message(proposal(
+                Some(miner_view(TenureChangeCause::Extended)),
+                Some(tenure_extend),
+            )),
+        ),
+        (
+            "tagged_block_proposal",
+            Fixture::Tagged(
+                ProtocolVersion::V0,
+                MinerWatermark::new(812345, 2),
+                proposal(Some(miner_view(TenureChangeCause::BlockFound)), None),
+            ),
+        ),
+        // written by signers
+        (
+            "block_response_accepted",
+            Fixture::Message(
+                BlockResponse::accepted(
+                    Sha512Trunc256Sum([0x11; 32]),
+                    MessageSignature([0x22; 65]),
+                )
+                .into(),
+            ),
+        ),
+        (
+            "block_response_rejected_validation_failed",
+            Fixture::Message(rejection(RejectCode::ValidationFailed(
+                ValidateRejectCode::InvalidBlock,
+            ))),
+        ),
+        (
+            "block_response_rejected_connectivity_issues",
+            Fixture::Message(rejection(RejectCode::ConnectivityIssues)),
+        ),
+        (
+            "block_response_rejected_in_prior_round",
+            Fixture::Message(rejection(RejectCode::RejectedInPriorRound)),
+        ),
+        (
+            "block_response_rejected_no_sortition_view",
+            Fixture::Message(rejection(RejectCode::NoSortitionView)),
+        ),
+        (
+            "block_response_rejected_sortition_view_mismatch",
+            Fixture::Message(view_mismatch.into()),
+        ),
+        (
+            "block_response_rejected_testing_directive",
+            Fixture::Message(rejection(RejectCode::TestingDirective)),
+        ),
+        (
+            "block_response_rejected_unknown_code",
+            Fixture::Message(rejection(RejectCode::Unknown(
+                UnknownRejectCode::try_from(0xf0).unwrap(),
+            ))),
+        ),
+        (
+            "protocol_capabilities",
+            Fixture::Capabilities(ProtocolCapabilities::new(vec![
+                ProtocolVersion::V0,
+                ProtocolVersion::V1,
+            ])),
+        ),
+    ]
+}
+
+fn regenerating() -> bool {
+    std::env::var_os(REGENERATE_ENV_VAR).is_some()
+}
+
+/// The stored fixtures, as bytes by fixture name
+fn load_fixtures() -> BTreeMap<String, Vec<u8>> {
+    let contents = fs::read_to_string(FIXTURES_PATH).unwrap();
+    contents
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, hex) = line
+                .split_once(' ')
+                .unwrap_or_else(|| panic!("Malformed fixture line: {line}"));
+            (name.to_string(), hex_bytes(hex).unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn regenerate_message_fixtures() {
+    if !regenerating() {
+        return;
+    }
+    let mut contents = FIXTURES_HEADER.to_string();
+    for (name, fixture) in fixtures() {
+        contents.push_str(&format!("{name} {}\n", to_hex(&fixture.serialize())));
+    }
+    fs::write(FIXTURES_PATH, contents).unwrap();
+}
+
+#[test]
+fn messages_serialize_to_fixtures() {
+    if regenerating() {
+        return;
+    }
+    let stored = load_fixtures();
+    let fixtures = fixtures();
+    let names: BTreeSet<_> = fixtures.iter().map(|(name, _)| *name).collect();
+    assert_eq!(
+        stored.keys().map(String::as_str).collect::<BTreeSet<_>>(),
+        names,
+        "The stored fixtures do not match the message shapes. Set {REGENERATE_ENV_VAR} to regenerate them."
+    );
+    for (name, fixture) in fixtures {
+        assert_eq!(
+            to_hex(&fixture.serialize()),
+            to_hex(&stored[name]),
+            "The wire format of {name} changed. If this is intended, set {REGENERATE_ENV_VAR} to regenerate the fixtures."
+        );
+    }
+}
+
+#[test]
+fn fixtures_deserialize_to_messages() {
+    if regenerating() {
+        return;
+    }
+    let stored = load_fixtures();
+    for (name, fixture) in fixtures() {
+        let bytes = stored
+            .get(name)
+            .unwrap_or_else(|| panic!("No stored fixture for {name}"));
+        assert_eq!(fixture.decode_like(bytes), fixture, "{name}");
+    }
+}
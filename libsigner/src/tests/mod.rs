@@ -15,6 +15,8 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 mod http;
+mod message_fixtures;
+mod session;
 
 use std::fmt::Debug;
 use std::io::{Read, Write};
@@ -39,7 +41,7 @@ use wsts::net::{DkgBegin, Packet};
 
 use crate::events::{SignerEvent, SignerEventTrait};
 use crate::v1::messages::SignerMessage;
-use crate::{Signer, SignerEventReceiver, SignerRunLoop};
+use crate::{Signer, SignerEventReceiver, SignerRunLoop, SignerSlotMessage};
 
 /// Simple runloop implementation.  It receives `max_events` events and returns `events` from the
 /// last call to `run_one_pass` as its final state.
@@ -165,9 +167,17 @@ fn test_simple_signer() {
     let sent_events: Vec<SignerEvent<SignerMessage>> = chunks
         .iter()
         .map(|chunk| {
-            let msg = chunk.modified_slots[0].data.clone();
-            let signer_message = read_next::<SignerMessage, _>(&mut &msg[..]).unwrap();
-            SignerEvent::SignerMessages(0, vec![signer_message])
+            let chunk = &chunk.modified_slots[0];
+            let signer_message = read_next::<SignerMessage, _>(&mut &chunk.data[..]).unwrap();
+            SignerEvent::SignerMessages(
+                0,
+                vec![SignerSlotMessage {
+                    slot_id: chunk.slot_id,
+                    slot_version: chunk.slot_version,
+                    signer_pubkey: chunk.recover_pk().unwrap(),
+                    message: signer_message,
+                }],
+            )
         })
         .collect();
 
@@ -0,0 +1,245 @@
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use libstackerdb::{StackerDBChunkAckData, StackerDBChunkData};
+use stacks_common::util::hash::to_hex;
+
+use crate::error::RPCError;
+use crate::session::{SignerSession, StackerDBSession};
+
+/// Read an HTTP request, including any `Content-Length` body, off of `sock`
+fn read_request(sock: &mut TcpStream) -> String {
+    let mut buf = vec![];
+    let mut byte = [0u8; 1];
+    while !buf.ends_with(b"\r\n\r\n") {
+        sock.read_exact(&mut byte).unwrap();
+        buf.push(byte[0]);
+    }
+    let headers = String::from_utf8(buf).unwrap();
+    let content_length = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("Content-Length: "))
+        .map(|len| len.parse::<usize>().unwrap())
+        .unwrap_or(0);
+    let mut body = vec![0u8; content_length];
+    sock.read_exact(&mut body).unwrap();
+    headers
+}
+
+/// Spawn a mock node that hangs up on the first `drops` connections it accepts, then replies
+/// to the next request with `body`. Returns the node's address and a handle that yields the
+/// number of connections it accepted.
+fn spawn_flaky_node(drops: usize, body: &'static str) -> (String, thread::JoinHandle<usize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = thread::spawn(move || {
+        let mut accepted = 0;
+        for sock in listener.incoming() {
+            let mut sock = sock.unwrap();
+            accepted += 1;
+            if accepted <= drops {
+                drop(sock);
+                continue;
+            }
+            read_request(&mut sock);
+            write!(
+                sock,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            break;
+        }
+        accepted
+    });
+    (addr, handle)
+}
+
+/// Spawn a mock node that serves `requests` latest-chunk requests out of `chunks`, indexed by
+/// slot ID, answering 404 for missing slots. Multi-slot requests are answered only if `batch`
+/// is set, and with at most `batch_limit` slots; otherwise they get a 404.
+fn spawn_chunk_node(
+    chunks: Vec<Option<&'static str>>,
+    requests: usize,
+    batch: bool,
+    batch_limit: usize,
+) -> (String, thread::JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    let handle = thread::spawn(move || {
+        for sock in listener.incoming().take(requests) {
+            let mut sock = sock.unwrap();
+            let request = read_request(&mut sock);
+            let path = request.split_whitespace().nth(1).unwrap();
+            if let Some((_, slot_ids)) = path.split_once("/latest_chunks?slot_ids=") {
+                if !batch {
+                    write!(sock, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n").unwrap();
+                    continue;
+                }
+                let body: Vec<_> = slot_ids
+                    .split(',')
+                    .take(batch_limit)
+                    .map(|slot_id| {
+                        let slot_id: usize = slot_id.parse().unwrap();
+                        chunks
+                            .get(slot_id)
+                            .copied()
+                            .flatten()
+                            .map(|chunk| to_hex(chunk.as_bytes()))
+                    })
+                    .collect();
+                let body = serde_json::to_string(&body).unwrap();
+                write!(
+                    sock,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+                continue;
+            }
+            let slot_id: usize = path.rsplit('/').next().unwrap().parse().unwrap();
+            match chunks.get(slot_id).copied().flatten() {
+                Some(body) => write!(
+                    sock,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                ),
+                None => write!(sock, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n"),
+            }
+            .unwrap();
+        }
+    });
+    (addr, handle)
+}
+
+fn contract_id() -> QualifiedContractIdentifier {
+    QualifiedContractIdentifier::parse("SP000000000000000000002Q6VF78.miners").unwrap()
+}
+
+#[test]
+fn test_session_reconnects_on_dropped_connection() {
+    let (addr, node) = spawn_flaky_node(1, "hello");
+    let mut session = StackerDBSession::new(&addr, contract_id());
+    assert_eq!(
+        session.get_latest_chunk(0).unwrap(),
+        Some(b"hello".to_vec())
+    );
+    assert_eq!(node.join().unwrap(), 2);
+
+    let (addr, node) = spawn_flaky_node(1, r#"{"accepted":true}"#);
+    let mut session = StackerDBSession::new(&addr, contract_id());
+    let ack = session
+        .put_chunk(&StackerDBChunkData::new(0, 1, vec![1, 2, 3]))
+        .unwrap();
+    assert_eq!(
+        ack,
+        StackerDBChunkAckData {
+            accepted: true,
+            reason: None,
+            metadata: None,
+            code: None,
+            retry_after: None,
+        }
+    );
+    assert_eq!(node.join().unwrap(), 2);
+}
+
+#[test]
+fn test_session_gives_up_after_max_reconnect_attempts() {
+    let (addr, node) = spawn_flaky_node(2, "hello");
+    let mut session = StackerDBSession::new(&addr, contract_id());
+    let err = session.get_latest_chunk(0).unwrap_err();
+    assert!(matches!(err, RPCError::IO(_)), "unexpected error {err:?}");
+
+    // the node now answers the next request
+    let mut session = StackerDBSession::new(&addr, contract_id()).max_reconnect_attempts(0);
+    assert_eq!(
+        session.get_latest_chunk(0).unwrap(),
+        Some(b"hello".to_vec())
+    );
+    assert_eq!(node.join().unwrap(), 3);
+}
+
+#[test]
+fn test_session_request_timeout() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    // accept the connection, but never answer
+    let node = thread::spawn(move || {
+        let (mut sock, _) = listener.accept().unwrap();
+        read_request(&mut sock);
+        thread::sleep(Duration::from_secs(2));
+    });
+
+    let mut session = StackerDBSession::new(&addr, contract_id())
+        .connect_timeout(Duration::from_secs(1))
+        .request_timeout(Duration::from_millis(100));
+    let start = Instant::now();
+    let err = session.get_latest_chunk(0).unwrap_err();
+    assert!(matches!(err, RPCError::Timeout), "unexpected error {err:?}");
+    assert!(start.elapsed() < Duration::from_secs(2));
+    node.join().unwrap();
+}
+
+#[test]
+fn test_session_get_latest_chunks_matches_individual_fetches() {
+    let chunks = vec![Some("zero"), None, Some("two"), None, Some("four")];
+    let slot_ids = [4, 0, 1, 2, 3, 7, 2];
+    // the node answers three slots per multi-slot request, so the batch takes three requests,
+    // plus one individual request per slot
+    let (addr, node) = spawn_chunk_node(chunks, 3 + slot_ids.len(), true, 3);
+    let mut session = StackerDBSession::new(&addr, contract_id());
+
+    let batched = session.get_latest_chunks(&slot_ids).unwrap();
+    let individual: Vec<_> = slot_ids
+        .iter()
+        .map(|slot_id| session.get_latest_chunk(*slot_id).unwrap())
+        .collect();
+    assert_eq!(batched, individual);
+    assert_eq!(
+        batched,
+        vec![
+            Some(b"four".to_vec()),
+            Some(b"zero".to_vec()),
+            None,
+            Some(b"two".to_vec()),
+            None,
+            None,
+            Some(b"two".to_vec()),
+        ]
+    );
+    node.join().unwrap();
+}
+
+#[test]
+fn test_session_get_latest_chunks_falls_back_to_individual_fetches() {
+    let chunks = vec![Some("zero"), None, Some("two")];
+    let slot_ids = [2, 0, 1, 5];
+    // one rejected multi-slot request, then one request per slot for each of the two calls
+    let (addr, node) = spawn_chunk_node(chunks, 1 + slot_ids.len() * 2, false, 0);
+    let mut session = StackerDBSession::new(&addr, contract_id());
+
+    let expected = vec![Some(b"two".to_vec()), Some(b"zero".to_vec()), None, None];
+    assert_eq!(session.get_latest_chunks(&slot_ids).unwrap(), expected);
+    // the session remembers that the node has no multi-slot endpoint
+    assert_eq!(session.get_latest_chunks(&slot_ids).unwrap(), expected);
+    node.join().unwrap();
+}
@@ -55,7 +55,8 @@ use tiny_http::{
 };
 
 use crate::http::{decode_http_body, decode_http_request};
-use crate::{BlockProposal, EventError};
+use crate::protocol::{read_extension_block, write_extension_block};
+use crate::{BlockProposal, EventError, TenureView};
 
 define_u8_enum!(
 /// Enum representing the stackerdb message identifier: this is
@@ -195,7 +196,15 @@ RejectCodeTypePrefix {
     /// The block was rejected due to validation issues
     ValidationFailed = 0,
     /// The block was rejected due to connectivity issues with the signer
-    ConnectivityIssues = 1
+    ConnectivityIssues = 1,
+    /// The block was rejected in a prior round
+    RejectedInPriorRound = 2,
+    /// The block was rejected due to no sortition view
+    NoSortitionView = 3,
+    /// The block was rejected due to a mismatch with expected sortition view
+    SortitionViewMismatch = 4,
+    /// The block was rejected due to a testing directive
+    TestingDirective = 5
 });
 
 impl TryFrom<u8> for RejectCodeTypePrefix {
@@ -207,22 +216,74 @@ impl TryFrom<u8> for RejectCodeTypePrefix {
     }
 }
 
-impl From<&RejectCode> for RejectCodeTypePrefix {
-    fn from(reject_code: &RejectCode) -> Self {
-        match reject_code {
-            RejectCode::ValidationFailed(_) => RejectCodeTypePrefix::ValidationFailed,
-            RejectCode::ConnectivityIssues => RejectCodeTypePrefix::ConnectivityIssues,
-        }
-    }
-}
-
-/// This enum is used to supply a `reason_code` for block rejections
+/// This enum is used to supply a `reason_code` for block rejections.
+///
+/// Only `ValidationFailed` carries data after its type prefix. New codes must not carry any,
+/// so that readers which predate them can decode them as `Unknown`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum RejectCode {
     /// RPC endpoint Validation failed
     ValidationFailed(ValidateRejectCode),
     /// The block was rejected due to connectivity issues with the signer
     ConnectivityIssues,
+    /// The block was rejected in a prior round
+    RejectedInPriorRound,
+    /// The block was rejected due to no sortition view
+    NoSortitionView,
+    /// The block was rejected due to a mismatch with expected sortition view
+    SortitionViewMismatch,
+    /// The block was rejected due to a testing directive
+    TestingDirective,
+    /// A reject code this version does not know about, holding its raw type prefix
+    Unknown(UnknownRejectCode),
+}
+
+/// The raw type prefix of a reject code this version does not know about.
+/// It can only hold a prefix that is not a `RejectCodeTypePrefix`, so an unknown code always
+/// serializes to bytes that are decoded back as unknown.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub struct UnknownRejectCode(u8);
+
+impl UnknownRejectCode {
+    /// The raw type prefix
+    pub fn type_prefix_byte(&self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<u8> for UnknownRejectCode {
+    type Error = CodecError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if RejectCodeTypePrefix::from_u8(value).is_some() {
+            return Err(CodecError::DeserializeError(format!(
+                "Reject code type prefix {value} is known"
+            )));
+        }
+        Ok(Self(value))
+    }
+}
+
+impl From<UnknownRejectCode> for u8 {
+    fn from(code: UnknownRejectCode) -> Self {
+        code.0
+    }
+}
+
+impl RejectCode {
+    /// The type prefix byte this code is serialized with
+    fn type_prefix_byte(&self) -> u8 {
+        let prefix = match self {
+            RejectCode::ValidationFailed(_) => RejectCodeTypePrefix::ValidationFailed,
+            RejectCode::ConnectivityIssues => RejectCodeTypePrefix::ConnectivityIssues,
+            RejectCode::RejectedInPriorRound => RejectCodeTypePrefix::RejectedInPriorRound,
+            RejectCode::NoSortitionView => RejectCodeTypePrefix::NoSortitionView,
+            RejectCode::SortitionViewMismatch => RejectCodeTypePrefix::SortitionViewMismatch,
+            RejectCode::TestingDirective => RejectCodeTypePrefix::TestingDirective,
+            RejectCode::Unknown(code) => return code.type_prefix_byte(),
+        };
+        prefix as u8
+    }
 }
 
 define_u8_enum!(
@@ -338,6 +399,9 @@ pub struct BlockRejection {
     pub reason_code: RejectCode,
     /// The signer signature hash of the block that was rejected
     pub signer_signature_hash: Sha512Trunc256Sum,
+    /// The signer's view of the block's tenure, echoed back when the proposal carried the
+    /// miner's. Encoded in an extension block, so older readers ignore it.
+    pub signer_view: Option<TenureView>,
 }
 
 impl BlockRejection {
@@ -347,6 +411,7 @@ impl BlockRejection {
             reason: reason_code.to_string(),
             reason_code,
             signer_signature_hash,
+            signer_view: None,
         }
     }
 }
@@ -356,6 +421,7 @@ impl StacksMessageCodec for BlockRejection {
         write_next(fd, &self.reason.as_bytes().to_vec())?;
         write_next(fd, &self.reason_code)?;
         write_next(fd, &self.signer_signature_hash)?;
+        write_extension_block(fd, self.signer_view.as_ref())?;
         Ok(())
     }
 
@@ -366,10 +432,12 @@ impl StacksMessageCodec for BlockRejection {
         })?;
         let reason_code = read_next::<RejectCode, _>(fd)?;
         let signer_signature_hash = read_next::<Sha512Trunc256Sum, _>(fd)?;
+        let signer_view = read_extension_block(fd)?;
         Ok(Self {
             reason,
             reason_code,
             signer_signature_hash,
+            signer_view,
         })
     }
 }
@@ -380,17 +448,23 @@ impl From<BlockValidateReject> for BlockRejection {
             reason: reject.reason,
             reason_code: RejectCode::ValidationFailed(reject.reason_code),
             signer_signature_hash: reject.signer_signature_hash,
+            signer_view: None,
         }
     }
 }
 
 impl StacksMessageCodec for RejectCode {
     fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
-        write_next(fd, &(RejectCodeTypePrefix::from(self) as u8))?;
+        write_next(fd, &self.type_prefix_byte())?;
         // Do not do a single match here as we may add other variants in the future and don't want to miss adding it
         match self {
             RejectCode::ValidationFailed(code) => write_next(fd, &(*code as u8))?,
-            RejectCode::ConnectivityIssues => {
+            RejectCode::ConnectivityIssues
+            | RejectCode::RejectedInPriorRound
+            | RejectCode::NoSortitionView
+            | RejectCode::SortitionViewMismatch
+            | RejectCode::TestingDirective
+            | RejectCode::Unknown(_) => {
                 // No additional data to serialize / deserialize
             }
         };
@@ -399,7 +473,9 @@ impl StacksMessageCodec for RejectCode {
 
     fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
         let type_prefix_byte = read_next::<u8, _>(fd)?;
-        let type_prefix = RejectCodeTypePrefix::try_from(type_prefix_byte)?;
+        let Some(type_prefix) = RejectCodeTypePrefix::from_u8(type_prefix_byte) else {
+            return Ok(RejectCode::Unknown(UnknownRejectCode(type_prefix_byte)));
+        };
         let code = match type_prefix {
             RejectCodeTypePrefix::ValidationFailed => RejectCode::ValidationFailed(
                 ValidateRejectCode::try_from(read_next::<u8, _>(fd)?).map_err(|e| {
@@ -410,6 +486,10 @@ impl StacksMessageCodec for RejectCode {
                 })?,
             ),
             RejectCodeTypePrefix::ConnectivityIssues => RejectCode::ConnectivityIssues,
+            RejectCodeTypePrefix::RejectedInPriorRound => RejectCode::RejectedInPriorRound,
+            RejectCodeTypePrefix::NoSortitionView => RejectCode::NoSortitionView,
+            RejectCodeTypePrefix::SortitionViewMismatch => RejectCode::SortitionViewMismatch,
+            RejectCodeTypePrefix::TestingDirective => RejectCode::TestingDirective,
         };
         Ok(code)
     }
@@ -424,6 +504,23 @@ impl std::fmt::Display for RejectCode {
                 f,
                 "The block was rejected due to connectivity issues with the signer."
             ),
+            RejectCode::RejectedInPriorRound => write!(
+                f,
+                "The block was proposed before and rejected by the signer."
+            ),
+            RejectCode::NoSortitionView => {
+                write!(f, "The block was rejected due to no sortition view.")
+            }
+            RejectCode::SortitionViewMismatch => write!(
+                f,
+                "The block was rejected due to a mismatch with expected sortition view."
+            ),
+            RejectCode::TestingDirective => {
+                write!(f, "The block was rejected due to a testing directive.")
+            }
+            RejectCode::Unknown(code) => {
+                write!(f, "Unknown reject code: {}", code.type_prefix_byte())
+            }
         }
     }
 }
@@ -484,11 +581,56 @@ mod test {
             .expect("Failed to deserialize RejectCode");
         assert_eq!(code, deserialized_code);
 
-        let code = RejectCode::ConnectivityIssues;
-        let serialized_code = code.serialize_to_vec();
-        let deserialized_code = read_next::<RejectCode, _>(&mut &serialized_code[..])
-            .expect("Failed to deserialize RejectCode");
-        assert_eq!(code, deserialized_code);
+        for code in [
+            RejectCode::ConnectivityIssues,
+            RejectCode::RejectedInPriorRound,
+            RejectCode::NoSortitionView,
+            RejectCode::SortitionViewMismatch,
+            RejectCode::TestingDirective,
+        ] {
+            let serialized_code = code.serialize_to_vec();
+            assert_eq!(serialized_code.len(), 1);
+            let deserialized_code = read_next::<RejectCode, _>(&mut &serialized_code[..])
+                .expect("Failed to deserialize RejectCode");
+            assert_eq!(code, deserialized_code);
+        }
+    }
+
+    #[test]
+    fn serde_unknown_reject_code() {
+        // a rejection written by a newer signer, with a reject code this version doesn't know
+        let hash = Sha512Trunc256Sum([3u8; 32]);
+        let mut bytes = vec![];
+        write_next(&mut bytes, &b"a reason from the future".to_vec()).unwrap();
+        write_next(&mut bytes, &0xf0u8).unwrap();
+        write_next(&mut bytes, &hash).unwrap();
+
+        let rejection = read_next::<BlockRejection, _>(&mut &bytes[..])
+            .expect("Failed to deserialize BlockRejection with unknown code");
+        assert_eq!(
+            rejection.reason_code,
+            RejectCode::Unknown(UnknownRejectCode::try_from(0xf0).unwrap())
+        );
+        assert_eq!(rejection.reason, "a reason from the future");
+        assert_eq!(rejection.signer_signature_hash, hash);
+        // relaying it preserves the original bytes
+        assert_eq!(rejection.serialize_to_vec(), bytes);
+    }
+
+    #[test]
+    fn unknown_reject_code_rejects_known_prefixes() {
+        for prefix in RejectCodeTypePrefix::ALL {
+            assert!(UnknownRejectCode::try_from(*prefix as u8).is_err());
+            assert!(
+                serde_json::from_str::<UnknownRejectCode>(&(*prefix as u8).to_string()).is_err()
+            );
+        }
+        let code = UnknownRejectCode::try_from(0xf0).unwrap();
+        assert_eq!(serde_json::to_string(&code).unwrap(), "240");
+        assert_eq!(
+            serde_json::from_str::<UnknownRejectCode>("240").unwrap(),
+            code
+        );
     }
 
     #[test]
@@ -561,6 +703,9 @@ mod test {
             block,
             burn_height: thread_rng().next_u64(),
             reward_cycle: thread_rng().next_u64(),
+            miner_view: None,
+            tenure_extend: None,
+            attempt: None,
         };
         let signer_message = SignerMessage::BlockProposal(block_proposal);
         let serialized_signer_message = signer_message.serialize_to_vec();
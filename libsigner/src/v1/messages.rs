@@ -42,12 +42,14 @@ use clarity::util::retry::BoundReader;
 use clarity::vm::types::serialization::SerializationError;
 use clarity::vm::types::QualifiedContractIdentifier;
 use hashbrown::{HashMap, HashSet};
+use libstackerdb::STACKERDB_MAX_CHUNK_SIZE;
 use serde::{Deserialize, Serialize};
 use stacks_common::codec::{
     read_next, read_next_at_most, read_next_exact, write_next, Error as CodecError,
     StacksMessageCodec,
 };
 use stacks_common::consts::SIGNER_SLOTS_PER_USER;
+use stacks_common::types::chainstate::ConsensusHash;
 use stacks_common::util::hash::Sha512Trunc256Sum;
 use tiny_http::{
     Method as HttpMethod, Request as HttpRequest, Response as HttpResponse, Server as HttpServer,
@@ -64,7 +66,8 @@ use wsts::schnorr::ID;
 use wsts::state_machine::{signer, SignError};
 
 use crate::http::{decode_http_body, decode_http_request};
-use crate::EventError;
+use crate::protocol::{read_extension_block, write_extension_block};
+use crate::{EventError, TenureView};
 
 define_u8_enum!(
 /// Enum representing the stackerdb message identifier: this is
@@ -97,7 +100,9 @@ MessageSlotID {
     /// DKG Results
     DkgResults = 12,
     /// Persisted encrypted signer state containing DKG shares
-    EncryptedSignerState = 13
+    EncryptedSignerState = 13,
+    /// The protocol versions the signer speaks. See `crate::protocol::ProtocolCapabilities`.
+    ProtocolCapabilities = 14
 });
 
 define_u8_enum!(
@@ -138,6 +143,24 @@ impl Display for MessageSlotID {
     }
 }
 
+define_u8_enum!(
+/// Enum representing the messages a miner writes to its slots in the `.miners` contract.
+///  Each kind of message gets a slot of its own.
+MinerSlotID {
+    /// Block proposals and the WSTS packets of the signing round
+    BlockProposal = 0
+});
+
+impl MinerSlotID {
+    /// Return the offset of this message's slot from the first of the miner's slots.
+    /// Each variant is mapped explicitly, so that adding one forces a decision about its slot.
+    pub fn slot_offset(&self) -> u32 {
+        match self {
+            Self::BlockProposal => 0,
+        }
+    }
+}
+
 impl TryFrom<u8> for SignerMessageTypePrefix {
     type Error = CodecError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
@@ -147,6 +170,36 @@ impl TryFrom<u8> for SignerMessageTypePrefix {
     }
 }
 
+/// The longest a serialized block response can be. A rejection that names every signer of a
+/// reward set as malicious, or lists the few vote transactions a signer expected, fits well
+/// within it.
+pub const MAX_BLOCK_RESPONSE_LEN: usize = 128 * 1024;
+
+impl SignerMessageTypePrefix {
+    /// The longest a serialized message of this type has any reason to be. Messages that
+    /// carry blocks, transactions or per-key WSTS data can fill a whole chunk.
+    pub fn max_message_len(&self) -> usize {
+        match self {
+            SignerMessageTypePrefix::BlockResponse => MAX_BLOCK_RESPONSE_LEN,
+            SignerMessageTypePrefix::Packet
+            | SignerMessageTypePrefix::Transactions
+            | SignerMessageTypePrefix::DkgResults
+            | SignerMessageTypePrefix::EncryptedSignerState => {
+                usize::try_from(STACKERDB_MAX_CHUNK_SIZE).expect("FATAL: u32 overflows usize")
+            }
+        }
+    }
+}
+
+/// If the serialized signer message in `payload` is longer than messages of its type can be,
+/// its type and the most it may take. Only the type prefix is read, so this is cheap enough
+/// to check before deserializing `payload`.
+pub fn oversized_message(payload: &[u8]) -> Option<(SignerMessageTypePrefix, usize)> {
+    let prefix = SignerMessageTypePrefix::from_u8(*payload.first()?)?;
+    let max_len = prefix.max_message_len();
+    (payload.len() > max_len).then_some((prefix, max_len))
+}
+
 impl From<&SignerMessage> for SignerMessageTypePrefix {
     #[cfg_attr(test, mutants::skip)]
     fn from(message: &SignerMessage) -> Self {
@@ -227,7 +280,9 @@ RejectCodeTypePrefix {
     /// Nonce timeout
     NonceTimeout = 5,
     /// Aggregator error
-    AggregatorError = 6
+    AggregatorError = 6,
+    /// Rejected in prior round
+    RejectedInPriorRound = 7
 });
 
 impl TryFrom<u8> for RejectCodeTypePrefix {
@@ -249,6 +304,7 @@ impl From<&RejectCode> for RejectCodeTypePrefix {
             RejectCode::ConnectivityIssues => RejectCodeTypePrefix::ConnectivityIssues,
             RejectCode::NonceTimeout(_) => RejectCodeTypePrefix::NonceTimeout,
             RejectCode::AggregatorError(_) => RejectCodeTypePrefix::AggregatorError,
+            RejectCode::RejectedInPriorRound => RejectCodeTypePrefix::RejectedInPriorRound,
         }
     }
 }
@@ -415,11 +471,8 @@ impl StacksMessageCodec for SignerMessage {
             SignerMessageTypePrefix::DkgResults => {
                 let aggregate_key = Point::inner_consensus_deserialize(fd)?;
                 let party_polynomial_len = u32::consensus_deserialize(fd)?;
-                let mut party_polynomials = Vec::with_capacity(
-                    party_polynomial_len
-                        .try_into()
-                        .expect("FATAL: u32 could not fit in usize"),
-                );
+                // the length is untrusted, so only allocate as polynomials are actually read
+                let mut party_polynomials = Vec::new();
                 for _ in 0..party_polynomial_len {
                     let party_id = u32::consensus_deserialize(fd)?;
                     let polynomial = PolyCommitment::inner_consensus_deserialize(fd)?;
@@ -492,11 +545,8 @@ impl StacksMessageCodecExtensions for PolyCommitment {
     fn inner_consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
         let id = ID::inner_consensus_deserialize(fd)?;
         let commit_len = u32::consensus_deserialize(fd)?;
-        let mut poly = Vec::with_capacity(
-            commit_len
-                .try_into()
-                .expect("FATAL: u32 could not fit in usize"),
-        );
+        // the length is untrusted, so only allocate as points are actually read
+        let mut poly = Vec::new();
         for _ in 0..commit_len {
             poly.push(Point::inner_consensus_deserialize(fd)?);
         }
@@ -1238,6 +1288,40 @@ pub struct BlockRejection {
     pub reason_code: RejectCode,
     /// The signer signature hash of the block that was rejected
     pub signer_signature_hash: Sha512Trunc256Sum,
+    /// The signer's view of the block's tenure, echoed back when the proposal carried the
+    /// miner's. Encoded in an extension block, so older readers ignore it.
+    pub signer_view: Option<TenureView>,
+    /// Consensus hash of the sortition that elected the rejected block's tenure, so that the
+    /// miner can tell which election a late rejection belongs to. Encoded in a second
+    /// extension block after `signer_view`'s, so it is only sent along with `signer_view`.
+    #[serde(default)]
+    pub election_consensus_hash: Option<ConsensusHash>,
+    /// The miner's signing attempt for the rejected proposal, echoed back when the proposal
+    /// carried it, so that the miner can ignore rejections of an earlier attempt. Encoded at
+    /// the end of the election's extension block, so it is only sent along with the election.
+    #[serde(default)]
+    pub attempt: Option<u64>,
+}
+
+/// The contents of a block rejection's election extension block: the election's consensus
+/// hash, followed by the fields added to the rejection after it
+struct ElectionExtension {
+    election_consensus_hash: ConsensusHash,
+    attempt: Option<u64>,
+}
+
+impl StacksMessageCodec for ElectionExtension {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), CodecError> {
+        write_next(fd, &self.election_consensus_hash)?;
+        write_extension_block(fd, self.attempt.as_ref())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<Self, CodecError> {
+        Ok(Self {
+            election_consensus_hash: read_next(fd)?,
+            attempt: read_extension_block(fd)?,
+        })
+    }
 }
 
 impl BlockRejection {
@@ -1247,6 +1331,9 @@ impl BlockRejection {
             reason: reason_code.to_string(),
             reason_code,
             signer_signature_hash,
+            signer_view: None,
+            election_consensus_hash: None,
+            attempt: None,
         }
     }
 }
@@ -1256,6 +1343,16 @@ impl StacksMessageCodec for BlockRejection {
         write_next(fd, &self.reason.as_bytes().to_vec())?;
         write_next(fd, &self.reason_code)?;
         write_next(fd, &self.signer_signature_hash)?;
+        write_extension_block(fd, self.signer_view.as_ref())?;
+        if self.signer_view.is_some() {
+            let election =
+                self.election_consensus_hash
+                    .map(|election_consensus_hash| ElectionExtension {
+                        election_consensus_hash,
+                        attempt: self.attempt,
+                    });
+            write_extension_block(fd, election.as_ref())?;
+        }
         Ok(())
     }
 
@@ -1266,10 +1363,19 @@ impl StacksMessageCodec for BlockRejection {
         })?;
         let reason_code = read_next::<RejectCode, _>(fd)?;
         let signer_signature_hash = read_next::<Sha512Trunc256Sum, _>(fd)?;
+        let signer_view = read_extension_block(fd)?;
+        let election: Option<ElectionExtension> = if signer_view.is_some() {
+            read_extension_block(fd)?
+        } else {
+            None
+        };
         Ok(Self {
             reason,
             reason_code,
             signer_signature_hash,
+            signer_view,
+            election_consensus_hash: election.as_ref().map(|e| e.election_consensus_hash),
+            attempt: election.and_then(|e| e.attempt),
         })
     }
 }
@@ -1280,6 +1386,9 @@ impl From<BlockValidateReject> for BlockRejection {
             reason: reject.reason,
             reason_code: RejectCode::ValidationFailed(reject.reason_code),
             signer_signature_hash: reject.signer_signature_hash,
+            signer_view: None,
+            election_consensus_hash: None,
+            attempt: None,
         }
     }
 }
@@ -1301,6 +1410,8 @@ pub enum RejectCode {
     MissingTransactions(Vec<StacksTransaction>),
     /// The block was rejected due to connectivity issues with the signer
     ConnectivityIssues,
+    /// The proposal was superseded by the miner's proposal of a later signing attempt
+    RejectedInPriorRound,
 }
 
 impl From<&SignError> for RejectCode {
@@ -1330,6 +1441,7 @@ impl StacksMessageCodec for RejectCode {
             }
             RejectCode::AggregatorError(reason) => write_next(fd, &reason.as_bytes().to_vec())?,
             RejectCode::ConnectivityIssues => write_next(fd, &4u8)?,
+            RejectCode::RejectedInPriorRound => {}
         };
         Ok(())
     }
@@ -1358,7 +1470,13 @@ impl StacksMessageCodec for RejectCode {
             RejectCodeTypePrefix::NonceTimeout => {
                 RejectCode::NonceTimeout(read_next::<Vec<u32>, _>(fd)?)
             }
-            RejectCodeTypePrefix::ConnectivityIssues => RejectCode::ConnectivityIssues,
+            RejectCodeTypePrefix::ConnectivityIssues => {
+                // the writer repeats the type prefix; consume it so that any extension blocks
+                // after the code are read from the right offset
+                read_next::<u8, _>(fd)?;
+                RejectCode::ConnectivityIssues
+            }
+            RejectCodeTypePrefix::RejectedInPriorRound => RejectCode::RejectedInPriorRound,
             RejectCodeTypePrefix::AggregatorError => {
                 let reason_bytes = read_next::<Vec<u8>, _>(fd)?;
                 let reason = String::from_utf8(reason_bytes).map_err(|e| {
@@ -1405,6 +1523,10 @@ impl std::fmt::Display for RejectCode {
                 "An internal error occurred in the signer when aggregating the signaure: {:?}",
                 reason
             ),
+            RejectCode::RejectedInPriorRound => write!(
+                f,
+                "The proposal was superseded by a proposal of a later signing attempt."
+            ),
         }
     }
 }
@@ -1443,14 +1565,13 @@ mod test {
     use rand::Rng;
     use rand_core::OsRng;
     use stacks_common::consts::CHAIN_ID_TESTNET;
-    use stacks_common::types::chainstate::StacksPrivateKey;
+    use stacks_common::types::chainstate::{ConsensusHash, StacksPrivateKey};
     use wsts::common::Signature;
 
     use super::{StacksMessageCodecExtensions, *};
+    use crate::protocol::PROTOCOL_CAPABILITIES_MESSAGE_ID;
 
     #[test]
-    #[should_panic]
-    // V1 signer slots do not have enough slots in Epoch 2.5. Something will need to be updated!
     fn signer_slots_count_is_sane() {
         let slot_identifiers_len = MessageSlotID::ALL.len();
         assert!(
@@ -1461,6 +1582,82 @@ mod test {
         );
     }
 
+    #[test]
+    fn capabilities_have_their_own_slot() {
+        assert_eq!(
+            MessageSlotID::ProtocolCapabilities.to_u32(),
+            PROTOCOL_CAPABILITIES_MESSAGE_ID
+        );
+        assert!(PROTOCOL_CAPABILITIES_MESSAGE_ID < SIGNER_SLOTS_PER_USER);
+        assert!(MessageSlotID::ALL
+            .iter()
+            .filter(|msg_id| **msg_id != MessageSlotID::ProtocolCapabilities)
+            .all(|msg_id| msg_id.to_u32() != PROTOCOL_CAPABILITIES_MESSAGE_ID));
+    }
+
+    #[test]
+    fn stacker_db_contracts_are_the_signers_boot_contracts() {
+        for (mainnet, boot_addr) in [
+            (true, "SP000000000000000000002Q6VF78"),
+            (false, "ST000000000000000000002AMW42H"),
+        ] {
+            for reward_cycle in [6, 7] {
+                for msg_id in MessageSlotID::ALL {
+                    let expected = QualifiedContractIdentifier::parse(&format!(
+                        "{boot_addr}.signers-{}-{}",
+                        reward_cycle % 2,
+                        msg_id.to_u8()
+                    ))
+                    .unwrap();
+                    assert_eq!(msg_id.stacker_db_contract(mainnet, reward_cycle), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn only_oversized_messages_of_their_type_are_flagged() {
+        let sighash = Sha512Trunc256Sum([0x01; 32]);
+        // rejections naming thousands of signers are still well within the ceiling
+        let mut rejection = BlockRejection::new(
+            sighash.clone(),
+            RejectCode::InsufficientSigners((0..4000).collect()),
+        );
+        rejection.reason = "x".repeat(1024);
+        let response = SignerMessage::BlockResponse(BlockResponse::Rejected(rejection));
+        assert_eq!(oversized_message(&response.serialize_to_vec()), None);
+
+        let accepted = SignerMessage::BlockResponse(BlockResponse::accepted(
+            sighash,
+            Signature {
+                R: Point::new(),
+                z: Scalar::new(),
+            },
+        ));
+        let mut padded = accepted.serialize_to_vec();
+        padded.resize(MAX_BLOCK_RESPONSE_LEN, 0);
+        assert_eq!(oversized_message(&padded), None);
+        padded.push(0);
+        assert_eq!(
+            oversized_message(&padded),
+            Some((
+                SignerMessageTypePrefix::BlockResponse,
+                MAX_BLOCK_RESPONSE_LEN
+            ))
+        );
+
+        // transactions can fill a chunk
+        let mut transactions = SignerMessage::Transactions(vec![]).serialize_to_vec();
+        transactions.resize(MAX_BLOCK_RESPONSE_LEN * 2, 0);
+        assert_eq!(oversized_message(&transactions), None);
+        // and payloads that are not signer messages are left to the decoder
+        assert_eq!(oversized_message(&[]), None);
+        assert_eq!(
+            oversized_message(&vec![0xff; MAX_BLOCK_RESPONSE_LEN * 2]),
+            None
+        );
+    }
+
     #[test]
     fn serde_reject_code() {
         let code = RejectCode::ValidationFailed(ValidateRejectCode::InvalidBlock);
@@ -1493,6 +1690,12 @@ mod test {
             .expect("Failed to deserialize RejectCode");
         assert_eq!(code, deserialized_code);
 
+        let code = RejectCode::RejectedInPriorRound;
+        let serialized_code = code.serialize_to_vec();
+        let deserialized_code = read_next::<RejectCode, _>(&mut &serialized_code[..])
+            .expect("Failed to deserialize RejectCode");
+        assert_eq!(code, deserialized_code);
+
         let sk = StacksPrivateKey::new();
         let tx = StacksTransaction {
             version: TransactionVersion::Testnet,
@@ -1568,6 +1771,117 @@ mod test {
         let deserialized_rejection = read_next::<BlockRejection, _>(&mut &serialized_rejection[..])
             .expect("Failed to deserialize BlockRejection");
         assert_eq!(rejection, deserialized_rejection);
+
+        let legacy_rejection = BlockRejection::new(
+            Sha512Trunc256Sum([3u8; 32]),
+            RejectCode::ValidationFailed(ValidateRejectCode::InvalidBlock),
+        );
+        let mut rejection = legacy_rejection.clone();
+        rejection.signer_view = Some(TenureView {
+            burn_view_consensus_hash: ConsensusHash([4u8; 20]),
+            parent_tenure_id: None,
+            tenure_change_cause: None,
+        });
+        let serialized_rejection = rejection.serialize_to_vec();
+        let deserialized_rejection = read_next::<BlockRejection, _>(&mut &serialized_rejection[..])
+            .expect("Failed to deserialize BlockRejection");
+        assert_eq!(rejection, deserialized_rejection);
+        // the signer's view is appended after the legacy encoding
+        let serialized_legacy = legacy_rejection.serialize_to_vec();
+        assert!(serialized_rejection.starts_with(&serialized_legacy));
+    }
+
+    #[test]
+    fn serde_block_rejection_with_election() {
+        let mut viewed_rejection = BlockRejection::new(
+            Sha512Trunc256Sum([3u8; 32]),
+            RejectCode::ValidationFailed(ValidateRejectCode::InvalidBlock),
+        );
+        viewed_rejection.signer_view = Some(TenureView {
+            burn_view_consensus_hash: ConsensusHash([4u8; 20]),
+            parent_tenure_id: None,
+            tenure_change_cause: None,
+        });
+        let mut rejection = viewed_rejection.clone();
+        rejection.election_consensus_hash = Some(ConsensusHash([5u8; 20]));
+        let serialized_rejection = rejection.serialize_to_vec();
+        let deserialized_rejection = read_next::<BlockRejection, _>(&mut &serialized_rejection[..])
+            .expect("Failed to deserialize BlockRejection");
+        assert_eq!(rejection, deserialized_rejection);
+
+        // the election is appended after the signer's view, where readers that only know of
+        // the view stop reading
+        let serialized_viewed = viewed_rejection.serialize_to_vec();
+        assert!(serialized_rejection.starts_with(&serialized_viewed));
+        let deserialized_viewed = read_next::<BlockRejection, _>(&mut &serialized_viewed[..])
+            .expect("Failed to deserialize BlockRejection");
+        assert_eq!(deserialized_viewed.election_consensus_hash, None);
+
+        // without the signer's view there is nowhere to put the election
+        let mut legacy_rejection = BlockRejection::new(
+            Sha512Trunc256Sum([3u8; 32]),
+            RejectCode::ValidationFailed(ValidateRejectCode::InvalidBlock),
+        );
+        let serialized_legacy = legacy_rejection.serialize_to_vec();
+        legacy_rejection.election_consensus_hash = Some(ConsensusHash([5u8; 20]));
+        assert_eq!(legacy_rejection.serialize_to_vec(), serialized_legacy);
+    }
+
+    #[test]
+    fn serde_connectivity_rejection_with_election() {
+        let mut rejection =
+            BlockRejection::new(Sha512Trunc256Sum([3u8; 32]), RejectCode::ConnectivityIssues);
+        let serialized_rejection = rejection.serialize_to_vec();
+        let deserialized_rejection = read_next::<BlockRejection, _>(&mut &serialized_rejection[..])
+            .expect("Failed to deserialize BlockRejection");
+        assert_eq!(rejection, deserialized_rejection);
+
+        // the extension blocks start right after the reject code's data
+        rejection.signer_view = Some(TenureView {
+            burn_view_consensus_hash: ConsensusHash([4u8; 20]),
+            parent_tenure_id: None,
+            tenure_change_cause: None,
+        });
+        rejection.election_consensus_hash = Some(ConsensusHash([5u8; 20]));
+        let serialized_rejection = rejection.serialize_to_vec();
+        let deserialized_rejection = read_next::<BlockRejection, _>(&mut &serialized_rejection[..])
+            .expect("Failed to deserialize BlockRejection");
+        assert_eq!(rejection, deserialized_rejection);
+    }
+
+    #[test]
+    fn serde_block_rejection_with_attempt() {
+        let mut elected_rejection = BlockRejection::new(
+            Sha512Trunc256Sum([3u8; 32]),
+            RejectCode::RejectedInPriorRound,
+        );
+        elected_rejection.signer_view = Some(TenureView {
+            burn_view_consensus_hash: ConsensusHash([4u8; 20]),
+            parent_tenure_id: None,
+            tenure_change_cause: None,
+        });
+        elected_rejection.election_consensus_hash = Some(ConsensusHash([5u8; 20]));
+        let mut rejection = elected_rejection.clone();
+        rejection.attempt = Some(7);
+        let serialized_rejection = rejection.serialize_to_vec();
+        let deserialized_rejection = read_next::<BlockRejection, _>(&mut &serialized_rejection[..])
+            .expect("Failed to deserialize BlockRejection");
+        assert_eq!(rejection, deserialized_rejection);
+
+        // the attempt is appended inside the election's extension block, and a rejection
+        // from a signer that does not send it still decodes
+        let serialized_elected = elected_rejection.serialize_to_vec();
+        assert!(serialized_rejection.len() > serialized_elected.len());
+        let deserialized_elected = read_next::<BlockRejection, _>(&mut &serialized_elected[..])
+            .expect("Failed to deserialize BlockRejection");
+        assert_eq!(deserialized_elected.attempt, None);
+
+        // without the election there is nowhere to put the attempt
+        let mut unelected_rejection = elected_rejection.clone();
+        unelected_rejection.election_consensus_hash = None;
+        let serialized_unelected = unelected_rejection.serialize_to_vec();
+        unelected_rejection.attempt = Some(7);
+        assert_eq!(unelected_rejection.serialize_to_vec(), serialized_unelected);
     }
 
     #[test]
@@ -1851,4 +2165,31 @@ mod test {
                 .expect("Failed to deserialize SignerMessage");
         assert_eq!(signer_message, deserialized_signer_message);
     }
+
+    #[test]
+    fn untrusted_dkg_results_lengths_are_not_preallocated() {
+        let rng = &mut OsRng;
+        let point = Point::from(Scalar::random(rng));
+
+        // a DkgResults message that claims u32::MAX party polynomials but carries none
+        let mut bytes = vec![SignerMessageTypePrefix::DkgResults as u8];
+        point.inner_consensus_serialize(&mut bytes).unwrap();
+        write_next(&mut bytes, &u32::MAX).unwrap();
+        assert!(read_next::<SignerMessage, _>(&mut &bytes[..]).is_err());
+
+        // one party polynomial that claims u32::MAX commitments but carries none
+        let mut bytes = vec![SignerMessageTypePrefix::DkgResults as u8];
+        point.inner_consensus_serialize(&mut bytes).unwrap();
+        write_next(&mut bytes, &1u32).unwrap();
+        write_next(&mut bytes, &0u32).unwrap();
+        Scalar::random(rng)
+            .inner_consensus_serialize(&mut bytes)
+            .unwrap();
+        point.inner_consensus_serialize(&mut bytes).unwrap();
+        Scalar::random(rng)
+            .inner_consensus_serialize(&mut bytes)
+            .unwrap();
+        write_next(&mut bytes, &u32::MAX).unwrap();
+        assert!(read_next::<SignerMessage, _>(&mut &bytes[..]).is_err());
+    }
 }
@@ -37,6 +37,8 @@ use stacks_common::util::secp256k1::MessageSignature;
 pub const STACKERDB_MAX_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
 /// CHUNK_SIZE constant for signers StackerDBs (2MB)
 pub const SIGNERS_STACKERDB_CHUNK_SIZE: usize = 2 * 1024 * 1024; // 2MB
+/// maximum number of slots whose latest chunks can be requested at once
+pub const STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST: usize = 256;
 
 #[cfg(test)]
 mod tests;
@@ -108,6 +110,10 @@ pub struct StackerDBChunkAckData {
     pub metadata: Option<SlotMetadata>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub code: Option<u32>,
+    /// If the chunk was rejected because its slot is written too often, the number of seconds
+    /// until the slot may be written again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
 }
 
 impl SlotMetadata {
@@ -281,6 +287,20 @@ pub fn stackerdb_get_chunk_path(
     }
 }
 
+/// Calculate the GET path for the latest chunks of several stacker DB slots
+pub fn stackerdb_get_latest_chunks_path(
+    contract_id: QualifiedContractIdentifier,
+    slot_ids: &[u32],
+) -> String {
+    let slot_ids: Vec<_> = slot_ids.iter().map(|slot_id| slot_id.to_string()).collect();
+    format!(
+        "/v2/stackerdb/{}/{}/latest_chunks?slot_ids={}",
+        &StacksAddress::from(contract_id.issuer),
+        &contract_id.name,
+        slot_ids.join(",")
+    )
+}
+
 /// Calculate POST path for a stacker DB chunk
 pub fn stackerdb_post_chunk_path(contract_id: QualifiedContractIdentifier) -> String {
     format!(
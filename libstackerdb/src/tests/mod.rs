@@ -100,6 +100,11 @@ fn test_stackerdb_paths() {
         "/v2/stackerdb/SP1Y0NECNCJ6YDVM7GQ594FF065NN3NT72FASBXB8/hello-world/1".to_string()
     );
 
+    assert_eq!(
+        stackerdb_get_latest_chunks_path(contract_id.clone(), &[0, 3, 14]),
+        "/v2/stackerdb/SP1Y0NECNCJ6YDVM7GQ594FF065NN3NT72FASBXB8/hello-world/latest_chunks?slot_ids=0,3,14".to_string()
+    );
+
     assert_eq!(
         stackerdb_post_chunk_path(contract_id),
         "/v2/stackerdb/SP1Y0NECNCJ6YDVM7GQ594FF065NN3NT72FASBXB8/hello-world/chunks".to_string()
@@ -0,0 +1,48 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use stacks_common::util::hash::{hex_bytes, hex_decode_into, hex_encode_into, to_hex};
+
+fn bench_hex(c: &mut Criterion) {
+    // a compressed public key, a hash, and a typical read-only call result
+    for len in [33, 64, 1024] {
+        let bytes: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let hex = to_hex(&bytes);
+
+        let mut group = c.benchmark_group(format!("hex_{len}"));
+        group.bench_function(BenchmarkId::new("decode", "hex_bytes"), |b| {
+            b.iter(|| hex_bytes(black_box(&hex)).unwrap())
+        });
+        let mut buf = vec![0u8; len];
+        group.bench_function(BenchmarkId::new("decode", "hex_decode_into"), |b| {
+            b.iter(|| hex_decode_into(black_box(&hex), &mut buf).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("encode", "to_hex"), |b| {
+            b.iter(|| to_hex(black_box(&bytes)))
+        });
+        let mut out = String::with_capacity(len * 2);
+        group.bench_function(BenchmarkId::new("encode", "hex_encode_into"), |b| {
+            b.iter(|| {
+                out.clear();
+                hex_encode_into(black_box(&bytes), &mut out);
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_hex);
+criterion_main!(benches);
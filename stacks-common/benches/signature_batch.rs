@@ -0,0 +1,58 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use stacks_common::types::{PrivateKey, PublicKey};
+use stacks_common::util::secp256k1::{
+    MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey, SignatureBatch,
+};
+
+fn bench_signature_batch(c: &mut Criterion) {
+    let msg = [0x42u8; 32];
+    // from a handful of signers up to a large signer set
+    for num_signers in [10, 100, 400] {
+        let signatures: Vec<(Secp256k1PublicKey, MessageSignature)> = (0..num_signers)
+            .map(|_| {
+                let privk = Secp256k1PrivateKey::new();
+                (
+                    Secp256k1PublicKey::from_private(&privk),
+                    privk.sign(&msg).unwrap(),
+                )
+            })
+            .collect();
+
+        let mut group = c.benchmark_group(format!("verify_{num_signers}_signatures"));
+        group.bench_function(BenchmarkId::new("verify", "individually"), |b| {
+            b.iter(|| {
+                signatures
+                    .iter()
+                    .all(|(pubkey, sig)| pubkey.verify(black_box(&msg), sig) == Ok(true))
+            })
+        });
+        group.bench_function(BenchmarkId::new("verify", "batch"), |b| {
+            b.iter(|| {
+                let mut batch = SignatureBatch::with_capacity(signatures.len());
+                for (pubkey, sig) in signatures.iter() {
+                    batch.push(pubkey, black_box(&msg), sig);
+                }
+                batch.verify_all()
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_signature_batch);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+/*
+ * Copyright (C) 2024 Stacks Open Internet Foundation
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/*
+ * C interface to the stacks-common c32 address codec.
+ * Built when stacks-common is compiled with the `ffi` feature.
+ */
+
+#ifndef STACKS_C32_H
+#define STACKS_C32_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define STACKS_C32_ERR_NULL_POINTER -1
+#define STACKS_C32_ERR_INVALID_VERSION -2
+#define STACKS_C32_ERR_BUFFER_TOO_SMALL -3
+#define STACKS_C32_ERR_INVALID_CROCKFORD32 -4
+#define STACKS_C32_ERR_BAD_CHECKSUM -5
+#define STACKS_C32_ERR_OTHER -6
+#define STACKS_C32_ERR_PANIC -7
+
+/*
+ * Encode `data_len` bytes at `data_ptr` as a c32 address with the given version.
+ * The NUL-terminated address is written to `out_buf`, which holds `out_len` bytes.
+ * Returns the address length (excluding the NUL) on success, or a negative error code.
+ */
+int32_t stacks_c32_address(uint8_t version,
+                           const uint8_t *data_ptr,
+                           size_t data_len,
+                           char *out_buf,
+                           size_t out_len);
+
+/*
+ * Decode the NUL-terminated c32 address at `str_ptr`.
+ * The version is written to `out_version` and the payload to `out_buf`, which holds `out_len` bytes.
+ * Returns the payload length on success, or a negative error code.
+ */
+int32_t stacks_c32_address_decode(const char *str_ptr,
+                                  uint8_t *out_version,
+                                  uint8_t *out_buf,
+                                  size_t out_len);
+
+#endif /* STACKS_C32_H */
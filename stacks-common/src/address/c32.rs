@@ -297,7 +297,21 @@ fn double_sha256_checksum(data: &[u8]) -> Vec<u8> {
     tmp[0..4].to_vec()
 }
 
-fn c32_check_encode(version: u8, data: &[u8]) -> Result<String, Error> {
+/// Length of the c32check encoding of a `data_len`-byte payload, without any `S` prefix.
+/// Since c32 drops leading zero digits, payloads whose first bits are zero encode to fewer
+/// characters; every other payload of this length encodes to exactly this many.
+pub fn c32_check_encode_len(data_len: usize) -> usize {
+    // the version character, then the payload and its 4-byte checksum at 5 bits per character
+    data_len
+        .saturating_add(4)
+        .saturating_mul(8)
+        .div_ceil(5)
+        .saturating_add(1)
+}
+
+/// Encode `data` with `version` as a c32check string, without any `S` prefix. Any payload
+/// length can be encoded, e.g. 20-byte address hashes or 32-byte hashes.
+pub fn c32_check_encode(version: u8, data: &[u8]) -> Result<String, Error> {
     if version >= 32 {
         return Err(Error::InvalidVersion(version));
     }
@@ -317,7 +331,10 @@ fn c32_check_encode(version: u8, data: &[u8]) -> Result<String, Error> {
     Ok(String::from_utf8(c32_string).unwrap())
 }
 
-fn c32_check_decode(check_data_unsanitized: &str) -> Result<(u8, Vec<u8>), Error> {
+/// Decode a c32check string into its version and payload.
+/// If `strict` is set, the string must also be the canonical encoding of the decoded
+/// payload (up to ASCII case), so the `O`, `L`, and `I` aliases are rejected.
+fn c32_check_decode(check_data_unsanitized: &str, strict: bool) -> Result<(u8, Vec<u8>), Error> {
     // must be ASCII
     if !check_data_unsanitized.is_ascii() {
         return Err(Error::InvalidCrockford32);
@@ -356,15 +373,91 @@ fn c32_check_decode(check_data_unsanitized: &str) -> Result<(u8, Vec<u8>), Error
 
     let version = check_data[0];
     let data = data_bytes.to_vec();
+
+    if strict {
+        let canonical = c32_check_encode(version, &data)?;
+        if !canonical.eq_ignore_ascii_case(check_data_unsanitized) {
+            return Err(Error::NonCanonical);
+        }
+    }
     Ok((version, data))
 }
 
-pub fn c32_address_decode(c32_address_str: &str) -> Result<(u8, Vec<u8>), Error> {
+/// Decode a c32check string (without any `S` prefix) whose payload must be one of
+/// `allowed_lengths` bytes long. Only canonical encodings are accepted. Strings too long to
+/// encode any of the allowed lengths are rejected before they are decoded.
+pub fn c32_check_decode_with_lengths(
+    check_data: &str,
+    allowed_lengths: &[usize],
+) -> Result<(u8, Vec<u8>), Error> {
+    let max_len = allowed_lengths
+        .iter()
+        .map(|len| c32_check_encode_len(*len))
+        .max()
+        .unwrap_or(0);
+    if check_data.len() > max_len {
+        return Err(Error::InvalidLength(check_data.len()));
+    }
+    let (version, data) = c32_check_decode(check_data, true)?;
+    if !allowed_lengths.contains(&data.len()) {
+        return Err(Error::InvalidLength(data.len()));
+    }
+    Ok((version, data))
+}
+
+/// The letters a c32 address may start with, in either case
+pub const C32_ADDRESS_PREFIXES: &[char] = &['S'];
+
+fn c32_address_decode_inner(
+    c32_address_str: &str,
+    prefixes: &[char],
+    strict: bool,
+) -> Result<(u8, Vec<u8>), Error> {
     if !c32_address_str.is_ascii() || c32_address_str.len() <= 5 {
-        Err(Error::InvalidCrockford32)
-    } else {
-        c32_check_decode(&c32_address_str[1..])
+        return Err(Error::InvalidCrockford32);
+    }
+    let (prefix, check_data) = c32_address_str.split_at(1);
+    let prefix = char::from(prefix.as_bytes()[0]);
+    if !prefixes
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(&prefix))
+    {
+        return Err(Error::BadPrefix(prefix));
+    }
+    c32_check_decode(check_data, strict)
+}
+
+/// Decode a c32 address into its version and payload. The address must start with one of
+/// `C32_ADDRESS_PREFIXES`, or decoding fails with `Error::BadPrefix`.
+pub fn c32_address_decode(c32_address_str: &str) -> Result<(u8, Vec<u8>), Error> {
+    c32_address_decode_inner(c32_address_str, C32_ADDRESS_PREFIXES, false)
+}
+
+/// Like `c32_address_decode`, but the address may start with any of `prefixes` instead
+pub fn c32_address_decode_with_prefixes(
+    c32_address_str: &str,
+    prefixes: &[char],
+) -> Result<(u8, Vec<u8>), Error> {
+    c32_address_decode_inner(c32_address_str, prefixes, false)
+}
+
+/// Like `c32_address_decode`, but additionally rejects any string that is not the canonical
+/// encoding of the decoded address with `Error::NonCanonical`.
+pub fn c32_address_decode_strict(c32_address_str: &str) -> Result<(u8, Vec<u8>), Error> {
+    c32_address_decode_inner(c32_address_str, C32_ADDRESS_PREFIXES, true)
+}
+
+/// Like `c32_address_decode`, but additionally rejects an address whose version is not one of
+/// `expected_versions` with `Error::InvalidVersion`
+pub fn c32_address_decode_versioned(
+    expected_versions: &[u8],
+    c32_address_str: &str,
+) -> Result<(u8, Vec<u8>), Error> {
+    let (version, data) = c32_address_decode(c32_address_str)?;
+    if !expected_versions.contains(&version) {
+        return Err(Error::InvalidVersion(version));
     }
+    Ok((version, data))
 }
 
 pub fn c32_address(version: u8, data: &[u8]) -> Result<String, Error> {
@@ -380,7 +473,9 @@ mod test {
         c32_address as c32_address_old, c32_address_decode as c32_address_decode_old,
     };
     use super::*;
-    use crate::util::hash::hex_bytes;
+    use crate::types::chainstate::StacksAddress;
+    use crate::types::Address;
+    use crate::util::hash::{hex_bytes, hex_decode_into};
 
     #[test]
     fn old_c32_validation() {
@@ -469,8 +564,10 @@ mod test {
 
         for (i, h) in hex_strs.iter().enumerate() {
             for (j, v) in versions.iter().enumerate() {
-                let b = hex_bytes(h).unwrap();
-                let z = c32_address(*v, &b).unwrap();
+                let mut buf = [0u8; 20];
+                let len = hex_decode_into(h, &mut buf).unwrap();
+                let b = &buf[..len];
+                let z = c32_address(*v, b).unwrap();
 
                 assert_eq!(z, c32_addrs[j][i]);
 
@@ -530,7 +627,9 @@ mod test {
             .iter()
             .zip(c32_strs.iter())
             .map(|(hex_str, expected)| {
-                let bytes = hex_bytes(hex_str).unwrap();
+                let mut buf = [0u8; 20];
+                let len = hex_decode_into(hex_str, &mut buf).unwrap();
+                let bytes = buf[..len].to_vec();
                 let c32_encoded = c32_encode(&bytes);
                 let decoded_bytes = c32_decode(&c32_encoded).unwrap();
                 let result = (bytes, c32_encoded, decoded_bytes, expected);
@@ -567,6 +666,58 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_strict_rejects_non_canonical() {
+        let canonical = "S02J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE";
+        let expected = (
+            0,
+            hex_bytes("a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap(),
+        );
+        assert_eq!(c32_address_decode_strict(canonical).unwrap(), expected);
+        assert_eq!(
+            c32_address_decode_strict(&canonical.to_lowercase()).unwrap(),
+            expected
+        );
+
+        // the version digit and a zero digit padded with their aliases
+        let aliased = [
+            "SO2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKPVKG2CE",
+            "S02J6ZY48GVLEZ5V2V5RB9MP66SW86PYKKPVKG2CE",
+            "S02J6ZY48GVIEZ5V2V5RB9MP66SW86PYKKPVKG2CE",
+            "sO2j6zy48gvlez5v2v5rb9mp66sw86pykkpvkg2ce",
+        ];
+        for addr in aliased {
+            assert_eq!(c32_address_decode(addr).unwrap(), expected);
+            assert!(matches!(
+                c32_address_decode_strict(addr),
+                Err(Error::NonCanonical)
+            ));
+        }
+
+        // leading zero bytes are padded with aliased zero digits
+        let zeros = "SP000000000000000000002Q6VF78";
+        let padded = "SPOO0000000000000000002Q6VF78";
+        assert_eq!(
+            c32_address_decode(padded).unwrap(),
+            c32_address_decode_strict(zeros).unwrap()
+        );
+        assert!(matches!(
+            c32_address_decode_strict(padded),
+            Err(Error::NonCanonical)
+        ));
+
+        // an extra zero digit changes the payload, so it fails either way
+        let extra_zero = "SP0000000000000000000002Q6VF78";
+        assert!(matches!(
+            c32_address_decode(extra_zero),
+            Err(Error::BadChecksum(..))
+        ));
+        assert!(matches!(
+            c32_address_decode_strict(extra_zero),
+            Err(Error::BadChecksum(..))
+        ));
+    }
+
     #[test]
     fn test_ascii_only() {
         assert!(matches!(
@@ -574,4 +725,190 @@ mod test {
             Err(Error::InvalidCrockford32)
         ));
     }
+
+    #[test]
+    fn test_check_encode_32_byte_payloads() {
+        for _ in 0..1000 {
+            let version: u8 = rand::thread_rng().gen_range(0..32);
+            let data = rand::thread_rng().gen::<[u8; 32]>();
+            let encoded = c32_check_encode(version, &data).unwrap();
+            assert!(encoded.len() <= c32_check_encode_len(32));
+            assert_eq!(
+                c32_check_decode_with_lengths(&encoded, &[32]).unwrap(),
+                (version, data.to_vec())
+            );
+            assert_eq!(
+                c32_check_decode_with_lengths(&encoded, &[20, 32]).unwrap(),
+                (version, data.to_vec())
+            );
+            // the payload length must be on the caller's list
+            assert!(matches!(
+                c32_check_decode_with_lengths(&encoded, &[20]),
+                Err(Error::InvalidLength(_))
+            ));
+        }
+
+        // the payload itself is encoded the same way at any length
+        let data = [0xff; 32];
+        let encoded = c32_check_encode(22, &data).unwrap();
+        assert_eq!(encoded.len(), c32_check_encode_len(32));
+        assert_eq!(
+            &encoded[1..],
+            c32_encode(&[&data[..], &encoded_checksum(22, &data)[..]].concat())
+        );
+
+        // leading zero bytes survive the round trip
+        let mut data = [0x01; 32];
+        data[..3].copy_from_slice(&[0, 0, 0]);
+        let encoded = c32_check_encode(26, &data).unwrap();
+        assert!(encoded.starts_with("T000"));
+        assert_eq!(
+            c32_check_decode_with_lengths(&encoded, &[32]).unwrap(),
+            (26, data.to_vec())
+        );
+
+        // 32-byte payloads are still not addresses
+        let address = format!("S{}", c32_check_encode(22, &[0xff; 32]).unwrap());
+        assert_eq!(c32_address_decode_strict(&address).unwrap().1.len(), 32);
+        assert!(StacksAddress::from_string(&address).is_none());
+    }
+
+    /// The checksum `c32_check_encode` appends to `data`
+    fn encoded_checksum(version: u8, data: &[u8]) -> Vec<u8> {
+        double_sha256_checksum(&[&[version], data].concat())
+    }
+
+    #[test]
+    fn test_check_encode_len() {
+        assert_eq!(c32_check_encode_len(0), 8);
+        // the length of a c32 address, less its `S` prefix
+        assert_eq!(c32_check_encode_len(20), 40);
+        assert_eq!(c32_check_encode_len(32), 59);
+
+        for data_len in 0..64 {
+            let data = vec![0xff; data_len];
+            let encoded = c32_check_encode(31, &data).unwrap();
+            if data_len > 0 {
+                assert_eq!(encoded.len(), c32_check_encode_len(data_len));
+            } else {
+                assert!(encoded.len() <= c32_check_encode_len(data_len));
+            }
+            let zeros = vec![0; data_len];
+            let encoded = c32_check_encode(31, &zeros).unwrap();
+            assert!(encoded.len() <= c32_check_encode_len(data_len));
+        }
+    }
+
+    #[test]
+    fn test_check_decode_with_lengths_rejects_other_lengths() {
+        let address_hash = hex_bytes("a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap();
+        let encoded = c32_check_encode(22, &address_hash).unwrap();
+        assert_eq!(
+            c32_check_decode_with_lengths(&encoded, &[20]).unwrap(),
+            (22, address_hash)
+        );
+        assert!(matches!(
+            c32_check_decode_with_lengths(&encoded, &[32]),
+            Err(Error::InvalidLength(20))
+        ));
+        assert!(matches!(
+            c32_check_decode_with_lengths(&encoded, &[]),
+            Err(Error::InvalidLength(_))
+        ));
+
+        // too long for any allowed length, so not even decoded
+        let too_long = c32_check_encode(22, &[0xff; 33]).unwrap();
+        assert!(matches!(
+            c32_check_decode_with_lengths(&too_long, &[32]),
+            Err(Error::InvalidLength(len)) if len == too_long.len()
+        ));
+
+        // only canonical encodings are accepted
+        let aliased = encoded.replacen('1', "L", 1);
+        assert_ne!(aliased, encoded);
+        assert!(matches!(
+            c32_check_decode_with_lengths(&aliased, &[20]),
+            Err(Error::NonCanonical)
+        ));
+        let mut bad_checksum = encoded.into_bytes();
+        let last = bad_checksum.len() - 1;
+        bad_checksum[last] = if bad_checksum[last] == b'0' {
+            b'1'
+        } else {
+            b'0'
+        };
+        assert!(matches!(
+            c32_check_decode_with_lengths(&String::from_utf8(bad_checksum).unwrap(), &[20]),
+            Err(Error::BadChecksum(..))
+        ));
+    }
+
+    #[test]
+    fn test_address_decode_rejects_unknown_prefixes() {
+        let address_hash = hex_bytes("a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap();
+        let addr = c32_address(22, &address_hash).unwrap();
+        assert_eq!(addr, "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7");
+
+        // a wrong prefix letter is reported as such, not as a bad checksum
+        let wrong_prefix = addr.replacen('S', "X", 1);
+        assert!(matches!(
+            c32_address_decode(&wrong_prefix),
+            Err(Error::BadPrefix('X'))
+        ));
+        // so is a missing one, which would otherwise shift the version into the prefix
+        assert!(matches!(
+            c32_address_decode(&addr[1..]),
+            Err(Error::BadPrefix('P'))
+        ));
+        assert!(matches!(
+            c32_address_decode_strict(&wrong_prefix),
+            Err(Error::BadPrefix('X'))
+        ));
+
+        // the prefix is case-insensitive, like the rest of the address
+        let lowercase_prefix = addr.replacen('S', "s", 1);
+        assert_eq!(
+            c32_address_decode(&lowercase_prefix).unwrap(),
+            (22, address_hash.clone())
+        );
+        assert_eq!(
+            c32_address_decode(&addr.to_lowercase()).unwrap(),
+            (22, address_hash.clone())
+        );
+
+        // other prefixes can be allowed explicitly
+        assert_eq!(
+            c32_address_decode_with_prefixes(&wrong_prefix, &['S', 'X']).unwrap(),
+            (22, address_hash)
+        );
+        assert!(matches!(
+            c32_address_decode_with_prefixes(&addr, &['X']),
+            Err(Error::BadPrefix('S'))
+        ));
+    }
+
+    #[test]
+    fn test_address_decode_versioned() {
+        let address_hash = hex_bytes("a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap();
+        let mainnet_addr = c32_address(22, &address_hash).unwrap();
+        let testnet_addr = c32_address(26, &address_hash).unwrap();
+
+        assert_eq!(
+            c32_address_decode_versioned(&[22, 20], &mainnet_addr).unwrap(),
+            (22, address_hash.clone())
+        );
+        // the right prefix, but the wrong version
+        assert!(matches!(
+            c32_address_decode_versioned(&[22, 20], &testnet_addr),
+            Err(Error::InvalidVersion(26))
+        ));
+        assert!(matches!(
+            c32_address_decode_versioned(&[], &mainnet_addr),
+            Err(Error::InvalidVersion(22))
+        ));
+        assert!(matches!(
+            c32_address_decode_versioned(&[22], &mainnet_addr.replacen('S', "X", 1)),
+            Err(Error::BadPrefix('X'))
+        ));
+    }
 }
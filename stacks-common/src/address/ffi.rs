@@ -0,0 +1,268 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! C ABI for the c32 address codec, so that wallets written in other languages can link
+//! against the canonical implementation. The matching header lives in
+//! `stacks-common/include/stacks_c32.h` and must be kept in sync with this file.
+//!
+//! Every entry point returns a non-negative length on success or one of the negative
+//! `STACKS_C32_ERR_*` codes on failure. Panics are caught and reported as
+//! `STACKS_C32_ERR_PANIC`; they never unwind across the FFI boundary.
+
+use std::ffi::CStr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::slice;
+
+use libc::{c_char, size_t};
+
+use super::c32::{c32_address, c32_address_decode};
+use super::Error;
+
+/// A required pointer argument was NULL
+pub const STACKS_C32_ERR_NULL_POINTER: i32 = -1;
+/// The address version is not a valid c32 character (must be < 32)
+pub const STACKS_C32_ERR_INVALID_VERSION: i32 = -2;
+/// The caller-supplied output buffer cannot hold the result
+pub const STACKS_C32_ERR_BUFFER_TOO_SMALL: i32 = -3;
+/// The input string is not a well-formed c32 address
+pub const STACKS_C32_ERR_INVALID_CROCKFORD32: i32 = -4;
+/// The address checksum did not match its payload
+pub const STACKS_C32_ERR_BAD_CHECKSUM: i32 = -5;
+/// Any other codec error
+pub const STACKS_C32_ERR_OTHER: i32 = -6;
+/// The codec panicked; this is always a bug
+pub const STACKS_C32_ERR_PANIC: i32 = -7;
+
+impl Error {
+    /// Map a codec error onto its stable FFI error code
+    fn ffi_code(&self) -> i32 {
+        match self {
+            Error::InvalidVersion(_) => STACKS_C32_ERR_INVALID_VERSION,
+            Error::InvalidCrockford32 => STACKS_C32_ERR_INVALID_CROCKFORD32,
+            Error::BadChecksum(..) => STACKS_C32_ERR_BAD_CHECKSUM,
+            _ => STACKS_C32_ERR_OTHER,
+        }
+    }
+}
+
+/// Run `f`, converting a panic into `STACKS_C32_ERR_PANIC`
+fn guard<F: FnOnce() -> i32>(f: F) -> i32 {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(STACKS_C32_ERR_PANIC)
+}
+
+/// Convert a result length into the i32 return convention
+fn ffi_len(len: usize) -> i32 {
+    i32::try_from(len).unwrap_or(STACKS_C32_ERR_OTHER)
+}
+
+/// Encode `data_len` bytes at `data_ptr` as a c32 address with the given `version`.
+/// The NUL-terminated address is written into `out_buf`, which has room for `out_len` bytes.
+///
+/// Returns the length of the address (excluding the NUL) or a negative error code.
+///
+/// # Safety
+/// `data_ptr` must be valid for reads of `data_len` bytes (it may be NULL only if `data_len`
+/// is 0), and `out_buf` must be valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn stacks_c32_address(
+    version: u8,
+    data_ptr: *const u8,
+    data_len: size_t,
+    out_buf: *mut c_char,
+    out_len: size_t,
+) -> i32 {
+    guard(|| {
+        if out_buf.is_null() || (data_ptr.is_null() && data_len > 0) {
+            return STACKS_C32_ERR_NULL_POINTER;
+        }
+        let data = if data_len == 0 {
+            &[][..]
+        } else {
+            slice::from_raw_parts(data_ptr, data_len)
+        };
+        let addr = match c32_address(version, data) {
+            Ok(addr) => addr,
+            Err(e) => return e.ffi_code(),
+        };
+        // leave room for the NUL terminator
+        if addr.len() >= out_len {
+            return STACKS_C32_ERR_BUFFER_TOO_SMALL;
+        }
+        let out = slice::from_raw_parts_mut(out_buf as *mut u8, out_len);
+        out[..addr.len()].copy_from_slice(addr.as_bytes());
+        out[addr.len()] = 0;
+        ffi_len(addr.len())
+    })
+}
+
+/// Decode the NUL-terminated c32 address at `str_ptr`. The address version is written to
+/// `out_version` and the decoded payload into `out_buf`, which has room for `out_len` bytes.
+///
+/// Returns the length of the decoded payload or a negative error code.
+///
+/// # Safety
+/// `str_ptr` must point to a NUL-terminated string, `out_version` must be valid for a
+/// one-byte write, and `out_buf` must be valid for writes of `out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn stacks_c32_address_decode(
+    str_ptr: *const c_char,
+    out_version: *mut u8,
+    out_buf: *mut u8,
+    out_len: size_t,
+) -> i32 {
+    guard(|| {
+        if str_ptr.is_null() || out_version.is_null() || out_buf.is_null() {
+            return STACKS_C32_ERR_NULL_POINTER;
+        }
+        let Ok(addr) = CStr::from_ptr(str_ptr).to_str() else {
+            return STACKS_C32_ERR_INVALID_CROCKFORD32;
+        };
+        let (version, bytes) = match c32_address_decode(addr) {
+            Ok(decoded) => decoded,
+            Err(e) => return e.ffi_code(),
+        };
+        if bytes.len() > out_len {
+            return STACKS_C32_ERR_BUFFER_TOO_SMALL;
+        }
+        slice::from_raw_parts_mut(out_buf, out_len)[..bytes.len()].copy_from_slice(&bytes);
+        *out_version = version;
+        ffi_len(bytes.len())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::CString;
+
+    use super::*;
+    use crate::util::hash::hex_bytes;
+
+    type EncodeFn = unsafe extern "C" fn(u8, *const u8, size_t, *mut c_char, size_t) -> i32;
+    type DecodeFn = unsafe extern "C" fn(*const c_char, *mut u8, *mut u8, size_t) -> i32;
+
+    const ENCODE: EncodeFn = stacks_c32_address;
+    const DECODE: DecodeFn = stacks_c32_address_decode;
+
+    fn encode(version: u8, data: &[u8], out_len: usize) -> Result<String, i32> {
+        let mut out = vec![0 as c_char; out_len];
+        let rc = unsafe {
+            ENCODE(
+                version,
+                data.as_ptr(),
+                data.len(),
+                out.as_mut_ptr(),
+                out_len,
+            )
+        };
+        if rc < 0 {
+            return Err(rc);
+        }
+        let s = unsafe { CStr::from_ptr(out.as_ptr()) };
+        assert_eq!(s.to_bytes().len(), rc as usize);
+        Ok(s.to_str().unwrap().to_string())
+    }
+
+    fn decode(addr: &str, out_len: usize) -> Result<(u8, Vec<u8>), i32> {
+        let addr = CString::new(addr).unwrap();
+        let mut version = 0xffu8;
+        let mut out = vec![0u8; out_len];
+        let rc = unsafe { DECODE(addr.as_ptr(), &mut version, out.as_mut_ptr(), out_len) };
+        if rc < 0 {
+            return Err(rc);
+        }
+        out.truncate(rc as usize);
+        Ok((version, out))
+    }
+
+    #[test]
+    fn test_ffi_round_trip() {
+        let bytes = hex_bytes("a46ff88886c2ef9762d970b4d2c63678835bd39d").unwrap();
+        let addr = encode(22, &bytes, 64).unwrap();
+        assert_eq!(addr, "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7");
+        assert_eq!(decode(&addr, 20).unwrap(), (22, bytes));
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        let bytes = [0u8; 20];
+        assert_eq!(encode(32, &bytes, 64), Err(STACKS_C32_ERR_INVALID_VERSION));
+        // exact length leaves no room for the NUL terminator
+        let addr = encode(22, &bytes, 64).unwrap();
+        assert_eq!(
+            encode(22, &bytes, addr.len()),
+            Err(STACKS_C32_ERR_BUFFER_TOO_SMALL)
+        );
+        assert_eq!(decode(&addr, 19), Err(STACKS_C32_ERR_BUFFER_TOO_SMALL));
+        assert_eq!(decode("S!", 20), Err(STACKS_C32_ERR_INVALID_CROCKFORD32));
+        assert_eq!(
+            decode("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8", 20),
+            Err(STACKS_C32_ERR_BAD_CHECKSUM)
+        );
+
+        let rc = unsafe { ENCODE(22, std::ptr::null(), 20, std::ptr::null_mut(), 0) };
+        assert_eq!(rc, STACKS_C32_ERR_NULL_POINTER);
+        let rc = unsafe {
+            DECODE(
+                std::ptr::null(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        assert_eq!(rc, STACKS_C32_ERR_NULL_POINTER);
+    }
+
+    #[test]
+    fn test_ffi_header_matches() {
+        let header = include_str!("../../include/stacks_c32.h");
+        let codes = [
+            ("STACKS_C32_ERR_NULL_POINTER", STACKS_C32_ERR_NULL_POINTER),
+            (
+                "STACKS_C32_ERR_INVALID_VERSION",
+                STACKS_C32_ERR_INVALID_VERSION,
+            ),
+            (
+                "STACKS_C32_ERR_BUFFER_TOO_SMALL",
+                STACKS_C32_ERR_BUFFER_TOO_SMALL,
+            ),
+            (
+                "STACKS_C32_ERR_INVALID_CROCKFORD32",
+                STACKS_C32_ERR_INVALID_CROCKFORD32,
+            ),
+            ("STACKS_C32_ERR_BAD_CHECKSUM", STACKS_C32_ERR_BAD_CHECKSUM),
+            ("STACKS_C32_ERR_OTHER", STACKS_C32_ERR_OTHER),
+            ("STACKS_C32_ERR_PANIC", STACKS_C32_ERR_PANIC),
+        ];
+        for (name, code) in codes {
+            assert!(
+                header.contains(&format!("#define {name} {code}\n")),
+                "header is missing or disagrees on {name}"
+            );
+        }
+        let signatures = [
+            "int32_t stacks_c32_address(uint8_t version,",
+            "const uint8_t *data_ptr,",
+            "size_t data_len,",
+            "char *out_buf,",
+            "int32_t stacks_c32_address_decode(const char *str_ptr,",
+            "uint8_t *out_version,",
+            "uint8_t *out_buf,",
+            "size_t out_len);",
+        ];
+        for sig in signatures {
+            assert!(header.contains(sig), "header is missing `{sig}`");
+        }
+    }
+}
@@ -27,6 +27,11 @@ pub mod b58;
 pub mod c32;
 #[cfg(test)]
 pub mod c32_old;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod principal;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub const C32_ADDRESS_VERSION_MAINNET_SINGLESIG: u8 = 22; // P
 pub const C32_ADDRESS_VERSION_MAINNET_MULTISIG: u8 = 20; // M
@@ -48,6 +53,12 @@ pub enum Error {
     InvalidLength(usize),
     /// Checked data was less than 4 bytes
     TooShort(usize),
+    /// The c32 string decodes, but is not the canonical encoding of its payload
+    NonCanonical,
+    /// The contract name portion of a contract principal is invalid
+    InvalidContractName(String),
+    /// The address does not start with a known network prefix letter
+    BadPrefix(char),
     /// Any other error
     Other(String),
 }
@@ -66,6 +77,9 @@ impl fmt::Display for Error {
             ),
             Error::InvalidLength(ell) => write!(f, "length {} invalid for this base58 type", ell),
             Error::TooShort(_) => write!(f, "base58ck data not even long enough for a checksum"),
+            Error::NonCanonical => f.write_str("Non-canonical crockford 32 string"),
+            Error::InvalidContractName(ref s) => write!(f, "Invalid contract name: {}", s),
+            Error::BadPrefix(c) => write!(f, "Invalid address prefix {:?}", c),
             Error::Other(ref s) => f.write_str(s),
         }
     }
@@ -84,6 +98,9 @@ impl error::Error for Error {
             Error::BadChecksum(_, _) => "invalid b58ck checksum",
             Error::InvalidLength(_) => "invalid length for b58 type",
             Error::TooShort(_) => "b58ck data less than 4 bytes",
+            Error::NonCanonical => "non-canonical c32 string",
+            Error::InvalidContractName(_) => "invalid contract name",
+            Error::BadPrefix(_) => "invalid address prefix",
             Error::Other(_) => "unknown b58 error",
         }
     }
@@ -0,0 +1,156 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use super::c32::{c32_address, c32_address_decode_strict};
+use super::Error;
+
+/// Minimum length of a contract name, matching Clarity's `CONTRACT_MIN_NAME_LENGTH`
+pub const CONTRACT_MIN_NAME_LENGTH: usize = 1;
+/// Maximum length of a contract name, matching Clarity's `CONTRACT_MAX_NAME_LENGTH`
+pub const CONTRACT_MAX_NAME_LENGTH: usize = 40;
+
+/// A contract name that has been validated against the Clarity naming rules:
+/// an ASCII letter followed by ASCII letters, digits, `-` or `_`, between
+/// `CONTRACT_MIN_NAME_LENGTH` and `CONTRACT_MAX_NAME_LENGTH` bytes long.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContractNameStr(String);
+
+impl ContractNameStr {
+    /// Validate `name` as a contract name
+    pub fn new(name: &str) -> Result<Self, Error> {
+        if name.len() < CONTRACT_MIN_NAME_LENGTH || name.len() > CONTRACT_MAX_NAME_LENGTH {
+            return Err(Error::InvalidContractName(format!(
+                "contract name must be {CONTRACT_MIN_NAME_LENGTH} to {CONTRACT_MAX_NAME_LENGTH} bytes, got {}",
+                name.len()
+            )));
+        }
+        let mut chars = name.chars();
+        if !chars.next().is_some_and(|c| c.is_ascii_alphabetic()) {
+            return Err(Error::InvalidContractName(format!(
+                "contract name must start with an ASCII letter: {name}"
+            )));
+        }
+        if let Some(c) = chars.find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_')) {
+            return Err(Error::InvalidContractName(format!(
+                "invalid character {c:?} in contract name: {name}"
+            )));
+        }
+        Ok(Self(name.to_string()))
+    }
+
+    /// The validated name
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ContractNameStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Parse a contract principal of the form `<c32 address>.<contract name>` into its address
+/// version, address hash bytes, and contract name.
+/// The address must be the canonical c32 encoding of a 20-byte hash.
+pub fn parse_contract_principal(s: &str) -> Result<(u8, [u8; 20], ContractNameStr), Error> {
+    let Some((addr, name)) = s.split_once('.') else {
+        return Err(Error::InvalidContractName(format!(
+            "missing contract name in principal: {s}"
+        )));
+    };
+    let (version, bytes) = c32_address_decode_strict(addr)?;
+    let hash: [u8; 20] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| Error::InvalidLength(bytes.len()))?;
+    let name = ContractNameStr::new(name)?;
+    Ok((version, hash, name))
+}
+
+/// Format a contract principal as `<c32 address>.<contract name>`.
+/// This is the inverse of `parse_contract_principal`.
+pub fn format_contract_principal(
+    version: u8,
+    hash: &[u8; 20],
+    name: &ContractNameStr,
+) -> Result<String, Error> {
+    Ok(format!("{}.{name}", c32_address(version, hash)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ADDR: &str = "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7";
+
+    #[test]
+    fn test_contract_principal_round_trip() {
+        for name in ["a", "my-contract", "pox_4", "A1-b_2", &"z".repeat(40)] {
+            let principal = format!("{ADDR}.{name}");
+            let (version, hash, parsed_name) = parse_contract_principal(&principal).unwrap();
+            assert_eq!(version, 22);
+            assert_eq!(parsed_name.as_str(), name);
+            assert_eq!(
+                format_contract_principal(version, &hash, &parsed_name).unwrap(),
+                principal
+            );
+        }
+    }
+
+    #[test]
+    fn test_contract_principal_bad_names() {
+        let bad = [
+            ADDR.to_string(),
+            format!("{ADDR}."),
+            format!("{ADDR}.{}", "z".repeat(41)),
+            format!("{ADDR}.1contract"),
+            format!("{ADDR}.-contract"),
+            format!("{ADDR}.my contract"),
+            format!("{ADDR}.my.contract"),
+            format!("{ADDR}.caf\u{e9}"),
+        ];
+        for principal in bad.iter() {
+            assert!(
+                matches!(
+                    parse_contract_principal(principal),
+                    Err(Error::InvalidContractName(_))
+                ),
+                "accepted {principal}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_contract_principal_bad_address() {
+        // non-canonical alias for a zero digit
+        assert!(matches!(
+            parse_contract_principal("SPOOOOOOOOOOOOOOOOOOOO2Q6VF78.foo"),
+            Err(Error::NonCanonical)
+        ));
+        assert!(matches!(
+            parse_contract_principal("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8.foo"),
+            Err(Error::BadChecksum(..))
+        ));
+        // valid c32, but not a 20-byte hash
+        let short = c32_address(22, &[1u8; 19]).unwrap();
+        assert!(matches!(
+            parse_contract_principal(&format!("{short}.foo")),
+            Err(Error::InvalidLength(19))
+        ));
+    }
+}
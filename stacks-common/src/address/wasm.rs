@@ -0,0 +1,139 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `wasm_bindgen` exports of the c32 address codec, so that browser tooling can use the
+//! canonical implementation instead of maintaining a JS port.
+//!
+//! Codec errors are thrown as JS `Error`s whose `name` is the error kind (see
+//! [`Error::kind`]) and whose `message` is the `Display` output.
+//!
+//! The tests run under `wasm-pack test --node -- --no-default-features --features wasm`.
+
+use wasm_bindgen::prelude::*;
+
+use super::c32::{c32_address, c32_address_decode};
+use super::Error;
+
+impl Error {
+    /// Stable name of the error variant, used as the JS exception name
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::InvalidCrockford32 => "InvalidCrockford32",
+            Error::InvalidVersion(_) => "InvalidVersion",
+            Error::EmptyData => "EmptyData",
+            Error::BadByte(_) => "BadByte",
+            Error::BadChecksum(..) => "BadChecksum",
+            Error::InvalidLength(_) => "InvalidLength",
+            Error::TooShort(_) => "TooShort",
+            Error::NonCanonical => "NonCanonical",
+            Error::InvalidContractName(_) => "InvalidContractName",
+            Error::BadPrefix(_) => "BadPrefix",
+            Error::Other(_) => "Other",
+        }
+    }
+}
+
+impl From<Error> for JsValue {
+    fn from(e: Error) -> Self {
+        let js_err = js_sys::Error::new(&e.to_string());
+        js_err.set_name(e.kind());
+        js_err.into()
+    }
+}
+
+/// A decoded c32 address
+#[wasm_bindgen]
+pub struct C32DecodedAddress {
+    version: u8,
+    bytes: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl C32DecodedAddress {
+    /// The address version
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The address payload
+    #[wasm_bindgen(getter)]
+    pub fn bytes(&self) -> Vec<u8> {
+        self.bytes.clone()
+    }
+}
+
+/// Encode `bytes` as a c32 address with the given `version`
+#[wasm_bindgen(js_name = c32AddressEncode)]
+pub fn c32_address_encode(version: u8, bytes: &[u8]) -> Result<String, JsValue> {
+    Ok(c32_address(version, bytes)?)
+}
+
+/// Decode a c32 address into its version and payload
+#[wasm_bindgen(js_name = c32AddressDecode)]
+pub fn c32_address_decode_js(addr: &str) -> Result<C32DecodedAddress, JsValue> {
+    let (version, bytes) = c32_address_decode(addr)?;
+    Ok(C32DecodedAddress { version, bytes })
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod test {
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::*;
+
+    use super::*;
+    use crate::util::hash::hex_bytes;
+
+    #[wasm_bindgen_test]
+    fn test_known_vectors() {
+        let vectors = [
+            (
+                22,
+                "a46ff88886c2ef9762d970b4d2c63678835bd39d",
+                "SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ7",
+            ),
+            (
+                0,
+                "0000000000000000000000000000000000000000",
+                "S0000000000000000000002AA028H",
+            ),
+            (
+                26,
+                "1000000000000000000000000000000000000001",
+                "ST80000000000000000000000000000006BYJ4R4",
+            ),
+        ];
+        for (version, hex, addr) in vectors {
+            let bytes = hex_bytes(hex).unwrap();
+            assert_eq!(c32_address_encode(version, &bytes).unwrap(), addr);
+            let decoded = c32_address_decode_js(addr).unwrap();
+            assert_eq!(decoded.version(), version);
+            assert_eq!(decoded.bytes(), bytes);
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_error_kinds() {
+        let err = c32_address_encode(32, &[0u8; 20]).err().unwrap();
+        let err: js_sys::Error = err.dyn_into().unwrap();
+        assert_eq!(err.name(), "InvalidVersion");
+
+        let err = c32_address_decode_js("SP2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKNRV9EJ8")
+            .err()
+            .unwrap();
+        let err: js_sys::Error = err.dyn_into().unwrap();
+        assert_eq!(err.name(), "BadChecksum");
+    }
+}
@@ -146,6 +146,29 @@ impl<const MAX_SIZE: u16> BitVec<MAX_SIZE> {
         Ok(bitvec)
     }
 
+    /// Construct a new BitVec of total length `len` with the entries at `indices` set to `true`.
+    /// Errors if `len` exceeds `MAX_SIZE` or any index is outside of the bitvec.
+    pub fn from_slot_set<I: IntoIterator<Item = u16>>(
+        indices: I,
+        len: u16,
+    ) -> Result<BitVec<MAX_SIZE>, String> {
+        let mut bitvec: BitVec<MAX_SIZE> = BitVec::zeros(len)?;
+        for i in indices {
+            bitvec.set(i, true)?;
+        }
+        Ok(bitvec)
+    }
+
+    /// Sum the weights of the `entries`, given as (index, weight) pairs, whose entry in this
+    /// bitvec is set. Entries outside of the bitvec count as unset.
+    pub fn weight_of<I: IntoIterator<Item = (u16, u32)>>(&self, entries: I) -> u64 {
+        entries
+            .into_iter()
+            .filter(|(i, _)| self.get(*i).unwrap_or(false))
+            .map(|(_, weight)| u64::from(weight))
+            .sum()
+    }
+
     pub fn len(&self) -> u16 {
         self.len
     }
@@ -331,6 +354,54 @@ mod test {
         info!("bitvec_ones_10: {:?}", bitvec_ones_10.binary_str());
     }
 
+    #[test]
+    fn from_slot_set_boundaries() {
+        const CAP: u16 = 16;
+        // empty, single-entry and full-capacity bitvecs can be constructed
+        let empty = BitVec::<CAP>::from_slot_set(0..0, 0).unwrap();
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.get(0), None);
+
+        let single = BitVec::<CAP>::from_slot_set([0], 1).unwrap();
+        assert_eq!(single.binary_str(), "1");
+
+        let full = BitVec::<CAP>::from_slot_set(0..CAP, CAP).unwrap();
+        assert_eq!(full, BitVec::<CAP>::ones(CAP).unwrap());
+
+        let sparse = BitVec::<CAP>::from_slot_set([1, 3, CAP - 1], CAP).unwrap();
+        assert_eq!(sparse.binary_str(), "0101000000000001");
+
+        // one past the capacity, or an index past the length, is an error
+        assert!(BitVec::<CAP>::from_slot_set(0..0, CAP + 1).is_err());
+        assert!(BitVec::<CAP>::from_slot_set([CAP], CAP).is_err());
+        assert!(BitVec::<CAP>::from_slot_set([1], 1).is_err());
+    }
+
+    #[test]
+    fn weight_of_boundaries() {
+        const CAP: u16 = 16;
+        let empty = BitVec::<CAP>::zeros(0).unwrap();
+        assert_eq!(empty.weight_of([(0, 5)]), 0);
+
+        let single = BitVec::<CAP>::from_slot_set([0], 1).unwrap();
+        assert_eq!(single.weight_of([(0, 5)]), 5);
+        // entries outside of the bitvec count as unset
+        assert_eq!(single.weight_of([(0, 5), (1, 7), (CAP, 11)]), 5);
+
+        let full = BitVec::<CAP>::ones(CAP).unwrap();
+        let weights: Vec<(u16, u32)> = (0..CAP).map(|i| (i, u32::MAX)).collect();
+        assert_eq!(
+            full.weight_of(weights.iter().copied()),
+            u64::from(CAP) * u64::from(u32::MAX)
+        );
+
+        let sparse = BitVec::<CAP>::from_slot_set([1, CAP - 1], CAP).unwrap();
+        assert_eq!(
+            sparse.weight_of(weights.iter().copied()),
+            2 * u64::from(u32::MAX)
+        );
+    }
+
     #[test]
     fn vectors() {
         let mut inputs = vec![
@@ -25,6 +25,7 @@ pub enum SignalId {
     CtrlC = 0x00,
     Termination = 0x01,
     Bus = 0x02,
+    Hangup = 0x03,
     Other = 0xff,
 }
 
@@ -34,6 +35,7 @@ impl std::fmt::Display for SignalId {
             SignalId::CtrlC => write!(f, "CtrlC"),
             SignalId::Termination => write!(f, "Termination"),
             SignalId::Bus => write!(f, "Bus"),
+            SignalId::Hangup => write!(f, "Hangup"),
             SignalId::Other => write!(f, "Other"),
         }
     }
@@ -63,7 +63,12 @@ pub mod consts {
 
     /// The number of StackerDB slots each signing key needs
     ///  to use to participate in DKG and block validation signing.
-    pub const SIGNER_SLOTS_PER_USER: u32 = 13;
+    pub const SIGNER_SLOTS_PER_USER: u32 = 15;
+
+    /// The number of StackerDB slots per signing key that the .signers-x-y
+    ///  contracts were instantiated with in Epoch 2.5. The contracts for the slots
+    ///  beyond these are instantiated at the start of Epoch 3.0.
+    pub const SIGNER_SLOTS_PER_USER_EPOCH_2_5: u32 = 13;
 }
 
 /// This test asserts that the constant above doesn't change.
@@ -75,7 +80,7 @@ pub mod consts {
 #[test]
 fn signer_slots_count_2_5() {
     assert_eq!(
-        consts::SIGNER_SLOTS_PER_USER,
+        consts::SIGNER_SLOTS_PER_USER_EPOCH_2_5,
         13,
         "The .signers-x-y contracts in Epoch 2.5 were instantiated with 13 slots"
     );
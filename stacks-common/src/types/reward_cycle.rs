@@ -0,0 +1,132 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+/// The reward cycle schedule of a burnchain, as set by its PoX constants. Everything about
+/// which reward cycle a burn block belongs to follows from these three numbers, so anything
+/// that knows them (e.g. from `/v2/pox`) can work it out without asking a node.
+///
+/// The first block of reward cycle `n` is at `first_block_height + n * reward_cycle_length + 1`.
+/// The last `prepare_length` blocks of each cycle, up to and including the block before the
+/// next cycle's first block, are its prepare phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewardCycleInfo {
+    /// The burn block height PoX starts counting from
+    pub first_block_height: u64,
+    /// The number of burn blocks in a reward cycle, including its prepare phase
+    pub reward_cycle_length: u64,
+    /// The number of burn blocks in a prepare phase
+    pub prepare_length: u64,
+}
+
+impl RewardCycleInfo {
+    /// `reward_cycle_length` must be greater than `prepare_length`
+    pub const fn new(
+        first_block_height: u64,
+        reward_cycle_length: u64,
+        prepare_length: u64,
+    ) -> Self {
+        Self {
+            first_block_height,
+            reward_cycle_length,
+            prepare_length,
+        }
+    }
+
+    /// The reward cycle `burn_height` is in, or `None` if it is before the first block height
+    pub const fn cycle_of(&self, burn_height: u64) -> Option<u64> {
+        match burn_height.checked_sub(self.first_block_height) {
+            Some(effective_height) => Some(effective_height / self.reward_cycle_length),
+            None => None,
+        }
+    }
+
+    /// Where `burn_height` falls within its reward cycle, or `None` if it is before the first
+    /// block height. The first block of a cycle is at index 1, and its prepare phase ends at
+    /// index 0.
+    pub const fn cycle_index(&self, burn_height: u64) -> Option<u64> {
+        match burn_height.checked_sub(self.first_block_height) {
+            Some(effective_height) => Some(effective_height % self.reward_cycle_length),
+            None => None,
+        }
+    }
+
+    /// The burn height of the first block of `reward_cycle`
+    pub const fn first_height_of(&self, reward_cycle: u64) -> u64 {
+        self.first_block_height
+            .saturating_add(reward_cycle.saturating_mul(self.reward_cycle_length))
+            .saturating_add(1)
+    }
+
+    /// Is `burn_height` in a prepare phase? The first block height itself is not.
+    pub const fn is_in_prepare_phase(&self, burn_height: u64) -> bool {
+        if burn_height <= self.first_block_height {
+            return false;
+        }
+        let reward_index = (burn_height - self.first_block_height) % self.reward_cycle_length;
+        reward_index == 0 || reward_index > self.reward_cycle_length - self.prepare_length
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Bitcoin mainnet
+    const MAINNET: RewardCycleInfo = RewardCycleInfo::new(666050, 2100, 100);
+    /// Bitcoin testnet
+    const TESTNET: RewardCycleInfo = RewardCycleInfo::new(2000000, 1050, 50);
+
+    #[test]
+    fn mainnet_reward_cycles() {
+        assert_eq!(MAINNET.cycle_of(666049), None);
+        assert_eq!(MAINNET.cycle_of(666050), Some(0));
+        assert_eq!(MAINNET.first_height_of(0), 666051);
+
+        // cycle 84 runs from 842451 to 844550, and its prepare phase is its last 100 blocks
+        assert_eq!(MAINNET.first_height_of(84), 842451);
+        assert_eq!(MAINNET.cycle_of(842451), Some(84));
+        assert_eq!(MAINNET.cycle_index(842451), Some(1));
+        assert!(!MAINNET.is_in_prepare_phase(842451));
+        assert!(!MAINNET.is_in_prepare_phase(844450));
+        assert!(MAINNET.is_in_prepare_phase(844451));
+        assert!(MAINNET.is_in_prepare_phase(844550));
+        assert_eq!(MAINNET.cycle_index(844550), Some(0));
+        // the last block of the prepare phase already counts towards the next cycle
+        assert_eq!(MAINNET.cycle_of(844550), Some(85));
+        assert_eq!(MAINNET.first_height_of(85), 844551);
+        assert!(!MAINNET.is_in_prepare_phase(844551));
+    }
+
+    #[test]
+    fn testnet_reward_cycles() {
+        assert_eq!(TESTNET.cycle_of(1999999), None);
+        assert_eq!(TESTNET.cycle_index(1999999), None);
+        assert!(!TESTNET.is_in_prepare_phase(2000000));
+
+        // cycle 500 runs from 2525001 to 2526050
+        assert_eq!(TESTNET.first_height_of(500), 2525001);
+        assert_eq!(TESTNET.cycle_of(2525001), Some(500));
+        assert_eq!(TESTNET.cycle_of(2526049), Some(500));
+        assert!(!TESTNET.is_in_prepare_phase(2526000));
+        assert!(TESTNET.is_in_prepare_phase(2526001));
+        assert!(TESTNET.is_in_prepare_phase(2526050));
+        assert_eq!(TESTNET.cycle_index(2526001), Some(1001));
+    }
+
+    #[test]
+    fn first_height_of_saturates() {
+        assert_eq!(MAINNET.first_height_of(u64::MAX), u64::MAX);
+    }
+}
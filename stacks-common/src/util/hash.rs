@@ -609,6 +609,123 @@ pub fn hex_bytes(s: &str) -> Result<Vec<u8>, HexError> {
     }
 }
 
+/// Lowercase hex digits, indexed by nibble
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// Nibble value of each ASCII byte, or `0xff` if the byte is not a hex digit
+const HEX_DECODE_TABLE: [u8; 256] = {
+    let mut table = [0xffu8; 256];
+    let mut i = 0;
+    while i < 10 {
+        table[b'0' as usize + i] = i as u8;
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 6 {
+        table[b'a' as usize + i] = 10 + i as u8;
+        table[b'A' as usize + i] = 10 + i as u8;
+        i += 1;
+    }
+    table
+};
+
+/// Decode the hex string `src` into the front of `dst` without allocating.
+/// `src` must have an even length and contain only hex digits (no prefix or whitespace).
+/// Returns the number of bytes written.
+pub fn hex_decode_into(src: &str, dst: &mut [u8]) -> Result<usize, HexError> {
+    hex_decode_into_with_prefix(src, dst, false)
+}
+
+/// Like `hex_decode_into`, but if `allow_prefix` is set, a leading `0x` is skipped.
+pub fn hex_decode_into_with_prefix(
+    src: &str,
+    dst: &mut [u8],
+    allow_prefix: bool,
+) -> Result<usize, HexError> {
+    let digits = if allow_prefix {
+        src.strip_prefix("0x").unwrap_or(src)
+    } else {
+        src
+    };
+    let digits = digits.as_bytes();
+    if digits.len() % 2 != 0 {
+        return Err(HexError::BadLength(src.len()));
+    }
+    let len = digits.len() / 2;
+    let Some(dst) = dst.get_mut(..len) else {
+        return Err(HexError::BufferTooSmall(len));
+    };
+    for (out, pair) in dst.iter_mut().zip(digits.chunks_exact(2)) {
+        let hi = HEX_DECODE_TABLE[pair[0] as usize];
+        let lo = HEX_DECODE_TABLE[pair[1] as usize];
+        if hi == 0xff {
+            return Err(HexError::BadCharacter(hex_bad_char(src, pair[0])));
+        }
+        if lo == 0xff {
+            return Err(HexError::BadCharacter(hex_bad_char(src, pair[1])));
+        }
+        *out = (hi << 4) | lo;
+    }
+    Ok(len)
+}
+
+/// Recover the offending character for an error report, given its first byte
+fn hex_bad_char(src: &str, byte: u8) -> char {
+    if byte.is_ascii() {
+        return byte as char;
+    }
+    src.chars()
+        .find(|c| !c.is_ascii())
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// Append the lowercase hex encoding of `src` to `dst`.
+/// Does not allocate if `dst` already has capacity for `2 * src.len()` more bytes.
+pub fn hex_encode_into(src: &[u8], dst: &mut String) {
+    dst.reserve(src.len() * 2);
+    for b in src.iter() {
+        dst.push(HEX_CHARS[(b >> 4) as usize] as char);
+        dst.push(HEX_CHARS[(b & 0x0f) as usize] as char);
+    }
+}
+
+/// A reader of the bytes a hex string encodes, decoded as they are read, so that a
+/// hex-encoded value can be deserialized without first decoding it into a buffer.
+/// The string must not have a prefix or whitespace.
+pub struct HexReader<'a> {
+    src: &'a str,
+    /// Offset into `src` of the next hex digit to decode
+    pos: usize,
+}
+
+impl<'a> HexReader<'a> {
+    /// Errors if `src` has an odd length. Bad characters are only found as they are read.
+    pub fn new(src: &'a str) -> Result<Self, HexError> {
+        if src.len() % 2 != 0 {
+            return Err(HexError::BadLength(src.len()));
+        }
+        Ok(Self { src, pos: 0 })
+    }
+}
+
+impl std::io::Read for HexReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min((self.src.len() - self.pos) / 2);
+        let end = self.pos + 2 * len;
+        let digits = self.src.get(self.pos..end).ok_or_else(|| {
+            // a non-ASCII character straddles the end of the digits to decode
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                HexError::BadCharacter(char::REPLACEMENT_CHARACTER),
+            )
+        })?;
+        let read = hex_decode_into(digits, buf)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        self.pos = end;
+        Ok(read)
+    }
+}
+
 /// Convert a binary-encoded string to its corresponding bytes
 pub fn bin_bytes(s: &str) -> Result<Vec<u8>, HexError> {
     let mut v = Vec::with_capacity(s.len() / 8 + 1);
@@ -657,9 +774,13 @@ pub fn bytes_to_hex(s: &[u8]) -> String {
 
 #[cfg(test)]
 mod test {
+    use rand::{Rng, RngCore};
+
     use super::{
-        bin_bytes, hex_bytes, to_bin, DoubleSha256, MerkleHashFunc, MerklePath, MerkleTree,
+        bin_bytes, hex_bytes, hex_decode_into, hex_decode_into_with_prefix, hex_encode_into,
+        to_bin, to_hex, DoubleSha256, HexReader, MerkleHashFunc, MerklePath, MerkleTree,
     };
+    use crate::util::HexError;
 
     struct MerkleTreeFixture {
         data: Vec<Vec<u8>>,
@@ -817,4 +938,100 @@ mod test {
         assert_eq!(bin_bytes("").unwrap().len(), 0);
         assert!(bin_bytes("2").is_err());
     }
+
+    #[test]
+    fn test_hex_into_matches_allocating_impl() {
+        let mut rng = rand::thread_rng();
+        let mut buf = [0u8; 64];
+        for _ in 0..1000 {
+            let len = rng.gen_range(0..=64);
+            let mut bytes = vec![0u8; len];
+            rng.fill_bytes(&mut bytes);
+
+            let mut encoded = String::new();
+            hex_encode_into(&bytes, &mut encoded);
+            assert_eq!(encoded, to_hex(&bytes));
+
+            let upper = encoded.to_uppercase();
+            for s in [&encoded, &upper] {
+                let n = hex_decode_into(s, &mut buf).unwrap();
+                assert_eq!(&buf[..n], hex_bytes(s).unwrap().as_slice());
+            }
+
+            let prefixed = format!("0x{encoded}");
+            let n = hex_decode_into_with_prefix(&prefixed, &mut buf, true).unwrap();
+            assert_eq!(&buf[..n], bytes.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_hex_reader_matches_allocating_impl() {
+        use std::io::Read;
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let len = rng.gen_range(0..=256);
+            let mut bytes = vec![0u8; len];
+            rng.fill_bytes(&mut bytes);
+            let encoded = to_hex(&bytes);
+
+            // read in small chunks, so that reads end mid-string
+            let mut reader = HexReader::new(&encoded).unwrap();
+            let mut decoded = vec![];
+            let mut chunk = [0u8; 7];
+            loop {
+                let n = reader.read(&mut chunk).unwrap();
+                if n == 0 {
+                    break;
+                }
+                decoded.extend_from_slice(&chunk[..n]);
+            }
+            assert_eq!(decoded, hex_bytes(&encoded).unwrap());
+        }
+
+        assert!(matches!(HexReader::new("abc"), Err(HexError::BadLength(3))));
+        let mut decoded = vec![];
+        let err = HexReader::new("00zz")
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let mut decoded = vec![];
+        assert!(HexReader::new("0\u{e9}0")
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .is_err());
+    }
+
+    #[test]
+    fn test_hex_decode_into_errors() {
+        let mut buf = [0u8; 4];
+        assert_eq!(
+            hex_decode_into("abc", &mut buf),
+            Err(HexError::BadLength(3))
+        );
+        assert_eq!(
+            hex_decode_into("0011223344", &mut buf),
+            Err(HexError::BufferTooSmall(5))
+        );
+        assert_eq!(
+            hex_decode_into("00 1", &mut buf),
+            Err(HexError::BadCharacter(' '))
+        );
+        assert_eq!(
+            hex_decode_into("0g", &mut buf),
+            Err(HexError::BadCharacter('g'))
+        );
+        assert_eq!(
+            hex_decode_into("0\u{e9}0", &mut buf),
+            Err(HexError::BadCharacter('\u{e9}'))
+        );
+        // the prefix is only accepted when asked for
+        assert_eq!(
+            hex_decode_into("0x00", &mut buf),
+            Err(HexError::BadCharacter('x'))
+        );
+        assert_eq!(hex_decode_into_with_prefix("0x00", &mut buf, true), Ok(1));
+        assert_eq!(hex_decode_into("", &mut buf), Ok(0));
+    }
 }
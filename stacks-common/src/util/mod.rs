@@ -59,6 +59,8 @@ pub enum HexError {
     BadLength(usize),
     /// Non-hex character in string
     BadCharacter(char),
+    /// Output buffer cannot hold the decoded bytes (number of bytes needed)
+    BufferTooSmall(usize),
 }
 
 impl fmt::Display for HexError {
@@ -66,6 +68,7 @@ impl fmt::Display for HexError {
         match *self {
             HexError::BadLength(n) => write!(f, "bad length {} for hex string", n),
             HexError::BadCharacter(c) => write!(f, "bad character {} for hex string", c),
+            HexError::BufferTooSmall(n) => write!(f, "need {} bytes to decode hex string", n),
         }
     }
 }
@@ -78,6 +81,7 @@ impl error::Error for HexError {
         match *self {
             HexError::BadLength(_) => "hex string non-64 length",
             HexError::BadCharacter(_) => "bad hex character",
+            HexError::BufferTooSmall(_) => "hex output buffer too small",
         }
     }
 }
@@ -426,6 +426,76 @@ pub fn secp256k1_recover(
     })
 }
 
+/// A batch of signatures over 32-byte hashes, verified together. Checking that a whole batch
+/// is valid is faster than finding the invalid signatures in it, which is only done if the
+/// batch check fails. With the `parallel-verify` feature, signatures are checked in parallel.
+#[derive(Debug, Default)]
+pub struct SignatureBatch<'a> {
+    entries: Vec<(&'a Secp256k1PublicKey, &'a [u8], &'a MessageSignature)>,
+}
+
+impl<'a> SignatureBatch<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Add the signature `sig` by `pubkey` over `data_hash` to the batch
+    pub fn push(
+        &mut self,
+        pubkey: &'a Secp256k1PublicKey,
+        data_hash: &'a [u8],
+        sig: &'a MessageSignature,
+    ) {
+        self.entries.push((pubkey, data_hash, sig));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn is_valid(entry: &(&Secp256k1PublicKey, &[u8], &MessageSignature)) -> bool {
+        let (pubkey, data_hash, sig) = entry;
+        pubkey.verify(data_hash, sig) == Ok(true)
+    }
+
+    /// Whether every signature in the batch is valid
+    pub fn verify_all(&self) -> bool {
+        #[cfg(feature = "parallel-verify")]
+        {
+            use rayon::prelude::*;
+            self.entries.par_iter().all(Self::is_valid)
+        }
+        #[cfg(not(feature = "parallel-verify"))]
+        {
+            self.entries.iter().all(Self::is_valid)
+        }
+    }
+
+    /// The positions, in the order they were pushed, of the invalid signatures in the batch.
+    /// Empty if the batch is valid.
+    pub fn invalid_indices(&self) -> Vec<usize> {
+        if self.verify_all() {
+            return vec![];
+        }
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !Self::is_valid(entry))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
+
 pub fn secp256k1_verify(
     message_arr: &[u8],
     serialized_signature_arr: &[u8],
@@ -714,6 +784,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn signature_batch_finds_the_invalid_signatures() {
+        let mut rng = rand::thread_rng();
+        let signers: Vec<_> = (0..8)
+            .map(|_| {
+                let privk = Secp256k1PrivateKey::new();
+                (Secp256k1PublicKey::from_private(&privk), privk)
+            })
+            .collect();
+        let mut msg = [0u8; 32];
+        rng.fill_bytes(&mut msg);
+        let mut other_msg = [0u8; 32];
+        rng.fill_bytes(&mut other_msg);
+        let mut sigs: Vec<_> = signers
+            .iter()
+            .map(|(_, privk)| privk.sign(&msg).unwrap())
+            .collect();
+
+        let batch_of = |sigs: &[MessageSignature]| {
+            let mut batch = SignatureBatch::with_capacity(sigs.len());
+            for ((pubkey, _), sig) in signers.iter().zip(sigs) {
+                batch.push(pubkey, &msg, sig);
+            }
+            batch.verify_all()
+        };
+        assert!(batch_of(&sigs));
+        assert!(SignatureBatch::new().verify_all());
+
+        // a signature over another message, one by another signer, and a malformed one
+        sigs[1] = signers[1].1.sign(&other_msg).unwrap();
+        sigs[4] = signers[5].1.sign(&msg).unwrap();
+        sigs[6] = MessageSignature::empty();
+        assert!(!batch_of(&sigs));
+
+        let mut batch = SignatureBatch::new();
+        for ((pubkey, _), sig) in signers.iter().zip(&sigs) {
+            batch.push(pubkey, &msg, sig);
+        }
+        assert_eq!(batch.len(), 8);
+        assert_eq!(batch.invalid_indices(), vec![1, 4, 6]);
+    }
+
     /*
     #[test]
     fn test_schnorr_signature_serde() {
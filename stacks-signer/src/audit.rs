@@ -0,0 +1,864 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use blockstack_lib::chainstate::stacks::{StacksTransaction, TransactionPayload};
+use clarity::vm::types::QualifiedContractIdentifier;
+use hmac::{Hmac, Mac};
+use libstackerdb::StackerDBChunkData;
+use rand::RngCore;
+use serde_derive::{Deserialize, Serialize};
+use sha2::Sha256;
+use slog::slog_error;
+use stacks_common::error;
+use stacks_common::util::get_epoch_time_secs;
+use stacks_common::util::hash::{hex_bytes, to_hex, Sha256Sum, Sha512Trunc256Sum};
+
+/// The HMAC that chains the audit records together
+type HmacSha256 = Hmac<Sha256>;
+
+/// Default size (in bytes) above which the audit log is rotated
+pub const AUDIT_LOG_MAX_BYTES: u64 = 64 * 1024 * 1024;
+/// Default number of rotated audit log files to keep
+pub const AUDIT_LOG_MAX_FILES: u32 = 10;
+/// The MAC the first record of a new audit log chains from
+const GENESIS_MAC: [u8; 32] = [0u8; 32];
+/// How many bytes are read at a time when reading back from the end of the audit log
+const TAIL_READ_BYTES: u64 = 4096;
+/// The domain separator of the MAC over the audit log's anchor
+const ANCHOR_MAC_DOMAIN: &[u8] = b"stacks-signer-audit-anchor";
+
+/// An audit log shared by the clients that publish on the signer's behalf
+pub type SharedAuditLog = Arc<Mutex<AuditLog>>;
+
+#[derive(thiserror::Error, Debug)]
+/// An error occurred writing or verifying an audit log
+pub enum AuditLogError {
+    /// The audit log or its key could not be read or written
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The audit log key is malformed
+    #[error("Invalid audit log key: {0}")]
+    InvalidKey(String),
+    /// A record could not be parsed
+    #[error("Malformed record at {file}:{line}: {reason}")]
+    Malformed {
+        /// The file holding the record
+        file: String,
+        /// The (1-based) line of the record
+        line: usize,
+        /// Why the record could not be parsed
+        reason: String,
+    },
+    /// A record was altered, removed, or inserted
+    #[error("Broken chain at {file}:{line}: {reason}")]
+    BrokenChain {
+        /// The file holding the record
+        file: String,
+        /// The (1-based) line of the record
+        line: usize,
+        /// How the record breaks the chain
+        reason: String,
+    },
+    /// The log does not end at the record its anchor names, e.g. because records were
+    /// removed from its end
+    #[error("Audit log does not match its anchor: {0}")]
+    AnchorMismatch(String),
+}
+
+/// Where the audit log is written and how it is rotated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditLogConfig {
+    /// The path of the current audit log file. Rotated files are suffixed with `.1`, `.2`, ...
+    pub path: PathBuf,
+    /// The path of the file holding the hex-encoded HMAC key. Created if it does not exist.
+    pub key_path: PathBuf,
+    /// The path of the file naming the last record written
+    pub anchor_path: PathBuf,
+    /// The size (in bytes) above which the current file is rotated
+    pub max_bytes: u64,
+    /// The number of rotated files to keep
+    pub max_files: u32,
+}
+
+impl AuditLogConfig {
+    /// The default key path for the audit log at `path`
+    pub fn default_key_path(path: &Path) -> PathBuf {
+        let mut key_path = path.as_os_str().to_owned();
+        key_path.push(".key");
+        key_path.into()
+    }
+
+    /// The default anchor path for the audit log at `path`
+    pub fn default_anchor_path(path: &Path) -> PathBuf {
+        let mut anchor_path = path.as_os_str().to_owned();
+        anchor_path.push(".anchor");
+        anchor_path.into()
+    }
+}
+
+/// Something the signer published
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEntry {
+    /// A signed StackerDB chunk sent to the stacks node
+    Chunk {
+        /// The StackerDB contract the chunk was written to
+        contract: String,
+        /// The slot the chunk was written to
+        slot_id: u32,
+        /// The version of the chunk
+        slot_version: u32,
+        /// The hex-encoded SHA-256 of the chunk data
+        payload_hash: String,
+        /// The signer signature hash of the block, if the chunk is a block response
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signer_signature_hash: Option<String>,
+        /// Whether the stacks node accepted the chunk
+        accepted: bool,
+    },
+    /// A signed transaction the stacks node accepted
+    Transaction {
+        /// The transaction ID
+        txid: String,
+        /// What the transaction does, e.g. `contract-call <address>.<contract>::<function>`
+        payload: String,
+    },
+    /// Records that could not be written, e.g. because the disk was full
+    Dropped {
+        /// The number of records lost
+        records: u64,
+    },
+}
+
+impl AuditEntry {
+    /// The entry for `chunk`, written to `contract`
+    pub fn chunk(
+        contract: &QualifiedContractIdentifier,
+        chunk: &StackerDBChunkData,
+        signer_signature_hash: Option<&Sha512Trunc256Sum>,
+        accepted: bool,
+    ) -> Self {
+        Self::Chunk {
+            contract: contract.to_string(),
+            slot_id: chunk.slot_id,
+            slot_version: chunk.slot_version,
+            payload_hash: Sha256Sum::from_data(&chunk.data).to_hex(),
+            signer_signature_hash: signer_signature_hash.map(|hash| hash.to_hex()),
+            accepted,
+        }
+    }
+
+    /// The entry for `tx`
+    pub fn transaction(tx: &StacksTransaction) -> Self {
+        let payload = match &tx.payload {
+            TransactionPayload::ContractCall(call) => format!(
+                "contract-call {}.{}::{}",
+                call.address, call.contract_name, call.function_name
+            ),
+            TransactionPayload::TokenTransfer(recipient, amount, _) => {
+                format!("token-transfer {amount} to {recipient}")
+            }
+            payload => payload.name().to_string(),
+        };
+        Self::Transaction {
+            txid: tx.txid().to_hex(),
+            payload,
+        }
+    }
+}
+
+/// A line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AuditRecord {
+    /// The position of the record in the log, counting from 0 across rotations
+    seq: u64,
+    /// When the record was written, in seconds since the epoch
+    timestamp: u64,
+    /// What was published
+    entry: AuditEntry,
+    /// The hex-encoded MAC of the previous record
+    prev_mac: String,
+    /// The hex-encoded HMAC-SHA256 of this record (minus this field) under the log key
+    mac: String,
+}
+
+/// The part of a record its MAC is computed over
+#[derive(Serialize)]
+struct MacInput<'a> {
+    seq: u64,
+    timestamp: u64,
+    entry: &'a AuditEntry,
+    prev_mac: &'a str,
+}
+
+/// Compute the MAC of a record
+fn record_mac(
+    key: &[u8; 32],
+    seq: u64,
+    timestamp: u64,
+    entry: &AuditEntry,
+    prev_mac: &str,
+) -> [u8; 32] {
+    let input = MacInput {
+        seq,
+        timestamp,
+        entry,
+        prev_mac,
+    };
+    let bytes = serde_json::to_vec(&input).expect("BUG: failed to serialize an audit record");
+    let mut mac = HmacSha256::new_from_slice(key).expect("BUG: HMAC accepts keys of any length");
+    mac.update(&bytes);
+    mac.finalize().into_bytes().into()
+}
+
+/// Decode a hex-encoded 32-byte MAC or key
+fn decode_32_bytes(hex: &str) -> Option<[u8; 32]> {
+    hex_bytes(hex.trim()).ok()?.try_into().ok()
+}
+
+/// The last record written to the audit log, kept apart from the log so that records
+/// removed from its end are detected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct AuditAnchor {
+    /// The sequence number of the last record
+    seq: u64,
+    /// The hex-encoded MAC of the last record
+    mac: String,
+    /// The hex-encoded HMAC-SHA256 of the fields above under the log key
+    anchor_mac: String,
+}
+
+/// Compute the MAC of an anchor
+fn anchor_mac(key: &[u8; 32], seq: u64, mac: &str) -> [u8; 32] {
+    let mut anchor_mac =
+        HmacSha256::new_from_slice(key).expect("BUG: HMAC accepts keys of any length");
+    anchor_mac.update(ANCHOR_MAC_DOMAIN);
+    anchor_mac.update(&seq.to_be_bytes());
+    anchor_mac.update(mac.as_bytes());
+    anchor_mac.finalize().into_bytes().into()
+}
+
+/// Read the anchor at `path`, if there is one, checking its MAC under `key`
+fn read_anchor(path: &Path, key: &[u8; 32]) -> Result<Option<AuditAnchor>, AuditLogError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let anchor: AuditAnchor =
+        serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| AuditLogError::Malformed {
+            file: path.display().to_string(),
+            line: 1,
+            reason: e.to_string(),
+        })?;
+    if to_hex(&anchor_mac(key, anchor.seq, &anchor.mac)) != anchor.anchor_mac {
+        return Err(AuditLogError::AnchorMismatch(format!(
+            "MAC mismatch on the anchor at {}",
+            path.display()
+        )));
+    }
+    Ok(Some(anchor))
+}
+
+/// Replace the anchor at `path` with one naming the record `seq` with MAC `mac`
+fn write_anchor(
+    path: &Path,
+    key: &[u8; 32],
+    seq: u64,
+    mac: &[u8; 32],
+) -> Result<(), AuditLogError> {
+    let mac = to_hex(mac);
+    let anchor = AuditAnchor {
+        seq,
+        anchor_mac: to_hex(&anchor_mac(key, seq, &mac)),
+        mac,
+    };
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(
+        &serde_json::to_vec(&anchor).expect("BUG: failed to serialize an audit anchor"),
+    )?;
+    file.sync_all()?;
+    // a rename never leaves a partially written anchor behind
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Check that a log whose last record is `last` ends where `anchor` says it does. The log
+/// may be one record ahead of its anchor, if the signer stopped between writing a record
+/// and its anchor.
+fn check_anchor(
+    anchor: Option<&AuditAnchor>,
+    last: Option<&AuditRecord>,
+) -> Result<(), AuditLogError> {
+    let matches = match (anchor, last) {
+        (None, None) => true,
+        (None, Some(last)) => last.seq == 0 && last.prev_mac == to_hex(&GENESIS_MAC),
+        (Some(_), None) => false,
+        (Some(anchor), Some(last)) => {
+            (last.seq == anchor.seq && last.mac == anchor.mac)
+                || (last.seq == anchor.seq.saturating_add(1) && last.prev_mac == anchor.mac)
+        }
+    };
+    if matches {
+        return Ok(());
+    }
+    let anchored = anchor.map_or("no anchor".to_string(), |anchor| {
+        format!("an anchor at record {}", anchor.seq)
+    });
+    Err(AuditLogError::AnchorMismatch(match last {
+        Some(last) => format!("the log ends at record {}, but has {anchored}", last.seq),
+        None => format!("the log holds no records, but has {anchored}"),
+    }))
+}
+
+/// Read the audit log key at `path`
+pub fn read_key(path: &Path) -> Result<[u8; 32], AuditLogError> {
+    let contents = fs::read_to_string(path)?;
+    decode_32_bytes(&contents).ok_or_else(|| {
+        AuditLogError::InvalidKey(format!("{} does not hold 32 hex bytes", path.display()))
+    })
+}
+
+/// Read the audit log key at `path`, generating a new one if the file does not exist
+fn load_or_create_key(path: &Path) -> Result<[u8; 32], AuditLogError> {
+    if path.exists() {
+        return read_key(path);
+    }
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(to_hex(&key).as_bytes())?;
+    Ok(key)
+}
+
+/// The path of the `n`th most recently rotated file of the audit log at `path`
+fn rotated_path(path: &Path, n: u32) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{n}"));
+    rotated.into()
+}
+
+/// Parse the records of the audit log file at `path`
+fn read_records(path: &Path) -> Result<Vec<AuditRecord>, AuditLogError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        let record = serde_json::from_str(&line?).map_err(|e| AuditLogError::Malformed {
+            file: path.display().to_string(),
+            line: i + 1,
+            reason: e.to_string(),
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Parse the last record of the audit log file at `path`, reading back from its end only
+/// as far as the start of that record
+fn read_last_record(path: &Path) -> Result<Option<AuditRecord>, AuditLogError> {
+    let mut file = File::open(path)?;
+    let mut pos = file.metadata()?.len();
+    let mut tail = vec![];
+    while pos > 0 {
+        let read_len = pos.min(TAIL_READ_BYTES);
+        pos -= read_len;
+        let mut buf = vec![0u8; read_len as usize];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut buf)?;
+        buf.append(&mut tail);
+        tail = buf;
+        // the last record starts after the newline that ends the record before it
+        let body_len = tail.len() - usize::from(tail.ends_with(b"\n"));
+        if let Some(i) = tail[..body_len].iter().rposition(|b| *b == b'\n') {
+            tail.drain(..=i);
+            break;
+        }
+    }
+    let line = String::from_utf8_lossy(&tail);
+    let line = line.trim_end_matches('\n');
+    if line.is_empty() {
+        return Ok(None);
+    }
+    let record = serde_json::from_str(line).map_err(|e| AuditLogError::Malformed {
+        file: path.display().to_string(),
+        line: 0,
+        reason: format!("last record: {e}"),
+    })?;
+    Ok(Some(record))
+}
+
+/// An append-only log of everything the signer publishes. Each record carries an HMAC over
+/// its contents and the previous record's HMAC, so altering, removing, or inserting a
+/// record breaks the chain from that record onwards. The last record is also named by a
+/// separately stored anchor, so removing records from the end of the log is detected too.
+pub struct AuditLog {
+    /// Where the log is written and how it is rotated
+    config: AuditLogConfig,
+    /// The HMAC key
+    key: [u8; 32],
+    /// The current log file
+    file: File,
+    /// The size of the current log file
+    len: u64,
+    /// The sequence number of the next record
+    next_seq: u64,
+    /// The MAC of the last record written
+    last_mac: [u8; 32],
+    /// The number of records that could not be written since the last one that was
+    dropped: u64,
+}
+
+impl std::fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never print the key
+        f.debug_struct("AuditLog")
+            .field("path", &self.config.path)
+            .field("next_seq", &self.next_seq)
+            .finish()
+    }
+}
+
+impl AuditLog {
+    /// Open the audit log, continuing the chain of its last record if it already exists.
+    /// Fails if the log does not end at the record its anchor names.
+    pub fn open(config: &AuditLogConfig) -> Result<Self, AuditLogError> {
+        let key = load_or_create_key(&config.key_path)?;
+        let mut last_record = None;
+        for path in [config.path.clone(), rotated_path(&config.path, 1)] {
+            if path.exists() {
+                last_record = read_last_record(&path)?;
+            }
+            if last_record.is_some() {
+                break;
+            }
+        }
+        check_anchor(
+            read_anchor(&config.anchor_path, &key)?.as_ref(),
+            last_record.as_ref(),
+        )?;
+        let (next_seq, last_mac) = match last_record {
+            Some(record) => {
+                let mac = decode_32_bytes(&record.mac).ok_or_else(|| AuditLogError::Malformed {
+                    file: config.path.display().to_string(),
+                    line: 0,
+                    reason: format!("bad MAC on record {}", record.seq),
+                })?;
+                // catch the anchor up, in case the signer stopped before writing it
+                write_anchor(&config.anchor_path, &key, record.seq, &mac)?;
+                (record.seq.saturating_add(1), mac)
+            }
+            None => (0, GENESIS_MAC),
+        };
+        let file = Self::open_file(&config.path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            config: config.clone(),
+            key,
+            file,
+            len,
+            next_seq,
+            last_mac,
+            dropped: 0,
+        })
+    }
+
+    /// Open the audit log for sharing between clients
+    pub fn open_shared(config: &AuditLogConfig) -> Result<SharedAuditLog, AuditLogError> {
+        Ok(Arc::new(Mutex::new(Self::open(config)?)))
+    }
+
+    /// Open `path` for appending
+    fn open_file(path: &Path) -> Result<File, AuditLogError> {
+        let mut options = fs::OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        Ok(options.open(path)?)
+    }
+
+    /// Append a record of `entry` to the log. If records could not be written since the last
+    /// one that was, a record of how many were lost is appended first. A record that cannot
+    /// be written is counted as lost.
+    pub fn append(&mut self, entry: AuditEntry) -> Result<(), AuditLogError> {
+        if self.dropped > 0 {
+            let dropped = AuditEntry::Dropped {
+                records: self.dropped,
+            };
+            if let Err(e) = self.write_record(dropped) {
+                self.dropped = self.dropped.saturating_add(1);
+                return Err(e);
+            }
+            self.dropped = 0;
+        }
+        let next_seq = self.next_seq;
+        let result = self.write_record(entry);
+        if result.is_err() && self.next_seq == next_seq {
+            self.dropped = self.dropped.saturating_add(1);
+        }
+        result
+    }
+
+    /// Write a record of `entry` to the log, and then name it in the anchor. A record that
+    /// fails part way through is truncated away, so the log never holds a partial line.
+    fn write_record(&mut self, entry: AuditEntry) -> Result<(), AuditLogError> {
+        let timestamp = get_epoch_time_secs();
+        let prev_mac = to_hex(&self.last_mac);
+        let mac = record_mac(&self.key, self.next_seq, timestamp, &entry, &prev_mac);
+        let record = AuditRecord {
+            seq: self.next_seq,
+            timestamp,
+            entry,
+            prev_mac,
+            mac: to_hex(&mac),
+        };
+        let mut line =
+            serde_json::to_string(&record).expect("BUG: failed to serialize an audit record");
+        line.push('\n');
+        if self.len > 0 && self.len.saturating_add(line.len() as u64) > self.config.max_bytes {
+            self.rotate()?;
+        }
+        if let Err(e) = self
+            .file
+            .write_all(line.as_bytes())
+            .and_then(|_| self.file.flush())
+        {
+            let _ = self.file.set_len(self.len);
+            return Err(e.into());
+        }
+        self.len = self.len.saturating_add(line.len() as u64);
+        self.last_mac = mac;
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.saturating_add(1);
+        write_anchor(&self.config.anchor_path, &self.key, seq, &mac)
+    }
+
+    /// Shift the rotated files back by one, dropping the oldest, and start a new current file
+    fn rotate(&mut self) -> Result<(), AuditLogError> {
+        let path = &self.config.path;
+        for n in (1..self.config.max_files).rev() {
+            let from = rotated_path(path, n);
+            if from.exists() {
+                fs::rename(from, rotated_path(path, n + 1))?;
+            }
+        }
+        if self.config.max_files > 0 {
+            fs::rename(path, rotated_path(path, 1))?;
+        } else {
+            fs::remove_file(path)?;
+        }
+        self.file = Self::open_file(path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+/// Append a record of `entry` to `audit_log`. An unwritable log must not stop the signer
+/// from signing, so a failure is not returned. It is not silent either: it is logged and
+/// counted, and the log records how many records were lost once it is writable again.
+pub fn record(audit_log: &SharedAuditLog, entry: AuditEntry) {
+    let result = match audit_log.lock() {
+        Ok(mut audit_log) => audit_log.append(entry),
+        Err(poisoned) => poisoned.into_inner().append(entry),
+    };
+    if let Err(e) = result {
+        error!("Failed to write to the audit log: {e}");
+        crate::monitoring::increment_audit_log_write_errors();
+    }
+}
+
+/// Verify the chain of the audit log at `path` under `key`, from its oldest rotated file to
+/// the current one, and that it ends at the record named by the anchor at `anchor_path`,
+/// returning the number of records checked. The first record of the oldest file is trusted
+/// to chain from whatever preceded it, unless it is the log's first record.
+pub fn verify_log(path: &Path, key: &[u8; 32], anchor_path: &Path) -> Result<u64, AuditLogError> {
+    let mut files = vec![];
+    let mut n = 1;
+    while rotated_path(path, n).exists() {
+        files.push(rotated_path(path, n));
+        n += 1;
+    }
+    files.reverse();
+    if path.exists() || files.is_empty() {
+        files.push(path.to_path_buf());
+    }
+
+    let mut count = 0;
+    let mut last: Option<AuditRecord> = None;
+    for file in files {
+        for (i, record) in read_records(&file)?.into_iter().enumerate() {
+            let broken = |reason: String| AuditLogError::BrokenChain {
+                file: file.display().to_string(),
+                line: i + 1,
+                reason,
+            };
+            match &last {
+                Some(prev) => {
+                    if record.seq != prev.seq.saturating_add(1) {
+                        return Err(broken(format!(
+                            "expected record {}, found record {}",
+                            prev.seq.saturating_add(1),
+                            record.seq
+                        )));
+                    }
+                    if record.prev_mac != prev.mac {
+                        return Err(broken("does not chain from the previous record".into()));
+                    }
+                }
+                None if record.seq == 0 && record.prev_mac != to_hex(&GENESIS_MAC) => {
+                    return Err(broken("first record does not chain from genesis".into()));
+                }
+                None => {}
+            }
+            let mac = record_mac(
+                key,
+                record.seq,
+                record.timestamp,
+                &record.entry,
+                &record.prev_mac,
+            );
+            if to_hex(&mac) != record.mac {
+                return Err(broken(format!("MAC mismatch on record {}", record.seq)));
+            }
+            last = Some(record);
+            count += 1;
+        }
+    }
+    check_anchor(read_anchor(anchor_path, key)?.as_ref(), last.as_ref())?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config for a new audit log in a fresh temporary directory
+    fn tmp_config(max_bytes: u64, max_files: u32) -> AuditLogConfig {
+        let dir = std::env::temp_dir().join(format!(
+            "stacks-signer-audit-{}-{}",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+        AuditLogConfig {
+            key_path: AuditLogConfig::default_key_path(&path),
+            anchor_path: AuditLogConfig::default_anchor_path(&path),
+            path,
+            max_bytes,
+            max_files,
+        }
+    }
+
+    fn tx_entry(i: u32) -> AuditEntry {
+        AuditEntry::Transaction {
+            txid: to_hex(&[i as u8; 32]),
+            payload: format!("contract-call test-{i}"),
+        }
+    }
+
+    #[test]
+    fn appended_records_verify() {
+        let config = tmp_config(AUDIT_LOG_MAX_BYTES, AUDIT_LOG_MAX_FILES);
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        for i in 0..5 {
+            audit_log.append(tx_entry(i)).unwrap();
+        }
+        drop(audit_log);
+
+        // reopening continues the chain
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        audit_log.append(tx_entry(5)).unwrap();
+
+        let key = read_key(&config.key_path).unwrap();
+        assert_eq!(
+            verify_log(&config.path, &key, &config.anchor_path).unwrap(),
+            6
+        );
+        assert!(matches!(
+            verify_log(&config.path, &[1u8; 32], &config.anchor_path),
+            Err(AuditLogError::BrokenChain { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn tampered_middle_record_breaks_the_chain() {
+        let config = tmp_config(AUDIT_LOG_MAX_BYTES, AUDIT_LOG_MAX_FILES);
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        for i in 0..3 {
+            audit_log.append(tx_entry(i)).unwrap();
+        }
+        let key = read_key(&config.key_path).unwrap();
+
+        let contents = fs::read_to_string(&config.path).unwrap();
+        let mut lines: Vec<_> = contents.lines().map(str::to_string).collect();
+        lines[1] = lines[1].replace("test-1", "test-7");
+        fs::write(&config.path, lines.join("\n") + "\n").unwrap();
+        assert!(matches!(
+            verify_log(&config.path, &key, &config.anchor_path),
+            Err(AuditLogError::BrokenChain { line: 2, .. })
+        ));
+
+        // dropping the middle record is caught too
+        lines.remove(1);
+        fs::write(&config.path, lines.join("\n") + "\n").unwrap();
+        assert!(matches!(
+            verify_log(&config.path, &key, &config.anchor_path),
+            Err(AuditLogError::BrokenChain { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn chain_continues_across_rotations() {
+        let config = tmp_config(1, 2);
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        for i in 0..4 {
+            audit_log.append(tx_entry(i)).unwrap();
+        }
+        // one record per file, and only the two most recent rotated files are kept
+        assert!(rotated_path(&config.path, 2).exists());
+        assert!(!rotated_path(&config.path, 3).exists());
+        let key = read_key(&config.key_path).unwrap();
+        assert_eq!(
+            verify_log(&config.path, &key, &config.anchor_path).unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn records_removed_from_the_end_are_detected() {
+        let config = tmp_config(AUDIT_LOG_MAX_BYTES, AUDIT_LOG_MAX_FILES);
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        for i in 0..3 {
+            audit_log.append(tx_entry(i)).unwrap();
+        }
+        drop(audit_log);
+        let key = read_key(&config.key_path).unwrap();
+
+        let contents = fs::read_to_string(&config.path).unwrap();
+        let lines: Vec<_> = contents.lines().collect();
+        fs::write(&config.path, lines[..2].join("\n") + "\n").unwrap();
+        assert!(matches!(
+            verify_log(&config.path, &key, &config.anchor_path),
+            Err(AuditLogError::AnchorMismatch(_))
+        ));
+        assert!(matches!(
+            AuditLog::open(&config),
+            Err(AuditLogError::AnchorMismatch(_))
+        ));
+
+        // emptying the log is caught too, and so is removing the anchor
+        fs::write(&config.path, "").unwrap();
+        assert!(matches!(
+            AuditLog::open(&config),
+            Err(AuditLogError::AnchorMismatch(_))
+        ));
+        fs::write(&config.path, lines[..2].join("\n") + "\n").unwrap();
+        fs::remove_file(&config.anchor_path).unwrap();
+        assert!(matches!(
+            AuditLog::open(&config),
+            Err(AuditLogError::AnchorMismatch(_))
+        ));
+    }
+
+    #[test]
+    fn log_may_be_one_record_ahead_of_its_anchor() {
+        let config = tmp_config(AUDIT_LOG_MAX_BYTES, AUDIT_LOG_MAX_FILES);
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        audit_log.append(tx_entry(0)).unwrap();
+        let anchor = fs::read(&config.anchor_path).unwrap();
+        audit_log.append(tx_entry(1)).unwrap();
+        drop(audit_log);
+
+        // as if the signer stopped before writing the second record's anchor
+        fs::write(&config.anchor_path, anchor).unwrap();
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        audit_log.append(tx_entry(2)).unwrap();
+        let key = read_key(&config.key_path).unwrap();
+        assert_eq!(
+            verify_log(&config.path, &key, &config.anchor_path).unwrap(),
+            3
+        );
+    }
+
+    #[test]
+    fn open_reads_the_last_record_from_the_end_of_the_log() {
+        let config = tmp_config(AUDIT_LOG_MAX_BYTES, AUDIT_LOG_MAX_FILES);
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        for i in 0..100 {
+            audit_log.append(tx_entry(i)).unwrap();
+        }
+        assert!(audit_log.len > 2 * TAIL_READ_BYTES);
+        drop(audit_log);
+
+        let last = read_last_record(&config.path).unwrap().unwrap();
+        assert_eq!(last.seq, 99);
+        assert_eq!(last.entry, tx_entry(99));
+        let audit_log = AuditLog::open(&config).unwrap();
+        assert_eq!(audit_log.next_seq, 100);
+    }
+
+    #[test]
+    fn lost_records_are_recorded_once_the_log_is_writable() {
+        let config = tmp_config(AUDIT_LOG_MAX_BYTES, AUDIT_LOG_MAX_FILES);
+        let mut audit_log = AuditLog::open(&config).unwrap();
+        audit_log.append(tx_entry(0)).unwrap();
+
+        // a read-only handle fails every write
+        audit_log.file = File::open(&config.path).unwrap();
+        assert!(audit_log.append(tx_entry(1)).is_err());
+        assert!(audit_log.append(tx_entry(2)).is_err());
+        assert_eq!(audit_log.dropped, 2);
+
+        audit_log.file = AuditLog::open_file(&config.path).unwrap();
+        audit_log.append(tx_entry(3)).unwrap();
+        let entries: Vec<_> = read_records(&config.path)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.entry)
+            .collect();
+        assert_eq!(
+            entries,
+            vec![tx_entry(0), AuditEntry::Dropped { records: 2 }, tx_entry(3)]
+        );
+        let key = read_key(&config.key_path).unwrap();
+        assert_eq!(
+            verify_log(&config.path, &key, &config.anchor_path).unwrap(),
+            3
+        );
+    }
+}
@@ -25,7 +25,13 @@ use stacks_common::address::{
     C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_MULTISIG,
     C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
-use stacks_common::types::chainstate::StacksPrivateKey;
+use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+use stacks_common::types::Address;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use stacks_common::util::secp256k1::MessageSignature;
+use wsts::curve::point::Point;
+
+use crate::client::aggregate_key_from_hex;
 
 extern crate alloc;
 
@@ -55,6 +61,28 @@ pub enum Command {
     GenerateStackingSignature(GenerateStackingSignatureArgs),
     /// Check a configuration file and output config information
     CheckConfig(RunSignerArgs),
+    /// Vote for an aggregate public key manually, e.g. to recover from a failed DKG
+    VoteAggregateKey(VoteAggregateKeyArgs),
+    /// Print the latest message in each of the signer's StackerDB slots
+    InspectSlots(InspectSlotsArgs),
+    /// Encrypt the plaintext Stacks private key of a signer config into a key file
+    EncryptKey(EncryptKeyArgs),
+    /// Work with the audit log of the chunks and transactions the signer published
+    #[command(subcommand)]
+    Audit(AuditCommand),
+    /// Check a signer's signature over a block's signer signature hash, or recover the key
+    /// that made it
+    VerifyResponse(VerifyResponseArgs),
+    /// Print the signers added, removed, or reweighted between the signer sets of two reward
+    /// cycles
+    DiffCycles(DiffCyclesArgs),
+}
+
+/// Subcommands for the audit log
+#[derive(clap::Subcommand, Debug)]
+pub enum AuditCommand {
+    /// Verify that no record of the audit log was altered, removed, or inserted
+    Verify(AuditVerifyArgs),
 }
 
 /// Basic arguments for all cyrptographic and stacker-db functionality
@@ -196,6 +224,135 @@ pub struct GenerateStackingSignatureArgs {
     pub json: bool,
 }
 
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the vote-aggregate-key command
+pub struct VoteAggregateKeyArgs {
+    /// Path to signer config file
+    #[arg(long, short, value_name = "FILE")]
+    pub config: PathBuf,
+    /// The aggregate public key to vote for, as a hex-encoded compressed point
+    #[arg(long, value_parser = parse_aggregate_key)]
+    pub aggregate_key: Point,
+    /// The reward cycle the aggregate key is for
+    #[arg(short, long)]
+    pub reward_cycle: u64,
+    /// The voting round to cast the vote in
+    #[arg(long)]
+    pub round: u64,
+    /// The fee to pay for the vote transaction in uSTX. Defaults to the config's `tx_fee_ustx`
+    #[arg(long)]
+    pub fee_ustx: Option<u64>,
+    /// Print the serialized vote transaction instead of submitting it
+    #[arg(long, action=ArgAction::SetTrue, required=false)]
+    pub dry_run: bool,
+    /// Wait up to this many seconds for the vote to be counted, voting again if it can no longer be
+    /// counted as submitted
+    #[arg(long, value_name = "SECONDS")]
+    pub wait: Option<u64>,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the inspect-slots command
+pub struct InspectSlotsArgs {
+    /// Path to signer config file
+    #[arg(long, short, value_name = "FILE")]
+    pub config: PathBuf,
+    /// The reward cycle whose signers StackerDBs to inspect
+    #[arg(short, long)]
+    pub reward_cycle: u64,
+    /// Also print the block response slots of all the other signers
+    #[arg(long, action=ArgAction::SetTrue, required=false)]
+    pub all_block_responses: bool,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the encrypt-key command
+pub struct EncryptKeyArgs {
+    /// Path to the signer config file holding the plaintext key
+    #[arg(long, short, value_name = "FILE")]
+    pub config: PathBuf,
+    /// Path to write the encrypted key file to. Must not already exist.
+    #[arg(long, short, value_name = "FILE")]
+    pub output: PathBuf,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the audit verify command
+pub struct AuditVerifyArgs {
+    /// Path to the current audit log file. Its rotated files are verified too.
+    #[arg(long, short, value_name = "FILE")]
+    pub log: PathBuf,
+    /// Path to the audit log's key file. Defaults to the log path with a `.key` suffix.
+    #[arg(long, short, value_name = "FILE")]
+    pub key: Option<PathBuf>,
+    /// Path to the audit log's anchor file. Defaults to the log path with a `.anchor` suffix.
+    #[arg(long, short, value_name = "FILE")]
+    pub anchor: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the verify-response command
+pub struct VerifyResponseArgs {
+    /// The signer signature hash of the block, as hex
+    #[arg(long, value_parser = parse_sighash)]
+    pub sighash: Sha512Trunc256Sum,
+    /// The signer's recoverable signature, as 65 bytes of hex
+    #[arg(long, value_parser = parse_message_signature)]
+    pub signature: MessageSignature,
+    /// The signer's public key as hex, or its c32 address. If omitted, the key that made the
+    /// signature is printed instead.
+    #[arg(long, value_parser = parse_signer_key)]
+    pub pubkey: Option<SignerKey>,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the diff-cycles command
+pub struct DiffCyclesArgs {
+    /// Path to signer config file
+    #[arg(long, short, value_name = "FILE")]
+    pub config: PathBuf,
+    /// The reward cycle to compare from
+    #[arg(long)]
+    pub from: u64,
+    /// The reward cycle to compare to
+    #[arg(long)]
+    pub to: u64,
+    /// Output the differences in JSON format
+    #[arg(long, action=ArgAction::SetTrue, required=false)]
+    pub json: bool,
+}
+
+/// A signer's key, as given on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignerKey {
+    /// The signer's public key
+    PublicKey(StacksPublicKey),
+    /// The address of the signer's public key
+    Address(StacksAddress),
+}
+
+/// Parse a hex-encoded signer signature hash, with or without a `0x` prefix
+pub fn parse_sighash(sighash: &str) -> Result<Sha512Trunc256Sum, String> {
+    Sha512Trunc256Sum::from_hex(sighash.strip_prefix("0x").unwrap_or(sighash))
+        .map_err(|e| format!("Invalid signer signature hash: {e}"))
+}
+
+/// Parse a hex-encoded recoverable signature, with or without a `0x` prefix
+pub fn parse_message_signature(signature: &str) -> Result<MessageSignature, String> {
+    MessageSignature::from_hex(signature.strip_prefix("0x").unwrap_or(signature))
+        .map_err(|e| format!("Invalid signature: {e}"))
+}
+
+/// Parse a signer's hex-encoded public key, with or without a `0x` prefix, or its c32 address
+pub fn parse_signer_key(key: &str) -> Result<SignerKey, String> {
+    if let Some(address) = StacksAddress::from_string(key) {
+        return Ok(SignerKey::Address(address));
+    }
+    StacksPublicKey::from_hex(key.strip_prefix("0x").unwrap_or(key))
+        .map(SignerKey::PublicKey)
+        .map_err(|e| format!("Invalid public key or address: {e}"))
+}
+
 /// Parse the contract ID
 fn parse_contract(contract: &str) -> Result<QualifiedContractIdentifier, String> {
     QualifiedContractIdentifier::parse(contract).map_err(|e| format!("Invalid contract: {}", e))
@@ -223,6 +380,11 @@ pub fn parse_pox_addr(pox_address_literal: &str) -> Result<PoxAddress, String> {
     }
 }
 
+/// Parse a hex-encoded compressed point, with or without a `0x` prefix
+pub fn parse_aggregate_key(aggregate_key: &str) -> Result<Point, String> {
+    aggregate_key_from_hex(aggregate_key).map_err(|e| format!("Invalid aggregate key: {e}"))
+}
+
 /// Parse the hexadecimal Stacks private key
 fn parse_private_key(private_key: &str) -> Result<StacksPrivateKey, String> {
     StacksPrivateKey::from_hex(private_key).map_err(|e| format!("Invalid private key: {}", e))
@@ -248,7 +410,8 @@ mod tests {
     use blockstack_lib::chainstate::stacks::address::{PoxAddressType20, PoxAddressType32};
     use blockstack_lib::util_lib::signed_structured_data::pox4::make_pox_4_signer_key_message_hash;
     use clarity::consts::CHAIN_ID_TESTNET;
-    use clarity::util::hash::Sha256Sum;
+    use clarity::util::hash::{to_hex, Sha256Sum};
+    use wsts::curve::scalar::Scalar;
 
     use super::*;
 
@@ -279,6 +442,39 @@ mod tests {
             .expect("Expected version to be a uint")
     }
 
+    #[test]
+    fn test_parse_signer_key() {
+        let pubkey = StacksPublicKey::from_private(&StacksPrivateKey::new());
+        let address = StacksAddress::p2pkh(false, &pubkey);
+        assert_eq!(
+            parse_signer_key(&pubkey.to_hex()),
+            Ok(SignerKey::PublicKey(pubkey.clone()))
+        );
+        assert_eq!(
+            parse_signer_key(&format!("0x{}", pubkey.to_hex())),
+            Ok(SignerKey::PublicKey(pubkey))
+        );
+        assert_eq!(
+            parse_signer_key(&address.to_string()),
+            Ok(SignerKey::Address(address))
+        );
+        assert!(parse_signer_key("not a key").is_err());
+
+        let sighash = Sha512Trunc256Sum([0xab; 32]);
+        assert_eq!(parse_sighash(&to_hex(&sighash.0)), Ok(sighash));
+        assert_eq!(
+            parse_sighash(&format!("0x{}", to_hex(&sighash.0))),
+            Ok(sighash)
+        );
+        assert!(parse_sighash(&to_hex(&[0xab; 20])).is_err());
+        let signature = MessageSignature([0x01; 65]);
+        assert_eq!(
+            parse_message_signature(&format!("0x{}", to_hex(&signature.0))),
+            Ok(signature)
+        );
+        assert!(parse_message_signature(&to_hex(&[0x01; 64])).is_err());
+    }
+
     #[test]
     fn test_parse_pox_addr() {
         let tr = "bc1p8vg588hldsnv4a558apet4e9ff3pr4awhqj2hy8gy6x2yxzjpmqsvvpta4";
@@ -404,4 +600,17 @@ mod tests {
             _ => panic!("Invalid parsed address"),
         }
     }
+
+    #[test]
+    fn test_parse_aggregate_key() {
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let hex = to_hex(point.compress().as_bytes());
+        assert_eq!(parse_aggregate_key(&hex).unwrap(), point);
+        assert_eq!(parse_aggregate_key(&format!("0x{hex}")).unwrap(), point);
+
+        // not hex, too short, and not a point on the curve
+        assert!(parse_aggregate_key("zz").is_err());
+        assert!(parse_aggregate_key(&hex[..64]).is_err());
+        assert!(parse_aggregate_key(&format!("02{}", "ff".repeat(32))).is_err());
+    }
 }
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+/// The interface to the stacks node that the signer's logic is tested against
+pub(crate) mod node_api;
 /// The stacker db module for communicating with the stackerdb contract
 mod stackerdb;
 /// The stacks node client module for communicating with the stacks node
@@ -21,14 +23,20 @@ pub(crate) mod stacks_client;
 
 use std::time::Duration;
 
+use blockstack_lib::burnchains::Txid;
+use clarity::util::hash::hex_decode_into;
 use clarity::vm::errors::Error as ClarityError;
 use clarity::vm::types::serialization::SerializationError;
+use libsigner::RPCError;
 use libstackerdb::Error as StackerDBError;
+pub use node_api::StacksNodeApi;
 use slog::slog_debug;
 pub use stackerdb::*;
 pub use stacks_client::*;
 use stacks_common::codec::Error as CodecError;
 use stacks_common::debug;
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+use wsts::curve::point::{Compressed, Point};
 
 /// Backoff timer initial interval in milliseconds
 const BACKOFF_INITIAL_INTERVAL: u64 = 128;
@@ -50,6 +58,9 @@ pub enum ClientError {
     /// Stacker-db instance rejected the chunk
     #[error("Stacker-db rejected the chunk. Reason: {0}")]
     PutChunkRejected(String),
+    /// Failed to send a chunk to the stacks node
+    #[error("Failed to send the chunk to the stacks node: {0}")]
+    PutChunkFailed(#[from] RPCError),
     /// Failed to call a read only function
     #[error("Failed to call read only function. {0}")]
     ReadOnlyFailure(String),
@@ -86,6 +97,85 @@ pub enum ClientError {
     /// Invalid response from the stacks node
     #[error("Invalid response from the stacks node: {0}")]
     InvalidResponse(String),
+    /// A submitted transaction was not confirmed in time
+    #[error("Transaction {0} was not confirmed in time")]
+    ConfirmationTimeout(Txid),
+    /// The stacks node rate limited writes to a StackerDB slot
+    #[error("Stacker-db slot is rate limited. Retry after {retry_after:?}")]
+    SlotRateLimited {
+        /// How long until the slot may be written again
+        retry_after: Duration,
+    },
+    /// The stacks node does not have the requested block
+    #[error("Stacks node does not have block {0}")]
+    UnknownBlock(StacksBlockId),
+    /// The stacks node's current tenure is not the requested one
+    #[error("Stacks node's current tenure is not {0}")]
+    UnknownTenure(ConsensusHash),
+    /// The stacks node has no value for the requested contract data
+    #[error("Not found: {0}")]
+    NotFound(String),
+    /// A hex string in a stacks node response could not be decoded
+    #[error("Malformed hex string: {0}")]
+    MalformedHex(String),
+}
+
+/// The digits of a hex string from a stacks node response. Nodes differ in whether they prefix
+/// hex with `0x`, so the prefix is optional, and surrounding whitespace is ignored. Errors on an
+/// odd number of digits, or on a non-hex character, naming its position in the trimmed string.
+pub fn hex_digits(hex: &str) -> Result<&str, ClientError> {
+    let hex = hex.trim();
+    let (prefix_len, digits) = match hex.strip_prefix("0x") {
+        Some(digits) => (2, digits),
+        None => (0, hex),
+    };
+    if let Some((pos, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        return Err(ClientError::MalformedHex(format!(
+            "non-hex character {c:?} at position {}",
+            prefix_len + pos
+        )));
+    }
+    if digits.len() % 2 != 0 {
+        return Err(ClientError::MalformedHex(format!(
+            "odd number of hex digits ({})",
+            digits.len()
+        )));
+    }
+    Ok(digits)
+}
+
+/// Decode a hex string from a stacks node response, as [`hex_digits`] reads it
+pub fn parse_hex_flexible(hex: &str) -> Result<Vec<u8>, ClientError> {
+    let digits = hex_digits(hex)?;
+    let mut bytes = vec![0u8; digits.len() / 2];
+    hex_decode_into(digits, &mut bytes).map_err(|e| ClientError::MalformedHex(e.to_string()))?;
+    Ok(bytes)
+}
+
+/// Convert a compressed point to the aggregate public key it encodes. The point may be invalid
+/// even if it was voted upon, since pox-4 cannot prevent that.
+pub fn aggregate_key_from_bytes(bytes: &[u8]) -> Result<Point, ClientError> {
+    let compressed = Compressed::try_from(bytes).map_err(|e| {
+        ClientError::MalformedClarityValue(format!(
+            "Failed to convert aggregate public key to compressed data: {e}"
+        ))
+    })?;
+    Point::try_from(&compressed).map_err(|e| {
+        ClientError::MalformedClarityValue(format!(
+            "Failed to convert aggregate public key to a point: {e}"
+        ))
+    })
+}
+
+/// Decode a hex-encoded compressed point, as [`hex_digits`] reads it, as an aggregate public
+/// key, without allocating
+pub fn aggregate_key_from_hex(hex: &str) -> Result<Point, ClientError> {
+    let digits = hex_digits(hex)?;
+    // the size of a compressed point
+    let mut bytes = [0u8; 33];
+    let len = hex_decode_into(digits, &mut bytes)
+        .map_err(|e| ClientError::MalformedHex(e.to_string()))?;
+    aggregate_key_from_bytes(&bytes[..len])
 }
 
 /// Retry a function F with an exponential backoff and notification on transient failure
@@ -111,7 +201,8 @@ where
 #[cfg(test)]
 pub(crate) mod tests {
     use std::io::{Read, Write};
-    use std::net::{SocketAddr, TcpListener};
+    use std::net::{SocketAddr, TcpListener, TcpStream};
+    use std::time::Instant;
 
     use blockstack_lib::chainstate::stacks::boot::POX_4_NAME;
     use blockstack_lib::net::api::getaccount::AccountEntryResponse;
@@ -129,6 +220,7 @@ pub(crate) mod tests {
     use rand::distributions::Standard;
     use rand::{thread_rng, Rng};
     use rand_core::{OsRng, RngCore};
+    use stacks_common::consts::SIGNER_SLOTS_PER_USER;
     use stacks_common::types::chainstate::{
         BlockHeaderHash, ConsensusHash, StacksAddress, StacksPrivateKey, StacksPublicKey,
     };
@@ -141,6 +233,33 @@ pub(crate) mod tests {
 
     use super::*;
     use crate::config::{GlobalConfig, SignerConfig};
+    use crate::health::HealthState;
+
+    #[test]
+    fn hex_is_parsed_with_or_without_a_prefix() {
+        for hex in ["0a0B", "0x0a0B", " 0x0a0B\n", "\t0a0B "] {
+            assert_eq!(
+                parse_hex_flexible(hex).unwrap(),
+                vec![0x0a, 0x0b],
+                "{hex:?}"
+            );
+        }
+        assert!(parse_hex_flexible("").unwrap().is_empty());
+        assert!(parse_hex_flexible("0x").unwrap().is_empty());
+
+        let error = |hex| match parse_hex_flexible(hex) {
+            Err(ClientError::MalformedHex(reason)) => reason,
+            res => panic!("Expected {hex:?} to be malformed, got {res:?}"),
+        };
+        assert_eq!(error("0x0a0"), "odd number of hex digits (3)");
+        assert_eq!(error("0x0a0g"), "non-hex character 'g' at position 5");
+        assert_eq!(error(" 0azz"), "non-hex character 'z' at position 2");
+        // a prefix is only stripped once, and only in lowercase
+        assert_eq!(error("0x0x0a"), "non-hex character 'x' at position 3");
+        assert_eq!(error("0X0a"), "non-hex character 'X' at position 1");
+        // multi-byte characters are reported by their byte position
+        assert_eq!(error("0aé0"), "non-hex character 'é' at position 2");
+    }
 
     pub struct MockServerClient {
         pub server: TcpListener,
@@ -191,18 +310,80 @@ pub(crate) mod tests {
         TcpListener::bind(config.node_host.to_string()).unwrap()
     }
 
-    /// Write a response to the mock server and return the request bytes
+    /// Write a response to the mock server and return the first 1024 bytes of the request
     pub fn write_response(mock_server: TcpListener, bytes: &[u8]) -> [u8; 1024] {
         debug!("Writing a response...");
         let mut request_bytes = [0u8; 1024];
         {
             let mut stream = mock_server.accept().unwrap().0;
-            let _ = stream.read(&mut request_bytes).unwrap();
+            let request = read_request(&mut stream);
+            let len = request.len().min(request_bytes.len());
+            request_bytes[..len].copy_from_slice(&request[..len]);
             stream.write_all(bytes).unwrap();
         }
         request_bytes
     }
 
+    /// How long a mock server waits on each scripted request before failing the test
+    const MOCK_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Read an HTTP request, including any `Content-Length` body, off of `stream`. Clients may
+    /// write the headers and the body separately, so a single read is not enough.
+    pub fn read_request(stream: &mut TcpStream) -> Vec<u8> {
+        let mut request = vec![];
+        let mut byte = [0u8; 1];
+        while !request.ends_with(b"\r\n\r\n") {
+            stream.read_exact(&mut byte).unwrap();
+            request.push(byte[0]);
+        }
+        let content_length = String::from_utf8_lossy(&request)
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().unwrap())
+            })
+            .unwrap_or(0);
+        let headers_len = request.len();
+        request.resize(headers_len + content_length, 0);
+        stream.read_exact(&mut request[headers_len..]).unwrap();
+        request
+    }
+
+    /// Answer each request in `script` in order with its response. Each request's request
+    /// line must start with the scripted method and path, and it must arrive in time, so a
+    /// client that sends something else fails the test instead of hanging it.
+    pub fn write_scripted_responses(mock_server: &TcpListener, script: &[(String, Vec<u8>)]) {
+        mock_server.set_nonblocking(true).unwrap();
+        for (expected, response) in script {
+            let deadline = Instant::now() + MOCK_REQUEST_TIMEOUT;
+            let mut stream = loop {
+                match mock_server.accept() {
+                    Ok((stream, _)) => break stream,
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        assert!(
+                            Instant::now() < deadline,
+                            "Timed out waiting for request {expected:?}"
+                        );
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                    Err(e) => panic!("Failed to accept request {expected:?}: {e}"),
+                }
+            };
+            stream.set_nonblocking(false).unwrap();
+            stream.set_read_timeout(Some(MOCK_REQUEST_TIMEOUT)).unwrap();
+            let request = read_request(&mut stream);
+            let request = String::from_utf8_lossy(&request);
+            let request_line = request.lines().next().unwrap_or_default();
+            assert!(
+                request_line.starts_with(&format!("{expected} ")),
+                "Expected request {expected:?}, got {request_line:?}"
+            );
+            stream.write_all(response).unwrap();
+        }
+        mock_server.set_nonblocking(false).unwrap();
+    }
+
     pub fn generate_random_consensus_hash() -> ConsensusHash {
         let rng = rand::thread_rng();
         let bytes: Vec<u8> = rng.sample_iter(Standard).take(20).collect();
@@ -401,6 +582,18 @@ pub(crate) mod tests {
         format!("HTTP/1.1 200 OK\n\n{{\"okay\":true,\"result\":\"{hex}\"}}")
     }
 
+    /// Build a response for a map entry request, as the node returns it for a present or absent entry
+    pub fn build_map_entry_response(value: Option<ClarityValue>) -> String {
+        let value = match value {
+            Some(value) => ClarityValue::some(value).expect("Failed to create optional value"),
+            None => ClarityValue::none(),
+        };
+        let hex = value
+            .serialize_to_hex()
+            .expect("Failed to serialize hex value");
+        format!("HTTP/1.1 200 OK\n\n{{\"data\":\"0x{hex}\"}}")
+    }
+
     /// Build a response for the get_medium_estimated_fee_ustx_response request with a specific medium estimate
     pub fn build_get_medium_estimated_fee_ustx_response(
         medium_estimate: u64,
@@ -558,6 +751,13 @@ pub(crate) mod tests {
             tx_fee_ustx: config.tx_fee_ustx,
             max_tx_fee_ustx: config.max_tx_fee_ustx,
             db_path: config.db_path.clone(),
+            health: HealthState::new_shared(),
+            payload_envelope: config.payload_envelope,
+            max_node_staleness: config.max_node_staleness,
+            audit_log: None,
+            block_response_delay_step: config.block_response_delay_step,
+            block_response_max_delay: config.block_response_max_delay,
+            dry_run: config.dry_run,
         }
     }
 
@@ -584,4 +784,29 @@ pub(crate) mod tests {
         let clarity_value = ClarityValue::UInt(threshold as u128);
         build_read_only_response(&clarity_value)
     }
+
+    /// Build a response for the get_signer_slots request, with one slot for each of `signers`
+    pub fn build_get_signer_slots_response(signers: &[StacksAddress]) -> String {
+        let slots = signers
+            .iter()
+            .map(|signer| {
+                ClarityValue::Tuple(
+                    TupleData::from_data(vec![
+                        ("signer".into(), ClarityValue::Principal((*signer).into())),
+                        (
+                            "num-slots".into(),
+                            ClarityValue::UInt(SIGNER_SLOTS_PER_USER.into()),
+                        ),
+                    ])
+                    .expect("BUG: Failed to create clarity value from tuple data"),
+                )
+            })
+            .collect();
+        let clarity_value = ClarityValue::okay(
+            ClarityValue::cons_list_unsanitized(slots)
+                .expect("BUG: Failed to create clarity value from list data"),
+        )
+        .expect("BUG: Failed to create clarity value from list data");
+        build_read_only_response(&clarity_value)
+    }
 }
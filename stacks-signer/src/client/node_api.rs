@@ -0,0 +1,499 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use blockstack_lib::burnchains::Txid;
+use blockstack_lib::chainstate::stacks::boot::NakamotoSignerEntry;
+use blockstack_lib::chainstate::stacks::StacksTransaction;
+use clarity::vm::{ClarityName, ContractName, Value as ClarityValue};
+use stacks_common::types::chainstate::StacksAddress;
+use wsts::curve::point::Point;
+
+use crate::client::{ClientError, StacksClient};
+use crate::runloop::RewardCycleInfo;
+
+/// The requests the signer makes of its stacks node, so that the logic making them can be
+/// tested against a `testing::MockStacksNode` instead of a socket. Writes to the signers'
+/// StackerDBs go through `StackerDB`, which has its own session with the node.
+pub trait StacksNodeApi {
+    /// Make a read only contract call to a stacks contract
+    fn read_only_contract_call(
+        &self,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        function_name: &ClarityName,
+        function_args: &[ClarityValue],
+    ) -> Result<ClarityValue, ClientError>;
+
+    /// Get the current reward cycle info
+    fn get_current_reward_cycle_info(&self) -> Result<RewardCycleInfo, ClientError>;
+
+    /// Get the reward set signers of `reward_cycle`
+    fn get_reward_set_signers(
+        &self,
+        reward_cycle: u64,
+    ) -> Result<Option<Vec<NakamotoSignerEntry>>, ClientError>;
+
+    /// Get the approved aggregate public key of `reward_cycle`
+    fn get_approved_aggregate_key(&self, reward_cycle: u64) -> Result<Option<Point>, ClientError>;
+
+    /// Get the aggregate public key `signer` voted for in `round` of `reward_cycle`
+    fn get_vote_for_aggregate_public_key(
+        &self,
+        round: u64,
+        reward_cycle: u64,
+        signer: StacksAddress,
+    ) -> Result<Option<Point>, ClientError>;
+
+    /// Get the last aggregate public key voting round of `reward_cycle`
+    fn get_last_round(&self, reward_cycle: u64) -> Result<Option<u64>, ClientError>;
+
+    /// Get the weight voted so far in `round_id` of `reward_cycle`
+    fn get_round_vote_weight(
+        &self,
+        reward_cycle: u64,
+        round_id: u64,
+    ) -> Result<Option<u128>, ClientError>;
+
+    /// Get the weight needed to approve an aggregate public key in `reward_cycle`
+    fn get_vote_threshold_weight(&self, reward_cycle: u64) -> Result<u128, ClientError>;
+
+    /// Get the current account nonce of `address`
+    fn get_account_nonce(&self, address: &StacksAddress) -> Result<u64, ClientError>;
+
+    /// Vote for `dkg_public_key` as the aggregate public key of `reward_cycle` in `round`,
+    /// returning the submitted vote transaction
+    fn cast_aggregate_public_key_vote(
+        &self,
+        signer_index: u32,
+        round: u64,
+        dkg_public_key: Point,
+        reward_cycle: u64,
+        tx_fee_ustx: u64,
+    ) -> Result<StacksTransaction, ClientError>;
+
+    /// Submit `tx` to the mempool
+    fn submit_transaction(&self, tx: &StacksTransaction) -> Result<Txid, ClientError>;
+}
+
+impl StacksNodeApi for StacksClient {
+    fn read_only_contract_call(
+        &self,
+        contract_addr: &StacksAddress,
+        contract_name: &ContractName,
+        function_name: &ClarityName,
+        function_args: &[ClarityValue],
+    ) -> Result<ClarityValue, ClientError> {
+        StacksClient::read_only_contract_call(
+            self,
+            contract_addr,
+            contract_name,
+            function_name,
+            function_args,
+        )
+    }
+
+    fn get_current_reward_cycle_info(&self) -> Result<RewardCycleInfo, ClientError> {
+        StacksClient::get_current_reward_cycle_info(self)
+    }
+
+    fn get_reward_set_signers(
+        &self,
+        reward_cycle: u64,
+    ) -> Result<Option<Vec<NakamotoSignerEntry>>, ClientError> {
+        StacksClient::get_reward_set_signers(self, reward_cycle)
+    }
+
+    fn get_approved_aggregate_key(&self, reward_cycle: u64) -> Result<Option<Point>, ClientError> {
+        StacksClient::get_approved_aggregate_key(self, reward_cycle)
+    }
+
+    fn get_vote_for_aggregate_public_key(
+        &self,
+        round: u64,
+        reward_cycle: u64,
+        signer: StacksAddress,
+    ) -> Result<Option<Point>, ClientError> {
+        StacksClient::get_vote_for_aggregate_public_key(self, round, reward_cycle, signer)
+    }
+
+    fn get_last_round(&self, reward_cycle: u64) -> Result<Option<u64>, ClientError> {
+        StacksClient::get_last_round(self, reward_cycle)
+    }
+
+    fn get_round_vote_weight(
+        &self,
+        reward_cycle: u64,
+        round_id: u64,
+    ) -> Result<Option<u128>, ClientError> {
+        StacksClient::get_round_vote_weight(self, reward_cycle, round_id)
+    }
+
+    fn get_vote_threshold_weight(&self, reward_cycle: u64) -> Result<u128, ClientError> {
+        StacksClient::get_vote_threshold_weight(self, reward_cycle)
+    }
+
+    fn get_account_nonce(&self, address: &StacksAddress) -> Result<u64, ClientError> {
+        StacksClient::get_account_nonce(self, address)
+    }
+
+    fn cast_aggregate_public_key_vote(
+        &self,
+        signer_index: u32,
+        round: u64,
+        dkg_public_key: Point,
+        reward_cycle: u64,
+        tx_fee_ustx: u64,
+    ) -> Result<StacksTransaction, ClientError> {
+        StacksClient::cast_aggregate_public_key_vote(
+            self,
+            signer_index,
+            round,
+            dkg_public_key,
+            reward_cycle,
+            tx_fee_ustx,
+        )
+    }
+
+    fn submit_transaction(&self, tx: &StacksTransaction) -> Result<Txid, ClientError> {
+        StacksClient::submit_transaction(self, tx)
+    }
+}
+
+/// An in-memory stacks node for unit testing the signer's logic without sockets
+#[cfg(test)]
+pub mod testing {
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::sync::{Mutex, MutexGuard};
+
+    use stacks_common::types::chainstate::StacksPrivateKey;
+
+    use super::*;
+
+    /// A request made of a `MockStacksNode`
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum NodeCall {
+        ReadOnlyContractCall {
+            contract_name: String,
+            function_name: String,
+        },
+        GetCurrentRewardCycleInfo,
+        GetRewardSetSigners(u64),
+        GetApprovedAggregateKey(u64),
+        GetVoteForAggregatePublicKey {
+            round: u64,
+            reward_cycle: u64,
+            signer: StacksAddress,
+        },
+        GetLastRound(u64),
+        GetRoundVoteWeight {
+            reward_cycle: u64,
+            round_id: u64,
+        },
+        GetVoteThresholdWeight(u64),
+        GetAccountNonce(StacksAddress),
+        CastAggregatePublicKeyVote {
+            round: u64,
+            reward_cycle: u64,
+            tx_fee_ustx: u64,
+        },
+        SubmitTransaction(Txid),
+    }
+
+    /// What the mock node answers with
+    #[derive(Default)]
+    struct MockNodeState {
+        /// Read only call results, by contract and function name
+        read_only_responses: HashMap<(String, String), ClarityValue>,
+        reward_cycle_info: Option<RewardCycleInfo>,
+        reward_set_signers: HashMap<u64, Vec<NakamotoSignerEntry>>,
+        approved_keys: HashMap<u64, Point>,
+        /// Votes by reward cycle, round, and voter
+        votes: HashMap<(u64, u64, StacksAddress), Point>,
+        last_rounds: HashMap<u64, u64>,
+        round_vote_weights: HashMap<(u64, u64), u128>,
+        threshold_weights: HashMap<u64, u128>,
+        nonces: HashMap<StacksAddress, u64>,
+        /// Whether every request fails as if the node were down
+        offline: bool,
+        calls: Vec<NodeCall>,
+        submitted: Vec<StacksTransaction>,
+    }
+
+    /// A stacks node whose answers are programmed by the test, and which records the
+    /// requests made of it. Accounts it has no nonce for are at nonce 0, and contract data
+    /// it has no value for is absent.
+    pub struct MockStacksNode {
+        /// Builds and signs the vote transactions, without ever reaching a node
+        signer_client: StacksClient,
+        state: Mutex<MockNodeState>,
+    }
+
+    impl MockStacksNode {
+        /// A testnet node, whose vote transactions are signed with `stacks_private_key`
+        pub fn new(stacks_private_key: StacksPrivateKey) -> Self {
+            let unreachable = SocketAddr::from(([127, 0, 0, 1], 0));
+            Self {
+                signer_client: StacksClient::new(
+                    stacks_private_key,
+                    unreachable,
+                    String::new(),
+                    false,
+                ),
+                state: Mutex::new(MockNodeState::default()),
+            }
+        }
+
+        fn state(&self) -> MutexGuard<MockNodeState> {
+            self.state.lock().expect("MockStacksNode state poisoned")
+        }
+
+        /// Record `call`, failing it if the node is offline
+        fn request(&self, call: NodeCall) -> Result<MutexGuard<MockNodeState>, ClientError> {
+            let mut state = self.state();
+            state.calls.push(call);
+            if state.offline {
+                return Err(ClientError::NotConnected);
+            }
+            Ok(state)
+        }
+
+        /// The address the node's vote transactions are sent from
+        pub fn signer_address(&self) -> StacksAddress {
+            *self.signer_client.get_signer_address()
+        }
+
+        /// Answer read only calls to `function_name` of `contract_name` with `value`
+        pub fn set_read_only_response(
+            &self,
+            contract_name: &str,
+            function_name: &str,
+            value: ClarityValue,
+        ) {
+            self.state().read_only_responses.insert(
+                (contract_name.to_string(), function_name.to_string()),
+                value,
+            );
+        }
+
+        /// Answer reward cycle info requests with `reward_cycle_info`
+        pub fn set_reward_cycle_info(&self, reward_cycle_info: RewardCycleInfo) {
+            self.state().reward_cycle_info = Some(reward_cycle_info);
+        }
+
+        /// Answer with `signers` as the reward set signers of `reward_cycle`
+        pub fn set_reward_set_signers(&self, reward_cycle: u64, signers: Vec<NakamotoSignerEntry>) {
+            self.state()
+                .reward_set_signers
+                .insert(reward_cycle, signers);
+        }
+
+        /// Answer with `key` as the approved aggregate public key of `reward_cycle`
+        pub fn set_approved_aggregate_key(&self, reward_cycle: u64, key: Point) {
+            self.state().approved_keys.insert(reward_cycle, key);
+        }
+
+        /// Count `signer`'s vote for `key` in `round` of `reward_cycle`
+        pub fn set_vote(&self, reward_cycle: u64, round: u64, signer: StacksAddress, key: Point) {
+            self.state()
+                .votes
+                .insert((reward_cycle, round, signer), key);
+        }
+
+        /// Answer with `round` as the last voting round of `reward_cycle`
+        pub fn set_last_round(&self, reward_cycle: u64, round: u64) {
+            self.state().last_rounds.insert(reward_cycle, round);
+        }
+
+        /// Answer with `weight` as the weight voted in `round_id` of `reward_cycle`
+        pub fn set_round_vote_weight(&self, reward_cycle: u64, round_id: u64, weight: u128) {
+            self.state()
+                .round_vote_weights
+                .insert((reward_cycle, round_id), weight);
+        }
+
+        /// Answer with `weight` as the weight needed to approve a key in `reward_cycle`
+        pub fn set_vote_threshold_weight(&self, reward_cycle: u64, weight: u128) {
+            self.state().threshold_weights.insert(reward_cycle, weight);
+        }
+
+        /// Answer with `nonce` as the account nonce of `address`
+        pub fn set_account_nonce(&self, address: StacksAddress, nonce: u64) {
+            self.state().nonces.insert(address, nonce);
+        }
+
+        /// Fail every request, as if the node were down
+        pub fn set_offline(&self, offline: bool) {
+            self.state().offline = offline;
+        }
+
+        /// The requests made so far, in order
+        pub fn calls(&self) -> Vec<NodeCall> {
+            self.state().calls.clone()
+        }
+
+        /// Forget the requests made so far
+        pub fn clear_calls(&self) {
+            self.state().calls.clear();
+        }
+
+        /// The transactions submitted so far, in order
+        pub fn submitted_transactions(&self) -> Vec<StacksTransaction> {
+            self.state().submitted.clone()
+        }
+    }
+
+    impl StacksNodeApi for MockStacksNode {
+        fn read_only_contract_call(
+            &self,
+            _contract_addr: &StacksAddress,
+            contract_name: &ContractName,
+            function_name: &ClarityName,
+            _function_args: &[ClarityValue],
+        ) -> Result<ClarityValue, ClientError> {
+            let key = (contract_name.to_string(), function_name.to_string());
+            let state = self.request(NodeCall::ReadOnlyContractCall {
+                contract_name: key.0.clone(),
+                function_name: key.1.clone(),
+            })?;
+            state.read_only_responses.get(&key).cloned().ok_or_else(|| {
+                ClientError::ReadOnlyFailure(format!("No response for {}.{}", key.0, key.1))
+            })
+        }
+
+        fn get_current_reward_cycle_info(&self) -> Result<RewardCycleInfo, ClientError> {
+            self.request(NodeCall::GetCurrentRewardCycleInfo)?
+                .reward_cycle_info
+                .ok_or_else(|| ClientError::InvalidResponse("No reward cycle info".into()))
+        }
+
+        fn get_reward_set_signers(
+            &self,
+            reward_cycle: u64,
+        ) -> Result<Option<Vec<NakamotoSignerEntry>>, ClientError> {
+            Ok(self
+                .request(NodeCall::GetRewardSetSigners(reward_cycle))?
+                .reward_set_signers
+                .get(&reward_cycle)
+                .cloned())
+        }
+
+        fn get_approved_aggregate_key(
+            &self,
+            reward_cycle: u64,
+        ) -> Result<Option<Point>, ClientError> {
+            Ok(self
+                .request(NodeCall::GetApprovedAggregateKey(reward_cycle))?
+                .approved_keys
+                .get(&reward_cycle)
+                .copied())
+        }
+
+        fn get_vote_for_aggregate_public_key(
+            &self,
+            round: u64,
+            reward_cycle: u64,
+            signer: StacksAddress,
+        ) -> Result<Option<Point>, ClientError> {
+            Ok(self
+                .request(NodeCall::GetVoteForAggregatePublicKey {
+                    round,
+                    reward_cycle,
+                    signer,
+                })?
+                .votes
+                .get(&(reward_cycle, round, signer))
+                .copied())
+        }
+
+        fn get_last_round(&self, reward_cycle: u64) -> Result<Option<u64>, ClientError> {
+            Ok(self
+                .request(NodeCall::GetLastRound(reward_cycle))?
+                .last_rounds
+                .get(&reward_cycle)
+                .copied())
+        }
+
+        fn get_round_vote_weight(
+            &self,
+            reward_cycle: u64,
+            round_id: u64,
+        ) -> Result<Option<u128>, ClientError> {
+            Ok(self
+                .request(NodeCall::GetRoundVoteWeight {
+                    reward_cycle,
+                    round_id,
+                })?
+                .round_vote_weights
+                .get(&(reward_cycle, round_id))
+                .copied())
+        }
+
+        fn get_vote_threshold_weight(&self, reward_cycle: u64) -> Result<u128, ClientError> {
+            self.request(NodeCall::GetVoteThresholdWeight(reward_cycle))?
+                .threshold_weights
+                .get(&reward_cycle)
+                .copied()
+                .ok_or_else(|| ClientError::NotFound(format!("threshold of {reward_cycle}")))
+        }
+
+        fn get_account_nonce(&self, address: &StacksAddress) -> Result<u64, ClientError> {
+            Ok(self
+                .request(NodeCall::GetAccountNonce(*address))?
+                .nonces
+                .get(address)
+                .copied()
+                .unwrap_or(0))
+        }
+
+        fn cast_aggregate_public_key_vote(
+            &self,
+            signer_index: u32,
+            round: u64,
+            dkg_public_key: Point,
+            reward_cycle: u64,
+            tx_fee_ustx: u64,
+        ) -> Result<StacksTransaction, ClientError> {
+            drop(self.request(NodeCall::CastAggregatePublicKeyVote {
+                round,
+                reward_cycle,
+                tx_fee_ustx,
+            })?);
+            let nonce = self.get_account_nonce(&self.signer_address())?;
+            let mut unsigned_tx = self
+                .signer_client
+                .build_unsigned_vote_for_aggregate_public_key(
+                    signer_index,
+                    round,
+                    dkg_public_key,
+                    reward_cycle,
+                    nonce,
+                )?;
+            unsigned_tx.set_tx_fee(tx_fee_ustx);
+            let tx = self
+                .signer_client
+                .sign_transaction(unsigned_tx, reward_cycle)?;
+            self.submit_transaction(&tx)?;
+            Ok(tx)
+        }
+
+        fn submit_transaction(&self, tx: &StacksTransaction) -> Result<Txid, ClientError> {
+            let mut state = self.request(NodeCall::SubmitTransaction(tx.txid()))?;
+            state.submitted.push(tx.clone());
+            Ok(tx.txid())
+        }
+    }
+}
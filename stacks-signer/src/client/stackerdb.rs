@@ -14,21 +14,57 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 //
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
 use blockstack_lib::chainstate::stacks::StacksTransaction;
 use blockstack_lib::net::api::poststackerdbchunk::StackerDBErrorCodes;
 use hashbrown::HashMap;
-use libsigner::v1::messages::{MessageSlotID, SignerMessage};
-use libsigner::{SignerSession, StackerDBSession};
+use libsigner::envelope::{open_payload, seal_payload, EnvelopeConfig};
+use libsigner::protocol::ProtocolCapabilities;
+use libsigner::v1::messages::{BlockResponse, MessageSlotID, SignerMessage};
+use libsigner::{signer_set_for_cycle, SignerSession, StackerDBSession};
 use libstackerdb::{StackerDBChunkAckData, StackerDBChunkData};
-use slog::{slog_debug, slog_error, slog_warn};
+use slog::{slog_debug, slog_error, slog_info, slog_warn};
 use stacks_common::codec::{read_next, StacksMessageCodec};
 use stacks_common::types::chainstate::StacksPrivateKey;
-use stacks_common::{debug, error, warn};
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use stacks_common::{debug, error, info, warn};
 use wsts::net::Packet;
 
 use super::ClientError;
+use crate::audit::{self, AuditEntry, SharedAuditLog};
 use crate::client::retry_with_exponential_backoff;
 use crate::config::SignerConfig;
+use crate::health::SharedHealthState;
+
+/// The shortest time writes to a slot are deferred for after the node rate limits them, in
+/// seconds, in case it does not say how long to wait
+const MIN_SLOT_WRITE_DEFERRAL_SECS: u64 = 1;
+
+/// Signers holding at least this percentage of the signing weight send their block responses
+/// without delay, so that staggering the responses does not hold back the signing threshold
+pub const UNDELAYED_WEIGHT_PERCENT: u64 = 10;
+
+/// How long the signer in `slot_id`, holding `weight` of the `total_weight`, holds its block
+/// responses back for, to stagger the signers' StackerDB writes: `step` times the slot ID modulo
+/// the number of steps that fit in `max_delay`, capped at `max_delay`
+pub fn block_response_delay(
+    slot_id: SignerSlotID,
+    weight: u32,
+    total_weight: u32,
+    step: Duration,
+    max_delay: Duration,
+) -> Duration {
+    let large_weight =
+        u64::from(weight) * 100 >= u64::from(total_weight) * UNDELAYED_WEIGHT_PERCENT;
+    if step.is_zero() || max_delay.is_zero() || large_weight {
+        return Duration::ZERO;
+    }
+    let steps = (max_delay.as_nanos() / step.as_nanos()).max(1) + 1;
+    let bucket = u32::try_from(u128::from(slot_id.0) % steps).unwrap_or(u32::MAX);
+    step.saturating_mul(bucket).min(max_delay)
+}
 
 /// The signer StackerDB slot ID, purposefully wrapped to prevent conflation with SignerID
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, PartialOrd, Ord)]
@@ -40,6 +76,127 @@ impl std::fmt::Display for SignerSlotID {
     }
 }
 
+/// The latest chunk in a signer's StackerDB slot, decoded for inspection
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotInspection {
+    /// The kind of message the slot's contract holds
+    pub msg_id: MessageSlotID,
+    /// The slot, which is the index of its signer in the signer set
+    pub slot_id: u32,
+    /// The version of the latest chunk, or 0 if the slot was never written
+    pub slot_version: u32,
+    /// What the chunk holds, e.g. `BlockResponse(Accepted)`
+    pub message_type: String,
+    /// The signer signature hash of the block the chunk is about, if it is about one
+    pub sighash: Option<Sha512Trunc256Sum>,
+}
+
+impl SlotInspection {
+    /// Decode `chunk`, the data of version `slot_version` of `slot_id` in the `msg_id` contract
+    pub fn new(
+        msg_id: MessageSlotID,
+        slot_id: u32,
+        slot_version: u32,
+        chunk: Option<&[u8]>,
+    ) -> Self {
+        let (message_type, sighash) = match chunk {
+            None => ("-".to_string(), None),
+            Some([]) => ("empty".to_string(), None),
+            Some(data) if msg_id == MessageSlotID::ProtocolCapabilities => {
+                match ProtocolCapabilities::advertised(Some(data)) {
+                    Some(capabilities) => {
+                        let versions: Vec<_> = capabilities
+                            .versions
+                            .iter()
+                            .map(|version| version.to_string())
+                            .collect();
+                        (
+                            format!("ProtocolCapabilities({})", versions.join(",")),
+                            None,
+                        )
+                    }
+                    None => (
+                        "undecodable: not a capabilities advertisement".to_string(),
+                        None,
+                    ),
+                }
+            }
+            Some(data) => match open_payload(data)
+                .and_then(|payload| read_next::<SignerMessage, _>(&mut &payload[..]))
+            {
+                Ok(SignerMessage::BlockResponse(BlockResponse::Accepted((sighash, _)))) => {
+                    ("BlockResponse(Accepted)".to_string(), Some(sighash))
+                }
+                Ok(SignerMessage::BlockResponse(BlockResponse::Rejected(rejection))) => (
+                    format!("BlockResponse(Rejected: {})", rejection.reason_code),
+                    Some(rejection.signer_signature_hash),
+                ),
+                Ok(message @ SignerMessage::Packet(_)) => {
+                    (format!("Packet({:?})", message.msg_id()), None)
+                }
+                Ok(SignerMessage::Transactions(txs)) => {
+                    (format!("Transactions({})", txs.len()), None)
+                }
+                Ok(SignerMessage::DkgResults { .. }) => ("DkgResults".to_string(), None),
+                Ok(SignerMessage::EncryptedSignerState(_)) => {
+                    ("EncryptedSignerState".to_string(), None)
+                }
+                Err(e) => (format!("undecodable: {e}"), None),
+            },
+        };
+        Self {
+            msg_id,
+            slot_id,
+            slot_version,
+            message_type,
+            sighash,
+        }
+    }
+}
+
+/// Format `slots` as a table with a row per slot
+pub fn format_slot_table(slots: &[SlotInspection]) -> String {
+    let header = [
+        "CONTRACT".to_string(),
+        "SLOT".to_string(),
+        "VERSION".to_string(),
+        "MESSAGE".to_string(),
+        "SIGHASH".to_string(),
+    ];
+    let rows: Vec<[String; 5]> = slots
+        .iter()
+        .map(|slot| {
+            [
+                format!("{:?}", slot.msg_id),
+                slot.slot_id.to_string(),
+                slot.slot_version.to_string(),
+                slot.message_type.clone(),
+                slot.sighash
+                    .map(|sighash| sighash.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    let mut widths = header.clone().map(|column| column.len());
+    for row in rows.iter() {
+        for (width, column) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(column.len());
+        }
+    }
+    std::iter::once(&header)
+        .chain(rows.iter())
+        .map(|row| {
+            let line: Vec<_> = row
+                .iter()
+                .zip(widths)
+                .map(|(column, width)| format!("{column:<width$}"))
+                .collect();
+            line.join("  ").trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// The StackerDB client for communicating with the .signers contract
 #[derive(Debug)]
 pub struct StackerDB {
@@ -56,17 +213,50 @@ pub struct StackerDB {
     reward_cycle: u64,
     /// The stacker-db transaction msg session for the NEXT reward cycle
     next_transaction_session: StackerDBSession,
+    /// The signer's health state, updated with the outcome of each write
+    health: Option<SharedHealthState>,
+    /// When writes to each message slot may resume, for the slots the node rate limited
+    slot_deferrals: HashMap<MessageSlotID, Instant>,
+    /// How messages are enveloped in the chunks this signer writes. Bare if `None`.
+    payload_envelope: Option<EnvelopeConfig>,
+    /// The message and next transaction sessions of the stacks nodes this signer switched
+    /// away from, keyed by host, for when it switches back
+    standby_sessions: HashMap<String, (HashMap<MessageSlotID, StackerDBSession>, StackerDBSession)>,
+    /// The log of the chunks this signer writes, if enabled
+    audit_log: Option<SharedAuditLog>,
+    /// The number of keys this signer holds in the reward cycle
+    signer_weight: u32,
+    /// The number of keys all signers hold in the reward cycle
+    total_weight: u32,
+    /// How long to hold each block response back for before writing it
+    block_response_delay: Duration,
+    /// The block responses held back, in the order they were sent, with when to write each
+    delayed_block_responses: VecDeque<(Instant, SignerMessage)>,
+    /// Whether to log the chunks this signer would write instead of writing them
+    dry_run: bool,
 }
 
 impl From<&SignerConfig> for StackerDB {
     fn from(config: &SignerConfig) -> Self {
-        Self::new(
+        let mut stackerdb = Self::new(
             &config.node_host,
             config.stacks_private_key,
             config.mainnet,
             config.reward_cycle,
             config.signer_slot_id,
-        )
+        );
+        stackerdb.health = Some(config.health.clone());
+        stackerdb.payload_envelope = config.payload_envelope;
+        stackerdb.audit_log = config.audit_log.clone();
+        stackerdb.dry_run = config.dry_run;
+        stackerdb.signer_weight = u32::try_from(config.key_ids.len()).unwrap_or(u32::MAX);
+        stackerdb.total_weight =
+            u32::try_from(config.signer_entries.public_keys.key_ids.len()).unwrap_or(u32::MAX);
+        stackerdb.set_block_response_delay(
+            config.block_response_delay_step,
+            config.block_response_max_delay,
+        );
+        stackerdb
     }
 }
 impl StackerDB {
@@ -98,17 +288,150 @@ impl StackerDB {
             signer_slot_id,
             reward_cycle,
             next_transaction_session,
+            health: None,
+            slot_deferrals: HashMap::new(),
+            payload_envelope: None,
+            standby_sessions: HashMap::new(),
+            audit_log: None,
+            signer_weight: 0,
+            total_weight: 0,
+            block_response_delay: Duration::ZERO,
+            delayed_block_responses: VecDeque::new(),
+            dry_run: false,
+        }
+    }
+
+    /// Reconnect every session to the stacks node at `host`. The last known slot versions
+    /// are kept, so the next write to each slot does not conflict with the previous one.
+    pub fn set_node_host(&mut self, host: &str) {
+        for session in self.signers_message_stackerdb_sessions.values_mut() {
+            *session = StackerDBSession::new(host, session.stackerdb_contract_id.clone());
         }
+        self.next_transaction_session = StackerDBSession::new(
+            host,
+            self.next_transaction_session.stackerdb_contract_id.clone(),
+        );
+    }
+
+    /// Send to the stacks node at `host` instead, e.g. after the signer failed over to it.
+    /// Each node keeps its own sessions: those of the current node are set aside, and those
+    /// last used with `host` are picked up again. The last known slot versions are kept.
+    pub fn switch_node_host(&mut self, host: &str) {
+        let current_host = self.next_transaction_session.host.clone();
+        if current_host == host {
+            return;
+        }
+        let (sessions, next_transaction_session) =
+            self.standby_sessions.remove(host).unwrap_or_else(|| {
+                let sessions = self
+                    .signers_message_stackerdb_sessions
+                    .iter()
+                    .map(|(msg_id, session)| {
+                        (
+                            *msg_id,
+                            StackerDBSession::new(host, session.stackerdb_contract_id.clone()),
+                        )
+                    })
+                    .collect();
+                let next_transaction_session = StackerDBSession::new(
+                    host,
+                    self.next_transaction_session.stackerdb_contract_id.clone(),
+                );
+                (sessions, next_transaction_session)
+            });
+        let previous_sessions = (
+            std::mem::replace(&mut self.signers_message_stackerdb_sessions, sessions),
+            std::mem::replace(&mut self.next_transaction_session, next_transaction_session),
+        );
+        self.standby_sessions
+            .insert(current_host, previous_sessions);
+    }
+
+    /// Envelope the messages this signer writes as configured by `payload_envelope`, or write
+    /// them bare if it is `None`
+    pub fn set_payload_envelope(&mut self, payload_envelope: Option<EnvelopeConfig>) {
+        self.payload_envelope = payload_envelope;
+    }
+
+    /// Stagger this signer's block responses by `step` per slot, up to `max_delay`. See
+    /// `block_response_delay`.
+    pub fn set_block_response_delay(&mut self, step: Duration, max_delay: Duration) {
+        self.block_response_delay = block_response_delay(
+            self.signer_slot_id,
+            self.signer_weight,
+            self.total_weight,
+            step,
+            max_delay,
+        );
     }
 
-    /// Sends messages to the .signers stacker-db with an exponential backoff retry
+    /// Sends messages to the .signers stacker-db with an exponential backoff retry.
+    /// Block responses are instead queued for the configured block response delay, and
+    /// written by `send_due_block_responses` once it has passed. The returned ack of a
+    /// queued block response only says that it was queued.
     pub fn send_message_with_retry(
         &mut self,
         message: SignerMessage,
+    ) -> Result<StackerDBChunkAckData, ClientError> {
+        if matches!(message, SignerMessage::BlockResponse(_))
+            && !self.block_response_delay.is_zero()
+        {
+            debug!(
+                "Delaying the block response by {:?} to stagger StackerDB writes",
+                self.block_response_delay
+            );
+            self.delayed_block_responses
+                .push_back((Instant::now() + self.block_response_delay, message));
+            return Ok(StackerDBChunkAckData {
+                accepted: true,
+                reason: Some("Queued to stagger block responses".into()),
+                metadata: None,
+                code: None,
+                retry_after: None,
+            });
+        }
+        self.send_message_now(message)
+    }
+
+    /// Write the queued block responses whose delay has passed
+    pub fn send_due_block_responses(&mut self) {
+        let now = Instant::now();
+        let (due, pending) = std::mem::take(&mut self.delayed_block_responses)
+            .into_iter()
+            .partition(|(deadline, _)| *deadline <= now);
+        self.delayed_block_responses = pending;
+        for (_, message) in due {
+            if let Err(e) = self.send_message_now(message) {
+                warn!("Failed to send a delayed block response to stacker-db: {e:?}");
+            }
+        }
+    }
+
+    /// When the next queued block response is due to be written, if any is queued
+    pub fn next_block_response_deadline(&self) -> Option<Instant> {
+        self.delayed_block_responses
+            .iter()
+            .map(|(deadline, _)| *deadline)
+            .min()
+    }
+
+    /// Sends messages to the .signers stacker-db with an exponential backoff retry, without
+    /// holding block responses back
+    fn send_message_now(
+        &mut self,
+        message: SignerMessage,
     ) -> Result<StackerDBChunkAckData, ClientError> {
         let msg_id = message.msg_id();
-        let message_bytes = message.serialize_to_vec();
-        self.send_message_bytes_with_retry(&msg_id, message_bytes)
+        let signer_signature_hash = match &message {
+            SignerMessage::BlockResponse(BlockResponse::Accepted((hash, _))) => Some(*hash),
+            SignerMessage::BlockResponse(BlockResponse::Rejected(rejection)) => {
+                Some(rejection.signer_signature_hash)
+            }
+            _ => None,
+        };
+        let message_bytes =
+            seal_payload(message.serialize_to_vec(), self.payload_envelope.as_ref())?;
+        self.send_chunk(&msg_id, message_bytes, signer_signature_hash.as_ref(), true)
     }
 
     /// Sends message (as a raw msg ID and bytes) to the .signers stacker-db with an
@@ -117,8 +440,44 @@ impl StackerDB {
         &mut self,
         msg_id: &MessageSlotID,
         message_bytes: Vec<u8>,
+    ) -> Result<StackerDBChunkAckData, ClientError> {
+        self.send_chunk(msg_id, message_bytes, None, true)
+    }
+
+    /// Advertise the protocol versions this signer speaks in its slot of the capabilities
+    /// StackerDB, so that miners know it reads tagged messages. The write is attempted once,
+    /// without retrying if the node cannot be reached or has no replica of the StackerDB.
+    pub fn advertise_capabilities(
+        &mut self,
+        capabilities: &ProtocolCapabilities,
+    ) -> Result<StackerDBChunkAckData, ClientError> {
+        let message_bytes = seal_payload(
+            capabilities.serialize_to_vec(),
+            self.payload_envelope.as_ref(),
+        )?;
+        self.send_chunk(
+            &MessageSlotID::ProtocolCapabilities,
+            message_bytes,
+            None,
+            false,
+        )
+    }
+
+    /// Sends message bytes to the .signers stacker-db, recording each chunk sent in the audit
+    /// log along with the signer signature hash of the block the message responds to, if any.
+    /// If `retry` is set, failures to reach the node are retried with an exponential backoff.
+    fn send_chunk(
+        &mut self,
+        msg_id: &MessageSlotID,
+        message_bytes: Vec<u8>,
+        signer_signature_hash: Option<&Sha512Trunc256Sum>,
+        retry: bool,
     ) -> Result<StackerDBChunkAckData, ClientError> {
         let slot_id = self.signer_slot_id;
+        if let Some(retry_after) = self.slot_deferral(msg_id) {
+            debug!("Deferring a write to rate limited stackerdb slot ID {slot_id} with message ID {msg_id} for {retry_after:?}");
+            return Err(ClientError::SlotRateLimited { retry_after });
+        }
         loop {
             let mut slot_version = if let Some(versions) = self.slot_versions.get_mut(msg_id) {
                 if let Some(version) = versions.get(&slot_id) {
@@ -141,13 +500,53 @@ impl StackerDB {
                 panic!("FATAL: would loop forever trying to send a message with ID {}, for which we don't have a session", msg_id);
             };
 
+            if self.dry_run {
+                info!(
+                    "Dry run: not writing a chunk to stackerdb";
+                    "contract_id" => %session.stackerdb_contract_id,
+                    "msg_id" => %msg_id,
+                    "slot_id" => %slot_id,
+                    "slot_version" => slot_version,
+                    "payload_hash" => %chunk.data_hash(),
+                    "signer_signature_hash" => ?signer_signature_hash,
+                );
+                if let Some(versions) = self.slot_versions.get_mut(msg_id) {
+                    versions.insert(slot_id, slot_version.saturating_add(1));
+                }
+                return Ok(StackerDBChunkAckData {
+                    accepted: true,
+                    reason: None,
+                    metadata: None,
+                    code: None,
+                    retry_after: None,
+                });
+            }
+
             debug!(
                 "Sending a chunk to stackerdb slot ID {slot_id} with version {slot_version} and message ID {msg_id} to contract {:?}!\n{chunk:?}",
                 &session.stackerdb_contract_id
             );
 
-            let send_request = || session.put_chunk(&chunk).map_err(backoff::Error::transient);
-            let chunk_ack: StackerDBChunkAckData = retry_with_exponential_backoff(send_request)?;
+            let contract_id = session.stackerdb_contract_id.clone();
+            let chunk_ack = if retry {
+                let send_request = || session.put_chunk(&chunk).map_err(backoff::Error::transient);
+                retry_with_exponential_backoff(send_request)
+            } else {
+                session.put_chunk(&chunk).map_err(ClientError::from)
+            };
+            let chunk_ack: StackerDBChunkAckData = chunk_ack.map_err(|e| {
+                self.record_error(&e);
+                e
+            })?;
+            if let Some(audit_log) = self.audit_log.as_ref() {
+                let entry = AuditEntry::chunk(
+                    &contract_id,
+                    &chunk,
+                    signer_signature_hash,
+                    chunk_ack.accepted,
+                );
+                audit::record(audit_log, entry);
+            }
 
             if let Some(versions) = self.slot_versions.get_mut(msg_id) {
                 // NOTE: per the above, this is always executed
@@ -158,6 +557,9 @@ impl StackerDB {
 
             if chunk_ack.accepted {
                 debug!("Chunk accepted by stackerdb: {chunk_ack:?}");
+                if let Some(Ok(mut health)) = self.health.as_ref().map(|health| health.write()) {
+                    health.record_stackerdb_ack();
+                }
                 return Ok(chunk_ack);
             } else {
                 warn!("Chunk rejected by stackerdb: {chunk_ack:?}");
@@ -178,19 +580,60 @@ impl StackerDB {
                             return Err(ClientError::NotConnected);
                         }
                     }
+                    Some(StackerDBErrorCodes::TooFrequentSlotWrites) => {
+                        // Retrying now would only be rejected again, so hold off on every
+                        // write to this slot until the node accepts them
+                        let retry_after = Duration::from_secs(
+                            chunk_ack
+                                .retry_after
+                                .unwrap_or(0)
+                                .max(MIN_SLOT_WRITE_DEFERRAL_SECS),
+                        );
+                        warn!("Failed to send message to stackerdb: slot ID {slot_id} with message ID {msg_id} is rate limited. Deferring writes to it for {retry_after:?}");
+                        // the rejected chunk did not use up its version
+                        if let Some(versions) = self.slot_versions.get_mut(msg_id) {
+                            versions.insert(slot_id, slot_version);
+                        }
+                        self.slot_deferrals
+                            .insert(*msg_id, Instant::now() + retry_after);
+                        let err = ClientError::SlotRateLimited { retry_after };
+                        self.record_error(&err);
+                        return Err(err);
+                    }
                     _ => {
                         warn!("Failed to send message to stackerdb: {:?}", chunk_ack);
-                        return Err(ClientError::PutChunkRejected(
+                        let err = ClientError::PutChunkRejected(
                             chunk_ack
                                 .reason
                                 .unwrap_or_else(|| "No reason given".to_string()),
-                        ));
+                        );
+                        self.record_error(&err);
+                        return Err(err);
                     }
                 }
             }
         }
     }
 
+    /// How much longer writes to the `msg_id` slot are deferred for, if the node rate limited
+    /// them and the window has not yet passed
+    fn slot_deferral(&mut self, msg_id: &MessageSlotID) -> Option<Duration> {
+        let deadline = self.slot_deferrals.get(msg_id)?;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            self.slot_deferrals.remove(msg_id);
+            return None;
+        }
+        Some(remaining)
+    }
+
+    /// Record an error in the signer's health state, if one is attached
+    fn record_error(&self, err: &ClientError) {
+        if let Some(Ok(mut health)) = self.health.as_ref().map(|health| health.write()) {
+            health.record_error(err);
+        }
+    }
+
     /// Get all signer messages from stackerdb for the given slot IDs
     fn get_messages(
         session: &mut StackerDBSession,
@@ -207,7 +650,9 @@ impl StackerDB {
             let Some(data) = chunk else {
                 continue;
             };
-            let Ok(message) = read_next::<SignerMessage, _>(&mut &data[..]) else {
+            let Ok(message) = open_payload(data)
+                .and_then(|payload| read_next::<SignerMessage, _>(&mut &payload[..]))
+            else {
                 if !data.is_empty() {
                     warn!("Failed to deserialize chunk data into a SignerMessage");
                     debug!("slot #{i}: Failed chunk ({}): {data:?}", &data.len(),);
@@ -324,8 +769,9 @@ impl StackerDB {
             return Ok(None);
         }
 
+        let payload = open_payload(&chunk)?;
         let SignerMessage::EncryptedSignerState(state) =
-            read_next::<SignerMessage, _>(&mut chunk.as_slice())?
+            read_next::<SignerMessage, _>(&mut &payload[..])?
         else {
             error!("Wrong message type stored in signer state slot for signer {signer_id}");
             return Ok(None);
@@ -334,13 +780,44 @@ impl StackerDB {
         Ok(Some(state))
     }
 
+    /// Read and decode the latest chunk in each of `slot_ids` of the `msg_id` contract
+    pub fn inspect_slots(
+        &mut self,
+        msg_id: MessageSlotID,
+        slot_ids: &[u32],
+    ) -> Result<Vec<SlotInspection>, ClientError> {
+        let session = self
+            .signers_message_stackerdb_sessions
+            .get_mut(&msg_id)
+            .ok_or(ClientError::NotConnected)?;
+        let list_request = || session.list_chunks().map_err(backoff::Error::transient);
+        let slot_versions: HashMap<u32, u32> = retry_with_exponential_backoff(list_request)?
+            .into_iter()
+            .map(|slot| (slot.slot_id, slot.slot_version))
+            .collect();
+        let chunks_request = || {
+            session
+                .get_latest_chunks(slot_ids)
+                .map_err(backoff::Error::transient)
+        };
+        let chunks = retry_with_exponential_backoff(chunks_request)?;
+        Ok(slot_ids
+            .iter()
+            .zip(chunks.iter())
+            .map(|(slot_id, chunk)| {
+                let slot_version = slot_versions.get(slot_id).copied().unwrap_or(0);
+                SlotInspection::new(msg_id, *slot_id, slot_version, chunk.as_deref())
+            })
+            .collect())
+    }
+
     /// Retrieve the signer set this stackerdb client is attached to
     pub fn get_signer_set(&self) -> u32 {
-        u32::try_from(self.reward_cycle % 2).expect("FATAL: reward cycle % 2 exceeds u32::MAX")
+        signer_set_for_cycle(self.reward_cycle)
     }
 
     /// Retrieve the signer slot ID
-    pub fn get_signer_slot_id(&mut self) -> SignerSlotID {
+    pub fn get_signer_slot_id(&self) -> SignerSlotID {
         self.signer_slot_id
     }
 }
@@ -351,13 +828,17 @@ mod tests {
     use std::time::Duration;
 
     use blockstack_lib::chainstate::stacks::{
-        TransactionAnchorMode, TransactionAuth, TransactionPayload, TransactionPostConditionMode,
-        TransactionSmartContract, TransactionVersion,
+        ThresholdSignature, TransactionAnchorMode, TransactionAuth, TransactionPayload,
+        TransactionPostConditionMode, TransactionSmartContract, TransactionVersion,
     };
     use blockstack_lib::util_lib::strings::StacksString;
+    use libsigner::v1::messages::{BlockRejection, RejectCode};
+    use stacks_common::util::hash::to_hex;
 
     use super::*;
-    use crate::client::tests::{generate_signer_config, mock_server_from_config, write_response};
+    use crate::client::tests::{
+        generate_signer_config, mock_server_from_config, mock_server_random, write_response,
+    };
     use crate::config::GlobalConfig;
 
     #[test]
@@ -382,22 +863,24 @@ mod tests {
             ),
         };
 
-        let signer_message = SignerMessage::Transactions(vec![tx.clone()]);
-        let message = signer_message.serialize_to_vec();
+        let messages = [
+            SignerMessage::Transactions(vec![tx.clone()]),
+            SignerMessage::Transactions(vec![]),
+        ];
+        let chunks: Vec<_> = messages
+            .iter()
+            .map(|message| Some(to_hex(&message.serialize_to_vec())))
+            .collect();
 
+        // both slots are fetched with a single latest chunks request
         let signer_slot_ids = vec![SignerSlotID(0), SignerSlotID(1)];
         let h = spawn(move || stackerdb.get_next_transactions(&signer_slot_ids));
-        let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
-        response_bytes.extend(message);
-        let mock_server = mock_server_from_config(&config);
-        write_response(mock_server, response_bytes.as_slice());
-
-        let signer_message = SignerMessage::Transactions(vec![]);
-        let message = signer_message.serialize_to_vec();
-        let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
-        response_bytes.extend(message);
+        let response = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(&chunks).unwrap()
+        );
         let mock_server = mock_server_from_config(&config);
-        write_response(mock_server, response_bytes.as_slice());
+        write_response(mock_server, response.as_bytes());
 
         let transactions = h.join().unwrap().unwrap();
         assert_eq!(transactions, vec![tx]);
@@ -408,6 +891,7 @@ mod tests {
         let config = GlobalConfig::load_from_file("./src/tests/conf/signer-1.toml").unwrap();
         let signer_config = generate_signer_config(&config, 5, 20);
         let mut stackerdb = StackerDB::from(&signer_config);
+        let health = signer_config.health.clone();
 
         let sk = StacksPrivateKey::new();
         let tx = StacksTransaction {
@@ -432,6 +916,7 @@ mod tests {
             reason: None,
             metadata: None,
             code: None,
+            retry_after: None,
         };
         let mock_server = mock_server_from_config(&config);
         let h = spawn(move || stackerdb.send_message_with_retry(signer_message));
@@ -441,5 +926,440 @@ mod tests {
         std::thread::sleep(Duration::from_millis(500));
         write_response(mock_server, response_bytes.as_slice());
         assert_eq!(ack, h.join().unwrap().unwrap());
+        assert!(health
+            .read()
+            .unwrap()
+            .report()
+            .last_stackerdb_ack_secs
+            .is_some());
+    }
+
+    #[test]
+    fn dry_run_writes_nothing_to_stackerdb() {
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-1.toml").unwrap();
+        let mut signer_config = generate_signer_config(&config, 5, 20);
+        let (mock_server, mock_server_addr) = mock_server_random();
+        signer_config.node_host = mock_server_addr.to_string();
+        signer_config.dry_run = true;
+        let mut stackerdb = StackerDB::from(&signer_config);
+        let slot_id = signer_config.signer_slot_id;
+        let msg_id = MessageSlotID::Transactions;
+
+        for expected_version in [1, 2] {
+            let ack = stackerdb
+                .send_message_with_retry(SignerMessage::Transactions(vec![]))
+                .unwrap();
+            assert!(ack.accepted);
+            // the would-be version still advances, as it would have had the write been made
+            assert_eq!(
+                stackerdb.slot_versions[&msg_id][&slot_id],
+                expected_version + 1
+            );
+        }
+
+        mock_server.set_nonblocking(true).unwrap();
+        assert_eq!(
+            mock_server.accept().unwrap_err().kind(),
+            std::io::ErrorKind::WouldBlock,
+            "A dry run connected to the stacks node"
+        );
+    }
+
+    #[test]
+    fn send_message_defers_writes_to_a_rate_limited_slot() {
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-1.toml").unwrap();
+        let signer_config = generate_signer_config(&config, 5, 20);
+        let mut stackerdb = StackerDB::from(&signer_config);
+        let slot_id = signer_config.signer_slot_id;
+        let msg_id = MessageSlotID::Transactions;
+        let ack_response = |ack: &StackerDBChunkAckData| {
+            let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
+            response_bytes.extend(serde_json::to_string(ack).unwrap().as_bytes());
+            response_bytes
+        };
+
+        // the node rejects the first write to the slot as too frequent
+        let rate_limited = StackerDBChunkAckData {
+            accepted: false,
+            reason: Some("Slot was written too recently".into()),
+            metadata: None,
+            code: Some(StackerDBErrorCodes::TooFrequentSlotWrites.code()),
+            retry_after: Some(60),
+        };
+        let mock_server = mock_server_from_config(&config);
+        let h = spawn(move || {
+            let result = stackerdb.send_message_with_retry(SignerMessage::Transactions(vec![]));
+            (stackerdb, result)
+        });
+        write_response(mock_server, &ack_response(&rate_limited));
+        let (mut stackerdb, result) = h.join().unwrap();
+        let Err(ClientError::SlotRateLimited { retry_after }) = result else {
+            panic!("Expected the write to be rate limited, got {result:?}");
+        };
+        assert_eq!(retry_after, Duration::from_secs(60));
+        // the rejected write did not use up its version
+        assert_eq!(stackerdb.slot_versions[&msg_id][&slot_id], 1);
+
+        // further writes to the slot are deferred without contacting the node
+        let Err(ClientError::SlotRateLimited { retry_after }) =
+            stackerdb.send_message_with_retry(SignerMessage::Transactions(vec![]))
+        else {
+            panic!("Expected the write to be deferred");
+        };
+        assert!(retry_after <= Duration::from_secs(60));
+        assert!(retry_after > Duration::from_secs(50));
+
+        // but writes to other slots are not
+        assert_eq!(stackerdb.slot_deferral(&MessageSlotID::BlockResponse), None);
+
+        // once the window passes, the slot is written again with the same version
+        stackerdb.slot_deferrals.insert(msg_id, Instant::now());
+        let accepted = StackerDBChunkAckData {
+            accepted: true,
+            reason: None,
+            metadata: None,
+            code: None,
+            retry_after: None,
+        };
+        let mock_server = mock_server_from_config(&config);
+        let h = spawn(move || {
+            let result = stackerdb.send_message_with_retry(SignerMessage::Transactions(vec![]));
+            (stackerdb, result)
+        });
+        let request = write_response(mock_server, &ack_response(&accepted));
+        let (stackerdb, result) = h.join().unwrap();
+        assert_eq!(result.unwrap(), accepted);
+        assert!(String::from_utf8_lossy(&request).contains("\"slot_version\":1"));
+        assert_eq!(stackerdb.slot_versions[&msg_id][&slot_id], 2);
+        assert!(stackerdb.slot_deferrals.is_empty());
+    }
+
+    #[test]
+    fn ack_without_retry_after_still_defers_writes() {
+        let ack: StackerDBChunkAckData =
+            serde_json::from_str(r#"{"accepted":false,"code":4}"#).unwrap();
+        assert_eq!(ack.retry_after, None);
+        assert_eq!(
+            StackerDBErrorCodes::from_code(ack.code.unwrap()),
+            Some(StackerDBErrorCodes::TooFrequentSlotWrites)
+        );
+
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-1.toml").unwrap();
+        let signer_config = generate_signer_config(&config, 5, 20);
+        let mut stackerdb = StackerDB::from(&signer_config);
+        let mock_server = mock_server_from_config(&config);
+        let h =
+            spawn(move || stackerdb.send_message_with_retry(SignerMessage::Transactions(vec![])));
+        write_response(
+            mock_server,
+            b"HTTP/1.1 200 OK\n\n{\"accepted\":false,\"code\":4}",
+        );
+        let Err(ClientError::SlotRateLimited { retry_after }) = h.join().unwrap() else {
+            panic!("Expected the write to be rate limited");
+        };
+        assert_eq!(
+            retry_after,
+            Duration::from_secs(MIN_SLOT_WRITE_DEFERRAL_SECS)
+        );
+    }
+
+    #[test]
+    fn only_block_responses_are_delayed() {
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-1.toml").unwrap();
+        let mut signer_config = generate_signer_config(&config, 5, 20);
+        let (mock_server, mock_server_addr) = mock_server_random();
+        signer_config.node_host = mock_server_addr.to_string();
+        let mut stackerdb = StackerDB::from(&signer_config);
+        stackerdb.block_response_delay = Duration::from_secs(3600);
+        let block_response_contract = stackerdb.signers_message_stackerdb_sessions
+            [&MessageSlotID::BlockResponse]
+            .stackerdb_contract_id
+            .name
+            .to_string();
+        let mut ack_response = b"HTTP/1.1 200 OK\n\n".to_vec();
+        ack_response.extend(
+            serde_json::to_string(&StackerDBChunkAckData {
+                accepted: true,
+                reason: None,
+                metadata: None,
+                code: None,
+                retry_after: None,
+            })
+            .unwrap()
+            .as_bytes(),
+        );
+
+        // the block response is queued without contacting the node
+        let rejection = SignerMessage::BlockResponse(BlockResponse::Rejected(BlockRejection::new(
+            Sha512Trunc256Sum([1u8; 32]),
+            RejectCode::ConnectivityIssues,
+        )));
+        let start = Instant::now();
+        assert!(
+            stackerdb
+                .send_message_with_retry(rejection)
+                .unwrap()
+                .accepted
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(stackerdb.next_block_response_deadline().unwrap() > start);
+        mock_server.set_nonblocking(true).unwrap();
+        assert_eq!(
+            mock_server.accept().unwrap_err().kind(),
+            std::io::ErrorKind::WouldBlock,
+            "A delayed block response was written right away"
+        );
+        mock_server.set_nonblocking(false).unwrap();
+
+        // other messages are written right away, and it is not due yet
+        let h = spawn(move || {
+            let result = stackerdb.send_message_with_retry(SignerMessage::Transactions(vec![]));
+            stackerdb.send_due_block_responses();
+            (stackerdb, result)
+        });
+        write_response(mock_server.try_clone().unwrap(), &ack_response);
+        let (mut stackerdb, result) = h.join().unwrap();
+        assert!(result.unwrap().accepted);
+        assert_eq!(stackerdb.delayed_block_responses.len(), 1);
+
+        // once its delay passes, it is written on the next tick
+        stackerdb.delayed_block_responses[0].0 = Instant::now();
+        let h = spawn(move || {
+            stackerdb.send_due_block_responses();
+            stackerdb
+        });
+        let request = write_response(mock_server, &ack_response);
+        let stackerdb = h.join().unwrap();
+        assert!(String::from_utf8_lossy(&request).contains(&block_response_contract));
+        assert_eq!(stackerdb.next_block_response_deadline(), None);
+    }
+
+    #[test]
+    fn set_node_host_keeps_slot_versions() {
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        let signer_config = generate_signer_config(&config, 5, 20);
+        let mut stackerdb = StackerDB::from(&signer_config);
+        let slot_id = signer_config.signer_slot_id;
+        stackerdb.slot_versions.insert(
+            MessageSlotID::BlockResponse,
+            [(slot_id, 7)].into_iter().collect(),
+        );
+        let contracts: HashMap<_, _> = stackerdb
+            .signers_message_stackerdb_sessions
+            .iter()
+            .map(|(msg_id, session)| (*msg_id, session.stackerdb_contract_id.clone()))
+            .collect();
+
+        stackerdb.set_node_host("127.0.0.1:40443");
+
+        for (msg_id, session) in &stackerdb.signers_message_stackerdb_sessions {
+            assert_eq!(session.host, "127.0.0.1:40443");
+            assert_eq!(session.stackerdb_contract_id, contracts[msg_id]);
+        }
+        assert_eq!(stackerdb.next_transaction_session.host, "127.0.0.1:40443");
+        assert_eq!(
+            stackerdb.slot_versions[&MessageSlotID::BlockResponse][&slot_id],
+            7
+        );
+    }
+
+    #[test]
+    fn switch_node_host_keeps_the_sessions_of_each_host() {
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        let signer_config = generate_signer_config(&config, 5, 20);
+        let mut stackerdb = StackerDB::from(&signer_config);
+        let primary_host = stackerdb.next_transaction_session.host.clone();
+        let slot_id = signer_config.signer_slot_id;
+        stackerdb.slot_versions.insert(
+            MessageSlotID::BlockResponse,
+            [(slot_id, 7)].into_iter().collect(),
+        );
+
+        stackerdb.switch_node_host("127.0.0.1:40443");
+        for session in stackerdb.signers_message_stackerdb_sessions.values() {
+            assert_eq!(session.host, "127.0.0.1:40443");
+        }
+        assert_eq!(stackerdb.next_transaction_session.host, "127.0.0.1:40443");
+        assert!(stackerdb.standby_sessions.contains_key(&primary_host));
+
+        stackerdb.switch_node_host(&primary_host);
+        for session in stackerdb.signers_message_stackerdb_sessions.values() {
+            assert_eq!(session.host, primary_host);
+        }
+        assert!(stackerdb.standby_sessions.contains_key("127.0.0.1:40443"));
+        assert!(!stackerdb.standby_sessions.contains_key(&primary_host));
+        assert_eq!(
+            stackerdb.slot_versions[&MessageSlotID::BlockResponse][&slot_id],
+            7
+        );
+    }
+
+    #[test]
+    fn slot_inspection_decodes_signer_messages() {
+        let sighash = Sha512Trunc256Sum([0x01; 32]);
+        let accepted = SignerMessage::BlockResponse(BlockResponse::Accepted((
+            sighash,
+            ThresholdSignature::empty(),
+        )))
+        .serialize_to_vec();
+        let slot = SlotInspection::new(MessageSlotID::BlockResponse, 2, 5, Some(&accepted));
+        assert_eq!(
+            slot,
+            SlotInspection {
+                msg_id: MessageSlotID::BlockResponse,
+                slot_id: 2,
+                slot_version: 5,
+                message_type: "BlockResponse(Accepted)".into(),
+                sighash: Some(sighash),
+            }
+        );
+
+        let rejected = SignerMessage::BlockResponse(BlockResponse::Rejected(BlockRejection::new(
+            sighash,
+            RejectCode::ConnectivityIssues,
+        )))
+        .serialize_to_vec();
+        let slot = SlotInspection::new(MessageSlotID::BlockResponse, 2, 6, Some(&rejected));
+        assert!(slot.message_type.starts_with("BlockResponse(Rejected"));
+        assert_eq!(slot.sighash, Some(sighash));
+
+        let transactions = SignerMessage::Transactions(vec![]).serialize_to_vec();
+        let slot = SlotInspection::new(MessageSlotID::Transactions, 2, 1, Some(&transactions));
+        assert_eq!(slot.message_type, "Transactions(0)");
+        assert_eq!(slot.sighash, None);
+
+        // enveloped chunks decode the same as bare ones
+        let enveloped = seal_payload(rejected, Some(&EnvelopeConfig::default())).unwrap();
+        let slot = SlotInspection::new(MessageSlotID::BlockResponse, 2, 7, Some(&enveloped));
+        assert!(slot.message_type.starts_with("BlockResponse(Rejected"));
+        assert_eq!(slot.sighash, Some(sighash));
+
+        // never written, cleared, and garbage
+        let slot = SlotInspection::new(MessageSlotID::DkgBegin, 2, 0, None);
+        assert_eq!(slot.message_type, "-");
+        let slot = SlotInspection::new(MessageSlotID::DkgBegin, 2, 3, Some(&[]));
+        assert_eq!(slot.message_type, "empty");
+        let slot = SlotInspection::new(MessageSlotID::DkgBegin, 2, 3, Some(&[0xff; 4]));
+        assert!(slot.message_type.starts_with("undecodable"));
+    }
+
+    #[test]
+    fn slot_table_has_a_row_per_slot() {
+        let sighash = Sha512Trunc256Sum([0x01; 32]);
+        let slots = vec![
+            SlotInspection {
+                msg_id: MessageSlotID::BlockResponse,
+                slot_id: 2,
+                slot_version: 15,
+                message_type: "BlockResponse(Accepted)".into(),
+                sighash: Some(sighash),
+            },
+            SlotInspection {
+                msg_id: MessageSlotID::DkgBegin,
+                slot_id: 2,
+                slot_version: 0,
+                message_type: "-".into(),
+                sighash: None,
+            },
+        ];
+        let table = format_slot_table(&slots);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "CONTRACT       SLOT  VERSION  MESSAGE                  SIGHASH".to_string(),
+                format!("BlockResponse  2     15       BlockResponse(Accepted)  {sighash}"),
+                "DkgBegin       2     0        -                        -".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn inspect_slots_should_succeed() {
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        let signer_config = generate_signer_config(&config, 5, 20);
+        let mut stackerdb = StackerDB::from(&signer_config);
+
+        let sighash = Sha512Trunc256Sum([0x01; 32]);
+        let message = SignerMessage::BlockResponse(BlockResponse::Accepted((
+            sighash,
+            ThresholdSignature::empty(),
+        )));
+        let mut chunk = StackerDBChunkData::new(1, 4, message.serialize_to_vec());
+        chunk.sign(&StacksPrivateKey::new()).unwrap();
+
+        let h = spawn(move || stackerdb.inspect_slots(MessageSlotID::BlockResponse, &[1]));
+        let metadata = serde_json::to_string(&vec![chunk.get_slot_metadata()])
+            .expect("Failed to serialize slot metadata");
+        let response = format!("HTTP/1.1 200 OK\n\n{metadata}");
+        let mock_server = mock_server_from_config(&config);
+        write_response(mock_server, response.as_bytes());
+        let mut response_bytes = b"HTTP/1.1 200 OK\n\n".to_vec();
+        response_bytes.extend(chunk.data);
+        let mock_server = mock_server_from_config(&config);
+        write_response(mock_server, response_bytes.as_slice());
+
+        assert_eq!(
+            h.join().unwrap().unwrap(),
+            vec![SlotInspection {
+                msg_id: MessageSlotID::BlockResponse,
+                slot_id: 1,
+                slot_version: 4,
+                message_type: "BlockResponse(Accepted)".into(),
+                sighash: Some(sighash),
+            }]
+        );
+    }
+
+    #[test]
+    fn block_response_delays_are_staggered_by_slot() {
+        let step = Duration::from_millis(100);
+        let max_delay = Duration::from_millis(300);
+        let delays: Vec<_> = (0..6)
+            .map(|slot_id| block_response_delay(SignerSlotID(slot_id), 1, 100, step, max_delay))
+            .collect();
+        assert_eq!(
+            delays,
+            [0, 100, 200, 300, 0, 100]
+                .map(Duration::from_millis)
+                .to_vec()
+        );
+
+        // large-weight signers and the default zero step are never delayed
+        assert_eq!(
+            block_response_delay(SignerSlotID(3), 10, 100, step, max_delay),
+            Duration::ZERO
+        );
+        assert_eq!(
+            block_response_delay(SignerSlotID(3), 1, 100, Duration::ZERO, max_delay),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn block_response_delay_is_capped() {
+        let max_delay = Duration::from_millis(500);
+        for slot_id in 0..20 {
+            for step_ms in [1, 30, 499, 500, 700, 10_000] {
+                let delay = block_response_delay(
+                    SignerSlotID(slot_id),
+                    1,
+                    100,
+                    Duration::from_millis(step_ms),
+                    max_delay,
+                );
+                assert!(delay <= max_delay, "{delay:?} exceeds {max_delay:?}");
+            }
+        }
+        assert_eq!(
+            block_response_delay(
+                SignerSlotID(1),
+                1,
+                100,
+                Duration::from_millis(700),
+                max_delay
+            ),
+            max_delay
+        );
     }
 }
@@ -14,11 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 use std::net::SocketAddr;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
 use blockstack_lib::burnchains::Txid;
-use blockstack_lib::chainstate::nakamoto::NakamotoBlock;
+use blockstack_lib::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
 use blockstack_lib::chainstate::stacks::boot::{
-    NakamotoSignerEntry, SIGNERS_VOTING_FUNCTION_NAME, SIGNERS_VOTING_NAME,
+    NakamotoSignerEntry, RewardSet, SIGNERS_NAME, SIGNERS_VOTING_FUNCTION_NAME, SIGNERS_VOTING_NAME,
 };
 use blockstack_lib::chainstate::stacks::{
     StacksTransaction, StacksTransactionSigner, TransactionAnchorMode, TransactionAuth,
@@ -27,29 +29,281 @@ use blockstack_lib::chainstate::stacks::{
 };
 use blockstack_lib::net::api::callreadonly::CallReadOnlyResponse;
 use blockstack_lib::net::api::getaccount::AccountEntryResponse;
+use blockstack_lib::net::api::getdatavar::DataVarResponse;
 use blockstack_lib::net::api::getinfo::RPCPeerInfoData;
+use blockstack_lib::net::api::getmapentry::MapEntryResponse;
 use blockstack_lib::net::api::getpoxinfo::RPCPoxInfoData;
 use blockstack_lib::net::api::getstackers::GetStackersResponse;
+use blockstack_lib::net::api::gettenureinfo::RPCGetTenureInfo;
 use blockstack_lib::net::api::postblock_proposal::NakamotoBlockProposal;
 use blockstack_lib::net::api::postfeerate::{FeeRateEstimateRequestBody, RPCFeeEstimateResponse};
 use blockstack_lib::util_lib::boot::{boot_code_addr, boot_code_id};
-use clarity::util::hash::to_hex;
-use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use clarity::util::hash::{to_hex, HexReader};
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier, TupleData};
 use clarity::vm::{ClarityName, ContractName, Value as ClarityValue};
+use libsigner::signer_set_for_cycle;
+use reqwest::blocking::{RequestBuilder, Response};
 use reqwest::header::AUTHORIZATION;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use slog::slog_debug;
+use slog::{slog_debug, slog_info, slog_warn};
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::consts::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
-use stacks_common::debug;
-use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+use stacks_common::types::chainstate::{
+    BlockHeaderHash, ConsensusHash, SortitionId, StacksAddress, StacksBlockId, StacksPrivateKey,
+    StacksPublicKey,
+};
 use stacks_common::types::StacksEpochId;
-use wsts::curve::point::{Compressed, Point};
-
-use crate::client::{retry_with_exponential_backoff, ClientError};
-use crate::config::GlobalConfig;
+use stacks_common::util::get_epoch_time_ms;
+use stacks_common::util::hash::Hash160;
+use stacks_common::{debug, info, warn};
+use wsts::curve::point::Point;
+
+use crate::audit::{self, AuditEntry, SharedAuditLog};
+use crate::client::{
+    aggregate_key_from_bytes, hex_digits, retry_with_exponential_backoff, ClientError,
+};
+use crate::config::{
+    GlobalConfig, NextStacksKey, BURN_BLOCK_INTERVAL_MS, NODE_MAX_CONCURRENT_READS,
+    NODE_READ_TIMEOUT_MS, NODE_WRITE_MAX_IDLE_CONNECTIONS, NODE_WRITE_TIMEOUT_MS,
+};
+use crate::health::{HealthState, SharedHealthState};
 use crate::runloop::RewardCycleInfo;
 
+/// How often to check whether a submitted transaction has been confirmed
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a single node reachability probe may take
+const NODE_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many `/v2/info` responses to sample when measuring the clock skew from the node
+const CLOCK_SKEW_SAMPLES: usize = 3;
+
+/// The node's view of a sortition, which the signers judge block proposals against, as
+/// served by its `/v3/sortitions` endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortitionInfo {
+    /// The height of the burn block the sortition was held in
+    pub burn_block_height: u64,
+    /// The ID of the sortition
+    pub sortition_id: SortitionId,
+    /// The ID of the sortition before this one on the same burnchain fork
+    pub parent_sortition_id: SortitionId,
+    /// The consensus hash of the sortition
+    pub consensus_hash: ConsensusHash,
+    /// Whether a miner won the sortition
+    pub was_sortition: bool,
+    /// Hash160 of the winning miner's public key, if a miner won
+    pub miner_pk_hash160: Option<Hash160>,
+    /// Consensus hash of the sortition that elected the tenure the winner builds on, if a
+    /// miner won
+    pub stacks_parent_ch: Option<ConsensusHash>,
+    /// Consensus hash of the latest sortition a miner won, at or before this one
+    pub last_sortition_ch: Option<ConsensusHash>,
+    /// The block the winning miner committed to, if a miner won
+    pub committed_block_hash: Option<BlockHeaderHash>,
+}
+
+/// A source of monotonic time, so that tests can control how much of it passes
+pub trait MonotonicClock: Send + Sync + std::fmt::Debug {
+    /// The current instant
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl MonotonicClock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The chain tip a stacks node last reported in its `/v2/info` response, and when it was
+/// first seen
+#[derive(Debug, Clone, PartialEq)]
+struct NodeChainTip {
+    burn_block_height: u64,
+    stacks_tip_consensus_hash: ConsensusHash,
+    stacks_tip: BlockHeaderHash,
+    /// When the node first reported this tip
+    first_seen: Instant,
+}
+
+/// The stacks nodes a `StacksClient` sends its requests to: the primary node, and the
+/// fallback nodes it fails over to while the primary is unreachable
+#[derive(Debug)]
+struct NodeHosts {
+    /// The primary host, followed by the fallback hosts in the order they are tried
+    hosts: Vec<String>,
+    /// The index in `hosts` of the host requests are sent to
+    active: usize,
+    /// When the client failed over, if it is not on the primary host
+    failed_over_at: Option<Instant>,
+    /// How long to stay on a fallback host before trying the primary again
+    cooldown: Duration,
+    /// The network ID the nodes have reported. Nodes on any other network are not failed over to.
+    network_id: Option<u32>,
+}
+
+impl NodeHosts {
+    fn new(primary: &str, fallbacks: &[String], cooldown: Duration) -> Self {
+        Self {
+            hosts: std::iter::once(primary.to_string())
+                .chain(fallbacks.iter().cloned())
+                .collect(),
+            active: 0,
+            failed_over_at: None,
+            cooldown,
+            network_id: None,
+        }
+    }
+
+    /// The host to send requests to. Once the cooldown has passed, this is the primary again.
+    fn active_host(&mut self) -> String {
+        if self
+            .failed_over_at
+            .map(|failed_over_at| failed_over_at.elapsed() >= self.cooldown)
+            .unwrap_or(false)
+        {
+            info!(
+                "Failover cooldown elapsed, returning to the primary stacks node";
+                "primary" => &self.hosts[0],
+                "fallback" => &self.hosts[self.active],
+            );
+            self.active = 0;
+            self.failed_over_at = None;
+        }
+        self.hosts[self.active].clone()
+    }
+
+    /// The hosts to try, in order, after `failed_host` could not be reached
+    fn failover_candidates(&self, failed_host: &str) -> Vec<String> {
+        let Some(failed_idx) = self.hosts.iter().position(|host| host == failed_host) else {
+            return vec![];
+        };
+        (1..self.hosts.len())
+            .map(|offset| self.hosts[(failed_idx + offset) % self.hosts.len()].clone())
+            .collect()
+    }
+
+    /// Send requests to `host`, a node on network `network_id`, from now on
+    fn switch_to(&mut self, host: &str, network_id: u32) {
+        let Some(idx) = self.hosts.iter().position(|candidate| candidate == host) else {
+            return;
+        };
+        self.active = idx;
+        self.failed_over_at = (idx != 0).then(Instant::now);
+        self.network_id.get_or_insert(network_id);
+    }
+}
+
+/// A limited number of permits for requests to be in flight at once
+#[derive(Debug)]
+struct RequestPermits {
+    /// The number of permits not held by a request
+    available: Mutex<usize>,
+    /// Notified whenever a permit is released
+    released: Condvar,
+}
+
+impl RequestPermits {
+    fn new(permits: usize) -> Self {
+        Self {
+            available: Mutex::new(permits),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Wait for a permit, which is released when the returned guard is dropped
+    fn acquire(&self) -> RequestPermit<'_> {
+        let mut available = self
+            .available
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        while *available == 0 {
+            available = self
+                .released
+                .wait(available)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *available -= 1;
+        RequestPermit(self)
+    }
+}
+
+/// A request's permit to be in flight, released on drop
+struct RequestPermit<'a>(&'a RequestPermits);
+
+impl Drop for RequestPermit<'_> {
+    fn drop(&mut self) {
+        *self
+            .0
+            .available
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) += 1;
+        self.0.released.notify_one();
+    }
+}
+
+/// The HTTP clients a `StacksClient` talks to the stacks node with. Reads and writes are
+/// sent over separate connection pools, and only a limited number of reads may be in flight
+/// at once, so that a burst of reads cannot hold up transaction and block proposal
+/// submissions.
+#[derive(Clone, Debug)]
+struct NodeHttpClients {
+    /// The client read requests are sent with
+    read: reqwest::blocking::Client,
+    /// The client transactions and block proposals are submitted with
+    write: reqwest::blocking::Client,
+    /// The permits read requests wait for, shared with any clones of this client
+    read_permits: Arc<RequestPermits>,
+}
+
+impl NodeHttpClients {
+    fn new(
+        read_timeout: Duration,
+        write_timeout: Duration,
+        max_concurrent_reads: usize,
+        write_max_idle_connections: usize,
+    ) -> Self {
+        Self {
+            read: reqwest::blocking::Client::builder()
+                .timeout(read_timeout)
+                .pool_max_idle_per_host(max_concurrent_reads)
+                .build()
+                .expect("FATAL: failed to build the stacks node read client"),
+            write: reqwest::blocking::Client::builder()
+                .timeout(write_timeout)
+                .pool_max_idle_per_host(write_max_idle_connections)
+                .build()
+                .expect("FATAL: failed to build the stacks node write client"),
+            read_permits: Arc::new(RequestPermits::new(max_concurrent_reads)),
+        }
+    }
+
+    fn from_config(config: &GlobalConfig) -> Self {
+        Self::new(
+            config.node_read_timeout,
+            config.node_write_timeout,
+            config.node_max_concurrent_reads,
+            config.node_write_max_idle_connections,
+        )
+    }
+}
+
+impl Default for NodeHttpClients {
+    fn default() -> Self {
+        Self::new(
+            Duration::from_millis(NODE_READ_TIMEOUT_MS),
+            Duration::from_millis(NODE_WRITE_TIMEOUT_MS),
+            usize::try_from(NODE_MAX_CONCURRENT_READS).unwrap_or(usize::MAX),
+            usize::try_from(NODE_WRITE_MAX_IDLE_CONNECTIONS).unwrap_or(usize::MAX),
+        )
+    }
+}
+
 /// The Stacks signer client used to communicate with the stacks node
 #[derive(Clone, Debug)]
 pub struct StacksClient {
@@ -57,31 +311,60 @@ pub struct StacksClient {
     stacks_address: StacksAddress,
     /// The private key used in all stacks node communications
     stacks_private_key: StacksPrivateKey,
-    /// The stacks node HTTP base endpoint
-    http_origin: String,
+    /// The key that replaces `stacks_private_key` from its activation reward cycle onwards
+    next_stacks_key: Option<NextStacksKey>,
+    /// The stacks nodes to send requests to, shared with any clones of this client
+    node_hosts: Arc<Mutex<NodeHosts>>,
     /// The types of transactions
     tx_version: TransactionVersion,
     /// The chain we are interacting with
     chain_id: u32,
     /// Whether we are mainnet or not
     mainnet: bool,
-    /// The Client used to make HTTP connects
-    stacks_node_client: reqwest::blocking::Client,
+    /// The clients used to make HTTP connects
+    http_clients: NodeHttpClients,
     /// the auth password for the stacks node
     auth_password: String,
+    /// The signer's health state, shared with any clones of this client
+    health: SharedHealthState,
+    /// The chain tip last reported by the stacks node, shared with any clones of this client
+    chain_tip: Arc<Mutex<Option<NodeChainTip>>>,
+    /// How often burn blocks are expected to arrive
+    burn_block_interval: Duration,
+    /// The clock that node staleness is measured with
+    clock: Arc<dyn MonotonicClock>,
+    /// The log of the transactions this client submits, shared with any clones of this client
+    audit_log: Option<SharedAuditLog>,
+    /// Whether to log the transactions this client would submit instead of submitting them
+    dry_run: bool,
 }
 
 impl From<&GlobalConfig> for StacksClient {
     fn from(config: &GlobalConfig) -> Self {
+        let health = HealthState::new_shared();
+        if let Ok(mut health) = health.write() {
+            health.set_dry_run(config.dry_run);
+        }
         Self {
             stacks_private_key: config.stacks_private_key,
             stacks_address: config.stacks_address,
-            http_origin: format!("http://{}", config.node_host),
+            next_stacks_key: config.next_stacks_key.clone(),
+            node_hosts: Arc::new(Mutex::new(NodeHosts::new(
+                &config.node_host,
+                &config.fallback_node_hosts,
+                config.node_failover_cooldown,
+            ))),
             tx_version: config.network.to_transaction_version(),
             chain_id: config.network.to_chain_id(),
-            stacks_node_client: reqwest::blocking::Client::new(),
+            http_clients: NodeHttpClients::from_config(config),
             mainnet: config.network.is_mainnet(),
             auth_password: config.auth_password.clone(),
+            health,
+            chain_tip: Arc::new(Mutex::new(None)),
+            burn_block_interval: config.burn_block_interval,
+            clock: Arc::new(SystemClock),
+            audit_log: None,
+            dry_run: config.dry_run,
         }
     }
 }
@@ -109,20 +392,270 @@ impl StacksClient {
         Self {
             stacks_private_key,
             stacks_address,
-            http_origin: format!("http://{}", node_host),
+            next_stacks_key: None,
+            node_hosts: Arc::new(Mutex::new(NodeHosts::new(
+                &node_host.to_string(),
+                &[],
+                Duration::ZERO,
+            ))),
             tx_version,
             chain_id,
-            stacks_node_client: reqwest::blocking::Client::new(),
+            http_clients: NodeHttpClients::default(),
             mainnet,
             auth_password,
+            health: HealthState::new_shared(),
+            chain_tip: Arc::new(Mutex::new(None)),
+            burn_block_interval: Duration::from_millis(BURN_BLOCK_INTERVAL_MS),
+            clock: Arc::new(SystemClock),
+            audit_log: None,
+            dry_run: false,
+        }
+    }
+
+    /// Measure node staleness with `clock` instead of the system clock
+    #[cfg(test)]
+    pub fn set_clock(&mut self, clock: Arc<dyn MonotonicClock>) {
+        self.clock = clock;
+    }
+
+    /// Get the health state updated by this client
+    pub fn health_state(&self) -> SharedHealthState {
+        self.health.clone()
+    }
+
+    /// Record the transactions this client submits in `audit_log`
+    pub fn set_audit_log(&mut self, audit_log: SharedAuditLog) {
+        self.audit_log = Some(audit_log);
+    }
+
+    /// Get the audit log this client records its transactions in, if any
+    pub fn audit_log(&self) -> Option<SharedAuditLog> {
+        self.audit_log.clone()
+    }
+
+    /// Point this client at the stacks nodes of a reloaded config, starting with the primary,
+    /// with fresh HTTP clients. The signer's keys, network and health state are kept.
+    pub fn reload_config(&mut self, config: &GlobalConfig) {
+        let mut node_hosts = self.node_hosts();
+        let network_id = node_hosts.network_id;
+        *node_hosts = NodeHosts::new(
+            &config.node_host,
+            &config.fallback_node_hosts,
+            config.node_failover_cooldown,
+        );
+        node_hosts.network_id = network_id;
+        drop(node_hosts);
+        self.auth_password = config.auth_password.clone();
+        self.burn_block_interval = config.burn_block_interval;
+        self.http_clients = NodeHttpClients::from_config(config);
+    }
+
+    /// Record an error in the signer's health state
+    pub fn record_error(&self, err: &ClientError) {
+        if let Ok(mut health) = self.health.write() {
+            health.record_error(err);
+        }
+    }
+
+    /// Record the outcome of a `/v2/info` request in the signer's health state, and the
+    /// network the node is on
+    fn record_peer_info_result(&self, result: &Result<RPCPeerInfoData, ClientError>) {
+        match result {
+            Ok(peer_info) => {
+                if let Ok(mut health) = self.health.write() {
+                    health.record_node_contact();
+                }
+                self.node_hosts()
+                    .network_id
+                    .get_or_insert(peer_info.network_id);
+                self.record_chain_tip(peer_info);
+            }
+            Err(e) => self.record_error(e),
         }
     }
 
+    /// Record the chain tip reported by the node, noting when it last advanced
+    fn record_chain_tip(&self, peer_info: &RPCPeerInfoData) {
+        let mut chain_tip = self
+            .chain_tip
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let advanced = chain_tip
+            .as_ref()
+            .map(|tip| {
+                tip.burn_block_height != peer_info.burn_block_height
+                    || tip.stacks_tip_consensus_hash != peer_info.stacks_tip_consensus_hash
+                    || tip.stacks_tip != peer_info.stacks_tip
+            })
+            .unwrap_or(true);
+        if advanced {
+            *chain_tip = Some(NodeChainTip {
+                burn_block_height: peer_info.burn_block_height,
+                stacks_tip_consensus_hash: peer_info.stacks_tip_consensus_hash,
+                stacks_tip: peer_info.stacks_tip,
+                first_seen: self.clock.now(),
+            });
+        }
+    }
+
+    /// How far the node's chain tip is behind where it should be: how long the node has
+    /// reported the same tip, beyond the time a burn block is expected to take. Zero until
+    /// the node has reported a tip.
+    pub fn node_staleness(&self) -> Duration {
+        let chain_tip = self
+            .chain_tip
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Some(tip) = chain_tip.as_ref() else {
+            return Duration::ZERO;
+        };
+        self.clock
+            .now()
+            .saturating_duration_since(tip.first_seen)
+            .saturating_sub(self.burn_block_interval)
+    }
+
+    fn node_hosts(&self) -> MutexGuard<'_, NodeHosts> {
+        self.node_hosts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The stacks node host requests are sent to: the primary host, or a fallback host
+    /// while the primary is unreachable
+    pub fn node_host(&self) -> String {
+        self.node_hosts().active_host()
+    }
+
+    /// The HTTP base endpoint of the active stacks node
+    fn http_origin(&self) -> String {
+        format!("http://{}", self.node_host())
+    }
+
+    /// Send the request `request` builds for the active stacks node. If the node cannot be
+    /// reached, fail over to the next node that can and send the request there instead.
+    /// Any response from a node, including an HTTP error, is returned as it is.
+    fn send_with_failover(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let mut failovers_left = self.node_hosts().hosts.len().saturating_sub(1);
+        loop {
+            let host = self.node_host();
+            match request().send() {
+                Err(e) if e.is_connect() && failovers_left > 0 && self.fail_over(&host, &e) => {
+                    failovers_left -= 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Send the read request `request` builds with the read client, once fewer than the
+    /// maximum number of reads are in flight
+    fn send_read(
+        &self,
+        request: impl Fn(&reqwest::blocking::Client) -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let _permit = self.http_clients.read_permits.acquire();
+        self.send_with_failover(|| request(&self.http_clients.read))
+    }
+
+    /// Send the submission `request` builds with the write client, which reads do not hold up
+    fn send_write(
+        &self,
+        request: impl Fn(&reqwest::blocking::Client) -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        self.send_with_failover(|| request(&self.http_clients.write))
+    }
+
+    /// Fail over from `failed_host` to the next host that is reachable and on the same
+    /// network. Returns false if there is no such host.
+    fn fail_over(&self, failed_host: &str, error: &reqwest::Error) -> bool {
+        let (candidates, network_id) = {
+            let node_hosts = self.node_hosts();
+            if node_hosts.hosts[node_hosts.active] != failed_host {
+                // another request has already failed over
+                return true;
+            }
+            (
+                node_hosts.failover_candidates(failed_host),
+                node_hosts.network_id,
+            )
+        };
+        for candidate in candidates {
+            let candidate_network_id = match self.probe_network_id(&candidate) {
+                Ok(candidate_network_id) => candidate_network_id,
+                Err(e) => {
+                    debug!("Not failing over to an unreachable stacks node"; "host" => &candidate, "error" => %e);
+                    continue;
+                }
+            };
+            if let Some(network_id) = network_id.filter(|id| *id != candidate_network_id) {
+                warn!(
+                    "Not failing over to a stacks node on another network";
+                    "host" => &candidate,
+                    "network_id" => format!("{candidate_network_id:#010x}"),
+                    "expected_network_id" => format!("{network_id:#010x}"),
+                );
+                continue;
+            }
+            warn!(
+                "Stacks node is unreachable, failing over";
+                "from" => failed_host,
+                "to" => &candidate,
+                "error" => %error,
+            );
+            self.node_hosts()
+                .switch_to(&candidate, candidate_network_id);
+            crate::monitoring::increment_node_failovers();
+            return true;
+        }
+        false
+    }
+
+    /// The network ID of the stacks node at `host`, from a single `/v2/info` request
+    fn probe_network_id(&self, host: &str) -> Result<u32, ClientError> {
+        // sent without a read permit, since the request that failed over may hold the last one
+        let response = self
+            .http_clients
+            .read
+            .get(format!("http://{host}/v2/info"))
+            .timeout(NODE_PROBE_TIMEOUT)
+            .send()?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        Ok(response.json::<RPCPeerInfoData>()?.network_id)
+    }
+
     /// Get our signer address
     pub const fn get_signer_address(&self) -> &StacksAddress {
         &self.stacks_address
     }
 
+    /// The rotated-to key, if it is the one to sign with in `reward_cycle`
+    fn active_next_key(&self, reward_cycle: u64) -> Option<&NextStacksKey> {
+        self.next_stacks_key
+            .as_ref()
+            .filter(|next_key| next_key.is_active(reward_cycle))
+    }
+
+    /// Get our signer address for `reward_cycle`, which differs from `get_signer_address`
+    /// once a configured key rotation has taken effect
+    pub fn get_signer_address_for_cycle(&self, reward_cycle: u64) -> &StacksAddress {
+        self.active_next_key(reward_cycle)
+            .map(|next_key| &next_key.stacks_address)
+            .unwrap_or(&self.stacks_address)
+    }
+
+    /// The private key to sign with for operations targeting `reward_cycle`
+    fn private_key_for_cycle(&self, reward_cycle: u64) -> &StacksPrivateKey {
+        self.active_next_key(reward_cycle)
+            .map(|next_key| &next_key.stacks_private_key)
+            .unwrap_or(&self.stacks_private_key)
+    }
+
     /// Retrieve the signer slots stored within the stackerdb contract
     pub fn get_stackerdb_signer_slots(
         &self,
@@ -141,6 +674,20 @@ impl StacksClient {
         self.parse_signer_slots(value)
     }
 
+    /// Retrieve the addresses of the signers of `reward_cycle`, in the order of their slots in
+    /// the signers StackerDBs
+    pub fn get_signer_slots(&self, reward_cycle: u64) -> Result<Vec<StacksAddress>, ClientError> {
+        let signer_stackerdb_contract_id = boot_code_id(SIGNERS_NAME, self.mainnet);
+        let signer_slots = self.get_stackerdb_signer_slots(
+            &signer_stackerdb_contract_id,
+            signer_set_for_cycle(reward_cycle),
+        )?;
+        Ok(signer_slots
+            .into_iter()
+            .map(|(address, _)| address)
+            .collect())
+    }
+
     /// Helper function  that attempts to deserialize a clarity hext string as a list of signer slots and their associated number of signer slots
     fn parse_signer_slots(
         &self,
@@ -207,15 +754,18 @@ impl StacksClient {
             estimated_len: Some(tx.tx_len()),
             transaction_payload: to_hex(&tx.payload.serialize_to_vec()),
         };
-        let timer =
-            crate::monitoring::new_rpc_call_timer(&self.fees_transaction_path(), &self.http_origin);
+        let timer = crate::monitoring::new_rpc_call_timer(
+            &self.fees_transaction_path(),
+            &self.http_origin(),
+        );
         let send_request = || {
-            self.stacks_node_client
-                .post(self.fees_transaction_path())
-                .header("Content-Type", "application/json")
-                .json(&request)
-                .send()
-                .map_err(backoff::Error::transient)
+            self.send_read(|client| {
+                client
+                    .post(self.fees_transaction_path())
+                    .header("Content-Type", "application/json")
+                    .json(&request)
+            })
+            .map_err(backoff::Error::transient)
         };
         let response = retry_with_exponential_backoff(send_request)?;
         if !response.status().is_success() {
@@ -272,15 +822,16 @@ impl StacksClient {
             chain_id: self.chain_id,
         };
         let timer =
-            crate::monitoring::new_rpc_call_timer(&self.block_proposal_path(), &self.http_origin);
+            crate::monitoring::new_rpc_call_timer(&self.block_proposal_path(), &self.http_origin());
         let send_request = || {
-            self.stacks_node_client
-                .post(self.block_proposal_path())
-                .header("Content-Type", "application/json")
-                .header(AUTHORIZATION, self.auth_password.clone())
-                .json(&block_proposal)
-                .send()
-                .map_err(backoff::Error::transient)
+            self.send_write(|client| {
+                client
+                    .post(self.block_proposal_path())
+                    .header("Content-Type", "application/json")
+                    .header(AUTHORIZATION, self.auth_password.clone())
+                    .json(&block_proposal)
+            })
+            .map_err(backoff::Error::transient)
         };
 
         let response = retry_with_exponential_backoff(send_request)?;
@@ -339,6 +890,32 @@ impl StacksClient {
         Ok(Some(votes_weight))
     }
 
+    /// Retrieve the weight voted so far for `candidate_key` in the given reward cycle and DKG
+    /// round, or `None` if no signer has voted for it
+    pub fn get_round_vote_tally(
+        &self,
+        reward_cycle: u64,
+        round_id: u64,
+        candidate_key: &Point,
+    ) -> Result<Option<u128>, ClientError> {
+        let voting_contract_id = boot_code_id(SIGNERS_VOTING_NAME, self.mainnet);
+        let key = ClarityValue::Tuple(TupleData::from_data(vec![
+            (
+                "reward-cycle".into(),
+                ClarityValue::UInt(reward_cycle as u128),
+            ),
+            ("round".into(), ClarityValue::UInt(round_id as u128)),
+            (
+                "aggregate-public-key".into(),
+                ClarityValue::buff_from(candidate_key.compress().data.to_vec())?,
+            ),
+        ])?);
+        let Some(tally) = self.get_map_entry(&voting_contract_id, "tally", &key)? else {
+            return Ok(None);
+        };
+        Ok(Some(tally.expect_u128()?))
+    }
+
     /// Retrieve the weight threshold required to approve a DKG vote
     pub fn get_vote_threshold_weight(&self, reward_cycle: u64) -> Result<u128, ClientError> {
         let function_name = ClarityName::from("get-threshold-weight");
@@ -360,13 +937,37 @@ impl StacksClient {
 
     /// Get the current peer info data from the stacks node
     pub fn get_peer_info(&self) -> Result<RPCPeerInfoData, ClientError> {
+        let result = self.get_peer_info_with_retry();
+        self.record_peer_info_result(&result);
+        result
+    }
+
+    /// Check whether the stacks node is reachable with a single `/v2/info` request.
+    /// Unlike `get_peer_info`, this does not retry, so it returns promptly if the node is down.
+    pub fn probe_peer_info(&self) -> Result<RPCPeerInfoData, ClientError> {
+        let result = self.send_peer_info_probe();
+        self.record_peer_info_result(&result);
+        result
+    }
+
+    fn send_peer_info_probe(&self) -> Result<RPCPeerInfoData, ClientError> {
+        let response = self.send_read(|client| {
+            client
+                .get(self.core_info_path())
+                .timeout(NODE_PROBE_TIMEOUT)
+        })?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        Ok(response.json::<RPCPeerInfoData>()?)
+    }
+
+    fn get_peer_info_with_retry(&self) -> Result<RPCPeerInfoData, ClientError> {
         debug!("Getting stacks node info...");
         let timer =
-            crate::monitoring::new_rpc_call_timer(&self.core_info_path(), &self.http_origin);
+            crate::monitoring::new_rpc_call_timer(&self.core_info_path(), &self.http_origin());
         let send_request = || {
-            self.stacks_node_client
-                .get(self.core_info_path())
-                .send()
+            self.send_read(|client| client.get(self.core_info_path()))
                 .map_err(backoff::Error::transient)
         };
         let response = retry_with_exponential_backoff(send_request)?;
@@ -378,6 +979,44 @@ impl StacksClient {
         Ok(peer_info_data)
     }
 
+    /// Measure how far the stacks node's clock is ahead of ours, in milliseconds (negative if
+    /// it is behind), from the `Date` header of a few `/v2/info` responses. `Date` only has a
+    /// resolution of one second, so neither does the result.
+    pub fn measure_clock_skew(&self) -> Result<i64, ClientError> {
+        let mut samples = Vec::with_capacity(CLOCK_SKEW_SAMPLES);
+        for _ in 0..CLOCK_SKEW_SAMPLES {
+            samples.push(self.sample_clock_skew()?);
+        }
+        samples.sort_unstable();
+        Ok(samples[samples.len() / 2])
+    }
+
+    /// Compare the `Date` of a single `/v2/info` response against the middle of its round trip
+    fn sample_clock_skew(&self) -> Result<i64, ClientError> {
+        let sent_at = get_epoch_time_ms();
+        let response = self.send_read(|client| {
+            client
+                .get(self.core_info_path())
+                .timeout(NODE_PROBE_TIMEOUT)
+        })?;
+        let received_at = get_epoch_time_ms();
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let date = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|date| date.to_str().ok())
+            .ok_or_else(|| ClientError::InvalidResponse("missing Date header".to_string()))?;
+        let node_time = chrono::DateTime::parse_from_rfc2822(date)
+            .map_err(|e| ClientError::InvalidResponse(format!("malformed Date header: {e}")))?;
+        // `Date` is truncated to the second, so on average the node is half a second past it
+        let node_time_ms = i128::from(node_time.timestamp_millis()) + 500;
+        let local_time_ms = sent_at + received_at.saturating_sub(sent_at) / 2;
+        let skew_ms = node_time_ms - local_time_ms as i128;
+        Ok(skew_ms.clamp(i64::MIN.into(), i64::MAX.into()) as i64)
+    }
+
     /// Retrieve the last DKG vote round number for the current reward cycle
     pub fn get_last_round(&self, reward_cycle: u64) -> Result<Option<u64>, ClientError> {
         debug!("Getting the last DKG vote round of reward cycle {reward_cycle}...");
@@ -410,15 +1049,18 @@ impl StacksClient {
         &self,
         reward_cycle: u64,
     ) -> Result<Option<Vec<NakamotoSignerEntry>>, ClientError> {
+        Ok(self.get_reward_set(reward_cycle)?.signers)
+    }
+
+    /// Get the reward set from the stacks node for the given reward cycle
+    pub fn get_reward_set(&self, reward_cycle: u64) -> Result<RewardSet, ClientError> {
         debug!("Getting reward set for reward cycle {reward_cycle}...");
         let timer = crate::monitoring::new_rpc_call_timer(
             &self.reward_set_path(reward_cycle),
-            &self.http_origin,
+            &self.http_origin(),
         );
         let send_request = || {
-            self.stacks_node_client
-                .get(self.reward_set_path(reward_cycle))
-                .send()
+            self.send_read(|client| client.get(self.reward_set_path(reward_cycle)))
                 .map_err(backoff::Error::transient)
         };
         let response = retry_with_exponential_backoff(send_request)?;
@@ -427,18 +1069,111 @@ impl StacksClient {
             return Err(ClientError::RequestFailure(response.status()));
         }
         let stackers_response = response.json::<GetStackersResponse>()?;
-        Ok(stackers_response.stacker_set.signers)
+        Ok(stackers_response.stacker_set)
+    }
+
+    /// Get the node's info on the sortition with `consensus_hash`, or on the latest sortition
+    /// if `consensus_hash` is `None`. Nodes without the `/v3/sortitions` endpoint answer with
+    /// `ClientError::UnsupportedStacksFeature`.
+    pub fn get_sortition_info(
+        &self,
+        consensus_hash: Option<&ConsensusHash>,
+    ) -> Result<SortitionInfo, ClientError> {
+        debug!("Getting sortition info..."; "consensus_hash" => ?consensus_hash);
+        let timer = crate::monitoring::new_rpc_call_timer(
+            &self.sortitions_path(consensus_hash),
+            &self.http_origin(),
+        );
+        let send_request = || {
+            self.send_read(|client| client.get(self.sortitions_path(consensus_hash)))
+                .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ClientError::UnsupportedStacksFeature(
+                "/v3/sortitions".to_string(),
+            ));
+        }
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        Ok(response.json::<SortitionInfo>()?)
+    }
+
+    /// Get the node's info on the latest sortition
+    pub fn get_latest_sortition(&self) -> Result<SortitionInfo, ClientError> {
+        self.get_sortition_info(None)
+    }
+
+    /// Get the header of the highest block the node has in the tenure with `consensus_hash`.
+    /// The node only reports the tip of its current tenure, so any other tenure is
+    /// `ClientError::UnknownTenure`.
+    pub fn get_tenure_tip(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Result<NakamotoBlockHeader, ClientError> {
+        debug!("Getting tenure tip..."; "consensus_hash" => %consensus_hash);
+        let timer =
+            crate::monitoring::new_rpc_call_timer(&self.tenure_info_path(), &self.http_origin());
+        let send_request = || {
+            self.send_read(|client| client.get(self.tenure_info_path()))
+                .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let tenure_info = response.json::<RPCGetTenureInfo>()?;
+        if &tenure_info.consensus_hash != consensus_hash {
+            return Err(ClientError::UnknownTenure(*consensus_hash));
+        }
+        let block = self.get_block_by_id(&tenure_info.tip_block_id)?;
+        if &block.header.consensus_hash != consensus_hash {
+            return Err(ClientError::InvalidResponse(format!(
+                "Tenure tip {} is not in tenure {consensus_hash}",
+                tenure_info.tip_block_id
+            )));
+        }
+        Ok(block.header)
+    }
+
+    /// Get the block with `block_id` from the stacks node
+    pub fn get_block_by_id(&self, block_id: &StacksBlockId) -> Result<NakamotoBlock, ClientError> {
+        debug!("Getting block..."; "block_id" => %block_id);
+        let timer =
+            crate::monitoring::new_rpc_call_timer(&self.block_path(block_id), &self.http_origin());
+        let send_request = || {
+            self.send_read(|client| client.get(self.block_path(block_id)))
+                .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ClientError::UnknownBlock(*block_id));
+        }
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let bytes = response.bytes()?;
+        let block = NakamotoBlock::consensus_deserialize(&mut &bytes[..])?;
+        if &block.block_id() != block_id {
+            return Err(ClientError::InvalidResponse(format!(
+                "Requested block {block_id}, but received block {}",
+                block.block_id()
+            )));
+        }
+        Ok(block)
     }
 
     /// Retreive the current pox data from the stacks node
     pub fn get_pox_data(&self) -> Result<RPCPoxInfoData, ClientError> {
         debug!("Getting pox data...");
         #[cfg(feature = "monitoring_prom")]
-        let timer = crate::monitoring::new_rpc_call_timer(&self.pox_path(), &self.http_origin);
+        let timer = crate::monitoring::new_rpc_call_timer(&self.pox_path(), &self.http_origin());
         let send_request = || {
-            self.stacks_node_client
-                .get(self.pox_path())
-                .send()
+            self.send_read(|client| client.get(self.pox_path()))
                 .map_err(backoff::Error::transient)
         };
         let response = retry_with_exponential_backoff(send_request)?;
@@ -459,20 +1194,17 @@ impl StacksClient {
     /// Get the current reward cycle info from the stacks node
     pub fn get_current_reward_cycle_info(&self) -> Result<RewardCycleInfo, ClientError> {
         let pox_data = self.get_pox_data()?;
-        let blocks_mined = pox_data
-            .current_burnchain_block_height
-            .saturating_sub(pox_data.first_burnchain_block_height);
-        let reward_cycle_length = pox_data
-            .reward_phase_block_length
-            .saturating_add(pox_data.prepare_phase_block_length);
-        let reward_cycle = blocks_mined / reward_cycle_length;
-        Ok(RewardCycleInfo {
-            reward_cycle,
-            reward_cycle_length,
+        let mut reward_cycle_info = RewardCycleInfo {
+            reward_cycle: 0,
+            reward_cycle_length: pox_data
+                .reward_phase_block_length
+                .saturating_add(pox_data.prepare_phase_block_length),
             prepare_phase_block_length: pox_data.prepare_phase_block_length,
             first_burnchain_block_height: pox_data.first_burnchain_block_height,
-            last_burnchain_block_height: pox_data.current_burnchain_block_height,
-        })
+            last_burnchain_block_height: 0,
+        };
+        reward_cycle_info.advance_to(pox_data.current_burnchain_block_height);
+        Ok(reward_cycle_info)
     }
 
     /// Helper function to retrieve the account info from the stacks node for a specific address
@@ -481,12 +1213,12 @@ impl StacksClient {
         address: &StacksAddress,
     ) -> Result<AccountEntryResponse, ClientError> {
         debug!("Getting account info...");
-        let timer =
-            crate::monitoring::new_rpc_call_timer(&self.accounts_path(address), &self.http_origin);
+        let timer = crate::monitoring::new_rpc_call_timer(
+            &self.accounts_path(address),
+            &self.http_origin(),
+        );
         let send_request = || {
-            self.stacks_node_client
-                .get(self.accounts_path(address))
-                .send()
+            self.send_read(|client| client.get(self.accounts_path(address)))
                 .map_err(backoff::Error::transient)
         };
         let response = retry_with_exponential_backoff(send_request)?;
@@ -498,26 +1230,14 @@ impl StacksClient {
         Ok(account_entry)
     }
 
-    /// Helper function that attempts to deserialize a clarity hex string as the aggregate public key
+    /// Helper function that attempts to convert a clarity buffer to the aggregate public key
     fn parse_aggregate_public_key(
         &self,
         value: ClarityValue,
     ) -> Result<Option<Point>, ClientError> {
         debug!("Parsing aggregate public key...");
         let data = value.expect_buff(33)?;
-        // It is possible that the point was invalid though when voted upon and this cannot be prevented by pox 4 definitions...
-        // Pass up this error if the conversions fail.
-        let compressed_data = Compressed::try_from(data.as_slice()).map_err(|e| {
-            ClientError::MalformedClarityValue(format!(
-                "Failed to convert aggregate public key to compressed data: {e}"
-            ))
-        })?;
-        let dkg_public_key = Point::try_from(&compressed_data).map_err(|e| {
-            ClientError::MalformedClarityValue(format!(
-                "Failed to convert aggregate public key to a point: {e}"
-            ))
-        })?;
-        Ok(Some(dkg_public_key))
+        aggregate_key_from_bytes(&data).map(Some)
     }
 
     /// Helper function to create a stacks transaction for a modifying contract call
@@ -545,7 +1265,7 @@ impl StacksClient {
             contract_name,
             function_name,
             &function_args,
-            &self.stacks_private_key,
+            self.private_key_for_cycle(reward_cycle),
             self.tx_version,
             self.chain_id,
             nonce,
@@ -553,28 +1273,126 @@ impl StacksClient {
         Ok(unsigned_tx)
     }
 
+    /// The index of this signer in the reward set of `reward_cycle`, which its votes for the
+    /// cycle's aggregate public key must carry. `None` if it is not one of the cycle's signers.
+    pub fn get_signer_index(&self, reward_cycle: u64) -> Result<Option<u32>, ClientError> {
+        let signing_key = StacksPublicKey::from_private(self.private_key_for_cycle(reward_cycle));
+        let signing_key = signing_key.to_bytes_compressed();
+        let signers = self
+            .get_reward_set_signers(reward_cycle)?
+            .unwrap_or_default();
+        let Some(signer_index) = signers
+            .iter()
+            .position(|signer| signer.signing_key.as_slice() == signing_key.as_slice())
+        else {
+            return Ok(None);
+        };
+        let signer_index = u32::try_from(signer_index)
+            .map_err(|_| ClientError::MalformedContractData("More than u32::MAX signers".into()))?;
+        Ok(Some(signer_index))
+    }
+
+    /// Build and sign a vote for `dkg_public_key` as the aggregate public key of `reward_cycle`
+    /// in voting `round`, at the signer's current account nonce and paying `tx_fee_ustx`
+    pub fn build_vote_for_aggregate_public_key(
+        &self,
+        signer_index: u32,
+        round: u64,
+        dkg_public_key: Point,
+        reward_cycle: u64,
+        tx_fee_ustx: u64,
+    ) -> Result<StacksTransaction, ClientError> {
+        let nonce = self.get_account_nonce(self.get_signer_address_for_cycle(reward_cycle))?;
+        let mut unsigned_tx = self.build_unsigned_vote_for_aggregate_public_key(
+            signer_index,
+            round,
+            dkg_public_key,
+            reward_cycle,
+            nonce,
+        )?;
+        unsigned_tx.set_tx_fee(tx_fee_ustx);
+        self.sign_transaction(unsigned_tx, reward_cycle)
+    }
+
+    /// Vote for `dkg_public_key` as the aggregate public key of `reward_cycle` in voting
+    /// `round` by submitting a vote transaction to the mempool. Returns the submitted transaction.
+    pub fn cast_aggregate_public_key_vote(
+        &self,
+        signer_index: u32,
+        round: u64,
+        dkg_public_key: Point,
+        reward_cycle: u64,
+        tx_fee_ustx: u64,
+    ) -> Result<StacksTransaction, ClientError> {
+        let tx = self.build_vote_for_aggregate_public_key(
+            signer_index,
+            round,
+            dkg_public_key,
+            reward_cycle,
+            tx_fee_ustx,
+        )?;
+        self.submit_transaction(&tx)?;
+        Ok(tx)
+    }
+
+    /// Wait up to `timeout` for `tx` to be confirmed. The node only reports the status of
+    /// unconfirmed transactions, so `tx` counts as confirmed once its origin account's nonce
+    /// has moved past it (which is also the case if another transaction replaced it).
+    pub fn wait_for_confirmation(
+        &self,
+        tx: &StacksTransaction,
+        timeout: Duration,
+    ) -> Result<(), ClientError> {
+        let origin = tx.origin_address();
+        let start = Instant::now();
+        loop {
+            if self.get_account_nonce(&origin)? > tx.get_origin_nonce() {
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                return Err(ClientError::ConfirmationTimeout(tx.txid()));
+            }
+            std::thread::sleep(CONFIRMATION_POLL_INTERVAL.min(timeout));
+        }
+    }
+
     /// Helper function to submit a transaction to the Stacks mempool
     pub fn submit_transaction(&self, tx: &StacksTransaction) -> Result<Txid, ClientError> {
         let txid = tx.txid();
-        let tx = tx.serialize_to_vec();
+        let tx_bytes = tx.serialize_to_vec();
+        if self.dry_run {
+            info!(
+                "Dry run: not submitting a transaction";
+                "txid" => %txid,
+                "origin" => %tx.origin_address(),
+                "nonce" => tx.get_origin_nonce(),
+                "tx_fee" => tx.get_tx_fee(),
+                "tx_len" => tx_bytes.len(),
+            );
+            return Ok(txid);
+        }
         let timer =
-            crate::monitoring::new_rpc_call_timer(&self.transaction_path(), &self.http_origin);
+            crate::monitoring::new_rpc_call_timer(&self.transaction_path(), &self.http_origin());
         let send_request = || {
-            self.stacks_node_client
-                .post(self.transaction_path())
-                .header("Content-Type", "application/octet-stream")
-                .body(tx.clone())
-                .send()
-                .map_err(|e| {
-                    debug!("Failed to submit transaction to the Stacks node: {e:?}");
-                    backoff::Error::transient(e)
-                })
+            self.send_write(|client| {
+                client
+                    .post(self.transaction_path())
+                    .header("Content-Type", "application/octet-stream")
+                    .body(tx_bytes.clone())
+            })
+            .map_err(|e| {
+                debug!("Failed to submit transaction to the Stacks node: {e:?}");
+                backoff::Error::transient(e)
+            })
         };
         let response = retry_with_exponential_backoff(send_request)?;
         timer.stop_and_record();
         if !response.status().is_success() {
             return Err(ClientError::RequestFailure(response.status()));
         }
+        if let Some(audit_log) = self.audit_log.as_ref() {
+            audit::record(audit_log, AuditEntry::transaction(tx));
+        }
         Ok(txid)
     }
 
@@ -600,13 +1418,13 @@ impl StacksClient {
         let body =
             json!({"sender": self.stacks_address.to_string(), "arguments": args}).to_string();
         let path = self.read_only_path(contract_addr, contract_name, function_name);
-        let timer = crate::monitoring::new_rpc_call_timer(&path, &self.http_origin);
-        let response = self
-            .stacks_node_client
-            .post(path)
-            .header("Content-Type", "application/json")
-            .body(body)
-            .send()?;
+        let timer = crate::monitoring::new_rpc_call_timer(&path, &self.http_origin());
+        let response = self.send_read(|client| {
+            client
+                .post(self.read_only_path(contract_addr, contract_name, function_name))
+                .header("Content-Type", "application/json")
+                .body(body.clone())
+        })?;
         timer.stop_and_record();
         if !response.status().is_success() {
             return Err(ClientError::RequestFailure(response.status()));
@@ -621,16 +1439,96 @@ impl StacksClient {
             )));
         }
         let hex = call_read_only_response.result.unwrap_or_default();
-        let value = ClarityValue::try_deserialize_hex_untyped(&hex)?;
+        Self::parse_clarity_hex(&hex, function_name)
+    }
+
+    /// Read the data var `var_name` of `contract` directly from the node's state, without the
+    /// cost of a read-only call
+    pub fn get_data_var(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        var_name: &str,
+    ) -> Result<ClarityValue, ClientError> {
+        debug!("Getting data var {contract}.{var_name}...");
+        let timer = crate::monitoring::new_rpc_call_timer(
+            &self.data_var_path(contract, var_name),
+            &self.http_origin(),
+        );
+        let send_request = || {
+            self.send_read(|client| client.get(self.data_var_path(contract, var_name)))
+                .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ClientError::NotFound(format!(
+                "data var {contract}.{var_name}"
+            )));
+        }
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let data_var_response = response.json::<DataVarResponse>()?;
+        Self::parse_clarity_hex(&data_var_response.data, var_name)
+    }
+
+    /// Read the entry for `key` in the map `map_name` of `contract` directly from the node's
+    /// state, or `None` if the map has no such entry
+    pub fn get_map_entry(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        map_name: &str,
+        key: &ClarityValue,
+    ) -> Result<Option<ClarityValue>, ClientError> {
+        debug!("Getting map entry {contract}.{map_name} for key {key}...");
+        let key_hex = key.serialize_to_hex().map_err(|e| {
+            ClientError::MalformedClarityValue(format!(
+                "{map_name}: failed to serialize key: {e:?}"
+            ))
+        })?;
+        let body = json!(key_hex).to_string();
+        let timer = crate::monitoring::new_rpc_call_timer(
+            &self.map_entry_path(contract, map_name),
+            &self.http_origin(),
+        );
+        let send_request = || {
+            self.send_read(|client| {
+                client
+                    .post(self.map_entry_path(contract, map_name))
+                    .header("Content-Type", "application/json")
+                    .body(body.clone())
+            })
+            .map_err(backoff::Error::transient)
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        timer.stop_and_record();
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let map_entry_response = response.json::<MapEntryResponse>()?;
+        // The node returns the entry as an `(optional ...)`, which is `none` if it is absent
+        let value = Self::parse_clarity_hex(&map_entry_response.data, map_name)?;
+        Ok(value.expect_optional()?)
+    }
+
+    /// Deserialize the hex-encoded Clarity value that the node returned for `name`, with or
+    /// without a `0x` prefix. The value is decoded as it is deserialized, without an
+    /// intermediate buffer.
+    fn parse_clarity_hex(hex: &str, name: &str) -> Result<ClarityValue, ClientError> {
+        let bad_hex =
+            |e| ClientError::MalformedClarityValue(format!("{name}: bad hex result: {e}"));
+        let digits = hex_digits(hex).map_err(|e| bad_hex(e.to_string()))?;
+        let mut reader = HexReader::new(digits).map_err(|e| bad_hex(e.to_string()))?;
+        let value = ClarityValue::deserialize_read(&mut reader, None, false)?;
         Ok(value)
     }
 
     fn pox_path(&self) -> String {
-        format!("{}/v2/pox", self.http_origin)
+        format!("{}/v2/pox", self.http_origin())
     }
 
     fn transaction_path(&self) -> String {
-        format!("{}/v2/transactions", self.http_origin)
+        format!("{}/v2/transactions", self.http_origin())
     }
 
     fn read_only_path(
@@ -641,28 +1539,69 @@ impl StacksClient {
     ) -> String {
         format!(
             "{}/v2/contracts/call-read/{contract_addr}/{contract_name}/{function_name}",
-            self.http_origin
+            self.http_origin()
+        )
+    }
+
+    fn data_var_path(&self, contract: &QualifiedContractIdentifier, var_name: &str) -> String {
+        format!(
+            "{}/v2/data_var/{}/{}/{var_name}?proof=0",
+            self.http_origin(),
+            contract.issuer,
+            contract.name
+        )
+    }
+
+    fn map_entry_path(&self, contract: &QualifiedContractIdentifier, map_name: &str) -> String {
+        format!(
+            "{}/v2/map_entry/{}/{}/{map_name}?proof=0",
+            self.http_origin(),
+            contract.issuer,
+            contract.name
         )
     }
 
     fn block_proposal_path(&self) -> String {
-        format!("{}/v2/block_proposal", self.http_origin)
+        format!("{}/v2/block_proposal", self.http_origin())
     }
 
     fn core_info_path(&self) -> String {
-        format!("{}/v2/info", self.http_origin)
+        format!("{}/v2/info", self.http_origin())
     }
 
     fn accounts_path(&self, stacks_address: &StacksAddress) -> String {
-        format!("{}/v2/accounts/{stacks_address}?proof=0", self.http_origin)
+        format!(
+            "{}/v2/accounts/{stacks_address}?proof=0",
+            self.http_origin()
+        )
     }
 
     fn reward_set_path(&self, reward_cycle: u64) -> String {
-        format!("{}/v2/stacker_set/{reward_cycle}", self.http_origin)
+        format!("{}/v2/stacker_set/{reward_cycle}", self.http_origin())
+    }
+
+    fn sortitions_path(&self, consensus_hash: Option<&ConsensusHash>) -> String {
+        match consensus_hash {
+            Some(consensus_hash) => {
+                format!(
+                    "{}/v3/sortitions/consensus/{consensus_hash}",
+                    self.http_origin()
+                )
+            }
+            None => format!("{}/v3/sortitions", self.http_origin()),
+        }
+    }
+
+    fn tenure_info_path(&self) -> String {
+        format!("{}/v3/tenures/info", self.http_origin())
+    }
+
+    fn block_path(&self, block_id: &StacksBlockId) -> String {
+        format!("{}/v3/blocks/{block_id}", self.http_origin())
     }
 
     fn fees_transaction_path(&self) -> String {
-        format!("{}/v2/fees/transaction", self.http_origin)
+        format!("{}/v2/fees/transaction", self.http_origin())
     }
 
     /// Helper function to create a stacks transaction for a modifying contract call
@@ -702,14 +1641,15 @@ impl StacksClient {
         Ok(unsigned_tx)
     }
 
-    /// Sign an unsigned transaction
+    /// Sign an unsigned transaction with the key used in `reward_cycle`
     pub fn sign_transaction(
         &self,
         unsigned_tx: StacksTransaction,
+        reward_cycle: u64,
     ) -> Result<StacksTransaction, ClientError> {
         let mut tx_signer = StacksTransactionSigner::new(&unsigned_tx);
         tx_signer
-            .sign_origin(&self.stacks_private_key)
+            .sign_origin(self.private_key_for_cycle(reward_cycle))
             .map_err(|e| ClientError::TransactionGenerationFailure(e.to_string()))?;
 
         tx_signer
@@ -723,11 +1663,11 @@ impl StacksClient {
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
-    use std::io::{BufWriter, Write};
+    use std::io::{BufWriter, Read, Write};
+    use std::net::TcpListener;
     use std::thread::spawn;
 
     use blockstack_lib::burnchains::Address;
-    use blockstack_lib::chainstate::nakamoto::NakamotoBlockHeader;
     use blockstack_lib::chainstate::stacks::address::PoxAddress;
     use blockstack_lib::chainstate::stacks::boot::{
         NakamotoSignerEntry, PoxStartCycleInfo, RewardSet,
@@ -747,7 +1687,8 @@ mod tests {
         build_get_last_round_response, build_get_medium_estimated_fee_ustx_response,
         build_get_peer_info_response, build_get_pox_data_response, build_get_round_info_response,
         build_get_vote_for_aggregate_key_response, build_get_weight_threshold_response,
-        build_read_only_response, write_response, MockServerClient,
+        build_map_entry_response, build_read_only_response, mock_server_from_config,
+        mock_server_random, write_response, MockServerClient,
     };
 
     #[test]
@@ -847,6 +1788,45 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn get_data_var_should_succeed() {
+        let mock = MockServerClient::new();
+        let contract = boot_code_id(SIGNERS_VOTING_NAME, false);
+        let value = ClarityValue::UInt(thread_rng().next_u64() as u128);
+        let response = format!(
+            "HTTP/1.1 200 OK\n\n{{\"data\":\"0x{}\"}}",
+            value.serialize_to_hex().unwrap()
+        );
+        let h = spawn(move || mock.client.get_data_var(&contract, "round-state"));
+        let request = write_response(mock.server, response.as_bytes());
+        assert!(String::from_utf8_lossy(&request).starts_with(&format!(
+            "GET /v2/data_var/{}/{SIGNERS_VOTING_NAME}/round-state?proof=0 ",
+            boot_code_addr(false)
+        )));
+        assert_eq!(h.join().unwrap().unwrap(), value);
+    }
+
+    #[test]
+    fn get_absent_data_var_should_fail() {
+        let mock = MockServerClient::new();
+        let contract = boot_code_id(SIGNERS_VOTING_NAME, false);
+        let h = spawn(move || mock.client.get_data_var(&contract, "round-state"));
+        write_response(mock.server, b"HTTP/1.1 404 Not Found\n\n");
+        assert!(matches!(h.join().unwrap(), Err(ClientError::NotFound(_))));
+    }
+
+    #[test]
+    fn get_malformed_data_var_should_fail() {
+        let mock = MockServerClient::new();
+        let contract = boot_code_id(SIGNERS_VOTING_NAME, false);
+        let h = spawn(move || mock.client.get_data_var(&contract, "round-state"));
+        write_response(mock.server, b"HTTP/1.1 200 OK\n\n{\"data\":\"0xzz01\"}");
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::MalformedClarityValue(_))
+        ));
+    }
+
     #[test]
     fn valid_reward_cycle_should_succeed() {
         let mock = MockServerClient::new();
@@ -894,6 +1874,38 @@ mod tests {
         assert!(res.is_none());
     }
 
+    #[test]
+    fn get_aggregate_public_key_should_accept_either_hex_style() {
+        let orig_point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let value = ClarityValue::some(
+            ClarityValue::buff_from(orig_point.compress().as_bytes().to_vec()).unwrap(),
+        )
+        .unwrap();
+        let hex = value.serialize_to_hex().unwrap();
+        // some nodes prefix their read-only results with `0x`, and others do not
+        for result in [hex.clone(), format!("0x{hex}"), format!(" 0x{hex}\\n")] {
+            let response = format!("HTTP/1.1 200 OK\n\n{{\"okay\":true,\"result\":\"{result}\"}}");
+            let mock = MockServerClient::new();
+            let h = spawn(move || mock.client.get_approved_aggregate_key(0));
+            write_response(mock.server, response.as_bytes());
+            assert_eq!(h.join().unwrap().unwrap(), Some(orig_point), "{result:?}");
+        }
+
+        // an odd number of digits is refused in either style
+        for result in [format!("{hex}0"), format!("0x{hex}0")] {
+            let response = format!("HTTP/1.1 200 OK\n\n{{\"okay\":true,\"result\":\"{result}\"}}");
+            let mock = MockServerClient::new();
+            let h = spawn(move || mock.client.get_approved_aggregate_key(0));
+            write_response(mock.server, response.as_bytes());
+            match h.join().unwrap() {
+                Err(ClientError::MalformedClarityValue(reason)) => {
+                    assert!(reason.contains("odd number of hex digits"), "{reason}")
+                }
+                res => panic!("Expected {result:?} to be malformed, got {res:?}"),
+            }
+        }
+    }
+
     #[test]
     fn parse_valid_aggregate_public_key_should_succeed() {
         let mock = MockServerClient::new();
@@ -931,7 +1943,7 @@ mod tests {
         )
         .unwrap();
 
-        let tx = mock.client.sign_transaction(unsigned_tx).unwrap();
+        let tx = mock.client.sign_transaction(unsigned_tx, 0).unwrap();
 
         let mut tx_bytes = [0u8; 1024];
         {
@@ -967,6 +1979,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dry_run_does_not_submit_transactions() {
+        let mut config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        let (server, mock_server_addr) = mock_server_random();
+        config.node_host = mock_server_addr.to_string();
+        config.dry_run = true;
+        let client = StacksClient::from(&config);
+        assert!(client.health_state().read().unwrap().report().dry_run);
+
+        let unsigned_tx = StacksClient::build_unsigned_contract_call_transaction(
+            &client.stacks_address,
+            ContractName::from("contract-name"),
+            ClarityName::from("function-name"),
+            &[],
+            &config.stacks_private_key,
+            TransactionVersion::Testnet,
+            CHAIN_ID_TESTNET,
+            0,
+        )
+        .unwrap();
+        let tx = client.sign_transaction(unsigned_tx, 0).unwrap();
+        assert_eq!(client.submit_transaction(&tx).unwrap(), tx.txid());
+
+        server.set_nonblocking(true).unwrap();
+        assert_eq!(
+            server.accept().unwrap_err().kind(),
+            std::io::ErrorKind::WouldBlock,
+            "A dry run connected to the stacks node"
+        );
+    }
+
     #[test]
     fn build_vote_for_aggregate_public_key_should_succeed() {
         let mock = MockServerClient::new();
@@ -1006,7 +2049,10 @@ mod tests {
                 nonce,
             )
             .unwrap();
-        let tx = mock.client.sign_transaction(unsigned_tx).unwrap();
+        let tx = mock
+            .client
+            .sign_transaction(unsigned_tx, reward_cycle)
+            .unwrap();
         let tx_clone = tx.clone();
         let h = spawn(move || mock.client.submit_transaction(&tx_clone));
 
@@ -1019,6 +2065,167 @@ mod tests {
         assert_eq!(returned_txid, tx.txid());
     }
 
+    #[test]
+    fn vote_transactions_are_signed_with_the_key_for_their_cycle() {
+        let config_toml = std::fs::read_to_string("./src/tests/conf/signer-0.toml").unwrap();
+        let next_key = StacksPrivateKey::new();
+        let config = GlobalConfig::load_from_str(&format!(
+            "{config_toml}\nnext_stacks_private_key = \"{}\"\nnext_key_activation_reward_cycle = 10\n",
+            next_key.to_hex()
+        ))
+        .unwrap();
+        let client = StacksClient::from(&config);
+        let next_address = config.next_stacks_key.as_ref().unwrap().stacks_address;
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+
+        for (reward_cycle, address) in [
+            (9, config.stacks_address),
+            (10, next_address),
+            (11, next_address),
+        ] {
+            assert_eq!(client.get_signer_address_for_cycle(reward_cycle), &address);
+            let unsigned_tx = client
+                .build_unsigned_vote_for_aggregate_public_key(0, 0, point, reward_cycle, 0)
+                .unwrap();
+            let tx = client.sign_transaction(unsigned_tx, reward_cycle).unwrap();
+            assert_eq!(tx.origin_address(), address);
+            tx.verify().unwrap();
+        }
+
+        // a vote for the last cycle of the old key cannot be signed with the new one
+        let unsigned_tx = client
+            .build_unsigned_vote_for_aggregate_public_key(0, 0, point, 9, 0)
+            .unwrap();
+        let signed = client.sign_transaction(unsigned_tx, 10);
+        assert!(!matches!(signed.map(|tx| tx.verify()), Ok(Ok(()))));
+    }
+
+    #[test]
+    fn cast_aggregate_public_key_vote_should_succeed() {
+        let mock = MockServerClient::new();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let h = spawn(move || {
+            mock.client
+                .cast_aggregate_public_key_vote(3, 1, point, 10, 500)
+        });
+        write_response(mock.server, build_account_nonce_response(7).as_bytes());
+        let mock_server = mock_server_from_config(&mock.config);
+        let request_bytes = write_response(mock_server, b"HTTP/1.1 200 OK\n\n");
+        let tx = h.join().unwrap().unwrap();
+        assert_eq!(tx.get_origin_nonce(), 7);
+        assert_eq!(tx.get_tx_fee(), 500);
+        assert_eq!(tx.origin_address(), mock.config.stacks_address);
+        let tx_bytes = tx.serialize_to_vec();
+        assert!(
+            request_bytes
+                .windows(tx_bytes.len())
+                .any(|window| window == tx_bytes.as_slice()),
+            "Request bytes did not contain the transaction bytes"
+        );
+        let TransactionPayload::ContractCall(call) = &tx.payload else {
+            panic!("Expected a contract call");
+        };
+        assert_eq!(call.function_name.as_str(), SIGNERS_VOTING_FUNCTION_NAME);
+        assert_eq!(
+            call.function_args,
+            vec![
+                ClarityValue::UInt(3),
+                ClarityValue::buff_from(point.compress().data.to_vec()).unwrap(),
+                ClarityValue::UInt(1),
+                ClarityValue::UInt(10),
+            ]
+        );
+    }
+
+    #[test]
+    fn cast_aggregate_public_key_vote_rejected() {
+        let mock = MockServerClient::new();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let h = spawn(move || {
+            mock.client
+                .cast_aggregate_public_key_vote(3, 1, point, 10, 500)
+        });
+        write_response(mock.server, build_account_nonce_response(7).as_bytes());
+        let mock_server = mock_server_from_config(&mock.config);
+        write_response(mock_server, b"HTTP/1.1 400 Bad Request\n\n");
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::RequestFailure(
+                reqwest::StatusCode::BAD_REQUEST
+            ))
+        ));
+    }
+
+    #[test]
+    fn wait_for_confirmation_checks_the_account_nonce() {
+        let mock = MockServerClient::new();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let unsigned_tx = mock
+            .client
+            .build_unsigned_vote_for_aggregate_public_key(3, 1, point, 10, 7)
+            .unwrap();
+        let tx = mock.client.sign_transaction(unsigned_tx, 10).unwrap();
+
+        let tx_clone = tx.clone();
+        let h = spawn(move || {
+            mock.client
+                .wait_for_confirmation(&tx_clone, Duration::from_secs(60))
+        });
+        write_response(mock.server, build_account_nonce_response(8).as_bytes());
+        h.join().unwrap().unwrap();
+
+        // still pending when the time is up
+        let mock = MockServerClient::new();
+        let tx_clone = tx.clone();
+        let h = spawn(move || mock.client.wait_for_confirmation(&tx_clone, Duration::ZERO));
+        write_response(mock.server, build_account_nonce_response(7).as_bytes());
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::ConfirmationTimeout(txid)) if txid == tx.txid()
+        ));
+    }
+
+    #[test]
+    fn get_signer_index_should_succeed() {
+        let mock = MockServerClient::new();
+        let signing_key =
+            StacksPublicKey::from_private(&mock.config.stacks_private_key).to_bytes_compressed();
+        let other_key = Point::from(Scalar::random(&mut rand::thread_rng())).compress();
+        let signer = |signing_key: &[u8]| NakamotoSignerEntry {
+            signing_key: signing_key.try_into().unwrap(),
+            stacked_amt: 1,
+            weight: 1,
+        };
+        let stacker_set = RewardSet {
+            rewarded_addresses: vec![PoxAddress::standard_burn_address(false)],
+            start_cycle_state: PoxStartCycleInfo {
+                missed_reward_slots: vec![],
+            },
+            signers: Some(vec![signer(other_key.as_bytes()), signer(&signing_key)]),
+            pox_ustx_threshold: None,
+        };
+        let stackers_response_json = serde_json::to_string(&GetStackersResponse {
+            stacker_set: stacker_set.clone(),
+        })
+        .expect("Failed to serialize get stacker response");
+        let response = format!("HTTP/1.1 200 OK\n\n{stackers_response_json}");
+
+        let h = spawn(move || mock.client.get_signer_index(0));
+        write_response(mock.server, response.as_bytes());
+        assert_eq!(h.join().unwrap().unwrap(), Some(1));
+
+        // not one of the cycle's signers
+        let mock = MockServerClient::new();
+        let mut stacker_set = stacker_set;
+        stacker_set.signers.as_mut().unwrap().pop();
+        let stackers_response_json = serde_json::to_string(&GetStackersResponse { stacker_set })
+            .expect("Failed to serialize get stacker response");
+        let response = format!("HTTP/1.1 200 OK\n\n{stackers_response_json}");
+        let h = spawn(move || mock.client.get_signer_index(0));
+        write_response(mock.server, response.as_bytes());
+        assert_eq!(h.join().unwrap().unwrap(), None);
+    }
+
     #[test]
     fn core_info_call_for_burn_block_height_should_succeed() {
         let mock = MockServerClient::new();
@@ -1090,7 +2297,10 @@ mod tests {
                 let principal_data = StacksAddress::from_string(signer).unwrap().into();
 
                 let data_map = [
-                    ("num-slots".into(), ClarityValue::UInt(13)),
+                    (
+                        "num-slots".into(),
+                        ClarityValue::UInt(SIGNER_SLOTS_PER_USER.into()),
+                    ),
                     (
                         "signer".into(),
                         ClarityValue::Principal(PrincipalData::Standard(principal_data)),
@@ -1256,6 +2466,138 @@ mod tests {
         assert_eq!(h.join().unwrap().unwrap(), peer_info);
     }
 
+    /// A clock that only moves when it is told to
+    #[derive(Debug)]
+    struct MockClock(Mutex<Instant>);
+
+    impl MockClock {
+        fn advance(&self, by: Duration) {
+            *self.0.lock().unwrap() += by;
+        }
+    }
+
+    impl MonotonicClock for MockClock {
+        fn now(&self) -> Instant {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    /// Have `client` fetch `peer_info` from a mock server at the node host in `config`
+    fn serve_peer_info(client: &StacksClient, config: &GlobalConfig, peer_info: &RPCPeerInfoData) {
+        let server = mock_server_from_config(config);
+        let response = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(peer_info).unwrap()
+        );
+        let client = client.clone();
+        let h = spawn(move || client.get_peer_info());
+        write_response(server, response.as_bytes());
+        h.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn node_staleness_grows_while_the_chain_tip_stands_still() {
+        let MockServerClient {
+            server,
+            mut client,
+            config,
+        } = MockServerClient::new();
+        drop(server);
+        let clock = Arc::new(MockClock(Mutex::new(Instant::now())));
+        client.set_clock(clock.clone());
+        assert_eq!(client.node_staleness(), Duration::ZERO);
+
+        let (_, mut peer_info) = build_get_peer_info_response(None, None);
+        serve_peer_info(&client, &config, &peer_info);
+        assert_eq!(client.node_staleness(), Duration::ZERO);
+
+        // No new burn block within the expected interval is not stale
+        clock.advance(config.burn_block_interval);
+        assert_eq!(client.node_staleness(), Duration::ZERO);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(client.node_staleness(), Duration::from_secs(60));
+
+        // Reporting the same tip again does not make the node any fresher
+        serve_peer_info(&client, &config, &peer_info);
+        assert_eq!(client.node_staleness(), Duration::from_secs(60));
+
+        // A new stacks block does
+        peer_info.stacks_tip = BlockHeaderHash([0x07; 32]);
+        serve_peer_info(&client, &config, &peer_info);
+        assert_eq!(client.node_staleness(), Duration::ZERO);
+
+        // And so does a new burn block
+        clock.advance(config.burn_block_interval * 2);
+        assert_eq!(client.node_staleness(), config.burn_block_interval);
+        peer_info.burn_block_height += 1;
+        serve_peer_info(&client, &config, &peer_info);
+        assert_eq!(client.node_staleness(), Duration::ZERO);
+    }
+
+    #[test]
+    fn probe_peer_info_should_update_health_state() {
+        let mock = MockServerClient::new();
+        let health = mock.client.health_state();
+        let (response, peer_info) = build_get_peer_info_response(None, None);
+        let client = mock.client.clone();
+        let h = spawn(move || client.probe_peer_info());
+        write_response(mock.server, response.as_bytes());
+        assert_eq!(h.join().unwrap().unwrap(), peer_info);
+        let report = health.read().unwrap().report();
+        assert!(report.node_reachable);
+        assert!(report.recent_errors.is_empty());
+
+        let server = mock_server_from_config(&mock.config);
+        let client = mock.client.clone();
+        let h = spawn(move || client.probe_peer_info());
+        write_response(server, b"HTTP/1.1 500 Internal Server Error\n\n");
+        assert!(h.join().unwrap().is_err());
+        let report = health.read().unwrap().report();
+        assert!(report.node_reachable);
+        assert_eq!(report.recent_errors.len(), 1);
+    }
+
+    /// Answer each clock skew sample with a `/v2/info` response whose `Date` is `offset` from
+    /// the local time
+    fn write_skewed_date_responses(server: TcpListener, offset: chrono::Duration) {
+        let (response, _) = build_get_peer_info_response(None, None);
+        let body = response.split_once("\n\n").unwrap().1.to_string();
+        for _ in 0..CLOCK_SKEW_SAMPLES {
+            let date = (chrono::Utc::now() + offset).format("%a, %d %b %Y %H:%M:%S GMT");
+            let mut stream = server.accept().unwrap().0;
+            let _ = stream.read(&mut [0u8; 1024]).unwrap();
+            write!(stream, "HTTP/1.1 200 OK\nDate: {date}\n\n{body}").unwrap();
+        }
+    }
+
+    #[test]
+    fn measure_clock_skew_should_detect_skewed_node_clock() {
+        // the Date header only has a resolution of one second
+        let tolerance_ms = 1_500;
+        for offset_secs in [600, -120, 0] {
+            let mock = MockServerClient::new();
+            let h = spawn(move || mock.client.measure_clock_skew());
+            write_skewed_date_responses(mock.server, chrono::Duration::seconds(offset_secs));
+            let skew_ms = h.join().unwrap().unwrap();
+            assert!(
+                (skew_ms - offset_secs * 1000).abs() <= tolerance_ms,
+                "measured {skew_ms}ms for a {offset_secs}s skew"
+            );
+        }
+    }
+
+    #[test]
+    fn measure_clock_skew_should_fail_without_date_header() {
+        let mock = MockServerClient::new();
+        let (response, _) = build_get_peer_info_response(None, None);
+        let h = spawn(move || mock.client.measure_clock_skew());
+        write_response(mock.server, response.as_bytes());
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::InvalidResponse(_))
+        ));
+    }
+
     #[test]
     fn get_last_round_should_succeed() {
         let mock = MockServerClient::new();
@@ -1297,6 +2639,72 @@ mod tests {
         assert_eq!(h.join().unwrap().unwrap(), stacker_set.signers);
     }
 
+    #[test]
+    fn get_round_vote_tally_should_succeed() {
+        let mock = MockServerClient::new();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let tally = thread_rng().next_u64() as u128;
+        let response = build_map_entry_response(Some(ClarityValue::UInt(tally)));
+        let h = spawn(move || mock.client.get_round_vote_tally(1, 2, &point));
+        let request = write_response(mock.server, response.as_bytes());
+        let request = String::from_utf8_lossy(&request);
+        assert!(request.starts_with(&format!(
+            "POST /v2/map_entry/{}/{SIGNERS_VOTING_NAME}/tally?proof=0 ",
+            boot_code_addr(false)
+        )));
+        let key = ClarityValue::Tuple(
+            TupleData::from_data(vec![
+                ("reward-cycle".into(), ClarityValue::UInt(1)),
+                ("round".into(), ClarityValue::UInt(2)),
+                (
+                    "aggregate-public-key".into(),
+                    ClarityValue::buff_from(point.compress().data.to_vec()).unwrap(),
+                ),
+            ])
+            .unwrap(),
+        );
+        assert!(request.contains(&key.serialize_to_hex().unwrap()));
+        assert_eq!(h.join().unwrap().unwrap(), Some(tally));
+
+        let mock = MockServerClient::new();
+        let response = build_map_entry_response(None);
+        let h = spawn(move || mock.client.get_round_vote_tally(1, 2, &point));
+        write_response(mock.server, response.as_bytes());
+        assert_eq!(h.join().unwrap().unwrap(), None);
+    }
+
+    #[test]
+    fn get_map_entry_with_a_malformed_body_should_fail() {
+        let mock = MockServerClient::new();
+        let contract = boot_code_id(SIGNERS_VOTING_NAME, false);
+        let h = spawn(move || {
+            mock.client
+                .get_map_entry(&contract, "tally", &ClarityValue::UInt(0))
+        });
+        write_response(mock.server, b"HTTP/1.1 200 OK\n\n{\"data\":\"0xzz\"}");
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::MalformedClarityValue(_))
+        ));
+
+        // A value that is not an `(optional ...)`
+        let mock = MockServerClient::new();
+        let contract = boot_code_id(SIGNERS_VOTING_NAME, false);
+        let response = format!(
+            "HTTP/1.1 200 OK\n\n{{\"data\":\"0x{}\"}}",
+            ClarityValue::UInt(0).serialize_to_hex().unwrap()
+        );
+        let h = spawn(move || {
+            mock.client
+                .get_map_entry(&contract, "tally", &ClarityValue::UInt(0))
+        });
+        write_response(mock.server, response.as_bytes());
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::ClarityError(_))
+        ));
+    }
+
     #[test]
     fn get_vote_for_aggregate_public_key_should_succeed() {
         let mock = MockServerClient::new();
@@ -1370,4 +2778,310 @@ mod tests {
         write_response(mock.server, response.as_bytes());
         assert_eq!(h.join().unwrap().unwrap(), estimate);
     }
+
+    fn build_sortition_info(consensus_hash: ConsensusHash) -> SortitionInfo {
+        SortitionInfo {
+            burn_block_height: thread_rng().next_u64(),
+            sortition_id: SortitionId([0x01; 32]),
+            parent_sortition_id: SortitionId([0x02; 32]),
+            consensus_hash,
+            was_sortition: true,
+            miner_pk_hash160: Some(Hash160([0x03; 20])),
+            stacks_parent_ch: Some(ConsensusHash([0x04; 20])),
+            last_sortition_ch: Some(consensus_hash),
+            committed_block_hash: Some(BlockHeaderHash([0x05; 32])),
+        }
+    }
+
+    #[test]
+    fn get_latest_sortition_should_succeed() {
+        let mock = MockServerClient::new();
+        let sortition_info = build_sortition_info(ConsensusHash([0x06; 20]));
+        let response = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(&sortition_info).unwrap()
+        );
+        let h = spawn(move || mock.client.get_latest_sortition());
+        let request = write_response(mock.server, response.as_bytes());
+        assert!(String::from_utf8_lossy(&request).starts_with("GET /v3/sortitions "));
+        assert_eq!(h.join().unwrap().unwrap(), sortition_info);
+    }
+
+    #[test]
+    fn get_sortition_info_by_consensus_hash_should_succeed() {
+        let mock = MockServerClient::new();
+        let consensus_hash = ConsensusHash([0x07; 20]);
+        let sortition_info = build_sortition_info(consensus_hash);
+        let response = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(&sortition_info).unwrap()
+        );
+        let h = spawn(move || mock.client.get_sortition_info(Some(&consensus_hash)));
+        let request = write_response(mock.server, response.as_bytes());
+        assert!(String::from_utf8_lossy(&request)
+            .starts_with(&format!("GET /v3/sortitions/consensus/{consensus_hash} ")));
+        assert_eq!(h.join().unwrap().unwrap(), sortition_info);
+    }
+
+    #[test]
+    fn get_sortition_info_from_an_older_node_should_fail() {
+        let mock = MockServerClient::new();
+        let h = spawn(move || mock.client.get_latest_sortition());
+        write_response(mock.server, b"HTTP/1.1 404 Not Found\n\n");
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::UnsupportedStacksFeature(_))
+        ));
+    }
+
+    /// A block in the tenure with `consensus_hash`, as the node would store it
+    fn build_nakamoto_block(consensus_hash: ConsensusHash) -> NakamotoBlock {
+        let mut header = NakamotoBlockHeader::empty();
+        header.chain_length = 42;
+        header.burn_spent = 1000;
+        header.consensus_hash = consensus_hash;
+        header.parent_block_id = StacksBlockId([0x08; 32]);
+        NakamotoBlock {
+            header,
+            txs: vec![],
+        }
+    }
+
+    /// An HTTP response with the consensus-serialized `block` as its body
+    fn build_get_block_response(block: &NakamotoBlock) -> Vec<u8> {
+        let mut response = b"HTTP/1.1 200 OK\n\n".to_vec();
+        response.extend(block.serialize_to_vec());
+        response
+    }
+
+    fn build_tenure_info(block: &NakamotoBlock) -> RPCGetTenureInfo {
+        RPCGetTenureInfo {
+            consensus_hash: block.header.consensus_hash,
+            tenure_start_block_id: StacksBlockId([0x09; 32]),
+            parent_consensus_hash: ConsensusHash([0x0a; 20]),
+            parent_tenure_start_block_id: block.header.parent_block_id,
+            tip_block_id: block.block_id(),
+            tip_height: block.header.chain_length,
+            reward_cycle: 7,
+        }
+    }
+
+    #[test]
+    fn get_block_by_id_should_succeed() {
+        let mock = MockServerClient::new();
+        let block = build_nakamoto_block(ConsensusHash([0x0b; 20]));
+        let block_id = block.block_id();
+        let response = build_get_block_response(&block);
+        let h = spawn(move || mock.client.get_block_by_id(&block_id));
+        let request = write_response(mock.server, &response);
+        assert!(
+            String::from_utf8_lossy(&request).starts_with(&format!("GET /v3/blocks/{block_id} "))
+        );
+        assert_eq!(h.join().unwrap().unwrap(), block);
+    }
+
+    #[test]
+    fn get_block_by_id_for_an_unknown_block_should_fail() {
+        let mock = MockServerClient::new();
+        let block_id = StacksBlockId([0x0c; 32]);
+        let h = spawn(move || mock.client.get_block_by_id(&block_id));
+        write_response(mock.server, b"HTTP/1.1 404 Not Found\n\n");
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::UnknownBlock(id)) if id == block_id
+        ));
+    }
+
+    #[test]
+    fn get_block_by_id_for_another_block_should_fail() {
+        let mock = MockServerClient::new();
+        let block = build_nakamoto_block(ConsensusHash([0x0d; 20]));
+        let response = build_get_block_response(&block);
+        let h = spawn(move || mock.client.get_block_by_id(&StacksBlockId([0x0e; 32])));
+        write_response(mock.server, &response);
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn get_tenure_tip_should_succeed() {
+        let mock = MockServerClient::new();
+        let consensus_hash = ConsensusHash([0x0f; 20]);
+        let block = build_nakamoto_block(consensus_hash);
+        let tenure_info = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(&build_tenure_info(&block)).unwrap()
+        );
+        let h = spawn(move || mock.client.get_tenure_tip(&consensus_hash));
+        let request = write_response(mock.server, tenure_info.as_bytes());
+        assert!(String::from_utf8_lossy(&request).starts_with("GET /v3/tenures/info "));
+        let server = mock_server_from_config(&mock.config);
+        let request = write_response(server, &build_get_block_response(&block));
+        assert!(String::from_utf8_lossy(&request)
+            .starts_with(&format!("GET /v3/blocks/{} ", block.block_id())));
+        assert_eq!(h.join().unwrap().unwrap(), block.header);
+    }
+
+    #[test]
+    fn get_tenure_tip_of_another_tenure_should_fail() {
+        let mock = MockServerClient::new();
+        let block = build_nakamoto_block(ConsensusHash([0x10; 20]));
+        let tenure_info = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(&build_tenure_info(&block)).unwrap()
+        );
+        let consensus_hash = ConsensusHash([0x11; 20]);
+        let h = spawn(move || mock.client.get_tenure_tip(&consensus_hash));
+        write_response(mock.server, tenure_info.as_bytes());
+        assert!(matches!(
+            h.join().unwrap(),
+            Err(ClientError::UnknownTenure(ch)) if ch == consensus_hash
+        ));
+    }
+
+    /// A client whose primary stacks node is at `primary` and whose only fallback is at `fallback`
+    fn failover_client(
+        primary: SocketAddr,
+        fallback: SocketAddr,
+        cooldown: Duration,
+    ) -> StacksClient {
+        let mut config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        config.node_host = primary.to_string();
+        config.fallback_node_hosts = vec![fallback.to_string()];
+        config.node_failover_cooldown = cooldown;
+        StacksClient::from(&config)
+    }
+
+    fn call_read_only(client: &StacksClient) -> Result<ClarityValue, ClientError> {
+        client.read_only_contract_call(
+            &client.stacks_address,
+            &ContractName::from("contract-name"),
+            &ClarityName::from("function-name"),
+            &[],
+        )
+    }
+
+    #[test]
+    fn unreachable_node_fails_over_and_returns_to_the_primary() {
+        let (primary, primary_addr) = mock_server_random();
+        let (fallback, fallback_addr) = mock_server_random();
+        let cooldown = Duration::from_millis(500);
+        let client = failover_client(primary_addr, fallback_addr, cooldown);
+        let value = ClarityValue::UInt(10_u128);
+        let response = build_read_only_response(&value);
+
+        // kill the primary node
+        drop(primary);
+        let h = spawn({
+            let client = client.clone();
+            move || call_read_only(&client)
+        });
+        // the fallback is checked to be on the same network before the call is sent to it
+        let peer_info_response = build_get_peer_info_response(None, None).0;
+        write_response(fallback.try_clone().unwrap(), peer_info_response.as_bytes());
+        let request = write_response(fallback, response.as_bytes());
+        assert!(String::from_utf8_lossy(&request).starts_with("POST /v2/contracts/call-read/"));
+        assert_eq!(h.join().unwrap().unwrap(), value);
+        assert_eq!(client.node_host(), fallback_addr.to_string());
+
+        // the primary node comes back, and is used again once the cooldown has passed
+        let primary = TcpListener::bind(primary_addr).unwrap();
+        std::thread::sleep(cooldown);
+        assert_eq!(client.node_host(), primary_addr.to_string());
+        let h = spawn({
+            let client = client.clone();
+            move || call_read_only(&client)
+        });
+        write_response(primary, response.as_bytes());
+        assert_eq!(h.join().unwrap().unwrap(), value);
+    }
+
+    #[test]
+    fn no_failover_to_a_node_on_another_network() {
+        let (primary, primary_addr) = mock_server_random();
+        let (fallback, fallback_addr) = mock_server_random();
+        let client = failover_client(primary_addr, fallback_addr, Duration::from_secs(60));
+        let (peer_info_response, peer_info) = build_get_peer_info_response(None, None);
+        let h = spawn({
+            let client = client.clone();
+            move || client.get_peer_info()
+        });
+        // the primary node goes away with its listener once it has answered
+        write_response(primary, peer_info_response.as_bytes());
+        h.join().unwrap().unwrap();
+
+        let (_, mut other_peer_info) = build_get_peer_info_response(None, None);
+        other_peer_info.network_id = peer_info.network_id.wrapping_add(1);
+        let other_peer_info_response = format!(
+            "HTTP/1.1 200 OK\n\n{}",
+            serde_json::to_string(&other_peer_info).unwrap()
+        );
+        let h = spawn({
+            let client = client.clone();
+            move || call_read_only(&client)
+        });
+        write_response(fallback, other_peer_info_response.as_bytes());
+        assert!(h.join().unwrap().is_err());
+        assert_eq!(client.node_host(), primary_addr.to_string());
+    }
+
+    #[test]
+    fn saturated_reads_do_not_delay_submissions() {
+        let (server, server_addr) = mock_server_random();
+        let mut config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        config.node_host = server_addr.to_string();
+        config.node_max_concurrent_reads = 1;
+        config.node_write_timeout = Duration::from_secs(2);
+        let client = StacksClient::from(&config);
+        let value = ClarityValue::UInt(10_u128);
+        let response = build_read_only_response(&value);
+
+        // a slow read holds the only read permit, and a second read waits for it
+        let slow_read = spawn({
+            let client = client.clone();
+            move || call_read_only(&client)
+        });
+        let mut slow_stream = server.accept().unwrap().0;
+        let mut request_bytes = [0u8; 1024];
+        let _ = slow_stream.read(&mut request_bytes).unwrap();
+        let queued_read = spawn({
+            let client = client.clone();
+            move || call_read_only(&client)
+        });
+
+        let unsigned_tx = client
+            .build_unsigned_vote_for_aggregate_public_key(
+                0,
+                1,
+                Point::from(Scalar::random(&mut rand::thread_rng())),
+                10,
+                0,
+            )
+            .unwrap();
+        let tx = client.sign_transaction(unsigned_tx, 10).unwrap();
+        let submitted_at = Instant::now();
+        let submission = spawn({
+            let client = client.clone();
+            let tx = tx.clone();
+            move || client.submit_transaction(&tx)
+        });
+        // the submission is the next request to reach the node
+        let request = write_response(
+            server.try_clone().unwrap(),
+            format!("HTTP/1.1 200 OK\n\n{}", tx.txid()).as_bytes(),
+        );
+        assert!(String::from_utf8_lossy(&request).starts_with("POST /v2/transactions "));
+        assert_eq!(submission.join().unwrap().unwrap(), tx.txid());
+        assert!(submitted_at.elapsed() < config.node_write_timeout);
+
+        // once the slow read is answered, the queued read is sent
+        slow_stream.write_all(response.as_bytes()).unwrap();
+        drop(slow_stream);
+        assert_eq!(slow_read.join().unwrap().unwrap(), value);
+        let request = write_response(server, response.as_bytes());
+        assert!(String::from_utf8_lossy(&request).starts_with("POST /v2/contracts/call-read/"));
+        assert_eq!(queued_read.join().unwrap().unwrap(), value);
+    }
 }
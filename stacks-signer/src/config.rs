@@ -17,10 +17,11 @@
 use std::fmt::Display;
 use std::fs;
 use std::net::{SocketAddr, ToSocketAddrs};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use blockstack_lib::chainstate::stacks::TransactionVersion;
+use libsigner::envelope::{EnvelopeConfig, DEFAULT_COMPRESSION_THRESHOLD};
 use libsigner::SignerEntries;
 use serde::Deserialize;
 use stacks_common::address::{
@@ -31,11 +32,37 @@ use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPu
 use stacks_common::types::PrivateKey;
 use wsts::curve::scalar::Scalar;
 
+use crate::audit::{AuditLogConfig, SharedAuditLog, AUDIT_LOG_MAX_BYTES, AUDIT_LOG_MAX_FILES};
 use crate::client::SignerSlotID;
+use crate::health::SharedHealthState;
+use crate::key_file::{self, KeyFileError};
 
 const EVENT_TIMEOUT_MS: u64 = 5000;
+/// The prefix of the environment variables that override config file values
+pub const ENV_VAR_PREFIX: &str = "SIGNER_";
 // Default transaction fee to use in microstacks (if unspecificed in the config file)
 const TX_FEE_USTX: u64 = 10_000;
+/// Default clock skew from the stacks node (in millisecs) above which the signer warns
+const MAX_CLOCK_SKEW_MS: u64 = 5_000;
+/// Default time (in millisecs) to stay on a fallback stacks node before trying the primary again
+const NODE_FAILOVER_COOLDOWN_MS: u64 = 60_000;
+/// Default time (in millisecs) a burn block is expected to take
+pub const BURN_BLOCK_INTERVAL_MS: u64 = 600_000;
+/// Default staleness (in millisecs) of the stacks node's chain tip above which the signer
+/// rejects new block proposals
+const MAX_NODE_STALENESS_MS: u64 = 3_600_000;
+/// Default delay (in millisecs) per slot that block responses are staggered by
+const BLOCK_RESPONSE_DELAY_STEP_MS: u64 = 0;
+/// Default cap (in millisecs) on the block response delay
+const BLOCK_RESPONSE_MAX_DELAY_MS: u64 = 2_000;
+/// Default timeout (in millisecs) of a read request to the stacks node
+pub const NODE_READ_TIMEOUT_MS: u64 = 30_000;
+/// Default timeout (in millisecs) of a transaction or block proposal submission to the stacks node
+pub const NODE_WRITE_TIMEOUT_MS: u64 = 30_000;
+/// Default number of read requests that may be in flight to the stacks node at once
+pub const NODE_MAX_CONCURRENT_READS: u64 = 8;
+/// Default number of idle connections to keep open for submissions to the stacks node
+pub const NODE_WRITE_MAX_IDLE_CONNECTIONS: u64 = 4;
 
 #[derive(thiserror::Error, Debug)]
 /// An error occurred parsing the provided configuration
@@ -52,6 +79,32 @@ pub enum ConfigError {
     /// An unsupported address version
     #[error("Failed to convert private key to address: unsupported address version.")]
     UnsupportedAddressVersion,
+    /// The encrypted key file could not be loaded
+    #[error("Failed to load stacks_private_key_file: {0}")]
+    KeyFile(#[from] KeyFileError),
+    /// A required field is set neither in the config file nor in the environment
+    #[error("missing required field {0}")]
+    MissingField(String),
+    /// A reloaded config changes a field that only takes effect on restart
+    #[error("{0} cannot be changed without restarting the signer")]
+    RestartRequired(String),
+    /// Several fields are invalid
+    #[error("{} invalid fields: {}", .0.len(), join_errors(.0))]
+    Multiple(Vec<ConfigError>),
+}
+
+/// Render several config errors on one line
+fn join_errors(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// The environment variable that overrides the config field `field`
+fn env_var_name(field: &str) -> String {
+    format!("{ENV_VAR_PREFIX}{}", field.to_uppercase())
 }
 
 #[derive(serde::Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -76,6 +129,19 @@ impl std::fmt::Display for Network {
     }
 }
 
+impl std::str::FromStr for Network {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "mocknet" => Ok(Self::Mocknet),
+            _ => Err(ConfigError::BadField("network".to_string(), s.to_string())),
+        }
+    }
+}
+
 impl Network {
     /// Converts a Network enum variant to a corresponding chain id
     pub const fn to_chain_id(&self) -> u32 {
@@ -149,6 +215,72 @@ pub struct SignerConfig {
     pub max_tx_fee_ustx: Option<u64>,
     /// The path to the signer's database file
     pub db_path: PathBuf,
+    /// The health state shared with the runloop's stacks client
+    pub health: SharedHealthState,
+    /// How messages are enveloped in the chunks the signer writes. Bare if `None`.
+    pub payload_envelope: Option<EnvelopeConfig>,
+    /// The staleness of the stacks node's chain tip above which new block proposals are rejected
+    pub max_node_staleness: Duration,
+    /// The audit log shared with the runloop's stacks client, if enabled
+    pub audit_log: Option<SharedAuditLog>,
+    /// The delay per slot that block responses are staggered by
+    pub block_response_delay_step: Duration,
+    /// The cap on the block response delay
+    pub block_response_max_delay: Duration,
+    /// Whether to log the chunks the signer would write instead of writing them
+    pub dry_run: bool,
+}
+
+/// A Stacks private key the signer switches to from a given reward cycle onwards
+#[derive(Clone, Debug)]
+pub struct NextStacksKey {
+    /// The first reward cycle to sign for with this key
+    pub activation_reward_cycle: u64,
+    /// The Scalar representation of the key for signer communication
+    pub ecdsa_private_key: Scalar,
+    /// The Stacks private key
+    pub stacks_private_key: StacksPrivateKey,
+    /// The Stacks address of the key
+    pub stacks_address: StacksAddress,
+}
+
+impl NextStacksKey {
+    /// Whether this key replaces the signer's current key in `reward_cycle`
+    pub const fn is_active(&self, reward_cycle: u64) -> bool {
+        reward_cycle >= self.activation_reward_cycle
+    }
+}
+
+/// Parse a hex-encoded Stacks private key from the config field `field`, and derive its
+/// signer communication key and Stacks address
+fn parse_stacks_private_key(
+    field: &str,
+    hex: &str,
+    network: &Network,
+) -> Result<(StacksPrivateKey, Scalar, StacksAddress), ConfigError> {
+    let bad_key = || ConfigError::BadField(field.to_string(), hex.to_string());
+    let stacks_private_key = StacksPrivateKey::from_hex(hex).map_err(|_| bad_key())?;
+    derive_signer_keys(stacks_private_key, network, bad_key)
+}
+
+/// Derive the signer communication key and Stacks address of `stacks_private_key`.
+/// `bad_key` builds the error returned if it cannot be used for signer communication.
+fn derive_signer_keys(
+    stacks_private_key: StacksPrivateKey,
+    network: &Network,
+    bad_key: impl FnOnce() -> ConfigError,
+) -> Result<(StacksPrivateKey, Scalar, StacksAddress), ConfigError> {
+    let ecdsa_private_key =
+        Scalar::try_from(&stacks_private_key.to_bytes()[..32]).map_err(|_| bad_key())?;
+    let stacks_public_key = StacksPublicKey::from_private(&stacks_private_key);
+    let stacks_address = StacksAddress::from_public_keys(
+        network.to_address_version(),
+        &AddressHashMode::SerializeP2PKH,
+        1,
+        &vec![stacks_public_key],
+    )
+    .ok_or(ConfigError::UnsupportedAddressVersion)?;
+    Ok((stacks_private_key, ecdsa_private_key, stacks_address))
 }
 
 /// The parsed configuration for the signer
@@ -156,6 +288,18 @@ pub struct SignerConfig {
 pub struct GlobalConfig {
     /// endpoint to the stacks node
     pub node_host: String,
+    /// endpoints to fail over to, in order, while the stacks node at `node_host` is unreachable
+    pub fallback_node_hosts: Vec<String>,
+    /// How long to stay on a fallback stacks node before trying `node_host` again
+    pub node_failover_cooldown: Duration,
+    /// The timeout of a read request to the stacks node
+    pub node_read_timeout: Duration,
+    /// The timeout of a transaction or block proposal submission to the stacks node
+    pub node_write_timeout: Duration,
+    /// How many read requests may be in flight to the stacks node at once
+    pub node_max_concurrent_reads: usize,
+    /// How many idle connections to keep open for submissions to the stacks node
+    pub node_write_max_idle_connections: usize,
     /// endpoint to the event receiver
     pub endpoint: SocketAddr,
     /// The Scalar representation of the private key for signer communication
@@ -164,6 +308,8 @@ pub struct GlobalConfig {
     pub stacks_private_key: StacksPrivateKey,
     /// The signer's Stacks address
     pub stacks_address: StacksAddress,
+    /// The key the signer switches to at its activation reward cycle, if it is rotating keys
+    pub next_stacks_key: Option<NextStacksKey>,
     /// The network to use. One of "mainnet" or "testnet".
     pub network: Network,
     /// The time to wait for a response from the stacker-db instance
@@ -188,20 +334,68 @@ pub struct GlobalConfig {
     pub db_path: PathBuf,
     /// Metrics endpoint
     pub metrics_endpoint: Option<SocketAddr>,
+    /// Health endpoint
+    pub health_endpoint: Option<SocketAddr>,
+    /// The clock skew from the stacks node above which the signer warns
+    pub max_clock_skew: Duration,
+    /// How messages are enveloped in the chunks the signer writes. Bare if `None`.
+    pub payload_envelope: Option<EnvelopeConfig>,
+    /// How often burn blocks are expected to arrive
+    pub burn_block_interval: Duration,
+    /// The staleness of the stacks node's chain tip above which new block proposals are rejected
+    pub max_node_staleness: Duration,
+    /// Where to log the chunks and transactions the signer publishes. Not logged if `None`.
+    pub audit_log: Option<AuditLogConfig>,
+    /// The delay per slot that block responses are staggered by
+    pub block_response_delay_step: Duration,
+    /// The cap on the block response delay
+    pub block_response_max_delay: Duration,
+    /// Whether to log the StackerDB chunks and transactions the signer would publish instead
+    /// of publishing them
+    pub dry_run: bool,
 }
 
-/// Internal struct for loading up the config file
+/// Internal struct for loading up the config file. Every field is optional here so that
+/// it can be supplied by an environment variable instead; required fields are checked
+/// when converting to a `GlobalConfig`.
 #[derive(Deserialize, Debug)]
 struct RawConfigFile {
     /// endpoint to stacks node
-    pub node_host: String,
+    pub node_host: Option<String>,
+    /// endpoints to fail over to, in order, while the stacks node at `node_host` is unreachable
+    pub fallback_node_hosts: Option<Vec<String>>,
+    /// The time (in millisecs) to stay on a fallback stacks node before trying `node_host` again.
+    /// If not set, will default to NODE_FAILOVER_COOLDOWN_MS
+    pub node_failover_cooldown_ms: Option<u64>,
+    /// The timeout (in millisecs) of a read request to the stacks node.
+    /// If not set, will default to NODE_READ_TIMEOUT_MS
+    pub node_read_timeout_ms: Option<u64>,
+    /// The timeout (in millisecs) of a transaction or block proposal submission to the
+    /// stacks node. If not set, will default to NODE_WRITE_TIMEOUT_MS
+    pub node_write_timeout_ms: Option<u64>,
+    /// How many read requests may be in flight to the stacks node at once. Submissions are
+    /// sent over their own connections and are not limited by this.
+    /// If not set, will default to NODE_MAX_CONCURRENT_READS
+    pub node_max_concurrent_reads: Option<u64>,
+    /// How many idle connections to keep open for submissions to the stacks node.
+    /// If not set, will default to NODE_WRITE_MAX_IDLE_CONNECTIONS
+    pub node_write_max_idle_connections: Option<u64>,
     /// endpoint to event receiver
-    pub endpoint: String,
+    pub endpoint: Option<String>,
     /// The hex representation of the signer's Stacks private key used for communicating
     /// with the Stacks Node, including writing to the Stacker DB instance.
-    pub stacks_private_key: String,
+    /// Mutually exclusive with `stacks_private_key_file`.
+    pub stacks_private_key: Option<String>,
+    /// The path to a key file holding the encrypted `stacks_private_key`, as written by
+    /// `stacks-signer encrypt-key`. Mutually exclusive with `stacks_private_key`.
+    pub stacks_private_key_file: Option<String>,
+    /// The hex representation of the Stacks private key to rotate to. Must be set
+    /// together with `next_key_activation_reward_cycle`.
+    pub next_stacks_private_key: Option<String>,
+    /// The first reward cycle to sign for with `next_stacks_private_key`
+    pub next_key_activation_reward_cycle: Option<u64>,
     /// The network to use. One of "mainnet" or "testnet".
-    pub network: Network,
+    pub network: Option<Network>,
     /// The time to wait (in millisecs) for a response from the stacker-db instance
     pub event_timeout_ms: Option<u64>,
     /// timeout in (millisecs) to gather DkgPublicShares messages
@@ -220,11 +414,54 @@ struct RawConfigFile {
     /// If not set, will use tx_fee_ustx.
     pub max_tx_fee_ustx: Option<u64>,
     /// The authorization password for the block proposal endpoint
-    pub auth_password: String,
+    pub auth_password: Option<String>,
     /// The path to the signer's database file or :memory: for an in-memory database
-    pub db_path: String,
+    pub db_path: Option<String>,
     /// Metrics endpoint
     pub metrics_endpoint: Option<String>,
+    /// Health endpoint. The health listener is disabled if not set.
+    pub health_endpoint: Option<String>,
+    /// The clock skew (in millisecs) from the stacks node above which the signer warns.
+    /// If not set, will default to MAX_CLOCK_SKEW_MS
+    pub max_clock_skew_ms: Option<u64>,
+    /// Whether to write StackerDB chunks in a versioned payload envelope. Only enable once
+    /// every miner and signer reading them understands envelopes. Defaults to false.
+    pub payload_envelope: Option<bool>,
+    /// Enveloped payloads longer than this many bytes are compressed.
+    /// If not set, will default to DEFAULT_COMPRESSION_THRESHOLD
+    pub payload_compression_threshold: Option<u64>,
+    /// The time (in millisecs) a burn block is expected to take.
+    /// If not set, will default to BURN_BLOCK_INTERVAL_MS
+    pub burn_block_interval_ms: Option<u64>,
+    /// How long (in millisecs) the stacks node may report the same chain tip, beyond
+    /// `burn_block_interval_ms`, before the signer rejects new block proposals.
+    /// If not set, will default to MAX_NODE_STALENESS_MS
+    pub max_node_staleness_ms: Option<u64>,
+    /// The path of the audit log of the chunks and transactions the signer publishes.
+    /// The audit log is disabled if not set.
+    pub audit_log_path: Option<String>,
+    /// The path of the audit log's HMAC key, created if it does not exist.
+    /// If not set, will default to `audit_log_path` with a `.key` suffix
+    pub audit_log_key_path: Option<String>,
+    /// The path of the audit log's anchor, which records the last record written so that
+    /// records removed from the end of the log are detected. Keep it off the log's volume.
+    /// If not set, will default to `audit_log_path` with a `.anchor` suffix
+    pub audit_log_anchor_path: Option<String>,
+    /// The size (in bytes) above which the audit log is rotated.
+    /// If not set, will default to AUDIT_LOG_MAX_BYTES
+    pub audit_log_max_bytes: Option<u64>,
+    /// The number of rotated audit log files to keep.
+    /// If not set, will default to AUDIT_LOG_MAX_FILES
+    pub audit_log_max_files: Option<u64>,
+    /// The time (in millisecs) per slot that block responses are staggered by, to spread the
+    /// signers' StackerDB writes. If not set, will default to BLOCK_RESPONSE_DELAY_STEP_MS
+    pub block_response_delay_step_ms: Option<u64>,
+    /// The longest time (in millisecs) a block response is delayed for.
+    /// If not set, will default to BLOCK_RESPONSE_MAX_DELAY_MS
+    pub block_response_max_delay_ms: Option<u64>,
+    /// Whether to run as a shadow signer, which validates and responds to everything as usual
+    /// but only logs the StackerDB chunks and transactions it would publish. Defaults to false.
+    pub dry_run: Option<bool>,
 }
 
 impl RawConfigFile {
@@ -234,6 +471,123 @@ impl RawConfigFile {
             toml::from_str(data).map_err(|e| ConfigError::ParseError(format!("{e:?}")))?;
         Ok(config)
     }
+
+    /// Override the config file's values with those of the `SIGNER_<FIELD>` environment
+    /// variables returned by `env`, e.g. `SIGNER_NODE_HOST` for `node_host`
+    fn apply_env_overrides(
+        &mut self,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        let mut errors = ConfigErrors::default();
+        let apply = |field: &str, value: &mut Option<String>| {
+            if let Some(env_value) = env(&env_var_name(field)) {
+                *value = Some(env_value);
+            }
+        };
+        apply("node_host", &mut self.node_host);
+        apply("endpoint", &mut self.endpoint);
+        apply("stacks_private_key", &mut self.stacks_private_key);
+        apply("stacks_private_key_file", &mut self.stacks_private_key_file);
+        apply("next_stacks_private_key", &mut self.next_stacks_private_key);
+        apply("auth_password", &mut self.auth_password);
+        apply("db_path", &mut self.db_path);
+        apply("metrics_endpoint", &mut self.metrics_endpoint);
+        apply("health_endpoint", &mut self.health_endpoint);
+        apply("audit_log_path", &mut self.audit_log_path);
+        apply("audit_log_key_path", &mut self.audit_log_key_path);
+        apply("audit_log_anchor_path", &mut self.audit_log_anchor_path);
+
+        let mut apply_parsed = |field: &str, value: &mut Option<u64>| {
+            let var = env_var_name(field);
+            if let Some(env_value) = env(&var) {
+                match env_value.parse() {
+                    Ok(parsed) => *value = Some(parsed),
+                    Err(_) => errors.0.push(ConfigError::BadField(var, env_value)),
+                }
+            }
+        };
+        apply_parsed(
+            "next_key_activation_reward_cycle",
+            &mut self.next_key_activation_reward_cycle,
+        );
+        apply_parsed("event_timeout_ms", &mut self.event_timeout_ms);
+        apply_parsed("dkg_public_timeout_ms", &mut self.dkg_public_timeout_ms);
+        apply_parsed("dkg_private_timeout_ms", &mut self.dkg_private_timeout_ms);
+        apply_parsed("dkg_end_timeout_ms", &mut self.dkg_end_timeout_ms);
+        apply_parsed("nonce_timeout_ms", &mut self.nonce_timeout_ms);
+        apply_parsed("sign_timeout_ms", &mut self.sign_timeout_ms);
+        apply_parsed("tx_fee_ustx", &mut self.tx_fee_ustx);
+        apply_parsed("max_tx_fee_ustx", &mut self.max_tx_fee_ustx);
+        apply_parsed("max_clock_skew_ms", &mut self.max_clock_skew_ms);
+        apply_parsed(
+            "node_failover_cooldown_ms",
+            &mut self.node_failover_cooldown_ms,
+        );
+        apply_parsed("node_read_timeout_ms", &mut self.node_read_timeout_ms);
+        apply_parsed("node_write_timeout_ms", &mut self.node_write_timeout_ms);
+        apply_parsed(
+            "node_max_concurrent_reads",
+            &mut self.node_max_concurrent_reads,
+        );
+        apply_parsed(
+            "node_write_max_idle_connections",
+            &mut self.node_write_max_idle_connections,
+        );
+        apply_parsed(
+            "payload_compression_threshold",
+            &mut self.payload_compression_threshold,
+        );
+        apply_parsed("burn_block_interval_ms", &mut self.burn_block_interval_ms);
+        apply_parsed("max_node_staleness_ms", &mut self.max_node_staleness_ms);
+        apply_parsed("audit_log_max_bytes", &mut self.audit_log_max_bytes);
+        apply_parsed("audit_log_max_files", &mut self.audit_log_max_files);
+        apply_parsed(
+            "block_response_delay_step_ms",
+            &mut self.block_response_delay_step_ms,
+        );
+        apply_parsed(
+            "block_response_max_delay_ms",
+            &mut self.block_response_max_delay_ms,
+        );
+
+        // a comma-separated list, e.g. `SIGNER_FALLBACK_NODE_HOSTS=10.0.0.2:20443,10.0.0.3:20443`
+        if let Some(env_value) = env(&env_var_name("fallback_node_hosts")) {
+            self.fallback_node_hosts = Some(
+                env_value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|host| !host.is_empty())
+                    .map(String::from)
+                    .collect(),
+            );
+        }
+
+        let var = env_var_name("payload_envelope");
+        if let Some(env_value) = env(&var) {
+            match env_value.parse() {
+                Ok(payload_envelope) => self.payload_envelope = Some(payload_envelope),
+                Err(_) => errors.0.push(ConfigError::BadField(var, env_value)),
+            }
+        }
+
+        let var = env_var_name("dry_run");
+        if let Some(env_value) = env(&var) {
+            match env_value.parse() {
+                Ok(dry_run) => self.dry_run = Some(dry_run),
+                Err(_) => errors.0.push(ConfigError::BadField(var, env_value)),
+            }
+        }
+
+        let var = env_var_name("network");
+        if let Some(env_value) = env(&var) {
+            match env_value.parse() {
+                Ok(network) => self.network = Some(network),
+                Err(_) => errors.0.push(ConfigError::BadField(var, env_value)),
+            }
+        }
+        errors.into_result()
+    }
+
     /// load the config from a file and parse it
     #[allow(dead_code)]
     pub fn load_from_file(path: &str) -> Result<Self, ConfigError> {
@@ -251,104 +605,400 @@ impl TryFrom<&PathBuf> for RawConfigFile {
     }
 }
 
+/// Collects the errors of independently validated config fields, so that all of them
+/// are reported at once rather than only the first
+#[derive(Default)]
+struct ConfigErrors(Vec<ConfigError>);
+
+impl ConfigErrors {
+    /// Record the error(s) of `result`, if any, and return its value
+    fn check<T>(&mut self, result: Result<T, ConfigError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(ConfigError::Multiple(errors)) => {
+                self.0.extend(errors);
+                None
+            }
+            Err(e) => {
+                self.0.push(e);
+                None
+            }
+        }
+    }
+
+    /// Fail with the recorded error, or with all of them if there are several
+    fn into_result(mut self) -> Result<(), ConfigError> {
+        match self.0.len() {
+            0 => Ok(()),
+            1 => Err(self.0.remove(0)),
+            _ => Err(ConfigError::Multiple(self.0)),
+        }
+    }
+}
+
+/// Fail if the required config field `field` is not set
+fn required<T>(field: &str, value: Option<T>) -> Result<T, ConfigError> {
+    value.ok_or_else(|| ConfigError::MissingField(field.to_string()))
+}
+
+/// Check that the config field `field` is a well-formed stacks node host. The host is only
+/// resolved when connecting to the node, so it may omit the port or be unreachable for now.
+fn check_node_host(field: &str, value: &str) -> Result<(), ConfigError> {
+    url::Url::parse(&format!("http://{value}"))
+        .map(|_| ())
+        .map_err(|_| ConfigError::BadField(field.to_string(), value.to_string()))
+}
+
+/// Resolve the config field `field` to a socket address
+fn parse_socket_addr(field: &str, value: &str) -> Result<SocketAddr, ConfigError> {
+    value
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(|| ConfigError::BadField(field.to_string(), value.to_string()))
+}
+
+/// Fail if any two of the signer's listeners are configured on the same port
+fn check_distinct_ports(listeners: &[(&str, Option<SocketAddr>)]) -> Result<(), ConfigError> {
+    let mut errors = ConfigErrors::default();
+    let listeners: Vec<_> = listeners
+        .iter()
+        .filter_map(|(field, addr)| addr.map(|addr| (*field, addr)))
+        .collect();
+    for (i, (field, addr)) in listeners.iter().enumerate() {
+        if let Some((other_field, _)) = listeners[..i]
+            .iter()
+            .find(|(_, other_addr)| other_addr.port() == addr.port())
+        {
+            errors.0.push(ConfigError::BadField(
+                field.to_string(),
+                format!("port {} is already used by {other_field}", addr.port()),
+            ));
+        }
+    }
+    errors.into_result()
+}
+
+/// Parse the signer's current Stacks private key, from either the plaintext field or the key file
+fn parse_signer_key(
+    raw_data: &RawConfigFile,
+    network: &Network,
+) -> Result<(StacksPrivateKey, Scalar, StacksAddress), ConfigError> {
+    match (
+        &raw_data.stacks_private_key,
+        &raw_data.stacks_private_key_file,
+    ) {
+        (Some(hex), None) => parse_stacks_private_key("stacks_private_key", hex, network),
+        (None, Some(path)) => {
+            let stacks_private_key = key_file::load_key_file(Path::new(path))?;
+            derive_signer_keys(stacks_private_key, network, || {
+                ConfigError::BadField("stacks_private_key_file".to_string(), path.clone())
+            })
+        }
+        _ => Err(ConfigError::InvalidConfig(
+            "exactly one of stacks_private_key and stacks_private_key_file must be set".to_string(),
+        )),
+    }
+}
+
+/// Parse the key the signer rotates to, if any. `current_address` is the address of the
+/// signer's current key, if it could be parsed.
+fn parse_next_stacks_key(
+    raw_data: &RawConfigFile,
+    network: &Network,
+    current_address: Option<&StacksAddress>,
+) -> Result<Option<NextStacksKey>, ConfigError> {
+    match (
+        &raw_data.next_stacks_private_key,
+        raw_data.next_key_activation_reward_cycle,
+    ) {
+        (Some(next_key), Some(activation_reward_cycle)) => {
+            let (stacks_private_key, ecdsa_private_key, stacks_address) =
+                parse_stacks_private_key("next_stacks_private_key", next_key, network)?;
+            if current_address == Some(&stacks_address) {
+                return Err(ConfigError::BadField(
+                    "next_stacks_private_key".to_string(),
+                    "same as stacks_private_key".to_string(),
+                ));
+            }
+            Ok(Some(NextStacksKey {
+                activation_reward_cycle,
+                ecdsa_private_key,
+                stacks_private_key,
+                stacks_address,
+            }))
+        }
+        (None, None) => Ok(None),
+        _ => Err(ConfigError::InvalidConfig(
+            "next_stacks_private_key and next_key_activation_reward_cycle must be set together"
+                .to_string(),
+        )),
+    }
+}
+
 impl TryFrom<RawConfigFile> for GlobalConfig {
     type Error = ConfigError;
 
-    /// Attempt to decode the raw config file's primitive types into our types.
+    /// Attempt to decode the raw config file's primitive types into our types, reporting
+    /// every invalid field at once.
     /// NOTE: network access is required for this to work
     fn try_from(raw_data: RawConfigFile) -> Result<Self, Self::Error> {
-        url::Url::parse(&format!("http://{}", raw_data.node_host)).map_err(|_| {
-            ConfigError::BadField("node_host".to_string(), raw_data.node_host.clone())
-        })?;
-
-        let endpoint = raw_data
-            .endpoint
-            .to_socket_addrs()
-            .map_err(|_| ConfigError::BadField("endpoint".to_string(), raw_data.endpoint.clone()))?
-            .next()
-            .ok_or_else(|| {
-                ConfigError::BadField("endpoint".to_string(), raw_data.endpoint.clone())
-            })?;
-
-        let stacks_private_key =
-            StacksPrivateKey::from_hex(&raw_data.stacks_private_key).map_err(|_| {
-                ConfigError::BadField(
-                    "stacks_private_key".to_string(),
-                    raw_data.stacks_private_key.clone(),
-                )
-            })?;
+        let mut errors = ConfigErrors::default();
+        let network = errors.check(required("network", raw_data.network.clone()));
+        let node_host = errors.check(required("node_host", raw_data.node_host.clone()).and_then(
+            |node_host| {
+                check_node_host("node_host", &node_host)?;
+                Ok(node_host)
+            },
+        ));
+        let fallback_node_hosts: Vec<_> = raw_data
+            .fallback_node_hosts
+            .iter()
+            .flatten()
+            .filter_map(|host| {
+                errors.check(check_node_host("fallback_node_hosts", host).map(|_| host.clone()))
+            })
+            .collect();
+        let endpoint = errors.check(
+            required("endpoint", raw_data.endpoint.as_ref())
+                .and_then(|endpoint| parse_socket_addr("endpoint", endpoint)),
+        );
+        let auth_password = errors.check(required("auth_password", raw_data.auth_password.clone()));
+        let db_path = errors.check(required("db_path", raw_data.db_path.clone()));
+        let metrics_endpoint = errors.check(
+            raw_data
+                .metrics_endpoint
+                .as_ref()
+                .map(|endpoint| parse_socket_addr("metrics_endpoint", endpoint))
+                .transpose(),
+        );
+        let health_endpoint = errors.check(
+            raw_data
+                .health_endpoint
+                .as_ref()
+                .map(|endpoint| parse_socket_addr("health_endpoint", endpoint))
+                .transpose(),
+        );
+        if let (Some(endpoint), Some(metrics_endpoint), Some(health_endpoint)) =
+            (endpoint, metrics_endpoint, health_endpoint)
+        {
+            errors.check(check_distinct_ports(&[
+                ("endpoint", Some(endpoint)),
+                ("metrics_endpoint", metrics_endpoint),
+                ("health_endpoint", health_endpoint),
+            ]));
+        }
+        // reads would wait forever for a permit
+        let node_max_concurrent_reads = errors.check(
+            match raw_data
+                .node_max_concurrent_reads
+                .unwrap_or(NODE_MAX_CONCURRENT_READS)
+            {
+                0 => Err(ConfigError::BadField(
+                    "node_max_concurrent_reads".to_string(),
+                    "0".to_string(),
+                )),
+                max_reads => Ok(usize::try_from(max_reads).unwrap_or(usize::MAX)),
+            },
+        );
+        let signer_key = network
+            .as_ref()
+            .and_then(|network| errors.check(parse_signer_key(&raw_data, network)));
+        let next_stacks_key = network.as_ref().and_then(|network| {
+            errors.check(parse_next_stacks_key(
+                &raw_data,
+                network,
+                signer_key.as_ref().map(|(_, _, address)| address),
+            ))
+        });
+        errors.into_result()?;
 
-        let ecdsa_private_key =
-            Scalar::try_from(&stacks_private_key.to_bytes()[..32]).map_err(|_| {
-                ConfigError::BadField(
-                    "stacks_private_key".to_string(),
-                    raw_data.stacks_private_key.clone(),
-                )
-            })?;
-        let stacks_public_key = StacksPublicKey::from_private(&stacks_private_key);
-        let stacks_address = StacksAddress::from_public_keys(
-            raw_data.network.to_address_version(),
-            &AddressHashMode::SerializeP2PKH,
-            1,
-            &vec![stacks_public_key],
+        let (
+            Some(network),
+            Some(node_host),
+            Some(endpoint),
+            Some(auth_password),
+            Some(db_path),
+            Some(metrics_endpoint),
+            Some(health_endpoint),
+            Some(node_max_concurrent_reads),
+            Some((stacks_private_key, ecdsa_private_key, stacks_address)),
+            Some(next_stacks_key),
+        ) = (
+            network,
+            node_host,
+            endpoint,
+            auth_password,
+            db_path,
+            metrics_endpoint,
+            health_endpoint,
+            node_max_concurrent_reads,
+            signer_key,
+            next_stacks_key,
         )
-        .ok_or(ConfigError::UnsupportedAddressVersion)?;
-        let event_timeout =
-            Duration::from_millis(raw_data.event_timeout_ms.unwrap_or(EVENT_TIMEOUT_MS));
-        let dkg_end_timeout = raw_data.dkg_end_timeout_ms.map(Duration::from_millis);
-        let dkg_public_timeout = raw_data.dkg_public_timeout_ms.map(Duration::from_millis);
-        let dkg_private_timeout = raw_data.dkg_private_timeout_ms.map(Duration::from_millis);
-        let nonce_timeout = raw_data.nonce_timeout_ms.map(Duration::from_millis);
-        let sign_timeout = raw_data.sign_timeout_ms.map(Duration::from_millis);
-        let db_path = raw_data.db_path.into();
-
-        let metrics_endpoint = match raw_data.metrics_endpoint {
-            Some(endpoint) => Some(
-                endpoint
-                    .to_socket_addrs()
-                    .map_err(|_| ConfigError::BadField("endpoint".to_string(), endpoint.clone()))?
-                    .next()
-                    .ok_or_else(|| {
-                        ConfigError::BadField("endpoint".to_string(), endpoint.clone())
-                    })?,
-            ),
-            None => None,
+        else {
+            unreachable!("BUG: every unset field has a recorded error");
         };
 
+        let block_response_delay_step = Duration::from_millis(
+            raw_data
+                .block_response_delay_step_ms
+                .unwrap_or(BLOCK_RESPONSE_DELAY_STEP_MS),
+        );
+        let block_response_max_delay = Duration::from_millis(
+            raw_data
+                .block_response_max_delay_ms
+                .unwrap_or(BLOCK_RESPONSE_MAX_DELAY_MS),
+        );
         Ok(Self {
-            node_host: raw_data.node_host,
+            node_host,
+            fallback_node_hosts,
+            node_failover_cooldown: Duration::from_millis(
+                raw_data
+                    .node_failover_cooldown_ms
+                    .unwrap_or(NODE_FAILOVER_COOLDOWN_MS),
+            ),
+            node_read_timeout: Duration::from_millis(
+                raw_data
+                    .node_read_timeout_ms
+                    .unwrap_or(NODE_READ_TIMEOUT_MS),
+            ),
+            node_write_timeout: Duration::from_millis(
+                raw_data
+                    .node_write_timeout_ms
+                    .unwrap_or(NODE_WRITE_TIMEOUT_MS),
+            ),
+            node_max_concurrent_reads,
+            node_write_max_idle_connections: usize::try_from(
+                raw_data
+                    .node_write_max_idle_connections
+                    .unwrap_or(NODE_WRITE_MAX_IDLE_CONNECTIONS),
+            )
+            .unwrap_or(usize::MAX),
             endpoint,
             stacks_private_key,
             ecdsa_private_key,
             stacks_address,
-            network: raw_data.network,
-            event_timeout,
-            dkg_end_timeout,
-            dkg_public_timeout,
-            dkg_private_timeout,
-            nonce_timeout,
-            sign_timeout,
+            next_stacks_key,
+            network,
+            event_timeout: Duration::from_millis(
+                raw_data.event_timeout_ms.unwrap_or(EVENT_TIMEOUT_MS),
+            ),
+            dkg_end_timeout: raw_data.dkg_end_timeout_ms.map(Duration::from_millis),
+            dkg_public_timeout: raw_data.dkg_public_timeout_ms.map(Duration::from_millis),
+            dkg_private_timeout: raw_data.dkg_private_timeout_ms.map(Duration::from_millis),
+            nonce_timeout: raw_data.nonce_timeout_ms.map(Duration::from_millis),
+            sign_timeout: raw_data.sign_timeout_ms.map(Duration::from_millis),
             tx_fee_ustx: raw_data.tx_fee_ustx.unwrap_or(TX_FEE_USTX),
             max_tx_fee_ustx: raw_data.max_tx_fee_ustx,
-            auth_password: raw_data.auth_password,
-            db_path,
+            auth_password,
+            db_path: db_path.into(),
             metrics_endpoint,
+            health_endpoint,
+            max_clock_skew: Duration::from_millis(
+                raw_data.max_clock_skew_ms.unwrap_or(MAX_CLOCK_SKEW_MS),
+            ),
+            payload_envelope: raw_data
+                .payload_envelope
+                .unwrap_or(false)
+                .then(|| EnvelopeConfig {
+                    compression_threshold: raw_data
+                        .payload_compression_threshold
+                        .and_then(|threshold| usize::try_from(threshold).ok())
+                        .unwrap_or(DEFAULT_COMPRESSION_THRESHOLD),
+                }),
+            burn_block_interval: Duration::from_millis(
+                raw_data
+                    .burn_block_interval_ms
+                    .unwrap_or(BURN_BLOCK_INTERVAL_MS),
+            ),
+            max_node_staleness: Duration::from_millis(
+                raw_data
+                    .max_node_staleness_ms
+                    .unwrap_or(MAX_NODE_STALENESS_MS),
+            ),
+            audit_log: raw_data.audit_log_path.map(|path| {
+                let path = PathBuf::from(path);
+                AuditLogConfig {
+                    key_path: raw_data
+                        .audit_log_key_path
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| AuditLogConfig::default_key_path(&path)),
+                    anchor_path: raw_data
+                        .audit_log_anchor_path
+                        .map(PathBuf::from)
+                        .unwrap_or_else(|| AuditLogConfig::default_anchor_path(&path)),
+                    path,
+                    max_bytes: raw_data.audit_log_max_bytes.unwrap_or(AUDIT_LOG_MAX_BYTES),
+                    max_files: raw_data
+                        .audit_log_max_files
+                        .and_then(|max_files| u32::try_from(max_files).ok())
+                        .unwrap_or(AUDIT_LOG_MAX_FILES),
+                }
+            }),
+            block_response_delay_step,
+            block_response_max_delay,
+            dry_run: raw_data.dry_run.unwrap_or(false),
         })
     }
 }
 
 impl TryFrom<&PathBuf> for GlobalConfig {
     type Error = ConfigError;
+    /// Load the config file at `path`, with values overridden by `SIGNER_<FIELD>` environment
+    /// variables. Precedence: environment > config file > defaults.
     fn try_from(path: &PathBuf) -> Result<Self, ConfigError> {
         let config_file = RawConfigFile::try_from(path)?;
-        Self::try_from(config_file)
+        Self::from_raw_with_env(config_file, |var| std::env::var(var).ok(), true)
     }
 }
 
 impl GlobalConfig {
-    /// load the config from a string and parse it
+    /// load the config from a string and parse it, with values overridden by
+    /// `SIGNER_<FIELD>` environment variables
     pub fn load_from_str(data: &str) -> Result<Self, ConfigError> {
-        RawConfigFile::load_from_str(data)?.try_into()
+        Self::from_raw_with_env(
+            RawConfigFile::load_from_str(data)?,
+            |var| std::env::var(var).ok(),
+            true,
+        )
+    }
+
+    /// Load the config file at `path` for the running signer to reload. Unlike `try_from`,
+    /// this fails rather than prompting for the key file passphrase if it is not in the
+    /// environment, since nobody is at the terminal to answer a prompt.
+    pub fn load_for_reload(path: &PathBuf) -> Result<Self, ConfigError> {
+        let config_file = RawConfigFile::try_from(path)?;
+        Self::from_raw_with_env(config_file, |var| std::env::var(var).ok(), false)
+    }
+
+    /// Apply the environment overrides returned by `env` to `raw_data` and parse it.
+    /// Unless `allow_prompt` is set, a config with a key file whose passphrase `env` does
+    /// not hold is rejected before it is parsed, so that nothing prompts for the passphrase.
+    fn from_raw_with_env(
+        mut raw_data: RawConfigFile,
+        env: impl Fn(&str) -> Option<String>,
+        allow_prompt: bool,
+    ) -> Result<Self, ConfigError> {
+        let mut errors = ConfigErrors::default();
+        let passphrase_is_set = env(key_file::PASSPHRASE_ENV_VAR).is_some();
+        errors.check(raw_data.apply_env_overrides(env));
+        if !allow_prompt && raw_data.stacks_private_key_file.is_some() && !passphrase_is_set {
+            errors.0.push(
+                KeyFileError::Passphrase(format!(
+                    "{} is not set, and the signer does not prompt for it on reload",
+                    key_file::PASSPHRASE_ENV_VAR
+                ))
+                .into(),
+            );
+            // parsing the config would prompt for the passphrase
+            return Err(errors
+                .into_result()
+                .expect_err("BUG: a recorded error was not reported"));
+        }
+        let config = errors.check(Self::try_from(raw_data));
+        errors.into_result()?;
+        Ok(config.expect("BUG: a config that failed to parse has a recorded error"))
     }
 
     /// load the config from a file and parse it
@@ -356,6 +1006,75 @@ impl GlobalConfig {
         Self::try_from(&PathBuf::from(path))
     }
 
+    /// Check that `new_config` only differs from this config in fields that can be
+    /// reloaded while the signer is running. The signer's identity (its keys and
+    /// network), the listener addresses, the database path, the audit log and dry-run mode
+    /// require a restart.
+    pub fn check_reloadable(&self, new_config: &GlobalConfig) -> Result<(), ConfigError> {
+        let next_key_id = |config: &GlobalConfig| {
+            config.next_stacks_key.as_ref().map(|next_key| {
+                (
+                    next_key.stacks_private_key,
+                    next_key.activation_reward_cycle,
+                )
+            })
+        };
+        let mut errors = ConfigErrors::default();
+        let mut require_unchanged = |field: &str, unchanged: bool| {
+            if !unchanged {
+                errors
+                    .0
+                    .push(ConfigError::RestartRequired(field.to_string()));
+            }
+        };
+        require_unchanged(
+            "stacks_private_key",
+            self.stacks_private_key == new_config.stacks_private_key,
+        );
+        require_unchanged(
+            "next_stacks_private_key",
+            next_key_id(self) == next_key_id(new_config),
+        );
+        require_unchanged("network", self.network == new_config.network);
+        require_unchanged("endpoint", self.endpoint == new_config.endpoint);
+        require_unchanged(
+            "health_endpoint",
+            self.health_endpoint == new_config.health_endpoint,
+        );
+        require_unchanged("db_path", self.db_path == new_config.db_path);
+        require_unchanged("audit_log_path", self.audit_log == new_config.audit_log);
+        require_unchanged("dry_run", self.dry_run == new_config.dry_run);
+        errors.into_result()
+    }
+
+    /// The rotated-to key, if it is the one to sign with in `reward_cycle`
+    fn active_next_key(&self, reward_cycle: u64) -> Option<&NextStacksKey> {
+        self.next_stacks_key
+            .as_ref()
+            .filter(|next_key| next_key.is_active(reward_cycle))
+    }
+
+    /// The Stacks private key to sign with in `reward_cycle`
+    pub fn stacks_private_key_for_cycle(&self, reward_cycle: u64) -> StacksPrivateKey {
+        self.active_next_key(reward_cycle)
+            .map(|next_key| next_key.stacks_private_key)
+            .unwrap_or(self.stacks_private_key)
+    }
+
+    /// The signer communication key to use in `reward_cycle`
+    pub fn ecdsa_private_key_for_cycle(&self, reward_cycle: u64) -> Scalar {
+        self.active_next_key(reward_cycle)
+            .map(|next_key| next_key.ecdsa_private_key)
+            .unwrap_or(self.ecdsa_private_key)
+    }
+
+    /// The Stacks address the signer is registered under in `reward_cycle`
+    pub fn stacks_address_for_cycle(&self, reward_cycle: u64) -> StacksAddress {
+        self.active_next_key(reward_cycle)
+            .map(|next_key| next_key.stacks_address)
+            .unwrap_or(self.stacks_address)
+    }
+
     /// Return a string with non-sensitive configuration
     /// information for logging purposes
     pub fn config_to_log_string(&self) -> String {
@@ -367,25 +1086,49 @@ impl GlobalConfig {
             Some(endpoint) => endpoint.to_string(),
             None => "None".to_string(),
         };
+        let health_endpoint = match &self.health_endpoint {
+            Some(endpoint) => endpoint.to_string(),
+            None => "None".to_string(),
+        };
+        let next_key = match &self.next_stacks_key {
+            Some(next_key) => format!(
+                "{} from reward cycle {}",
+                StacksPublicKey::from_private(&next_key.stacks_private_key).to_hex(),
+                next_key.activation_reward_cycle
+            ),
+            None => "None".to_string(),
+        };
+        let fallback_node_hosts = match self.fallback_node_hosts.as_slice() {
+            [] => "None".to_string(),
+            hosts => hosts.join(", "),
+        };
         format!(
             r#"
 Stacks node host: {node_host}
+Fallback stacks node hosts: {fallback_node_hosts}
 Signer endpoint: {endpoint}
 Stacks address: {stacks_address}
 Public key: {public_key}
+Next public key: {next_key}
 Network: {network}
 Database path: {db_path}
 DKG transaction fee: {tx_fee} uSTX
 Metrics endpoint: {metrics_endpoint}
+Health endpoint: {health_endpoint}
+Dry run: {dry_run}
 "#,
             node_host = self.node_host,
+            fallback_node_hosts = fallback_node_hosts,
             endpoint = self.endpoint,
             stacks_address = self.stacks_address,
             public_key = StacksPublicKey::from_private(&self.stacks_private_key).to_hex(),
+            next_key = next_key,
             network = self.network,
             db_path = self.db_path.to_str().unwrap_or_default(),
             tx_fee = tx_fee,
             metrics_endpoint = metrics_endpoint,
+            health_endpoint = health_endpoint,
+            dry_run = self.dry_run,
         )
     }
 }
@@ -511,10 +1254,11 @@ mod tests {
         let config =
             RawConfigFile::load_from_str(&config_tomls[0]).expect("Failed to parse config file");
 
-        assert_eq!(config.auth_password, "melon");
+        assert_eq!(config.auth_password, Some("melon".to_string()));
         assert!(config.max_tx_fee_ustx.is_none());
         assert!(config.tx_fee_ustx.is_none());
         assert_eq!(config.metrics_endpoint, Some("localhost:4000".to_string()));
+        assert!(config.health_endpoint.is_none());
     }
 
     #[test]
@@ -625,6 +1369,500 @@ mod tests {
         assert_eq!(Some(config.tx_fee_ustx), tx_fee_ustx);
     }
 
+    #[test]
+    fn next_stacks_key_is_used_from_its_activation_cycle() {
+        let config_toml = std::fs::read_to_string("./src/tests/conf/signer-0.toml").unwrap();
+        let next_key = StacksPrivateKey::new();
+        let config = GlobalConfig::load_from_str(&format!(
+            "{config_toml}\nnext_stacks_private_key = \"{}\"\nnext_key_activation_reward_cycle = 10\n",
+            next_key.to_hex()
+        ))
+        .expect("Failed to parse config with a next key");
+        let next_address = StacksAddress::p2pkh(false, &StacksPublicKey::from_private(&next_key));
+
+        assert_eq!(
+            config.stacks_private_key_for_cycle(9),
+            config.stacks_private_key
+        );
+        assert_eq!(config.stacks_address_for_cycle(9), config.stacks_address);
+        assert_eq!(
+            config.ecdsa_private_key_for_cycle(9),
+            config.ecdsa_private_key
+        );
+        assert_eq!(config.stacks_private_key_for_cycle(10), next_key);
+        assert_eq!(config.stacks_address_for_cycle(10), next_address);
+        assert_eq!(
+            config.ecdsa_private_key_for_cycle(10),
+            Scalar::try_from(&next_key.to_bytes()[..32]).unwrap()
+        );
+        assert_eq!(config.stacks_address_for_cycle(11), next_address);
+    }
+
+    #[test]
+    fn next_stacks_key_must_be_complete_and_new() {
+        let config_toml = std::fs::read_to_string("./src/tests/conf/signer-0.toml").unwrap();
+        let next_key = StacksPrivateKey::new().to_hex();
+
+        let missing_cycle = format!("{config_toml}\nnext_stacks_private_key = \"{next_key}\"\n");
+        assert!(matches!(
+            GlobalConfig::load_from_str(&missing_cycle),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+
+        let missing_key = format!("{config_toml}\nnext_key_activation_reward_cycle = 10\n");
+        assert!(matches!(
+            GlobalConfig::load_from_str(&missing_key),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+
+        let current_key = RawConfigFile::load_from_str(&config_toml)
+            .unwrap()
+            .stacks_private_key
+            .unwrap();
+        let same_key = format!(
+            "{config_toml}\nnext_stacks_private_key = \"{current_key}\"\nnext_key_activation_reward_cycle = 10\n"
+        );
+        assert!(matches!(
+            GlobalConfig::load_from_str(&same_key),
+            Err(ConfigError::BadField(field, _)) if field == "next_stacks_private_key"
+        ));
+    }
+
+    /// Load the signer-0 test config with `config_toml_extra` appended and the given
+    /// environment variables set
+    fn load_with_env(
+        config_toml_extra: &str,
+        vars: &[(&str, &str)],
+    ) -> Result<GlobalConfig, ConfigError> {
+        let config_toml = std::fs::read_to_string("./src/tests/conf/signer-0.toml").unwrap();
+        let raw = RawConfigFile::load_from_str(&format!("{config_toml}\n{config_toml_extra}"))?;
+        GlobalConfig::from_raw_with_env(
+            raw,
+            |var| {
+                vars.iter()
+                    .find(|(name, _)| *name == var)
+                    .map(|(_, value)| value.to_string())
+            },
+            true,
+        )
+    }
+
+    #[test]
+    fn env_vars_override_config_file_values() {
+        let next_key = StacksPrivateKey::new();
+        let next_key_hex = next_key.to_hex();
+        let defaults = load_with_env("", &[]).unwrap();
+        assert_eq!(
+            defaults.event_timeout,
+            Duration::from_millis(EVENT_TIMEOUT_MS)
+        );
+        assert_eq!(defaults.tx_fee_ustx, TX_FEE_USTX);
+
+        let config = load_with_env(
+            "",
+            &[
+                ("SIGNER_NODE_HOST", "localhost:20444"),
+                ("SIGNER_ENDPOINT", "127.0.0.1:30001"),
+                ("SIGNER_STACKS_PRIVATE_KEY", &next_key_hex),
+                ("SIGNER_NETWORK", "mocknet"),
+                ("SIGNER_EVENT_TIMEOUT_MS", "1000"),
+                ("SIGNER_DKG_PUBLIC_TIMEOUT_MS", "1001"),
+                ("SIGNER_DKG_PRIVATE_TIMEOUT_MS", "1002"),
+                ("SIGNER_DKG_END_TIMEOUT_MS", "1003"),
+                ("SIGNER_NONCE_TIMEOUT_MS", "1004"),
+                ("SIGNER_SIGN_TIMEOUT_MS", "1005"),
+                ("SIGNER_TX_FEE_USTX", "1006"),
+                ("SIGNER_MAX_TX_FEE_USTX", "1007"),
+                ("SIGNER_AUTH_PASSWORD", "melon"),
+                ("SIGNER_DB_PATH", "/tmp/signer.sqlite"),
+                ("SIGNER_METRICS_ENDPOINT", "127.0.0.1:9091"),
+                ("SIGNER_HEALTH_ENDPOINT", "127.0.0.1:9092"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.node_host, "localhost:20444");
+        assert_eq!(config.endpoint, "127.0.0.1:30001".parse().unwrap());
+        assert_eq!(config.stacks_private_key, next_key);
+        assert_eq!(config.network, Network::Mocknet);
+        assert_eq!(config.event_timeout, Duration::from_millis(1000));
+        assert_eq!(config.dkg_public_timeout, Some(Duration::from_millis(1001)));
+        assert_eq!(
+            config.dkg_private_timeout,
+            Some(Duration::from_millis(1002))
+        );
+        assert_eq!(config.dkg_end_timeout, Some(Duration::from_millis(1003)));
+        assert_eq!(config.nonce_timeout, Some(Duration::from_millis(1004)));
+        assert_eq!(config.sign_timeout, Some(Duration::from_millis(1005)));
+        assert_eq!(config.tx_fee_ustx, 1006);
+        assert_eq!(config.max_tx_fee_ustx, Some(1007));
+        assert_eq!(config.auth_password, "melon");
+        assert_eq!(config.db_path, PathBuf::from("/tmp/signer.sqlite"));
+        assert_eq!(
+            config.metrics_endpoint,
+            Some("127.0.0.1:9091".parse().unwrap())
+        );
+        assert_eq!(
+            config.health_endpoint,
+            Some("127.0.0.1:9092".parse().unwrap())
+        );
+
+        let rotating = load_with_env(
+            "",
+            &[
+                ("SIGNER_NEXT_STACKS_PRIVATE_KEY", &next_key_hex),
+                ("SIGNER_NEXT_KEY_ACTIVATION_REWARD_CYCLE", "12"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(rotating.stacks_private_key_for_cycle(12), next_key);
+    }
+
+    #[test]
+    fn required_fields_can_come_from_the_environment() {
+        let keyless = keyless_config_toml();
+        let raw = RawConfigFile::load_from_str(&keyless).unwrap();
+        let key = StacksPrivateKey::new().to_hex();
+        let config = GlobalConfig::from_raw_with_env(
+            raw,
+            |var| (var == "SIGNER_STACKS_PRIVATE_KEY").then(|| key.clone()),
+            true,
+        )
+        .unwrap();
+        assert_eq!(config.stacks_private_key.to_hex(), key);
+    }
+
+    #[test]
+    fn invalid_env_values_are_rejected() {
+        assert!(matches!(
+            load_with_env("", &[("SIGNER_TX_FEE_USTX", "lots")]),
+            Err(ConfigError::BadField(var, value)) if var == "SIGNER_TX_FEE_USTX" && value == "lots"
+        ));
+        assert!(matches!(
+            load_with_env("", &[("SIGNER_NETWORK", "devnet")]),
+            Err(ConfigError::BadField(var, _)) if var == "SIGNER_NETWORK"
+        ));
+    }
+
+    #[test]
+    fn dry_run_is_opt_in_and_requires_a_restart() {
+        let config = load_with_env("", &[]).unwrap();
+        assert!(!config.dry_run);
+        let dry_run_config = load_with_env("dry_run = true", &[]).unwrap();
+        assert!(dry_run_config.dry_run);
+        assert!(
+            !load_with_env("dry_run = true", &[("SIGNER_DRY_RUN", "false")])
+                .unwrap()
+                .dry_run
+        );
+        assert!(matches!(
+            load_with_env("", &[("SIGNER_DRY_RUN", "1")]),
+            Err(ConfigError::BadField(var, _)) if var == "SIGNER_DRY_RUN"
+        ));
+
+        assert!(matches!(
+            config.check_reloadable(&dry_run_config),
+            Err(ConfigError::RestartRequired(field)) if field == "dry_run"
+        ));
+    }
+
+    #[test]
+    fn payload_envelope_is_opt_in() {
+        assert_eq!(load_with_env("", &[]).unwrap().payload_envelope, None);
+        assert_eq!(
+            load_with_env("payload_envelope = true", &[])
+                .unwrap()
+                .payload_envelope,
+            Some(EnvelopeConfig::default())
+        );
+        assert_eq!(
+            load_with_env(
+                "payload_envelope = false",
+                &[
+                    ("SIGNER_PAYLOAD_ENVELOPE", "true"),
+                    ("SIGNER_PAYLOAD_COMPRESSION_THRESHOLD", "1024"),
+                ],
+            )
+            .unwrap()
+            .payload_envelope,
+            Some(EnvelopeConfig {
+                compression_threshold: 1024
+            })
+        );
+        assert!(matches!(
+            load_with_env("", &[("SIGNER_PAYLOAD_ENVELOPE", "yes")]),
+            Err(ConfigError::BadField(var, _)) if var == "SIGNER_PAYLOAD_ENVELOPE"
+        ));
+    }
+
+    #[test]
+    fn node_staleness_bounds() {
+        let config = load_with_env("", &[]).unwrap();
+        assert_eq!(
+            config.burn_block_interval,
+            Duration::from_millis(BURN_BLOCK_INTERVAL_MS)
+        );
+        assert_eq!(
+            config.max_node_staleness,
+            Duration::from_millis(MAX_NODE_STALENESS_MS)
+        );
+        let config = load_with_env(
+            "burn_block_interval_ms = 30000\nmax_node_staleness_ms = 60000",
+            &[("SIGNER_MAX_NODE_STALENESS_MS", "90000")],
+        )
+        .unwrap();
+        assert_eq!(config.burn_block_interval, Duration::from_secs(30));
+        assert_eq!(config.max_node_staleness, Duration::from_secs(90));
+    }
+
+    #[test]
+    fn block_response_delay_defaults_to_none() {
+        let config = load_with_env("", &[]).unwrap();
+        assert_eq!(config.block_response_delay_step, Duration::ZERO);
+        assert_eq!(
+            config.block_response_max_delay,
+            Duration::from_millis(BLOCK_RESPONSE_MAX_DELAY_MS)
+        );
+        let config = load_with_env(
+            "block_response_delay_step_ms = 100",
+            &[("SIGNER_BLOCK_RESPONSE_MAX_DELAY_MS", "500")],
+        )
+        .unwrap();
+        assert_eq!(config.block_response_delay_step, Duration::from_millis(100));
+        assert_eq!(config.block_response_max_delay, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn node_http_client_limits() {
+        let config = load_with_env("", &[]).unwrap();
+        assert_eq!(
+            config.node_read_timeout,
+            Duration::from_millis(NODE_READ_TIMEOUT_MS)
+        );
+        assert_eq!(
+            config.node_write_timeout,
+            Duration::from_millis(NODE_WRITE_TIMEOUT_MS)
+        );
+        assert_eq!(config.node_max_concurrent_reads, 8);
+        assert_eq!(config.node_write_max_idle_connections, 4);
+        let config = load_with_env(
+            "node_read_timeout_ms = 10000\nnode_max_concurrent_reads = 2",
+            &[
+                ("SIGNER_NODE_WRITE_TIMEOUT_MS", "5000"),
+                ("SIGNER_NODE_WRITE_MAX_IDLE_CONNECTIONS", "1"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.node_read_timeout, Duration::from_secs(10));
+        assert_eq!(config.node_write_timeout, Duration::from_secs(5));
+        assert_eq!(config.node_max_concurrent_reads, 2);
+        assert_eq!(config.node_write_max_idle_connections, 1);
+        assert!(matches!(
+            load_with_env("node_max_concurrent_reads = 0", &[]),
+            Err(ConfigError::BadField(field, _)) if field == "node_max_concurrent_reads"
+        ));
+    }
+
+    #[test]
+    fn audit_log_is_configured_by_its_path() {
+        let config = load_with_env("", &[]).unwrap();
+        assert_eq!(config.audit_log, None);
+        let config = load_with_env(
+            "audit_log_path = \"/tmp/audit.jsonl\"\naudit_log_max_files = 3",
+            &[("SIGNER_AUDIT_LOG_MAX_BYTES", "1024")],
+        )
+        .unwrap();
+        assert_eq!(
+            config.audit_log,
+            Some(AuditLogConfig {
+                path: PathBuf::from("/tmp/audit.jsonl"),
+                key_path: PathBuf::from("/tmp/audit.jsonl.key"),
+                anchor_path: PathBuf::from("/tmp/audit.jsonl.anchor"),
+                max_bytes: 1024,
+                max_files: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn fallback_node_hosts_are_validated() {
+        let config = load_with_env("", &[]).unwrap();
+        assert!(config.fallback_node_hosts.is_empty());
+        assert_eq!(
+            config.node_failover_cooldown,
+            Duration::from_millis(NODE_FAILOVER_COOLDOWN_MS)
+        );
+        let config = load_with_env(
+            r#"fallback_node_hosts = ["127.0.0.1:30443", "127.0.0.1:40443"]"#,
+            &[("SIGNER_NODE_FAILOVER_COOLDOWN_MS", "1000")],
+        )
+        .unwrap();
+        assert_eq!(
+            config.fallback_node_hosts,
+            vec!["127.0.0.1:30443", "127.0.0.1:40443"]
+        );
+        assert_eq!(config.node_failover_cooldown, Duration::from_secs(1));
+        let config = load_with_env(
+            r#"fallback_node_hosts = ["127.0.0.1:30443"]"#,
+            &[(
+                "SIGNER_FALLBACK_NODE_HOSTS",
+                "127.0.0.1:40443, 127.0.0.1:50443",
+            )],
+        )
+        .unwrap();
+        assert_eq!(
+            config.fallback_node_hosts,
+            vec!["127.0.0.1:40443", "127.0.0.1:50443"]
+        );
+        assert!(matches!(
+            load_with_env(r#"fallback_node_hosts = ["not a host"]"#, &[]),
+            Err(ConfigError::BadField(field, _)) if field == "fallback_node_hosts"
+        ));
+    }
+
+    #[test]
+    fn each_validation_failure_is_reported() {
+        assert!(matches!(
+            GlobalConfig::load_from_str(&config_toml_without("auth_password")),
+            Err(ConfigError::MissingField(field)) if field == "auth_password"
+        ));
+        assert!(matches!(
+            load_with_env("", &[("SIGNER_NODE_HOST", "not a host")]),
+            Err(ConfigError::BadField(field, _)) if field == "node_host"
+        ));
+        assert!(matches!(
+            load_with_env("", &[("SIGNER_ENDPOINT", "not an endpoint")]),
+            Err(ConfigError::BadField(field, _)) if field == "endpoint"
+        ));
+        assert!(matches!(
+            load_with_env("", &[("SIGNER_METRICS_ENDPOINT", "127.0.0.1:30000")]),
+            Err(ConfigError::BadField(field, _)) if field == "metrics_endpoint"
+        ));
+        assert!(matches!(
+            load_with_env("health_endpoint = \"127.0.0.1:9090\"", &[]),
+            Err(ConfigError::BadField(field, _)) if field == "health_endpoint"
+        ));
+    }
+
+    #[test]
+    fn validation_failures_are_aggregated() {
+        let err = load_with_env(
+            "",
+            &[
+                ("SIGNER_NODE_HOST", "not a host"),
+                ("SIGNER_ENDPOINT", "not an endpoint"),
+                ("SIGNER_STACKS_PRIVATE_KEY", "not a key"),
+                ("SIGNER_TX_FEE_USTX", "lots"),
+            ],
+        )
+        .unwrap_err();
+        let ConfigError::Multiple(errors) = &err else {
+            panic!("Expected several errors, got {err:?}");
+        };
+        let fields: Vec<_> = errors
+            .iter()
+            .filter_map(|e| match e {
+                ConfigError::BadField(field, _) => Some(field.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "SIGNER_TX_FEE_USTX",
+                "node_host",
+                "endpoint",
+                "stacks_private_key"
+            ]
+        );
+        let message = err.to_string();
+        assert!(message.starts_with("4 invalid fields"), "{message}");
+    }
+
+    /// The signer-0 test config without its plaintext `stacks_private_key`
+    fn keyless_config_toml() -> String {
+        config_toml_without("stacks_private_key")
+    }
+
+    /// The signer-0 test config without the line setting `field`
+    fn config_toml_without(field: &str) -> String {
+        std::fs::read_to_string("./src/tests/conf/signer-0.toml")
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with(field))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn stacks_private_key_can_be_loaded_from_a_key_file() {
+        let plaintext_config =
+            GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "stacks-signer-config-key-file-{}.json",
+            rand::random::<u64>()
+        ));
+        let contents = key_file::encrypt_private_key_with_cost(
+            &plaintext_config.stacks_private_key,
+            "melon",
+            4,
+        )
+        .unwrap();
+        key_file::write_key_file(&path, &contents).unwrap();
+        std::env::set_var(key_file::PASSPHRASE_ENV_VAR, "melon");
+
+        let config = GlobalConfig::load_from_str(&format!(
+            "{}\nstacks_private_key_file = \"{}\"\n",
+            keyless_config_toml(),
+            path.display()
+        ))
+        .expect("Failed to load config with a key file");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            config.stacks_private_key,
+            plaintext_config.stacks_private_key
+        );
+        assert_eq!(config.stacks_address, plaintext_config.stacks_address);
+        assert_eq!(config.ecdsa_private_key, plaintext_config.ecdsa_private_key);
+    }
+
+    #[test]
+    fn reloads_never_prompt_for_the_key_file_passphrase() {
+        let with_key_file = format!(
+            "{}\nstacks_private_key_file = \"signer.key\"\n",
+            keyless_config_toml()
+        );
+        let raw = RawConfigFile::load_from_str(&with_key_file).unwrap();
+        assert!(matches!(
+            GlobalConfig::from_raw_with_env(raw, |_| None, false),
+            Err(ConfigError::KeyFile(KeyFileError::Passphrase(_)))
+        ));
+        // a key file set from the environment is caught too
+        let raw = RawConfigFile::load_from_str(&keyless_config_toml()).unwrap();
+        assert!(matches!(
+            GlobalConfig::from_raw_with_env(
+                raw,
+                |var| (var == "SIGNER_STACKS_PRIVATE_KEY_FILE").then(|| "signer.key".into()),
+                false
+            ),
+            Err(ConfigError::KeyFile(KeyFileError::Passphrase(_)))
+        ));
+    }
+
+    #[test]
+    fn stacks_private_key_and_key_file_are_mutually_exclusive() {
+        let config_toml = std::fs::read_to_string("./src/tests/conf/signer-0.toml").unwrap();
+        let both = format!("{config_toml}\nstacks_private_key_file = \"signer.key\"\n");
+        assert!(matches!(
+            GlobalConfig::load_from_str(&both),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+        assert!(matches!(
+            GlobalConfig::load_from_str(&keyless_config_toml()),
+            Err(ConfigError::InvalidConfig(_))
+        ));
+    }
+
     #[test]
     fn test_config_to_string() {
         let config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
@@ -634,13 +1872,17 @@ mod tests {
             format!(
                 r#"
 Stacks node host: 127.0.0.1:20443
+Fallback stacks node hosts: None
 Signer endpoint: [::1]:30000
 Stacks address: ST3FPN8KBZ3YPBP0ZJGAAHTVFMQDTJCR5QPS7VTNJ
 Public key: 03bc489f27da3701d9f9e577c88de5567cf4023111b7577042d55cde4d823a3505
+Next public key: None
 Network: testnet
 Database path: :memory:
 DKG transaction fee: 0.01 uSTX
 Metrics endpoint: 0.0.0.0:9090
+Health endpoint: None
+Dry run: false
 "#
             )
         );
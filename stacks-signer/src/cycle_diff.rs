@@ -0,0 +1,301 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use blockstack_lib::chainstate::stacks::boot::{signing_threshold_of, NakamotoSignerEntry};
+use serde_derive::Serialize;
+use stacks_common::address::c32::c32_address;
+use stacks_common::address::{
+    C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
+use stacks_common::util::hash::{to_hex, Hash160};
+
+/// A signer of one of the compared signer sets
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignerWeight {
+    /// The c32 address of the signer's signing key
+    pub address: String,
+    /// The signer's signing key, as hex
+    pub signing_key: String,
+    /// The signer's weight in the signer set
+    pub weight: u32,
+}
+
+/// A signer of both signer sets whose weight changed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignerWeightChange {
+    /// The c32 address of the signer's signing key
+    pub address: String,
+    /// The signer's signing key, as hex
+    pub signing_key: String,
+    /// The signer's weight in the earlier signer set
+    pub from_weight: u32,
+    /// The signer's weight in the later signer set
+    pub to_weight: u32,
+}
+
+/// How the signer set of one reward cycle differs from another's. Signers are told apart by
+/// their signing keys, and listed in the order of their signing keys.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SignerSetDiff {
+    /// The reward cycle compared from
+    pub from_cycle: u64,
+    /// The reward cycle compared to
+    pub to_cycle: u64,
+    /// The total weight of the signers of `from_cycle`
+    pub from_total_weight: u32,
+    /// The total weight of the signers of `to_cycle`
+    pub to_total_weight: u32,
+    /// The weight needed to sign a block in `from_cycle`
+    pub from_threshold: u32,
+    /// The weight needed to sign a block in `to_cycle`
+    pub to_threshold: u32,
+    /// The signers of `to_cycle` that are not signers of `from_cycle`
+    pub added: Vec<SignerWeight>,
+    /// The signers of `from_cycle` that are not signers of `to_cycle`
+    pub removed: Vec<SignerWeight>,
+    /// The signers of both cycles whose weight changed
+    pub changed: Vec<SignerWeightChange>,
+}
+
+/// The weight of each signing key in `signers`, and their total weight. Errors if the weights
+/// do not fit in a u32.
+fn weights_by_key(
+    reward_cycle: u64,
+    signers: &[NakamotoSignerEntry],
+) -> Result<(BTreeMap<[u8; 33], u32>, u32), String> {
+    let overflow = || format!("Signer weights of reward cycle {reward_cycle} exceed u32::MAX");
+    let mut weights = BTreeMap::new();
+    let mut total_weight = 0u32;
+    for signer in signers.iter() {
+        // a key listed twice signs with both of its weights
+        let weight = weights.entry(signer.signing_key).or_insert(0u32);
+        *weight = weight.checked_add(signer.weight).ok_or_else(overflow)?;
+        total_weight = total_weight
+            .checked_add(signer.weight)
+            .ok_or_else(overflow)?;
+    }
+    Ok((weights, total_weight))
+}
+
+/// The c32 address of `signing_key`, which need not be a valid public key
+fn signer_address(mainnet: bool, signing_key: &[u8; 33]) -> String {
+    let version = if mainnet {
+        C32_ADDRESS_VERSION_MAINNET_SINGLESIG
+    } else {
+        C32_ADDRESS_VERSION_TESTNET_SINGLESIG
+    };
+    c32_address(version, &Hash160::from_data(signing_key).0)
+        .expect("FATAL: singlesig address version is not a valid c32 version")
+}
+
+impl SignerSetDiff {
+    /// Compare the signers of `from_cycle` to those of `to_cycle`. Errors if the weights of
+    /// either signer set do not fit in a u32.
+    pub fn new(
+        mainnet: bool,
+        from_cycle: u64,
+        from_signers: &[NakamotoSignerEntry],
+        to_cycle: u64,
+        to_signers: &[NakamotoSignerEntry],
+    ) -> Result<Self, String> {
+        let (from_weights, from_total_weight) = weights_by_key(from_cycle, from_signers)?;
+        let (to_weights, to_total_weight) = weights_by_key(to_cycle, to_signers)?;
+        let signer_weight = |signing_key: &[u8; 33], weight: u32| SignerWeight {
+            address: signer_address(mainnet, signing_key),
+            signing_key: to_hex(signing_key),
+            weight,
+        };
+        let added = to_weights
+            .iter()
+            .filter(|(signing_key, _)| !from_weights.contains_key(*signing_key))
+            .map(|(signing_key, weight)| signer_weight(signing_key, *weight))
+            .collect();
+        let mut removed = vec![];
+        let mut changed = vec![];
+        for (signing_key, from_weight) in from_weights.iter() {
+            match to_weights.get(signing_key) {
+                None => removed.push(signer_weight(signing_key, *from_weight)),
+                Some(to_weight) if to_weight != from_weight => changed.push(SignerWeightChange {
+                    address: signer_address(mainnet, signing_key),
+                    signing_key: to_hex(signing_key),
+                    from_weight: *from_weight,
+                    to_weight: *to_weight,
+                }),
+                Some(_) => {}
+            }
+        }
+        Ok(Self {
+            from_cycle,
+            to_cycle,
+            from_total_weight,
+            to_total_weight,
+            from_threshold: signing_threshold_of(from_total_weight),
+            to_threshold: signing_threshold_of(to_total_weight),
+            added,
+            removed,
+            changed,
+        })
+    }
+
+    /// Whether both signer sets have the same signers with the same weights
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Display for SignerSetDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Signer set of reward cycle {} compared to reward cycle {}",
+            self.to_cycle, self.from_cycle
+        )?;
+        writeln!(
+            f,
+            "Total weight: {} -> {}",
+            self.from_total_weight, self.to_total_weight
+        )?;
+        write!(
+            f,
+            "Signing threshold: {} -> {}",
+            self.from_threshold, self.to_threshold
+        )?;
+        if self.is_empty() {
+            return write!(f, "\nNo signers were added, removed, or reweighted");
+        }
+        for signer in self.added.iter() {
+            write!(f, "\n+ {} weight {}", signer.address, signer.weight)?;
+        }
+        for signer in self.removed.iter() {
+            write!(f, "\n- {} weight {}", signer.address, signer.weight)?;
+        }
+        for signer in self.changed.iter() {
+            write!(
+                f,
+                "\n~ {} weight {} -> {}",
+                signer.address, signer.from_weight, signer.to_weight
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+
+    use super::*;
+
+    /// A signer with a valid signing key derived from `seed`
+    fn signer(seed: u8, weight: u32) -> NakamotoSignerEntry {
+        let private_key = StacksPrivateKey::from_seed(&[seed]);
+        let signing_key = StacksPublicKey::from_private(&private_key)
+            .to_bytes_compressed()
+            .try_into()
+            .unwrap();
+        NakamotoSignerEntry {
+            signing_key,
+            stacked_amt: u128::from(weight) * 1_000_000,
+            weight,
+        }
+    }
+
+    #[test]
+    fn signers_are_diffed_by_signing_key() {
+        let from = [signer(1, 4), signer(2, 3), signer(3, 3)];
+        let to = [signer(4, 2), signer(2, 5), signer(3, 3)];
+        let diff = SignerSetDiff::new(false, 10, &from, 11, &to).unwrap();
+
+        assert_eq!((diff.from_total_weight, diff.to_total_weight), (10, 10));
+        assert_eq!((diff.from_threshold, diff.to_threshold), (7, 7));
+        let address_of = |entry: &NakamotoSignerEntry| {
+            let pubkey = StacksPublicKey::from_slice(&entry.signing_key).unwrap();
+            StacksAddress::p2pkh(false, &pubkey).to_string()
+        };
+        assert_eq!(
+            diff.added,
+            vec![SignerWeight {
+                address: address_of(&to[0]),
+                signing_key: to_hex(&to[0].signing_key),
+                weight: 2,
+            }]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![SignerWeight {
+                address: address_of(&from[0]),
+                signing_key: to_hex(&from[0].signing_key),
+                weight: 4,
+            }]
+        );
+        assert_eq!(
+            diff.changed,
+            vec![SignerWeightChange {
+                address: address_of(&from[1]),
+                signing_key: to_hex(&from[1].signing_key),
+                from_weight: 3,
+                to_weight: 5,
+            }]
+        );
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["changed"][0]["from_weight"], 3);
+        assert_eq!(json["added"][0]["address"], address_of(&to[0]));
+    }
+
+    #[test]
+    fn threshold_changes_are_reported_for_unchanged_signers() {
+        let signers = [signer(1, 4), signer(2, 3)];
+        let diff = SignerSetDiff::new(true, 10, &signers, 11, &signers).unwrap();
+        assert!(diff.is_empty());
+        assert!(diff
+            .to_string()
+            .ends_with("No signers were added, removed, or reweighted"));
+
+        // the threshold rounds up, so dropping a signer of weight 1 from 10 keeps it at 7
+        let from = [signer(1, 9), signer(2, 1)];
+        let to = [signer(1, 9)];
+        let diff = SignerSetDiff::new(true, 10, &from, 11, &to).unwrap();
+        assert_eq!((diff.from_threshold, diff.to_threshold), (7, 7));
+        assert_eq!(diff.removed.len(), 1);
+        assert!(diff.removed[0].address.starts_with("SP"));
+    }
+
+    #[test]
+    fn malformed_and_repeated_signing_keys_are_diffed() {
+        let malformed = NakamotoSignerEntry {
+            signing_key: [0x04; 33],
+            stacked_amt: 0,
+            weight: 1,
+        };
+        // a key listed twice counts with both of its weights
+        let from = [signer(1, 2), signer(1, 3)];
+        let to = [signer(1, 5), malformed.clone()];
+        let diff = SignerSetDiff::new(false, 10, &from, 11, &to).unwrap();
+        assert!(diff.changed.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].signing_key, to_hex(&malformed.signing_key));
+        assert!(diff.added[0].address.starts_with("ST"));
+        assert_eq!(diff.to_total_weight, 6);
+
+        let overweight = [signer(1, u32::MAX), signer(2, 1)];
+        assert!(SignerSetDiff::new(false, 10, &from, 11, &overweight).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use serde_derive::{Deserialize, Serialize};
+use slog::{slog_debug, slog_error, slog_info};
+use stacks_common::{debug, error, info};
+use tiny_http::{Header, Request, Response as HttpResponse, Server as HttpServer};
+
+/// The maximum number of recent errors reported by the health endpoint
+pub const MAX_RECENT_ERRORS: usize = 16;
+
+/// How long the signer may go without a successful `/v2/info` response before
+/// the stacks node is considered unreachable
+pub const NODE_UNREACHABLE_AFTER: Duration = Duration::from_secs(120);
+
+/// The health state shared between the runloop and the health listener
+pub type SharedHealthState = Arc<RwLock<HealthState>>;
+
+#[derive(thiserror::Error, Debug)]
+/// Health listener errors
+pub enum HealthError {
+    /// Failed to bind the listener to the configured address
+    #[error("Failed to bind health listener to {0}: {1}")]
+    BindFailure(SocketAddr, String),
+    /// Failed to spawn the listener thread
+    #[error("Failed to spawn health listener thread: {0}")]
+    SpawnFailure(String),
+}
+
+/// An error recently observed by the signer
+#[derive(Debug, Clone)]
+struct RecentError {
+    /// When the error was observed
+    observed_at: Instant,
+    /// The rendered error
+    message: String,
+}
+
+/// The signer's view of its own health, updated by the runloop and its clients
+#[derive(Debug, Default)]
+pub struct HealthState {
+    /// The last time the stacks node answered a `/v2/info` request
+    last_node_contact: Option<Instant>,
+    /// The last time a StackerDB write was acknowledged
+    last_stackerdb_ack: Option<Instant>,
+    /// The current reward cycle, once known
+    reward_cycle: Option<u64>,
+    /// Whether this signer is registered for the current reward cycle
+    in_active_set: bool,
+    /// How far the stacks node's clock was last measured to be ahead of ours, in milliseconds
+    clock_skew_ms: Option<i64>,
+    /// The most recent errors, oldest first
+    recent_errors: VecDeque<RecentError>,
+    /// Whether the signer only logs what it would publish
+    dry_run: bool,
+}
+
+/// An error as reported by the health endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthErrorEntry {
+    /// Seconds since the error was observed
+    pub age_secs: u64,
+    /// The rendered error
+    pub error: String,
+}
+
+/// The JSON body served by the health endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Whether the signer considers itself healthy
+    pub healthy: bool,
+    /// Whether the stacks node answered a `/v2/info` request recently
+    pub node_reachable: bool,
+    /// Seconds since the last successful `/v2/info` response
+    pub last_node_contact_secs: Option<u64>,
+    /// Seconds since the last acknowledged StackerDB write
+    pub last_stackerdb_ack_secs: Option<u64>,
+    /// The current reward cycle, once known
+    pub reward_cycle: Option<u64>,
+    /// Whether this signer is registered for the current reward cycle
+    pub in_active_set: bool,
+    /// How far the stacks node's clock is ahead of the signer's, in milliseconds, once measured
+    pub clock_skew_ms: Option<i64>,
+    /// The most recent errors, oldest first
+    pub recent_errors: Vec<HealthErrorEntry>,
+    /// Whether the signer is a dry-run signer, which publishes nothing
+    pub dry_run: bool,
+}
+
+impl HealthState {
+    /// Create a new, empty health state to share between threads
+    pub fn new_shared() -> SharedHealthState {
+        Arc::new(RwLock::new(Self::default()))
+    }
+
+    /// Record a successful `/v2/info` response from the stacks node
+    pub fn record_node_contact(&mut self) {
+        self.last_node_contact = Some(Instant::now());
+    }
+
+    /// Record an acknowledged StackerDB write
+    pub fn record_stackerdb_ack(&mut self) {
+        self.last_stackerdb_ack = Some(Instant::now());
+    }
+
+    /// Record the current reward cycle and whether this signer is registered for it
+    pub fn update_reward_cycle(&mut self, reward_cycle: u64, in_active_set: bool) {
+        self.reward_cycle = Some(reward_cycle);
+        self.in_active_set = in_active_set;
+    }
+
+    /// Record whether the signer only logs what it would publish
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    /// Record the measured clock skew from the stacks node
+    pub fn record_clock_skew(&mut self, clock_skew_ms: i64) {
+        self.clock_skew_ms = Some(clock_skew_ms);
+    }
+
+    /// Record an error, evicting the oldest one if the buffer is full
+    pub fn record_error(&mut self, err: &impl Display) {
+        if self.recent_errors.len() >= MAX_RECENT_ERRORS {
+            self.recent_errors.pop_front();
+        }
+        self.recent_errors.push_back(RecentError {
+            observed_at: Instant::now(),
+            message: err.to_string(),
+        });
+    }
+
+    /// Build the report served by the health endpoint
+    pub fn report(&self) -> HealthReport {
+        let now = Instant::now();
+        let age_secs = |at: &Instant| now.saturating_duration_since(*at).as_secs();
+        let node_reachable = self
+            .last_node_contact
+            .map(|at| now.saturating_duration_since(at) <= NODE_UNREACHABLE_AFTER)
+            .unwrap_or(false);
+        HealthReport {
+            healthy: node_reachable,
+            node_reachable,
+            last_node_contact_secs: self.last_node_contact.as_ref().map(age_secs),
+            last_stackerdb_ack_secs: self.last_stackerdb_ack.as_ref().map(age_secs),
+            reward_cycle: self.reward_cycle,
+            in_active_set: self.in_active_set,
+            clock_skew_ms: self.clock_skew_ms,
+            recent_errors: self
+                .recent_errors
+                .iter()
+                .map(|err| HealthErrorEntry {
+                    age_secs: age_secs(&err.observed_at),
+                    error: err.message.clone(),
+                })
+                .collect(),
+            dry_run: self.dry_run,
+        }
+    }
+}
+
+/// Bind the health listener to `endpoint` and serve `/health` from a background thread.
+/// Returns the address the listener is bound to.
+pub fn start_health_server(
+    endpoint: SocketAddr,
+    state: SharedHealthState,
+) -> Result<SocketAddr, HealthError> {
+    let http_server = HttpServer::http(endpoint)
+        .map_err(|e| HealthError::BindFailure(endpoint, e.to_string()))?;
+    let local_addr = http_server.server_addr().to_ip().unwrap_or(endpoint);
+    std::thread::Builder::new()
+        .name("signer_health".to_string())
+        .spawn(move || {
+            info!("Health: listening on {local_addr}");
+            for request in http_server.incoming_requests() {
+                serve_request(&state, request);
+            }
+            error!("Health: listener on {local_addr} terminated");
+        })
+        .map_err(|e| HealthError::SpawnFailure(e.to_string()))?;
+    Ok(local_addr)
+}
+
+/// Answer a single request to the health listener
+fn serve_request(state: &SharedHealthState, request: Request) {
+    debug!("Health: received request {}", request.url());
+    let response = if request.url() == "/health" {
+        let report = match state.read() {
+            Ok(state) => state.report(),
+            Err(poisoned) => poisoned.into_inner().report(),
+        };
+        let status = if report.healthy { 200 } else { 503 };
+        let body = serde_json::to_string(&report).unwrap_or_default();
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+            .expect("FATAL: invalid static header");
+        HttpResponse::from_string(body)
+            .with_status_code(status)
+            .with_header(content_type)
+    } else {
+        HttpResponse::from_string("Not Found").with_status_code(404)
+    };
+    if let Err(e) = request.respond(response) {
+        error!("Health: failed to respond to request: {e:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_health(addr: SocketAddr) -> (u16, serde_json::Value) {
+        let response = reqwest::blocking::get(format!("http://{addr}/health")).unwrap();
+        let status = response.status().as_u16();
+        (status, response.json().unwrap())
+    }
+
+    fn start_test_server(state: SharedHealthState) -> SocketAddr {
+        start_health_server("127.0.0.1:0".parse().unwrap(), state).unwrap()
+    }
+
+    #[test]
+    fn health_endpoint_reports_healthy_signer() {
+        let state = HealthState::new_shared();
+        {
+            let mut state = state.write().unwrap();
+            state.record_node_contact();
+            state.record_stackerdb_ack();
+            state.update_reward_cycle(7, true);
+            state.record_clock_skew(-1_500);
+        }
+        let addr = start_test_server(state);
+        let (status, body) = get_health(addr);
+        assert_eq!(status, 200);
+        for field in [
+            "healthy",
+            "node_reachable",
+            "last_node_contact_secs",
+            "last_stackerdb_ack_secs",
+            "reward_cycle",
+            "in_active_set",
+            "clock_skew_ms",
+            "recent_errors",
+            "dry_run",
+        ] {
+            assert!(body.get(field).is_some(), "missing field {field}");
+        }
+        assert_eq!(body["healthy"], true);
+        assert_eq!(body["node_reachable"], true);
+        assert_eq!(body["reward_cycle"], 7);
+        assert_eq!(body["in_active_set"], true);
+        assert_eq!(body["clock_skew_ms"], -1_500);
+        assert!(body["last_stackerdb_ack_secs"].is_u64());
+        assert_eq!(body["recent_errors"].as_array().unwrap().len(), 0);
+        assert_eq!(body["dry_run"], false);
+    }
+
+    #[test]
+    fn health_endpoint_reports_degraded_signer() {
+        let state = HealthState::new_shared();
+        state
+            .write()
+            .unwrap()
+            .record_error(&"Failed to connect to stacks node");
+        let addr = start_test_server(state);
+        let (status, body) = get_health(addr);
+        assert_eq!(status, 503);
+        assert_eq!(body["healthy"], false);
+        assert_eq!(body["node_reachable"], false);
+        assert!(body["last_node_contact_secs"].is_null());
+        assert!(body["last_stackerdb_ack_secs"].is_null());
+        assert!(body["reward_cycle"].is_null());
+        assert_eq!(body["in_active_set"], false);
+        assert!(body["clock_skew_ms"].is_null());
+        let errors = body["recent_errors"].as_array().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0]["error"], "Failed to connect to stacks node");
+        assert!(errors[0]["age_secs"].is_u64());
+
+        let response = reqwest::blocking::get(format!("http://{addr}/metrics")).unwrap();
+        assert_eq!(response.status().as_u16(), 404);
+    }
+
+    #[test]
+    fn recent_errors_are_bounded() {
+        let mut state = HealthState::default();
+        for i in 0..(MAX_RECENT_ERRORS + 5) {
+            state.record_error(&format!("error {i}"));
+        }
+        let report = state.report();
+        assert_eq!(report.recent_errors.len(), MAX_RECENT_ERRORS);
+        assert_eq!(report.recent_errors[0].error, "error 5");
+        assert_eq!(
+            report.recent_errors.last().unwrap().error,
+            format!("error {}", MAX_RECENT_ERRORS + 4)
+        );
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use rand::RngCore;
+use serde_derive::{Deserialize, Serialize};
+use stacks_common::types::chainstate::StacksPrivateKey;
+use stacks_common::util::hash::{hex_bytes, to_hex, Sha256Sum};
+use zeroize::Zeroizing;
+
+/// The environment variable the key file passphrase is read from. If it is not set,
+/// the signer prompts for the passphrase on the terminal.
+pub const PASSPHRASE_ENV_VAR: &str = "STACKS_SIGNER_KEY_PASSPHRASE";
+
+/// The version of the key file format written by this module
+const KEY_FILE_VERSION: u8 = 1;
+/// The scrypt cost parameter (log2 of N) used for new key files
+const SCRYPT_LOG_N: u8 = 15;
+/// The scrypt block size used for new key files
+const SCRYPT_R: u32 = 8;
+/// The scrypt parallelization used for new key files
+const SCRYPT_P: u32 = 1;
+/// The length of the scrypt output: an AES-256 key followed by the passphrase check key
+const DERIVED_KEY_LEN: usize = 64;
+/// The highest scrypt cost (log2 of N) a key file may ask for. Together with `MAX_SCRYPT_R`,
+/// this caps the memory a key file can make the signer spend deriving its key at 1 GiB.
+const MAX_SCRYPT_LOG_N: u8 = 20;
+/// The highest scrypt block size a key file may ask for
+const MAX_SCRYPT_R: u32 = 8;
+/// The highest scrypt parallelization a key file may ask for
+const MAX_SCRYPT_P: u32 = 4;
+
+#[derive(thiserror::Error, Debug)]
+/// An error occurred reading, writing, or decrypting a key file
+pub enum KeyFileError {
+    /// The key file could not be read or written
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The passphrase does not match the one the key file was encrypted with
+    #[error("Wrong passphrase")]
+    WrongPassphrase,
+    /// The key file is malformed or has been tampered with
+    #[error("Corrupt key file: {0}")]
+    Corrupt(String),
+    /// No passphrase could be obtained
+    #[error("Failed to read passphrase: {0}")]
+    Passphrase(String),
+}
+
+/// The on-disk representation of an encrypted Stacks private key
+#[derive(Serialize, Deserialize, Debug)]
+struct EncryptedKeyFile {
+    /// The key file format version
+    version: u8,
+    /// log2 of the scrypt cost parameter N
+    scrypt_log_n: u8,
+    /// The scrypt block size
+    scrypt_r: u32,
+    /// The scrypt parallelization
+    scrypt_p: u32,
+    /// The hex-encoded scrypt salt
+    salt: String,
+    /// The hex-encoded AES-GCM nonce
+    nonce: String,
+    /// The hex-encoded SHA-256 of the second half of the derived key, which tells
+    /// a wrong passphrase apart from a corrupt ciphertext
+    passphrase_check: String,
+    /// The hex-encoded AES-256-GCM encryption of the hex-encoded private key
+    ciphertext: String,
+}
+
+/// Derive the AES key and the passphrase check from `passphrase`.
+/// Fails without deriving anything if the scrypt parameters are beyond the `MAX_SCRYPT_*`
+/// bounds, so that a key file cannot make the signer spend unbounded memory or time.
+fn derive_keys(
+    passphrase: &str,
+    salt: &[u8],
+    log_n: u8,
+    r: u32,
+    p: u32,
+) -> Result<(Zeroizing<[u8; 32]>, Sha256Sum), KeyFileError> {
+    if log_n > MAX_SCRYPT_LOG_N || r > MAX_SCRYPT_R || p > MAX_SCRYPT_P {
+        return Err(KeyFileError::Corrupt(format!(
+            "scrypt parameters log_n={log_n}, r={r}, p={p} exceed the maximum of log_n={MAX_SCRYPT_LOG_N}, r={MAX_SCRYPT_R}, p={MAX_SCRYPT_P}"
+        )));
+    }
+    let params = scrypt::Params::new(log_n, r, p, DERIVED_KEY_LEN)
+        .map_err(|e| KeyFileError::Corrupt(format!("invalid scrypt parameters: {e}")))?;
+    let mut derived = Zeroizing::new([0u8; DERIVED_KEY_LEN]);
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, derived.as_mut_slice())
+        .map_err(|e| KeyFileError::Corrupt(format!("scrypt failed: {e}")))?;
+    let mut aes_key = Zeroizing::new([0u8; 32]);
+    aes_key.copy_from_slice(&derived[..32]);
+    Ok((aes_key, Sha256Sum::from_data(&derived[32..])))
+}
+
+/// Encrypt `private_key` under `passphrase`, returning the contents of the key file
+pub fn encrypt_private_key(
+    private_key: &StacksPrivateKey,
+    passphrase: &str,
+) -> Result<String, KeyFileError> {
+    encrypt_private_key_with_cost(private_key, passphrase, SCRYPT_LOG_N)
+}
+
+/// Encrypt `private_key` with a custom scrypt cost (log2 of N)
+pub(crate) fn encrypt_private_key_with_cost(
+    private_key: &StacksPrivateKey,
+    passphrase: &str,
+    scrypt_log_n: u8,
+) -> Result<String, KeyFileError> {
+    let mut rng = rand::thread_rng();
+    let mut salt = [0u8; 32];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; 12];
+    rng.fill_bytes(&mut nonce);
+
+    let (aes_key, passphrase_check) =
+        derive_keys(passphrase, &salt, scrypt_log_n, SCRYPT_R, SCRYPT_P)?;
+    let cipher = Aes256Gcm::new_from_slice(aes_key.as_slice())
+        .expect("FATAL: AES-256 key is always 32 bytes");
+    let private_key_hex = Zeroizing::new(private_key.to_hex());
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), private_key_hex.as_bytes())
+        .map_err(|e| KeyFileError::Corrupt(format!("encryption failed: {e}")))?;
+
+    let key_file = EncryptedKeyFile {
+        version: KEY_FILE_VERSION,
+        scrypt_log_n,
+        scrypt_r: SCRYPT_R,
+        scrypt_p: SCRYPT_P,
+        salt: to_hex(&salt),
+        nonce: to_hex(&nonce),
+        passphrase_check: to_hex(passphrase_check.as_bytes()),
+        ciphertext: to_hex(&ciphertext),
+    };
+    serde_json::to_string_pretty(&key_file)
+        .map_err(|e| KeyFileError::Corrupt(format!("failed to serialize key file: {e}")))
+}
+
+/// Decrypt the contents of a key file with `passphrase`
+pub fn decrypt_private_key(
+    contents: &str,
+    passphrase: &str,
+) -> Result<StacksPrivateKey, KeyFileError> {
+    let key_file: EncryptedKeyFile =
+        serde_json::from_str(contents).map_err(|e| KeyFileError::Corrupt(e.to_string()))?;
+    if key_file.version != KEY_FILE_VERSION {
+        return Err(KeyFileError::Corrupt(format!(
+            "unsupported key file version {}",
+            key_file.version
+        )));
+    }
+    let decode = |field: &str, value: &str| {
+        hex_bytes(value).map_err(|_| KeyFileError::Corrupt(format!("{field} is not valid hex")))
+    };
+    let salt = decode("salt", &key_file.salt)?;
+    let nonce = decode("nonce", &key_file.nonce)?;
+    let passphrase_check = decode("passphrase_check", &key_file.passphrase_check)?;
+    let ciphertext = decode("ciphertext", &key_file.ciphertext)?;
+    if nonce.len() != 12 {
+        return Err(KeyFileError::Corrupt("nonce must be 12 bytes".into()));
+    }
+
+    let (aes_key, expected_check) = derive_keys(
+        passphrase,
+        &salt,
+        key_file.scrypt_log_n,
+        key_file.scrypt_r,
+        key_file.scrypt_p,
+    )?;
+    if expected_check.as_bytes()[..] != passphrase_check[..] {
+        return Err(KeyFileError::WrongPassphrase);
+    }
+    let cipher = Aes256Gcm::new_from_slice(aes_key.as_slice())
+        .expect("FATAL: AES-256 key is always 32 bytes");
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map(Zeroizing::new)
+        .map_err(|_| KeyFileError::Corrupt("ciphertext failed authentication".into()))?;
+    let private_key_hex = std::str::from_utf8(&plaintext)
+        .map_err(|_| KeyFileError::Corrupt("decrypted key is not valid hex".into()))?;
+    StacksPrivateKey::from_hex(private_key_hex)
+        .map_err(|_| KeyFileError::Corrupt("decrypted key is not a valid private key".into()))
+}
+
+/// Read the key file passphrase from `PASSPHRASE_ENV_VAR`, or prompt for it on the terminal
+pub fn read_passphrase(prompt: &str) -> Result<Zeroizing<String>, KeyFileError> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(Zeroizing::new(passphrase));
+    }
+    rpassword::prompt_password(prompt)
+        .map(Zeroizing::new)
+        .map_err(|e| {
+            KeyFileError::Passphrase(format!("{e}. Set {PASSPHRASE_ENV_VAR} to run unattended"))
+        })
+}
+
+/// Read and decrypt the key file at `path`, obtaining the passphrase with `read_passphrase`
+pub fn load_key_file(path: &Path) -> Result<StacksPrivateKey, KeyFileError> {
+    let contents = fs::read_to_string(path)?;
+    let passphrase = read_passphrase(&format!("Passphrase for key file {}: ", path.display()))?;
+    decrypt_private_key(&contents, &passphrase)
+}
+
+/// Write a new key file to `path`, readable only by its owner. Fails if `path` exists.
+pub fn write_key_file(path: &Path, contents: &str) -> Result<(), KeyFileError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A cheap scrypt cost so the tests stay fast
+    const TEST_SCRYPT_LOG_N: u8 = 4;
+
+    #[test]
+    fn private_key_round_trips_through_encryption() {
+        let private_key = StacksPrivateKey::new();
+        let contents =
+            encrypt_private_key_with_cost(&private_key, "hunter2", TEST_SCRYPT_LOG_N).unwrap();
+        assert!(!contents.contains(&private_key.to_hex()));
+        assert_eq!(
+            decrypt_private_key(&contents, "hunter2").unwrap(),
+            private_key
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_is_distinguished_from_corruption() {
+        let private_key = StacksPrivateKey::new();
+        let contents =
+            encrypt_private_key_with_cost(&private_key, "hunter2", TEST_SCRYPT_LOG_N).unwrap();
+        assert!(matches!(
+            decrypt_private_key(&contents, "hunter3"),
+            Err(KeyFileError::WrongPassphrase)
+        ));
+
+        let mut key_file: EncryptedKeyFile = serde_json::from_str(&contents).unwrap();
+        let mut ciphertext = hex_bytes(&key_file.ciphertext).unwrap();
+        ciphertext[0] ^= 0xff;
+        key_file.ciphertext = to_hex(&ciphertext);
+        let tampered = serde_json::to_string(&key_file).unwrap();
+        assert!(matches!(
+            decrypt_private_key(&tampered, "hunter2"),
+            Err(KeyFileError::Corrupt(_))
+        ));
+
+        assert!(matches!(
+            decrypt_private_key("not a key file", "hunter2"),
+            Err(KeyFileError::Corrupt(_))
+        ));
+    }
+
+    #[test]
+    fn costly_scrypt_parameters_are_rejected() {
+        let private_key = StacksPrivateKey::new();
+        let contents =
+            encrypt_private_key_with_cost(&private_key, "hunter2", TEST_SCRYPT_LOG_N).unwrap();
+        for (log_n, r, p) in [
+            (MAX_SCRYPT_LOG_N + 1, SCRYPT_R, SCRYPT_P),
+            (TEST_SCRYPT_LOG_N, MAX_SCRYPT_R + 1, SCRYPT_P),
+            (TEST_SCRYPT_LOG_N, SCRYPT_R, MAX_SCRYPT_P + 1),
+            (u8::MAX, u32::MAX, u32::MAX),
+        ] {
+            let costly = EncryptedKeyFile {
+                scrypt_log_n: log_n,
+                scrypt_r: r,
+                scrypt_p: p,
+                ..serde_json::from_str(&contents).unwrap()
+            };
+            let costly = serde_json::to_string(&costly).unwrap();
+            assert!(matches!(
+                decrypt_private_key(&costly, "hunter2"),
+                Err(KeyFileError::Corrupt(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn key_files_are_never_overwritten() {
+        let path = std::env::temp_dir().join(format!(
+            "stacks-signer-key-file-test-{}.json",
+            rand::thread_rng().next_u64()
+        ));
+        write_key_file(&path, "first").unwrap();
+        assert!(matches!(
+            write_key_file(&path, "second"),
+            Err(KeyFileError::Io(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -20,12 +20,20 @@ Usage documentation can be found in the [README](https://github.com/Trust-Machin
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+/// A tamper-evident log of everything the signer publishes
+pub mod audit;
 /// The cli module for the signer binary
 pub mod cli;
 /// The signer client for communicating with stackerdb/stacks nodes
 pub mod client;
 /// The configuration module for the signer
 pub mod config;
+/// Comparison of the signer sets of two reward cycles
+pub mod cycle_diff;
+/// The health endpoint for the signer
+pub mod health;
+/// Encrypted storage for the signer's Stacks private key
+pub mod key_file;
 /// The monitoring server for the signer
 pub mod monitoring;
 /// The primary runloop for the signer
@@ -36,12 +44,13 @@ pub mod v0;
 pub mod v1;
 use std::fmt::{Debug, Display};
 use std::sync::mpsc::Sender;
+use std::time::Instant;
 
 use libsigner::{SignerEvent, SignerEventTrait};
 use wsts::state_machine::OperationResult;
 
-use crate::client::StacksClient;
-use crate::config::SignerConfig;
+use crate::client::{SignerSlotID, StacksClient};
+use crate::config::{GlobalConfig, SignerConfig};
 use crate::runloop::RunLoopCommand;
 
 /// A trait which provides a common `Signer` interface for `v1` and `v2`
@@ -50,8 +59,19 @@ pub trait Signer<T: SignerEventTrait>: Debug + Display {
     fn new(config: SignerConfig) -> Self;
     /// Update the `Signer` instance's next reward cycle data with the latest `SignerConfig`
     fn update_next_signer_data(&mut self, next_signer_config: &SignerConfig);
+    /// Apply the reloadable fields of a reloaded `GlobalConfig`, keeping all other state
+    fn reload_config(&mut self, config: &GlobalConfig);
+    /// Send StackerDB messages through the stacks node at `node_host`, which the runloop's
+    /// stacks client failed over to (or back from)
+    fn switch_node_host(&mut self, node_host: &str);
     /// Get the reward cycle of the signer
     fn reward_cycle(&self) -> u64;
+    /// Get the slot the signer writes to in its reward cycle's `.signers` contracts
+    fn signer_slot_id(&self) -> SignerSlotID;
+    /// Write the messages the signer held back whose delay has passed
+    fn send_delayed_messages(&mut self);
+    /// When the next message the signer held back is due to be written, if any
+    fn next_delayed_message_deadline(&self) -> Option<Instant>;
     /// Process an event
     fn process_event(
         &mut self,
@@ -27,22 +27,38 @@ extern crate serde_json;
 extern crate toml;
 
 use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
 
 use blockstack_lib::util_lib::signed_structured_data::pox4::make_pox_4_signer_key_signature;
 use clap::Parser;
 use clarity::vm::types::QualifiedContractIdentifier;
+use libsigner::v1::messages::MessageSlotID;
 use libsigner::{SignerSession, StackerDBSession};
 use libstackerdb::StackerDBChunkData;
-use slog::slog_debug;
-use stacks_common::debug;
-use stacks_common::util::hash::to_hex;
+use slog::{slog_debug, slog_error, slog_info, slog_warn};
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::deps_common::ctrlc as termination;
+use stacks_common::deps_common::ctrlc::SignalId;
+use stacks_common::types::chainstate::{StacksAddress, StacksPublicKey};
+use stacks_common::types::PublicKey;
+use stacks_common::util::hash::{to_hex, Hash160};
 use stacks_common::util::secp256k1::{MessageSignature, Secp256k1PublicKey};
+use stacks_common::{debug, error, info, warn};
+use stacks_signer::audit::{read_key, verify_log, AuditLogConfig};
 use stacks_signer::cli::{
-    Cli, Command, GenerateStackingSignatureArgs, GetChunkArgs, GetLatestChunkArgs, PutChunkArgs,
-    RunSignerArgs, StackerDBArgs,
+    AuditCommand, AuditVerifyArgs, Cli, Command, DiffCyclesArgs, EncryptKeyArgs,
+    GenerateStackingSignatureArgs, GetChunkArgs, GetLatestChunkArgs, InspectSlotsArgs,
+    PutChunkArgs, RunSignerArgs, SignerKey, StackerDBArgs, VerifyResponseArgs,
+    VoteAggregateKeyArgs,
 };
+use stacks_signer::client::{format_slot_table, SignerSlotID, StackerDB, StacksClient};
 use stacks_signer::config::GlobalConfig;
+use stacks_signer::cycle_diff::SignerSetDiff;
+use stacks_signer::key_file::{encrypt_private_key, read_passphrase, write_key_file};
 use stacks_signer::v1;
+use stacks_signer::v1::vote_tracker::{VoteStatus, VoteTracker, VOTE_POLL_INTERVAL};
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter};
 
@@ -103,12 +119,47 @@ fn handle_put_chunk(args: PutChunkArgs) {
 fn handle_run(args: RunSignerArgs) {
     debug!("Running signer...");
     let config = GlobalConfig::try_from(&args.config).unwrap();
-    let spawned_signer = v1::SpawnedSigner::from(config);
+    let spawned_signer = match v1::SpawnedSigner::try_from(config) {
+        Ok(spawned_signer) => spawned_signer,
+        Err(e) => {
+            eprintln!("Failed to start the signer: {e}");
+            std::process::exit(1);
+        }
+    };
+    set_signal_handler(args.config, spawned_signer.reload_send.clone());
     println!("Signer spawned successfully. Waiting for messages to process...");
     // Wait for the spawned signer to stop (will only occur if an error occurs)
     let _ = spawned_signer.join();
 }
 
+/// Ask the running signer to reload the config at `config_path` on SIGHUP. The signer
+/// reloads it on its next pass, so the handler itself does no I/O.
+/// Any other terminating signal stops the signer, as it would without a handler.
+fn set_signal_handler(config_path: PathBuf, reload_send: Sender<PathBuf>) {
+    let install = termination::set_handler(move |sig_id| match sig_id {
+        SignalId::Hangup => {
+            info!(
+                "Received SIGHUP. Requesting a reload of the signer config from {}",
+                config_path.display()
+            );
+            if reload_send.send(config_path.clone()).is_err() {
+                warn!("Signer is no longer running. Ignoring the reload request.");
+            }
+        }
+        SignalId::Bus => {
+            eprintln!("Caught SIGBUS; crashing immediately and dumping core");
+            std::process::abort();
+        }
+        _ => {
+            eprintln!("Termination request received (signal `{sig_id}`). Stopping the signer.");
+            std::process::exit(0);
+        }
+    });
+    if let Err(e) = install {
+        error!("Failed to set the signal handler. Config reloads on SIGHUP are disabled: {e}");
+    }
+}
+
 fn handle_generate_stacking_signature(
     args: GenerateStackingSignatureArgs,
     do_print: bool,
@@ -162,6 +213,248 @@ fn handle_check_config(args: RunSignerArgs) {
     println!("Config: {}", config);
 }
 
+fn handle_vote_aggregate_key(args: VoteAggregateKeyArgs) {
+    let config = GlobalConfig::try_from(&args.config).unwrap();
+    let stacks_client = StacksClient::from(&config);
+    let signer_index = stacks_client
+        .get_signer_index(args.reward_cycle)
+        .expect("Failed to get the reward set")
+        .unwrap_or_else(|| {
+            panic!(
+                "Signer {} is not in the reward set of reward cycle {}",
+                config.stacks_address, args.reward_cycle
+            )
+        });
+    let tx_fee_ustx = args.fee_ustx.unwrap_or(config.tx_fee_ustx);
+    if args.dry_run {
+        let tx = stacks_client
+            .build_vote_for_aggregate_public_key(
+                signer_index,
+                args.round,
+                args.aggregate_key,
+                args.reward_cycle,
+                tx_fee_ustx,
+            )
+            .expect("Failed to build the vote transaction");
+        println!("{}", to_hex(&tx.serialize_to_vec()));
+        return;
+    }
+    let mut vote = VoteTracker::cast(
+        &stacks_client,
+        signer_index,
+        args.round,
+        args.aggregate_key,
+        args.reward_cycle,
+        tx_fee_ustx,
+    )
+    .expect("Failed to submit the vote transaction");
+    println!("{}", vote.tx().txid());
+    let Some(wait) = args.wait else {
+        return;
+    };
+    let deadline = Instant::now() + Duration::from_secs(wait);
+    loop {
+        match vote.poll(&stacks_client).expect("Failed to check the vote") {
+            VoteStatus::Confirmed { .. } => break,
+            // the vote could no longer be counted as submitted, and was cast again
+            VoteStatus::Recast { txid, .. } => println!("{txid}"),
+            VoteStatus::Pending => {}
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            panic!("Vote transaction was not confirmed");
+        }
+        std::thread::sleep(VOTE_POLL_INTERVAL.min(deadline - now));
+    }
+    println!("Confirmed");
+}
+
+fn handle_inspect_slots(args: InspectSlotsArgs) {
+    let config = GlobalConfig::try_from(&args.config).unwrap();
+    let stacks_client = StacksClient::from(&config);
+    let signer_slots = stacks_client
+        .get_signer_slots(args.reward_cycle)
+        .expect("Failed to get the signer slots");
+    let Some(signer_slot_id) = signer_slots
+        .iter()
+        .position(|address| address == &config.stacks_address)
+    else {
+        panic!(
+            "Signer {} has no slots in reward cycle {}",
+            config.stacks_address, args.reward_cycle
+        );
+    };
+    let signer_slot_id =
+        u32::try_from(signer_slot_id).expect("FATAL: number of signers exceeds u32::MAX");
+    let mut stackerdb = StackerDB::new(
+        &config.node_host,
+        config.stacks_private_key,
+        config.network.is_mainnet(),
+        args.reward_cycle,
+        SignerSlotID(signer_slot_id),
+    );
+    let mut slots = vec![];
+    for msg_id in MessageSlotID::ALL {
+        slots.extend(
+            stackerdb
+                .inspect_slots(*msg_id, &[signer_slot_id])
+                .expect("Failed to read the signer's slots"),
+        );
+    }
+    if args.all_block_responses {
+        let other_slot_ids: Vec<u32> = (0..signer_slots.len())
+            .filter_map(|slot_id| u32::try_from(slot_id).ok())
+            .filter(|slot_id| *slot_id != signer_slot_id)
+            .collect();
+        slots.extend(
+            stackerdb
+                .inspect_slots(MessageSlotID::BlockResponse, &other_slot_ids)
+                .expect("Failed to read the block response slots"),
+        );
+    }
+    println!("{}", format_slot_table(&slots));
+}
+
+fn handle_encrypt_key(args: EncryptKeyArgs) {
+    let config = GlobalConfig::try_from(&args.config).unwrap();
+    let passphrase = read_passphrase("Key file passphrase: ").unwrap();
+    if passphrase.is_empty() {
+        panic!("The key file passphrase must not be empty");
+    }
+    let confirmation = read_passphrase("Confirm key file passphrase: ").unwrap();
+    if passphrase != confirmation {
+        panic!("Passphrases do not match");
+    }
+    let contents = encrypt_private_key(&config.stacks_private_key, &passphrase)
+        .expect("Failed to encrypt the private key");
+    write_key_file(&args.output, &contents).expect("Failed to write the key file");
+    println!(
+        "Wrote the encrypted key of signer {} to {}.",
+        config.stacks_address,
+        args.output.display()
+    );
+    println!(
+        "Replace stacks_private_key in {} with:\nstacks_private_key_file = \"{}\"",
+        args.config.display(),
+        args.output.display()
+    );
+}
+
+fn handle_audit_verify(args: AuditVerifyArgs) {
+    let key_path = args
+        .key
+        .unwrap_or_else(|| AuditLogConfig::default_key_path(&args.log));
+    let anchor_path = args
+        .anchor
+        .unwrap_or_else(|| AuditLogConfig::default_anchor_path(&args.log));
+    let key = read_key(&key_path).expect("Failed to read the audit log key");
+    match verify_log(&args.log, &key, &anchor_path) {
+        Ok(count) => println!("Verified {count} records of {}", args.log.display()),
+        Err(e) => {
+            eprintln!("Audit log verification failed: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// The outcome of checking a signer's signature over a signer signature hash
+#[derive(Debug, PartialEq)]
+enum ResponseVerification {
+    /// The signature was made by the given key
+    Valid,
+    /// The signature was made by another key
+    Invalid { signed_by: StacksPublicKey },
+    /// No key was given, and the signature was made by this one
+    Recovered(StacksPublicKey),
+}
+
+/// Check the signature over the signer signature hash with the same `StacksPublicKey::verify`
+/// the miner uses, so that high-S signatures are rejected too
+fn verify_response(args: &VerifyResponseArgs) -> Result<ResponseVerification, String> {
+    let message = args.sighash.as_bytes();
+    let signed_by = StacksPublicKey::recover_to_pubkey(message, &args.signature)?;
+    let expected_key = match args.pubkey.as_ref() {
+        Some(SignerKey::PublicKey(pubkey)) => pubkey.clone(),
+        Some(SignerKey::Address(address)) => {
+            if Hash160::from_node_public_key(&signed_by) != address.bytes {
+                return Ok(ResponseVerification::Invalid { signed_by });
+            }
+            signed_by.clone()
+        }
+        None => {
+            signed_by.verify(message, &args.signature)?;
+            return Ok(ResponseVerification::Recovered(signed_by));
+        }
+    };
+    if expected_key.verify(message, &args.signature)? {
+        Ok(ResponseVerification::Valid)
+    } else {
+        Ok(ResponseVerification::Invalid { signed_by })
+    }
+}
+
+fn handle_verify_response(args: VerifyResponseArgs) {
+    let describe_key = |key: &StacksPublicKey| {
+        format!(
+            "{} ({} / {})",
+            key.to_hex(),
+            StacksAddress::p2pkh(true, key),
+            StacksAddress::p2pkh(false, key)
+        )
+    };
+    match verify_response(&args) {
+        Ok(ResponseVerification::Valid) => {
+            println!("The signature over {} is valid", args.sighash);
+        }
+        Ok(ResponseVerification::Recovered(signed_by)) => {
+            println!(
+                "The signature over {} was made by {}",
+                args.sighash,
+                describe_key(&signed_by)
+            );
+        }
+        Ok(ResponseVerification::Invalid { signed_by }) => {
+            eprintln!(
+                "The signature over {} is NOT valid for the given key. It was made by {}",
+                args.sighash,
+                describe_key(&signed_by)
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("The signature over {} is NOT valid: {e}", args.sighash);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn handle_diff_cycles(args: DiffCyclesArgs) {
+    let config = GlobalConfig::try_from(&args.config).unwrap();
+    let stacks_client = StacksClient::from(&config);
+    let signers_of = |reward_cycle: u64| {
+        stacks_client
+            .get_reward_set_signers(reward_cycle)
+            .expect("Failed to get the reward set")
+            .unwrap_or_else(|| panic!("Reward cycle {reward_cycle} has no signer set"))
+    };
+    let diff = SignerSetDiff::new(
+        config.network.is_mainnet(),
+        args.from,
+        &signers_of(args.from),
+        args.to,
+        &signers_of(args.to),
+    )
+    .expect("Failed to compare the signer sets");
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string(&diff).expect("Failed to serialize JSON")
+        );
+    } else {
+        println!("{diff}");
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
@@ -192,6 +485,24 @@ fn main() {
         Command::CheckConfig(args) => {
             handle_check_config(args);
         }
+        Command::VoteAggregateKey(args) => {
+            handle_vote_aggregate_key(args);
+        }
+        Command::InspectSlots(args) => {
+            handle_inspect_slots(args);
+        }
+        Command::EncryptKey(args) => {
+            handle_encrypt_key(args);
+        }
+        Command::Audit(AuditCommand::Verify(args)) => {
+            handle_audit_verify(args);
+        }
+        Command::VerifyResponse(args) => {
+            handle_verify_response(args);
+        }
+        Command::DiffCycles(args) => {
+            handle_diff_cycles(args);
+        }
     }
 }
 
@@ -204,7 +515,9 @@ pub mod tests {
     };
     use clarity::vm::{execute_v2, Value};
     use stacks_common::consts::CHAIN_ID_TESTNET;
-    use stacks_common::types::PublicKey;
+    use stacks_common::types::chainstate::StacksPrivateKey;
+    use stacks_common::types::{PrivateKey, PublicKey};
+    use stacks_common::util::hash::Sha512Trunc256Sum;
     use stacks_common::util::secp256k1::Secp256k1PublicKey;
     use stacks_signer::cli::parse_pox_addr;
 
@@ -336,4 +649,74 @@ pub mod tests {
         assert!(verify_result.is_ok());
         assert!(verify_result.unwrap());
     }
+
+    #[test]
+    fn test_verify_response() {
+        let signer_sk = StacksPrivateKey::new();
+        let signer_pk = StacksPublicKey::from_private(&signer_sk);
+        let other_pk = StacksPublicKey::from_private(&StacksPrivateKey::new());
+        let sighash = Sha512Trunc256Sum([0x05; 32]);
+        let signature = signer_sk.sign(sighash.as_bytes()).unwrap();
+        let verify = |sighash, signature, pubkey| {
+            verify_response(&VerifyResponseArgs {
+                sighash,
+                signature,
+                pubkey,
+            })
+        };
+
+        for key in [
+            SignerKey::PublicKey(signer_pk.clone()),
+            SignerKey::Address(StacksAddress::p2pkh(false, &signer_pk)),
+            SignerKey::Address(StacksAddress::p2pkh(true, &signer_pk)),
+        ] {
+            assert_eq!(
+                verify(sighash, signature, Some(key)),
+                Ok(ResponseVerification::Valid)
+            );
+        }
+        assert_eq!(
+            verify(sighash, signature, None),
+            Ok(ResponseVerification::Recovered(signer_pk.clone()))
+        );
+
+        // another signer's key
+        for key in [
+            SignerKey::PublicKey(other_pk.clone()),
+            SignerKey::Address(StacksAddress::p2pkh(false, &other_pk)),
+        ] {
+            assert_eq!(
+                verify(sighash, signature, Some(key)),
+                Ok(ResponseVerification::Invalid {
+                    signed_by: signer_pk.clone()
+                })
+            );
+        }
+
+        // a signature over another block
+        let other_sighash = Sha512Trunc256Sum([0x06; 32]);
+        let signer_key = || Some(SignerKey::PublicKey(signer_pk.clone()));
+        assert!(matches!(
+            verify(other_sighash, signature, signer_key()),
+            Ok(ResponseVerification::Invalid { .. })
+        ));
+
+        // corrupted signatures
+        let mut bad_recovery_id = signature;
+        bad_recovery_id.0[0] = 4;
+        assert!(verify(sighash, bad_recovery_id, signer_key()).is_err());
+        assert!(verify(sighash, bad_recovery_id, None).is_err());
+        for byte in [1, 33, 64] {
+            let mut corrupted = signature;
+            corrupted.0[byte] ^= 0x01;
+            assert_ne!(
+                verify(sighash, corrupted, signer_key()),
+                Ok(ResponseVerification::Valid)
+            );
+            assert_ne!(
+                verify(sighash, corrupted, None),
+                Ok(ResponseVerification::Recovered(signer_pk.clone()))
+            );
+        }
+    }
 }
@@ -124,6 +124,41 @@ pub fn increment_block_proposals_received() {
     prometheus::BLOCK_PROPOSALS_RECEIVED.inc();
 }
 
+/// Increment the number of events ignored because they were for another signer set
+#[allow(unused_variables)]
+pub fn increment_signer_set_mismatches() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::SIGNER_SET_MISMATCHES.inc();
+}
+
+/// Increment the number of block proposals dropped because a newer one superseded them
+#[allow(unused_variables)]
+pub fn increment_superseded_proposals(count: u64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::SUPERSEDED_PROPOSALS.inc_by(count);
+}
+
+/// Increment the number of block responses re-published for repeated proposals of a signed block
+#[allow(unused_variables)]
+pub fn increment_replayed_block_responses() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::REPLAYED_BLOCK_RESPONSES.inc();
+}
+
+/// Increment the number of times the signer failed over to another stacks node
+#[allow(unused_variables)]
+pub fn increment_node_failovers() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NODE_FAILOVERS.inc();
+}
+
+/// Increment the number of records the signer failed to write to its audit log
+#[allow(unused_variables)]
+pub fn increment_audit_log_write_errors() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::AUDIT_LOG_WRITE_ERRORS.inc();
+}
+
 /// Update the stx balance of the signer
 #[allow(unused_variables)]
 pub fn update_signer_stx_balance(balance: i64) {
@@ -186,3 +221,12 @@ pub fn start_serving_monitoring_metrics(config: GlobalConfig) -> Result<(), Stri
     }
     Ok(())
 }
+
+/// Stop the running metrics server, if any, and start serving metrics with `config`,
+/// e.g. after a config reload changed the metrics endpoint or the stacks node.
+/// This will only serve the metrics if the `monitoring_prom` feature is enabled.
+pub fn restart_serving_monitoring_metrics(config: GlobalConfig) -> Result<(), String> {
+    #[cfg(feature = "monitoring_prom")]
+    server::MonitoringServer::stop_running();
+    start_serving_monitoring_metrics(config)
+}
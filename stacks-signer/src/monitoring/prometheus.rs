@@ -76,6 +76,31 @@ lazy_static! {
         "The number of block proposals received by the signer"
     ))
     .unwrap();
+    pub static ref SIGNER_SET_MISMATCHES: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_signer_set_mismatches",
+        "The number of events ignored by the signer because they were for another signer set"
+    ))
+    .unwrap();
+    pub static ref SUPERSEDED_PROPOSALS: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_superseded_proposals",
+        "The number of block proposals dropped by the signer because a newer one for the same block height superseded them"
+    ))
+    .unwrap();
+    pub static ref REPLAYED_BLOCK_RESPONSES: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_replayed_block_responses",
+        "The number of block responses re-published by the signer for repeated proposals of a block it already signed"
+    ))
+    .unwrap();
+    pub static ref NODE_FAILOVERS: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_node_failovers",
+        "The number of times the signer failed over to another stacks node because the one it was using was unreachable"
+    ))
+    .unwrap();
+    pub static ref AUDIT_LOG_WRITE_ERRORS: IntCounter = register_int_counter!(opts!(
+        "stacks_signer_audit_log_write_errors",
+        "The number of records the signer failed to write to its audit log"
+    ))
+    .unwrap();
     pub static ref CURRENT_REWARD_CYCLE: IntGauge = register_int_gauge!(opts!(
         "stacks_signer_current_reward_cycle",
         "The current reward cycle"
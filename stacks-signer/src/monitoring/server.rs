@@ -15,10 +15,12 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::net::SocketAddr;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use clarity::util::hash::to_hex;
 use clarity::util::secp256k1::Secp256k1PublicKey;
+use lazy_static::lazy_static;
 use slog::{slog_debug, slog_error, slog_info, slog_warn};
 use stacks_common::{debug, error, info, warn};
 use tiny_http::{Response as HttpResponse, Server as HttpServer};
@@ -48,7 +50,7 @@ pub enum MonitoringError {
 
 /// Metrics and monitoring server
 pub struct MonitoringServer {
-    http_server: HttpServer,
+    http_server: Arc<HttpServer>,
     local_addr: SocketAddr,
     stacks_client: StacksClient,
     last_metrics_poll: Instant,
@@ -60,7 +62,7 @@ pub struct MonitoringServer {
 
 impl MonitoringServer {
     pub fn new(
-        http_server: HttpServer,
+        http_server: Arc<HttpServer>,
         local_addr: SocketAddr,
         stacks_client: StacksClient,
         network: Network,
@@ -85,7 +87,10 @@ impl MonitoringServer {
             return Err(MonitoringError::EndpointNotConfigured);
         };
         let stacks_client = StacksClient::from(config);
-        let http_server = HttpServer::http(endpoint).map_err(|_| MonitoringError::AlreadyBound)?;
+        let http_server = Arc::new(Self::bind(endpoint)?);
+        if let Ok(mut running_listener) = RUNNING_LISTENER.lock() {
+            *running_listener = Some(http_server.clone());
+        }
         let public_key = Secp256k1PublicKey::from_private(&config.stacks_private_key);
         let mut server = MonitoringServer::new(
             http_server,
@@ -99,6 +104,31 @@ impl MonitoringServer {
         server.main_loop()
     }
 
+    /// Bind the metrics listener to `endpoint`
+    fn bind(endpoint: SocketAddr) -> Result<HttpServer, MonitoringError> {
+        let mut attempts = 0;
+        loop {
+            match HttpServer::http(endpoint) {
+                Ok(http_server) => return Ok(http_server),
+                Err(_) if attempts + 1 < BIND_ATTEMPTS => {
+                    attempts += 1;
+                    std::thread::sleep(BIND_RETRY_INTERVAL);
+                }
+                Err(_) => return Err(MonitoringError::AlreadyBound),
+            }
+        }
+    }
+
+    /// Stop the running metrics server, if any. Its main loop returns once it is unblocked.
+    pub fn stop_running() {
+        let Ok(mut running_listener) = RUNNING_LISTENER.lock() else {
+            return;
+        };
+        if let Some(http_server) = running_listener.take() {
+            http_server.unblock();
+        }
+    }
+
     // /// Start and run the metrics server
     // pub fn run(endpoint: SocketAddr, stacks_client: StacksClient) -> Result<(), MonitoringError> {
     //     let http_server = HttpServer::http(endpoint).map_err(|_| MonitoringError::AlreadyBound)?;
@@ -15,25 +15,33 @@ use std::fmt::Debug;
 //
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
-use std::sync::mpsc::Sender;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::{Duration, Instant};
 
-use blockstack_lib::burnchains::PoxConstants;
-use blockstack_lib::chainstate::stacks::boot::SIGNERS_NAME;
-use blockstack_lib::util_lib::boot::boot_code_id;
+use blockstack_lib::chainstate::stacks::boot::signing_threshold_for;
 use clarity::codec::StacksMessageCodec;
 use hashbrown::HashMap;
 use libsigner::{BlockProposal, SignerEntries, SignerEvent, SignerRunLoop};
 use slog::{slog_debug, slog_error, slog_info, slog_warn};
 use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::types::reward_cycle::RewardCycleInfo as RewardCycleSchedule;
 use stacks_common::{debug, error, info, warn};
 use wsts::common::MerkleRoot;
 use wsts::state_machine::OperationResult;
 
+use crate::audit::{AuditLog, AuditLogError};
 use crate::client::{retry_with_exponential_backoff, ClientError, SignerSlotID, StacksClient};
-use crate::config::{GlobalConfig, SignerConfig};
+use crate::config::{ConfigError, GlobalConfig, SignerConfig};
+use crate::monitoring::restart_serving_monitoring_metrics;
 use crate::Signer as SignerTrait;
 
+/// How often the runloop probes the stacks node when the health endpoint is enabled
+const NODE_PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the runloop measures the clock skew from the stacks node
+const CLOCK_SKEW_CHECK_INTERVAL: Duration = Duration::from_secs(600);
+
 /// Which signer operation to perform
 #[derive(PartialEq, Clone, Debug)]
 pub enum SignerCommand {
@@ -86,37 +94,48 @@ pub struct RewardCycleInfo {
 }
 
 impl RewardCycleInfo {
+    /// The burnchain's reward cycle schedule. Any other reward cycle can be computed from it
+    /// locally, without asking the stacks node.
+    pub const fn schedule(&self) -> RewardCycleSchedule {
+        RewardCycleSchedule::new(
+            self.first_burnchain_block_height,
+            self.reward_cycle_length,
+            self.prepare_phase_block_length,
+        )
+    }
+
     /// Check if the provided burnchain block height is part of the reward cycle
     pub const fn is_in_reward_cycle(&self, burnchain_block_height: u64) -> bool {
-        let blocks_mined = burnchain_block_height.saturating_sub(self.first_burnchain_block_height);
-        let reward_cycle = blocks_mined / self.reward_cycle_length;
-        self.reward_cycle == reward_cycle
+        self.reward_cycle == self.get_reward_cycle(burnchain_block_height)
     }
 
     /// Get the reward cycle for a specific burnchain block height
     pub const fn get_reward_cycle(&self, burnchain_block_height: u64) -> u64 {
-        let blocks_mined = burnchain_block_height.saturating_sub(self.first_burnchain_block_height);
-        blocks_mined / self.reward_cycle_length
+        match self.schedule().cycle_of(burnchain_block_height) {
+            Some(reward_cycle) => reward_cycle,
+            None => 0,
+        }
     }
 
     /// Check if the provided burnchain block height is in the prepare phase
-    pub fn is_in_prepare_phase(&self, burnchain_block_height: u64) -> bool {
-        PoxConstants::static_is_in_prepare_phase(
-            self.first_burnchain_block_height,
-            self.reward_cycle_length,
-            self.prepare_phase_block_length,
-            burnchain_block_height,
-        )
+    pub const fn is_in_prepare_phase(&self, burnchain_block_height: u64) -> bool {
+        self.schedule().is_in_prepare_phase(burnchain_block_height)
     }
 
     /// Check if the provided burnchain block height is in the prepare phase of the next cycle
-    pub fn is_in_next_prepare_phase(&self, burnchain_block_height: u64) -> bool {
-        let effective_height = burnchain_block_height - self.first_burnchain_block_height;
-        let reward_index = effective_height % self.reward_cycle_length;
-
+    pub const fn is_in_next_prepare_phase(&self, burnchain_block_height: u64) -> bool {
+        let Some(reward_index) = self.schedule().cycle_index(burnchain_block_height) else {
+            return false;
+        };
         reward_index >= (self.reward_cycle_length - self.prepare_phase_block_length)
             && self.get_reward_cycle(burnchain_block_height) == self.reward_cycle
     }
+
+    /// Move on to the reward cycle of the provided burnchain block height
+    pub fn advance_to(&mut self, burnchain_block_height: u64) {
+        self.reward_cycle = self.get_reward_cycle(burnchain_block_height);
+        self.last_burnchain_block_height = burnchain_block_height;
+    }
 }
 
 /// The runloop for the stacks signer
@@ -138,23 +157,40 @@ where
     pub commands: VecDeque<RunLoopCommand>,
     /// The current reward cycle info. Only None if the runloop is uninitialized
     pub current_reward_cycle_info: Option<RewardCycleInfo>,
+    /// The last time the stacks node was probed for the health endpoint
+    last_node_probe: Option<Instant>,
+    /// The last time the clock skew from the stacks node was measured
+    last_clock_skew_check: Option<Instant>,
+    /// The stacks node host the signers send their StackerDB messages through
+    signers_node_host: String,
+    /// Requests to reload the config from a file, e.g. sent on SIGHUP
+    pub config_reloads: Option<Receiver<PathBuf>>,
     /// Phantom data for the message codec
     _phantom_data: std::marker::PhantomData<T>,
 }
 
 impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLoop<Signer, T> {
-    /// Create a new signer runloop from the provided configuration
-    pub fn new(config: GlobalConfig) -> Self {
-        let stacks_client = StacksClient::from(&config);
-        Self {
+    /// Create a new signer runloop from the provided configuration. Fails if the audit log
+    /// is enabled and cannot be opened.
+    pub fn new(config: GlobalConfig) -> Result<Self, AuditLogError> {
+        let mut stacks_client = StacksClient::from(&config);
+        if let Some(audit_log_config) = config.audit_log.as_ref() {
+            stacks_client.set_audit_log(AuditLog::open_shared(audit_log_config)?);
+        }
+        let signers_node_host = config.node_host.clone();
+        Ok(Self {
             config,
             stacks_client,
             stacks_signers: HashMap::with_capacity(2),
             state: State::Uninitialized,
             commands: VecDeque::new(),
             current_reward_cycle_info: None,
+            last_node_probe: None,
+            last_clock_skew_check: None,
+            signers_node_host,
+            config_reloads: None,
             _phantom_data: std::marker::PhantomData,
-        }
+        })
     }
     /// Get the registered signers for a specific reward cycle
     /// Returns None if no signers are registered or its not Nakamoto cycle
@@ -163,7 +199,8 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
         reward_cycle: u64,
     ) -> Result<Option<SignerEntries>, ClientError> {
         debug!("Getting registered signers for reward cycle {reward_cycle}...");
-        let Some(signers) = self.stacks_client.get_reward_set_signers(reward_cycle)? else {
+        let reward_set = self.stacks_client.get_reward_set(reward_cycle)?;
+        let Some(signers) = reward_set.signers.as_ref() else {
             warn!("No reward set signers found for reward cycle {reward_cycle}.");
             return Ok(None);
         };
@@ -171,7 +208,29 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
             warn!("No registered signers found for reward cycle {reward_cycle}.");
             return Ok(None);
         }
-        let entries = SignerEntries::parse(self.config.network.is_mainnet(), &signers).unwrap();
+        // the miners compute the threshold from the reward set itself, and a signer that
+        // disagrees with them would never know when a block is signed. This also checks that
+        // the weights fit in a u32 before they are parsed into key IDs.
+        let (threshold, total_weight) = signing_threshold_for(&reward_set)
+            .map_err(|e| ClientError::MalformedContractData(e.to_string()))?;
+        let entries = SignerEntries::parse(self.config.network.is_mainnet(), signers).unwrap();
+        let num_keys = entries
+            .count_keys()
+            .map_err(|e| ClientError::MalformedContractData(format!("{e:?}")))?;
+        let signing_threshold = SignerEntries::signing_threshold(num_keys);
+        if (signing_threshold, num_keys) != (threshold, total_weight) {
+            error!(
+                "Parsed reward set disagrees with the miners' signing threshold";
+                "reward_cycle" => reward_cycle,
+                "threshold" => threshold,
+                "total_weight" => total_weight,
+                "signing_threshold" => signing_threshold,
+                "num_keys" => num_keys,
+            );
+            return Err(ClientError::MalformedContractData(format!(
+                "Signing threshold of {signing_threshold} over {num_keys} keys does not match {threshold} over {total_weight}"
+            )));
+        }
         Ok(Some(entries))
     }
 
@@ -181,15 +240,9 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
         stacks_client: &StacksClient,
         reward_cycle: u64,
     ) -> Result<HashMap<StacksAddress, SignerSlotID>, ClientError> {
-        let signer_set =
-            u32::try_from(reward_cycle % 2).expect("FATAL: reward_cycle % 2 exceeds u32::MAX");
-        let signer_stackerdb_contract_id =
-            boot_code_id(SIGNERS_NAME, self.config.network.is_mainnet());
-        // Get the signer writers from the stacker-db to find the signer slot id
-        let stackerdb_signer_slots =
-            stacks_client.get_stackerdb_signer_slots(&signer_stackerdb_contract_id, signer_set)?;
-        let mut signer_slot_ids = HashMap::with_capacity(stackerdb_signer_slots.len());
-        for (index, (address, _)) in stackerdb_signer_slots.into_iter().enumerate() {
+        let signer_slots = stacks_client.get_signer_slots(reward_cycle)?;
+        let mut signer_slot_ids = HashMap::with_capacity(signer_slots.len());
+        for (index, address) in signer_slots.into_iter().enumerate() {
             signer_slot_ids.insert(
                 address,
                 SignerSlotID(
@@ -206,15 +259,32 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
         let signer_slot_ids = self
             .get_parsed_signer_slots(&self.stacks_client, reward_cycle)
             .ok()?;
-        let current_addr = self.stacks_client.get_signer_address();
+        let current_addr = self.config.stacks_address_for_cycle(reward_cycle);
 
-        let Some(signer_slot_id) = signer_slot_ids.get(current_addr) else {
-            warn!(
+        let Some(signer_slot_id) = signer_slot_ids.get(&current_addr) else {
+            let registered_addr = [self.config.stacks_address]
+                .into_iter()
+                .chain(
+                    self.config
+                        .next_stacks_key
+                        .as_ref()
+                        .map(|k| k.stacks_address),
+                )
+                .find(|addr| signer_slot_ids.contains_key(addr));
+            match registered_addr {
+                Some(registered_addr) => warn!(
+                    "Signer {registered_addr} is registered for reward cycle {reward_cycle}, but the configured key rotation signs with {current_addr} in that cycle. Check next_key_activation_reward_cycle."
+                ),
+                None if self.config.next_stacks_key.is_some() => warn!(
+                    "Neither configured signing key is registered for reward cycle {reward_cycle}. The registered signing key does not match stacks_private_key or next_stacks_private_key."
+                ),
+                None => warn!(
                     "Signer {current_addr} was not found in stacker db. Must not be registered for this reward cycle {reward_cycle}."
-                );
+                ),
+            }
             return None;
         };
-        let Some(signer_id) = signer_entries.signer_ids.get(current_addr) else {
+        let Some(signer_id) = signer_entries.signer_ids.get(&current_addr) else {
             warn!(
                 "Signer {current_addr} was found in stacker db but not the reward set for reward cycle {reward_cycle}."
             );
@@ -235,9 +305,9 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
             key_ids,
             signer_entries,
             signer_slot_ids: signer_slot_ids.into_values().collect(),
-            ecdsa_private_key: self.config.ecdsa_private_key,
-            stacks_private_key: self.config.stacks_private_key,
-            node_host: self.config.node_host.to_string(),
+            ecdsa_private_key: self.config.ecdsa_private_key_for_cycle(reward_cycle),
+            stacks_private_key: self.config.stacks_private_key_for_cycle(reward_cycle),
+            node_host: self.signers_node_host.clone(),
             mainnet: self.config.network.is_mainnet(),
             dkg_end_timeout: self.config.dkg_end_timeout,
             dkg_private_timeout: self.config.dkg_private_timeout,
@@ -247,6 +317,13 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
             tx_fee_ustx: self.config.tx_fee_ustx,
             max_tx_fee_ustx: self.config.max_tx_fee_ustx,
             db_path: self.config.db_path.clone(),
+            health: self.stacks_client.health_state(),
+            payload_envelope: self.config.payload_envelope,
+            max_node_staleness: self.config.max_node_staleness,
+            audit_log: self.stacks_client.audit_log(),
+            block_response_delay_step: self.config.block_response_delay_step,
+            block_response_max_delay: self.config.block_response_max_delay,
+            dry_run: self.config.dry_run,
         })
     }
 
@@ -298,35 +375,76 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
         Ok(())
     }
 
+    /// Roll the signer over to `reward_cycle`, which just started. The signer of the new cycle
+    /// was registered in its prepare phase, but its slot in the new cycle's `.signers`
+    /// contracts is resolved again now that the cycle is active. A signer whose slot moved, or
+    /// that was never registered, is initialized afresh, so it writes to the new slot starting
+    /// from fresh slot versions. A signer that is no longer in the signer set is dropped.
+    fn handle_reward_cycle_transition(
+        &mut self,
+        prior_reward_cycle: u64,
+        reward_cycle: u64,
+    ) -> Result<(), ClientError> {
+        let reward_index = reward_cycle % 2;
+        let signer_slot_ids = self.get_parsed_signer_slots(&self.stacks_client, reward_cycle)?;
+        let current_addr = self.config.stacks_address_for_cycle(reward_cycle);
+        let resolved_slot_id = signer_slot_ids.get(&current_addr).copied();
+        let registered_slot_id = self
+            .stacks_signers
+            .get(&reward_index)
+            .filter(|signer| signer.reward_cycle() == reward_cycle)
+            .map(|signer| signer.signer_slot_id());
+        match resolved_slot_id {
+            Some(signer_slot_id) if registered_slot_id == Some(signer_slot_id) => {
+                info!(
+                    "Signer is in the signer set of the new reward cycle";
+                    "prior_reward_cycle" => prior_reward_cycle,
+                    "reward_cycle" => reward_cycle,
+                    "signer_set" => reward_index,
+                    "signer_slot_id" => %signer_slot_id,
+                );
+            }
+            Some(signer_slot_id) => {
+                info!(
+                    "Signer is in the signer set of the new reward cycle, but is not registered for its slot. Initializing the signer.";
+                    "prior_reward_cycle" => prior_reward_cycle,
+                    "reward_cycle" => reward_cycle,
+                    "signer_set" => reward_index,
+                    "signer_slot_id" => %signer_slot_id,
+                    "registered_slot_id" => ?registered_slot_id,
+                );
+                self.stacks_signers.remove(&reward_index);
+                self.refresh_signer_config(reward_cycle);
+            }
+            None => {
+                info!(
+                    "Signer is not in the signer set of the new reward cycle";
+                    "prior_reward_cycle" => prior_reward_cycle,
+                    "reward_cycle" => reward_cycle,
+                    "signer_set" => reward_index,
+                    "address" => %current_addr,
+                );
+                self.stacks_signers.remove(&reward_index);
+            }
+        }
+        Ok(())
+    }
+
     fn refresh_runloop(&mut self, current_burn_block_height: u64) -> Result<(), ClientError> {
-        let reward_cycle_info = self
+        let mut reward_cycle_info = self
             .current_reward_cycle_info
-            .as_mut()
             .expect("FATAL: cannot be an initialized signer with no reward cycle info.");
         let current_reward_cycle = reward_cycle_info.reward_cycle;
         let block_reward_cycle = reward_cycle_info.get_reward_cycle(current_burn_block_height);
 
-        // First ensure we refresh our view of the current reward cycle information
+        // The reward cycle follows from the burn height and the PoX constants fetched at
+        // startup, so there is no need to ask the stacks node for it again. The cycle only
+        // advances once the signer has rolled over to it, so a failed rollover is retried at
+        // the next burn block.
         if block_reward_cycle != current_reward_cycle {
-            let new_reward_cycle_info = retry_with_exponential_backoff(|| {
-                let info = self
-                    .stacks_client
-                    .get_current_reward_cycle_info()
-                    .map_err(backoff::Error::transient)?;
-                if info.reward_cycle < block_reward_cycle {
-                    // If the stacks-node is still processing the burn block, the /v2/pox endpoint
-                    // may return the previous reward cycle. In this case, we should retry.
-                    return Err(backoff::Error::transient(ClientError::InvalidResponse(
-                        format!("Received reward cycle ({}) does not match the expected reward cycle ({}) for block {}.",
-                            info.reward_cycle,
-                            block_reward_cycle,
-                            current_burn_block_height
-                        ),
-                    )));
-                }
-                Ok(info)
-            })?;
-            *reward_cycle_info = new_reward_cycle_info;
+            self.handle_reward_cycle_transition(current_reward_cycle, block_reward_cycle)?;
+            reward_cycle_info.advance_to(current_burn_block_height);
+            self.current_reward_cycle_info = Some(reward_cycle_info);
         }
         let current_reward_cycle = reward_cycle_info.reward_cycle;
         // We should only attempt to refresh the signer if we are not configured for the next reward cycle yet and we received a new burn block for its prepare phase
@@ -351,6 +469,120 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug> RunLo
         Ok(())
     }
 
+    /// Update the state served by the health endpoint, probing the stacks node if
+    /// it has not been probed recently. Does nothing if the health endpoint is disabled.
+    fn update_health(&mut self, current_reward_cycle: u64) {
+        if self.config.health_endpoint.is_none() {
+            return;
+        }
+        let in_active_set = self
+            .stacks_signers
+            .values()
+            .any(|signer| signer.reward_cycle() == current_reward_cycle);
+        if let Ok(mut health) = self.stacks_client.health_state().write() {
+            health.update_reward_cycle(current_reward_cycle, in_active_set);
+        }
+        if self
+            .last_node_probe
+            .map(|probed_at| probed_at.elapsed() < NODE_PROBE_INTERVAL)
+            .unwrap_or(false)
+        {
+            return;
+        }
+        self.last_node_probe = Some(Instant::now());
+        if let Err(e) = self.stacks_client.probe_peer_info() {
+            warn!("Failed to reach the stacks node: {e}");
+        }
+    }
+
+    /// Measure the clock skew from the stacks node, if it has not been measured recently, and
+    /// warn if it exceeds the configured maximum. Response timestamps and proposal validity
+    /// windows are only meaningful if the signer and the node roughly agree on the time.
+    fn check_clock_skew(&mut self) {
+        if self
+            .last_clock_skew_check
+            .map(|checked_at| checked_at.elapsed() < CLOCK_SKEW_CHECK_INTERVAL)
+            .unwrap_or(false)
+        {
+            return;
+        }
+        self.last_clock_skew_check = Some(Instant::now());
+        let clock_skew_ms = match self.stacks_client.measure_clock_skew() {
+            Ok(clock_skew_ms) => clock_skew_ms,
+            Err(e) => {
+                warn!("Failed to measure the clock skew from the stacks node: {e}");
+                return;
+            }
+        };
+        if let Ok(mut health) = self.stacks_client.health_state().write() {
+            health.record_clock_skew(clock_skew_ms);
+        }
+        let max_clock_skew_ms = self.config.max_clock_skew.as_millis();
+        if clock_skew_ms.unsigned_abs() as u128 > max_clock_skew_ms {
+            warn!("Clock skew from the stacks node is {clock_skew_ms}ms, more than max_clock_skew_ms ({max_clock_skew_ms}ms). Check that both hosts are synchronized with NTP.");
+        } else {
+            debug!("Clock skew from the stacks node: {clock_skew_ms}ms");
+        }
+    }
+
+    /// Apply a reloaded config without restarting: the node host, timeouts, fees and
+    /// metrics endpoint take effect immediately, and each signer keeps its StackerDB slot
+    /// versions, persisted state and in-flight rounds. Nothing is applied if the reloaded
+    /// config changes a field that requires a restart.
+    pub fn reload_config(&mut self, new_config: GlobalConfig) -> Result<(), ConfigError> {
+        self.config.check_reloadable(&new_config)?;
+        self.stacks_client.reload_config(&new_config);
+        for signer in self.stacks_signers.values_mut() {
+            signer.reload_config(&new_config);
+        }
+        if new_config.metrics_endpoint != self.config.metrics_endpoint
+            || new_config.node_host != self.config.node_host
+        {
+            if let Err(e) = restart_serving_monitoring_metrics(new_config.clone()) {
+                error!("Failed to restart the metrics server: {e}");
+            }
+        }
+        self.signers_node_host = new_config.node_host.clone();
+        self.config = new_config;
+        info!("Reloaded signer config: {}", self.config);
+        Ok(())
+    }
+
+    /// Move the signers' StackerDB sessions to the stacks node the stacks client is using,
+    /// if it failed over to another node (or back to the primary) since the last pass
+    fn follow_node_failover(&mut self) {
+        let node_host = self.stacks_client.node_host();
+        if node_host == self.signers_node_host {
+            return;
+        }
+        info!(
+            "Moving StackerDB sessions to another stacks node";
+            "from" => &self.signers_node_host,
+            "to" => &node_host,
+        );
+        for signer in self.stacks_signers.values_mut() {
+            signer.switch_node_host(&node_host);
+        }
+        self.signers_node_host = node_host;
+    }
+
+    /// Reload the config if that was requested since the last pass. Requests that pile up
+    /// between passes are handled by a single reload of the most recently requested file.
+    fn apply_config_reloads(&mut self) {
+        let Some(config_reloads) = self.config_reloads.as_ref() else {
+            return;
+        };
+        let Some(config_path) = config_reloads.try_iter().last() else {
+            return;
+        };
+        info!("Reloading signer config from {}", config_path.display());
+        let reloaded = GlobalConfig::load_for_reload(&config_path)
+            .and_then(|new_config| self.reload_config(new_config));
+        if let Err(e) = reloaded {
+            error!("Rejected reloaded signer config: {e}");
+        }
+    }
+
     fn cleanup_stale_signers(&mut self, current_reward_cycle: u64) {
         let mut to_delete = Vec::new();
         for (idx, signer) in &mut self.stacks_signers {
@@ -373,8 +605,15 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug>
         self.config.event_timeout = timeout;
     }
 
+    /// The event timeout, shortened so that the runloop wakes up in time to write the
+    /// signers' delayed messages
     fn get_event_timeout(&self) -> Duration {
-        self.config.event_timeout
+        let now = Instant::now();
+        self.stacks_signers
+            .values()
+            .filter_map(|signer| signer.next_delayed_message_deadline())
+            .map(|deadline| deadline.saturating_duration_since(now))
+            .fold(self.config.event_timeout, Duration::min)
     }
 
     fn run_one_pass(
@@ -390,9 +629,11 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug>
         if let Some(cmd) = cmd {
             self.commands.push_back(cmd);
         }
+        self.apply_config_reloads();
         if self.state == State::Uninitialized {
             if let Err(e) = self.initialize_runloop() {
                 error!("Failed to initialize signer runloop: {e}.");
+                self.stacks_client.record_error(&e);
                 if let Some(event) = event {
                     warn!("Ignoring event: {event:?}");
                 }
@@ -401,6 +642,7 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug>
         } else if let Some(SignerEvent::NewBurnBlock(current_burn_block_height)) = event {
             if let Err(e) = self.refresh_runloop(current_burn_block_height) {
                 error!("Failed to refresh signer runloop: {e}.");
+                self.stacks_client.record_error(&e);
                 warn!("Signer may have an outdated view of the network.");
             }
         }
@@ -409,6 +651,9 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug>
             .as_ref()
             .expect("FATAL: cannot be an initialized signer with no reward cycle info.")
             .reward_cycle;
+        self.update_health(current_reward_cycle);
+        self.check_clock_skew();
+        self.follow_node_failover();
         if self.state == State::NoRegisteredSigners {
             let next_reward_cycle = current_reward_cycle.saturating_add(1);
             if let Some(event) = event {
@@ -430,6 +675,7 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug>
                 current_reward_cycle,
                 self.commands.pop_front(),
             );
+            signer.send_delayed_messages();
         }
         None
     }
@@ -437,12 +683,95 @@ impl<Signer: SignerTrait<T>, T: StacksMessageCodec + Clone + Send + Debug>
 
 #[cfg(test)]
 mod tests {
+    use std::sync::mpsc::channel;
+    use std::thread::spawn;
+    use std::time::Duration;
+
     use blockstack_lib::chainstate::stacks::boot::NakamotoSignerEntry;
-    use libsigner::SignerEntries;
+    use blockstack_lib::chainstate::stacks::boot::SIGNERS_NAME;
+    use blockstack_lib::util_lib::boot::boot_code_id;
+    use libsigner::v1::messages::{MessageSlotID, SignerMessage};
+    use libsigner::{SignerEntries, SignerRunLoop};
+    use libstackerdb::{stackerdb_get_chunk_path, stackerdb_post_chunk_path};
     use rand::{thread_rng, Rng, RngCore};
-    use stacks_common::types::chainstate::{StacksPrivateKey, StacksPublicKey};
+    use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+
+    use super::{RewardCycleInfo, RunLoop, State};
+    use crate::client::tests::{
+        build_get_signer_slots_response, generate_signer_config, write_scripted_responses,
+        MockServerClient,
+    };
+    use crate::client::SignerSlotID;
+    use crate::config::{ConfigError, GlobalConfig, Network};
+    use crate::v1::signer::Signer;
+    use crate::Signer as SignerTrait;
 
-    use super::RewardCycleInfo;
+    fn test_runloop() -> RunLoop<Signer, SignerMessage> {
+        let config = GlobalConfig::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        RunLoop::new(config).unwrap()
+    }
+
+    #[test]
+    fn reloaded_config_is_applied() {
+        let mut runloop = test_runloop();
+        let (reload_send, reload_recv) = channel();
+        runloop.config_reloads = Some(reload_recv);
+
+        let config_toml: String = std::fs::read_to_string("./src/tests/conf/signer-0.toml")
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("node_host"))
+            .map(|line| format!("{line}\n"))
+            .collect();
+        let path = std::env::temp_dir().join(format!(
+            "stacks-signer-reload-test-{}.toml",
+            rand::random::<u64>()
+        ));
+        std::fs::write(
+            &path,
+            format!(
+                "{config_toml}node_host = \"127.0.0.1:30443\"\nevent_timeout_ms = 1234\nsign_timeout_ms = 9000\ntx_fee_ustx = 42\n"
+            ),
+        )
+        .unwrap();
+        // the signal handler only asks for a reload, which happens on the runloop's next pass
+        reload_send.send(path.clone()).unwrap();
+        runloop.apply_config_reloads();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(runloop.config.node_host, "127.0.0.1:30443");
+        assert_eq!(runloop.get_event_timeout(), Duration::from_millis(1234));
+        assert_eq!(runloop.config.sign_timeout, Some(Duration::from_secs(9)));
+        assert_eq!(runloop.config.tx_fee_ustx, 42);
+    }
+
+    #[test]
+    fn reloaded_config_changing_identity_is_rejected() {
+        let mut runloop = test_runloop();
+        let original_config = runloop.config.clone();
+
+        let mut new_config = original_config.clone();
+        new_config.node_host = "127.0.0.1:30443".to_string();
+        new_config.stacks_private_key = StacksPrivateKey::new();
+        new_config.network = Network::Mainnet;
+        let Err(ConfigError::Multiple(errors)) = runloop.reload_config(new_config) else {
+            panic!("Expected the reload to be rejected");
+        };
+        let fields: Vec<_> = errors
+            .iter()
+            .filter_map(|e| match e {
+                ConfigError::RestartRequired(field) => Some(field.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fields, ["stacks_private_key", "network"]);
+        // Nothing is applied, not even the reloadable node host
+        assert_eq!(runloop.config.node_host, original_config.node_host);
+        assert_eq!(
+            runloop.config.stacks_private_key,
+            original_config.stacks_private_key
+        );
+    }
 
     #[test]
     fn parse_nakamoto_signer_entries_test() {
@@ -607,4 +936,138 @@ mod tests {
             }
         }
     }
+
+    /// A runloop in reward cycle 5 of 10 burn blocks, that talks to `mock`'s server, with a
+    /// signer in slot 1 of each of `reward_cycles`
+    fn rollover_runloop(
+        mock: &MockServerClient,
+        reward_cycles: &[u64],
+    ) -> RunLoop<Signer, SignerMessage> {
+        let mainnet = mock.config.network.is_mainnet();
+        let config = mock.config.clone();
+        let cycles = reward_cycles.to_vec();
+        let h = spawn(move || {
+            let mut runloop = RunLoop::new(config.clone()).unwrap();
+            runloop.current_reward_cycle_info = Some(RewardCycleInfo {
+                reward_cycle: 5,
+                reward_cycle_length: 10,
+                prepare_phase_block_length: 5,
+                first_burnchain_block_height: 0,
+                last_burnchain_block_height: 59,
+            });
+            for reward_cycle in cycles {
+                let mut signer_config = generate_signer_config(&config, 5, 20);
+                signer_config.reward_cycle = reward_cycle;
+                signer_config.signer_slot_id = SignerSlotID(1);
+                runloop
+                    .stacks_signers
+                    .insert(reward_cycle % 2, Signer::new(signer_config));
+            }
+            runloop.state = State::RegisteredSigners;
+            runloop
+        });
+        // each new signer looks for its persisted state, and advertises its capabilities
+        let script: Vec<_> = reward_cycles
+            .iter()
+            .flat_map(|reward_cycle| {
+                let state_contract =
+                    MessageSlotID::EncryptedSignerState.stacker_db_contract(mainnet, *reward_cycle);
+                let capabilities_contract =
+                    MessageSlotID::ProtocolCapabilities.stacker_db_contract(mainnet, *reward_cycle);
+                [
+                    (
+                        format!("GET {}", stackerdb_get_chunk_path(state_contract, 1, None)),
+                        b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec(),
+                    ),
+                    (
+                        format!("POST {}", stackerdb_post_chunk_path(capabilities_contract)),
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 17\r\n\r\n{\"accepted\":true}"
+                            .to_vec(),
+                    ),
+                ]
+            })
+            .collect();
+        write_scripted_responses(&mock.server, &script);
+        h.join().unwrap()
+    }
+
+    /// Cross into reward cycle 6, with `signers` in its signer slots
+    fn cross_into_next_cycle(
+        mock: MockServerClient,
+        mut runloop: RunLoop<Signer, SignerMessage>,
+        signers: &[StacksAddress],
+    ) -> RunLoop<Signer, SignerMessage> {
+        let signers_contract = boot_code_id(SIGNERS_NAME, mock.config.network.is_mainnet());
+        let h = spawn(move || {
+            runloop.refresh_runloop(60).unwrap();
+            runloop
+        });
+        write_scripted_responses(
+            &mock.server,
+            &[(
+                format!(
+                    "POST /v2/contracts/call-read/{}/{}/stackerdb-get-signer-slots-page",
+                    StacksAddress::from(signers_contract.issuer),
+                    signers_contract.name
+                ),
+                build_get_signer_slots_response(signers).into_bytes(),
+            )],
+        );
+        h.join().unwrap()
+    }
+
+    #[test]
+    fn signer_rolls_over_to_its_slot_in_the_next_cycle() {
+        let mock = MockServerClient::new();
+        let runloop = rollover_runloop(&mock, &[5, 6]);
+        let other_signer = StacksAddress::p2pkh(
+            false,
+            &StacksPublicKey::from_private(&StacksPrivateKey::new()),
+        );
+        let signers = [other_signer, mock.config.stacks_address];
+
+        let runloop = cross_into_next_cycle(mock, runloop, &signers);
+        assert_eq!(runloop.current_reward_cycle_info.unwrap().reward_cycle, 6);
+        assert_eq!(runloop.state, State::RegisteredSigners);
+        // the last cycle's signer is done, and the new one keeps writing to its slot
+        assert_eq!(runloop.stacks_signers.len(), 1);
+        let signer = &runloop.stacks_signers[&0];
+        assert_eq!(signer.reward_cycle(), 6);
+        assert_eq!(signer.signer_slot_id(), SignerSlotID(1));
+    }
+
+    #[test]
+    fn signer_absent_from_the_next_cycle_stops_signing() {
+        let mock = MockServerClient::new();
+        let runloop = rollover_runloop(&mock, &[5, 6]);
+        let other_signer = StacksAddress::p2pkh(
+            false,
+            &StacksPublicKey::from_private(&StacksPrivateKey::new()),
+        );
+
+        let runloop = cross_into_next_cycle(mock, runloop, &[other_signer]);
+        assert_eq!(runloop.current_reward_cycle_info.unwrap().reward_cycle, 6);
+        assert_eq!(runloop.state, State::NoRegisteredSigners);
+        assert!(runloop.stacks_signers.is_empty());
+    }
+
+    #[test]
+    fn advance_to_next_reward_cycle() {
+        let mut reward_cycle_info = RewardCycleInfo {
+            reward_cycle: 5,
+            reward_cycle_length: 10,
+            prepare_phase_block_length: 5,
+            first_burnchain_block_height: 0,
+            last_burnchain_block_height: 55,
+        };
+        reward_cycle_info.advance_to(59);
+        assert_eq!(reward_cycle_info.reward_cycle, 5);
+        assert!(reward_cycle_info.is_in_next_prepare_phase(59));
+
+        reward_cycle_info.advance_to(60);
+        assert_eq!(reward_cycle_info.reward_cycle, 6);
+        assert_eq!(reward_cycle_info.last_burnchain_block_height, 60);
+        assert!(!reward_cycle_info.is_in_next_prepare_phase(59));
+        assert!(reward_cycle_info.is_in_next_prepare_phase(65));
+    }
 }
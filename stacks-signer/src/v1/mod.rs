@@ -16,20 +16,27 @@
 
 /// The coordinator selector for the signer
 pub mod coordinator;
+/// The proposal intake for dropping superseded block proposals
+pub mod proposal_intake;
 /// The signer module for processing events
 pub mod signer;
 /// The state module for the signer
 pub mod signerdb;
+/// Tracking aggregate public key votes until they are counted
+pub mod vote_tracker;
 
+use std::path::PathBuf;
 use std::sync::mpsc::{channel, Receiver, Sender};
 
 use libsigner::v1::messages::SignerMessage;
 use libsigner::SignerEventReceiver;
-use slog::slog_info;
-use stacks_common::info;
+use slog::{slog_error, slog_info};
+use stacks_common::{error, info};
 use wsts::state_machine::OperationResult;
 
+use crate::audit::AuditLogError;
 use crate::config::GlobalConfig;
+use crate::health::start_health_server;
 use crate::runloop::{RunLoop, RunLoopCommand};
 use crate::v1::signer::Signer;
 
@@ -48,20 +55,34 @@ pub struct SpawnedSigner {
     pub cmd_send: Sender<RunLoopCommand>,
     /// The result receiver for interacting with the running signer
     pub res_recv: Receiver<Vec<OperationResult>>,
+    /// The sender for asking the running signer to reload its config from a file
+    pub reload_send: Sender<PathBuf>,
 }
 
-impl From<GlobalConfig> for SpawnedSigner {
-    fn from(config: GlobalConfig) -> Self {
+impl TryFrom<GlobalConfig> for SpawnedSigner {
+    type Error = AuditLogError;
+
+    fn try_from(config: GlobalConfig) -> Result<Self, Self::Error> {
         let endpoint = config.endpoint;
         info!("Starting signer with config: {}", config);
         let (cmd_send, cmd_recv) = channel();
         let (res_send, res_recv) = channel();
+        let (reload_send, reload_recv) = channel();
         let ev = SignerEventReceiver::new(config.network.is_mainnet());
         #[cfg(feature = "monitoring_prom")]
         {
             crate::monitoring::start_serving_monitoring_metrics(config.clone()).ok();
         }
-        let runloop = RunLoop::new(config);
+        let health_endpoint = config.health_endpoint;
+        let mut runloop = RunLoop::new(config)?;
+        runloop.config_reloads = Some(reload_recv);
+        if let Some(health_endpoint) = health_endpoint {
+            if let Err(e) =
+                start_health_server(health_endpoint, runloop.stacks_client.health_state())
+            {
+                error!("Failed to start the health listener: {e}");
+            }
+        }
         let mut signer: libsigner::Signer<
             RunLoopCommand,
             Vec<OperationResult>,
@@ -70,11 +91,12 @@ impl From<GlobalConfig> for SpawnedSigner {
             SignerMessage,
         > = libsigner::Signer::new(runloop, ev, cmd_recv, res_send);
         let running_signer = signer.spawn(endpoint).unwrap();
-        SpawnedSigner {
+        Ok(SpawnedSigner {
             running_signer,
             cmd_send,
             res_recv,
-        }
+            reload_send,
+        })
     }
 }
 
@@ -0,0 +1,463 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+use blockstack_lib::chainstate::stacks::ThresholdSignature;
+use hashbrown::HashMap;
+use libsigner::protocol::MinerWatermark;
+use libsigner::v1::messages::{BlockResponse, SignerMessage};
+use libsigner::{BlockProposal, MinerSlotMessage};
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::ConsensusHash;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use wsts::net::Message;
+
+/// How many signed blocks the signer remembers its signature for
+pub const SIGNED_BLOCK_CACHE_SIZE: usize = 64;
+
+/// The tenure and height a proposed block is for. A miner that re-proposes a block for the
+/// same key replaces its earlier proposal.
+type ProposalKey = (ConsensusHash, u64);
+
+/// How recent a block proposal is among the proposals for the same tenure and height
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposalOrder {
+    /// The miner's signing attempt, if the proposal carried it
+    pub attempt: Option<u64>,
+    /// The watermark the miner tagged the message with, if any
+    pub watermark: Option<MinerWatermark>,
+    /// The version of the miner slot chunk the proposal was read from
+    pub slot_version: u32,
+}
+
+impl ProposalOrder {
+    /// Is this proposal newer than `other`? Signing attempts are compared first, then
+    /// watermarks, then slot versions, using the first that both proposals have and that
+    /// differ.
+    pub fn supersedes(&self, other: &ProposalOrder) -> bool {
+        if let (Some(attempt), Some(other_attempt)) = (self.attempt, other.attempt) {
+            if attempt != other_attempt {
+                return attempt > other_attempt;
+            }
+        }
+        if let (Some(watermark), Some(other_watermark)) = (&self.watermark, &other.watermark) {
+            if watermark != other_watermark {
+                return watermark.is_newer_than(Some(other_watermark));
+            }
+        }
+        self.slot_version > other.slot_version
+    }
+
+    /// Is this proposal from a later signing attempt than `other`? False if either proposal
+    /// did not carry its attempt.
+    pub fn is_later_attempt_than(&self, other: &ProposalOrder) -> bool {
+        matches!((self.attempt, other.attempt), (Some(attempt), Some(other_attempt)) if attempt > other_attempt)
+    }
+}
+
+/// A block proposal that `ProposalIntake::coalesce` dropped in favor of a newer one
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupersededProposal {
+    /// The dropped proposal
+    pub proposal: BlockProposal,
+    /// Whether the miner superseded it with a proposal of a later signing attempt, rather than
+    /// with a newer message of the same attempt
+    pub by_later_attempt: bool,
+}
+
+/// The signatures of the blocks the signer most recently accepted, keyed by the blocks' signer
+/// signature hashes. Once full, the least recently used signature is evicted.
+#[derive(Debug)]
+pub struct SignedBlockCache {
+    /// The most signatures to keep
+    capacity: usize,
+    /// The signature of each cached block
+    signatures: HashMap<Sha512Trunc256Sum, ThresholdSignature>,
+    /// The cached blocks' signer signature hashes, least recently used first
+    recency: VecDeque<Sha512Trunc256Sum>,
+}
+
+impl Default for SignedBlockCache {
+    fn default() -> Self {
+        Self::new(SIGNED_BLOCK_CACHE_SIZE)
+    }
+}
+
+impl SignedBlockCache {
+    /// A cache of up to `capacity` signatures
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            signatures: HashMap::with_capacity(capacity),
+            recency: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Remember `signature` for the block with `signer_signature_hash`
+    pub fn insert(
+        &mut self,
+        signer_signature_hash: Sha512Trunc256Sum,
+        signature: ThresholdSignature,
+    ) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self
+            .signatures
+            .insert(signer_signature_hash, signature)
+            .is_some()
+        {
+            self.touch(&signer_signature_hash);
+            return;
+        }
+        if self.recency.len() >= self.capacity {
+            if let Some(evicted) = self.recency.pop_front() {
+                self.signatures.remove(&evicted);
+            }
+        }
+        self.recency.push_back(signer_signature_hash);
+    }
+
+    /// The signature of the block with `signer_signature_hash`, if it is cached
+    pub fn get(
+        &mut self,
+        signer_signature_hash: &Sha512Trunc256Sum,
+    ) -> Option<&ThresholdSignature> {
+        if self.signatures.contains_key(signer_signature_hash) {
+            self.touch(signer_signature_hash);
+        }
+        self.signatures.get(signer_signature_hash)
+    }
+
+    /// Forget every cached signature
+    pub fn clear(&mut self) {
+        self.signatures.clear();
+        self.recency.clear();
+    }
+
+    /// Mark the block with `signer_signature_hash` as the most recently used
+    fn touch(&mut self, signer_signature_hash: &Sha512Trunc256Sum) {
+        if let Some(position) = self
+            .recency
+            .iter()
+            .position(|hash| hash == signer_signature_hash)
+        {
+            self.recency.remove(position);
+        }
+        self.recency.push_back(*signer_signature_hash);
+    }
+}
+
+/// Drops the block proposals in a batch of miner messages that a newer proposal for the same
+/// tenure and height supersedes, either in the same batch or in an earlier one, so that the
+/// signer does not spend a block validation on a proposal the miner has already replaced.
+/// Proposals of a block the signer has already signed are answered with the cached signature
+/// instead of being validated and signed again.
+#[derive(Debug, Default)]
+pub struct ProposalIntake {
+    /// The newest proposal seen so far for each tenure and height
+    latest: HashMap<ProposalKey, ProposalOrder>,
+    /// The signatures of the blocks the signer recently accepted
+    signed: SignedBlockCache,
+}
+
+impl ProposalIntake {
+    /// Filter out the superseded proposals in `messages`, keeping the order of the rest.
+    /// Returns the remaining messages and the proposals dropped.
+    pub fn coalesce<'a>(
+        &mut self,
+        messages: Vec<&'a MinerSlotMessage<SignerMessage>>,
+    ) -> (Vec<&'a SignerMessage>, Vec<SupersededProposal>) {
+        let proposals: Vec<_> = messages
+            .iter()
+            .map(|msg| {
+                let proposal = proposal_of(&msg.message)?;
+                let key = (
+                    proposal.block.header.consensus_hash,
+                    proposal.block.header.chain_length,
+                );
+                let order = ProposalOrder {
+                    attempt: proposal.attempt,
+                    watermark: msg.watermark,
+                    slot_version: msg.slot_version,
+                };
+                Some((key, order, proposal))
+            })
+            .collect();
+
+        // whether any proposal seen so far for `key`, or in this batch, satisfies `newer`
+        let any_other = |key: &ProposalKey, newer: &dyn Fn(&ProposalOrder) -> bool| {
+            self.latest.get(key).map_or(false, newer)
+                || proposals
+                    .iter()
+                    .flatten()
+                    .any(|(other_key, other_order, _)| other_key == key && newer(other_order))
+        };
+        let mut kept = Vec::with_capacity(messages.len());
+        let mut newest = vec![];
+        let mut dropped = vec![];
+        for (msg, proposal) in messages.iter().zip(proposals.iter()) {
+            match proposal {
+                Some((key, order, proposal))
+                    if any_other(key, &|other: &ProposalOrder| other.supersedes(order)) =>
+                {
+                    // a later attempt always supersedes, since attempts are compared first
+                    let by_later_attempt = any_other(key, &|other: &ProposalOrder| {
+                        other.is_later_attempt_than(order)
+                    });
+                    dropped.push(SupersededProposal {
+                        proposal: (*proposal).clone(),
+                        by_later_attempt,
+                    });
+                }
+                Some((key, order, _)) => {
+                    newest.push((key.clone(), order.clone()));
+                    kept.push(&msg.message);
+                }
+                None => kept.push(&msg.message),
+            }
+        }
+        self.latest.extend(newest);
+        (kept, dropped)
+    }
+
+    /// Remember the signer's response to a block proposal, so that a repeat of the proposal can
+    /// be answered with it. Only accepted blocks are remembered.
+    pub fn record_response(&mut self, response: &BlockResponse) {
+        if let BlockResponse::Accepted((signer_signature_hash, signature)) = response {
+            self.signed
+                .insert(*signer_signature_hash, signature.clone());
+        }
+    }
+
+    /// Take the proposals of already signed blocks out of `messages`, keeping the order of the
+    /// rest. Returns the remaining messages and the cached responses to publish again for the
+    /// proposals taken out.
+    pub fn replay_signed<'a>(
+        &mut self,
+        messages: Vec<&'a SignerMessage>,
+    ) -> (Vec<&'a SignerMessage>, Vec<BlockResponse>) {
+        let mut kept = Vec::with_capacity(messages.len());
+        let mut replayed = vec![];
+        for message in messages {
+            let signer_signature_hash =
+                proposal_of(message).map(|proposal| proposal.block.header.signer_signature_hash());
+            match signer_signature_hash
+                .and_then(|hash| Some((hash, self.signed.get(&hash)?.clone())))
+            {
+                Some(accepted) => replayed.push(BlockResponse::Accepted(accepted)),
+                None => kept.push(message),
+            }
+        }
+        (kept, replayed)
+    }
+
+    /// Forget the proposals seen and the blocks signed so far, e.g. because the signer's view
+    /// of the sortition changed
+    pub fn clear(&mut self) {
+        self.latest.clear();
+        self.signed.clear();
+    }
+}
+
+/// The block proposal a miner message asks the signers to sign, if it is one
+fn proposal_of(message: &SignerMessage) -> Option<BlockProposal> {
+    let SignerMessage::Packet(packet) = message else {
+        return None;
+    };
+    let Message::NonceRequest(nonce_request) = &packet.msg else {
+        return None;
+    };
+    BlockProposal::consensus_deserialize(&mut nonce_request.message.as_slice()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use blockstack_lib::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
+    use blockstack_lib::chainstate::stacks::ThresholdSignature;
+    use libsigner::TenureView;
+    use wsts::net::{NonceRequest, Packet};
+
+    use super::*;
+
+    fn proposal_message(
+        chain_length: u64,
+        attempt: Option<u64>,
+        watermark: Option<MinerWatermark>,
+        slot_version: u32,
+    ) -> MinerSlotMessage<SignerMessage> {
+        let mut header = NakamotoBlockHeader::empty();
+        header.chain_length = chain_length;
+        header.consensus_hash = ConsensusHash([0x01; 20]);
+        let proposal = BlockProposal {
+            block: NakamotoBlock {
+                header,
+                txs: vec![],
+            },
+            burn_height: 7,
+            reward_cycle: 42,
+            miner_view: Some(TenureView {
+                burn_view_consensus_hash: ConsensusHash([0x01; 20]),
+                parent_tenure_id: None,
+                tenure_change_cause: None,
+            }),
+            tenure_extend: None,
+            attempt,
+        };
+        let nonce_request = NonceRequest {
+            dkg_id: 0,
+            sign_id: 0,
+            sign_iter_id: attempt.unwrap_or(0),
+            message: proposal.serialize_to_vec(),
+            is_taproot: false,
+            merkle_root: None,
+        };
+        MinerSlotMessage {
+            slot_id: 0,
+            slot_version,
+            watermark,
+            message: SignerMessage::Packet(Packet {
+                msg: Message::NonceRequest(nonce_request),
+                sig: vec![],
+            }),
+        }
+    }
+
+    fn attempts_of(messages: &[&SignerMessage]) -> Vec<Option<u64>> {
+        messages
+            .iter()
+            .map(|msg| proposal_of(msg).unwrap().attempt)
+            .collect()
+    }
+
+    #[test]
+    fn rapid_fire_proposals_keep_only_the_newest_attempt() {
+        let mut intake = ProposalIntake::default();
+        let messages: Vec<_> = (0..5)
+            .map(|attempt| proposal_message(10, Some(attempt), None, 1))
+            .collect();
+        let (kept, dropped) = intake.coalesce(messages.iter().collect());
+        assert_eq!(dropped.len(), 4);
+        assert_eq!(attempts_of(&kept), vec![Some(4)]);
+
+        assert!(dropped.iter().all(|superseded| superseded.by_later_attempt));
+
+        // a late arrival of an older attempt is dropped too
+        let late = proposal_message(10, Some(2), None, 2);
+        let (kept, dropped) = intake.coalesce(vec![&late]);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].proposal.attempt, Some(2));
+        assert!(dropped[0].by_later_attempt);
+        assert!(kept.is_empty());
+
+        // but a newer attempt, or a proposal for the next block, is not
+        let newer = proposal_message(10, Some(5), None, 3);
+        let next_block = proposal_message(11, Some(0), None, 4);
+        let (kept, dropped) = intake.coalesce(vec![&newer, &next_block]);
+        assert!(dropped.is_empty());
+        assert_eq!(attempts_of(&kept), vec![Some(5), Some(0)]);
+    }
+
+    #[test]
+    fn proposals_without_attempts_are_ordered_by_watermark_then_slot_version() {
+        let mut intake = ProposalIntake::default();
+        let older = proposal_message(10, None, Some(MinerWatermark::new(100, 3)), 9);
+        let newer = proposal_message(10, None, Some(MinerWatermark::new(101, 0)), 8);
+        let (kept, dropped) = intake.coalesce(vec![&newer, &older]);
+        assert_eq!(dropped.len(), 1);
+        assert!(!dropped[0].by_later_attempt);
+        assert_eq!(kept, vec![&newer.message]);
+
+        let mut intake = ProposalIntake::default();
+        let older = proposal_message(10, None, None, 1);
+        let newer = proposal_message(10, None, None, 2);
+        let (kept, dropped) = intake.coalesce(vec![&older, &newer]);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(kept, vec![&newer.message]);
+
+        // a repeat of the same proposal is left to the watermark check
+        let (kept, dropped) = intake.coalesce(vec![&newer]);
+        assert!(dropped.is_empty());
+        assert_eq!(kept, vec![&newer.message]);
+    }
+
+    #[test]
+    fn other_miner_messages_are_kept_in_order() {
+        let mut intake = ProposalIntake::default();
+        let older = proposal_message(10, Some(0), None, 1);
+        let results = MinerSlotMessage {
+            slot_id: 0,
+            slot_version: 2,
+            watermark: None,
+            message: SignerMessage::EncryptedSignerState(vec![1, 2, 3]),
+        };
+        let newer = proposal_message(10, Some(1), None, 3);
+        let (kept, dropped) = intake.coalesce(vec![&older, &results, &newer]);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(kept, vec![&results.message, &newer.message]);
+    }
+
+    #[test]
+    fn repeated_proposals_of_a_signed_block_are_answered_from_the_cache() {
+        let mut intake = ProposalIntake::default();
+        let proposal = proposal_message(10, Some(0), None, 1);
+        let signer_signature_hash = proposal_of(&proposal.message)
+            .unwrap()
+            .block
+            .header
+            .signer_signature_hash();
+        let mut validations = 0;
+        let mut published = vec![];
+        // the miner re-sends the identical proposal, e.g. after restarting
+        for _ in 0..2 {
+            let (kept, _) = intake.coalesce(vec![&proposal]);
+            let (kept, replayed) = intake.replay_signed(kept);
+            if !kept.is_empty() {
+                // the signer validates and signs the block
+                validations += 1;
+                let response =
+                    BlockResponse::Accepted((signer_signature_hash, ThresholdSignature::empty()));
+                intake.record_response(&response);
+                published.push(response);
+            }
+            published.extend(replayed);
+        }
+        assert_eq!(validations, 1);
+        assert_eq!(published.len(), 2);
+        assert_eq!(published[0], published[1]);
+
+        // a new sortition view forgets the signed blocks
+        intake.clear();
+        let (kept, replayed) = intake.replay_signed(vec![&proposal.message]);
+        assert_eq!(kept, vec![&proposal.message]);
+        assert!(replayed.is_empty());
+    }
+
+    #[test]
+    fn signed_block_cache_evicts_the_least_recently_used() {
+        let mut cache = SignedBlockCache::new(2);
+        let hashes: Vec<_> = (0..3).map(|i| Sha512Trunc256Sum([i; 32])).collect();
+        cache.insert(hashes[0], ThresholdSignature::empty());
+        cache.insert(hashes[1], ThresholdSignature::empty());
+        // using the first block makes the second the least recently used
+        assert!(cache.get(&hashes[0]).is_some());
+        cache.insert(hashes[2], ThresholdSignature::empty());
+        assert!(cache.get(&hashes[0]).is_some());
+        assert!(cache.get(&hashes[1]).is_none());
+        assert!(cache.get(&hashes[2]).is_some());
+    }
+}
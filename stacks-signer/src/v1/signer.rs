@@ -17,7 +17,7 @@ use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::path::PathBuf;
 use std::sync::mpsc::Sender;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use blockstack_lib::chainstate::burn::ConsensusHashExtensions;
 use blockstack_lib::chainstate::nakamoto::signer_set::NakamotoSigners;
@@ -27,10 +27,14 @@ use blockstack_lib::chainstate::stacks::StacksTransaction;
 use blockstack_lib::net::api::postblock_proposal::BlockValidateResponse;
 use blockstack_lib::util_lib::db::Error as DBError;
 use hashbrown::HashSet;
+use libsigner::protocol::{ProtocolCapabilities, ProtocolVersion};
 use libsigner::v1::messages::{
     BlockRejection, BlockResponse, MessageSlotID, RejectCode, SignerMessage,
 };
-use libsigner::{BlockProposal, SignerEvent};
+use libsigner::{
+    cycle_matches_signer_set, signer_set_for_cycle, BlockProposal, MinerSlotMessage, SignerEvent,
+    TenureView,
+};
 use rand_core::OsRng;
 use serde_derive::{Deserialize, Serialize};
 use slog::{slog_debug, slog_error, slog_info, slog_warn};
@@ -54,9 +58,10 @@ use wsts::traits::Signer as _;
 use wsts::v2;
 
 use crate::client::{ClientError, SignerSlotID, StackerDB, StacksClient};
-use crate::config::SignerConfig;
+use crate::config::{GlobalConfig, SignerConfig};
 use crate::runloop::{RunLoopCommand, SignerCommand};
 use crate::v1::coordinator::CoordinatorSelector;
+use crate::v1::proposal_intake::ProposalIntake;
 use crate::v1::signerdb::SignerDb;
 use crate::Signer as SignerTrait;
 
@@ -77,6 +82,12 @@ pub struct BlockInfo {
     nonce_request: Option<NonceRequest>,
     /// Whether this block is already being signed over
     pub signed_over: bool,
+    /// The miner's view of the block's tenure, if the proposal carried it
+    #[serde(default)]
+    pub miner_view: Option<TenureView>,
+    /// The miner's signing attempt for the block, if the proposal carried it
+    #[serde(default)]
+    pub attempt: Option<u64>,
 }
 
 impl From<BlockProposal> for BlockInfo {
@@ -89,6 +100,8 @@ impl From<BlockProposal> for BlockInfo {
             valid: None,
             nonce_request: None,
             signed_over: false,
+            miner_view: value.miner_view,
+            attempt: value.attempt,
         }
     }
 }
@@ -169,6 +182,13 @@ pub struct Signer {
     pub db_path: PathBuf,
     /// SignerDB for state management
     pub signer_db: SignerDb,
+    /// Drops block proposals that the miner has already replaced with a newer one, and answers
+    /// repeated proposals of blocks we already signed
+    pub proposal_intake: ProposalIntake,
+    /// The staleness of the stacks node's chain tip above which new block proposals are rejected
+    pub max_node_staleness: Duration,
+    /// Whether the node accepted this signer's protocol capabilities advertisement
+    pub capabilities_advertised: bool,
 }
 
 impl std::fmt::Display for Signer {
@@ -198,10 +218,47 @@ impl SignerTrait<SignerMessage> for Signer {
             .collect();
         self.next_signer_slot_ids = new_signer_config.signer_slot_ids.clone();
     }
+    /// Apply the reloaded node host, timeouts and fees. The coordinator is rebuilt from its
+    /// saved state, so any in-flight round carries on under the new timeouts.
+    fn reload_config(&mut self, config: &GlobalConfig) {
+        self.stackerdb.set_node_host(&config.node_host);
+        self.stackerdb.set_payload_envelope(config.payload_envelope);
+        self.stackerdb.set_block_response_delay(
+            config.block_response_delay_step,
+            config.block_response_max_delay,
+        );
+        self.tx_fee_ustx = config.tx_fee_ustx;
+        self.max_tx_fee_ustx = config.max_tx_fee_ustx;
+        self.max_node_staleness = config.max_node_staleness;
+        let mut coordinator_state = self.coordinator.save();
+        coordinator_state.config.dkg_public_timeout = config.dkg_public_timeout;
+        coordinator_state.config.dkg_private_timeout = config.dkg_private_timeout;
+        coordinator_state.config.dkg_end_timeout = config.dkg_end_timeout;
+        coordinator_state.config.nonce_timeout = config.nonce_timeout;
+        coordinator_state.config.sign_timeout = config.sign_timeout;
+        self.coordinator = FireCoordinator::load(&coordinator_state);
+        debug!("{self}: Applied reloaded config");
+    }
+
+    fn switch_node_host(&mut self, node_host: &str) {
+        self.stackerdb.switch_node_host(node_host);
+    }
     /// Return the reward cycle of the signer
     fn reward_cycle(&self) -> u64 {
         self.reward_cycle
     }
+    /// Return the slot the signer writes to
+    fn signer_slot_id(&self) -> SignerSlotID {
+        self.stackerdb.get_signer_slot_id()
+    }
+    /// Write the block responses whose delay has passed
+    fn send_delayed_messages(&mut self) {
+        self.stackerdb.send_due_block_responses();
+    }
+    /// When the next delayed block response is due
+    fn next_delayed_message_deadline(&self) -> Option<Instant> {
+        self.stackerdb.next_block_response_deadline()
+    }
 
     /// Process the event
     fn process_event(
@@ -211,19 +268,28 @@ impl SignerTrait<SignerMessage> for Signer {
         res: Sender<Vec<OperationResult>>,
         current_reward_cycle: u64,
     ) {
-        let event_parity = match event {
-            Some(SignerEvent::BlockValidationResponse(_)) => Some(current_reward_cycle % 2),
+        let event_signer_set = match event {
+            Some(SignerEvent::BlockValidationResponse(_)) => {
+                Some(signer_set_for_cycle(current_reward_cycle))
+            }
             // Block proposal events do have reward cycles, but each proposal has its own cycle,
             //  and the vec could be heterogenous, so, don't differentiate.
             Some(SignerEvent::MinerMessages(..))
             | Some(SignerEvent::NewBurnBlock(_))
             | Some(SignerEvent::StatusCheck)
             | None => None,
-            Some(SignerEvent::SignerMessages(msg_parity, ..)) => Some(u64::from(*msg_parity) % 2),
+            Some(SignerEvent::SignerMessages(signer_set, ..)) => Some(*signer_set),
         };
-        let other_signer_parity = (self.reward_cycle + 1) % 2;
-        if event_parity == Some(other_signer_parity) {
-            return;
+        if let Some(signer_set) = event_signer_set {
+            if !cycle_matches_signer_set(self.reward_cycle, signer_set) {
+                debug!(
+                    "{self}: Ignoring event for another signer set";
+                    "expected_signer_set" => signer_set_for_cycle(self.reward_cycle),
+                    "signer_set" => signer_set,
+                );
+                crate::monitoring::increment_signer_set_mismatches();
+                return;
+            }
         }
         if self.approved_aggregate_public_key.is_none() {
             if let Err(e) = self.refresh_dkg(stacks_client, res.clone(), current_reward_cycle) {
@@ -249,7 +315,7 @@ impl SignerTrait<SignerMessage> for Signer {
                 )
             }
             Some(SignerEvent::SignerMessages(signer_set, messages)) => {
-                if *signer_set != self.stackerdb.get_signer_set() {
+                if !cycle_matches_signer_set(self.reward_cycle, *signer_set) {
                     debug!("{self}: Received a signer message for a reward cycle that does not belong to this signer. Ignoring...");
                     return;
                 }
@@ -257,6 +323,7 @@ impl SignerTrait<SignerMessage> for Signer {
                     "{self}: Received {} messages from the other signers...",
                     messages.len()
                 );
+                let messages = messages.iter().map(|msg| &msg.message);
                 self.handle_signer_messages(stacks_client, res, messages, current_reward_cycle);
             }
             Some(SignerEvent::MinerMessages(messages, miner_key)) => {
@@ -273,13 +340,48 @@ impl SignerTrait<SignerMessage> for Signer {
                     messages.len();
                     "miner_key" => ?miner_key,
                 );
-                self.handle_signer_messages(stacks_client, res, messages, current_reward_cycle);
+                let messages: Vec<_> = messages
+                    .iter()
+                    .filter(|msg| self.accept_miner_message(msg))
+                    .collect();
+                let (messages, superseded) = self.proposal_intake.coalesce(messages);
+                if !superseded.is_empty() {
+                    debug!(
+                        "{self}: Dropped {} block proposals superseded by a newer one",
+                        superseded.len()
+                    );
+                    crate::monitoring::increment_superseded_proposals(superseded.len() as u64);
+                }
+                for superseded in superseded {
+                    if superseded.by_later_attempt {
+                        self.reject_prior_attempt(stacks_client, superseded.proposal);
+                    }
+                }
+                let (messages, replayed) = self.proposal_intake.replay_signed(messages);
+                for response in replayed {
+                    debug!("{self}: Re-publishing our response to a block we already signed: {response}");
+                    crate::monitoring::increment_replayed_block_responses();
+                    if let Err(e) = self.stackerdb.send_message_with_retry(response.into()) {
+                        warn!("{self}: Failed to send block submission to stacker-db: {e:?}");
+                    }
+                }
+                self.handle_signer_messages(
+                    stacks_client,
+                    res,
+                    messages.into_iter(),
+                    current_reward_cycle,
+                );
             }
             Some(SignerEvent::StatusCheck) => {
                 debug!("{self}: Received a status check event.")
             }
             Some(SignerEvent::NewBurnBlock(height)) => {
-                debug!("{self}: Receved a new burn block event for block height {height}")
+                debug!("{self}: Receved a new burn block event for block height {height}");
+                self.proposal_intake.clear();
+                self.refresh_node_chain_tip(stacks_client);
+                if !self.capabilities_advertised {
+                    self.advertise_capabilities();
+                }
             }
             None => {
                 // No event. Do nothing.
@@ -491,7 +593,7 @@ impl From<SignerConfig> for Signer {
             state_machine.signer = state;
         };
 
-        Self {
+        let mut signer = Self {
             coordinator,
             state_machine,
             state: State::Uninitialized,
@@ -515,11 +617,32 @@ impl From<SignerConfig> for Signer {
             miner_key: None,
             db_path: signer_config.db_path,
             signer_db,
-        }
+            proposal_intake: ProposalIntake::default(),
+            max_node_staleness: signer_config.max_node_staleness,
+            capabilities_advertised: false,
+        };
+        signer.advertise_capabilities();
+        signer
     }
 }
 
 impl Signer {
+    /// Advertise the protocol versions this signer speaks. Miners only tag their messages once
+    /// enough signers advertise that they read them, so a failed advertisement (e.g. before the
+    /// capabilities StackerDB exists) is tried again on the next burn block.
+    fn advertise_capabilities(&mut self) {
+        let capabilities = ProtocolCapabilities::new(vec![ProtocolVersion::V1]);
+        match self.stackerdb.advertise_capabilities(&capabilities) {
+            Ok(_) => {
+                debug!("{self}: Advertised protocol capabilities {capabilities:?}");
+                self.capabilities_advertised = true;
+            }
+            Err(e) => {
+                warn!("{self}: Failed to advertise protocol capabilities, miners will send legacy messages until it succeeds: {e}");
+            }
+        }
+    }
+
     /// Refresh the coordinator selector
     pub fn refresh_coordinator(&mut self) {
         // TODO: do not use an empty consensus hash
@@ -667,7 +790,7 @@ impl Signer {
                         return;
                     }
                 };
-                let is_valid = self.verify_block_transactions(stacks_client, &block_info.block);
+                let is_valid = self.verify_block_transactions(stacks_client, &block_info);
                 block_info.valid = Some(is_valid);
                 self.signer_db
                     .insert_block(&block_info)
@@ -701,9 +824,14 @@ impl Signer {
                 // Submit a rejection response to the .signers contract for miners
                 // to observe so they know to send another block and to prove signers are doing work);
                 warn!("{self}: Broadcasting a block rejection due to stacks node validation failure...");
+                let mut block_rejection = BlockRejection::from(block_validate_reject.clone());
+                block_rejection.signer_view = self.rejection_view(stacks_client, &block_info);
+                block_rejection.election_consensus_hash =
+                    Some(block_info.block.header.consensus_hash);
+                block_rejection.attempt = block_info.attempt;
                 if let Err(e) = self
                     .stackerdb
-                    .send_message_with_retry(block_validate_reject.clone().into())
+                    .send_message_with_retry(block_rejection.into())
                 {
                     warn!("{self}: Failed to send block rejection to stacker-db: {e:?}",);
                 }
@@ -732,16 +860,43 @@ impl Signer {
             .unwrap_or_else(|_| panic!("{self}: Failed to insert block in DB"));
     }
 
+    /// Check a miner message's watermark against the newest one accepted from its slot, and
+    /// record it if it is newer. Messages without a watermark are from the legacy protocol
+    /// and cannot be checked.
+    fn accept_miner_message(&mut self, msg: &MinerSlotMessage<SignerMessage>) -> bool {
+        let Some(watermark) = msg.watermark.as_ref() else {
+            return true;
+        };
+        match self
+            .signer_db
+            .accept_miner_watermark(msg.slot_id, watermark)
+        {
+            Ok(true) => true,
+            Ok(false) => {
+                warn!(
+                    "{self}: Ignoring replayed miner message";
+                    "slot_id" => msg.slot_id,
+                    "slot_version" => msg.slot_version,
+                    "watermark" => %watermark,
+                );
+                false
+            }
+            Err(e) => {
+                error!("{self}: Failed to check miner message watermark: {e:?}");
+                false
+            }
+        }
+    }
+
     /// Handle signer messages submitted to signers stackerdb
-    fn handle_signer_messages(
+    fn handle_signer_messages<'a>(
         &mut self,
         stacks_client: &StacksClient,
         res: Sender<Vec<OperationResult>>,
-        messages: &[SignerMessage],
+        messages: impl Iterator<Item = &'a SignerMessage>,
         current_reward_cycle: u64,
     ) {
         let packets: Vec<Packet> = messages
-            .iter()
             .filter_map(|msg| match msg {
                 SignerMessage::DkgResults { .. }
                 | SignerMessage::BlockResponse(_)
@@ -930,6 +1085,21 @@ impl Signer {
             .block_lookup(self.reward_cycle, &signer_signature_hash)
             .expect("Failed to connect to signer DB")
         else {
+            if self.node_is_stale(stacks_client) {
+                debug!("{self}: Broadcasting a block rejection due to a stale stacks node...");
+                let mut block_rejection =
+                    BlockRejection::new(signer_signature_hash, RejectCode::ConnectivityIssues);
+                block_rejection.election_consensus_hash =
+                    Some(block_proposal.block.header.consensus_hash);
+                block_rejection.attempt = block_proposal.attempt;
+                if let Err(e) = self
+                    .stackerdb
+                    .send_message_with_retry(block_rejection.into())
+                {
+                    warn!("{self}: Failed to send block rejection to stacker-db: {e:?}",);
+                }
+                return None;
+            }
             debug!(
                 "{self}: received a nonce request for a new block. Submit block for validation. ";
                 "signer_sighash" => %signer_signature_hash,
@@ -954,12 +1124,87 @@ impl Signer {
         Some(block_info)
     }
 
+    /// Record the stacks node's chain tip once per burn block, so that judging a block proposal
+    /// never has to wait on the node
+    fn refresh_node_chain_tip(&self, stacks_client: &StacksClient) {
+        // A single request, so that an unreachable node does not hold up the event loop
+        if let Err(e) = stacks_client.probe_peer_info() {
+            warn!("{self}: Failed to refresh the stacks node's chain tip: {e:?}");
+        }
+    }
+
+    /// Whether the stacks node's chain tip, as of the last burn block, has stood still for so
+    /// long that its state cannot be trusted to validate a new block proposal against
+    fn node_is_stale(&self, stacks_client: &StacksClient) -> bool {
+        let staleness = stacks_client.node_staleness();
+        if staleness <= self.max_node_staleness {
+            return false;
+        }
+        warn!(
+            "{self}: The stacks node's chain tip is stale. Rejecting new block proposals until it advances.";
+            "staleness_ms" => staleness.as_millis(),
+            "max_node_staleness_ms" => self.max_node_staleness.as_millis(),
+        );
+        true
+    }
+
+    /// Tell the miner we will not consider a proposal it has since re-proposed in a later
+    /// signing attempt, so that it does not wait on our response to the stale one
+    fn reject_prior_attempt(&mut self, stacks_client: &StacksClient, proposal: BlockProposal) {
+        let signer_signature_hash = proposal.block.header.signer_signature_hash();
+        debug!(
+            "{self}: Rejecting a block proposal superseded by a later signing attempt";
+            "signer_sighash" => %signer_signature_hash,
+            "attempt" => ?proposal.attempt,
+        );
+        let block_info = BlockInfo::from(proposal);
+        let mut block_rejection =
+            BlockRejection::new(signer_signature_hash, RejectCode::RejectedInPriorRound);
+        block_rejection.signer_view = self.rejection_view(stacks_client, &block_info);
+        block_rejection.election_consensus_hash = Some(block_info.block.header.consensus_hash);
+        block_rejection.attempt = block_info.attempt;
+        if let Err(e) = self
+            .stackerdb
+            .send_message_with_retry(block_rejection.into())
+        {
+            warn!("{self}: Failed to send block rejection to stacker-db: {e:?}",);
+        }
+    }
+
+    /// Our view of the tenure of a block we are rejecting, to echo back to a miner that sent
+    /// its own view with the proposal so that the two can be compared
+    fn rejection_view(
+        &self,
+        stacks_client: &StacksClient,
+        block_info: &BlockInfo,
+    ) -> Option<TenureView> {
+        let miner_view = block_info.miner_view.as_ref()?;
+        let peer_info = stacks_client
+            .get_peer_info()
+            .map_err(|e| warn!("{self}: Failed to get our burnchain view to report: {e:?}"))
+            .ok()?;
+        if peer_info.pox_consensus != miner_view.burn_view_consensus_hash {
+            debug!(
+                "{self}: Rejected block was proposed with a different burnchain view";
+                "signer_sighash" => %block_info.signer_signature_hash(),
+                "miner_burn_view" => %miner_view.burn_view_consensus_hash,
+                "signer_burn_view" => %peer_info.pox_consensus,
+            );
+        }
+        Some(TenureView {
+            burn_view_consensus_hash: peer_info.pox_consensus,
+            parent_tenure_id: None,
+            tenure_change_cause: None,
+        })
+    }
+
     /// Verify the transactions in a block are as expected
     fn verify_block_transactions(
         &mut self,
         stacks_client: &StacksClient,
-        block: &NakamotoBlock,
+        block_info: &BlockInfo,
     ) -> bool {
+        let block = &block_info.block;
         let next_reward_cycle = self.reward_cycle.wrapping_add(1);
         let approved_aggregate_public_key = stacks_client
             .get_approved_aggregate_key(next_reward_cycle)
@@ -989,10 +1234,13 @@ impl Signer {
             let is_valid = missing_transactions.is_empty();
             if !is_valid {
                 debug!("{self}: Broadcasting a block rejection due to missing expected transactions...");
-                let block_rejection = BlockRejection::new(
+                let mut block_rejection = BlockRejection::new(
                     block.header.signer_signature_hash(),
                     RejectCode::MissingTransactions(missing_transactions),
                 );
+                block_rejection.signer_view = self.rejection_view(stacks_client, block_info);
+                block_rejection.election_consensus_hash = Some(block.header.consensus_hash);
+                block_rejection.attempt = block_info.attempt;
                 // Submit signature result to miners to observe
                 if let Err(e) = self
                     .stackerdb
@@ -1005,10 +1253,13 @@ impl Signer {
         } else {
             // Failed to connect to the stacks node to get transactions. Cannot validate the block. Reject it.
             debug!("{self}: Broadcasting a block rejection due to signer connectivity issues...",);
-            let block_rejection = BlockRejection::new(
+            let mut block_rejection = BlockRejection::new(
                 block.header.signer_signature_hash(),
                 RejectCode::ConnectivityIssues,
             );
+            block_rejection.signer_view = self.rejection_view(stacks_client, block_info);
+            block_rejection.election_consensus_hash = Some(block.header.consensus_hash);
+            block_rejection.attempt = block_info.attempt;
             // Submit signature result to miners to observe
             if let Err(e) = self
                 .stackerdb
@@ -1191,7 +1442,7 @@ impl Signer {
         }
 
         // Get our current nonce from the stacks node and compare it against what we have sitting in the stackerdb instance
-        let signer_address = stacks_client.get_signer_address();
+        let signer_address = stacks_client.get_signer_address_for_cycle(self.reward_cycle);
         // Retreieve ALL account nonces as we may have transactions from other signers in our stackerdb slot that we care about
         let account_nonces = self.get_account_nonces(stacks_client, &self.signer_addresses);
         let account_nonce = account_nonces.get(signer_address).unwrap_or(&0);
@@ -1265,7 +1516,7 @@ impl Signer {
             0
         };
         unsigned_tx.set_tx_fee(tx_fee);
-        stacks_client.sign_transaction(unsigned_tx)
+        stacks_client.sign_transaction(unsigned_tx, self.reward_cycle)
     }
 
     // Get the account nonces for the provided list of signer addresses
@@ -1340,6 +1591,7 @@ impl Signer {
             BlockResponse::accepted(block_vote.signer_signature_hash, signature.clone())
         };
 
+        self.proposal_intake.record_response(&block_submission);
         // Submit signature result to miners to observe
         info!("{self}: Submit block response: {block_submission}");
         if let Err(e) = self
@@ -1564,7 +1816,7 @@ impl Signer {
             // We are not the coordinator, we are in the middle of an operation, or we have already queued DKG. Do not attempt to queue DKG
             return Ok(false);
         }
-        let signer_address = stacks_client.get_signer_address();
+        let signer_address = stacks_client.get_signer_address_for_cycle(self.reward_cycle);
         let account_nonces = self.get_account_nonces(stacks_client, &[*signer_address]);
         let old_transactions = self.get_signer_transactions(&account_nonces).map_err(|e| {
                 warn!("{self}: Failed to get old signer transactions: {e:?}. May trigger DKG unnecessarily");
@@ -1600,6 +1852,23 @@ impl Signer {
                 return Ok(false);
             };
             let threshold_weight = stacks_client.get_vote_threshold_weight(self.reward_cycle)?;
+            let candidate_weight = stacks_client
+                .get_round_vote_tally(
+                    self.reward_cycle,
+                    self.coordinator.current_dkg_id,
+                    &aggregate_key,
+                )?
+                .unwrap_or(0);
+            if candidate_weight >= threshold_weight {
+                // Our candidate has been voted in. Starting a new round would only race its approval.
+                debug!("{self}: Not triggering a DKG round. Our candidate aggregate key has enough weight to be approved.";
+                    "voting_round" => self.coordinator.current_dkg_id,
+                    "aggregate_key" => %aggregate_key,
+                    "candidate_weight" => candidate_weight,
+                    "threshold_weight" => threshold_weight
+                );
+                return Ok(false);
+            }
             if round_weight < threshold_weight {
                 // The threshold weight has not been met yet. We should wait for more votes to arrive.
                 // TODO: this should be on a timeout of some kind. We should not wait forever for the threshold to be met.
@@ -17,9 +17,10 @@
 use std::path::Path;
 
 use blockstack_lib::util_lib::db::{
-    query_row, sqlite_open, table_exists, u64_to_sql, Error as DBError,
+    query_row, sqlite_open, table_exists, tx_begin_immediate, u64_to_sql, Error as DBError,
 };
-use rusqlite::{params, Connection, Error as SqliteError, OpenFlags, NO_PARAMS};
+use libsigner::protocol::MinerWatermark;
+use rusqlite::{params, Connection, Error as SqliteError, OpenFlags, OptionalExtension, NO_PARAMS};
 use slog::slog_debug;
 use stacks_common::debug;
 use stacks_common::util::hash::Sha512Trunc256Sum;
@@ -49,6 +50,13 @@ CREATE TABLE IF NOT EXISTS signer_states (
     encrypted_state BLOB NOT NULL
 )";
 
+const CREATE_MINER_WATERMARKS_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS miner_watermarks (
+    slot_id INTEGER PRIMARY KEY,
+    burn_height INTEGER NOT NULL,
+    nonce INTEGER NOT NULL
+)";
+
 impl SignerDb {
     /// Create a new `SignerState` instance.
     /// This will create a new SQLite database at the given path
@@ -72,6 +80,10 @@ impl SignerDb {
             self.db.execute(CREATE_SIGNER_STATE_TABLE, NO_PARAMS)?;
         }
 
+        if !table_exists(&self.db, "miner_watermarks")? {
+            self.db.execute(CREATE_MINER_WATERMARKS_TABLE, NO_PARAMS)?;
+        }
+
         Ok(())
     }
 
@@ -108,6 +120,55 @@ impl SignerDb {
         Ok(())
     }
 
+    /// Get the newest watermark accepted from the given miner slot, if any
+    pub fn get_miner_watermark(&self, slot_id: u32) -> Result<Option<MinerWatermark>, DBError> {
+        Self::get_miner_watermark_in(&self.db, slot_id)
+    }
+
+    fn get_miner_watermark_in(
+        conn: &Connection,
+        slot_id: u32,
+    ) -> Result<Option<MinerWatermark>, DBError> {
+        let row: Option<(i64, i64)> = conn
+            .query_row(
+                "SELECT burn_height, nonce FROM miner_watermarks WHERE slot_id = ?",
+                params![slot_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        row.map(|(burn_height, nonce)| {
+            let burn_height = u64::try_from(burn_height).map_err(|_| DBError::ParseError)?;
+            let nonce = u64::try_from(nonce).map_err(|_| DBError::ParseError)?;
+            Ok(MinerWatermark::new(burn_height, nonce))
+        })
+        .transpose()
+    }
+
+    /// Record `watermark` as the newest accepted from the given miner slot if it is newer than
+    /// the one already recorded. Returns whether it was, i.e., whether the message carrying it
+    /// should be processed rather than ignored as a replay.
+    pub fn accept_miner_watermark(
+        &mut self,
+        slot_id: u32,
+        watermark: &MinerWatermark,
+    ) -> Result<bool, DBError> {
+        let tx = tx_begin_immediate(&mut self.db)?;
+        let last_seen = Self::get_miner_watermark_in(&tx, slot_id)?;
+        if !watermark.is_newer_than(last_seen.as_ref()) {
+            return Ok(false);
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO miner_watermarks (slot_id, burn_height, nonce) VALUES (?1, ?2, ?3)",
+            params![
+                slot_id,
+                u64_to_sql(watermark.burn_height)?,
+                u64_to_sql(watermark.nonce)?
+            ],
+        )?;
+        tx.commit()?;
+        Ok(true)
+    }
+
     /// Fetch a block from the database using the block's
     /// `signer_signature_hash`
     pub fn block_lookup(
@@ -166,6 +227,7 @@ where
 }
 
 #[cfg(test)]
+/// Create a fresh signer DB at `db_path`, removing any existing one
 pub fn test_signer_db(db_path: &str) -> SignerDb {
     use std::fs;
 
@@ -205,6 +267,9 @@ mod tests {
             block,
             burn_height: 7,
             reward_cycle: 42,
+            miner_view: None,
+            tenure_extend: None,
+            attempt: None,
         };
         overrides(&mut block_proposal);
         (BlockInfo::from(block_proposal.clone()), block_proposal)
@@ -308,6 +373,33 @@ mod tests {
         assert_eq!(block_info.vote, Some(vote));
     }
 
+    #[test]
+    fn test_replayed_miner_watermark_is_rejected() {
+        let db_path = tmp_db_path();
+        let mut db = SignerDb::new(&db_path).expect("Failed to create signer db");
+        let older = MinerWatermark::new(10, 1);
+        let newer = MinerWatermark::new(10, 2);
+
+        assert!(db.get_miner_watermark(0).unwrap().is_none());
+        assert!(db.accept_miner_watermark(0, &older).unwrap());
+        assert!(db.accept_miner_watermark(0, &newer).unwrap());
+        // replaying the old message, or the new one again, is ignored
+        assert!(!db.accept_miner_watermark(0, &older).unwrap());
+        assert!(!db.accept_miner_watermark(0, &newer).unwrap());
+        assert_eq!(db.get_miner_watermark(0).unwrap(), Some(newer));
+        // other slots are tracked separately
+        assert!(db.accept_miner_watermark(1, &older).unwrap());
+
+        // the watermarks survive a restart
+        drop(db);
+        let mut db = SignerDb::new(&db_path).expect("Failed to reopen signer db");
+        assert_eq!(db.get_miner_watermark(0).unwrap(), Some(newer));
+        assert!(!db.accept_miner_watermark(0, &older).unwrap());
+        assert!(db
+            .accept_miner_watermark(0, &MinerWatermark::new(11, 0))
+            .unwrap());
+    }
+
     #[test]
     fn test_write_signer_state() {
         let db_path = tmp_db_path();
@@ -0,0 +1,324 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use blockstack_lib::burnchains::Txid;
+use blockstack_lib::chainstate::stacks::StacksTransaction;
+use slog::slog_info;
+use stacks_common::info;
+use wsts::curve::point::Point;
+
+use crate::client::{ClientError, StacksNodeApi};
+
+/// How often a vote should be polled while waiting for it to be counted
+pub const VOTE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What polling a `VoteTracker` found
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoteStatus {
+    /// The vote transaction has not been mined, and its round is still the latest
+    Pending,
+    /// The voting contract counted the vote in `round`
+    Confirmed {
+        /// The round the vote was counted in
+        round: u64,
+        /// The transaction that was last submitted for the vote
+        txid: Txid,
+    },
+    /// The vote was cast again in `round`, because its transaction was mined (or replaced)
+    /// without the vote being counted, or the voting contract moved on to a later round first
+    Recast {
+        /// The round the vote was cast again in
+        round: u64,
+        /// The transaction that was given up on
+        stale_txid: Txid,
+        /// The transaction that was submitted in its place
+        txid: Txid,
+    },
+}
+
+/// Follows an aggregate public key vote until the voting contract counts it. Rather than
+/// resubmitting a vote for a round that has passed, the tracker votes again in the round
+/// the contract is at.
+pub struct VoteTracker {
+    signer_index: u32,
+    dkg_public_key: Point,
+    reward_cycle: u64,
+    tx_fee_ustx: u64,
+    /// The round the last submitted vote is for
+    round: u64,
+    /// The last submitted vote
+    tx: StacksTransaction,
+}
+
+impl VoteTracker {
+    /// Vote for `dkg_public_key` as the aggregate public key of `reward_cycle` in `round`,
+    /// and track the vote
+    pub fn cast<C: StacksNodeApi>(
+        stacks_client: &C,
+        signer_index: u32,
+        round: u64,
+        dkg_public_key: Point,
+        reward_cycle: u64,
+        tx_fee_ustx: u64,
+    ) -> Result<Self, ClientError> {
+        let tx = stacks_client.cast_aggregate_public_key_vote(
+            signer_index,
+            round,
+            dkg_public_key,
+            reward_cycle,
+            tx_fee_ustx,
+        )?;
+        Ok(Self {
+            signer_index,
+            dkg_public_key,
+            reward_cycle,
+            tx_fee_ustx,
+            round,
+            tx,
+        })
+    }
+
+    /// The round the last submitted vote is for
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// The last submitted vote
+    pub fn tx(&self) -> &StacksTransaction {
+        &self.tx
+    }
+
+    /// Check whether the vote was counted, and vote again if it can no longer be
+    pub fn poll<C: StacksNodeApi>(&mut self, stacks_client: &C) -> Result<VoteStatus, ClientError> {
+        let voter = self.tx.origin_address();
+        if stacks_client
+            .get_vote_for_aggregate_public_key(self.round, self.reward_cycle, voter)?
+            .is_some()
+        {
+            return Ok(VoteStatus::Confirmed {
+                round: self.round,
+                txid: self.tx.txid(),
+            });
+        }
+        let last_round = stacks_client.get_last_round(self.reward_cycle)?;
+        let round = match last_round {
+            Some(last_round) if last_round > self.round => {
+                info!(
+                    "Voting round advanced before the aggregate key vote was counted. Voting again in the new round.";
+                    "reward_cycle" => self.reward_cycle,
+                    "round" => self.round,
+                    "last_round" => last_round,
+                    "txid" => %self.tx.txid(),
+                );
+                last_round
+            }
+            _ => {
+                if stacks_client.get_account_nonce(&voter)? <= self.tx.get_origin_nonce() {
+                    return Ok(VoteStatus::Pending);
+                }
+                info!(
+                    "Aggregate key vote transaction was mined or replaced without the vote being counted. Voting again.";
+                    "reward_cycle" => self.reward_cycle,
+                    "round" => self.round,
+                    "txid" => %self.tx.txid(),
+                );
+                self.round
+            }
+        };
+        self.recast(stacks_client, round)
+    }
+
+    /// Vote again in `round` with the account's current nonce
+    fn recast<C: StacksNodeApi>(
+        &mut self,
+        stacks_client: &C,
+        round: u64,
+    ) -> Result<VoteStatus, ClientError> {
+        // a stale vote still in the mempool holds the nonce, so the new vote has to outbid it
+        let nonce = stacks_client.get_account_nonce(&self.tx.origin_address())?;
+        let tx_fee_ustx = if nonce == self.tx.get_origin_nonce() {
+            self.tx_fee_ustx.max(self.tx.get_tx_fee().saturating_add(1))
+        } else {
+            self.tx_fee_ustx
+        };
+        let tx = stacks_client.cast_aggregate_public_key_vote(
+            self.signer_index,
+            round,
+            self.dkg_public_key,
+            self.reward_cycle,
+            tx_fee_ustx,
+        )?;
+        let stale_txid = self.tx.txid();
+        self.round = round;
+        self.tx = tx;
+        Ok(VoteStatus::Recast {
+            round,
+            stale_txid,
+            txid: self.tx.txid(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use blockstack_lib::chainstate::stacks::TransactionPayload;
+    use clarity::vm::Value as ClarityValue;
+    use stacks_common::types::chainstate::StacksPrivateKey;
+    use wsts::curve::scalar::Scalar;
+
+    use super::*;
+    use crate::client::node_api::testing::{MockStacksNode, NodeCall};
+
+    const REWARD_CYCLE: u64 = 10;
+
+    /// A node at which the signer's account is at nonce 7
+    fn mock_node() -> MockStacksNode {
+        let node = MockStacksNode::new(StacksPrivateKey::new());
+        node.set_account_nonce(node.signer_address(), 7);
+        node
+    }
+
+    /// Cast a vote in round 1 with a fee of 500 uSTX
+    fn cast_vote(node: &MockStacksNode, point: Point) -> VoteTracker {
+        let tracker = VoteTracker::cast(node, 3, 1, point, REWARD_CYCLE, 500).unwrap();
+        node.clear_calls();
+        tracker
+    }
+
+    /// The round the vote in `tx` is for
+    fn vote_round(tx: &StacksTransaction) -> u64 {
+        let TransactionPayload::ContractCall(call) = &tx.payload else {
+            panic!("Expected a contract call");
+        };
+        let ClarityValue::UInt(round) = call.function_args[2] else {
+            panic!("Expected a round");
+        };
+        u64::try_from(round).unwrap()
+    }
+
+    #[test]
+    fn counted_vote_is_confirmed() {
+        let node = mock_node();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let mut tracker = cast_vote(&node, point);
+        let txid = tracker.tx().txid();
+        assert_eq!(node.submitted_transactions(), vec![tracker.tx().clone()]);
+        assert_eq!(tracker.tx().get_origin_nonce(), 7);
+
+        node.set_last_round(REWARD_CYCLE, 1);
+        assert_eq!(tracker.poll(&node).unwrap(), VoteStatus::Pending);
+        let voter = node.signer_address();
+        assert_eq!(
+            node.calls(),
+            vec![
+                NodeCall::GetVoteForAggregatePublicKey {
+                    round: 1,
+                    reward_cycle: REWARD_CYCLE,
+                    signer: voter,
+                },
+                NodeCall::GetLastRound(REWARD_CYCLE),
+                NodeCall::GetAccountNonce(voter),
+            ]
+        );
+
+        node.set_vote(REWARD_CYCLE, 1, voter, point);
+        assert_eq!(
+            tracker.poll(&node).unwrap(),
+            VoteStatus::Confirmed { round: 1, txid }
+        );
+        assert_eq!(tracker.tx().txid(), txid);
+        assert_eq!(node.submitted_transactions().len(), 1);
+    }
+
+    #[test]
+    fn dropped_vote_is_cast_again_in_the_same_round() {
+        let node = mock_node();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let mut tracker = cast_vote(&node, point);
+        let stale_txid = tracker.tx().txid();
+
+        // the nonce moved past the vote, but the vote was not counted
+        node.set_last_round(REWARD_CYCLE, 1);
+        node.set_account_nonce(node.signer_address(), 8);
+        let status = tracker.poll(&node).unwrap();
+        let txid = tracker.tx().txid();
+        assert_eq!(
+            status,
+            VoteStatus::Recast {
+                round: 1,
+                stale_txid,
+                txid
+            }
+        );
+        assert_eq!(tracker.round(), 1);
+        assert_eq!(vote_round(tracker.tx()), 1);
+        assert_eq!(tracker.tx().get_origin_nonce(), 8);
+        assert_eq!(tracker.tx().get_tx_fee(), 500);
+        assert_eq!(node.submitted_transactions().last(), Some(tracker.tx()));
+    }
+
+    #[test]
+    fn vote_is_cast_again_when_the_round_advances() {
+        let node = mock_node();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let mut tracker = cast_vote(&node, point);
+        let stale_txid = tracker.tx().txid();
+
+        // the stale vote is still in the mempool, so the new one replaces it by fee
+        node.set_last_round(REWARD_CYCLE, 2);
+        let status = tracker.poll(&node).unwrap();
+        let txid = tracker.tx().txid();
+        assert_ne!(txid, stale_txid);
+        assert_eq!(
+            status,
+            VoteStatus::Recast {
+                round: 2,
+                stale_txid,
+                txid
+            }
+        );
+        assert_eq!(tracker.round(), 2);
+        assert_eq!(vote_round(tracker.tx()), 2);
+        assert_eq!(tracker.tx().get_origin_nonce(), 7);
+        assert_eq!(tracker.tx().get_tx_fee(), 501);
+
+        // and the new vote is what gets confirmed
+        node.set_vote(REWARD_CYCLE, 2, node.signer_address(), point);
+        assert_eq!(
+            tracker.poll(&node).unwrap(),
+            VoteStatus::Confirmed { round: 2, txid }
+        );
+    }
+
+    #[test]
+    fn unreachable_node_leaves_the_vote_as_it_was() {
+        let node = mock_node();
+        let point = Point::from(Scalar::random(&mut rand::thread_rng()));
+        let mut tracker = cast_vote(&node, point);
+        let txid = tracker.tx().txid();
+
+        node.set_offline(true);
+        assert!(matches!(
+            tracker.poll(&node),
+            Err(ClientError::NotConnected)
+        ));
+        assert_eq!(tracker.round(), 1);
+        assert_eq!(tracker.tx().txid(), txid);
+        assert_eq!(node.submitted_transactions().len(), 1);
+    }
+}
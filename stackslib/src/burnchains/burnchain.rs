@@ -25,6 +25,7 @@ use std::{fs, thread};
 use stacks_common::address::{public_keys_to_address_hash, AddressHashMode};
 use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash as BitcoinSha256dHash;
 use stacks_common::types::chainstate::{BurnchainHeaderHash, PoxId, StacksAddress, TrieHash};
+use stacks_common::types::reward_cycle::RewardCycleInfo;
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::vrf::VRFPublicKey;
 use stacks_common::util::{get_epoch_time_ms, get_epoch_time_secs, log, sleep_ms};
@@ -546,6 +547,12 @@ impl Burnchain {
             .is_reward_cycle_start(self.first_block_height, burn_height)
     }
 
+    /// This burnchain's reward cycle schedule
+    pub fn reward_cycle_info(&self) -> RewardCycleInfo {
+        self.pox_constants
+            .reward_cycle_info(self.first_block_height)
+    }
+
     pub fn reward_cycle_to_block_height(&self, reward_cycle: u64) -> u64 {
         self.pox_constants
             .reward_cycle_to_block_height(self.first_block_height, reward_cycle)
@@ -23,6 +23,7 @@ use stacks_common::address::AddressHashMode;
 use stacks_common::types::chainstate::{
     BurnchainHeaderHash, ConsensusHash, PoxId, StacksAddress, TrieHash,
 };
+use stacks_common::types::reward_cycle::RewardCycleInfo;
 pub use stacks_common::types::{Address, PrivateKey, PublicKey};
 use stacks_common::util::hash::{Hash160, Sha512Trunc256Sum};
 use stacks_common::util::secp256k1::MessageSignature;
@@ -536,6 +537,16 @@ impl PoxConstants {
         first_block_height + reward_cycle * u64::from(self.reward_cycle_length) + 1
     }
 
+    /// The reward cycle schedule these constants set for a burnchain whose PoX starts at
+    /// `first_block_height`
+    pub fn reward_cycle_info(&self, first_block_height: u64) -> RewardCycleInfo {
+        RewardCycleInfo::new(
+            first_block_height,
+            u64::from(self.reward_cycle_length),
+            u64::from(self.prepare_length),
+        )
+    }
+
     pub fn reward_cycle_index(&self, first_block_height: u64, burn_height: u64) -> Option<u64> {
         let effective_height = burn_height.checked_sub(first_block_height)?;
         Some(effective_height % u64::from(self.reward_cycle_length))
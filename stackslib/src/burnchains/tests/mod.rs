@@ -37,7 +37,10 @@ use crate::chainstate::burn::*;
 use crate::chainstate::coordinator::comm::*;
 use crate::chainstate::coordinator::*;
 use crate::chainstate::stacks::*;
-use crate::core::{STACKS_EPOCH_2_4_MARKER, STACKS_EPOCH_3_0_MARKER};
+use crate::core::{
+    BITCOIN_MAINNET_FIRST_BLOCK_HEIGHT, BITCOIN_TESTNET_FIRST_BLOCK_HEIGHT,
+    STACKS_EPOCH_2_4_MARKER, STACKS_EPOCH_3_0_MARKER,
+};
 use crate::cost_estimates::{CostEstimator, FeeEstimator};
 use crate::stacks_common::deps_common::bitcoin::network::serialize::BitcoinHash;
 use crate::util_lib::db::*;
@@ -1172,3 +1175,42 @@ fn mine_10_stacks_blocks_2_forks_disjoint_same_blocks() {
         prev_keys_2.append(&mut next_prev_keys_2);
     }
 }
+
+#[test]
+fn reward_cycle_info_matches_pox_constants() {
+    for (pox_constants, first_block_height) in [
+        (
+            PoxConstants::mainnet_default(),
+            BITCOIN_MAINNET_FIRST_BLOCK_HEIGHT,
+        ),
+        (
+            PoxConstants::testnet_default(),
+            BITCOIN_TESTNET_FIRST_BLOCK_HEIGHT,
+        ),
+        (PoxConstants::regtest_default(), 0),
+    ] {
+        let info = pox_constants.reward_cycle_info(first_block_height);
+        let cycle_length = u64::from(pox_constants.reward_cycle_length);
+        let end_height = first_block_height + 3 * cycle_length;
+        for burn_height in first_block_height.saturating_sub(2)..end_height {
+            assert_eq!(
+                info.cycle_of(burn_height),
+                pox_constants.block_height_to_reward_cycle(first_block_height, burn_height)
+            );
+            assert_eq!(
+                info.cycle_index(burn_height),
+                pox_constants.reward_cycle_index(first_block_height, burn_height)
+            );
+            assert_eq!(
+                info.is_in_prepare_phase(burn_height),
+                pox_constants.is_in_prepare_phase(first_block_height, burn_height)
+            );
+        }
+        for reward_cycle in 0..100 {
+            assert_eq!(
+                info.first_height_of(reward_cycle),
+                pox_constants.reward_cycle_to_block_height(first_block_height, reward_cycle)
+            );
+        }
+    }
+}
@@ -33,7 +33,7 @@ use stacks_common::types::chainstate::{
 use stacks_common::util::get_epoch_time_secs;
 
 pub use self::comm::CoordinatorCommunication;
-use super::stacks::boot::{RewardSet, RewardSetData};
+use super::stacks::boot::{RewardSet, RewardSetData, SIGNER_BITVEC_MAX_SIZE};
 use super::stacks::db::blocks::DummyEventDispatcher;
 use crate::burnchains::affirmation::{AffirmationMap, AffirmationMapEntry};
 use crate::burnchains::bitcoin::indexer::BitcoinIndexer;
@@ -178,7 +178,7 @@ pub trait BlockEventDispatcher {
         mblock_confirmed_consumed: &ExecutionCost,
         pox_constants: &PoxConstants,
         reward_set_data: &Option<RewardSetData>,
-        signer_bitvec: &Option<BitVec<4000>>,
+        signer_bitvec: &Option<BitVec<SIGNER_BITVEC_MAX_SIZE>>,
     );
 
     /// called whenever a burn block is about to be
@@ -58,7 +58,7 @@ use crate::chainstate::burn::*;
 use crate::chainstate::coordinator::{Error as CoordError, *};
 use crate::chainstate::stacks::address::{PoxAddress, PoxAddressType32};
 use crate::chainstate::stacks::boot::{
-    PoxStartCycleInfo, COSTS_2_NAME, POX_1_NAME, POX_2_NAME, POX_3_NAME,
+    PoxStartCycleInfo, COSTS_2_NAME, POX_1_NAME, POX_2_NAME, POX_3_NAME, SIGNER_BITVEC_MAX_SIZE,
 };
 use crate::chainstate::stacks::db::accounts::MinerReward;
 use crate::chainstate::stacks::db::{ClarityTx, StacksChainState, StacksHeaderInfo};
@@ -429,7 +429,7 @@ impl BlockEventDispatcher for NullEventDispatcher {
         _confirmed_mblock_cost: &ExecutionCost,
         _pox_constants: &PoxConstants,
         _reward_set_data: &Option<RewardSetData>,
-        _signer_bitvec: &Option<BitVec<4000>>,
+        _signer_bitvec: &Option<BitVec<SIGNER_BITVEC_MAX_SIZE>>,
     ) {
         assert!(
             false,
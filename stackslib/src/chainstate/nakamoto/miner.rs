@@ -116,6 +116,8 @@ pub struct NakamotoBlockBuilder {
     matured_miner_rewards_opt: Option<MaturedMinerRewards>,
     /// bytes of space consumed so far
     pub bytes_so_far: u64,
+    /// most bytes of space the block may consume
+    max_bytes: u64,
     /// transactions selected
     txs: Vec<StacksTransaction>,
     /// header we're filling in
@@ -151,6 +153,7 @@ impl NakamotoBlockBuilder {
             tenure_tx: Some(tenure_change.clone()),
             matured_miner_rewards_opt: None,
             bytes_so_far: 0,
+            max_bytes: MAX_EPOCH_SIZE.into(),
             txs: vec![],
             header: NakamotoBlockHeader::genesis(),
         }
@@ -203,6 +206,7 @@ impl NakamotoBlockBuilder {
             tenure_tx: tenure_change.cloned(),
             matured_miner_rewards_opt: None,
             bytes_so_far: 0,
+            max_bytes: MAX_EPOCH_SIZE.into(),
             txs: vec![],
             header: NakamotoBlockHeader::from_parent_empty(
                 next_height,
@@ -431,6 +435,9 @@ impl NakamotoBlockBuilder {
             tenure_info.coinbase_tx(),
             signer_bitvec_len,
         )?;
+        if let Some(max_block_size) = settings.max_block_size {
+            builder.max_bytes = builder.max_bytes.min(max_block_size);
+        }
 
         let ts_start = get_epoch_time_ms();
 
@@ -524,7 +531,7 @@ impl BlockBuilder for NakamotoBlockBuilder {
         limit_behavior: &BlockLimitFunction,
         ast_rules: ASTRules,
     ) -> TransactionResult {
-        if self.bytes_so_far + tx_len >= MAX_EPOCH_SIZE.into() {
+        if self.bytes_so_far + tx_len >= self.max_bytes {
             return TransactionResult::skipped_due_to_error(&tx, Error::BlockTooBigError);
         }
 
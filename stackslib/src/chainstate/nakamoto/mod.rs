@@ -59,6 +59,7 @@ use super::burn::operations::{DelegateStxOp, StackStxOp, TransferStxOp, VoteForA
 use super::stacks::boot::{
     PoxVersions, RawRewardSetEntry, RewardSet, RewardSetData, BOOT_TEST_POX_4_AGG_KEY_CONTRACT,
     BOOT_TEST_POX_4_AGG_KEY_FNAME, SIGNERS_MAX_LIST_SIZE, SIGNERS_NAME, SIGNERS_PK_LEN,
+    SIGNER_BITVEC_MAX_SIZE,
 };
 use super::stacks::db::accounts::MinerReward;
 use super::stacks::db::{
@@ -308,8 +309,8 @@ pub struct NakamotoBlockHeader {
     /// Schnorr signature over the block header from the signer set active during the tenure.
     pub signer_signature: ThresholdSignature,
     /// A bitvec which represents the signers that participated in this block signature.
-    /// The maximum number of entries in the bitvec is 4000.
-    pub signer_bitvec: BitVec<4000>,
+    /// The bitvec has at most `SIGNER_BITVEC_MAX_SIZE` entries, one per signer.
+    pub signer_bitvec: BitVec<SIGNER_BITVEC_MAX_SIZE>,
 }
 
 impl FromRow<NakamotoBlockHeader> for NakamotoBlockHeader {
@@ -3227,6 +3228,40 @@ impl NakamotoChainState {
         Ok((epoch_receipt, clarity_commit, reward_set_data))
     }
 
+    /// Get the hash160 of the Nakamoto signing key registered by the miner that won the
+    /// sortition in `sn`. This is the key the miner signs its blocks and `.miners` chunks with.
+    pub fn get_winning_miner_key_hash160(
+        ih: &SortitionHandleConn,
+        sn: &BlockSnapshot,
+    ) -> Result<Hash160, ChainstateError> {
+        // find the commit
+        let Some(block_commit) =
+            ih.get_block_commit_by_txid(&sn.sortition_id, &sn.winning_block_txid)?
+        else {
+            warn!(
+                "No block commit for {} in sortition for {}",
+                &sn.winning_block_txid, &sn.consensus_hash
+            );
+            return Err(ChainstateError::InvalidStacksBlock(
+                "No block-commit in sortition for block's consensus hash".into(),
+            ));
+        };
+
+        // key register of the winning miner
+        let leader_key = ih
+            .get_leader_key_at(
+                u64::from(block_commit.key_block_ptr),
+                u32::from(block_commit.key_vtxindex),
+            )?
+            .expect("FATAL: have block commit but no leader key");
+
+        // the leader key should always be valid (i.e. the unwrap_or() should be unreachable),
+        // but be defensive and just use the "null" address
+        Ok(leader_key
+            .interpret_nakamoto_signing_key()
+            .unwrap_or(Hash160([0x00; 20])))
+    }
+
     /// Create a StackerDB config for the .miners contract.
     /// It has two slots -- one for the past two sortition winners.
     pub fn make_miners_stackerdb_config(
@@ -3251,34 +3286,7 @@ impl NakamotoChainState {
         };
 
         for sn in sns {
-            // find the commit
-            let Some(block_commit) =
-                ih.get_block_commit_by_txid(&sn.sortition_id, &sn.winning_block_txid)?
-            else {
-                warn!(
-                    "No block commit for {} in sortition for {}",
-                    &sn.winning_block_txid, &sn.consensus_hash
-                );
-                return Err(ChainstateError::InvalidStacksBlock(
-                    "No block-commit in sortition for block's consensus hash".into(),
-                ));
-            };
-
-            // key register of the winning miner
-            let leader_key = ih
-                .get_leader_key_at(
-                    u64::from(block_commit.key_block_ptr),
-                    u32::from(block_commit.key_vtxindex),
-                )?
-                .expect("FATAL: have block commit but no leader key");
-
-            // the leader key should always be valid (i.e. the unwrap_or() should be unreachable),
-            // but be defensive and just use the "null" address
-            miner_key_hash160s.push(
-                leader_key
-                    .interpret_nakamoto_signing_key()
-                    .unwrap_or(Hash160([0x00; 20])),
-            );
+            miner_key_hash160s.push(Self::get_winning_miner_key_hash160(&ih, &sn)?);
         }
 
         let signers = miner_key_hash160s
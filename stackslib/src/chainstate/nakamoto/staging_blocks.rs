@@ -23,6 +23,7 @@ use rusqlite::blob::Blob;
 use rusqlite::types::{FromSql, FromSqlError};
 use rusqlite::{params, Connection, OpenFlags, OptionalExtension, ToSql, NO_PARAMS};
 use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::util::{get_epoch_time_secs, sleep_ms};
 
 use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionHandle};
@@ -269,6 +270,31 @@ impl<'a> NakamotoStagingBlocksConnRef<'a> {
         )))
     }
 
+    /// Get a staged Nakamoto block of tenure `consensus_hash` at `height` by its signer signature
+    /// hash. Unlike its index block hash, this does not cover the signer signature, so it finds
+    /// a block whether or not it has been signed.
+    /// Returns Ok(Some(block)) if the block was present
+    /// Returns Ok(None) if there was no such block
+    /// Returns Err(..) on DB error, including block corruption
+    pub fn get_nakamoto_block_by_signer_sighash(
+        &self,
+        consensus_hash: &ConsensusHash,
+        height: u64,
+        signer_sighash: &Sha512Trunc256Sum,
+    ) -> Result<Option<NakamotoBlock>, ChainstateError> {
+        let qry =
+            "SELECT data FROM nakamoto_staging_blocks WHERE consensus_hash = ?1 AND height = ?2";
+        let args: &[&dyn ToSql] = &[consensus_hash, &u64_to_sql(height)?];
+        let rows: Vec<Vec<u8>> = query_rows(self, qry, args)?;
+        for block_bytes in rows {
+            let block = NakamotoBlock::consensus_deserialize(&mut block_bytes.as_slice())?;
+            if &block.header.signer_signature_hash() == signer_sighash {
+                return Ok(Some(block));
+            }
+        }
+        Ok(None)
+    }
+
     /// Get the size of a Nakamoto block, given its index block hash
     /// Returns Ok(Some(size)) if the block was present
     /// Returns Ok(None) if there was no such block
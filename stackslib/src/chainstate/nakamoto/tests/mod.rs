@@ -1059,6 +1059,28 @@ pub fn test_load_store_update_nakamoto_blocks() {
         None
     );
 
+    // can find a staged block by its signer signature hash, which does not cover the signature
+    assert_eq!(
+        nakamoto_blocks_db
+            .get_nakamoto_block_by_signer_sighash(
+                &nakamoto_header_2.consensus_hash,
+                nakamoto_header_2.chain_length,
+                &nakamoto_header_2.signer_signature_hash(),
+            )
+            .unwrap(),
+        Some(nakamoto_block_2.clone())
+    );
+    assert_eq!(
+        nakamoto_blocks_db
+            .get_nakamoto_block_by_signer_sighash(
+                &nakamoto_header_2.consensus_hash,
+                nakamoto_header_2.chain_length,
+                &nakamoto_header.signer_signature_hash(),
+            )
+            .unwrap(),
+        None
+    );
+
     // nakamoto block should be treated as processed because even though the processed flag is not
     // set, the header is present (meaning that we're in-between processing the block and marking
     // it processed in the staging DB)
@@ -1991,6 +2013,18 @@ fn test_make_miners_stackerdb_config() {
             .unwrap()
             .unwrap();
 
+        if sortition {
+            // the sortition was won by this miner's key, and not by anyone else's
+            let ih = sort_db.index_handle(&last_snapshot.sortition_id);
+            let winner_key_hash =
+                NakamotoChainState::get_winning_miner_key_hash160(&ih, &last_snapshot).unwrap();
+            assert_eq!(winner_key_hash, miner_hash160s[i]);
+            assert_ne!(
+                winner_key_hash,
+                miner_hash160s[(i + 1) % miner_hash160s.len()]
+            );
+        }
+
         let tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn()).unwrap();
         // check the stackerdb config as of this chain tip
         let stackerdb_config =
@@ -83,6 +83,10 @@ pub const SIGNERS_VOTING_FUNCTION_NAME: &str = "vote-for-aggregate-public-key";
 /// reward cycle number.
 pub const SIGNERS_UPDATE_STATE: &'static str = "last-set-cycle";
 pub const SIGNERS_MAX_LIST_SIZE: usize = 4000;
+/// The most entries a signer bitvec can have: one per signer in the largest signer list the
+/// `.signers` contract accepts
+pub const SIGNER_BITVEC_MAX_SIZE: u16 = SIGNERS_MAX_LIST_SIZE as u16;
+const _: () = assert!(SIGNERS_MAX_LIST_SIZE <= u16::MAX as usize);
 pub const SIGNERS_PK_LEN: usize = 33;
 
 const POX_2_BODY: &'static str = std::include_str!("pox-2.clar");
@@ -275,6 +279,49 @@ impl RewardSet {
     pub fn metadata_deserialize(from: &str) -> Result<RewardSet, String> {
         serde_json::from_str(from).map_err(|e| e.to_string())
     }
+
+    /// The total weight of the reward set's signers, including those with malformed signing
+    /// keys. Errors if this is not a Nakamoto reward set, or if the weights do not fit in a
+    /// u32, so that anything summing a subset of the signers' weights cannot overflow.
+    pub fn total_signing_weight(&self) -> Result<u32, Error> {
+        let Some(signers) = self.signers.as_ref() else {
+            return Err(Error::InvalidStacksBlock(
+                "Invalid Reward Set: no signers".into(),
+            ));
+        };
+        signers
+            .iter()
+            .try_fold(0u32, |total, signer| total.checked_add(signer.weight))
+            .ok_or_else(|| {
+                Error::InvalidStacksBlock(
+                    "Invalid Reward Set: signer weights exceed u32::MAX".into(),
+                )
+            })
+    }
+}
+
+/// The share of the signers' total weight, in percent, that must sign a Nakamoto block
+pub const SIGNING_THRESHOLD_PERCENT: u64 = 70;
+
+/// The signing weight needed to sign a Nakamoto block when the signers hold `total_weight`
+/// in all: `SIGNING_THRESHOLD_PERCENT` of it, rounded up.
+pub fn signing_threshold_of(total_weight: u32) -> u32 {
+    let threshold = (u64::from(total_weight) * SIGNING_THRESHOLD_PERCENT + 99) / 100;
+    u32::try_from(threshold).expect("FATAL: signing threshold exceeds the total weight")
+}
+
+/// The signing weight needed to sign a block with `reward_set`'s signers, and the total weight
+/// they hold, as `(threshold, total_weight)`. Miners and signers must agree on both, so both
+/// compute them here. Signers with malformed signing keys still count towards the total.
+/// Errors if the reward set has no signers, or their weights do not fit in a u32.
+pub fn signing_threshold_for(reward_set: &RewardSet) -> Result<(u32, u32), Error> {
+    let total_weight = reward_set.total_signing_weight()?;
+    if total_weight == 0 {
+        return Err(Error::InvalidStacksBlock(
+            "Invalid Reward Set: signers hold no weight".into(),
+        ));
+    }
+    Ok((signing_threshold_of(total_weight), total_weight))
 }
 
 impl RewardSetData {
@@ -1403,6 +1450,83 @@ pub mod test {
         PoxAddress::try_from_pox_tuple(false, &Value::Tuple(tuple_data)).unwrap()
     }
 
+    fn reward_set_with_weights(weights: &[u32]) -> RewardSet {
+        let mut reward_set = RewardSet::empty();
+        reward_set.signers = Some(
+            weights
+                .iter()
+                .map(|weight| NakamotoSignerEntry {
+                    signing_key: [0x02; 33],
+                    stacked_amt: 0,
+                    weight: *weight,
+                })
+                .collect(),
+        );
+        reward_set
+    }
+
+    #[test]
+    fn signing_threshold_with_equal_weights() {
+        let threshold_for =
+            |weights: &[u32]| signing_threshold_for(&reward_set_with_weights(weights)).unwrap();
+        assert_eq!(threshold_for(&[1]), (1, 1));
+        assert_eq!(threshold_for(&[1; 3]), (3, 3));
+        assert_eq!(threshold_for(&[1; 10]), (7, 10));
+        assert_eq!(threshold_for(&[5; 4]), (14, 20));
+        assert_eq!(threshold_for(&[1; 4000]), (2800, 4000));
+    }
+
+    #[test]
+    fn signing_threshold_with_a_dominant_signer() {
+        let threshold_for =
+            |weights: &[u32]| signing_threshold_for(&reward_set_with_weights(weights)).unwrap();
+        // 70.7 rounds up, so the dominant signer cannot sign alone
+        assert_eq!(threshold_for(&[70, 31]), (71, 101));
+        assert_eq!(threshold_for(&[70, 30]), (70, 100));
+        assert_eq!(threshold_for(&[100, 1, 1, 1]), (73, 103));
+        assert_eq!(threshold_for(&[u32::MAX]), (3006477107, u32::MAX));
+    }
+
+    #[test]
+    fn signing_threshold_rounds_up_at_70_percent() {
+        assert_eq!(signing_threshold_of(0), 0);
+        assert_eq!(signing_threshold_of(10), 7);
+        assert_eq!(signing_threshold_of(11), 8);
+        assert_eq!(signing_threshold_of(30), 21);
+        assert_eq!(signing_threshold_of(31), 22);
+        assert_eq!(signing_threshold_of(100), 70);
+        assert_eq!(signing_threshold_of(101), 71);
+        // the same as the floating point threshold signers used to compute
+        for total_weight in (0..100_000).chain(u32::MAX - 100_000..=u32::MAX) {
+            let float_threshold = (total_weight as f64 * 7_f64 / 10_f64).ceil() as u32;
+            assert_eq!(signing_threshold_of(total_weight), float_threshold);
+        }
+    }
+
+    #[test]
+    fn total_signing_weight_must_fit_in_a_u32() {
+        let total_weight_of =
+            |weights: &[u32]| reward_set_with_weights(weights).total_signing_weight();
+        assert_eq!(total_weight_of(&[]).unwrap(), 0);
+        assert_eq!(total_weight_of(&[u32::MAX - 1, 1]).unwrap(), u32::MAX);
+        assert_eq!(
+            total_weight_of(&[u32::MAX / 2, u32::MAX / 2]).unwrap(),
+            u32::MAX - 1
+        );
+        assert!(total_weight_of(&[u32::MAX - 1, 2]).is_err());
+        assert!(total_weight_of(&[u32::MAX / 2 + 1, u32::MAX / 2 + 1]).is_err());
+        assert!(total_weight_of(&[u32::MAX, 1, 0]).is_err());
+        assert!(RewardSet::empty().total_signing_weight().is_err());
+    }
+
+    #[test]
+    fn signing_threshold_rejects_unusable_reward_sets() {
+        assert!(signing_threshold_for(&RewardSet::empty()).is_err());
+        assert!(signing_threshold_for(&reward_set_with_weights(&[])).is_err());
+        assert!(signing_threshold_for(&reward_set_with_weights(&[0, 0])).is_err());
+        assert!(signing_threshold_for(&reward_set_with_weights(&[u32::MAX, 1])).is_err());
+    }
+
     #[test]
     fn make_reward_set_units() {
         let threshold = 1_000;
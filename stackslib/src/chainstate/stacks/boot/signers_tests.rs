@@ -25,7 +25,7 @@ use clarity::vm::Value::Principal;
 use clarity::vm::{ClarityName, ClarityVersion, ContractName, Value};
 use stacks_common::address::AddressHashMode;
 use stacks_common::consts;
-use stacks_common::consts::SIGNER_SLOTS_PER_USER;
+use stacks_common::consts::SIGNER_SLOTS_PER_USER_EPOCH_2_5;
 use stacks_common::types::chainstate::{
     BurnchainHeaderHash, StacksAddress, StacksBlockId, StacksPrivateKey, StacksPublicKey,
 };
@@ -198,7 +198,7 @@ fn signers_get_config() {
     );
 
     for signer_set in 0..2 {
-        for message_id in 0..SIGNER_SLOTS_PER_USER {
+        for message_id in 0..SIGNER_SLOTS_PER_USER_EPOCH_2_5 {
             let contract_name = format!("signers-{}-{}", &signer_set, &message_id);
             let config = readonly_call(
                 &mut peer,
@@ -321,7 +321,7 @@ fn signers_db_get_slots() {
     .unwrap();
 
     for signer_set in 0..2 {
-        for message_id in 0..SIGNER_SLOTS_PER_USER {
+        for message_id in 0..SIGNER_SLOTS_PER_USER_EPOCH_2_5 {
             let contract_name = format!("signers-{}-{}", &signer_set, &message_id);
             let signers = readonly_call(
                 &mut peer,
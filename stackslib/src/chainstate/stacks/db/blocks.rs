@@ -55,6 +55,7 @@ use crate::chainstate::coordinator::BlockEventDispatcher;
 use crate::chainstate::nakamoto::signer_set::{NakamotoSigners, SignerCalculation};
 use crate::chainstate::nakamoto::NakamotoChainState;
 use crate::chainstate::stacks::address::{PoxAddress, StacksAddressExtensions};
+use crate::chainstate::stacks::boot::SIGNER_BITVEC_MAX_SIZE;
 use crate::chainstate::stacks::db::accounts::MinerReward;
 use crate::chainstate::stacks::db::transactions::TransactionNonceMismatch;
 use crate::chainstate::stacks::db::*;
@@ -185,7 +186,7 @@ impl BlockEventDispatcher for DummyEventDispatcher {
         _confirmed_mblock_cost: &ExecutionCost,
         _pox_constants: &PoxConstants,
         _reward_set_data: &Option<RewardSetData>,
-        _signer_bitvec: &Option<BitVec<4000>>,
+        _signer_bitvec: &Option<BitVec<SIGNER_BITVEC_MAX_SIZE>>,
     ) {
         assert!(
             false,
@@ -167,6 +167,9 @@ pub struct BlockBuilderSettings {
     pub miner_status: Arc<Mutex<MinerStatus>>,
     /// Should the builder attempt to confirm any parent microblocks
     pub confirm_microblocks: bool,
+    /// Largest size of the block, in bytes, if it should be smaller than the epoch allows.
+    /// Only honored when building Nakamoto blocks.
+    pub max_block_size: Option<u64>,
 }
 
 impl BlockBuilderSettings {
@@ -176,6 +179,7 @@ impl BlockBuilderSettings {
             mempool_settings: MemPoolWalkSettings::default(),
             miner_status: Arc::new(Mutex::new(MinerStatus::make_ready(0))),
             confirm_microblocks: true,
+            max_block_size: None,
         }
     }
 
@@ -185,6 +189,7 @@ impl BlockBuilderSettings {
             mempool_settings: MemPoolWalkSettings::zero(),
             miner_status: Arc::new(Mutex::new(MinerStatus::make_ready(0))),
             confirm_microblocks: true,
+            max_block_size: None,
         }
     }
 }
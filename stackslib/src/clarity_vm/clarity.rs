@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::ops::Range;
 use std::{error, fmt, thread};
 
 use clarity::vm::analysis::errors::{CheckError, CheckErrors};
@@ -35,7 +36,9 @@ use clarity::vm::types::{
     TypeSignature, Value,
 };
 use clarity::vm::{analysis, ast, ClarityVersion, ContractName};
-use stacks_common::consts::{CHAIN_ID_TESTNET, SIGNER_SLOTS_PER_USER};
+use stacks_common::consts::{
+    CHAIN_ID_TESTNET, SIGNER_SLOTS_PER_USER, SIGNER_SLOTS_PER_USER_EPOCH_2_5,
+};
 use stacks_common::types::chainstate::{
     BlockHeaderHash, BurnchainHeaderHash, SortitionId, StacksAddress, StacksBlockId, TrieHash,
 };
@@ -1409,53 +1412,9 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
             receipts.push(signers_initialization_receipt);
 
             // stackerdb contracts for each message type
-            for signer_set in 0..2 {
-                for message_id in 0..SIGNER_SLOTS_PER_USER {
-                    let signers_name =
-                        NakamotoSigners::make_signers_db_name(signer_set, message_id);
-                    let body = if signer_set == 0 {
-                        SIGNERS_DB_0_BODY
-                    } else {
-                        SIGNERS_DB_1_BODY
-                    };
-                    let payload = TransactionPayload::SmartContract(
-                        TransactionSmartContract {
-                            name: ContractName::try_from(signers_name.clone())
-                                .expect("FATAL: invalid boot-code contract name"),
-                            code_body: StacksString::from_str(body)
-                                .expect("FATAL: invalid boot code body"),
-                        },
-                        Some(ClarityVersion::Clarity2),
-                    );
-
-                    let signers_contract_tx =
-                        StacksTransaction::new(tx_version.clone(), boot_code_auth.clone(), payload);
-
-                    let signers_db_receipt = self.as_transaction(|tx_conn| {
-                        // initialize with a synthetic transaction
-                        debug!("Instantiate .{} contract", &signers_name);
-                        let receipt = StacksChainState::process_transaction_payload(
-                            tx_conn,
-                            &signers_contract_tx,
-                            &boot_code_account,
-                            ASTRules::PrecheckSize,
-                        )
-                        .expect("FATAL: Failed to process .signers DB contract initialization");
-                        receipt
-                    });
-
-                    if signers_db_receipt.result != Value::okay_true()
-                        || signers_db_receipt.post_condition_aborted
-                    {
-                        panic!(
-                            "FATAL: Failure processing signers DB contract initialization: {:#?}",
-                            &signers_db_receipt
-                        );
-                    }
-
-                    receipts.push(signers_db_receipt);
-                }
-            }
+            receipts.append(
+                &mut self.initialize_signers_db_contracts(0..SIGNER_SLOTS_PER_USER_EPOCH_2_5),
+            );
 
             let signers_voting_contract_id = boot_code_id(SIGNERS_VOTING_NAME, mainnet);
             let payload = TransactionPayload::SmartContract(
@@ -1520,11 +1479,84 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
                 tx_conn.epoch = StacksEpochId::Epoch30;
             });
 
+            // stackerdb contracts for the message types added since Epoch 2.5
+            let receipts = self.initialize_signers_db_contracts(
+                SIGNER_SLOTS_PER_USER_EPOCH_2_5..SIGNER_SLOTS_PER_USER,
+            );
+
             debug!("Epoch 3.0 initialized");
-            (old_cost_tracker, Ok(vec![]))
+            (old_cost_tracker, Ok(receipts))
         })
     }
 
+    /// Instantiate the `.signers-{0,1}-{message_id}` StackerDB contracts for the given
+    ///  message IDs with synthetic boot code transactions.
+    /// Must be called while the cost tracker is free (i.e., during epoch initialization).
+    fn initialize_signers_db_contracts(
+        &mut self,
+        message_ids: Range<u32>,
+    ) -> Vec<StacksTransactionReceipt> {
+        let mainnet = self.mainnet;
+        let tx_version = if mainnet {
+            TransactionVersion::Mainnet
+        } else {
+            TransactionVersion::Testnet
+        };
+        let boot_code_account = self
+            .get_boot_code_account()
+            .expect("FATAL: did not get boot account");
+        let boot_code_auth = boot_code_tx_auth(boot_code_addr(mainnet));
+
+        let mut receipts = vec![];
+        for signer_set in 0..2 {
+            for message_id in message_ids.clone() {
+                let signers_name = NakamotoSigners::make_signers_db_name(signer_set, message_id);
+                let body = if signer_set == 0 {
+                    SIGNERS_DB_0_BODY
+                } else {
+                    SIGNERS_DB_1_BODY
+                };
+                let payload = TransactionPayload::SmartContract(
+                    TransactionSmartContract {
+                        name: ContractName::try_from(signers_name.clone())
+                            .expect("FATAL: invalid boot-code contract name"),
+                        code_body: StacksString::from_str(body)
+                            .expect("FATAL: invalid boot code body"),
+                    },
+                    Some(ClarityVersion::Clarity2),
+                );
+
+                let signers_contract_tx =
+                    StacksTransaction::new(tx_version.clone(), boot_code_auth.clone(), payload);
+
+                let signers_db_receipt = self.as_transaction(|tx_conn| {
+                    // initialize with a synthetic transaction
+                    debug!("Instantiate .{} contract", &signers_name);
+                    let receipt = StacksChainState::process_transaction_payload(
+                        tx_conn,
+                        &signers_contract_tx,
+                        &boot_code_account,
+                        ASTRules::PrecheckSize,
+                    )
+                    .expect("FATAL: Failed to process .signers DB contract initialization");
+                    receipt
+                });
+
+                if signers_db_receipt.result != Value::okay_true()
+                    || signers_db_receipt.post_condition_aborted
+                {
+                    panic!(
+                        "FATAL: Failure processing signers DB contract initialization: {:#?}",
+                        &signers_db_receipt
+                    );
+                }
+
+                receipts.push(signers_db_receipt);
+            }
+        }
+        receipts
+    }
+
     pub fn start_transaction_processing<'c>(&'c mut self) -> ClarityTransactionConnection<'c, 'a> {
         let store = &mut self.datastore;
         let cost_track = &mut self.cost_track;
@@ -179,6 +179,78 @@ pub fn increment_stx_blocks_mined_counter() {
     prometheus::STX_BLOCKS_MINED_COUNTER.inc();
 }
 
+pub fn increment_naka_blocks_proposed_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_BLOCKS_PROPOSED_COUNTER.inc();
+}
+
+pub fn increment_naka_blocks_accepted_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_BLOCKS_ACCEPTED_COUNTER.inc();
+}
+
+pub fn increment_naka_blocks_rejected_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_BLOCKS_REJECTED_COUNTER.inc();
+}
+
+pub fn increment_naka_signing_rounds_timed_out_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_SIGNING_ROUNDS_TIMED_OUT_COUNTER.inc();
+}
+
+#[allow(unused_variables)]
+pub fn update_naka_signing_round_attempt(attempt: u64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_SIGNING_ROUND_ATTEMPT_GAUGE.set(attempt.try_into().unwrap_or(i64::MAX));
+}
+
+#[allow(unused_variables)]
+pub fn increment_naka_duplicate_signer_chunks_counter(count: u64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_DUPLICATE_SIGNER_CHUNKS_COUNTER.inc_by(count);
+}
+
+#[allow(unused_variables)]
+pub fn increment_naka_oversized_signer_chunks_counter(count: u64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_OVERSIZED_SIGNER_CHUNKS_COUNTER.inc_by(count);
+}
+
+pub fn increment_naka_proposal_rewrites_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_PROPOSAL_REWRITES_COUNTER.inc();
+}
+
+pub fn increment_naka_ignored_signer_responses_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_IGNORED_SIGNER_RESPONSES_COUNTER.inc();
+}
+
+#[allow(unused_variables)]
+pub fn update_naka_tenure_signer_responses(
+    signer_pubkey: &str,
+    accepted: u64,
+    rejected: u64,
+    missed: u64,
+) {
+    #[cfg(feature = "monitoring_prom")]
+    for (response, count) in [
+        ("accepted", accepted),
+        ("rejected", rejected),
+        ("missed", missed),
+    ] {
+        prometheus::NAKA_TENURE_SIGNER_RESPONSES_GAUGE_VEC
+            .with_label_values(&[signer_pubkey, response])
+            .set(count.try_into().unwrap_or(i64::MAX));
+    }
+}
+
+pub fn reset_naka_tenure_signer_responses() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::NAKA_TENURE_SIGNER_RESPONSES_GAUGE_VEC.reset();
+}
+
 pub fn increment_warning_emitted_counter() {
     #[cfg(feature = "monitoring_prom")]
     prometheus::WARNING_EMITTED_COUNTER.inc();
@@ -299,6 +371,27 @@ pub fn update_burnchain_height(value: i64) {
     prometheus::BURNCHAIN_HEIGHT_GAUGE.set(value);
 }
 
+/// Report how the signing weight of the reward set the miner is signing with is spread
+#[allow(unused_variables)]
+pub fn update_signer_weight_stats(
+    num_signers: i64,
+    min_weight: i64,
+    max_weight: i64,
+    median_weight: f64,
+    top_5_share: f64,
+    hhi: f64,
+) {
+    #[cfg(feature = "monitoring_prom")]
+    {
+        prometheus::SIGNER_SET_SIZE_GAUGE.set(num_signers);
+        prometheus::SIGNER_WEIGHT_MIN_GAUGE.set(min_weight);
+        prometheus::SIGNER_WEIGHT_MAX_GAUGE.set(max_weight);
+        prometheus::SIGNER_WEIGHT_MEDIAN_GAUGE.set(median_weight);
+        prometheus::SIGNER_WEIGHT_TOP_5_SHARE_GAUGE.set(top_5_share);
+        prometheus::SIGNER_WEIGHT_HHI_GAUGE.set(hhi);
+    }
+}
+
 #[allow(unused_variables)]
 pub fn update_inbound_neighbors(value: i64) {
     #[cfg(feature = "monitoring_prom")]
@@ -17,8 +17,9 @@
 use lazy_static::lazy_static;
 use prometheus::{
     histogram_opts, labels, opts, register_gauge, register_histogram, register_histogram_vec,
-    register_int_counter, register_int_counter_vec, register_int_gauge, Gauge, Histogram,
-    HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    register_int_counter, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    Gauge, Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec,
 };
 
 lazy_static! {
@@ -84,6 +85,57 @@ lazy_static! {
         "Total number of stacks blocks mined by node"
     )).unwrap();
 
+    pub static ref NAKA_BLOCKS_PROPOSED_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_blocks_proposed_total",
+        "Total number of Nakamoto blocks proposed to the signers by node"
+    )).unwrap();
+
+    pub static ref NAKA_BLOCKS_ACCEPTED_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_blocks_accepted_total",
+        "Total number of proposed Nakamoto blocks the signers produced a signature for"
+    )).unwrap();
+
+    pub static ref NAKA_BLOCKS_REJECTED_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_blocks_rejected_total",
+        "Total number of signing rounds for proposed Nakamoto blocks that failed"
+    )).unwrap();
+
+    pub static ref NAKA_SIGNING_ROUNDS_TIMED_OUT_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_signing_rounds_timed_out_total",
+        "Total number of signing rounds for proposed Nakamoto blocks that timed out"
+    )).unwrap();
+
+    pub static ref NAKA_SIGNING_ROUND_ATTEMPT_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_naka_signing_round_attempt",
+        "The signing attempt of the block proposal the miner is collecting signatures for"
+    )).unwrap();
+
+    pub static ref NAKA_DUPLICATE_SIGNER_CHUNKS_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_duplicate_signer_chunks_total",
+        "Total number of signer StackerDB chunks delivered to the sign coordinator again after they were processed"
+    )).unwrap();
+
+    pub static ref NAKA_OVERSIZED_SIGNER_CHUNKS_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_oversized_signer_chunks_total",
+        "Total number of signer StackerDB chunks the sign coordinator dropped unread because they were longer than any message of their type"
+    )).unwrap();
+
+    pub static ref NAKA_PROPOSAL_REWRITES_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_proposal_rewrites_total",
+        "Total number of block proposals the sign coordinator wrote again because its own StackerDB did not hold them"
+    )).unwrap();
+
+    pub static ref NAKA_IGNORED_SIGNER_RESPONSES_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_naka_ignored_signer_responses_total",
+        "Total number of signer responses the sign coordinator dropped because the signer's key is in miner.ignored_signer_keys"
+    )).unwrap();
+
+    pub static ref NAKA_TENURE_SIGNER_RESPONSES_GAUGE_VEC: IntGaugeVec = register_int_gauge_vec!(
+        "stacks_node_naka_tenure_signer_responses",
+        "Number of the current tenure's block proposals each signer accepted, rejected or missed",
+        &["signer_pubkey", "response"]
+    ).unwrap();
+
     pub static ref WARNING_EMITTED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_warning_emitted_total",
         "Total number of warning logs emitted by node"
@@ -230,6 +282,36 @@ lazy_static! {
         labels! {"handler".to_string() => "all".to_string(),}
     )).unwrap();
 
+    pub static ref SIGNER_SET_SIZE_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_signer_set_size",
+        "Number of signers in the reward set the miner is coordinating signing with"
+    )).unwrap();
+
+    pub static ref SIGNER_WEIGHT_MIN_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_signer_weight_min",
+        "Smallest signer weight in the reward set the miner is coordinating signing with"
+    )).unwrap();
+
+    pub static ref SIGNER_WEIGHT_MAX_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_signer_weight_max",
+        "Largest signer weight in the reward set the miner is coordinating signing with"
+    )).unwrap();
+
+    pub static ref SIGNER_WEIGHT_MEDIAN_GAUGE: Gauge = register_gauge!(opts!(
+        "stacks_node_signer_weight_median",
+        "Median signer weight in the reward set the miner is coordinating signing with"
+    )).unwrap();
+
+    pub static ref SIGNER_WEIGHT_TOP_5_SHARE_GAUGE: Gauge = register_gauge!(opts!(
+        "stacks_node_signer_weight_top_5_share",
+        "Share of the reward set's signing weight held by its 5 heaviest signers"
+    )).unwrap();
+
+    pub static ref SIGNER_WEIGHT_HHI_GAUGE: Gauge = register_gauge!(opts!(
+        "stacks_node_signer_weight_hhi",
+        "Herfindahl-Hirschman index of the signing weight in the reward set: 1 if a single signer holds all of it"
+    )).unwrap();
+
     pub static ref COMPUTED_RELATIVE_MINER_SCORE: Gauge = register_gauge!(opts!(
         "stacks_node_computed_relative_miner_score",
         "Percentage of the u256 range that this miner is assigned in a particular round of sortition"
@@ -0,0 +1,177 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::chainstate::ConsensusHash;
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use {serde, serde_json};
+
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{RPCRequestHandler, StacksHttpRequest, StacksHttpResponse};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Evidence that a signer voted to accept, and signed, two different blocks at the same
+/// position in a tenure. Recorded by the miner for observability only; it has no consensus
+/// impact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignerEquivocation {
+    /// The hex-encoded compressed public key of the signer
+    pub signer_key: String,
+    /// The consensus hash of the tenure the blocks are in
+    pub consensus_hash: ConsensusHash,
+    /// The height of the blocks
+    pub height: u64,
+    /// The signer signature hash of the block the signer voted to accept first
+    pub first_signer_sighash: Sha512Trunc256Sum,
+    /// The hex-encoded signer message holding the signer's signed WSTS nonce response for
+    /// the first block. Its signature covers the signer's vote, which names the block's
+    /// signer signature hash.
+    pub first_vote: String,
+    /// The signer signature hash of the conflicting block
+    pub second_signer_sighash: Sha512Trunc256Sum,
+    /// The hex-encoded signer message holding the signer's signed WSTS nonce response for
+    /// the conflicting block
+    pub second_vote: String,
+    /// When the conflicting signature was seen, in seconds since the epoch
+    pub detected_at: u64,
+}
+
+/// Where the node's miner records the signer equivocations it has seen
+pub trait SignerEquivocationSource {
+    /// All the signer equivocations recorded so far, oldest first
+    fn get_signer_equivocations(&self) -> Result<Vec<SignerEquivocation>, String>;
+}
+
+#[derive(Clone)]
+pub struct RPCGetMinerEquivocationsRequestHandler {}
+
+impl RPCGetMinerEquivocationsRequestHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetMinerEquivocationsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/miner/equivocations$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v2/miner/equivocations"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetMinerEquivocationsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {}
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let equivocations_res =
+            node.with_node_state(|_network, _sortdb, _chainstate, _mempool, rpc_args| {
+                rpc_args
+                    .equivocation_source
+                    .map(|source| source.get_signer_equivocations())
+            });
+        let equivocations = match equivocations_res {
+            Some(Ok(equivocations)) => equivocations,
+            None => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new("This node does not record signer equivocations\n".into()),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Some(Err(e)) => {
+                let msg = format!("Failed to load signer equivocations: {e}\n");
+                warn!("{msg}");
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        let preamble = HttpResponsePreamble::ok_json(&preamble);
+        let body = HttpResponseContents::try_from_json(&equivocations)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetMinerEquivocationsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let equivocations: Vec<SignerEquivocation> = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(equivocations)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the signer equivocations the node's miner has recorded
+    pub fn new_get_miner_equivocations(host: PeerHost) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v2/miner/equivocations".into(),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_miner_equivocations(self) -> Result<Vec<SignerEquivocation>, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let equivocations: Vec<SignerEquivocation> = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(equivocations)
+    }
+}
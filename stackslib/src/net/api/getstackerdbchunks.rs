@@ -0,0 +1,256 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clarity::vm::representations::{CONTRACT_NAME_REGEX_STRING, STANDARD_PRINCIPAL_REGEX_STRING};
+use clarity::vm::types::QualifiedContractIdentifier;
+use libstackerdb::STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST;
+use regex::{Captures, Regex};
+use stacks_common::codec::MAX_PAYLOAD_LEN;
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::hash::to_hex;
+use url::form_urlencoded;
+use {serde, serde_json};
+
+use crate::net::http::{
+    parse_json, Error, HttpContentType, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    request, HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// The latest chunks of a list of slots, in the order they were requested.
+/// Each chunk is hex-encoded, and a slot with no chunk is `null`.
+/// The list may be a strict prefix of the requested slots if the chunks would not all fit in one
+/// response; the client re-requests the remaining slots.
+pub type GetStackerDBLatestChunksResponse = Vec<Option<String>>;
+
+#[derive(Clone)]
+pub struct RPCGetStackerDBLatestChunksRequestHandler {
+    pub contract_identifier: Option<QualifiedContractIdentifier>,
+    pub slot_ids: Option<Vec<u32>>,
+}
+impl RPCGetStackerDBLatestChunksRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            contract_identifier: None,
+            slot_ids: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetStackerDBLatestChunksRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(&format!(
+            r#"^/v2/stackerdb/(?P<address>{})/(?P<contract>{})/latest_chunks$"#,
+            *STANDARD_PRINCIPAL_REGEX_STRING, *CONTRACT_NAME_REGEX_STRING
+        ))
+        .unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v2/stackerdb/:principal/:contract_name/latest_chunks"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let contract_identifier = request::get_contract_address(captures, "address", "contract")?;
+
+        let mut slot_ids = None;
+        for (key, value) in form_urlencoded::parse(query.unwrap_or("").as_bytes()) {
+            if key != "slot_ids" {
+                continue;
+            }
+            let ids = value
+                .split(',')
+                .map(|entry| entry.parse::<u32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| {
+                    Error::DecodeError("Invalid Http request: malformed slot_ids".to_string())
+                })?;
+            slot_ids = Some(ids);
+        }
+
+        let Some(slot_ids) = slot_ids else {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expecting slot_ids".to_string(),
+            ));
+        };
+        if slot_ids.len() > STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST {
+            return Err(Error::DecodeError(format!(
+                "Invalid Http request: at most {} slot_ids can be requested",
+                STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST
+            )));
+        }
+
+        self.contract_identifier = Some(contract_identifier);
+        self.slot_ids = Some(slot_ids);
+
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetStackerDBLatestChunksRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.contract_identifier = None;
+        self.slot_ids = None;
+    }
+
+    /// Make the response.
+    /// Like the single-chunk endpoint, the chunks are loaded and sent all at once.
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let contract_identifier = self
+            .contract_identifier
+            .take()
+            .ok_or(NetError::SendError("`contract_identifier` not set".into()))?;
+        let slot_ids = self
+            .slot_ids
+            .take()
+            .ok_or(NetError::SendError("`slot_ids` not set".into()))?;
+
+        let chunks_resp =
+            node.with_node_state(|network, _sortdb, _chainstate, _mempool, _rpc_args| {
+                let chunks_res = network
+                    .get_stackerdbs()
+                    .get_latest_chunks(&contract_identifier, &slot_ids);
+
+                match chunks_res {
+                    Ok(chunks) => Ok(chunks),
+                    Err(NetError::NoSuchStackerDB(..)) => {
+                        // no such replica, so none of the slots have chunks
+                        Ok(vec![None; slot_ids.len()])
+                    }
+                    Err(e) => {
+                        // some other error
+                        error!("Failed to load StackerDB chunks";
+                               "smart_contract_id" => contract_identifier.to_string(),
+                               "slot_ids" => ?slot_ids,
+                               "error" => format!("{:?}", &e)
+                        );
+                        Err(StacksHttpResponse::new_error(
+                            &preamble,
+                            &HttpServerError::new("Failed to load StackerDB chunks".to_string()),
+                        ))
+                    }
+                }
+            });
+
+        let chunks = match chunks_resp {
+            Ok(chunks) => chunks,
+            Err(response) => {
+                return response.try_into_contents().map_err(NetError::from);
+            }
+        };
+
+        // hex doubles the size of each chunk, so only send as many as fit in one payload
+        let budget = u64::from(MAX_PAYLOAD_LEN / 2);
+        let mut total_len: u64 = 0;
+        let mut resp: GetStackerDBLatestChunksResponse = Vec::with_capacity(chunks.len());
+        for chunk in chunks.into_iter() {
+            let chunk_len = chunk.as_ref().map(|data| data.len()).unwrap_or(0);
+            total_len = total_len.saturating_add(chunk_len as u64);
+            if total_len > budget && !resp.is_empty() {
+                debug!(
+                    "Truncating latest StackerDB chunks for {} to {} of {} slots",
+                    &contract_identifier,
+                    resp.len(),
+                    slot_ids.len()
+                );
+                break;
+            }
+            resp.push(chunk.map(|data| to_hex(&data)));
+        }
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&resp)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetStackerDBLatestChunksRequestHandler {
+    /// Decode this response from a byte stream.  This is called by the client to decode this
+    /// message
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let chunks: GetStackerDBLatestChunksResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(chunks)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a request for the latest chunks of several stackerDB slots
+    pub fn new_get_stackerdb_latest_chunks(
+        host: PeerHost,
+        stackerdb_contract_id: QualifiedContractIdentifier,
+        slot_ids: &[u32],
+    ) -> StacksHttpRequest {
+        let slot_ids: Vec<String> = slot_ids.iter().map(|id| format!("{}", id)).collect();
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!(
+                "/v2/stackerdb/{}/{}/latest_chunks",
+                &stackerdb_contract_id.issuer, &stackerdb_contract_id.name
+            ),
+            HttpRequestContents::new().query_arg("slot_ids".into(), slot_ids[..].join(",")),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    /// Decode an HTTP response into the latest chunks of the requested slots
+    /// If it fails, return Self::Error(..)
+    pub fn decode_stackerdb_latest_chunks(
+        self,
+    ) -> Result<GetStackerDBLatestChunksResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: GetStackerDBLatestChunksResponse = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
+}
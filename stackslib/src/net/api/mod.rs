@@ -48,9 +48,11 @@ pub mod getmapentry;
 pub mod getmicroblocks_confirmed;
 pub mod getmicroblocks_indexed;
 pub mod getmicroblocks_unconfirmed;
+pub mod getminerequivocations;
 pub mod getneighbors;
 pub mod getpoxinfo;
 pub mod getstackerdbchunk;
+pub mod getstackerdbchunks;
 pub mod getstackerdbmetadata;
 pub mod getstackers;
 pub mod getstxtransfercost;
@@ -101,9 +103,15 @@ impl StacksHttp {
         self.register_rpc_endpoint(
             getmicroblocks_unconfirmed::RPCMicroblocksUnconfirmedRequestHandler::new(),
         );
+        self.register_rpc_endpoint(
+            getminerequivocations::RPCGetMinerEquivocationsRequestHandler::new(),
+        );
         self.register_rpc_endpoint(getneighbors::RPCNeighborsRequestHandler::new());
         self.register_rpc_endpoint(getstxtransfercost::RPCGetStxTransferCostRequestHandler::new());
         self.register_rpc_endpoint(getstackerdbchunk::RPCGetStackerDBChunkRequestHandler::new());
+        self.register_rpc_endpoint(
+            getstackerdbchunks::RPCGetStackerDBLatestChunksRequestHandler::new(),
+        );
         self.register_rpc_endpoint(getpoxinfo::RPCPoxInfoRequestHandler::new());
         self.register_rpc_endpoint(
             getstackerdbmetadata::RPCGetStackerDBMetadataRequestHandler::new(),
@@ -29,14 +29,15 @@ use libstackerdb::{
     SlotMetadata, StackerDBChunkAckData, StackerDBChunkData, STACKERDB_MAX_CHUNK_SIZE,
 };
 use regex::{Captures, Regex};
+use serde;
 use serde::de::Error as de_Error;
-use serde_json::json;
+use serde_json::{self, json};
 use stacks_common::codec::{StacksMessageCodec, MAX_MESSAGE_LEN};
 use stacks_common::types::chainstate::StacksBlockId;
 use stacks_common::types::net::PeerHost;
+use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::secp256k1::MessageSignature;
-use {serde, serde_json};
 
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::{Error as ChainError, StacksBlock};
@@ -122,6 +123,8 @@ pub enum StackerDBErrorCodes {
     DataAlreadyExists,
     NoSuchSlot,
     BadSigner,
+    TooManySlotWrites,
+    TooFrequentSlotWrites,
 }
 
 impl StackerDBErrorCodes {
@@ -130,6 +133,8 @@ impl StackerDBErrorCodes {
             Self::DataAlreadyExists => 0,
             Self::NoSuchSlot => 1,
             Self::BadSigner => 2,
+            Self::TooManySlotWrites => 3,
+            Self::TooFrequentSlotWrites => 4,
         }
     }
 
@@ -139,6 +144,8 @@ impl StackerDBErrorCodes {
             Self::DataAlreadyExists => "Data for this slot and version already exist",
             Self::NoSuchSlot => "No such StackerDB slot",
             Self::BadSigner => "Signature does not match slot signer",
+            Self::TooManySlotWrites => "Slot has been written the maximum number of times",
+            Self::TooFrequentSlotWrites => "Slot was written too recently",
         }
     }
 
@@ -156,6 +163,8 @@ impl StackerDBErrorCodes {
             0 => Some(Self::DataAlreadyExists),
             1 => Some(Self::NoSuchSlot),
             2 => Some(Self::BadSigner),
+            3 => Some(Self::TooManySlotWrites),
+            4 => Some(Self::TooFrequentSlotWrites),
             _ => None,
         }
     }
@@ -234,14 +243,25 @@ impl RPCRequestHandler for RPCPostStackerDBChunkRequestHandler {
                         };
 
                     let err_code = if slot_metadata_opt.is_some() {
-                        if let NetError::BadSlotSigner(..) = e {
-                            StackerDBErrorCodes::BadSigner
-                        } else {
-                            StackerDBErrorCodes::DataAlreadyExists
+                        match e {
+                            NetError::BadSlotSigner(..) => StackerDBErrorCodes::BadSigner,
+                            NetError::TooManySlotWrites { .. } => {
+                                StackerDBErrorCodes::TooManySlotWrites
+                            }
+                            NetError::TooFrequentSlotWrites(..) => {
+                                StackerDBErrorCodes::TooFrequentSlotWrites
+                            }
+                            _ => StackerDBErrorCodes::DataAlreadyExists,
                         }
                     } else {
                         StackerDBErrorCodes::NoSuchSlot
                     };
+                    let retry_after = match e {
+                        NetError::TooFrequentSlotWrites(deadline) => {
+                            Some(deadline.saturating_sub(get_epoch_time_secs()))
+                        }
+                        _ => None,
+                    };
                     let reason = serde_json::to_string(&err_code.clone().into_json())
                         .unwrap_or("(unable to encode JSON)".to_string());
 
@@ -250,6 +270,7 @@ impl RPCRequestHandler for RPCPostStackerDBChunkRequestHandler {
                         reason: Some(reason),
                         metadata: slot_metadata_opt,
                         code: Some(err_code.code()),
+                        retry_after,
                     };
                     return Ok(ack);
                 }
@@ -288,6 +309,7 @@ impl RPCRequestHandler for RPCPostStackerDBChunkRequestHandler {
                     reason: None,
                     metadata: Some(slot_metadata),
                     code: None,
+                    retry_after: None,
                 };
 
                 return Ok(ack);
@@ -0,0 +1,168 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use libstackerdb::STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST;
+use stacks_common::util::hash::to_hex;
+
+use super::test_rpc;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::http::HttpRequestPreamble;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let contract_identifier = QualifiedContractIdentifier::parse(
+        "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world-unconfirmed",
+    )
+    .unwrap();
+    let request = StacksHttpRequest::new_get_stackerdb_latest_chunks(
+        addr.into(),
+        contract_identifier.clone(),
+        &[0, 3, 14],
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getstackerdbchunks::RPCGetStackerDBLatestChunksRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(
+        handler.contract_identifier,
+        Some(contract_identifier.clone())
+    );
+    assert_eq!(handler.slot_ids, Some(vec![0, 3, 14]));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.contract_identifier.is_none());
+    assert!(handler.slot_ids.is_none());
+}
+
+#[test]
+fn test_try_parse_request_rejects_bad_slot_ids() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let too_many: Vec<String> = (0..=STACKERDB_MAX_LATEST_CHUNKS_PER_REQUEST)
+        .map(|id| id.to_string())
+        .collect();
+    for query in [
+        "".to_string(),
+        "?slot_ids=0,x".to_string(),
+        format!("?slot_ids={}", too_many.join(",")),
+    ] {
+        let preamble = HttpRequestPreamble::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            format!(
+                "/v2/stackerdb/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/hello-world/latest_chunks{}",
+                query
+            ),
+        );
+        let mut handler = getstackerdbchunks::RPCGetStackerDBLatestChunksRequestHandler::new();
+        assert!(
+            http.handle_try_parse_request(&mut handler, &preamble, &[])
+                .is_err(),
+            "Parsed a request with query '{}'",
+            query
+        );
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let mut requests = vec![];
+
+    let contract_identifier =
+        QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world")
+            .unwrap();
+    let none_contract_identifier = QualifiedContractIdentifier::parse(
+        "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.does-not-ext",
+    )
+    .unwrap();
+
+    // a chunk, an empty slot, and a slot that does not exist, in request order
+    let request = StacksHttpRequest::new_get_stackerdb_latest_chunks(
+        addr.into(),
+        contract_identifier.clone(),
+        &[4093, 0, 1],
+    );
+    requests.push(request);
+
+    // no contract
+    let request = StacksHttpRequest::new_get_stackerdb_latest_chunks(
+        addr.into(),
+        none_contract_identifier.clone(),
+        &[0, 1],
+    );
+    requests.push(request);
+
+    let mut responses = test_rpc(function_name!(), requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    assert_eq!(
+        response.preamble().get_canonical_stacks_tip_height(),
+        Some(1)
+    );
+
+    let resp = response.decode_stackerdb_latest_chunks().unwrap();
+    assert_eq!(
+        resp,
+        vec![
+            None,
+            Some(to_hex("hello world".as_bytes())),
+            Some("".into())
+        ]
+    );
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let resp = response.decode_stackerdb_latest_chunks().unwrap();
+    assert_eq!(resp, vec![None, None]);
+}
@@ -67,9 +67,11 @@ mod getmapentry;
 mod getmicroblocks_confirmed;
 mod getmicroblocks_indexed;
 mod getmicroblocks_unconfirmed;
+mod getminerequivocations;
 mod getneighbors;
 mod getpoxinfo;
 mod getstackerdbchunk;
+mod getstackerdbchunks;
 mod getstackerdbmetadata;
 mod getstxtransfercost;
 mod gettenure;
@@ -83,6 +83,7 @@ use crate::core::mempool::*;
 use crate::core::{StacksEpoch, POX_REWARD_CYCLE_LENGTH};
 use crate::cost_estimates::metrics::CostMetric;
 use crate::cost_estimates::{CostEstimator, FeeEstimator, FeeRateEstimate};
+use crate::net::api::getminerequivocations::SignerEquivocationSource;
 use crate::net::atlas::{Attachment, AttachmentInstance};
 use crate::net::dns::*;
 use crate::net::http::error::{HttpNotFound, HttpServerError};
@@ -629,6 +630,8 @@ pub struct RPCHandlerArgs<'a> {
     pub cost_metric: Option<&'a dyn CostMetric>,
     /// coordinator channels
     pub coord_comms: Option<&'a CoordinatorChannels>,
+    /// the signer equivocations recorded by this node's miner
+    pub equivocation_source: Option<&'a dyn SignerEquivocationSource>,
 }
 
 impl<'a> RPCHandlerArgs<'a> {
@@ -1972,7 +1975,7 @@ pub mod test {
             _confirmed_mblock_cost: &ExecutionCost,
             pox_constants: &PoxConstants,
             reward_set_data: &Option<RewardSetData>,
-            _signer_bitvec: &Option<BitVec<4000>>,
+            _signer_bitvec: &Option<BitVec<SIGNER_BITVEC_MAX_SIZE>>,
         ) {
             self.blocks.lock().unwrap().push(TestEventObserverBlock {
                 block: block.clone(),
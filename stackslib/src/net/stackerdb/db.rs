@@ -665,9 +665,13 @@ impl StackerDBs {
         smart_contract: &QualifiedContractIdentifier,
         slot_ids: &[u32],
     ) -> Result<Vec<Option<Vec<u8>>>, net_error> {
-        let mut results = vec![];
+        let stackerdb_id = self.get_stackerdb_id(smart_contract)?;
+        let qry = "SELECT data FROM chunks WHERE stackerdb_id = ?1 AND slot_id = ?2";
+        let mut stmt = self.conn.prepare_cached(qry)?;
+        let mut results = Vec::with_capacity(slot_ids.len());
         for slot_id in slot_ids {
-            results.push(self.get_latest_chunk(smart_contract, *slot_id)?);
+            let args: &[&dyn ToSql] = &[&stackerdb_id, slot_id];
+            results.push(stmt.query_row(args, |row| row.get(0)).optional()?);
         }
         Ok(results)
     }
@@ -569,6 +569,16 @@ fn test_stackerdb_insert_query_chunks() {
     for ts in timestamps {
         assert!(ts > 0);
     }
+
+    // batched reads agree with individual reads, including for slots that don't exist
+    let slot_ids = [9, 0, 42, 3, 3, 10];
+    let chunks = db.get_latest_chunks(&sc, &slot_ids).unwrap();
+    assert_eq!(chunks.len(), slot_ids.len());
+    for (slot_id, chunk) in slot_ids.iter().zip(chunks) {
+        assert_eq!(chunk, db.get_latest_chunk(&sc, *slot_id).unwrap());
+    }
+    assert!(db.get_latest_chunks(&sc, &[42]).unwrap()[0].is_none());
+    assert!(db.get_latest_chunks(&sc, &[]).unwrap().is_empty());
 }
 
 /// Verify that we can reconfigure the database by changing its slots
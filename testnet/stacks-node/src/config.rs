@@ -9,6 +9,7 @@ use std::{cmp, fs, thread};
 use clarity::vm::costs::ExecutionCost;
 use clarity::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
 use lazy_static::lazy_static;
+use libsigner::envelope::{EnvelopeConfig, DEFAULT_COMPRESSION_THRESHOLD};
 use rand::RngCore;
 use serde::Deserialize;
 use stacks::burnchains::affirmation::AffirmationMap;
@@ -305,6 +306,153 @@ mod tests {
         assert_eq!(config.burnchain.affirmation_overrides.len(), 5);
         assert_eq!(config.burnchain.affirmation_overrides[&413], affirmation);
     }
+
+    #[test]
+    fn should_load_sign_coordinator_config() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap(), false)
+            .expect("Expected to be able to parse an empty config");
+        let defaults = SignCoordinatorConfig::from_config(&config).unwrap();
+        assert_eq!(
+            defaults,
+            SignCoordinatorConfig {
+                signing_round_timeout: Duration::from_secs(200),
+                signer_db_poll_interval: Duration::from_millis(500),
+                reproposal_backoff_max: Duration::from_secs(30),
+                post_threshold_grace: Duration::from_millis(500),
+                broadcast_deadline_margin: Duration::from_secs(30),
+                proposal_readback_delay: None,
+                signer_set_boundary_window: 0,
+                stacks_tip_lag_tolerance: 1,
+            }
+        );
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                wait_on_signers_ms = 60000
+                signer_db_poll_interval_ms = 250
+                reproposal_backoff_max_ms = 5000
+                post_threshold_grace_ms = 1000
+                broadcast_deadline_margin_ms = 20000
+                proposal_readback_delay_ms = 3000
+                signer_set_boundary_window = 3
+                stacks_tip_lag_tolerance = 2
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse the sign coordinator settings");
+        let coordinator_config = SignCoordinatorConfig::from_config(&config).unwrap();
+        assert_eq!(
+            coordinator_config,
+            SignCoordinatorConfig {
+                signing_round_timeout: Duration::from_secs(60),
+                signer_db_poll_interval: Duration::from_millis(250),
+                reproposal_backoff_max: Duration::from_secs(5),
+                post_threshold_grace: Duration::from_secs(1),
+                broadcast_deadline_margin: Duration::from_secs(20),
+                proposal_readback_delay: Some(Duration::from_secs(3)),
+                signer_set_boundary_window: 3,
+                stacks_tip_lag_tolerance: 2,
+            }
+        );
+
+        // serialized under the config file's option names
+        let json = serde_json::to_value(&coordinator_config).unwrap();
+        assert_eq!(json["wait_on_signers_ms"], 60000);
+        assert_eq!(json["signer_db_poll_interval_ms"], 250);
+        assert_eq!(json["proposal_readback_delay_ms"], 3000);
+        assert_eq!(json["stacks_tip_lag_tolerance"], 2);
+        assert_eq!(
+            serde_json::from_value::<SignCoordinatorConfig>(json).unwrap(),
+            coordinator_config
+        );
+    }
+
+    #[test]
+    fn should_fail_to_load_contradictory_sign_coordinator_config() {
+        for (miner_section, expected_err) in [
+            (
+                "wait_on_signers_ms = 0",
+                "Miner config error: `wait_on_signers_ms` must be greater than 0",
+            ),
+            (
+                "signer_db_poll_interval_ms = 0",
+                "Miner config error: `signer_db_poll_interval_ms` must be greater than 0",
+            ),
+            (
+                "wait_on_signers_ms = 400\nsigner_db_poll_interval_ms = 500",
+                "Miner config error: `signer_db_poll_interval_ms` (500) must not exceed `wait_on_signers_ms` (400)",
+            ),
+            (
+                "wait_on_signers_ms = 1000\npost_threshold_grace_ms = 1001",
+                "Miner config error: `post_threshold_grace_ms` (1001) must not exceed `wait_on_signers_ms` (1000)",
+            ),
+            (
+                "wait_on_signers_ms = 1000\nproposal_readback_delay_ms = 1000",
+                "Miner config error: `proposal_readback_delay_ms` (1000) must be less than `wait_on_signers_ms` (1000)",
+            ),
+        ] {
+            let file = ConfigFile::from_str(&format!("[miner]\n{miner_section}"))
+                .expect("Expected to be able to parse config file from string");
+            assert_eq!(
+                Config::from_config_file(file, false).unwrap_err(),
+                expected_err,
+                "Unexpected result for {miner_section}"
+            );
+        }
+
+        // the bounds are inclusive
+        let file = ConfigFile::from_str(
+            r#"
+            [miner]
+            wait_on_signers_ms = 1000
+            signer_db_poll_interval_ms = 1000
+            post_threshold_grace_ms = 1000
+            "#,
+        )
+        .unwrap();
+        assert!(Config::from_config_file(file, false).is_ok());
+    }
+
+    #[test]
+    fn should_refuse_ignored_signer_keys_on_mainnet() {
+        let signer_key = Secp256k1PublicKey::from_private(&Secp256k1PrivateKey::new());
+        let miner = MinerConfigFile {
+            ignored_signer_keys: Some(vec![signer_key.to_hex()]),
+            ..MinerConfigFile::default()
+        };
+
+        let file = ConfigFile {
+            miner: Some(miner.clone()),
+            ..ConfigFile::xenon()
+        };
+        let config = Config::from_config_file(file, false)
+            .expect("Expected to be able to ignore signer keys off mainnet");
+        assert_eq!(config.miner.ignored_signer_keys, vec![signer_key]);
+
+        let file = ConfigFile {
+            miner: Some(miner),
+            ..ConfigFile::mainnet()
+        };
+        assert_eq!(
+            Config::from_config_file(file, false).unwrap_err(),
+            "Attempted to run mainnet node with `miner.ignored_signer_keys`"
+        );
+
+        let file = ConfigFile::from_str(
+            r#"
+            [miner]
+            ignored_signer_keys = ["not-a-key"]
+            "#,
+        )
+        .unwrap();
+        assert!(Config::from_config_file(file, false)
+            .unwrap_err()
+            .starts_with("miner.ignored_signer_keys contains an invalid public key 'not-a-key'"));
+    }
 }
 
 impl ConfigFile {
@@ -1056,6 +1204,9 @@ impl Config {
             Some(miner) => miner.into_config_default(miner_default_config)?,
             None => miner_default_config,
         };
+        if is_mainnet && !miner.ignored_signer_keys.is_empty() {
+            return Err("Attempted to run mainnet node with `miner.ignored_signer_keys`".into());
+        }
 
         let initial_balances: Vec<InitialBalance> = match config_file.ustx_balance {
             Some(balances) => {
@@ -1134,7 +1285,7 @@ impl Config {
             .validate()
             .map_err(|e| format!("Atlas config error: {e}"))?;
 
-        Ok(Config {
+        let config = Config {
             config_path: config_file.__path,
             node,
             burnchain,
@@ -1144,7 +1295,12 @@ impl Config {
             estimation,
             miner,
             atlas,
-        })
+        };
+
+        SignCoordinatorConfig::from_config(&config)
+            .map_err(|e| format!("Miner config error: {e}"))?;
+
+        Ok(config)
     }
 
     fn get_burnchain_path(&self) -> PathBuf {
@@ -1219,6 +1375,20 @@ impl Config {
         path.to_str().expect("Unable to produce path").to_string()
     }
 
+    /// Path to the DB where the miner persists the block proposal it is collecting signatures for
+    pub fn get_inflight_proposal_db_path(&self) -> String {
+        let mut path = self.get_chainstate_path();
+        path.push("inflight_proposals.sqlite");
+        path.to_str().expect("Unable to produce path").to_string()
+    }
+
+    /// Path to the DB where the miner records signers that signed conflicting blocks
+    pub fn get_signer_equivocation_db_path(&self) -> String {
+        let mut path = self.get_chainstate_path();
+        path.push("signer_equivocations.sqlite");
+        path.to_str().expect("Unable to produce path").to_string()
+    }
+
     pub fn get_stacker_db_file_path(&self) -> String {
         let mut path = self.get_chainstate_path();
         path.set_file_name("stacker_db.sqlite");
@@ -1271,6 +1441,7 @@ impl Config {
             },
             miner_status,
             confirm_microblocks: false,
+            max_block_size: None,
         }
     }
 
@@ -1309,6 +1480,7 @@ impl Config {
             },
             miner_status,
             confirm_microblocks: true,
+            max_block_size: None,
         }
     }
 
@@ -1803,6 +1975,8 @@ pub struct NodeConfig {
     /// but environments where burn blocks are more frequent may want to decrease this value.
     pub next_initiative_delay: u64,
     pub prometheus_bind: Option<String>,
+    /// Where to serve the stream of `.signers` and `.miners` StackerDB chunk events, if at all
+    pub stackerdb_stream_bind: Option<String>,
     pub marf_cache_strategy: Option<String>,
     pub marf_defer_hashing: bool,
     pub pox_sync_sample_secs: u64,
@@ -2089,6 +2263,7 @@ impl Default for NodeConfig {
             wait_time_for_blocks: 30_000,
             next_initiative_delay: 10_000,
             prometheus_bind: None,
+            stackerdb_stream_bind: None,
             marf_cache_strategy: None,
             marf_defer_hashing: true,
             pox_sync_sample_secs: 30,
@@ -2312,6 +2487,44 @@ pub struct MinerConfig {
     pub max_reorg_depth: u64,
     /// Amount of time while mining in nakamoto to wait for signers to respond to a proposed block
     pub wait_on_signers: Duration,
+    /// How long the miner's StackerDB event receiver can go quiet before the miner reads the
+    /// signers' responses straight out of their StackerDBs instead
+    pub signer_db_poll_interval: Duration,
+    /// Longest time to wait before proposing another block after signers rejected the last one
+    /// because they could not reach their stacks-node
+    pub reproposal_backoff_max: Duration,
+    /// How long the miner keeps collecting signer responses after its block is signed, so that
+    /// signers that respond late are still counted in the block's signer bitvec
+    pub post_threshold_grace: Duration,
+    /// How close to the expected arrival of the next burn block the miner broadcasts a signed
+    /// block right away, rather than waiting out `post_threshold_grace`
+    pub broadcast_deadline_margin: Duration,
+    /// How long into a signing round without any signer response the miner reads its proposal
+    /// back out of its own StackerDB, and writes it again if it is not there. Disabled if `None`.
+    pub proposal_readback_delay: Option<Duration>,
+    /// Number of burn blocks on either side of a reward cycle boundary during which the miner
+    /// also accepts signer responses from the adjacent reward cycle's signer set. 0 disables it.
+    pub signer_set_boundary_window: u64,
+    /// Number of tenures between the tenure of the node's canonical Stacks tip and the tenure
+    /// being mined that the node may not have processed. With more, the node is taken to be
+    /// still syncing, and the miner proposes nothing the signers would reject as stale.
+    pub stacks_tip_lag_tolerance: u64,
+    /// How long the miner wants signers to take to sign its blocks. If set, blocks are capped
+    /// at the size the signers were observed to sign within this time.
+    pub target_signer_latency: Option<Duration>,
+    /// Signing keys of signers whose responses are processed first within each batch of
+    /// signer messages, e.g. large-weight signers known to respond quickly
+    pub prioritized_signer_keys: Vec<Secp256k1PublicKey>,
+    /// Signing keys of signers whose responses the miner drops unread, to simulate a network
+    /// partition in chaos tests. Refused on mainnet.
+    pub ignored_signer_keys: Vec<Secp256k1PublicKey>,
+    /// Path to append a JSONL trace of every signing round to, for post-mortems
+    pub sign_round_trace_path: Option<String>,
+    /// Size at which the signing round trace is moved aside and a new one started
+    pub sign_round_trace_max_bytes: u64,
+    /// How the miner envelopes the messages it writes to the `.miners` StackerDB. Messages are
+    /// written bare if `None`, which every signer can read.
+    pub payload_envelope: Option<EnvelopeConfig>,
 }
 
 impl Default for MinerConfig {
@@ -2342,10 +2555,142 @@ impl Default for MinerConfig {
             max_reorg_depth: 3,
             // TODO: update to a sane value based on stackerdb benchmarking
             wait_on_signers: Duration::from_secs(200),
+            signer_db_poll_interval: Duration::from_millis(500),
+            reproposal_backoff_max: Duration::from_secs(30),
+            post_threshold_grace: Duration::from_millis(500),
+            broadcast_deadline_margin: Duration::from_secs(30),
+            proposal_readback_delay: None,
+            signer_set_boundary_window: 0,
+            stacks_tip_lag_tolerance: 1,
+            target_signer_latency: None,
+            prioritized_signer_keys: vec![],
+            ignored_signer_keys: vec![],
+            sign_round_trace_path: None,
+            sign_round_trace_max_bytes: 64 * 1024 * 1024,
+            payload_envelope: None,
         }
     }
 }
 
+/// The miner settings that shape the signing rounds its sign coordinator runs, checked
+/// against each other. Serialized under the names of their `[miner]` config file options,
+/// with durations in milliseconds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SignCoordinatorConfig {
+    /// How long to wait for the signers to sign a proposed block
+    #[serde(rename = "wait_on_signers_ms", with = "duration_ms")]
+    pub signing_round_timeout: Duration,
+    /// How long the StackerDB event receiver can go quiet before the signers' responses are
+    /// read straight out of their StackerDBs
+    #[serde(rename = "signer_db_poll_interval_ms", with = "duration_ms")]
+    pub signer_db_poll_interval: Duration,
+    /// Ceiling on how long to wait before re-proposing after connectivity rejections
+    #[serde(rename = "reproposal_backoff_max_ms", with = "duration_ms")]
+    pub reproposal_backoff_max: Duration,
+    /// How long to keep collecting signer responses once the block is signed
+    #[serde(rename = "post_threshold_grace_ms", with = "duration_ms")]
+    pub post_threshold_grace: Duration,
+    /// How close to the broadcast deadline a signed block is returned without a grace period
+    #[serde(rename = "broadcast_deadline_margin_ms", with = "duration_ms")]
+    pub broadcast_deadline_margin: Duration,
+    /// How long into a round without responses the proposal is read back out of the miner's
+    /// own StackerDB. Never if `None`.
+    #[serde(rename = "proposal_readback_delay_ms", with = "opt_duration_ms")]
+    pub proposal_readback_delay: Option<Duration>,
+    /// Burn blocks on either side of a reward cycle boundary in which the adjacent signer set
+    /// is also accepted
+    pub signer_set_boundary_window: u64,
+    /// How many tenures the canonical Stacks tip may trail the tenure being mined by
+    pub stacks_tip_lag_tolerance: u64,
+}
+
+impl SignCoordinatorConfig {
+    /// Gather the sign coordinator's settings from the miner config. Errors if they
+    /// contradict each other.
+    pub fn from_config(config: &Config) -> Result<Self, String> {
+        let miner = &config.miner;
+        let coordinator_config = Self {
+            signing_round_timeout: miner.wait_on_signers,
+            signer_db_poll_interval: miner.signer_db_poll_interval,
+            reproposal_backoff_max: miner.reproposal_backoff_max,
+            post_threshold_grace: miner.post_threshold_grace,
+            broadcast_deadline_margin: miner.broadcast_deadline_margin,
+            proposal_readback_delay: miner.proposal_readback_delay,
+            signer_set_boundary_window: miner.signer_set_boundary_window,
+            stacks_tip_lag_tolerance: miner.stacks_tip_lag_tolerance,
+        };
+        coordinator_config.validate()?;
+        Ok(coordinator_config)
+    }
+
+    /// Check that the settings do not contradict each other
+    pub fn validate(&self) -> Result<(), String> {
+        if self.signing_round_timeout.is_zero() {
+            return Err("`wait_on_signers_ms` must be greater than 0".into());
+        }
+        if self.signer_db_poll_interval.is_zero() {
+            return Err("`signer_db_poll_interval_ms` must be greater than 0".into());
+        }
+        if self.signer_db_poll_interval > self.signing_round_timeout {
+            return Err(format!(
+                "`signer_db_poll_interval_ms` ({}) must not exceed `wait_on_signers_ms` ({})",
+                self.signer_db_poll_interval.as_millis(),
+                self.signing_round_timeout.as_millis()
+            ));
+        }
+        if self.post_threshold_grace > self.signing_round_timeout {
+            return Err(format!(
+                "`post_threshold_grace_ms` ({}) must not exceed `wait_on_signers_ms` ({})",
+                self.post_threshold_grace.as_millis(),
+                self.signing_round_timeout.as_millis()
+            ));
+        }
+        if let Some(readback_delay) = self.proposal_readback_delay {
+            if readback_delay >= self.signing_round_timeout {
+                return Err(format!(
+                    "`proposal_readback_delay_ms` ({}) must be less than `wait_on_signers_ms` ({})",
+                    readback_delay.as_millis(),
+                    self.signing_round_timeout.as_millis()
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// (De)serializes a `Duration` as a whole number of milliseconds
+mod duration_ms {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        u64::deserialize(d).map(Duration::from_millis)
+    }
+}
+
+/// (De)serializes an optional `Duration` as a whole number of milliseconds
+mod opt_duration_ms {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::duration_ms::serialize(duration, s),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(d).map(|ms| ms.map(Duration::from_millis))
+    }
+}
+
 #[derive(Clone, Default, Deserialize, Debug)]
 pub struct ConnectionOptionsFile {
     pub inbox_maxlen: Option<usize>,
@@ -2543,6 +2888,7 @@ pub struct NodeConfigFile {
     pub wait_time_for_blocks: Option<u64>,
     pub next_initiative_delay: Option<u64>,
     pub prometheus_bind: Option<String>,
+    pub stackerdb_stream_bind: Option<String>,
     pub marf_cache_strategy: Option<String>,
     pub marf_defer_hashing: Option<bool>,
     pub pox_sync_sample_secs: Option<u64>,
@@ -2606,6 +2952,7 @@ impl NodeConfigFile {
                 .next_initiative_delay
                 .unwrap_or(default_node_config.next_initiative_delay),
             prometheus_bind: self.prometheus_bind,
+            stackerdb_stream_bind: self.stackerdb_stream_bind,
             marf_cache_strategy: self.marf_cache_strategy,
             marf_defer_hashing: self
                 .marf_defer_hashing
@@ -2673,6 +3020,30 @@ pub struct MinerConfigFile {
     pub filter_origins: Option<String>,
     pub max_reorg_depth: Option<u64>,
     pub wait_on_signers_ms: Option<u64>,
+    pub signer_db_poll_interval_ms: Option<u64>,
+    pub reproposal_backoff_max_ms: Option<u64>,
+    pub post_threshold_grace_ms: Option<u64>,
+    pub broadcast_deadline_margin_ms: Option<u64>,
+    pub proposal_readback_delay_ms: Option<u64>,
+    pub signer_set_boundary_window: Option<u64>,
+    pub stacks_tip_lag_tolerance: Option<u64>,
+    pub target_signer_latency_ms: Option<u64>,
+    pub prioritized_signer_keys: Option<Vec<String>>,
+    pub ignored_signer_keys: Option<Vec<String>>,
+    pub sign_round_trace_path: Option<String>,
+    pub sign_round_trace_max_bytes: Option<u64>,
+    pub payload_envelope: Option<bool>,
+    pub payload_compression_threshold: Option<usize>,
+}
+
+/// Parse the hex public keys listed in `miner.{field}`
+fn parse_signer_keys(field: &str, keys: &[String]) -> Result<Vec<Secp256k1PublicKey>, String> {
+    keys.iter()
+        .map(|key| {
+            Secp256k1PublicKey::from_hex(key)
+                .map_err(|e| format!("miner.{field} contains an invalid public key '{key}': {e}"))
+        })
+        .collect()
 }
 
 impl MinerConfigFile {
@@ -2775,6 +3146,59 @@ impl MinerConfigFile {
                 .wait_on_signers_ms
                 .map(Duration::from_millis)
                 .unwrap_or(miner_default_config.wait_on_signers),
+            signer_db_poll_interval: self
+                .signer_db_poll_interval_ms
+                .map(Duration::from_millis)
+                .unwrap_or(miner_default_config.signer_db_poll_interval),
+            reproposal_backoff_max: self
+                .reproposal_backoff_max_ms
+                .map(Duration::from_millis)
+                .unwrap_or(miner_default_config.reproposal_backoff_max),
+            post_threshold_grace: self
+                .post_threshold_grace_ms
+                .map(Duration::from_millis)
+                .unwrap_or(miner_default_config.post_threshold_grace),
+            broadcast_deadline_margin: self
+                .broadcast_deadline_margin_ms
+                .map(Duration::from_millis)
+                .unwrap_or(miner_default_config.broadcast_deadline_margin),
+            proposal_readback_delay: self
+                .proposal_readback_delay_ms
+                .map(Duration::from_millis)
+                .or(miner_default_config.proposal_readback_delay),
+            signer_set_boundary_window: self
+                .signer_set_boundary_window
+                .unwrap_or(miner_default_config.signer_set_boundary_window),
+            stacks_tip_lag_tolerance: self
+                .stacks_tip_lag_tolerance
+                .unwrap_or(miner_default_config.stacks_tip_lag_tolerance),
+            target_signer_latency: self
+                .target_signer_latency_ms
+                .map(Duration::from_millis)
+                .or(miner_default_config.target_signer_latency),
+            prioritized_signer_keys: match self.prioritized_signer_keys {
+                Some(keys) => parse_signer_keys("prioritized_signer_keys", &keys)?,
+                None => miner_default_config.prioritized_signer_keys,
+            },
+            ignored_signer_keys: match self.ignored_signer_keys {
+                Some(keys) => parse_signer_keys("ignored_signer_keys", &keys)?,
+                None => miner_default_config.ignored_signer_keys,
+            },
+            sign_round_trace_path: self
+                .sign_round_trace_path
+                .or(miner_default_config.sign_round_trace_path),
+            sign_round_trace_max_bytes: self
+                .sign_round_trace_max_bytes
+                .unwrap_or(miner_default_config.sign_round_trace_max_bytes),
+            payload_envelope: match self.payload_envelope {
+                Some(true) => Some(EnvelopeConfig {
+                    compression_threshold: self
+                        .payload_compression_threshold
+                        .unwrap_or(DEFAULT_COMPRESSION_THRESHOLD),
+                }),
+                Some(false) => None,
+                None => miner_default_config.payload_envelope,
+            },
         })
     }
 }
@@ -1,9 +1,11 @@
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_h1::client;
 use async_std::net::TcpStream;
@@ -21,6 +23,7 @@ use stacks::chainstate::nakamoto::NakamotoBlock;
 use stacks::chainstate::stacks::address::PoxAddress;
 use stacks::chainstate::stacks::boot::{
     NakamotoSignerEntry, PoxStartCycleInfo, RewardSet, RewardSetData, SIGNERS_NAME,
+    SIGNER_BITVEC_MAX_SIZE,
 };
 use stacks::chainstate::stacks::db::accounts::MinerReward;
 use stacks::chainstate::stacks::db::unconfirmed::ProcessedUnconfirmedState;
@@ -48,6 +51,9 @@ use stacks_common::util::hash::{bytes_to_hex, Sha512Trunc256Sum};
 use stacks_common::util::secp256k1::MessageSignature;
 
 use super::config::{EventKeyType, EventObserverConfig};
+use crate::nakamoto_node::sign_coordinator::SigningSubsystemInfo;
+use crate::nakamoto_node::sign_round::SignerSignatureData;
+use crate::stackerdb_stream::STACKER_DB_STREAM;
 
 #[derive(Debug, Clone)]
 struct EventObserver {
@@ -79,9 +85,14 @@ pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
 pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
 pub const PATH_PROPOSAL_RESPONSE: &str = "proposal_response";
+pub const PATH_SIGNING_SUBSYSTEM_INFO: &str = "signing_subsystem_info";
 
 pub static STACKER_DB_CHANNEL: StackerDBChannel = StackerDBChannel::new();
 
+/// How many mined blocks' signer signature data is held until the blocks are processed. Data
+/// for a block that is never processed is dropped once this many newer blocks have been mined.
+const MAX_PENDING_SIGNER_SIGNATURE_DATA: usize = 64;
+
 /// This struct receives StackerDB event callbacks without registering
 /// over the JSON/RPC interface. To ensure that any event observer
 /// uses the same channel, we use a lazy_static global for the channel (this
@@ -93,16 +104,83 @@ pub static STACKER_DB_CHANNEL: StackerDBChannel = StackerDBChannel::new();
 /// seems bad. So instead, it uses a singleton sync channel.
 pub struct StackerDBChannel {
     sender_info: Mutex<Option<InnerStackerDBChannel>>,
+    /// The number of miner coordinators that have registered, which is the generation of the
+    /// next one
+    registrations: AtomicU64,
+}
+
+/// A miner coordinator's registration on the `StackerDBChannel`. Releasing it with
+/// [`StackerDBChannel::replace_receiver`] leaves any newer registration in place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackerDBChannelRegistration {
+    /// Coordinators registered earlier have lower generations
+    id: u64,
+    /// Were the senders of an earlier registration still in place? If so, the registration
+    /// was forced, they were dropped, and the coordinator that registered them is superseded.
+    pub replaced_other: bool,
+}
+
+impl StackerDBChannelRegistration {
+    /// Coordinators registered earlier have lower generations
+    pub fn generation(&self) -> u64 {
+        self.id
+    }
+}
+
+/// Why a miner coordinator could not register on the `StackerDBChannel`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackerDBChannelError {
+    /// The coordinator of generation `generation` is still registered. Registering another
+    /// without forcing it would have the two steal each other's events.
+    CoordinatorActive { generation: u64 },
+}
+
+impl fmt::Display for StackerDBChannelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StackerDBChannelError::CoordinatorActive { generation } => write!(
+                f,
+                "Miner coordinator of generation {generation} is still registered on the StackerDB channel"
+            ),
+        }
+    }
+}
+
+/// Decides whether a listener on the `StackerDBChannel` wants chunk events from a contract
+pub type StackerDBContractPredicate =
+    Arc<dyn Fn(&QualifiedContractIdentifier) -> bool + Send + Sync>;
+
+/// A chunks event sent over the `StackerDBChannel`, stamped with when it was sent so that the
+/// listener can tell how long it waited in the channel
+#[derive(Clone, Debug)]
+pub struct StackerDBChannelEvent {
+    pub event: StackerDBChunksEvent,
+    pub dispatched_at: Instant,
+}
+
+impl StackerDBChannelEvent {
+    /// Stamp `event` as dispatched now
+    pub fn new(event: StackerDBChunksEvent) -> Self {
+        Self {
+            event,
+            dispatched_at: Instant::now(),
+        }
+    }
 }
 
 #[derive(Clone)]
 struct InnerStackerDBChannel {
+    /// The registration that put this listener in place
+    registration_id: u64,
     /// A channel for sending the chunk events to the listener
-    sender: Sender<StackerDBChunksEvent>,
+    sender: Sender<StackerDBChannelEvent>,
     /// Does the listener want to receive `.signers` chunks?
     interested_in_signers: bool,
     /// Which StackerDB contracts is the listener interested in?
     other_interests: Vec<QualifiedContractIdentifier>,
+    /// Events from contracts that fail this check are never enqueued, even if the listener is
+    /// otherwise interested in them
+    contract_predicate: StackerDBContractPredicate,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -141,12 +219,17 @@ pub struct MinedNakamotoBlockEvent {
 }
 
 impl InnerStackerDBChannel {
-    pub fn new_miner_receiver() -> (Receiver<StackerDBChunksEvent>, Self) {
+    pub fn new_miner_receiver(
+        registration_id: u64,
+        contract_predicate: StackerDBContractPredicate,
+    ) -> (Receiver<StackerDBChannelEvent>, Self) {
         let (sender, recv) = channel();
         let sender_info = Self {
+            registration_id,
             sender,
             interested_in_signers: true,
             other_interests: vec![],
+            contract_predicate,
         };
 
         (recv, sender_info)
@@ -157,43 +240,106 @@ impl StackerDBChannel {
     pub const fn new() -> Self {
         Self {
             sender_info: Mutex::new(None),
+            registrations: AtomicU64::new(0),
         }
     }
 
-    /// Consume the receiver for the StackerDBChannel and drop the senders. This should be done
-    /// before another interested thread can subscribe to events, but it is not absolutely necessary
-    /// to do so (it would just result in temporary over-use of memory while the prior channel is still
-    /// open).
+    /// Lock the registered senders. Registering and releasing only ever swap them whole, so a
+    /// thread that panicked while holding the lock cannot have left them half-replaced, and a
+    /// poisoned lock is recovered rather than turned into another panic.
+    fn lock_sender_info(&self) -> MutexGuard<'_, Option<InnerStackerDBChannel>> {
+        self.sender_info
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Consume the receiver for the StackerDBChannel and drop the senders of `registration`.
+    /// This should be done before another interested thread can subscribe to events, but it is
+    /// not absolutely necessary to do so (it would just result in temporary over-use of memory
+    /// while the prior channel is still open).
+    ///
+    /// If another thread has registered since, its senders are left in place. Returns whether
+    /// `registration`'s senders were still registered.
     ///
     /// The StackerDBChnnel's receiver is guarded with a Mutex, so that ownership can
     /// be taken by different threads without unsafety.
-    pub fn replace_receiver(&self, receiver: Receiver<StackerDBChunksEvent>) {
+    pub fn replace_receiver(
+        &self,
+        receiver: Receiver<StackerDBChannelEvent>,
+        registration: StackerDBChannelRegistration,
+    ) -> bool {
         // not strictly necessary, but do this rather than mark the `receiver` argument as unused
         // so that we're explicit about the fact that `replace_receiver` consumes.
         drop(receiver);
-        let mut guard = self
-            .sender_info
-            .lock()
-            .expect("FATAL: poisoned StackerDBChannel lock");
-        guard.take();
+        let mut guard = self.lock_sender_info();
+        match guard.as_ref() {
+            Some(sender_info) if sender_info.registration_id == registration.id => {
+                guard.take();
+                true
+            }
+            _ => false,
+        }
     }
 
-    /// Create a new event receiver channel for receiving events relevant to the miner coordinator,
-    /// dropping the old StackerDB event sender channels if they are still registered.
-    ///  Returns the new receiver channel and a bool indicating whether or not sender channels were
-    ///   still in place.
+    /// Create a new event receiver channel for receiving events relevant to the miner coordinator.
+    ///  Returns the new receiver channel and its registration, which tells whether or not sender
+    ///   channels were still in place.
+    ///
+    /// If another coordinator's sender channels are still registered, this fails unless `force`
+    /// is set, in which case they are dropped and that coordinator is superseded.
     ///
     /// The StackerDBChannel senders are guarded by mutexes so that they can be replaced
     /// by different threads without unsafety.
-    pub fn register_miner_coordinator(&self) -> (Receiver<StackerDBChunksEvent>, bool) {
-        let mut sender_info = self
-            .sender_info
-            .lock()
-            .expect("FATAL: poisoned StackerDBChannel lock");
-        let (recv, new_sender) = InnerStackerDBChannel::new_miner_receiver();
-        let replaced_receiver = sender_info.replace(new_sender).is_some();
+    pub fn register_miner_coordinator(
+        &self,
+        force: bool,
+    ) -> Result<
+        (
+            Receiver<StackerDBChannelEvent>,
+            StackerDBChannelRegistration,
+        ),
+        StackerDBChannelError,
+    > {
+        self.register_miner_coordinator_filtered(force, |_| true)
+    }
+
+    /// Like `register_miner_coordinator`, but only events from contracts that satisfy
+    /// `contract_predicate` are sent to the new receiver. Events the miner coordinator would
+    /// discard anyway (e.g., those for the other reward cycle's signers) are dropped here,
+    /// before they are enqueued.
+    pub fn register_miner_coordinator_filtered<F>(
+        &self,
+        force: bool,
+        contract_predicate: F,
+    ) -> Result<
+        (
+            Receiver<StackerDBChannelEvent>,
+            StackerDBChannelRegistration,
+        ),
+        StackerDBChannelError,
+    >
+    where
+        F: Fn(&QualifiedContractIdentifier) -> bool + Send + Sync + 'static,
+    {
+        let mut sender_info = self.lock_sender_info();
+        if let (Some(active), false) = (sender_info.as_ref(), force) {
+            return Err(StackerDBChannelError::CoordinatorActive {
+                generation: active.registration_id,
+            });
+        }
+        let id = self.registrations.fetch_add(1, Ordering::SeqCst);
+        let (recv, new_sender) =
+            InnerStackerDBChannel::new_miner_receiver(id, Arc::new(contract_predicate));
+        let replaced_other = sender_info.replace(new_sender).is_some();
+
+        Ok((recv, StackerDBChannelRegistration { id, replaced_other }))
+    }
 
-        (recv, replaced_receiver)
+    /// Has a coordinator registered since `registration`? If so, the coordinator holding
+    /// `registration` no longer receives events, and should stop. Does not take the lock, so
+    /// that it can be checked on every poll.
+    pub fn is_superseded(&self, registration: &StackerDBChannelRegistration) -> bool {
+        self.registrations.load(Ordering::SeqCst) > registration.id + 1
     }
 
     /// Is there a thread holding the receiver, and is it interested in chunks events from `stackerdb`?
@@ -201,13 +347,13 @@ impl StackerDBChannel {
     pub fn is_active(
         &self,
         stackerdb: &QualifiedContractIdentifier,
-    ) -> Option<Sender<StackerDBChunksEvent>> {
+    ) -> Option<Sender<StackerDBChannelEvent>> {
         // if the receiver field is empty (i.e., None), then there is no listening thread, return None
-        let guard = self
-            .sender_info
-            .lock()
-            .expect("FATAL: poisoned StackerDBChannel lock");
+        let guard = self.lock_sender_info();
         let sender_info = guard.as_ref()?;
+        if !(sender_info.contract_predicate)(stackerdb) {
+            return None;
+        }
         if sender_info.interested_in_signers
             && stackerdb.is_boot()
             && stackerdb.name.starts_with(SIGNERS_NAME)
@@ -554,6 +700,10 @@ impl EventObserver {
         self.send_payload(payload, PATH_MINED_NAKAMOTO_BLOCK);
     }
 
+    fn send_signing_subsystem_info(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_SIGNING_SUBSYSTEM_INFO);
+    }
+
     fn send_stackerdb_chunks(&self, payload: &serde_json::Value) {
         self.send_payload(payload, PATH_STACKERDB_CHUNKS);
     }
@@ -578,7 +728,8 @@ impl EventObserver {
         mblock_confirmed_consumed: &ExecutionCost,
         pox_constants: &PoxConstants,
         reward_set_data: &Option<RewardSetData>,
-        signer_bitvec_opt: &Option<BitVec<4000>>,
+        signer_bitvec_opt: &Option<BitVec<SIGNER_BITVEC_MAX_SIZE>>,
+        signer_signature_data: Option<&[SignerSignatureData]>,
     ) -> serde_json::Value {
         // Serialize events to JSON
         let serialized_events: Vec<serde_json::Value> = filtered_events
@@ -612,6 +763,11 @@ impl EventObserver {
             None => (serde_json::Value::Null, serde_json::Value::Null),
         };
 
+        // only known for blocks this node's miner had signed
+        let signer_signature_data_value = signer_signature_data
+            .map(|data| serde_json::to_value(data).unwrap_or_default())
+            .unwrap_or_default();
+
         // Wrap events
         let mut payload = json!({
             "block_hash": format!("0x{}", block.block_hash),
@@ -639,6 +795,7 @@ impl EventObserver {
             "signer_bitvec": signer_bitvec_value,
             "reward_set": reward_set_value,
             "cycle_number": cycle_number_value,
+            "signer_signature_data": signer_signature_data_value,
         });
 
         let as_object_mut = payload.as_object_mut().unwrap();
@@ -680,6 +837,9 @@ pub struct EventDispatcher {
     mined_microblocks_observers_lookup: HashSet<u16>,
     stackerdb_observers_lookup: HashSet<u16>,
     block_proposal_observers_lookup: HashSet<u16>,
+    /// Which signers signed the blocks this node's miner broadcast, held until the blocks are
+    /// processed and announced. Shared with any clones of this dispatcher.
+    pending_signer_signature_data: Arc<Mutex<VecDeque<(StacksBlockId, Vec<SignerSignatureData>)>>>,
 }
 
 /// This struct is used specifically for receiving proposal responses.
@@ -820,7 +980,7 @@ impl BlockEventDispatcher for EventDispatcher {
         mblock_confirmed_consumed: &ExecutionCost,
         pox_constants: &PoxConstants,
         reward_set_data: &Option<RewardSetData>,
-        signer_bitvec: &Option<BitVec<4000>>,
+        signer_bitvec: &Option<BitVec<SIGNER_BITVEC_MAX_SIZE>>,
     ) {
         self.process_chain_tip(
             block,
@@ -874,7 +1034,41 @@ impl EventDispatcher {
             mined_microblocks_observers_lookup: HashSet::new(),
             stackerdb_observers_lookup: HashSet::new(),
             block_proposal_observers_lookup: HashSet::new(),
+            pending_signer_signature_data: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Hold which signers signed `block_id`, a block this node's miner is about to broadcast,
+    /// to include in the block's `new_block` payload once it is processed
+    pub fn process_signer_signature_data(
+        &self,
+        block_id: StacksBlockId,
+        signer_signature_data: Vec<SignerSignatureData>,
+    ) {
+        let mut pending = self
+            .pending_signer_signature_data
+            .lock()
+            .expect("FATAL: pending signer signature data lock poisoned");
+        pending.retain(|(pending_block_id, _)| *pending_block_id != block_id);
+        if pending.len() >= MAX_PENDING_SIGNER_SIGNATURE_DATA {
+            pending.pop_front();
         }
+        pending.push_back((block_id, signer_signature_data));
+    }
+
+    /// Take the signer signature data held for `block_id`, if any
+    fn take_signer_signature_data(
+        &self,
+        block_id: &StacksBlockId,
+    ) -> Option<Vec<SignerSignatureData>> {
+        let mut pending = self
+            .pending_signer_signature_data
+            .lock()
+            .expect("FATAL: pending signer signature data lock poisoned");
+        let idx = pending
+            .iter()
+            .position(|(pending_block_id, _)| pending_block_id == block_id)?;
+        pending.remove(idx).map(|(_, data)| data)
     }
 
     pub fn process_burn_block(
@@ -1019,8 +1213,9 @@ impl EventDispatcher {
         mblock_confirmed_consumed: &ExecutionCost,
         pox_constants: &PoxConstants,
         reward_set_data: &Option<RewardSetData>,
-        signer_bitvec: &Option<BitVec<4000>>,
+        signer_bitvec: &Option<BitVec<SIGNER_BITVEC_MAX_SIZE>>,
     ) {
+        let signer_signature_data = self.take_signer_signature_data(&metadata.index_block_hash());
         let all_receipts = receipts.to_owned();
         let (dispatch_matrix, events) = self.create_dispatch_matrix_and_event_vector(&all_receipts);
 
@@ -1071,6 +1266,7 @@ impl EventDispatcher {
                         pox_constants,
                         reward_set_data,
                         signer_bitvec,
+                        signer_signature_data.as_deref(),
                     );
 
                 // Send payload
@@ -1271,6 +1467,20 @@ impl EventDispatcher {
         }
     }
 
+    /// Report the signing protocol details of a miner's new tenure to the miner observers
+    pub fn process_signing_subsystem_info(&self, signing_info: &SigningSubsystemInfo) {
+        let interested_observers = self.filter_observers(&self.miner_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let payload = serde_json::to_value(signing_info)
+            .expect("FATAL: failed to serialize SigningSubsystemInfo to JSON");
+        for observer in interested_observers.iter() {
+            observer.send_signing_subsystem_info(&payload);
+        }
+    }
+
     /// Forward newly-accepted StackerDB chunk metadata to downstream `stackerdb` observers.
     /// Infallible.
     pub fn process_new_stackerdb_chunks(
@@ -1281,7 +1491,8 @@ impl EventDispatcher {
         let interested_observers = self.filter_observers(&self.stackerdb_observers_lookup, false);
 
         let interested_receiver = STACKER_DB_CHANNEL.is_active(&contract_id);
-        if interested_observers.is_empty() && interested_receiver.is_none() {
+        let interested_stream = STACKER_DB_STREAM.is_active(&contract_id);
+        if interested_observers.is_empty() && interested_receiver.is_none() && !interested_stream {
             return;
         }
 
@@ -1289,11 +1500,14 @@ impl EventDispatcher {
             contract_id,
             modified_slots,
         };
+        if interested_stream {
+            STACKER_DB_STREAM.broadcast(&event);
+        }
         let payload = serde_json::to_value(&event)
             .expect("FATAL: failed to serialize StackerDBChunksEvent to JSON");
 
         if let Some(channel) = interested_receiver {
-            if let Err(send_err) = channel.send(event) {
+            if let Err(send_err) = channel.send(StackerDBChannelEvent::new(event)) {
                 warn!(
                     "Failed to send StackerDB event to WSTS coordinator channel. Miner thread may have exited.";
                     "err" => ?send_err
@@ -1434,13 +1648,22 @@ impl EventDispatcher {
 #[cfg(test)]
 mod test {
     use clarity::vm::costs::ExecutionCost;
+    use clarity::vm::types::QualifiedContractIdentifier;
+    use serde_json::json;
     use stacks::burnchains::{PoxConstants, Txid};
+    use stacks::chainstate::stacks::boot::MINERS_NAME;
     use stacks::chainstate::stacks::db::StacksHeaderInfo;
+    use stacks::chainstate::stacks::events::StackerDBChunksEvent;
     use stacks::chainstate::stacks::StacksBlock;
+    use stacks::util_lib::boot::boot_code_id;
     use stacks_common::bitvec::BitVec;
     use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksBlockId};
 
-    use crate::event_dispatcher::EventObserver;
+    use crate::event_dispatcher::{
+        EventDispatcher, EventObserver, StackerDBChannel, StackerDBChannelError,
+        StackerDBChannelEvent, MAX_PENDING_SIGNER_SIGNATURE_DATA,
+    };
+    use crate::nakamoto_node::sign_round::SignerSignatureData;
 
     #[test]
     fn build_block_processed_event() {
@@ -1479,6 +1702,7 @@ mod test {
             &pox_constants,
             &None,
             &Some(signer_bitvec.clone()),
+            None,
         );
         assert_eq!(
             payload
@@ -1499,4 +1723,230 @@ mod test {
             expected_bitvec_str
         );
     }
+
+    #[test]
+    fn new_block_payload_includes_signer_signature_data() {
+        let observer = EventObserver {
+            endpoint: "nowhere".to_string(),
+        };
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        let signer_signature_data = vec![
+            SignerSignatureData {
+                signer_pubkey: "02".repeat(33),
+                weight: 3,
+                present: true,
+            },
+            SignerSignatureData {
+                signer_pubkey: "03".repeat(33),
+                weight: 1,
+                present: false,
+            },
+        ];
+        let make_payload = |signer_signature_data| {
+            observer.make_new_block_processed_payload(
+                vec![],
+                &StacksBlock::genesis_block().into(),
+                &metadata,
+                &[],
+                &StacksBlockId([0; 32]),
+                &Txid([0; 32]),
+                &serde_json::Value::Array(vec![]),
+                BurnchainHeaderHash([0; 32]),
+                0,
+                0,
+                &ExecutionCost::zero(),
+                &ExecutionCost::zero(),
+                &PoxConstants::testnet_default(),
+                &None,
+                &None,
+                signer_signature_data,
+            )
+        };
+
+        let payload = make_payload(Some(signer_signature_data.as_slice()));
+        assert_eq!(
+            payload.get("signer_signature_data").unwrap(),
+            &json!([
+                {"signer_pubkey": "02".repeat(33), "weight": 3, "present": true},
+                {"signer_pubkey": "03".repeat(33), "weight": 1, "present": false},
+            ])
+        );
+
+        // a block this node's miner did not sign has no signer signature data
+        let payload = make_payload(None);
+        assert!(payload.get("signer_signature_data").unwrap().is_null());
+    }
+
+    #[test]
+    fn signer_signature_data_is_held_until_taken() {
+        let dispatcher = EventDispatcher::new();
+        let data = |weight| {
+            vec![SignerSignatureData {
+                signer_pubkey: "02".repeat(33),
+                weight,
+                present: true,
+            }]
+        };
+        let block_id = |i: usize| StacksBlockId([u8::try_from(i).unwrap(); 32]);
+
+        // clones share what is held
+        dispatcher
+            .clone()
+            .process_signer_signature_data(block_id(0), data(1));
+        assert_eq!(
+            dispatcher.take_signer_signature_data(&block_id(0)),
+            Some(data(1))
+        );
+        assert_eq!(dispatcher.take_signer_signature_data(&block_id(0)), None);
+
+        // the oldest data is dropped once too many blocks are held
+        for i in 0..=MAX_PENDING_SIGNER_SIGNATURE_DATA {
+            dispatcher.process_signer_signature_data(block_id(i), data(1));
+        }
+        assert_eq!(dispatcher.take_signer_signature_data(&block_id(0)), None);
+        assert_eq!(
+            dispatcher.take_signer_signature_data(&block_id(MAX_PENDING_SIGNER_SIGNATURE_DATA)),
+            Some(data(1))
+        );
+    }
+
+    #[test]
+    fn filtered_stackerdb_channel() {
+        let channel = StackerDBChannel::new();
+        let (receiver, registration) = channel
+            .register_miner_coordinator_filtered(false, |contract| {
+                contract.name.starts_with("signers-1-")
+            })
+            .unwrap();
+        assert!(!registration.replaced_other);
+
+        let contracts = [
+            boot_code_id("signers-1-0", false),
+            boot_code_id("signers-0-0", false),
+            boot_code_id("signers-1-12", false),
+            boot_code_id(MINERS_NAME, false),
+            // not a boot contract
+            QualifiedContractIdentifier::parse(
+                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.signers-1-1",
+            )
+            .unwrap(),
+        ];
+        for contract_id in contracts.iter() {
+            if let Some(sender) = channel.is_active(contract_id) {
+                sender
+                    .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                        contract_id: contract_id.clone(),
+                        modified_slots: vec![],
+                    }))
+                    .unwrap();
+            }
+        }
+        let received: Vec<_> = receiver
+            .try_iter()
+            .map(|sent| sent.event.contract_id)
+            .collect();
+        assert_eq!(received, vec![contracts[0].clone(), contracts[2].clone()]);
+
+        // dropping the receiver unsubscribes
+        assert!(channel.replace_receiver(receiver, registration));
+        assert!(channel.is_active(&contracts[0]).is_none());
+
+        // an unfiltered registration gets every signers contract
+        let (_receiver, registration) = channel.register_miner_coordinator(false).unwrap();
+        assert!(!registration.replaced_other);
+        assert!(channel.is_active(&contracts[0]).is_some());
+        assert!(channel.is_active(&contracts[1]).is_some());
+        assert!(channel.is_active(&contracts[3]).is_none());
+        let (_receiver, registration) = channel
+            .register_miner_coordinator_filtered(true, |_| false)
+            .unwrap();
+        assert!(registration.replaced_other);
+        assert!(channel.is_active(&contracts[0]).is_none());
+    }
+
+    #[test]
+    fn stackerdb_channel_refuses_a_second_coordinator_unless_forced() {
+        let channel = StackerDBChannel::new();
+        let contract_id = boot_code_id("signers-0-0", false);
+
+        let (first_receiver, first) = channel.register_miner_coordinator(false).unwrap();
+        assert_eq!(first.generation(), 0);
+        assert!(!channel.is_superseded(&first));
+
+        // a second coordinator is refused, and the first keeps its events
+        assert_eq!(
+            channel.register_miner_coordinator(false).unwrap_err(),
+            StackerDBChannelError::CoordinatorActive { generation: 0 }
+        );
+        assert!(!channel.is_superseded(&first));
+        channel
+            .is_active(&contract_id)
+            .expect("The first registration was dropped")
+            .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                contract_id: contract_id.clone(),
+                modified_slots: vec![],
+            }))
+            .unwrap();
+        assert_eq!(first_receiver.try_iter().count(), 1);
+
+        // forcing it supersedes the first, whose receiver disconnects
+        let (receiver, second) = channel.register_miner_coordinator(true).unwrap();
+        assert!(second.replaced_other);
+        assert_eq!(second.generation(), 1);
+        assert!(channel.is_superseded(&first));
+        assert!(!channel.is_superseded(&second));
+        assert!(
+            first_receiver.recv().is_err(),
+            "The superseded receiver is still connected"
+        );
+
+        // once the newest coordinator is released, the next one registers without force
+        assert!(!channel.replace_receiver(first_receiver, first));
+        assert!(channel.replace_receiver(receiver, second));
+        let (_receiver, third) = channel.register_miner_coordinator(false).unwrap();
+        assert!(!third.replaced_other);
+        assert!(channel.is_superseded(&second));
+    }
+
+    #[test]
+    fn stale_stackerdb_channel_registrations_release_nothing() {
+        let channel = StackerDBChannel::new();
+        let contract_id = boot_code_id("signers-0-0", false);
+
+        // a miner thread crashes without releasing its registration, and the next one forces
+        //  it out
+        let (stale_receiver, stale_registration) =
+            channel.register_miner_coordinator(false).unwrap();
+        let (receiver, registration) = channel.register_miner_coordinator(true).unwrap();
+        assert!(registration.replaced_other);
+
+        // releasing the crashed thread's registration leaves the newest receiver in place
+        assert!(!channel.replace_receiver(stale_receiver, stale_registration));
+        channel
+            .is_active(&contract_id)
+            .expect("The newest registration was released")
+            .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                contract_id: contract_id.clone(),
+                modified_slots: vec![],
+            }))
+            .unwrap();
+        assert_eq!(receiver.try_iter().count(), 1);
+
+        // a thread that panics while holding the lock does not take the channel down with it
+        let poisoned = std::thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = channel.sender_info.lock().unwrap();
+                panic!("Miner thread crashed while holding the StackerDB channel");
+            })
+            .join()
+        });
+        assert!(poisoned.is_err());
+        assert!(channel.sender_info.is_poisoned());
+        assert!(channel.is_active(&contract_id).is_some());
+        assert!(channel.replace_receiver(receiver, registration));
+        assert!(channel.is_active(&contract_id).is_none());
+        // and releasing it again is harmless
+        let (_, receiver) = std::sync::mpsc::channel();
+        assert!(!channel.replace_receiver(receiver, registration));
+    }
 }
@@ -27,22 +27,28 @@ pub mod neon_node;
 pub mod node;
 pub mod operations;
 pub mod run_loop;
+pub mod stackerdb_stream;
 pub mod syncctl;
 pub mod tenure;
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::{env, panic, process};
 
 use backtrace::Backtrace;
 use pico_args::Arguments;
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::burn::operations::leader_block_commit::RewardSetInfo;
+use stacks::chainstate::burn::ConsensusHash;
 use stacks::chainstate::coordinator::{get_next_recipients, OnChainRewardSetProvider};
 use stacks::chainstate::stacks::address::PoxAddress;
 use stacks::chainstate::stacks::db::blocks::DummyEventDispatcher;
 use stacks::chainstate::stacks::db::StacksChainState;
+use stacks_common::types::chainstate::StacksPublicKey;
+use stacks_common::util::hash::Sha512Trunc256Sum;
 #[cfg(not(any(target_os = "macos", target_os = "windows", target_arch = "arm")))]
 use tikv_jemallocator::Jemalloc;
+use wsts::curve::ecdsa;
 
 pub use self::burnchains::{
     BitcoinRegtestController, BurnchainController, BurnchainTip, MocknetController,
@@ -54,6 +60,9 @@ pub use self::node::{ChainTip, Node};
 pub use self::run_loop::{helium, neon};
 pub use self::tenure::Tenure;
 use crate::chain_data::MinerStats;
+use crate::nakamoto_node::sign_replay::{
+    load_replay_events, load_reward_set, replay_sign_round, ReplayParams,
+};
 use crate::neon_node::{BlockMinerThread, TipCandidate};
 use crate::run_loop::boot_nakamoto;
 
@@ -265,6 +274,32 @@ fn cli_get_miner_spend(
     spend_amount
 }
 
+/// Implementation of the `replay-sign-round` CLI option. Prints each step of the replayed
+/// round, and then its outcome, as a line of JSON.
+fn cli_replay_sign_round(events_path: &str, reward_set_path: &str, params: ReplayParams) {
+    let events = load_replay_events(Path::new(events_path)).unwrap_or_else(|e| {
+        warn!("Failed to load StackerDB events: {}", e);
+        process::exit(1);
+    });
+    let reward_set = load_reward_set(Path::new(reward_set_path)).unwrap_or_else(|e| {
+        warn!("Failed to load stacker set: {}", e);
+        process::exit(1);
+    });
+    info!(
+        "Replaying {} StackerDB events against {} signers",
+        events.len(),
+        reward_set.signers.as_ref().map(Vec::len).unwrap_or(0)
+    );
+    let outcome = replay_sign_round(&params, &reward_set, events, |step| {
+        println!("{}", serde_json::to_string(step).unwrap());
+    })
+    .unwrap_or_else(|e| {
+        warn!("Failed to replay signing round: {}", e);
+        process::exit(1);
+    });
+    println!("{}", serde_json::to_string(&outcome).unwrap());
+}
+
 fn main() {
     panic::set_hook(Box::new(|panic_info| {
         error!("Process abort due to thread panic: {}", panic_info);
@@ -412,6 +447,37 @@ fn main() {
             println!("Will spend {}", spend_amount);
             process::exit(0);
         }
+        "replay-sign-round" => {
+            let events_path: String = args.value_from_str("--events").unwrap();
+            let reward_set_path: String = args.value_from_str("--stacker-set").unwrap();
+            let coordinator_pk: StacksPublicKey = args
+                .value_from_fn("--coordinator-pubkey", StacksPublicKey::from_hex)
+                .unwrap();
+            let params = ReplayParams {
+                is_mainnet: args.contains("--mainnet"),
+                reward_cycle: args.value_from_str("--reward-cycle").unwrap(),
+                boundary_reward_cycle: args.opt_value_from_str("--boundary-reward-cycle").unwrap(),
+                block_sighash: args
+                    .value_from_fn("--block-sighash", Sha512Trunc256Sum::from_hex)
+                    .unwrap(),
+                sign_id: args.value_from_str("--sign-id").unwrap(),
+                sign_iter_id: args.value_from_str("--sign-iter-id").unwrap(),
+                miner_burn_view: args
+                    .value_from_fn("--burn-view", ConsensusHash::from_hex)
+                    .unwrap(),
+                election_consensus_hash: args
+                    .opt_value_from_fn("--election-consensus-hash", ConsensusHash::from_hex)
+                    .unwrap(),
+                coordinator_pk: ecdsa::PublicKey::try_from(
+                    coordinator_pk.to_bytes_compressed().as_slice(),
+                )
+                .expect("Coordinator public key is not a valid compressed point"),
+            };
+            args.finish();
+
+            cli_replay_sign_round(&events_path, &reward_set_path, params);
+            process::exit(0);
+        }
         _ => {
             print_help();
             return;
@@ -502,6 +568,18 @@ key-for-seed\tOutput the associated secret key for a burnchain signer created wi
 \t\tCan be passed a config file for the seed via the `--config <file>` option *or* by supplying the hex seed on
 \t\tthe command line directly.
 
+replay-sign-round\tReplay a dump of the StackerDB events a miner received through a signing round, printing each
+\t\tstep of the round and its outcome as JSON.
+\t\tArguments:
+\t\t  --events: a JSONL file of timestamped StackerDB events, or a directory of them.
+\t\t  --stacker-set: the stacker set JSON, as returned by /v2/stacker_set/<cycle>.
+\t\t  --reward-cycle, --sign-id, --sign-iter-id: the signing round to replay.
+\t\t  --block-sighash, --burn-view: the proposed block's signer signature hash and burnchain view, in hex.
+\t\t  --coordinator-pubkey: the miner's coordinator public key, in hex.
+\t\t  --boundary-reward-cycle: optional adjacent reward cycle whose signers were also accepted.
+\t\t  --election-consensus-hash: optional consensus hash of the sortition that elected the block's tenure, in hex.
+\t\t  --mainnet: optional, the stacker set is a mainnet stacker set.
+
 help\t\tDisplay this help.
 
 OPTIONAL ARGUMENTS:
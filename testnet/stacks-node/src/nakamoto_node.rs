@@ -14,13 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 use std::collections::HashSet;
+use std::fmt;
 use std::sync::mpsc::Receiver;
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Duration;
 
 use stacks::burnchains::{BurnchainSigner, Txid};
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
-use stacks::chainstate::burn::BlockSnapshot;
+use stacks::chainstate::burn::{BlockSnapshot, ConsensusHash};
 use stacks::chainstate::stacks::Error as ChainstateError;
 use stacks::monitoring;
 use stacks::monitoring::update_active_miners_count_gauge;
@@ -30,6 +32,7 @@ use stacks::net::relay::Relayer;
 use stacks::net::stackerdb::StackerDBs;
 use stacks_common::types::chainstate::SortitionId;
 use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::Hash160;
 
 use super::{Config, EventDispatcher, Keychain};
 use crate::burnchains::bitcoin_regtest_controller::addr2str;
@@ -37,18 +40,29 @@ use crate::neon_node::{LeaderKeyRegistrationState, StacksNode as NeonNode};
 use crate::run_loop::nakamoto::{Globals, RunLoop};
 use crate::run_loop::RegisteredKey;
 
+pub mod equivocation_db;
 pub mod miner;
 pub mod peer;
+pub mod proposal_db;
 pub mod relayer;
 pub mod sign_coordinator;
+pub mod sign_replay;
+pub mod sign_round;
+pub mod sign_trace;
 
 use self::peer::PeerThread;
 use self::relayer::{RelayerDirective, RelayerThread};
+use self::sign_coordinator::ProposalPreflightError;
 
 pub const RELAYER_MAX_BUFFER: usize = 100;
 const VRF_MOCK_MINER_KEY: u64 = 1;
 
 pub const BLOCK_PROCESSOR_STACK_SIZE: usize = 32 * 1024 * 1024; // 32 MB
+/// How long a miner whose node is still syncing waits before it mines another block
+pub const NODE_SYNC_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+/// How long a miner waits for another miner thread's sign coordinator to give up the
+/// StackerDB channel before it tries to register its own again
+pub const COORDINATOR_REGISTRATION_RETRY_INTERVAL: Duration = Duration::from_secs(1);
 
 pub type BlockCommits = HashSet<Txid>;
 
@@ -74,7 +88,12 @@ pub enum Error {
     /// Can't find the block sortition snapshot for the chain tip
     SnapshotNotFoundForChainTip,
     /// The burnchain tip changed while this operation was in progress
-    BurnchainTipChanged,
+    BurnchainTipChanged {
+        /// Consensus hash of the burnchain tip the operation started on
+        old_consensus_hash: ConsensusHash,
+        /// Consensus hash of the new canonical burnchain tip
+        new_consensus_hash: ConsensusHash,
+    },
     /// Error while spawning a subordinate thread
     SpawnError(std::io::Error),
     /// Injected testing errors
@@ -96,15 +115,144 @@ pub enum Error {
     CannotSelfSign,
     MiningFailure(ChainstateError),
     MinerSignatureError(&'static str),
-    SignerSignatureError(String),
     /// A failure occurred while configuring the miner thread
     MinerConfigurationFailed(&'static str),
-    /// An error occurred while operating as the signing coordinator
-    SigningCoordinatorFailure(String),
+    /// The miner could not get its block signed by the signer set
+    SigningFailure(SigningError),
+    /// The block was not proposed, because it failed checks that the signers would reject it for
+    ProposalPreflightFailed(ProposalPreflightError),
+    /// Signing failed and most of the rejecting signers could not reach their stacks-node, so
+    /// the miner should wait before proposing another block
+    SignersConnectivityIssues {
+        /// Why signing failed
+        reason: SigningError,
+        /// How long to wait before proposing again
+        reproposal_backoff: Duration,
+    },
+    /// The miner key is not the one registered by the winner of the sortition that elected
+    /// the tenure, so signers would ignore anything written with it
+    NotWinningMiner {
+        /// Consensus hash of the election sortition
+        consensus_hash: ConsensusHash,
+        /// Hash160 of the winning miner's key, or `None` if there was no sortition
+        winner_key_hash: Option<Hash160>,
+        /// Hash160 of the configured miner key
+        miner_key_hash: Hash160,
+    },
+    /// The `.miners` contract gives this miner fewer slots than it writes messages to, so
+    /// the signers would never see some of them
+    MinersContractMisconfigured {
+        /// Number of slots the contract gives the miner
+        slot_count: u32,
+        /// Number of slots the miner needs, one per `MinerSlotID`
+        required_slots: u32,
+    },
+    /// The node's canonical Stacks tip trails the burnchain by more tenures than the miner
+    /// tolerates, so signers would reject anything it proposes until it catches up
+    NodeNotSynced {
+        /// Consensus hash of the tenure of the canonical Stacks tip
+        stacks_tip: ConsensusHash,
+        /// Consensus hash of the sortition that elected the tenure being mined
+        burn_tip: ConsensusHash,
+    },
     // The thread that we tried to send to has closed
     ChannelClosed,
 }
 
+/// Why the signing coordinator could not get a block signed
+#[derive(Debug, Clone, PartialEq)]
+pub enum SigningError {
+    /// The signers did not reach a decision before the signing round timed out
+    Timeout,
+    /// Signers holding `weight` rejected the block, so it can no longer be signed
+    Rejected { weight: u32 },
+    /// The WSTS coordinator failed to produce a signature
+    SignError(String),
+    /// The signature is not one the block can be broadcast with, e.g. because it does not
+    /// verify over the block
+    InvalidSignature(String),
+    /// The StackerDB event receiver disconnected, so signer responses can no longer be read
+    ChannelDisconnected,
+    /// The sign coordinator of generation `generation` is still registered on the StackerDB
+    /// channel, so this one could not register without stealing its events
+    CoordinatorActive { generation: u64 },
+    /// A newer sign coordinator registered on the StackerDB channel, so this one no longer
+    /// receives signer responses
+    Superseded,
+    /// A message could not be written to the miner's `.miners` slot
+    StackerDbWriteFailed { reason: String },
+    /// The coordinator could not be set up or run, e.g. because a database read failed
+    Coordinator(String),
+}
+
+impl fmt::Display for SigningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigningError::Timeout => write!(f, "Timed out waiting for group signature"),
+            SigningError::Rejected { weight } => {
+                write!(f, "Signers with weight {weight} rejected the block")
+            }
+            SigningError::SignError(e) => write!(f, "Signing failed: {e}"),
+            SigningError::ChannelDisconnected => {
+                write!(f, "StackerDB event receiver disconnected")
+            }
+            SigningError::CoordinatorActive { generation } => write!(
+                f,
+                "Sign coordinator of generation {generation} still holds the StackerDB channel"
+            ),
+            SigningError::Superseded => {
+                write!(
+                    f,
+                    "A newer sign coordinator took over the StackerDB channel"
+                )
+            }
+            SigningError::InvalidSignature(reason)
+            | SigningError::StackerDbWriteFailed { reason }
+            | SigningError::Coordinator(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+/// What the miner does after it failed to get a block signed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SigningFailureAction {
+    /// Propose a block again right away
+    RetryNow,
+    /// Wait this long, then propose a block again
+    ReproposeAfter(Duration),
+    /// Mine another block, since the signers would reject this one
+    MineAnother,
+    /// Wait this long for the node to catch up with the chain, then mine another block
+    AwaitSync(Duration),
+    /// Wait this long for another miner thread's sign coordinator to give up the StackerDB
+    /// channel, then mine another block
+    AwaitCoordinator(Duration),
+    /// Stop mining the tenure
+    EndTenure,
+}
+
+impl Error {
+    /// What the miner does if proposing a block to the signers failed with this error
+    pub fn signing_failure_action(&self) -> SigningFailureAction {
+        match self {
+            Error::SigningFailure(SigningError::StackerDbWriteFailed { .. }) => {
+                SigningFailureAction::RetryNow
+            }
+            Error::SignersConnectivityIssues {
+                reproposal_backoff, ..
+            } => SigningFailureAction::ReproposeAfter(*reproposal_backoff),
+            Error::ProposalPreflightFailed(_) => SigningFailureAction::MineAnother,
+            Error::NodeNotSynced { .. } => {
+                SigningFailureAction::AwaitSync(NODE_SYNC_RETRY_INTERVAL)
+            }
+            Error::SigningFailure(SigningError::CoordinatorActive { .. }) => {
+                SigningFailureAction::AwaitCoordinator(COORDINATOR_REGISTRATION_RETRY_INTERVAL)
+            }
+            _ => SigningFailureAction::EndTenure,
+        }
+    }
+}
+
 impl StacksNode {
     /// This function sets the global var `GLOBAL_BURNCHAIN_SIGNER`.
     ///
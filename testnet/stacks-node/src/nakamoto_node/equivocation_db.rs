@@ -0,0 +1,595 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistence for signers that vote to accept two different blocks at the same position in
+//! a tenure.
+//!
+//! In each signing round, a signer commits to its vote on the proposed block, which names the
+//! block's signer signature hash, in the nonce response it signs with its WSTS key. Once it
+//! also sends its signature share for the round, the miner records the first block the signer
+//! voted to accept at a given (tenure, height), along with that signed nonce response. If the
+//! same signer later votes to accept a different block at that position, both nonce responses
+//! are stored as evidence and served at `/v2/miner/equivocations`. Rejections are not recorded,
+//! so a signer that rejects a block and then accepts its re-proposal is not an equivocation.
+//! This is purely for observability; nothing in block production consults it.
+
+use std::path::{Path, PathBuf};
+
+use hashbrown::{HashMap, HashSet};
+use libsigner::v1::messages::SignerMessage;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, NO_PARAMS};
+use stacks::chainstate::burn::ConsensusHash;
+use stacks::chainstate::nakamoto::NakamotoBlockVote;
+use stacks::net::api::getminerequivocations::{SignerEquivocation, SignerEquivocationSource};
+use stacks::util_lib::db::{
+    sqlite_open, table_exists, tx_begin_immediate, u64_to_sql, Error as DBError,
+};
+use stacks_common::codec::{read_next, StacksMessageCodec};
+use stacks_common::util::get_epoch_time_secs;
+use stacks_common::util::hash::{to_hex, Sha512Trunc256Sum};
+use wsts::net::{Message as WstsMessage, Packet};
+
+/// How many blocks below the block being signed the signers' votes are kept for. Conflicting
+/// votes are only looked for among the kept ones; recorded equivocations are never pruned.
+pub const VOTE_RETENTION_BLOCKS: u64 = 256;
+
+const CREATE_SIGNER_FIRST_VOTES_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS signer_first_votes (
+    signer_key TEXT NOT NULL,
+    consensus_hash TEXT NOT NULL,
+    height INTEGER NOT NULL,
+    signer_sighash TEXT NOT NULL,
+    vote TEXT NOT NULL,
+    PRIMARY KEY (signer_key, consensus_hash, height)
+)";
+
+const CREATE_SIGNER_FIRST_VOTES_HEIGHT_INDEX: &str = "
+CREATE INDEX IF NOT EXISTS signer_first_votes_by_height ON signer_first_votes(height)";
+
+const CREATE_SIGNER_EQUIVOCATIONS_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS signer_equivocations (
+    signer_key TEXT NOT NULL,
+    consensus_hash TEXT NOT NULL,
+    height INTEGER NOT NULL,
+    first_sighash TEXT NOT NULL,
+    first_vote TEXT NOT NULL,
+    second_sighash TEXT NOT NULL,
+    second_vote TEXT NOT NULL,
+    detected_at INTEGER NOT NULL,
+    UNIQUE (signer_key, consensus_hash, height, second_sighash)
+)";
+
+/// The votes the signers signed in one signing round, matched up with their signature shares
+#[derive(Default)]
+pub struct RoundVotes {
+    /// Per WSTS signer ID, the signer's first vote in the round and the signer message that
+    /// carries it
+    votes: HashMap<u32, (NakamotoBlockVote, SignerMessage)>,
+    /// The WSTS signer IDs that sent signature shares in the round
+    signed: HashSet<u32>,
+}
+
+impl RoundVotes {
+    /// Take note of the nonce responses and signature shares of the round (`sign_id`,
+    /// `sign_iter_id`) in `packets`, which must already have been verified. Returns the WSTS
+    /// signer ID of each signer that has now both voted to accept the block with
+    /// `block_sighash` and sent its signature share, with the signer message holding its
+    /// signed nonce response. Each signer is returned at most once per round.
+    pub fn process_packets(
+        &mut self,
+        packets: &[Packet],
+        sign_id: u64,
+        sign_iter_id: u64,
+        block_sighash: &Sha512Trunc256Sum,
+    ) -> Vec<(u32, SignerMessage)> {
+        let mut signed_votes = vec![];
+        for packet in packets.iter() {
+            let signer_id = match &packet.msg {
+                WstsMessage::NonceResponse(response)
+                    if response.sign_id == sign_id && response.sign_iter_id == sign_iter_id =>
+                {
+                    if self.votes.contains_key(&response.signer_id) {
+                        continue;
+                    }
+                    // the signer signs its vote over the block, not the block itself
+                    let Ok(vote) = read_next::<NakamotoBlockVote, _>(&mut &response.message[..])
+                    else {
+                        continue;
+                    };
+                    self.votes.insert(
+                        response.signer_id,
+                        (vote, SignerMessage::Packet(packet.clone())),
+                    );
+                    if !self.signed.contains(&response.signer_id) {
+                        continue;
+                    }
+                    response.signer_id
+                }
+                WstsMessage::SignatureShareResponse(response)
+                    if response.sign_id == sign_id && response.sign_iter_id == sign_iter_id =>
+                {
+                    if !self.signed.insert(response.signer_id) {
+                        continue;
+                    }
+                    response.signer_id
+                }
+                _ => continue,
+            };
+            let Some((vote, message)) = self.votes.get(&signer_id) else {
+                continue;
+            };
+            if vote.rejected || &vote.signer_signature_hash != block_sighash {
+                continue;
+            }
+            signed_votes.push((signer_id, message.clone()));
+        }
+        signed_votes
+    }
+}
+
+/// SQLite-backed store for the votes the miner has seen and the equivocations among them
+pub struct SignerEquivocationDB {
+    db: Connection,
+}
+
+impl SignerEquivocationDB {
+    /// Open (or create) the database at the given path, or an in-memory database if the path
+    /// is ":memory:"
+    pub fn open(db_path: impl AsRef<Path>) -> Result<Self, DBError> {
+        let db = sqlite_open(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+            false,
+        )?;
+        let equivocation_db = Self { db };
+        equivocation_db.instantiate_db()?;
+        Ok(equivocation_db)
+    }
+
+    fn instantiate_db(&self) -> Result<(), DBError> {
+        if !table_exists(&self.db, "signer_first_votes")? {
+            self.db
+                .execute(CREATE_SIGNER_FIRST_VOTES_TABLE, NO_PARAMS)?;
+            self.db
+                .execute(CREATE_SIGNER_FIRST_VOTES_HEIGHT_INDEX, NO_PARAMS)?;
+        }
+        if !table_exists(&self.db, "signer_equivocations")? {
+            self.db
+                .execute(CREATE_SIGNER_EQUIVOCATIONS_TABLE, NO_PARAMS)?;
+        }
+        Ok(())
+    }
+
+    /// Record that the signer with `signer_key` voted to accept, and signed, the block with
+    /// `signer_sighash` at `height` in the tenure identified by `consensus_hash`. `vote` is
+    /// the signer message holding the signer's signed nonce response, which carries the vote.
+    /// Returns the evidence if the signer had already voted to accept a different block at
+    /// that position. Repeated reports of the same conflict are only recorded once.
+    pub fn record_vote(
+        &mut self,
+        signer_key: &str,
+        consensus_hash: &ConsensusHash,
+        height: u64,
+        signer_sighash: &Sha512Trunc256Sum,
+        vote: &SignerMessage,
+    ) -> Result<Option<SignerEquivocation>, DBError> {
+        let height_sql = u64_to_sql(height)?;
+        let vote = to_hex(&vote.serialize_to_vec());
+        let tx = tx_begin_immediate(&mut self.db)?;
+        let first: Option<(Sha512Trunc256Sum, String)> = tx
+            .query_row(
+                "SELECT signer_sighash, vote FROM signer_first_votes
+                    WHERE signer_key = ?1 AND consensus_hash = ?2 AND height = ?3",
+                params![signer_key, consensus_hash, height_sql],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((first_signer_sighash, first_vote)) = first else {
+            tx.execute(
+                "INSERT INTO signer_first_votes
+                    (signer_key, consensus_hash, height, signer_sighash, vote)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![signer_key, consensus_hash, height_sql, signer_sighash, vote],
+            )?;
+            tx.commit()?;
+            return Ok(None);
+        };
+        if &first_signer_sighash == signer_sighash {
+            return Ok(None);
+        }
+        let equivocation = SignerEquivocation {
+            signer_key: signer_key.to_string(),
+            consensus_hash: consensus_hash.clone(),
+            height,
+            first_signer_sighash,
+            first_vote,
+            second_signer_sighash: signer_sighash.clone(),
+            second_vote: vote,
+            detected_at: get_epoch_time_secs(),
+        };
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO signer_equivocations
+                (signer_key, consensus_hash, height, first_sighash, first_vote,
+                 second_sighash, second_vote, detected_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                equivocation.signer_key,
+                equivocation.consensus_hash,
+                height_sql,
+                equivocation.first_signer_sighash,
+                equivocation.first_vote,
+                equivocation.second_signer_sighash,
+                equivocation.second_vote,
+                u64_to_sql(equivocation.detected_at)?,
+            ],
+        )?;
+        tx.commit()?;
+        Ok((inserted > 0).then_some(equivocation))
+    }
+
+    /// Forget the votes recorded below `height`. Returns the number of votes forgotten.
+    pub fn prune_votes_below(&mut self, height: u64) -> Result<usize, DBError> {
+        let pruned = self.db.execute(
+            "DELETE FROM signer_first_votes WHERE height < ?1",
+            params![u64_to_sql(height)?],
+        )?;
+        Ok(pruned)
+    }
+
+    /// Get every equivocation recorded so far, oldest first
+    pub fn get_equivocations(&self) -> Result<Vec<SignerEquivocation>, DBError> {
+        let mut stmt = self.db.prepare(
+            "SELECT signer_key, consensus_hash, height, first_sighash, first_vote,
+                second_sighash, second_vote, detected_at
+                FROM signer_equivocations ORDER BY rowid ASC",
+        )?;
+        let rows = stmt.query_map(NO_PARAMS, |row| {
+            let height: i64 = row.get(2)?;
+            let detected_at: i64 = row.get(7)?;
+            let equivocation = SignerEquivocation {
+                signer_key: row.get(0)?,
+                consensus_hash: row.get(1)?,
+                height: u64::try_from(height).unwrap_or_default(),
+                first_signer_sighash: row.get(3)?,
+                first_vote: row.get(4)?,
+                second_signer_sighash: row.get(5)?,
+                second_vote: row.get(6)?,
+                detected_at: u64::try_from(detected_at).unwrap_or_default(),
+            };
+            Ok(equivocation)
+        })?;
+        rows.map(|row| row.map_err(DBError::from)).collect()
+    }
+}
+
+/// Serves the equivocations in the database at `path` to the RPC endpoint.
+/// The database is opened per request, since the miner thread holds its own connection.
+pub struct SignerEquivocationReader {
+    path: PathBuf,
+}
+
+impl SignerEquivocationReader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SignerEquivocationSource for SignerEquivocationReader {
+    fn get_signer_equivocations(&self) -> Result<Vec<SignerEquivocation>, String> {
+        SignerEquivocationDB::open(&self.path)
+            .and_then(|db| db.get_equivocations())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env::temp_dir;
+    use std::fs;
+
+    use libsigner::v1::messages::SignerMessage;
+    use libsigner::SignerSlotMessage;
+    use stacks::chainstate::burn::ConsensusHash;
+    use stacks::chainstate::nakamoto::NakamotoBlockVote;
+    use stacks::net::api::getminerequivocations::SignerEquivocation;
+    use stacks_common::codec::read_next;
+    use stacks_common::util::hash::{hex_bytes, to_hex, Sha512Trunc256Sum};
+    use wsts::net::{Message as WstsMessage, Packet, Signable};
+
+    use super::{RoundVotes, SignerEquivocationDB};
+    use crate::nakamoto_node::sign_round::testing::TestSigners;
+
+    const SIGN_ID: u64 = 3;
+
+    fn packet(message: SignerSlotMessage<SignerMessage>) -> Packet {
+        let SignerMessage::Packet(packet) = message.message else {
+            panic!("Expected a packet, got {:?}", message.message);
+        };
+        packet
+    }
+
+    fn vote(sighash: &Sha512Trunc256Sum, rejected: bool) -> NakamotoBlockVote {
+        NakamotoBlockVote {
+            signer_signature_hash: sighash.clone(),
+            rejected,
+        }
+    }
+
+    /// `signer`'s signed nonce response with `vote` and its signature share, in attempt
+    /// `sign_iter_id`
+    fn vote_and_share(
+        signers: &TestSigners,
+        signer: usize,
+        sign_iter_id: u64,
+        vote: &NakamotoBlockVote,
+    ) -> Vec<Packet> {
+        vec![
+            packet(signers.nonce_response(signer, SIGN_ID, sign_iter_id, vote)),
+            packet(signers.signature_share(signer, SIGN_ID, sign_iter_id)),
+        ]
+    }
+
+    /// Run attempt `sign_iter_id` at signing the block with `block_sighash` at `height` in
+    /// the tenure `consensus_hash`, in which `signer` sends `vote` and its signature share,
+    /// and record what the round yields
+    fn record_round(
+        db: &mut SignerEquivocationDB,
+        signers: &TestSigners,
+        signer: usize,
+        consensus_hash: &ConsensusHash,
+        height: u64,
+        sign_iter_id: u64,
+        block_sighash: &Sha512Trunc256Sum,
+        vote: &NakamotoBlockVote,
+    ) -> Vec<SignerEquivocation> {
+        let signer_key = to_hex(&signers.wsts_public_keys().signers[&(signer as u32)].to_bytes());
+        let packets = vote_and_share(signers, signer, sign_iter_id, vote);
+        RoundVotes::default()
+            .process_packets(&packets, SIGN_ID, sign_iter_id, block_sighash)
+            .into_iter()
+            .filter_map(|(_, vote)| {
+                db.record_vote(&signer_key, consensus_hash, height, block_sighash, &vote)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_signed_votes_to_accept_the_block_are_returned() {
+        let signers = TestSigners::new(&[1, 1, 1, 1]);
+        let block_sighash = Sha512Trunc256Sum([1; 32]);
+        let other_sighash = Sha512Trunc256Sum([2; 32]);
+        let mut round_votes = RoundVotes::default();
+
+        // signer 0 votes to accept the block and signs it
+        let packets = vote_and_share(&signers, 0, 1, &vote(&block_sighash, false));
+        let signed_votes = round_votes.process_packets(&packets, SIGN_ID, 1, &block_sighash);
+        assert_eq!(
+            signed_votes,
+            vec![(0, SignerMessage::Packet(packets[0].clone()))]
+        );
+        // but only once
+        assert!(round_votes
+            .process_packets(&packets, SIGN_ID, 1, &block_sighash)
+            .is_empty());
+
+        // signer 1 signs its rejection, and signer 2 votes for another block
+        let mut packets = vote_and_share(&signers, 1, 1, &vote(&block_sighash, true));
+        packets.extend(vote_and_share(&signers, 2, 1, &vote(&other_sighash, false)));
+        // signer 3 votes to accept the block, but has not signed it yet
+        let accepted = packet(signers.nonce_response(3, SIGN_ID, 1, &vote(&block_sighash, false)));
+        packets.push(accepted.clone());
+        // and the packets of another attempt are not part of this round
+        packets.extend(vote_and_share(&signers, 3, 2, &vote(&block_sighash, false)));
+        assert!(round_votes
+            .process_packets(&packets, SIGN_ID, 1, &block_sighash)
+            .is_empty());
+
+        // the signature share may arrive after the vote
+        let share = packet(signers.signature_share(3, SIGN_ID, 1));
+        assert_eq!(
+            round_votes.process_packets(&[share], SIGN_ID, 1, &block_sighash),
+            vec![(3, SignerMessage::Packet(accepted))]
+        );
+    }
+
+    #[test]
+    fn conflicting_accepted_votes_are_recorded() {
+        let mut db = SignerEquivocationDB::open(":memory:").unwrap();
+        let signers = TestSigners::new(&[1, 1]);
+        let consensus_hash = ConsensusHash([1; 20]);
+        let first_sighash = Sha512Trunc256Sum([2; 32]);
+        let second_sighash = Sha512Trunc256Sum([3; 32]);
+        let first_vote = vote(&first_sighash, false);
+        let second_vote = vote(&second_sighash, false);
+
+        let mut record =
+            |signer, consensus_hash, height, sign_iter_id, vote: &NakamotoBlockVote| {
+                let block_sighash = vote.signer_signature_hash.clone();
+                record_round(
+                    &mut db,
+                    &signers,
+                    signer,
+                    consensus_hash,
+                    height,
+                    sign_iter_id,
+                    &block_sighash,
+                    vote,
+                )
+            };
+        assert!(record(0, &consensus_hash, 10, 1, &first_vote).is_empty());
+        // signing the same block again is not an equivocation
+        assert!(record(0, &consensus_hash, 10, 2, &first_vote).is_empty());
+        // neither is signing a different block at another height or in another tenure
+        assert!(record(0, &consensus_hash, 11, 3, &second_vote).is_empty());
+        assert!(record(0, &ConsensusHash([4; 20]), 10, 4, &second_vote).is_empty());
+        // nor accepting the re-proposal of a block the signer rejected
+        assert!(record(1, &consensus_hash, 10, 1, &vote(&first_sighash, true)).is_empty());
+        assert!(record(1, &consensus_hash, 10, 2, &second_vote).is_empty());
+        drop(record);
+        assert!(db.get_equivocations().unwrap().is_empty());
+
+        let mut evidence = record_round(
+            &mut db,
+            &signers,
+            0,
+            &consensus_hash,
+            10,
+            5,
+            &second_sighash,
+            &second_vote,
+        );
+        assert_eq!(evidence.len(), 1);
+        let evidence = evidence.pop().unwrap();
+        let signer_key = signers.wsts_public_keys().signers[&0];
+        assert_eq!(evidence.signer_key, to_hex(&signer_key.to_bytes()));
+        assert_eq!(evidence.consensus_hash, consensus_hash);
+        assert_eq!(evidence.height, 10);
+        assert_eq!(evidence.first_signer_sighash, first_sighash);
+        assert_eq!(evidence.second_signer_sighash, second_sighash);
+
+        // each vote is the signer's signed nonce response, which names the block it accepts
+        for (vote_hex, expected) in [
+            (&evidence.first_vote, &first_vote),
+            (&evidence.second_vote, &second_vote),
+        ] {
+            let bytes = hex_bytes(vote_hex).unwrap();
+            let SignerMessage::Packet(packet) = read_next(&mut &bytes[..]).unwrap() else {
+                panic!("Expected the vote to be a packet");
+            };
+            let WstsMessage::NonceResponse(response) = &packet.msg else {
+                panic!("Expected the vote to be a nonce response");
+            };
+            assert!(response.verify(&packet.sig, &signer_key));
+            let signed_vote: NakamotoBlockVote = read_next(&mut &response.message[..]).unwrap();
+            assert_eq!(&signed_vote, expected);
+        }
+
+        // the same conflict reported again is only stored once
+        assert!(record_round(
+            &mut db,
+            &signers,
+            0,
+            &consensus_hash,
+            10,
+            6,
+            &second_sighash,
+            &second_vote
+        )
+        .is_empty());
+        assert_eq!(db.get_equivocations().unwrap(), vec![evidence]);
+    }
+
+    #[test]
+    fn votes_below_a_height_are_pruned() {
+        let mut db = SignerEquivocationDB::open(":memory:").unwrap();
+        let signers = TestSigners::new(&[1]);
+        let consensus_hash = ConsensusHash([5; 20]);
+        let first_sighash = Sha512Trunc256Sum([6; 32]);
+        let second_sighash = Sha512Trunc256Sum([7; 32]);
+        let first_vote = vote(&first_sighash, false);
+        let second_vote = vote(&second_sighash, false);
+        for height in [10, 20] {
+            record_round(
+                &mut db,
+                &signers,
+                0,
+                &consensus_hash,
+                height,
+                1,
+                &first_sighash,
+                &first_vote,
+            );
+        }
+
+        assert_eq!(db.prune_votes_below(15).unwrap(), 1);
+        assert_eq!(db.prune_votes_below(15).unwrap(), 0);
+
+        // the pruned vote is forgotten, but the kept one still catches a conflict
+        assert!(record_round(
+            &mut db,
+            &signers,
+            0,
+            &consensus_hash,
+            10,
+            2,
+            &second_sighash,
+            &second_vote
+        )
+        .is_empty());
+        assert_eq!(
+            record_round(
+                &mut db,
+                &signers,
+                0,
+                &consensus_hash,
+                20,
+                2,
+                &second_sighash,
+                &second_vote
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn equivocations_survive_restart() {
+        let path = temp_dir().join(format!(
+            "equivocations_survive_restart-{}.sqlite",
+            rand::random::<u64>()
+        ));
+        let signers = TestSigners::new(&[1]);
+        let consensus_hash = ConsensusHash([8; 20]);
+        let first_sighash = Sha512Trunc256Sum([9; 32]);
+        let second_sighash = Sha512Trunc256Sum([10; 32]);
+        {
+            // the signer signs one block, then the node goes away
+            let mut db = SignerEquivocationDB::open(&path).unwrap();
+            record_round(
+                &mut db,
+                &signers,
+                0,
+                &consensus_hash,
+                20,
+                1,
+                &first_sighash,
+                &vote(&first_sighash, false),
+            );
+        }
+
+        // after the restart, a conflicting vote is still caught
+        let mut db = SignerEquivocationDB::open(&path).unwrap();
+        let mut evidence = record_round(
+            &mut db,
+            &signers,
+            0,
+            &consensus_hash,
+            20,
+            2,
+            &second_sighash,
+            &vote(&second_sighash, false),
+        );
+        let evidence = evidence.pop().unwrap();
+        assert_eq!(evidence.first_signer_sighash, first_sighash);
+        drop(db);
+
+        let db = SignerEquivocationDB::open(&path).unwrap();
+        assert_eq!(db.get_equivocations().unwrap(), vec![evidence]);
+
+        drop(db);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -22,6 +22,7 @@ use clarity::vm::clarity::ClarityConnection;
 use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
 use hashbrown::HashSet;
 use libsigner::v1::messages::{MessageSlotID, SignerMessage};
+use libsigner::TenureView;
 use stacks::burnchains::Burnchain;
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::burn::{BlockSnapshot, ConsensusHash};
@@ -31,22 +32,28 @@ use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
 use stacks::chainstate::stacks::db::{StacksChainState, StacksHeaderInfo};
 use stacks::chainstate::stacks::{
     CoinbasePayload, Error as ChainstateError, StacksTransaction, StacksTransactionSigner,
-    TenureChangeCause, TenureChangePayload, ThresholdSignature, TransactionAnchorMode,
-    TransactionPayload, TransactionVersion,
+    TenureChangeCause, TenureChangePayload, TransactionAnchorMode, TransactionPayload,
+    TransactionVersion,
 };
 use stacks::net::stackerdb::StackerDBs;
 use stacks_common::codec::read_next;
 use stacks_common::types::chainstate::{StacksAddress, StacksBlockId};
 use stacks_common::types::{PrivateKey, StacksEpochId};
+use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::Hash160;
 use stacks_common::util::vrf::VRFProof;
 use wsts::curve::point::Point;
-use wsts::curve::scalar::Scalar;
 
+use super::proposal_db::InflightProposal;
 use super::relayer::RelayerThread;
-use super::sign_coordinator::SignCoordinator;
-use super::{Config, Error as NakamotoNodeError, EventDispatcher, Keychain};
+use super::sign_coordinator::{broadcast_deadline, SignCoordinator, SignedBlockResult};
+use super::sign_round::{SignerLatencyEstimate, TenureSigningStats};
+use super::{
+    Config, Error as NakamotoNodeError, EventDispatcher, Keychain, SigningError,
+    SigningFailureAction,
+};
 use crate::burnchains::bitcoin_regtest_controller::burnchain_params_from_config;
+use crate::config::SignCoordinatorConfig;
 use crate::nakamoto_node::VRF_MOCK_MINER_KEY;
 use crate::run_loop::nakamoto::Globals;
 use crate::run_loop::RegisteredKey;
@@ -106,6 +113,18 @@ pub struct BlockMinerThread {
     parent_tenure_id: StacksBlockId,
     /// Handle to the node's event dispatcher
     event_dispatcher: EventDispatcher,
+    /// Proposal from a previous run of this tenure whose signing round should be resumed
+    ///  before mining anything new
+    resume_proposal: Option<InflightProposal>,
+    /// How long the signers took to sign this tenure's blocks, to size the next ones by
+    signer_latency: SignerLatencyEstimate,
+    /// How each signer responded to this tenure's proposals
+    tenure_signing_stats: TenureSigningStats,
+    /// Whether this tenure's signing subsystem info has been reported yet
+    reported_signing_info: bool,
+    /// Since when another miner thread's sign coordinator has kept this one off the StackerDB
+    /// channel
+    coordinator_refused_since: Option<Instant>,
 }
 
 impl BlockMinerThread {
@@ -115,6 +134,7 @@ impl BlockMinerThread {
         registered_key: RegisteredKey,
         burn_block: BlockSnapshot,
         parent_tenure_id: StacksBlockId,
+        resume_proposal: Option<InflightProposal>,
     ) -> BlockMinerThread {
         BlockMinerThread {
             config: rt.config.clone(),
@@ -126,6 +146,11 @@ impl BlockMinerThread {
             burn_block,
             event_dispatcher: rt.event_dispatcher.clone(),
             parent_tenure_id,
+            resume_proposal,
+            signer_latency: SignerLatencyEstimate::default(),
+            tenure_signing_stats: TenureSigningStats::default(),
+            reported_signing_info: false,
+            coordinator_refused_since: None,
         }
     }
 
@@ -156,44 +181,133 @@ impl BlockMinerThread {
         let mut attempts = 0;
         // now, actually run this tenure
         loop {
-            let new_block = loop {
-                match self.mine_block(&stackerdbs) {
-                    Ok(x) => break Some(x),
-                    Err(NakamotoNodeError::MiningFailure(ChainstateError::MinerAborted)) => {
-                        info!("Miner interrupted while mining, will try again");
-                        // sleep, and try again. if the miner was interrupted because the burnchain
-                        // view changed, the next `mine_block()` invocation will error
-                        thread::sleep(Duration::from_millis(ABORT_TRY_AGAIN_MS));
-                        continue;
-                    }
-                    Err(NakamotoNodeError::MiningFailure(
-                        ChainstateError::NoTransactionsToMine,
-                    )) => {
-                        debug!("Miner did not find any transactions to mine");
-                        break None;
-                    }
-                    Err(e) => {
-                        warn!("Failed to mine block: {e:?}");
-                        return;
+            let resumed = self.resume_proposal.take();
+            let new_block = if let Some(proposal) = resumed.as_ref() {
+                Some(proposal.block.clone())
+            } else {
+                loop {
+                    match self.mine_block(&stackerdbs) {
+                        Ok(x) => break Some(x),
+                        Err(NakamotoNodeError::MiningFailure(ChainstateError::MinerAborted)) => {
+                            info!("Miner interrupted while mining, will try again");
+                            // sleep, and try again. if the miner was interrupted because the burnchain
+                            // view changed, the next `mine_block()` invocation will error
+                            thread::sleep(Duration::from_millis(ABORT_TRY_AGAIN_MS));
+                            continue;
+                        }
+                        Err(NakamotoNodeError::MiningFailure(
+                            ChainstateError::NoTransactionsToMine,
+                        )) => {
+                            debug!("Miner did not find any transactions to mine");
+                            break None;
+                        }
+                        Err(e) => {
+                            warn!("Failed to mine block: {e:?}");
+                            return;
+                        }
                     }
                 }
             };
 
             if let Some(mut new_block) = new_block {
-                let (aggregate_public_key, signers_signature) = match self.coordinate_signature(
+                let (aggregate_public_key, signed_block) = match self.coordinate_signature(
                     &mut new_block,
                     self.burn_block.block_height,
                     &mut stackerdbs,
                     &mut attempts,
+                    resumed.as_ref(),
                 ) {
                     Ok(x) => x,
+                    Err(e) => match e.signing_failure_action() {
+                        SigningFailureAction::RetryNow => {
+                            warn!("Miner: Failed to reach the signers, proposing again"; "err" => ?e);
+                            continue;
+                        }
+                        SigningFailureAction::ReproposeAfter(reproposal_backoff) => {
+                            warn!(
+                                "Signers could not reach their stacks-nodes. Waiting before proposing again.";
+                                "err" => ?e,
+                                "backoff_ms" => reproposal_backoff.as_millis(),
+                            );
+                            if self
+                                .sleep_unless_burn_tip_changed(reproposal_backoff)
+                                .is_err()
+                            {
+                                return;
+                            }
+                            continue;
+                        }
+                        SigningFailureAction::MineAnother => {
+                            // the signers would reject the block, so mine another one
+                            warn!("Miner: Block failed preflight checks, will mine another"; "err" => ?e);
+                            thread::sleep(Duration::from_millis(ABORT_TRY_AGAIN_MS));
+                            continue;
+                        }
+                        SigningFailureAction::AwaitSync(retry_interval) => {
+                            // the next block is built on whatever the node has caught up to
+                            info!(
+                                "Miner: Node is still syncing its Stacks chain. Waiting before mining another block.";
+                                "err" => ?e,
+                                "retry_ms" => retry_interval.as_millis(),
+                            );
+                            if self.sleep_unless_burn_tip_changed(retry_interval).is_err() {
+                                return;
+                            }
+                            continue;
+                        }
+                        SigningFailureAction::AwaitCoordinator(retry_interval) => {
+                            // another miner thread's coordinator is still running its round
+                            warn!(
+                                "Miner: Another sign coordinator holds the StackerDB channel. Waiting before mining another block.";
+                                "err" => ?e,
+                                "retry_ms" => retry_interval.as_millis(),
+                            );
+                            if self.sleep_unless_burn_tip_changed(retry_interval).is_err() {
+                                return;
+                            }
+                            continue;
+                        }
+                        SigningFailureAction::EndTenure => {
+                            if let NakamotoNodeError::BurnchainTipChanged {
+                                old_consensus_hash,
+                                new_consensus_hash,
+                            } = e
+                            {
+                                // the relayer starts a new miner thread for the new burnchain tip
+                                info!(
+                                    "Miner: Burnchain tip changed while waiting for signers. Ending tenure.";
+                                    "old_consensus_hash" => %old_consensus_hash,
+                                    "new_consensus_hash" => %new_consensus_hash,
+                                );
+                                self.globals.counters.bump_missed_tenures();
+                            } else if let NakamotoNodeError::SigningFailure(
+                                SigningError::Superseded,
+                            ) = e
+                            {
+                                // a newer miner thread took over signing, so this one is stale
+                                info!("Miner: A newer sign coordinator took over the StackerDB channel. Ending tenure.");
+                            } else {
+                                error!("Unrecoverable error while proposing block to signer set: {e:?}. Ending tenure.");
+                            }
+                            return;
+                        }
+                    },
+                };
+
+                let signature_provenance = signed_block.provenance();
+                let signer_signature_data = signed_block.signer_signature_data().to_vec();
+                new_block.header.signer_signature = match signed_block.into_broadcast_signature() {
+                    Ok(signature) => signature,
                     Err(e) => {
-                        error!("Unrecoverable error while proposing block to signer set: {e:?}. Ending tenure.");
+                        error!("Miner: Not broadcasting block: {e:?}. Ending tenure.");
                         return;
                     }
                 };
-
-                new_block.header.signer_signature = signers_signature;
+                if !signer_signature_data.is_empty() {
+                    // the block may be processed as soon as it is broadcast
+                    self.event_dispatcher
+                        .process_signer_signature_data(new_block.block_id(), signer_signature_data);
+                }
                 if let Err(e) = self.broadcast(new_block.clone(), &aggregate_public_key) {
                     warn!("Error accepting own block: {e:?}. Will try mining again.");
                     continue;
@@ -205,6 +319,7 @@ impl BlockMinerThread {
                         "stacks_block_id" => %new_block.header.block_id(),
                         "block_height" => new_block.header.chain_length,
                         "consensus_hash" => %new_block.header.consensus_hash,
+                        "signature_provenance" => ?signature_provenance,
                     );
                     self.globals.coord().announce_new_stacks_block();
                 }
@@ -217,29 +332,40 @@ impl BlockMinerThread {
                 self.mined_blocks.push(new_block);
             }
 
-            let sort_db = SortitionDB::open(
-                &self.config.get_burn_db_file_path(),
-                true,
-                self.burnchain.pox_constants.clone(),
-            )
-            .expect("FATAL: could not open sortition DB");
-            let wait_start = Instant::now();
-            while wait_start.elapsed() < self.config.miner.wait_on_interim_blocks {
-                thread::sleep(Duration::from_millis(ABORT_TRY_AGAIN_MS));
-                if self.check_burn_tip_changed(&sort_db).is_err() {
-                    return;
-                }
+            if self
+                .sleep_unless_burn_tip_changed(self.config.miner.wait_on_interim_blocks)
+                .is_err()
+            {
+                return;
             }
         }
     }
 
+    /// Sleep for `duration`, waking up periodically to check whether the burnchain tip
+    /// changed. Errors if it did, in which case this tenure is over.
+    fn sleep_unless_burn_tip_changed(&self, duration: Duration) -> Result<(), NakamotoNodeError> {
+        let sort_db = SortitionDB::open(
+            &self.config.get_burn_db_file_path(),
+            true,
+            self.burnchain.pox_constants.clone(),
+        )
+        .expect("FATAL: could not open sortition DB");
+        let wait_start = Instant::now();
+        while wait_start.elapsed() < duration {
+            thread::sleep(Duration::from_millis(ABORT_TRY_AGAIN_MS));
+            self.check_burn_tip_changed(&sort_db)?;
+        }
+        Ok(())
+    }
+
     fn coordinate_signature(
         &mut self,
         new_block: &mut NakamotoBlock,
         burn_block_height: u64,
         stackerdbs: &mut StackerDBs,
         attempts: &mut u64,
-    ) -> Result<(Point, ThresholdSignature), NakamotoNodeError> {
+        resumed: Option<&InflightProposal>,
+    ) -> Result<(Point, SignedBlockResult), NakamotoNodeError> {
         let Some(miner_privkey) = self.config.miner.mining_key else {
             return Err(NakamotoNodeError::MinerConfigurationFailed(
                 "No mining key configured, cannot mine",
@@ -269,20 +395,26 @@ impl BlockMinerThread {
         let reward_info = match sort_db.get_preprocessed_reward_set_of(&tip.sortition_id) {
             Ok(Some(x)) => x,
             Ok(None) => {
-                return Err(NakamotoNodeError::SigningCoordinatorFailure(
-                    "No reward set found. Cannot initialize miner coordinator.".into(),
+                return Err(NakamotoNodeError::SigningFailure(
+                    SigningError::Coordinator(
+                        "No reward set found. Cannot initialize miner coordinator.".into(),
+                    ),
                 ));
             }
             Err(e) => {
-                return Err(NakamotoNodeError::SigningCoordinatorFailure(format!(
-                    "Failure while fetching reward set. Cannot initialize miner coordinator. {e:?}"
+                return Err(NakamotoNodeError::SigningFailure(SigningError::Coordinator(
+                    format!(
+                        "Failure while fetching reward set. Cannot initialize miner coordinator. {e:?}"
+                    ),
                 )));
             }
         };
 
         let Some(reward_set) = reward_info.known_selected_anchor_block_owned() else {
-            return Err(NakamotoNodeError::SigningCoordinatorFailure(
-                "Current reward cycle did not select a reward set. Cannot mine!".into(),
+            return Err(NakamotoNodeError::SigningFailure(
+                SigningError::Coordinator(
+                    "Current reward cycle did not select a reward set. Cannot mine!".into(),
+                ),
             ));
         };
 
@@ -295,37 +427,177 @@ impl BlockMinerThread {
             &sortition_handle,
             &new_block,
         ) else {
-            return Err(NakamotoNodeError::SigningCoordinatorFailure(
-                "Failed to obtain the active aggregate public key. Cannot mine!".into(),
+            return Err(NakamotoNodeError::SigningFailure(
+                SigningError::Coordinator(
+                    "Failed to obtain the active aggregate public key. Cannot mine!".into(),
+                ),
             ));
         };
 
-        let miner_privkey_as_scalar = Scalar::from(miner_privkey.as_slice().clone());
-        let mut coordinator = SignCoordinator::new(
+        if let Some(proposal) = resumed {
+            // the signers may have relayed the block while the miner was down, in which case
+            //  there is no round left to resume
+            let header = &proposal.block.header;
+            let signer_sighash = header.signer_signature_hash();
+            match chain_state
+                .nakamoto_blocks_db()
+                .get_nakamoto_block_by_signer_sighash(
+                    &header.consensus_hash,
+                    header.chain_length,
+                    &signer_sighash,
+                ) {
+                Ok(Some(staged_block))
+                    if staged_block.header.verify_signer(&aggregate_public_key) =>
+                {
+                    info!(
+                        "Miner: Resumed proposal is already signed and staged. Not resuming its signing round.";
+                        "signer_sighash" => %signer_sighash,
+                        "block_id" => %staged_block.block_id(),
+                    );
+                    return Ok((
+                        aggregate_public_key,
+                        SignedBlockResult::from_staged_block(&staged_block),
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(
+                        "Miner: Failed to look up a staged copy of the resumed proposal";
+                        "signer_sighash" => %signer_sighash,
+                        "err" => ?e,
+                    );
+                }
+            }
+        }
+
+        let coordinator_config = SignCoordinatorConfig::from_config(&self.config).map_err(|e| {
+            NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
+                "Invalid sign coordinator config: {e}"
+            )))
+        })?;
+        // a coordinator can only hold the StackerDB channel for longer than a whole signing
+        //  round if its thread is stuck, or was leaked, so it is forced off the channel then
+        let force_registration = self
+            .coordinator_refused_since
+            .map_or(false, |refused_since| {
+                refused_since.elapsed() >= coordinator_config.signing_round_timeout
+            });
+        let coordinator = match SignCoordinator::new(
             &reward_set,
             reward_cycle,
-            miner_privkey_as_scalar,
+            &miner_privkey,
             aggregate_public_key,
             &stackerdbs,
-            &self.config,
-        )
-        .map_err(|e| {
-            NakamotoNodeError::SigningCoordinatorFailure(format!(
-                "Failed to initialize the signing coordinator. Cannot mine! {e:?}"
-            ))
-        })?;
-
-        *attempts += 1;
-        let signature = coordinator.begin_sign(
-            new_block,
-            burn_block_height,
-            *attempts,
-            &tip,
             &self.burnchain,
             &sort_db,
-            &stackerdbs,
-            &self.globals.counters,
-        )?;
+            &tip,
+            coordinator_config,
+            force_registration,
+            &self.config,
+        ) {
+            Ok(coordinator) => {
+                self.coordinator_refused_since = None;
+                coordinator
+            }
+            Err(e @ NakamotoNodeError::SigningFailure(SigningError::CoordinatorActive { .. })) => {
+                self.coordinator_refused_since
+                    .get_or_insert_with(Instant::now);
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+        let mut coordinator = coordinator
+            .with_latency_estimate(self.signer_latency.clone())
+            .with_tenure_signing_stats(self.tenure_signing_stats.clone());
+        if !self.reported_signing_info {
+            let signing_info = coordinator.signing_info();
+            signing_info.log();
+            self.event_dispatcher.process_signing_subsystem_info(signing_info);
+            self.reported_signing_info = true;
+        }
+        let reward_set_stats = coordinator.reward_set_stats();
+        debug!(
+            "Miner: Signing weight of the reward set";
+            "reward_cycle" => reward_cycle,
+            "num_signers" => reward_set_stats.num_signers,
+            "min_weight" => reward_set_stats.min_weight,
+            "max_weight" => reward_set_stats.max_weight,
+            "median_weight" => reward_set_stats.median_weight,
+            "top_5_share" => reward_set_stats.top_5_share,
+            "hhi" => reward_set_stats.hhi,
+        );
+
+        let burn_view = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn()).map_err(|e| {
+            NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
+                "Failed to load the burnchain tip: {e:?}"
+            )))
+        })?;
+        let broadcast_deadline = broadcast_deadline(
+            burn_view.burn_header_timestamp,
+            Instant::now(),
+            get_epoch_time_secs(),
+        );
+        let miner_view = TenureView {
+            burn_view_consensus_hash: burn_view.consensus_hash,
+            parent_tenure_id: Some(self.parent_tenure_id.clone()),
+            tenure_change_cause: new_block
+                .get_tenure_tx_payload()
+                .map(|payload| payload.cause),
+        };
+        let signature = if let Some(proposal) = resumed {
+            // the resumed round needs a new iteration, so the attempt must move past the
+            //  one that was persisted
+            *attempts = (*attempts).max(proposal.attempt) + 1;
+            coordinator.resume_round(
+                proposal,
+                burn_block_height,
+                *attempts,
+                broadcast_deadline,
+                miner_view,
+                &tip,
+                &self.burnchain,
+                &sort_db,
+                &stackerdbs,
+                &self.globals.counters,
+            )
+        } else if matches!(
+            new_block
+                .get_tenure_tx_payload()
+                .map(|payload| &payload.cause),
+            Some(TenureChangeCause::Extended)
+        ) {
+            *attempts += 1;
+            coordinator.propose_tenure_extend(
+                new_block,
+                burn_block_height,
+                *attempts,
+                broadcast_deadline,
+                miner_view,
+                &tip,
+                &self.burnchain,
+                &sort_db,
+                &stackerdbs,
+                &self.globals.counters,
+            )
+        } else {
+            *attempts += 1;
+            coordinator.begin_sign(
+                new_block,
+                burn_block_height,
+                *attempts,
+                broadcast_deadline,
+                miner_view,
+                &tip,
+                &self.burnchain,
+                &sort_db,
+                &stackerdbs,
+                &self.globals.counters,
+            )
+        };
+        // rounds that failed count toward the signers' stats too
+        self.tenure_signing_stats = coordinator.tenure_signing_stats().clone();
+        let signature = signature?;
+        self.signer_latency = coordinator.latency_estimate().clone();
 
         Ok((aggregate_public_key, signature))
     }
@@ -343,8 +615,10 @@ impl BlockMinerThread {
         let signers_contract_id =
             msg_id.stacker_db_contract(self.config.is_mainnet(), reward_cycle);
         if !stackerdb_contracts.contains(&signers_contract_id) {
-            return Err(NakamotoNodeError::SignerSignatureError(
-                "No signers contract found, cannot wait for signers".into(),
+            return Err(NakamotoNodeError::SigningFailure(
+                SigningError::Coordinator(
+                    "No signers contract found, cannot wait for signers".into(),
+                ),
             ));
         };
         // Get the slots for every signer
@@ -642,9 +916,9 @@ impl BlockMinerThread {
             stacks_tip_header,
         ) {
             Ok(parent_info) => Ok(parent_info),
-            Err(NakamotoNodeError::BurnchainTipChanged) => {
+            Err(e @ NakamotoNodeError::BurnchainTipChanged { .. }) => {
                 self.globals.counters.bump_missed_tenures();
-                Err(NakamotoNodeError::BurnchainTipChanged)
+                Err(e)
             }
             Err(e) => Err(e),
         }
@@ -754,6 +1028,20 @@ impl BlockMinerThread {
         let signer_bitvec_len =
             &burn_db.get_preprocessed_reward_set_size(&self.burn_block.sortition_id);
 
+        let mut builder_settings = self
+            .config
+            .make_nakamoto_block_builder_settings(self.globals.get_miner_status());
+        if let Some(target_latency) = self.config.miner.target_signer_latency {
+            builder_settings.max_block_size = self
+                .signer_latency
+                .recommended_max_block_size(target_latency);
+            debug!(
+                "Miner: Sizing block to the signers' observed latency";
+                "target_latency_ms" => target_latency.as_millis(),
+                "max_block_size" => ?builder_settings.max_block_size,
+            );
+        }
+
         // build the block itself
         let (mut block, consumed, size, tx_events) = NakamotoBlockBuilder::build_nakamoto_block(
             &chain_state,
@@ -763,8 +1051,7 @@ impl BlockMinerThread {
             &self.burn_block.consensus_hash,
             self.burn_block.total_burn,
             tenure_start_info,
-            self.config
-                .make_nakamoto_block_builder_settings(self.globals.get_miner_status()),
+            builder_settings,
             // we'll invoke the event dispatcher ourselves so that it calculates the
             //  correct signer_sighash for `process_mined_nakamoto_block_event`
             Some(&self.event_dispatcher),
@@ -824,7 +1111,10 @@ impl BlockMinerThread {
         if cur_burn_chain_tip.consensus_hash != self.burn_block.consensus_hash {
             info!("Miner: Cancel block assembly; burnchain tip has changed");
             self.globals.counters.bump_missed_tenures();
-            Err(NakamotoNodeError::BurnchainTipChanged)
+            Err(NakamotoNodeError::BurnchainTipChanged {
+                old_consensus_hash: self.burn_block.consensus_hash.clone(),
+                new_consensus_hash: cur_burn_chain_tip.consensus_hash,
+            })
         } else {
             Ok(())
         }
@@ -866,7 +1156,10 @@ impl ParentStacksBlockInfo {
                 "new_burn_height" => burn_chain_tip.block_height,
                 "old_burn_height" => check_burn_block.block_height
             );
-            return Err(NakamotoNodeError::BurnchainTipChanged);
+            return Err(NakamotoNodeError::BurnchainTipChanged {
+                old_consensus_hash: check_burn_block.consensus_hash.clone(),
+                new_consensus_hash: burn_chain_tip.consensus_hash,
+            });
         }
 
         let Ok(Some(parent_tenure_header)) =
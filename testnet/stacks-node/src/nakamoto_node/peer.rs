@@ -27,12 +27,14 @@ use stacks::chainstate::stacks::miner::signal_mining_blocked;
 use stacks::core::mempool::MemPoolDB;
 use stacks::cost_estimates::metrics::{CostMetric, UnitMetric};
 use stacks::cost_estimates::{CostEstimator, FeeEstimator, UnitEstimator};
+use stacks::net::api::getminerequivocations::SignerEquivocationSource;
 use stacks::net::dns::{DNSClient, DNSResolver};
 use stacks::net::p2p::PeerNetwork;
 use stacks::net::RPCHandlerArgs;
 use stacks_common::util::hash::Sha256Sum;
 
 use crate::burnchains::make_bitcoin_indexer;
+use crate::nakamoto_node::equivocation_db::SignerEquivocationReader;
 use crate::nakamoto_node::relayer::RelayerDirective;
 use crate::neon_node::open_chainstate_with_faults;
 use crate::run_loop::nakamoto::{Globals, RunLoop};
@@ -254,6 +256,12 @@ impl PeerThread {
 
         self.refresh_stackerdb();
 
+        // only a miner records signer equivocations
+        let equivocation_source =
+            self.config.node.miner.then(|| {
+                SignerEquivocationReader::new(self.config.get_signer_equivocation_db_path())
+            });
+
         // do one pass
         let p2p_res = {
             // NOTE: handler_args must be created such that it outlives the inner net.run() call and
@@ -266,6 +274,9 @@ impl PeerThread {
                 cost_estimator: Some(cost_estimator.as_ref()),
                 cost_metric: Some(cost_metric.as_ref()),
                 fee_estimator: fee_estimator.map(|boxed_estimator| boxed_estimator.as_ref()),
+                equivocation_source: equivocation_source
+                    .as_ref()
+                    .map(|source| source as &dyn SignerEquivocationSource),
                 ..RPCHandlerArgs::default()
             };
             self.net.run(
@@ -0,0 +1,330 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistence for the block proposal that the miner is currently collecting signatures for.
+//!
+//! If the node restarts in the middle of a signing round, the new miner thread consults this
+//! database so that it re-proposes the same block (and therefore the same signer signature
+//! hash) instead of building a fresh one that signers would reject as a duplicate.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, NO_PARAMS};
+use stacks::chainstate::burn::ConsensusHash;
+use stacks::chainstate::nakamoto::NakamotoBlock;
+use stacks::util_lib::db::{
+    query_rows, sqlite_open, table_exists, tx_begin_immediate, u64_to_sql, Error as DBError,
+};
+use stacks_common::codec::{read_next, StacksMessageCodec};
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+const CREATE_INFLIGHT_PROPOSALS_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS inflight_proposals (
+    consensus_hash TEXT PRIMARY KEY,
+    block_id TEXT NOT NULL,
+    signer_sighash TEXT NOT NULL,
+    attempt INTEGER NOT NULL,
+    block BLOB NOT NULL
+)";
+
+const CREATE_INFLIGHT_SIGNATURE_SHARES_TABLE: &str = "
+CREATE TABLE IF NOT EXISTS inflight_signature_shares (
+    signer_sighash TEXT NOT NULL,
+    signer_id INTEGER NOT NULL,
+    PRIMARY KEY (signer_sighash, signer_id)
+)";
+
+/// A block proposal whose signing round had not finished when it was last persisted
+#[derive(Debug, Clone, PartialEq)]
+pub struct InflightProposal {
+    /// The proposed block
+    pub block: NakamotoBlock,
+    /// The signing attempt that proposed this block
+    pub attempt: u64,
+    /// IDs of the signers that had returned signature shares for this block
+    pub signer_ids: BTreeSet<u32>,
+}
+
+/// SQLite-backed store for the miner's in-flight block proposal
+pub struct InflightProposalDB {
+    db: Connection,
+}
+
+impl InflightProposalDB {
+    /// Open (or create) the database at the given path, or an in-memory database if the path
+    /// is ":memory:"
+    pub fn open(db_path: impl AsRef<Path>) -> Result<Self, DBError> {
+        let db = sqlite_open(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE,
+            false,
+        )?;
+        let proposal_db = Self { db };
+        proposal_db.instantiate_db()?;
+        Ok(proposal_db)
+    }
+
+    fn instantiate_db(&self) -> Result<(), DBError> {
+        if !table_exists(&self.db, "inflight_proposals")? {
+            self.db
+                .execute(CREATE_INFLIGHT_PROPOSALS_TABLE, NO_PARAMS)?;
+        }
+        if !table_exists(&self.db, "inflight_signature_shares")? {
+            self.db
+                .execute(CREATE_INFLIGHT_SIGNATURE_SHARES_TABLE, NO_PARAMS)?;
+        }
+        Ok(())
+    }
+
+    /// Record that a signing round for `block` has started.
+    /// Only one round is in flight per tenure, so this replaces any earlier proposal for the
+    /// block's tenure along with the signature shares gathered for it.
+    pub fn begin_round(&mut self, block: &NakamotoBlock, attempt: u64) -> Result<(), DBError> {
+        let tx = tx_begin_immediate(&mut self.db)?;
+        tx.execute(
+            "DELETE FROM inflight_signature_shares WHERE signer_sighash IN
+                (SELECT signer_sighash FROM inflight_proposals WHERE consensus_hash = ?1)",
+            params![block.header.consensus_hash],
+        )?;
+        tx.execute(
+            "INSERT OR REPLACE INTO inflight_proposals
+                (consensus_hash, block_id, signer_sighash, attempt, block)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                block.header.consensus_hash,
+                block.header.block_id(),
+                block.header.signer_signature_hash(),
+                u64_to_sql(attempt)?,
+                block.serialize_to_vec(),
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Record that `signer_id` returned a signature share for the block with `signer_sighash`
+    pub fn record_signature_share(
+        &self,
+        signer_sighash: &Sha512Trunc256Sum,
+        signer_id: u32,
+    ) -> Result<(), DBError> {
+        self.db.execute(
+            "INSERT OR IGNORE INTO inflight_signature_shares (signer_sighash, signer_id)
+                VALUES (?1, ?2)",
+            params![signer_sighash, signer_id],
+        )?;
+        Ok(())
+    }
+
+    /// Remove the proposal with `signer_sighash` once its signing round has ended
+    pub fn end_round(&mut self, signer_sighash: &Sha512Trunc256Sum) -> Result<(), DBError> {
+        let tx = tx_begin_immediate(&mut self.db)?;
+        tx.execute(
+            "DELETE FROM inflight_signature_shares WHERE signer_sighash = ?1",
+            params![signer_sighash],
+        )?;
+        tx.execute(
+            "DELETE FROM inflight_proposals WHERE signer_sighash = ?1",
+            params![signer_sighash],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get the in-flight proposal for the tenure identified by `consensus_hash`, if any
+    pub fn get_inflight_proposal(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Result<Option<InflightProposal>, DBError> {
+        let row: Option<(Vec<u8>, i64)> = self
+            .db
+            .query_row(
+                "SELECT block, attempt FROM inflight_proposals WHERE consensus_hash = ?1",
+                params![consensus_hash],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let Some((block_bytes, attempt)) = row else {
+            return Ok(None);
+        };
+        let block: NakamotoBlock =
+            read_next(&mut &block_bytes[..]).map_err(|_| DBError::ParseError)?;
+        let attempt = u64::try_from(attempt).map_err(|_| DBError::ParseError)?;
+        let signer_ids = query_rows::<i64, _>(
+            &self.db,
+            "SELECT signer_id FROM inflight_signature_shares WHERE signer_sighash = ?1",
+            params![block.header.signer_signature_hash()],
+        )?
+        .into_iter()
+        .map(|id| u32::try_from(id).map_err(|_| DBError::ParseError))
+        .collect::<Result<_, _>>()?;
+        Ok(Some(InflightProposal {
+            block,
+            attempt,
+            signer_ids,
+        }))
+    }
+
+    /// Drop every proposal that does not belong to the tenure identified by `consensus_hash`.
+    /// Those rounds can never be resumed, since their tenure is no longer the active one.
+    pub fn clear_stale_proposals(&mut self, consensus_hash: &ConsensusHash) -> Result<(), DBError> {
+        let tx = tx_begin_immediate(&mut self.db)?;
+        tx.execute(
+            "DELETE FROM inflight_signature_shares WHERE signer_sighash IN
+                (SELECT signer_sighash FROM inflight_proposals WHERE consensus_hash != ?1)",
+            params![consensus_hash],
+        )?;
+        tx.execute(
+            "DELETE FROM inflight_proposals WHERE consensus_hash != ?1",
+            params![consensus_hash],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Count the in-flight proposals, for use in tests
+    #[cfg(test)]
+    fn count_proposals(&self) -> u64 {
+        let count: i64 = self
+            .db
+            .query_row(
+                "SELECT COUNT(*) FROM inflight_proposals",
+                NO_PARAMS,
+                |row| row.get(0),
+            )
+            .unwrap();
+        u64::try_from(count).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env::temp_dir;
+    use std::fs;
+
+    use stacks::chainstate::burn::ConsensusHash;
+    use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
+
+    use super::InflightProposalDB;
+
+    fn make_block(consensus_byte: u8, chain_length: u64) -> NakamotoBlock {
+        let mut header = NakamotoBlockHeader::empty();
+        header.consensus_hash = ConsensusHash([consensus_byte; 20]);
+        header.chain_length = chain_length;
+        NakamotoBlock {
+            header,
+            txs: vec![],
+        }
+    }
+
+    #[test]
+    fn inflight_proposal_round_trip() {
+        let mut db = InflightProposalDB::open(":memory:").unwrap();
+        let block = make_block(1, 10);
+        let sighash = block.header.signer_signature_hash();
+        assert!(db
+            .get_inflight_proposal(&block.header.consensus_hash)
+            .unwrap()
+            .is_none());
+
+        db.begin_round(&block, 3).unwrap();
+        db.record_signature_share(&sighash, 2).unwrap();
+        db.record_signature_share(&sighash, 0).unwrap();
+        db.record_signature_share(&sighash, 2).unwrap();
+
+        let proposal = db
+            .get_inflight_proposal(&block.header.consensus_hash)
+            .unwrap()
+            .unwrap();
+        assert_eq!(proposal.block, block);
+        assert_eq!(proposal.attempt, 3);
+        assert_eq!(proposal.signer_ids.into_iter().collect::<Vec<_>>(), [0, 2]);
+
+        // a new proposal in the same tenure replaces the old one and its shares
+        let next_block = make_block(1, 11);
+        db.begin_round(&next_block, 4).unwrap();
+        assert_eq!(db.count_proposals(), 1);
+        let proposal = db
+            .get_inflight_proposal(&block.header.consensus_hash)
+            .unwrap()
+            .unwrap();
+        assert_eq!(proposal.block, next_block);
+        assert!(proposal.signer_ids.is_empty());
+
+        db.end_round(&next_block.header.signer_signature_hash())
+            .unwrap();
+        assert_eq!(db.count_proposals(), 0);
+    }
+
+    #[test]
+    fn stale_proposals_are_cleared() {
+        let mut db = InflightProposalDB::open(":memory:").unwrap();
+        let old_block = make_block(1, 10);
+        let new_block = make_block(2, 11);
+        db.begin_round(&old_block, 1).unwrap();
+        db.begin_round(&new_block, 1).unwrap();
+        assert_eq!(db.count_proposals(), 2);
+
+        db.clear_stale_proposals(&new_block.header.consensus_hash)
+            .unwrap();
+        assert!(db
+            .get_inflight_proposal(&old_block.header.consensus_hash)
+            .unwrap()
+            .is_none());
+        assert!(db
+            .get_inflight_proposal(&new_block.header.consensus_hash)
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn inflight_proposal_survives_restart() {
+        let path = temp_dir().join(format!(
+            "inflight_proposal_survives_restart-{}.sqlite",
+            rand::random::<u64>()
+        ));
+        let block = make_block(3, 20);
+        let sighash = block.header.signer_signature_hash();
+        {
+            // the miner starts a round and gathers a share, then the node goes away
+            let mut db = InflightProposalDB::open(&path).unwrap();
+            db.begin_round(&block, 1).unwrap();
+            db.record_signature_share(&sighash, 5).unwrap();
+        }
+
+        // after the restart, the same proposal is available for resumption
+        let mut db = InflightProposalDB::open(&path).unwrap();
+        let proposal = db
+            .get_inflight_proposal(&block.header.consensus_hash)
+            .unwrap()
+            .unwrap();
+        assert_eq!(proposal.block.header.signer_signature_hash(), sighash);
+        assert_eq!(proposal.block.block_id(), block.block_id());
+        assert_eq!(proposal.attempt, 1);
+        assert!(proposal.signer_ids.contains(&5));
+
+        // the resumed round re-proposes the same block, then cleans up when it ends
+        db.begin_round(&proposal.block, proposal.attempt + 1)
+            .unwrap();
+        db.end_round(&sighash).unwrap();
+        drop(db);
+        let db = InflightProposalDB::open(&path).unwrap();
+        assert_eq!(db.count_proposals(), 0);
+
+        drop(db);
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -57,6 +57,7 @@ use super::{
 };
 use crate::burnchains::BurnchainController;
 use crate::nakamoto_node::miner::{BlockMinerThread, MinerDirective};
+use crate::nakamoto_node::proposal_db::{InflightProposal, InflightProposalDB};
 use crate::neon_node::{
     fault_injection_skip_mining, open_chainstate_with_faults, LeaderKeyRegistrationState,
 };
@@ -572,11 +573,58 @@ impl RelayerThread {
             "parent_tenure_id" => %parent_tenure_id,
         );
 
-        let miner_thread_state =
-            BlockMinerThread::new(self, registered_key, last_burn_block, parent_tenure_id);
+        let resume_proposal = self.load_resumable_proposal(&last_burn_block);
+        let miner_thread_state = BlockMinerThread::new(
+            self,
+            registered_key,
+            last_burn_block,
+            parent_tenure_id,
+            resume_proposal,
+        );
         Ok(miner_thread_state)
     }
 
+    /// Load the proposal that a previous miner thread for this tenure was still collecting
+    /// signatures for when the node stopped, if it still builds on the canonical Stacks tip.
+    /// Proposals for other tenures can never be resumed, so they are discarded.
+    fn load_resumable_proposal(&mut self, burn_block: &BlockSnapshot) -> Option<InflightProposal> {
+        let mut proposal_db = InflightProposalDB::open(self.config.get_inflight_proposal_db_path())
+            .map_err(|e| warn!("Relayer: Failed to open the in-flight proposal DB: {e:?}"))
+            .ok()?;
+        if let Err(e) = proposal_db.clear_stale_proposals(&burn_block.consensus_hash) {
+            warn!("Relayer: Failed to clear stale in-flight proposals: {e:?}");
+        }
+        let proposal = proposal_db
+            .get_inflight_proposal(&burn_block.consensus_hash)
+            .map_err(|e| warn!("Relayer: Failed to load in-flight proposal: {e:?}"))
+            .ok()??;
+        let canonical_tip =
+            NakamotoChainState::get_canonical_block_header(self.chainstate.db(), &self.sortdb)
+                .map_err(|e| warn!("Relayer: Failed to load canonical Stacks tip: {e:?}"))
+                .ok()??
+                .index_block_hash();
+        let signer_sighash = proposal.block.header.signer_signature_hash();
+        if proposal.block.header.parent_block_id != canonical_tip {
+            info!(
+                "Relayer: Discarding in-flight proposal that no longer builds on the canonical tip";
+                "signer_sighash" => %signer_sighash,
+                "parent_block_id" => %proposal.block.header.parent_block_id,
+                "canonical_tip" => %canonical_tip,
+            );
+            if let Err(e) = proposal_db.end_round(&signer_sighash) {
+                warn!("Relayer: Failed to clear in-flight proposal: {e:?}");
+            }
+            return None;
+        }
+        info!(
+            "Relayer: Found in-flight proposal to resume";
+            "signer_sighash" => %signer_sighash,
+            "block_id" => %proposal.block.block_id(),
+            "attempt" => proposal.attempt,
+        );
+        Some(proposal)
+    }
+
     fn start_new_tenure(
         &mut self,
         parent_tenure_start: StacksBlockId,
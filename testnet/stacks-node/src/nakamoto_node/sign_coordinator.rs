@@ -13,36 +13,74 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::VecDeque;
+use std::ops::Range;
 use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use clarity::vm::types::QualifiedContractIdentifier;
 use hashbrown::{HashMap, HashSet};
-use libsigner::v1::messages::{MessageSlotID, SignerMessage};
-use libsigner::{BlockProposal, SignerEntries, SignerEvent, SignerSession, StackerDBSession};
+use libsigner::envelope::{open_payload, seal_payload_into, EnvelopeConfig};
+use libsigner::protocol::{
+    advertised_weight, supporting_weight, untag_message, write_tagged_message, MinerWatermark,
+    ProtocolCapabilities, ProtocolVersion,
+};
+use libsigner::v1::messages::{oversized_message, MessageSlotID, MinerSlotID, SignerMessage};
+use libsigner::{
+    BlockProposal, SignerEntries, SignerEvent, SignerSession, SlotId, StackerDBSession,
+    TenureExtend, TenureView,
+};
+use rand::Rng;
 use stacks::burnchains::Burnchain;
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
-use stacks::chainstate::burn::BlockSnapshot;
+use stacks::chainstate::burn::{BlockSnapshot, ConsensusHash};
+use stacks::chainstate::nakamoto::signer_set::NakamotoSigners;
 use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
-use stacks::chainstate::stacks::boot::{NakamotoSignerEntry, RewardSet, MINERS_NAME, SIGNERS_NAME};
+use stacks::chainstate::stacks::boot::{
+    signing_threshold_for, NakamotoSignerEntry, RewardSet, MINERS_NAME, SIGNERS_NAME,
+    SIGNER_BITVEC_MAX_SIZE,
+};
 use stacks::chainstate::stacks::events::StackerDBChunksEvent;
-use stacks::chainstate::stacks::{Error as ChainstateError, ThresholdSignature};
-use stacks::libstackerdb::StackerDBChunkData;
+use stacks::chainstate::stacks::{Error as ChainstateError, ThresholdSignature, MAX_BLOCK_LEN};
+use stacks::libstackerdb::{SlotMetadata, StackerDBChunkData};
+use stacks::monitoring::update_signer_weight_stats;
 use stacks::net::stackerdb::StackerDBs;
+use stacks::net::Error as NetError;
 use stacks::util_lib::boot::boot_code_id;
+use stacks::util_lib::db::Error as DBError;
 use stacks_common::bitvec::BitVec;
 use stacks_common::codec::StacksMessageCodec;
+use stacks_common::consts::SIGNER_SLOTS_PER_USER;
 use stacks_common::types::chainstate::{StacksPrivateKey, StacksPublicKey};
+use stacks_common::types::reward_cycle::RewardCycleInfo;
+use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::{to_hex, Hash160, MerkleTree, Sha512Trunc256Sum};
+use stacks_common::util::log::LOGGER;
 use wsts::common::PolyCommitment;
 use wsts::curve::ecdsa;
 use wsts::curve::point::Point;
 use wsts::curve::scalar::Scalar;
+use wsts::net::{Message as WstsMessage, Packet};
 use wsts::state_machine::coordinator::fire::Coordinator as FireCoordinator;
 use wsts::state_machine::coordinator::{Config as CoordinatorConfig, Coordinator};
 use wsts::state_machine::PublicKeys;
 use wsts::v2::Aggregator;
 
-use super::Error as NakamotoNodeError;
-use crate::event_dispatcher::STACKER_DB_CHANNEL;
+use super::equivocation_db::{RoundVotes, SignerEquivocationDB, VOTE_RETENTION_BLOCKS};
+use super::proposal_db::{InflightProposal, InflightProposalDB};
+use super::sign_round::{
+    prioritize_signer_messages, RejectionTally, RewardSetStats, SignRound, SignRoundCompletion,
+    SignRoundEvent, SignRoundTiming, SignRoundUpdate, SignerLatencyEstimate, SignerSetParity,
+    SignerSetWeights, SignerSignatureData, TenureSigningStats,
+};
+use super::sign_trace::{SignRoundId, SignRoundTrace, SignTraceEvent, SignTraceWriter};
+use super::{Error as NakamotoNodeError, SigningError};
+use crate::config::SignCoordinatorConfig;
+use crate::event_dispatcher::{
+    StackerDBChannel, StackerDBChannelError, StackerDBChannelEvent, StackerDBChannelRegistration,
+    STACKER_DB_CHANNEL,
+};
 use crate::neon::Counters;
 use crate::Config;
 
@@ -50,6 +88,900 @@ use crate::Config;
 /// waking up to check timeouts?
 static EVENT_RECEIVER_POLL: Duration = Duration::from_millis(50);
 
+/// How often the coordinator warns about signer chunks it dropped for being oversized.
+/// Chunks dropped in between are only logged at debug level.
+const OVERSIZED_CHUNK_WARN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The protocol version the coordinator writes its messages in
+const MINER_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1;
+
+/// How long parsed signing params stay cached. A cycle's reward set does not change, so this
+/// only bounds how long the params of past cycles are kept around.
+const SIGNING_PARAMS_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+lazy_static::lazy_static! {
+    /// The parsed signing params of recent reward cycles, shared by the coordinators of all
+    ///  the tenures in a cycle
+    static ref SIGNING_PARAMS_CACHE: Mutex<SigningParamsCache> =
+        Mutex::new(SigningParamsCache::new(SIGNING_PARAMS_CACHE_TTL));
+}
+
+/// The key the miner signs its `.miners` chunks with. Its public key is compressed, like
+/// the one whose hash is registered for the miner's slot in the `.miners` contract.
+fn miner_chunk_key(miner_sk: &StacksPrivateKey) -> StacksPrivateKey {
+    let mut miner_sk = miner_sk.clone();
+    miner_sk.set_compress_public(true);
+    miner_sk
+}
+
+/// Check that `miner_pubkey` is the key registered by the miner that won
+/// `election_sortition`. Signers ignore `.miners` chunks written by anyone else.
+fn check_winning_miner(
+    sortdb: &SortitionDB,
+    election_sortition: &BlockSnapshot,
+    miner_pubkey: &StacksPublicKey,
+) -> Result<(), NakamotoNodeError> {
+    let miner_key_hash = Hash160::from_node_public_key(miner_pubkey);
+    let winner_key_hash = if election_sortition.sortition {
+        let ih = sortdb.index_handle(&election_sortition.sortition_id);
+        let winner_key_hash =
+            NakamotoChainState::get_winning_miner_key_hash160(&ih, election_sortition)
+                .map_err(NakamotoNodeError::MiningFailure)?;
+        Some(winner_key_hash)
+    } else {
+        None
+    };
+    if winner_key_hash.as_ref() != Some(&miner_key_hash) {
+        error!(
+            "Miner key did not win the sortition for this tenure. Signers will ignore its proposals.";
+            "consensus_hash" => %election_sortition.consensus_hash,
+            "winner_key_hash" => ?winner_key_hash,
+            "miner_key_hash" => %miner_key_hash,
+        );
+        return Err(NakamotoNodeError::NotWinningMiner {
+            consensus_hash: election_sortition.consensus_hash.clone(),
+            winner_key_hash,
+            miner_key_hash,
+        });
+    }
+    Ok(())
+}
+
+/// The sortitions with a winner between the tenure of the canonical Stacks tip, elected at
+/// burn height `stacks_tip_height`, and the tenure being mined, exclusive of both. Each is a
+/// tenure the node has not processed, unless its winner never mined it. `ancestors` walks
+/// back from the tenure being mined as `(burn height, whether it had a winner)`, and counting
+/// stops once it exceeds `limit`.
+fn missed_tenures<I>(
+    ancestors: I,
+    stacks_tip_height: u64,
+    limit: u64,
+) -> Result<u64, NakamotoNodeError>
+where
+    I: Iterator<Item = Result<(u64, bool), NakamotoNodeError>>,
+{
+    let mut missed = 0;
+    for ancestor in ancestors {
+        let (block_height, sortition) = ancestor?;
+        if block_height <= stacks_tip_height {
+            break;
+        }
+        if sortition {
+            missed += 1;
+            if missed > limit {
+                break;
+            }
+        }
+    }
+    Ok(missed)
+}
+
+/// Check that the node's canonical Stacks tip is at most `lag_tolerance` tenures behind
+/// `election_sortition`, the sortition that elected the tenure being mined. A node that is
+/// still syncing would propose blocks on a stale parent, which signers reject.
+fn check_node_synced(
+    sortdb: &SortitionDB,
+    election_sortition: &BlockSnapshot,
+    lag_tolerance: u64,
+) -> Result<(), NakamotoNodeError> {
+    let db_failure = |e: DBError| {
+        NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
+            "Failed to load the canonical Stacks tip: {e:?}"
+        )))
+    };
+    let (stacks_tip, _) =
+        SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn()).map_err(db_failure)?;
+    let Some(stacks_tip_sn) = SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &stacks_tip)
+        .map_err(db_failure)?
+    else {
+        warn!(
+            "Miner/Coordinator: No sortition found for the canonical Stacks tip's tenure";
+            "stacks_tip" => %stacks_tip,
+        );
+        return Ok(());
+    };
+    let mut parent_id = Some(election_sortition.parent_sortition_id.clone());
+    let ancestors = std::iter::from_fn(|| {
+        let sortition_id = parent_id.take()?;
+        match SortitionDB::get_block_snapshot(sortdb.conn(), &sortition_id) {
+            Ok(Some(sn)) => {
+                parent_id = Some(sn.parent_sortition_id);
+                Some(Ok((sn.block_height, sn.sortition)))
+            }
+            Ok(None) => None,
+            Err(e) => Some(Err(db_failure(e))),
+        }
+    });
+    let missed = missed_tenures(ancestors, stacks_tip_sn.block_height, lag_tolerance)?;
+    if missed > lag_tolerance {
+        warn!(
+            "Miner/Coordinator: Node is still syncing its Stacks chain. Not proposing a block the signers would reject.";
+            "stacks_tip" => %stacks_tip,
+            "stacks_tip_burn_height" => stacks_tip_sn.block_height,
+            "election_sortition" => %election_sortition.consensus_hash,
+            "election_burn_height" => election_sortition.block_height,
+            "missed_tenures" => missed,
+            "lag_tolerance" => lag_tolerance,
+        );
+        return Err(NakamotoNodeError::NodeNotSynced {
+            stacks_tip,
+            burn_tip: election_sortition.consensus_hash.clone(),
+        });
+    }
+    Ok(())
+}
+
+/// Reports the canonical burnchain tip, so that a signing round can notice that the tip
+/// moved while it was waiting for signers
+pub trait BurnTipWatcher {
+    /// Consensus hash of the canonical burnchain tip
+    fn canonical_tip(&self) -> Result<ConsensusHash, NakamotoNodeError>;
+}
+
+impl BurnTipWatcher for SortitionDB {
+    fn canonical_tip(&self) -> Result<ConsensusHash, NakamotoNodeError> {
+        SortitionDB::get_canonical_burn_chain_tip(self.conn())
+            .map(|tip| tip.consensus_hash)
+            .map_err(|e| {
+                NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
+                    "Failed to load the burnchain tip: {e:?}"
+                )))
+            })
+    }
+}
+
+/// Check that the canonical burnchain tip is still `round_burn_view`, the one the signing
+/// round started on. If it moved, the block's tenure may no longer be valid, so the round
+/// should not keep waiting for signatures.
+fn check_burn_tip(
+    watcher: &impl BurnTipWatcher,
+    round_burn_view: &ConsensusHash,
+) -> Result<(), NakamotoNodeError> {
+    let canonical_tip = watcher.canonical_tip()?;
+    if &canonical_tip != round_burn_view {
+        return Err(NakamotoNodeError::BurnchainTipChanged {
+            old_consensus_hash: round_burn_view.clone(),
+            new_consensus_hash: canonical_tip,
+        });
+    }
+    Ok(())
+}
+
+/// Check that `slot_range`, the miner's slots in the `.miners` contract, has a slot for
+/// every [`MinerSlotID`]. A stale contract config would otherwise silently keep some of the
+/// miner's messages from ever reaching the signers.
+fn check_miner_slot_range(slot_range: &Range<u32>) -> Result<(), NakamotoNodeError> {
+    let slot_count = slot_range.end.saturating_sub(slot_range.start);
+    let required_slots =
+        u32::try_from(MinerSlotID::ALL.len()).expect("FATAL: more than u32::MAX miner slot IDs");
+    if slot_count < required_slots {
+        error!(
+            "The .miners StackerDB contract gives this miner fewer slots than it needs. Signing is disabled until the contract config is updated to give each miner at least {required_slots} slots.";
+            "slot_start" => slot_range.start,
+            "slot_count" => slot_count,
+            "required_slots" => required_slots,
+        );
+        return Err(NakamotoNodeError::MinersContractMisconfigured {
+            slot_count,
+            required_slots,
+        });
+    }
+    Ok(())
+}
+
+/// The length of the signer bitvec for a reward set of `signer_count` signers.
+/// Errors if the reward set has more signers than a signer bitvec can hold, since blocks
+/// signed by it could never record every signer's participation.
+fn signer_bitvec_len(signer_count: usize) -> Result<u16, NakamotoNodeError> {
+    u16::try_from(signer_count)
+        .ok()
+        .filter(|len| *len <= SIGNER_BITVEC_MAX_SIZE)
+        .ok_or_else(|| {
+            NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
+                "Reward set has {signer_count} signers, more than the {SIGNER_BITVEC_MAX_SIZE} a signer bitvec can hold"
+            )))
+        })
+}
+
+/// The slot in the `.miners` contract that `msg_id` messages are written to, given the
+/// miner's `slot_range`.
+fn miner_slot_id(slot_range: &Range<u32>, msg_id: MinerSlotID) -> Result<u32, NakamotoNodeError> {
+    check_miner_slot_range(slot_range)?;
+    Ok(slot_range.start + msg_id.slot_offset())
+}
+
+/// What the miner's own StackerDB holds in the slot its proposal was written to
+#[derive(Debug, Clone, PartialEq)]
+enum ProposalReadback {
+    /// The slot holds the proposal
+    Found,
+    /// The slot holds a later message, which is not the proposal's to replace
+    Superseded { slot_version: u32 },
+    /// The slot does not hold the proposal, for the given reason
+    Missing(String),
+}
+
+/// Compare the proposal chunk the miner wrote with the metadata `read_back` from its slot
+fn check_proposal_readback(
+    proposal_chunk: &SlotMetadata,
+    read_back: Result<Option<SlotMetadata>, String>,
+) -> ProposalReadback {
+    let read_back = match read_back {
+        Ok(Some(read_back)) => read_back,
+        Ok(None) => return ProposalReadback::Missing("The slot is empty".into()),
+        Err(e) => return ProposalReadback::Missing(e),
+    };
+    if read_back.slot_version > proposal_chunk.slot_version {
+        return ProposalReadback::Superseded {
+            slot_version: read_back.slot_version,
+        };
+    }
+    if read_back.slot_version < proposal_chunk.slot_version {
+        return ProposalReadback::Missing(format!(
+            "The slot holds version {} instead of {}",
+            read_back.slot_version, proposal_chunk.slot_version
+        ));
+    }
+    if read_back.data_hash != proposal_chunk.data_hash {
+        return ProposalReadback::Missing(format!(
+            "The slot holds payload {} instead of {}",
+            read_back.data_hash, proposal_chunk.data_hash
+        ));
+    }
+    ProposalReadback::Found
+}
+
+/// How long a burn block is expected to follow the last one, which bounds how long a signed
+/// block can wait to be broadcast
+const EXPECTED_BURN_BLOCK_INTERVAL: Duration = Duration::from_secs(600);
+
+/// When the next burn block is expected, given the header timestamp of the burn tip and the
+/// time as both an `Instant` and seconds since the epoch. A tip with a header timestamp in the
+/// future is taken to have just arrived.
+pub fn broadcast_deadline(burn_header_timestamp: u64, now: Instant, now_secs: u64) -> Instant {
+    let since_tip = Duration::from_secs(now_secs.saturating_sub(burn_header_timestamp));
+    now + EXPECTED_BURN_BLOCK_INTERVAL.saturating_sub(since_tip)
+}
+
+/// How long to keep collecting signer responses after a block is signed at `now`. Late
+/// signers are waited for up to `grace`, but never past `margin` before `broadcast_deadline`,
+/// so a block signed that close to the deadline is broadcast right away.
+fn post_threshold_grace(
+    now: Instant,
+    broadcast_deadline: Instant,
+    margin: Duration,
+    grace: Duration,
+) -> Duration {
+    broadcast_deadline
+        .saturating_duration_since(now)
+        .saturating_sub(margin)
+        .min(grace)
+}
+
+/// Merge how the signers responded in `round`, of the tenure elected in
+/// `election_consensus_hash`, into `tenure_stats`, and update the metrics of the signers in the
+/// round. The previous tenure's metrics are dropped when the stats start over.
+fn merge_tenure_signing_stats(
+    tenure_stats: &mut TenureSigningStats,
+    election_consensus_hash: &ConsensusHash,
+    round: &SignRound,
+) {
+    let report = round.signer_report();
+    if tenure_stats.merge_round(election_consensus_hash, &report) {
+        stacks::monitoring::reset_naka_tenure_signer_responses();
+    }
+    for signer in report.iter() {
+        let Some(stats) = tenure_stats.signers.get(&signer.signer_pubkey) else {
+            continue;
+        };
+        stacks::monitoring::update_naka_tenure_signer_responses(
+            &signer.signer_pubkey,
+            stats.accepted,
+            stats.rejected,
+            stats.missed,
+        );
+    }
+    debug!(
+        "Miner/Coordinator: Merged the signing round into the tenure's signing stats";
+        "election_consensus_hash" => %election_consensus_hash,
+        "rounds" => tenure_stats.rounds,
+        "signers" => tenure_stats.signers.len(),
+    );
+}
+
+/// The writer of the signing round trace, if the miner is configured to keep one
+fn sign_trace_writer(config: &Config) -> Option<SignTraceWriter> {
+    let path = config.miner.sign_round_trace_path.as_ref()?;
+    Some(SignTraceWriter::new(
+        path,
+        config.miner.sign_round_trace_max_bytes,
+    ))
+}
+
+/// The error to return when the coordinator could not be initialized because of `e`
+fn init_failure(e: ChainstateError) -> NakamotoNodeError {
+    NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
+        "Failed to initialize the signing coordinator. Cannot mine! {e:?}"
+    )))
+}
+
+/// The tenure extension `block` makes, if its tenure change extends a tenure
+fn tenure_extend_of(block: &NakamotoBlock) -> Option<TenureExtend> {
+    block
+        .get_tenure_tx_payload()
+        .and_then(TenureExtend::from_tenure_change)
+}
+
+/// The reward cycle adjacent to `reward_cycle` whose signers may still be (or already be)
+/// responding at `burn_height`, if it is within `window` burn blocks of the boundary between
+/// the two cycles. Signers roll over to the next cycle's StackerDBs at slightly different
+/// times, so around the boundary the signer set is split across both.
+fn boundary_reward_cycle(
+    reward_cycles: &RewardCycleInfo,
+    reward_cycle: u64,
+    burn_height: u64,
+    window: u64,
+) -> Option<u64> {
+    if window == 0 {
+        return None;
+    }
+    let next_cycle = reward_cycle.checked_add(1)?;
+    if reward_cycles
+        .first_height_of(next_cycle)
+        .saturating_sub(burn_height)
+        <= window
+    {
+        return Some(next_cycle);
+    }
+    let prior_cycle = reward_cycle.checked_sub(1)?;
+    let cycle_start = reward_cycles.first_height_of(reward_cycle);
+    if burn_height.saturating_sub(cycle_start) < window {
+        return Some(prior_cycle);
+    }
+    None
+}
+
+/// Subscribe the miner coordinator to chunk events from the signers StackerDBs of each of
+/// `reward_cycles` on `channel`. Events for any other reward cycle's signers are never
+/// enqueued. Fails if another coordinator is still subscribed, unless `force` is set.
+fn register_signer_events(
+    channel: &StackerDBChannel,
+    reward_cycles: &[u64],
+    is_mainnet: bool,
+    force: bool,
+) -> Result<
+    (
+        Receiver<StackerDBChannelEvent>,
+        StackerDBChannelRegistration,
+    ),
+    NakamotoNodeError,
+> {
+    let signers_contracts: HashSet<_> = reward_cycles
+        .iter()
+        .flat_map(|reward_cycle| {
+            (0..SIGNER_SLOTS_PER_USER).map(move |message_id| {
+                NakamotoSigners::make_signers_db_contract_id(*reward_cycle, message_id, is_mainnet)
+            })
+        })
+        .collect();
+    let (receiver, registration) = channel
+        .register_miner_coordinator_filtered(force, move |contract_id| {
+            signers_contracts.contains(contract_id)
+        })
+        .map_err(|e| match e {
+            StackerDBChannelError::CoordinatorActive { generation } => {
+                NakamotoNodeError::SigningFailure(SigningError::CoordinatorActive { generation })
+            }
+        })?;
+    if registration.replaced_other {
+        warn!(
+            "Forced the miner/coordinator receiver of a prior thread off the StackerDB channel. Prior thread may have crashed.";
+            "generation" => registration.generation(),
+        );
+    }
+    Ok((receiver, registration))
+}
+
+/// Fails with `SigningError::Superseded` if a newer coordinator has registered on `channel`
+/// since `registration`, in which case the coordinator holding it gets no more signer events
+fn check_registration(
+    channel: &StackerDBChannel,
+    registration: &StackerDBChannelRegistration,
+) -> Result<(), NakamotoNodeError> {
+    if channel.is_superseded(registration) {
+        return Err(NakamotoNodeError::SigningFailure(SigningError::Superseded));
+    }
+    Ok(())
+}
+
+/// Give up a coordinator's `registration` on `channel`, unless a newer coordinator has already
+/// replaced it. Never panics, since it runs when a coordinator is dropped, possibly while its
+/// thread is already unwinding from a panic.
+fn release_signer_events(
+    channel: &StackerDBChannel,
+    receiver: Option<Receiver<StackerDBChannelEvent>>,
+    registration: StackerDBChannelRegistration,
+) {
+    let Some(receiver) = receiver else {
+        error!("SignCoordinator: lost possession of the StackerDB channel before it was dropped");
+        return;
+    };
+    if !channel.replace_receiver(receiver, registration) {
+        debug!("SignCoordinator: a newer coordinator registered on the StackerDB channel. Leaving it in place.");
+    }
+}
+
+/// The latest version of each signer StackerDB slot that the coordinator has processed.
+/// Chunks at or below a slot's watermark are dropped before they are deserialized, so a
+/// signer that keeps rewriting (or the channel that keeps re-delivering) the same chunk
+/// costs a map lookup, not a parse. There is one entry per slot that has been written,
+/// however long the round runs.
+#[derive(Default)]
+struct ProcessedChunks(HashMap<QualifiedContractIdentifier, HashMap<u32, u32>>);
+
+impl ProcessedChunks {
+    /// Record that `slot_version` of `slot_id` in `contract_id` was processed.
+    /// Returns false if that version (or a later one) already was.
+    fn record(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        slot_id: u32,
+        slot_version: u32,
+    ) -> bool {
+        // only clone the contract id the first time one of its chunks is seen
+        let slots = match self.0.get_mut(contract_id) {
+            Some(slots) => slots,
+            None => self.0.entry(contract_id.clone()).or_default(),
+        };
+        let watermark = slots.entry(slot_id).or_insert(0);
+        if *watermark >= slot_version {
+            return false;
+        }
+        *watermark = slot_version;
+        true
+    }
+
+    /// The number of slots with a watermark
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.0.values().map(HashMap::len).sum()
+    }
+}
+
+/// Where the coordinator gets the signers' responses from. They normally arrive over the
+/// StackerDB event channel, but if it goes quiet for `db_read_interval`, the response
+/// contracts are read back from the node's StackerDB replicas, so that a wedged channel
+/// cannot time out a round the signers have actually answered.
+struct SignerEventSource {
+    /// The signer contracts that hold responses to the miner's messages
+    contract_ids: Vec<QualifiedContractIdentifier>,
+    processed: ProcessedChunks,
+    /// Chunks the event channel delivered again after they were processed, not yet counted
+    /// in the metrics
+    duplicate_chunks: u64,
+    /// Chunks dropped unread for being longer than any message of their type, not yet counted
+    /// in the metrics
+    oversized_chunks: u64,
+    /// When a dropped oversized chunk was last warned about
+    last_oversized_warning: Option<Instant>,
+    /// How long the last event dequeued from the channel waited in it, if not yet recorded in
+    /// the metrics
+    dequeue_delay: Option<Duration>,
+    /// Events read from the StackerDB, not yet handed to the round
+    pending: VecDeque<StackerDBChunksEvent>,
+    db_read_interval: Duration,
+    last_event: Instant,
+    /// The slots the first read from the StackerDB is limited to, if it is limited. Later
+    /// reads cover every slot.
+    first_read_slots: Option<HashSet<u32>>,
+}
+
+impl SignerEventSource {
+    /// Watch the response contracts of the signer sets of `reward_cycles`. Chunks already in
+    /// them predate the round and are skipped, so that responses to an earlier proposal of
+    /// the same block are not counted again.
+    fn new(
+        reward_cycles: &[u64],
+        is_mainnet: bool,
+        stackerdbs: &StackerDBs,
+        db_read_interval: Duration,
+    ) -> Self {
+        let contract_ids = reward_cycles
+            .iter()
+            .flat_map(|reward_cycle| {
+                [
+                    MessageSlotID::NonceResponse,
+                    MessageSlotID::SignatureShareResponse,
+                    MessageSlotID::BlockResponse,
+                ]
+                .map(|msg_id| msg_id.stacker_db_contract(is_mainnet, *reward_cycle))
+            })
+            .collect();
+        let mut source = Self {
+            contract_ids,
+            processed: ProcessedChunks::default(),
+            duplicate_chunks: 0,
+            oversized_chunks: 0,
+            last_oversized_warning: None,
+            dequeue_delay: None,
+            pending: VecDeque::new(),
+            db_read_interval,
+            last_event: Instant::now(),
+            first_read_slots: None,
+        };
+        source.read_unprocessed_chunks(stackerdbs);
+        source
+    }
+
+    /// Limit the first read from the StackerDB to `slot_ids`, so that a sample of the signers
+    /// is polled before all of them are
+    fn with_first_read_slots(mut self, slot_ids: &[SlotId]) -> Self {
+        self.first_read_slots = Some(slot_ids.iter().map(|slot_id| slot_id.0).collect());
+        self
+    }
+
+    /// Read the chunks of the response contracts that have not been processed yet
+    fn read_unprocessed_chunks(&mut self, stackerdbs: &StackerDBs) -> Vec<StackerDBChunksEvent> {
+        let mut events = vec![];
+        for contract_id in self.contract_ids.iter() {
+            let slots = match stackerdbs.get_db_slot_metadata(contract_id) {
+                Ok(slots) => slots,
+                Err(e) => {
+                    debug!("Miner/Coordinator: Failed to read signer StackerDB slots"; "contract" => %contract_id, "err" => ?e);
+                    continue;
+                }
+            };
+            let mut modified_slots = vec![];
+            for slot in slots {
+                if let Some(first_read_slots) = self.first_read_slots.as_ref() {
+                    if !first_read_slots.contains(&slot.slot_id) {
+                        continue;
+                    }
+                }
+                if slot.slot_version == 0
+                    || !self
+                        .processed
+                        .record(contract_id, slot.slot_id, slot.slot_version)
+                {
+                    continue;
+                }
+                match stackerdbs.get_chunk(contract_id, slot.slot_id, slot.slot_version) {
+                    Ok(Some(chunk)) => modified_slots.push(chunk),
+                    // overwritten since the metadata was read; the next read picks it up
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("Miner/Coordinator: Failed to read signer StackerDB chunk"; "contract" => %contract_id, "slot_id" => slot.slot_id, "err" => ?e);
+                    }
+                }
+            }
+            if !modified_slots.is_empty() {
+                events.push(StackerDBChunksEvent {
+                    contract_id: contract_id.clone(),
+                    modified_slots,
+                });
+            }
+        }
+        events
+    }
+
+    /// The next signer event with chunks that have not been processed yet, or `None` if
+    /// there was none within a poll interval.
+    fn next_event(
+        &mut self,
+        receiver: &Receiver<StackerDBChannelEvent>,
+        stackerdbs: &StackerDBs,
+    ) -> Result<Option<StackerDBChunksEvent>, NakamotoNodeError> {
+        if self.pending.is_empty() && self.last_event.elapsed() >= self.db_read_interval {
+            self.last_event = Instant::now();
+            let events = self.read_unprocessed_chunks(stackerdbs);
+            self.first_read_slots = None;
+            if !events.is_empty() {
+                debug!("Miner/Coordinator: Read signer responses missed by the event receiver"; "events" => events.len());
+            }
+            self.pending.extend(events);
+        }
+        if let Some(mut event) = self.pending.pop_front() {
+            self.drop_oversized_chunks(&mut event);
+            if event.modified_slots.is_empty() {
+                return Ok(None);
+            }
+            return Ok(Some(event));
+        }
+        let mut event = match receiver.recv_timeout(EVENT_RECEIVER_POLL) {
+            Ok(StackerDBChannelEvent {
+                event,
+                dispatched_at,
+            }) => {
+                self.dequeue_delay = Some(dispatched_at.elapsed());
+                event
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => return Ok(None),
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(NakamotoNodeError::SigningFailure(
+                    SigningError::ChannelDisconnected,
+                ))
+            }
+        };
+        self.last_event = Instant::now();
+        let delivered = event.modified_slots.len();
+        event.modified_slots.retain(|chunk| {
+            self.processed
+                .record(&event.contract_id, chunk.slot_id, chunk.slot_version)
+        });
+        // StackerDB replication can deliver the same version of a slot more than once
+        self.duplicate_chunks += u64::try_from(delivered - event.modified_slots.len())
+            .expect("FATAL: more than u64::MAX chunks");
+        self.drop_oversized_chunks(&mut event);
+        if event.modified_slots.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(event))
+    }
+
+    /// Drop the chunks of signer event `event` whose messages are longer than any message of
+    /// their type can be, so that nobody who can write to a signer StackerDB can make the
+    /// coordinator parse chunk-sized payloads every round. The chunks stay recorded as
+    /// processed, so a redelivery of one is dropped as a duplicate.
+    fn drop_oversized_chunks(&mut self, event: &mut StackerDBChunksEvent) {
+        if !(event.contract_id.name.starts_with(SIGNERS_NAME) && event.contract_id.is_boot()) {
+            return;
+        }
+        let contract_id = &event.contract_id;
+        let oversized_chunks = &mut self.oversized_chunks;
+        let last_oversized_warning = &mut self.last_oversized_warning;
+        event.modified_slots.retain(|chunk| {
+            // an unreadable envelope is dropped when the chunk is parsed
+            let Ok(payload) = open_payload(&chunk.data) else {
+                return true;
+            };
+            let Some((message_type, max_len)) = oversized_message(&payload) else {
+                return true;
+            };
+            *oversized_chunks += 1;
+            if last_oversized_warning
+                .map_or(true, |warned_at| warned_at.elapsed() >= OVERSIZED_CHUNK_WARN_INTERVAL)
+            {
+                *last_oversized_warning = Some(Instant::now());
+                warn!(
+                    "Miner/Coordinator: Dropping a signer chunk that is too long for its message type";
+                    "contract" => %contract_id,
+                    "slot_id" => chunk.slot_id,
+                    "slot_version" => chunk.slot_version,
+                    "message_type" => ?message_type,
+                    "len" => payload.len(),
+                    "max_len" => max_len,
+                );
+            } else {
+                debug!(
+                    "Miner/Coordinator: Dropping a signer chunk that is too long for its message type";
+                    "contract" => %contract_id,
+                    "slot_id" => chunk.slot_id,
+                    "len" => payload.len(),
+                );
+            }
+            false
+        });
+    }
+
+    /// The number of chunks delivered again after they were processed, since the last call
+    fn take_duplicate_chunks(&mut self) -> u64 {
+        std::mem::take(&mut self.duplicate_chunks)
+    }
+
+    /// The number of chunks dropped for being oversized, since the last call
+    fn take_oversized_chunks(&mut self) -> u64 {
+        std::mem::take(&mut self.oversized_chunks)
+    }
+
+    /// How long the last event dequeued from the channel waited in it, since the last call
+    fn take_dequeue_delay(&mut self) -> Option<Duration> {
+        self.dequeue_delay.take()
+    }
+}
+
+/// Sample a minimal set of signers whose combined weight exceeds `target_weight`, drawing
+/// each signer with probability proportional to its weight, so that the miner can poll a few
+/// heavy signers instead of all of them. The signers are put in a random weighted order
+/// (Efraimidis-Spirakis), and the shortest prefix of it that exceeds the target is returned.
+/// If the whole set does not exceed the target, every signer with weight is returned.
+/// The sample only depends on `rng`, so a seeded one makes it reproducible.
+pub fn sample_signers_by_weight(
+    signer_set: &SignerSetWeights,
+    target_weight: u32,
+    rng: &mut impl Rng,
+) -> Vec<SlotId> {
+    let mut keyed: Vec<(f64, SlotId, u32)> = signer_set
+        .slot_weights()
+        .into_iter()
+        .filter(|(_, weight)| *weight > 0)
+        .map(|(slot_id, weight)| {
+            let key = rng.gen::<f64>().powf(1.0 / f64::from(weight));
+            (key, slot_id, weight)
+        })
+        .collect();
+    // highest key first; the sort is stable, so ties keep slot order
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let mut sample = vec![];
+    let mut sampled_weight = 0u64;
+    for (_, slot_id, weight) in keyed {
+        if sampled_weight > u64::from(target_weight) {
+            break;
+        }
+        sample.push(slot_id);
+        sampled_weight += u64::from(weight);
+    }
+    sample
+}
+
+/// Where the signer signature of a block came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureProvenance {
+    /// The signer set produced it in a signing round
+    RealSigners,
+    /// The test signing channel handed it out. Only test builds can produce one.
+    TestChannel,
+    /// It was already on a staged copy of the block, e.g. one the signers relayed while the
+    /// miner was down
+    StagedBlock,
+}
+
+/// A signer signature over a block, and where it came from. Only test builds can construct
+/// one from the test signing channel, and [`SignedBlockResult::into_broadcast_signature`]
+/// refuses to hand out such a signature outside of them, so a test shortcut cannot reach
+/// block broadcast.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedBlockResult {
+    signature: ThresholdSignature,
+    provenance: SignatureProvenance,
+    /// Which of the reward set's signers took part in the signature. Empty unless the
+    /// signer set produced it.
+    signer_signature_data: Vec<SignerSignatureData>,
+}
+
+impl SignedBlockResult {
+    /// A signature produced by the signer set, with which of its signers took part
+    fn from_signers(
+        signature: ThresholdSignature,
+        signer_signature_data: Vec<SignerSignatureData>,
+    ) -> Self {
+        Self {
+            signature,
+            provenance: SignatureProvenance::RealSigners,
+            signer_signature_data,
+        }
+    }
+
+    /// A signature handed out by the test signing channel
+    #[cfg(test)]
+    pub fn from_test_channel(signature: ThresholdSignature) -> Self {
+        Self {
+            signature,
+            provenance: SignatureProvenance::TestChannel,
+            signer_signature_data: vec![],
+        }
+    }
+
+    /// The signature of a staged copy of the block
+    pub fn from_staged_block(staged_block: &NakamotoBlock) -> Self {
+        Self {
+            signature: staged_block.header.signer_signature.clone(),
+            provenance: SignatureProvenance::StagedBlock,
+            signer_signature_data: vec![],
+        }
+    }
+
+    /// Where the signature came from
+    pub fn provenance(&self) -> SignatureProvenance {
+        self.provenance
+    }
+
+    /// Which of the reward set's signers took part in the signature, if the signer set
+    /// produced it in this miner's signing round
+    pub fn signer_signature_data(&self) -> &[SignerSignatureData] {
+        &self.signer_signature_data
+    }
+
+    /// The signature, whatever its provenance
+    pub fn signature(&self) -> &ThresholdSignature {
+        &self.signature
+    }
+
+    /// Take the signature to broadcast the block with. Outside of test builds, errors if it
+    /// came from the test signing channel. Errors if a signature that should come from
+    /// signers is empty.
+    pub fn into_broadcast_signature(self) -> Result<ThresholdSignature, NakamotoNodeError> {
+        match self.provenance {
+            SignatureProvenance::TestChannel => {
+                if !cfg!(test) {
+                    return Err(NakamotoNodeError::SigningFailure(
+                        SigningError::InvalidSignature(
+                            "Refusing to broadcast a block signed by the test signing channel"
+                                .into(),
+                        ),
+                    ));
+                }
+            }
+            SignatureProvenance::RealSigners | SignatureProvenance::StagedBlock => {
+                if self.signature == ThresholdSignature::empty() {
+                    return Err(NakamotoNodeError::SigningFailure(
+                        SigningError::InvalidSignature(format!(
+                            "Refusing to broadcast a block with an empty signature from {:?}",
+                            self.provenance
+                        )),
+                    ));
+                }
+            }
+        }
+        Ok(self.signature)
+    }
+}
+
+/// Why a block was not proposed to the signers, who would certainly have rejected it
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProposalPreflightError {
+    /// The serialized block is larger than any block may be
+    TooLarge { size: u64, max_size: u64 },
+    /// The header's transaction merkle root does not commit to the block's transactions
+    TxMerkleRootMismatch {
+        header_root: Sha512Trunc256Sum,
+        computed_root: Sha512Trunc256Sum,
+    },
+    /// The block's transactions fail static validation, e.g. because one is for the wrong
+    /// network or of a type that `epoch_id` does not allow
+    InvalidTransactions { epoch_id: StacksEpochId },
+}
+
+/// Cheap local checks that the signers would not reject `block` outright, the way the
+/// stacks-node validates a block before processing it
+fn preflight_checks(
+    block: &NakamotoBlock,
+    is_mainnet: bool,
+    chain_id: u32,
+    epoch_id: StacksEpochId,
+) -> Result<(), ProposalPreflightError> {
+    let size = u64::try_from(block.serialize_to_vec().len()).unwrap_or(u64::MAX);
+    let max_size = u64::from(MAX_BLOCK_LEN);
+    if size > max_size {
+        return Err(ProposalPreflightError::TooLarge { size, max_size });
+    }
+    let txids = block
+        .txs
+        .iter()
+        .map(|tx| tx.txid().as_bytes().to_vec())
+        .collect();
+    let computed_root: Sha512Trunc256Sum = MerkleTree::new(&txids).root();
+    if computed_root != block.header.tx_merkle_root {
+        return Err(ProposalPreflightError::TxMerkleRootMismatch {
+            header_root: block.header.tx_merkle_root.clone(),
+            computed_root,
+        });
+    }
+    if !block.validate_transactions_static(is_mainnet, chain_id, epoch_id) {
+        return Err(ProposalPreflightError::InvalidTransactions { epoch_id });
+    }
+    Ok(())
+}
+
 /// The `SignCoordinator` struct represents a WSTS FIRE coordinator whose
 ///  sole function is to serve as the coordinator for Nakamoto block signing.
 ///  This coordinator does not operate as a DKG coordinator. Rather, this struct
@@ -57,15 +989,140 @@ static EVENT_RECEIVER_POLL: Duration = Duration::from_millis(50);
 ///  produce.
 pub struct SignCoordinator {
     coordinator: FireCoordinator<Aggregator>,
-    receiver: Option<Receiver<StackerDBChunksEvent>>,
+    receiver: Option<Receiver<StackerDBChannelEvent>>,
+    /// The registration of `receiver` on the StackerDB channel
+    channel_registration: StackerDBChannelRegistration,
+    /// The miner's key, used to sign the chunks written to `.miners`
+    miner_sk: StacksPrivateKey,
+    /// The miner's key as a WSTS scalar, used to sign WSTS packets
     message_key: Scalar,
     wsts_public_keys: PublicKeys,
     is_mainnet: bool,
+    chain_id: u32,
+    /// The epoch of the burnchain tip the coordinator started on, whose rules the proposed
+    /// blocks' transactions must follow
+    epoch_id: StacksEpochId,
     miners_session: StackerDBSession,
-    signing_round_timeout: Duration,
-    pub next_signer_bitvec: BitVec<4000>,
+    /// How the miner's messages are enveloped in its StackerDB chunks. Bare if `None`.
+    payload_envelope: Option<EnvelopeConfig>,
+    /// The protocol version the miner's messages are tagged with, or `None` if too few
+    /// signers advertise that they read tagged messages
+    message_tag: Option<ProtocolVersion>,
+    /// Scratch buffer the miner's messages are written into. It is reused for every message,
+    /// so that each proposal does not reallocate and copy a buffer the size of the block.
+    message_buf: Vec<u8>,
+    /// The timeouts and tolerances of the signing rounds
+    coordinator_config: SignCoordinatorConfig,
+    /// Weights of the signers, and the threshold weight needed to sign a block
+    signer_set: SignerSetWeights,
+    /// The reward set's signers, in reward set order
+    reward_set_signers: Vec<NakamotoSignerEntry>,
+    /// The signer sets whose responses are accepted: that of the reward cycle of the burn tip
+    /// the coordinator started on, and that of the adjacent reward cycle if the burn tip was
+    /// close to the boundary between the cycles
+    signer_sets: SignerSetParity,
+    /// Where the in-flight proposal is persisted, so that a restarted miner can resume it.
+    /// `None` if the database could not be opened, in which case rounds are not persisted.
+    proposal_db: Option<InflightProposalDB>,
+    /// Where signers that sign conflicting blocks at the same height are recorded.
+    /// `None` if the database could not be opened, in which case nothing is recorded.
+    equivocation_db: Option<SignerEquivocationDB>,
+    /// How long the signers took to reach the threshold in this tenure's rounds
+    latency_estimate: SignerLatencyEstimate,
+    /// How each signer responded to this tenure's proposals
+    tenure_signing_stats: TenureSigningStats,
+    /// How the signing weight is spread over the reward set's signers
+    reward_set_stats: RewardSetStats,
+    /// Signing keys of the signers whose messages are processed first in each batch
+    prioritized_signer_keys: Vec<StacksPublicKey>,
+    /// Signing keys of the signers whose messages are dropped unread. Never set on mainnet.
+    ignored_signer_keys: Vec<StacksPublicKey>,
+    /// Where the signing rounds are traced, if anywhere
+    trace_writer: Option<SignTraceWriter>,
+    /// The signing protocol details the coordinator was set up with
+    signing_info: SigningSubsystemInfo,
+    pub next_signer_bitvec: BitVec<SIGNER_BITVEC_MAX_SIZE>,
+}
+
+/// The signing protocol details a miner's coordinator is set up with. Logged as one line at
+/// the start of each tenure, and sent to the event observers, so that mismatched expectations
+/// between the miner and its signers show up without piecing them together from other logs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigningSubsystemInfo {
+    /// The version of the signer messages the miner sends, e.g. `v1`
+    pub message_version: String,
+    /// The protocol version the miner advertises and tags its messages with
+    pub protocol_version: String,
+    pub reward_cycle: u64,
+    pub num_signers: usize,
+    /// Total weight of the signers with valid signing keys
+    pub total_weight: u32,
+    /// Weight needed to sign a block
+    pub threshold: u32,
+    /// The `.miners` contract the miner writes its messages to
+    pub miners_contract_id: String,
+    /// The first of the miner's slots in the `.miners` contract, if it has any
+    pub slot_start: Option<u32>,
+    /// One past the last of the miner's slots in the `.miners` contract, if it has any
+    pub slot_end: Option<u32>,
+    /// The compatibility shims that change how the miner talks to this signer set
+    pub compatibility_shims: Vec<String>,
+    /// The timeouts and tolerances of the miner's signing rounds
+    pub coordinator_config: SignCoordinatorConfig,
+}
+
+impl SigningSubsystemInfo {
+    pub fn new(
+        reward_cycle: u64,
+        signer_set: &SignerSetWeights,
+        miners_contract_id: &QualifiedContractIdentifier,
+        miner_slot_range: Option<&Range<u32>>,
+        payload_envelope: Option<&EnvelopeConfig>,
+        boundary_reward_cycle: Option<u64>,
+        coordinator_config: &SignCoordinatorConfig,
+    ) -> Self {
+        let mut compatibility_shims = vec![];
+        if payload_envelope.is_some() {
+            compatibility_shims.push("payload_envelope".to_string());
+        }
+        if let Some(boundary_reward_cycle) = boundary_reward_cycle {
+            compatibility_shims.push(format!("signer_set_boundary:{boundary_reward_cycle}"));
+        }
+        Self {
+            message_version: "v1".into(),
+            protocol_version: MINER_PROTOCOL_VERSION.to_string(),
+            reward_cycle,
+            num_signers: signer_set.num_signers(),
+            total_weight: signer_set.total_weight(),
+            threshold: signer_set.threshold(),
+            miners_contract_id: miners_contract_id.to_string(),
+            slot_start: miner_slot_range.map(|range| range.start),
+            slot_end: miner_slot_range.map(|range| range.end),
+            compatibility_shims,
+            coordinator_config: coordinator_config.clone(),
+        }
+    }
+
+    /// Log the details as one line
+    pub fn log(&self) {
+        info!(
+            "Miner/Coordinator: Signing subsystem info";
+            "message_version" => %self.message_version,
+            "protocol_version" => %self.protocol_version,
+            "reward_cycle" => self.reward_cycle,
+            "num_signers" => self.num_signers,
+            "total_weight" => self.total_weight,
+            "threshold" => self.threshold,
+            "miners_contract_id" => %self.miners_contract_id,
+            "slot_start" => self.slot_start,
+            "slot_end" => self.slot_end,
+            "compatibility_shims" => %self.compatibility_shims.join(","),
+            "coordinator_config" => %serde_json::to_string(&self.coordinator_config).unwrap_or_default(),
+        );
+    }
 }
 
+#[derive(Clone)]
 pub struct NakamotoSigningParams {
     /// total number of signers
     pub num_signers: u32,
@@ -80,34 +1137,123 @@ pub struct NakamotoSigningParams {
     pub wsts_public_keys: PublicKeys,
 }
 
+/// Hash of the signer entries of a reward set, which identifies the signing params parsed
+/// from them
+pub fn signer_entries_hash(signers: &[NakamotoSignerEntry]) -> Sha512Trunc256Sum {
+    let mut bytes = Vec::with_capacity(signers.len() * (33 + 16 + 4));
+    for entry in signers {
+        bytes.extend_from_slice(&entry.signing_key);
+        bytes.extend_from_slice(&entry.stacked_amt.to_be_bytes());
+        bytes.extend_from_slice(&entry.weight.to_be_bytes());
+    }
+    Sha512Trunc256Sum::from_data(&bytes)
+}
+
+struct CachedSigningParams {
+    /// Hash of the signer entries the params were parsed from
+    signer_entries_hash: Sha512Trunc256Sum,
+    cached_at: Instant,
+    params: Arc<NakamotoSigningParams>,
+}
+
+/// Parsed signing params by network and reward cycle. Parsing a reward set checks every
+/// signer's key, so it is done once per cycle rather than once per tenure. A cached entry is
+/// used only if it was parsed from the same signer entries, and only for `ttl`.
+pub struct SigningParamsCache {
+    ttl: Duration,
+    entries: HashMap<(bool, u64), CachedSigningParams>,
+}
+
+impl SigningParamsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The signing params of `reward_cycle`'s `reward_set`, parsed only if they are not cached
+    pub fn get_or_parse(
+        &mut self,
+        is_mainnet: bool,
+        reward_cycle: u64,
+        reward_set: &RewardSet,
+    ) -> Result<Arc<NakamotoSigningParams>, ChainstateError> {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, cached| cached.cached_at.elapsed() < ttl);
+        let entries_hash = signer_entries_hash(reward_set.signers.as_deref().unwrap_or(&[]));
+        if let Some(cached) = self.entries.get(&(is_mainnet, reward_cycle)) {
+            if cached.signer_entries_hash == entries_hash {
+                return Ok(cached.params.clone());
+            }
+            debug!(
+                "Signer entries of the reward cycle changed, parsing them again";
+                "reward_cycle" => reward_cycle,
+                "cached_hash" => %cached.signer_entries_hash,
+                "signer_entries_hash" => %entries_hash,
+            );
+        }
+        let params = Arc::new(NakamotoSigningParams::parse(is_mainnet, reward_set)?);
+        self.entries.insert(
+            (is_mainnet, reward_cycle),
+            CachedSigningParams {
+                signer_entries_hash: entries_hash,
+                cached_at: Instant::now(),
+                params: params.clone(),
+            },
+        );
+        Ok(params)
+    }
+}
+
 impl Drop for SignCoordinator {
     fn drop(&mut self) {
-        STACKER_DB_CHANNEL.replace_receiver(self.receiver.take().expect(
-            "FATAL: lost possession of the StackerDB channel before dropping SignCoordinator",
-        ));
+        release_signer_events(
+            &STACKER_DB_CHANNEL,
+            self.receiver.take(),
+            self.channel_registration,
+        );
     }
 }
 
 impl NakamotoSigningParams {
-    pub fn parse(
-        is_mainnet: bool,
-        reward_set: &[NakamotoSignerEntry],
-    ) -> Result<Self, ChainstateError> {
-        let parsed = SignerEntries::parse(is_mainnet, reward_set).map_err(|e| {
-            ChainstateError::InvalidStacksBlock(format!(
-                "Invalid Reward Set: Could not parse into WSTS structs: {e:?}"
-            ))
-        })?;
+    /// Parse the reward set's signers. Signers whose signing keys are malformed are left out,
+    /// since they could never sign, but their keys still count towards the threshold. Errors
+    /// if that leaves too few keys to reach the threshold.
+    pub fn parse(is_mainnet: bool, reward_set: &RewardSet) -> Result<Self, ChainstateError> {
+        let Some(reward_set_signers) = reward_set.signers.as_ref() else {
+            return Err(ChainstateError::NoRegisteredSigners(0));
+        };
+        let (threshold, num_keys) = signing_threshold_for(reward_set)?;
+        let (parsed, excluded) = SignerEntries::parse_valid(is_mainnet, reward_set_signers)
+            .map_err(|e| {
+                ChainstateError::InvalidStacksBlock(format!(
+                    "Invalid Reward Set: Could not parse into WSTS structs: {e:?}"
+                ))
+            })?;
+
+        for signer_id in excluded.iter() {
+            let entry = &reward_set_signers
+                [usize::try_from(*signer_id).expect("FATAL: u32 overflows usize")];
+            warn!(
+                "Reward set signer has a malformed signing key. Leaving it out of the signer set.";
+                "signer_id" => signer_id,
+                "weight" => entry.weight,
+                "signing_key" => to_hex(&entry.signing_key),
+            );
+        }
 
-        let num_keys = parsed
+        let valid_keys = parsed
             .count_keys()
             .expect("FATAL: more than u32::max() signers in the reward set");
-        let num_signers = parsed
-            .count_signers()
-            .expect("FATAL: more than u32::max() signers in the reward set");
-        let threshold = parsed
-            .get_signing_threshold()
+        let num_signers = u32::try_from(reward_set_signers.len())
             .expect("FATAL: more than u32::max() signers in the reward set");
+        if valid_keys < threshold {
+            return Err(ChainstateError::InvalidStacksBlock(format!(
+                "Signers with valid signing keys hold {valid_keys} of {num_keys} keys, below the signing threshold of {threshold}"
+            )));
+        }
 
         Ok(NakamotoSigningParams {
             num_signers,
@@ -120,22 +1266,88 @@ impl NakamotoSigningParams {
     }
 }
 
-fn get_signer_commitments(
+/// Check that signers holding at least `threshold` weight speak the coordinator's protocol
+/// version. Otherwise, a signing round could never succeed.
+/// Returns the protocol version to tag the coordinator's messages with. That is `None`, for
+/// untagged legacy messages, unless signers holding at least `threshold` weight advertise the
+/// coordinator's version: signers that do not advertise may not read tagged messages.
+fn check_protocol_support(
     is_mainnet: bool,
     reward_set: &[NakamotoSignerEntry],
     stackerdbs: &StackerDBs,
     reward_cycle: u64,
-    expected_aggregate_key: &Point,
-) -> Result<Vec<(u32, PolyCommitment)>, ChainstateError> {
-    let commitment_contract =
-        MessageSlotID::DkgResults.stacker_db_contract(is_mainnet, reward_cycle);
+    threshold: u32,
+) -> Result<Option<ProtocolVersion>, ChainstateError> {
+    let capabilities_contract = ProtocolCapabilities::stacker_db_contract(is_mainnet, reward_cycle);
     let signer_set_len = u32::try_from(reward_set.len())
         .map_err(|_| ChainstateError::InvalidStacksBlock("Reward set length exceeds u32".into()))?;
-    for signer_id in 0..signer_set_len {
-        let Some(signer_data) = stackerdbs.get_latest_chunk(&commitment_contract, signer_id)?
-        else {
-            warn!(
-                "Failed to fetch DKG result, will look for results from other signers.";
+    let slot_ids: Vec<u32> = (0..signer_set_len).collect();
+    let slots = match stackerdbs.get_latest_chunks(&capabilities_contract, &slot_ids) {
+        Ok(slots) => slots,
+        // without a replica of the capabilities contract, no signer has advertised
+        Err(NetError::NoSuchStackerDB(_)) => vec![None; slot_ids.len()],
+        Err(e) => return Err(e.into()),
+    };
+    let weights: Vec<u32> = reward_set.iter().map(|entry| entry.weight).collect();
+    let supporting = supporting_weight(&weights, &slots, MINER_PROTOCOL_VERSION);
+    if supporting < u64::from(threshold) {
+        error!(
+            "Not enough signers support the miner's protocol version, cannot coordinate a group signature";
+            "protocol_version" => %MINER_PROTOCOL_VERSION,
+            "supporting_weight" => supporting,
+            "threshold" => threshold,
+            "reward_cycle" => reward_cycle,
+        );
+        return Err(ChainstateError::InvalidStacksBlock(format!(
+            "Signers with weight {supporting} support protocol {MINER_PROTOCOL_VERSION}, below the signing threshold of {threshold}"
+        )));
+    }
+    let advertised = advertised_weight(&weights, &slots, MINER_PROTOCOL_VERSION);
+    if advertised < u64::from(threshold) {
+        info!(
+            "Not enough signers advertise the miner's protocol version, writing untagged messages";
+            "protocol_version" => %MINER_PROTOCOL_VERSION,
+            "advertised_weight" => advertised,
+            "threshold" => threshold,
+            "reward_cycle" => reward_cycle,
+        );
+        return Ok(None);
+    }
+    Ok(Some(MINER_PROTOCOL_VERSION))
+}
+
+/// Write `message` into `buf` enveloped as `payload_envelope` says. It is tagged with
+/// `message_tag`'s protocol version and watermark, or written untagged if that is `None`.
+fn seal_miner_message(
+    buf: &mut Vec<u8>,
+    payload_envelope: Option<&EnvelopeConfig>,
+    message_tag: Option<(ProtocolVersion, MinerWatermark)>,
+    message: &SignerMessage,
+) -> Result<(), String> {
+    seal_payload_into(buf, payload_envelope, |buf| match message_tag.as_ref() {
+        Some((version, watermark)) => write_tagged_message(buf, *version, watermark, message),
+        None => message.consensus_serialize(buf),
+    })
+    .map_err(|e| format!("Failed to envelope miner message: {e}"))
+}
+
+fn get_signer_commitments(
+    is_mainnet: bool,
+    reward_set: &[NakamotoSignerEntry],
+    stackerdbs: &StackerDBs,
+    reward_cycle: u64,
+    expected_aggregate_key: &Point,
+) -> Result<Vec<(u32, PolyCommitment)>, ChainstateError> {
+    let commitment_contract =
+        MessageSlotID::DkgResults.stacker_db_contract(is_mainnet, reward_cycle);
+    let signer_set_len = u32::try_from(reward_set.len())
+        .map_err(|_| ChainstateError::InvalidStacksBlock("Reward set length exceeds u32".into()))?;
+    let slot_ids: Vec<u32> = (0..signer_set_len).collect();
+    let signer_chunks = stackerdbs.get_latest_chunks(&commitment_contract, &slot_ids)?;
+    for (signer_id, signer_data) in slot_ids.into_iter().zip(signer_chunks) {
+        let Some(signer_data) = signer_data else {
+            warn!(
+                "Failed to fetch DKG result, will look for results from other signers.";
                 "signer_id" => signer_id
             );
             continue;
@@ -187,39 +1399,96 @@ fn get_signer_commitments(
 impl SignCoordinator {
     /// * `reward_set` - the active reward set data, used to construct the signer
     ///    set parameters.
-    /// * `message_key` - the signing key that the coordinator will use to sign messages
+    /// * `miner_sk` - the signing key that the coordinator will use to sign messages
     ///    broadcasted to the signer set. this should be the miner's registered key.
     /// * `aggregate_public_key` - the active aggregate key for this cycle
+    /// * `burn_tip` - the sortition whose `.miners` contract config the miner writes under
+    /// * `coordinator_config` - the timeouts and tolerances of the signing rounds
+    /// * `force_registration` - whether to take the StackerDB channel from another coordinator
+    ///    that still holds it, superseding that coordinator
     pub fn new(
         reward_set: &RewardSet,
         reward_cycle: u64,
-        message_key: Scalar,
+        miner_sk: &StacksPrivateKey,
         aggregate_public_key: Point,
         stackerdb_conn: &StackerDBs,
+        burnchain: &Burnchain,
+        sortdb: &SortitionDB,
+        burn_tip: &BlockSnapshot,
+        coordinator_config: SignCoordinatorConfig,
+        force_registration: bool,
         config: &Config,
-    ) -> Result<Self, ChainstateError> {
+    ) -> Result<Self, NakamotoNodeError> {
         let is_mainnet = config.is_mainnet();
+        let miner_sk = miner_chunk_key(miner_sk);
+        let message_key = Scalar::from(*miner_sk.as_slice());
         let Some(ref reward_set_signers) = reward_set.signers else {
             error!("Could not initialize WSTS coordinator for reward set without signer");
-            return Err(ChainstateError::NoRegisteredSigners(0));
+            return Err(init_failure(ChainstateError::NoRegisteredSigners(0)));
         };
 
+        let epoch_id = SortitionDB::get_stacks_epoch(sortdb.conn(), burn_tip.block_height)
+            .map_err(|e| init_failure(ChainstateError::DBError(e)))?
+            .ok_or_else(|| init_failure(ChainstateError::NoSuchBlockError))?
+            .epoch_id;
+
+        let miner_pubkey = StacksPublicKey::from_private(&miner_sk);
+        // a miner without any slot is not the active miner, which is caught before signing
+        let miner_slot_range = NakamotoChainState::get_miner_slot(sortdb, burn_tip, &miner_pubkey)
+            .map_err(init_failure)?;
+        if let Some(slot_range) = miner_slot_range.as_ref() {
+            check_miner_slot_range(slot_range)?;
+        }
+
         let rpc_socket = config
             .node
             .get_rpc_loopback()
-            .ok_or_else(|| ChainstateError::MinerAborted)?;
+            .ok_or_else(|| init_failure(ChainstateError::MinerAborted))?;
         let miners_contract_id = boot_code_id(MINERS_NAME, is_mainnet);
-        let miners_session = StackerDBSession::new(&rpc_socket.to_string(), miners_contract_id);
-
-        let next_signer_bitvec: BitVec<4000> = BitVec::zeros(
-            reward_set_signers
-                .clone()
-                .len()
-                .try_into()
-                .expect("FATAL: signer set length greater than u16"),
-        )
-        .expect("FATAL: unable to construct initial bitvec for signer set");
+        let miners_session =
+            StackerDBSession::new(&rpc_socket.to_string(), miners_contract_id.clone());
 
+        let next_signer_bitvec: BitVec<SIGNER_BITVEC_MAX_SIZE> =
+            BitVec::zeros(signer_bitvec_len(reward_set_signers.len())?)
+                .expect("FATAL: unable to construct initial bitvec for signer set");
+
+        let boundary_reward_cycle = boundary_reward_cycle(
+            &burnchain.reward_cycle_info(),
+            reward_cycle,
+            burn_tip.block_height,
+            coordinator_config.signer_set_boundary_window,
+        );
+        let round_reward_cycle = burnchain
+            .reward_cycle_info()
+            .cycle_of(burn_tip.block_height)
+            .ok_or_else(|| {
+                NakamotoNodeError::SigningFailure(SigningError::Coordinator(
+                    "Failed to initialize the signing coordinator. Cannot mine! The burn tip is before the first burn block.".into(),
+                ))
+            })?;
+        let signer_sets =
+            SignerSetParity::new(round_reward_cycle, boundary_reward_cycle).map_err(|e| {
+                NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
+                    "Failed to initialize the signing coordinator. Cannot mine! {e}"
+                )))
+            })?;
+        let signer_cycles: Vec<u64> = std::iter::once(reward_cycle)
+            .chain(boundary_reward_cycle)
+            .collect();
+        if let Some(boundary_reward_cycle) = boundary_reward_cycle {
+            info!(
+                "Miner/Coordinator: close to a reward cycle boundary, also accepting responses from the adjacent signer set";
+                "reward_cycle" => reward_cycle,
+                "boundary_reward_cycle" => boundary_reward_cycle,
+                "burn_height" => burn_tip.block_height,
+            );
+        }
+
+        let signing_params = SIGNING_PARAMS_CACHE
+            .lock()
+            .expect("FATAL: signing params cache lock poisoned")
+            .get_or_parse(is_mainnet, reward_cycle, reward_set)
+            .map_err(init_failure)?;
         let NakamotoSigningParams {
             num_signers,
             num_keys,
@@ -227,7 +1496,26 @@ impl SignCoordinator {
             signer_key_ids,
             signer_public_keys,
             wsts_public_keys,
-        } = NakamotoSigningParams::parse(is_mainnet, reward_set_signers.as_slice())?;
+        } = (*signing_params).clone();
+        let signer_set = SignerSetWeights::new(reward_set_signers.as_slice(), threshold);
+        let signing_info = SigningSubsystemInfo::new(
+            reward_cycle,
+            &signer_set,
+            &miners_contract_id,
+            miner_slot_range.as_ref(),
+            config.miner.payload_envelope.as_ref(),
+            boundary_reward_cycle,
+            &coordinator_config,
+        );
+        let reward_set_stats = RewardSetStats::new(reward_set_signers.as_slice());
+        update_signer_weight_stats(
+            reward_set_stats.num_signers.into(),
+            reward_set_stats.min_weight.into(),
+            reward_set_stats.max_weight.into(),
+            reward_set_stats.median_weight,
+            reward_set_stats.top_5_share,
+            reward_set_stats.hhi,
+        );
         debug!(
             "Initializing miner/coordinator";
             "num_signers" => num_signers,
@@ -249,6 +1537,12 @@ impl SignCoordinator {
         };
 
         let mut coordinator: FireCoordinator<Aggregator> = FireCoordinator::new(coord_config);
+        let proposal_db = InflightProposalDB::open(config.get_inflight_proposal_db_path())
+            .map_err(|e| warn!("Failed to open the in-flight proposal DB: {e:?}"))
+            .ok();
+        let equivocation_db = SignerEquivocationDB::open(config.get_signer_equivocation_db_path())
+            .map_err(|e| warn!("Failed to open the signer equivocation DB: {e:?}"))
+            .ok();
         #[cfg(test)]
         {
             // In test mode, short-circuit spinning up the SignCoordinator if the TEST_SIGNING
@@ -257,18 +1551,39 @@ impl SignCoordinator {
             use crate::tests::nakamoto_integrations::TEST_SIGNING;
             if TEST_SIGNING.lock().unwrap().is_some() {
                 debug!("Short-circuiting spinning up coordinator from signer commitments. Using test signers channel.");
-                let (receiver, replaced_other) = STACKER_DB_CHANNEL.register_miner_coordinator();
-                if replaced_other {
-                    warn!("Replaced the miner/coordinator receiver of a prior thread. Prior thread may have crashed.");
-                }
+                let (receiver, channel_registration) = register_signer_events(
+                    &STACKER_DB_CHANNEL,
+                    &signer_cycles,
+                    is_mainnet,
+                    force_registration,
+                )?;
                 let mut sign_coordinator = Self {
                     coordinator,
+                    miner_sk,
                     message_key,
                     receiver: Some(receiver),
+                    channel_registration,
                     wsts_public_keys,
                     is_mainnet,
+                    chain_id: config.burnchain.chain_id,
+                    epoch_id,
                     miners_session,
-                    signing_round_timeout: config.miner.wait_on_signers.clone(),
+                    payload_envelope: config.miner.payload_envelope,
+                    message_tag: None,
+                    message_buf: vec![],
+                    coordinator_config,
+                    signer_set,
+                    reward_set_signers: reward_set_signers.clone(),
+                    signer_sets,
+                    proposal_db,
+                    equivocation_db,
+                    latency_estimate: SignerLatencyEstimate::default(),
+                    tenure_signing_stats: TenureSigningStats::default(),
+                    reward_set_stats,
+                    prioritized_signer_keys: config.miner.prioritized_signer_keys.clone(),
+                    ignored_signer_keys: config.miner.ignored_signer_keys.clone(),
+                    trace_writer: sign_trace_writer(config),
+                    signing_info,
                     next_signer_bitvec,
                 };
                 sign_coordinator
@@ -277,65 +1592,145 @@ impl SignCoordinator {
                 return Ok(sign_coordinator);
             }
         }
+        let message_tag = check_protocol_support(
+            is_mainnet,
+            reward_set_signers.as_slice(),
+            stackerdb_conn,
+            reward_cycle,
+            threshold,
+        )
+        .map_err(init_failure)?;
         let party_polynomials = get_signer_commitments(
             is_mainnet,
             reward_set_signers.as_slice(),
             stackerdb_conn,
             reward_cycle,
             &aggregate_public_key,
-        )?;
+        )
+        .map_err(init_failure)?;
         if let Err(e) = coordinator
             .set_key_and_party_polynomials(aggregate_public_key.clone(), party_polynomials)
         {
             warn!("Failed to set a valid set of party polynomials"; "error" => %e);
         };
 
-        let (receiver, replaced_other) = STACKER_DB_CHANNEL.register_miner_coordinator();
-        if replaced_other {
-            warn!("Replaced the miner/coordinator receiver of a prior thread. Prior thread may have crashed.");
-        }
+        let (receiver, channel_registration) = register_signer_events(
+            &STACKER_DB_CHANNEL,
+            &signer_cycles,
+            is_mainnet,
+            force_registration,
+        )?;
 
         Ok(Self {
             coordinator,
+            miner_sk,
             message_key,
             receiver: Some(receiver),
+            channel_registration,
             wsts_public_keys,
             is_mainnet,
+            chain_id: config.burnchain.chain_id,
+            epoch_id,
             miners_session,
-            signing_round_timeout: config.miner.wait_on_signers.clone(),
+            payload_envelope: config.miner.payload_envelope,
+            message_tag,
+            message_buf: vec![],
+            coordinator_config,
+            signer_set,
+            reward_set_signers: reward_set_signers.clone(),
+            signer_sets,
+            proposal_db,
+            equivocation_db,
+            latency_estimate: SignerLatencyEstimate::default(),
+            tenure_signing_stats: TenureSigningStats::default(),
+            reward_set_stats,
+            prioritized_signer_keys: config.miner.prioritized_signer_keys.clone(),
+            ignored_signer_keys: config.miner.ignored_signer_keys.clone(),
+            trace_writer: sign_trace_writer(config),
+            signing_info,
             next_signer_bitvec,
         })
     }
 
+    /// Carry over the signer latency estimate of the tenure's earlier rounds
+    pub fn with_latency_estimate(mut self, latency_estimate: SignerLatencyEstimate) -> Self {
+        self.latency_estimate = latency_estimate;
+        self
+    }
+
+    /// The signer latency estimate, including the rounds this coordinator ran
+    pub fn latency_estimate(&self) -> &SignerLatencyEstimate {
+        &self.latency_estimate
+    }
+
+    /// Carry over the signing stats of the tenure's earlier rounds
+    pub fn with_tenure_signing_stats(mut self, tenure_signing_stats: TenureSigningStats) -> Self {
+        self.tenure_signing_stats = tenure_signing_stats;
+        self
+    }
+
+    /// The tenure's signing stats, including the rounds this coordinator ran
+    pub fn tenure_signing_stats(&self) -> &TenureSigningStats {
+        &self.tenure_signing_stats
+    }
+
+    /// How concentrated the signing weight of the reward set is
+    pub fn reward_set_stats(&self) -> &RewardSetStats {
+        &self.reward_set_stats
+    }
+
+    /// The signing protocol details the coordinator was set up with
+    pub fn signing_info(&self) -> &SigningSubsystemInfo {
+        &self.signing_info
+    }
+
+    /// Check that the signers would not reject `block` outright, so that the miner fixes a
+    /// block that cannot be signed instead of spending a signing round on it
+    pub fn preflight_proposal(&self, block: &NakamotoBlock) -> Result<(), ProposalPreflightError> {
+        preflight_checks(block, self.is_mainnet, self.chain_id, self.epoch_id)
+    }
+
+    /// Total weight of the signers in `signer_set` whose slots are set in `next_signer_bitvec`.
+    /// Takes the coordinator's fields rather than `self`, so that it can run while the event
+    /// receiver is borrowed.
+    fn responding_weight(
+        signer_set: &SignerSetWeights,
+        next_signer_bitvec: &BitVec<SIGNER_BITVEC_MAX_SIZE>,
+    ) -> u64 {
+        let slot_weights = signer_set
+            .slot_weights()
+            .into_iter()
+            .filter_map(|(slot_id, weight)| Some((u16::try_from(slot_id.0).ok()?, weight)));
+        next_signer_bitvec.weight_of(slot_weights)
+    }
+
     fn get_sign_id(burn_block_height: u64, burnchain: &Burnchain) -> u64 {
         burnchain
-            .pox_constants
-            .reward_cycle_index(burnchain.first_block_height, burn_block_height)
+            .reward_cycle_info()
+            .cycle_index(burn_block_height)
             .expect("FATAL: tried to initialize WSTS coordinator before first burn block height")
     }
 
     fn send_signers_message(
-        message_key: &Scalar,
+        miner_sk: &StacksPrivateKey,
         sortdb: &SortitionDB,
         tip: &BlockSnapshot,
         stackerdbs: &StackerDBs,
         message: SignerMessage,
         is_mainnet: bool,
+        payload_envelope: Option<&EnvelopeConfig>,
+        message_tag: Option<ProtocolVersion>,
+        message_buf: &mut Vec<u8>,
         miners_session: &mut StackerDBSession,
-    ) -> Result<(), String> {
-        let mut miner_sk = StacksPrivateKey::from_slice(&message_key.to_bytes()).unwrap();
-        miner_sk.set_compress_public(true);
-        let miner_pubkey = StacksPublicKey::from_private(&miner_sk);
+    ) -> Result<SlotMetadata, String> {
+        let miner_pubkey = StacksPublicKey::from_private(miner_sk);
         let Some(slot_range) = NakamotoChainState::get_miner_slot(sortdb, tip, &miner_pubkey)
             .map_err(|e| format!("Failed to read miner slot information: {e:?}"))?
         else {
             return Err("No slot for miner".into());
         };
-        // We only have one slot per miner
-        let slot_id = slot_range.start;
-        if !slot_range.contains(&slot_id) {
-            return Err("Not enough slots for miner messages".into());
-        }
+        let slot_id = miner_slot_id(&slot_range, MinerSlotID::BlockProposal)
+            .map_err(|e| format!("Not enough slots for miner messages: {e:?}"))?;
         // Get the LAST slot version number written to the DB. If not found, use 0.
         // Add 1 to get the NEXT version number
         // Note: we already check above for the slot's existence
@@ -345,12 +1740,117 @@ impl SignCoordinator {
             .map_err(|e| format!("Failed to read slot version: {e:?}"))?
             .unwrap_or(0)
             .saturating_add(1);
-        let mut chunk = StackerDBChunkData::new(slot_id, slot_version, message.serialize_to_vec());
+        // Every tagged message needs a higher watermark than the last one in the slot, so that
+        // signers can tell it apart from a replay of an older one.
+        let message_tag = match message_tag {
+            Some(version) => {
+                let last_watermark = stackerdbs
+                    .get_latest_chunk(&miners_contract_id, slot_id)
+                    .map_err(|e| format!("Failed to read latest miner chunk: {e:?}"))?
+                    .and_then(|data| {
+                        open_payload(&data)
+                            .and_then(|payload| {
+                                untag_message(&payload).map(|(_, watermark, _)| watermark)
+                            })
+                            .map_err(|e| warn!("Failed to read back our last miner message: {e}"))
+                            .ok()
+                            .flatten()
+                    });
+                let watermark = last_watermark.map_or_else(
+                    || MinerWatermark::new(tip.block_height, 0),
+                    |last| last.next_at(tip.block_height),
+                );
+                Some((version, watermark))
+            }
+            None => None,
+        };
+        seal_miner_message(message_buf, payload_envelope, message_tag, &message)?;
+        // the chunk holds the buffer while it is written, and hands it back for the next message
+        let mut chunk = StackerDBChunkData::new(slot_id, slot_version, std::mem::take(message_buf));
+        let result = Self::put_miner_chunk(miner_sk, &mut chunk, miners_session)
+            .map(|_| chunk.get_slot_metadata());
+        *message_buf = chunk.data;
+        result
+    }
+
+    /// Read the proposal back out of the miner's own StackerDB, and write it again under the
+    /// next slot version if the slot does not hold it. `message_buf` must still hold the
+    /// proposal's payload for it to be written again.
+    fn verify_proposal_written(
+        miner_sk: &StacksPrivateKey,
+        stackerdbs: &StackerDBs,
+        proposal_chunk: &SlotMetadata,
+        is_mainnet: bool,
+        message_buf: &mut Vec<u8>,
+        miners_session: &mut StackerDBSession,
+        counters: &Counters,
+    ) {
+        let miners_contract_id = boot_code_id(MINERS_NAME, is_mainnet);
+        let read_back = stackerdbs
+            .get_slot_metadata(&miners_contract_id, proposal_chunk.slot_id)
+            .map_err(|e| format!("Failed to read the miner slot: {e:?}"));
+        let reason = match check_proposal_readback(proposal_chunk, read_back) {
+            ProposalReadback::Found => {
+                debug!(
+                    "Miner/Coordinator: Read the proposal back out of the miner's StackerDB";
+                    "slot_id" => proposal_chunk.slot_id,
+                    "slot_version" => proposal_chunk.slot_version,
+                );
+                return;
+            }
+            ProposalReadback::Superseded { slot_version } => {
+                debug!(
+                    "Miner/Coordinator: A later message already replaced the proposal in the miner's StackerDB";
+                    "slot_id" => proposal_chunk.slot_id,
+                    "proposal_slot_version" => proposal_chunk.slot_version,
+                    "slot_version" => slot_version,
+                );
+                return;
+            }
+            ProposalReadback::Missing(reason) => reason,
+        };
+        error!(
+            "Miner/Coordinator: The miner's own StackerDB does not hold the proposal it wrote. Signers may never see it. Writing it again.";
+            "slot_id" => proposal_chunk.slot_id,
+            "slot_version" => proposal_chunk.slot_version,
+            "reason" => %reason,
+        );
+        if Sha512Trunc256Sum::from_data(message_buf) != proposal_chunk.data_hash {
+            warn!("Miner/Coordinator: The proposal's payload is no longer at hand. Not writing it again.");
+            return;
+        }
+        let mut chunk = StackerDBChunkData::new(
+            proposal_chunk.slot_id,
+            proposal_chunk.slot_version.saturating_add(1),
+            std::mem::take(message_buf),
+        );
+        let result = Self::put_miner_chunk(miner_sk, &mut chunk, miners_session);
+        *message_buf = chunk.data;
+        match result {
+            Ok(()) => {
+                counters.bump_naka_proposal_rewrites();
+                info!(
+                    "Miner/Coordinator: Wrote the proposal again";
+                    "slot_id" => chunk.slot_id,
+                    "slot_version" => chunk.slot_version,
+                );
+            }
+            Err(e) => {
+                warn!("Miner/Coordinator: Failed to write the proposal again: {e}");
+            }
+        }
+    }
+
+    fn put_miner_chunk(
+        miner_sk: &StacksPrivateKey,
+        chunk: &mut StackerDBChunkData,
+        miners_session: &mut StackerDBSession,
+    ) -> Result<(), String> {
         chunk
-            .sign(&miner_sk)
+            .sign(miner_sk)
             .map_err(|_| "Failed to sign StackerDB chunk")?;
 
-        match miners_session.put_chunk(&chunk) {
+        match miners_session.put_chunk(chunk) {
             Ok(ack) => {
                 debug!("Wrote message to stackerdb: {ack:?}");
                 Ok(())
@@ -362,52 +1862,385 @@ impl SignCoordinator {
         }
     }
 
+    /// Propose `block` to the signer set and wait for their threshold signature.
+    /// The proposal is persisted for the duration of the round, so that a miner that restarts
+    /// before the round ends can resume it with [`SignCoordinator::resume_round`].
+    /// Once signed, the block is returned right away if `broadcast_deadline` is near, and
+    /// otherwise after a brief grace period in which late signers are still counted.
     #[cfg_attr(test, mutants::skip)]
     pub fn begin_sign(
         &mut self,
         block: &NakamotoBlock,
         burn_block_height: u64,
         block_attempt: u64,
+        broadcast_deadline: Instant,
+        miner_view: TenureView,
+        burn_tip: &BlockSnapshot,
+        burnchain: &Burnchain,
+        sortdb: &SortitionDB,
+        stackerdbs: &StackerDBs,
+        counters: &Counters,
+    ) -> Result<SignedBlockResult, NakamotoNodeError> {
+        self.sign_proposal(
+            block,
+            None,
+            burn_block_height,
+            block_attempt,
+            broadcast_deadline,
+            miner_view,
+            burn_tip,
+            burnchain,
+            sortdb,
+            stackerdbs,
+            counters,
+        )
+    }
+
+    /// Propose `block`, whose tenure change extends the miner's tenure, to the signer set as a
+    /// tenure extension, and wait for their threshold signature. Signers validate a tenure
+    /// extension differently from a block that starts or continues a tenure. Errors if `block`
+    /// does not extend a tenure.
+    #[cfg_attr(test, mutants::skip)]
+    pub fn propose_tenure_extend(
+        &mut self,
+        block: &NakamotoBlock,
+        burn_block_height: u64,
+        block_attempt: u64,
+        broadcast_deadline: Instant,
+        miner_view: TenureView,
+        burn_tip: &BlockSnapshot,
+        burnchain: &Burnchain,
+        sortdb: &SortitionDB,
+        stackerdbs: &StackerDBs,
+        counters: &Counters,
+    ) -> Result<SignedBlockResult, NakamotoNodeError> {
+        let tenure_extend = tenure_extend_of(block).ok_or_else(|| {
+            NakamotoNodeError::SigningFailure(SigningError::Coordinator(
+                "Cannot propose a tenure extension for a block that does not extend a tenure"
+                    .into(),
+            ))
+        })?;
+        info!(
+            "SignCoordinator: Proposing tenure extension";
+            "signer_sighash" => %block.header.signer_signature_hash(),
+            "tenure_consensus_hash" => %tenure_extend.tenure_consensus_hash,
+            "burn_view_consensus_hash" => %tenure_extend.burn_view_consensus_hash,
+        );
+        self.sign_proposal(
+            block,
+            Some(tenure_extend),
+            burn_block_height,
+            block_attempt,
+            broadcast_deadline,
+            miner_view,
+            burn_tip,
+            burnchain,
+            sortdb,
+            stackerdbs,
+            counters,
+        )
+    }
+
+    /// Re-enter the signing round for a proposal that was still in flight when the miner
+    /// last stopped. The same block is re-broadcast, so signers see the same signer signature
+    /// hash they may already have signed. WSTS signature shares are bound to the nonces of the
+    /// round that produced them, so the persisted shares only tell us who had responded; a new
+    /// round iteration (`block_attempt`, which must exceed `proposal.attempt`) gathers them again.
+    /// Signers that already signed the block answer with its threshold signature instead,
+    /// which ends the round once it verifies.
+    #[cfg_attr(test, mutants::skip)]
+    pub fn resume_round(
+        &mut self,
+        proposal: &InflightProposal,
+        burn_block_height: u64,
+        block_attempt: u64,
+        broadcast_deadline: Instant,
+        miner_view: TenureView,
+        burn_tip: &BlockSnapshot,
+        burnchain: &Burnchain,
+        sortdb: &SortitionDB,
+        stackerdbs: &StackerDBs,
+        counters: &Counters,
+    ) -> Result<SignedBlockResult, NakamotoNodeError> {
+        info!(
+            "SignCoordinator: Resuming signing round for in-flight proposal";
+            "signer_sighash" => %proposal.block.header.signer_signature_hash(),
+            "block_id" => %proposal.block.block_id(),
+            "prior_attempt" => proposal.attempt,
+            "attempt" => block_attempt,
+            "responded_signers" => ?proposal.signer_ids,
+        );
+        // a resumed tenure extension is proposed as one again
+        self.sign_proposal(
+            &proposal.block,
+            tenure_extend_of(&proposal.block),
+            burn_block_height,
+            block_attempt,
+            broadcast_deadline,
+            miner_view,
+            burn_tip,
+            burnchain,
+            sortdb,
+            stackerdbs,
+            counters,
+        )
+    }
+
+    /// Persist the proposal of `block` while its signing round runs
+    #[cfg_attr(test, mutants::skip)]
+    fn sign_proposal(
+        &mut self,
+        block: &NakamotoBlock,
+        tenure_extend: Option<TenureExtend>,
+        burn_block_height: u64,
+        block_attempt: u64,
+        broadcast_deadline: Instant,
+        miner_view: TenureView,
+        burn_tip: &BlockSnapshot,
+        burnchain: &Burnchain,
+        sortdb: &SortitionDB,
+        stackerdbs: &StackerDBs,
+        counters: &Counters,
+    ) -> Result<SignedBlockResult, NakamotoNodeError> {
+        let block_sighash = block.header.signer_signature_hash();
+        if let Some(proposal_db) = self.proposal_db.as_mut() {
+            if let Err(e) = proposal_db.begin_round(block, block_attempt) {
+                warn!("Failed to persist the in-flight block proposal"; "signer_sighash" => %block_sighash, "err" => ?e);
+            }
+        }
+        let result = self.run_sign_round(
+            block,
+            tenure_extend,
+            burn_block_height,
+            block_attempt,
+            broadcast_deadline,
+            miner_view,
+            burn_tip,
+            burnchain,
+            sortdb,
+            stackerdbs,
+            counters,
+        );
+        if let Some(proposal_db) = self.proposal_db.as_mut() {
+            if let Err(e) = proposal_db.end_round(&block_sighash) {
+                warn!("Failed to clear the in-flight block proposal"; "signer_sighash" => %block_sighash, "err" => ?e);
+            }
+        }
+        result
+    }
+
+    /// Record the votes to accept `block` of the signers whose signature shares are in
+    /// `packets` in the equivocation DB, and warn about any signer that has already voted to
+    /// accept a different block at `block`'s height in its tenure. Takes the coordinator's
+    /// fields rather than `self`, so that it can run while the event receiver is borrowed.
+    fn record_signer_equivocations(
+        equivocation_db: Option<&mut SignerEquivocationDB>,
+        wsts_public_keys: &PublicKeys,
+        round_votes: &mut RoundVotes,
+        block: &NakamotoBlock,
+        packets: &[Packet],
+        sign_id: u64,
+        sign_iter_id: u64,
+    ) {
+        let Some(equivocation_db) = equivocation_db else {
+            return;
+        };
+        let block_sighash = block.header.signer_signature_hash();
+        for (signer_id, vote) in
+            round_votes.process_packets(packets, sign_id, sign_iter_id, &block_sighash)
+        {
+            let Some(signer_key) = wsts_public_keys.signers.get(&signer_id) else {
+                continue;
+            };
+            let signer_key = to_hex(&signer_key.to_bytes());
+            match equivocation_db.record_vote(
+                &signer_key,
+                &block.header.consensus_hash,
+                block.header.chain_length,
+                &block_sighash,
+                &vote,
+            ) {
+                Ok(Some(evidence)) => {
+                    warn!("Miner/Coordinator: signer voted to accept conflicting blocks at the same height";
+                        "signer_id" => signer_id,
+                        "signer_key" => &evidence.signer_key,
+                        "consensus_hash" => %evidence.consensus_hash,
+                        "height" => evidence.height,
+                        "first_signer_sighash" => %evidence.first_signer_sighash,
+                        "second_signer_sighash" => %evidence.second_signer_sighash,
+                    );
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!("Failed to record signer vote"; "signer_id" => signer_id, "err" => ?e);
+                }
+            }
+        }
+    }
+
+    #[cfg_attr(test, mutants::skip)]
+    fn run_sign_round(
+        &mut self,
+        block: &NakamotoBlock,
+        tenure_extend: Option<TenureExtend>,
+        burn_block_height: u64,
+        block_attempt: u64,
+        broadcast_deadline: Instant,
+        miner_view: TenureView,
         burn_tip: &BlockSnapshot,
         burnchain: &Burnchain,
         sortdb: &SortitionDB,
         stackerdbs: &StackerDBs,
         counters: &Counters,
-    ) -> Result<ThresholdSignature, NakamotoNodeError> {
+    ) -> Result<SignedBlockResult, NakamotoNodeError> {
+        self.preflight_proposal(block).map_err(|e| {
+            warn!(
+                "Miner/Coordinator: Not proposing a block the signers would reject";
+                "signer_sighash" => %block.header.signer_signature_hash(),
+                "err" => ?e,
+            );
+            NakamotoNodeError::ProposalPreflightFailed(e)
+        })?;
+        check_winning_miner(
+            sortdb,
+            burn_tip,
+            &StacksPublicKey::from_private(&self.miner_sk),
+        )?;
+        check_node_synced(
+            sortdb,
+            burn_tip,
+            self.coordinator_config.stacks_tip_lag_tolerance,
+        )?;
         let sign_id = Self::get_sign_id(burn_tip.block_height, burnchain);
         let sign_iter_id = block_attempt;
-        let reward_cycle_id = burnchain
-            .block_height_to_reward_cycle(burn_tip.block_height)
-            .expect("FATAL: tried to initialize coordinator before first burn block height");
+        let reward_cycle_id = self.signer_sets.reward_cycle();
         self.coordinator.current_sign_id = sign_id;
         self.coordinator.current_sign_iter_id = sign_iter_id;
+        stacks::monitoring::update_naka_signing_round_attempt(block_attempt);
+        // set up before the proposal goes out, so that no response to it is mistaken for
+        // one to an earlier proposal
+        let signer_cycles = self.signer_sets.signer_cycles();
+        // if the event receiver goes quiet, poll just enough of the signers to reach the
+        // threshold first
+        let first_read_slots = sample_signers_by_weight(
+            &self.signer_set,
+            self.signer_set.threshold().saturating_sub(1),
+            &mut rand::thread_rng(),
+        );
+        let mut signer_events = SignerEventSource::new(
+            &signer_cycles,
+            self.is_mainnet,
+            stackerdbs,
+            self.coordinator_config.signer_db_poll_interval,
+        )
+        .with_first_read_slots(&first_read_slots);
 
+        let miner_burn_view = miner_view.burn_view_consensus_hash.clone();
         let proposal_msg = BlockProposal {
             block: block.clone(),
             burn_height: burn_block_height,
             reward_cycle: reward_cycle_id,
+            miner_view: Some(miner_view),
+            tenure_extend,
+            attempt: Some(block_attempt),
         };
 
+        let coordinator_pk = ecdsa::PublicKey::new(&self.message_key).map_err(|_e| {
+            NakamotoNodeError::MinerSignatureError("Bad signing key for the FIRE coordinator")
+        })?;
+        let mut round = SignRound::new(
+            self.signer_set.clone(),
+            block.header.signer_signature_hash(),
+            sign_id,
+            sign_iter_id,
+            self.signer_sets,
+            miner_burn_view.clone(),
+            Some(block.header.consensus_hash.clone()),
+            self.wsts_public_keys.clone(),
+            coordinator_pk,
+        );
+        if !self.ignored_signer_keys.is_empty() {
+            warn!(
+                "Miner/Coordinator: Dropping the responses of ignored signers";
+                "ignored_signer_keys" => self.ignored_signer_keys.len(),
+            );
+            round.ignore_signers(&self.ignored_signer_keys);
+        }
+        if let Some(aggregate_public_key) = self.coordinator.aggregate_public_key {
+            round.accept_replayed_signatures(aggregate_public_key);
+        }
+        let mut round_votes = RoundVotes::default();
+        if let Some(equivocation_db) = self.equivocation_db.as_mut() {
+            let min_height = block
+                .header
+                .chain_length
+                .saturating_sub(VOTE_RETENTION_BLOCKS);
+            if let Err(e) = equivocation_db.prune_votes_below(min_height) {
+                warn!("Failed to prune the recorded signer votes"; "err" => ?e);
+            }
+        }
+        let block_id = block.block_id();
+
+        let block_size = block.serialize_to_vec().len() as u64;
         let block_bytes = proposal_msg.serialize_to_vec();
         let nonce_req_msg = self
             .coordinator
             .start_signing_round(&block_bytes, false, None)
             .map_err(|e| {
-                NakamotoNodeError::SigningCoordinatorFailure(format!(
+                NakamotoNodeError::SigningFailure(SigningError::Coordinator(format!(
                     "Failed to start signing round in FIRE coordinator: {e:?}"
-                ))
+                )))
             })?;
-        Self::send_signers_message(
-            &self.message_key,
+        let proposal_chunk = Self::send_signers_message(
+            &self.miner_sk,
             sortdb,
             burn_tip,
             &stackerdbs,
             nonce_req_msg.into(),
             self.is_mainnet,
+            self.payload_envelope.as_ref(),
+            self.message_tag,
+            &mut self.message_buf,
             &mut self.miners_session,
         )
-        .map_err(NakamotoNodeError::SigningCoordinatorFailure)?;
+        .map_err(|reason| {
+            NakamotoNodeError::SigningFailure(SigningError::StackerDbWriteFailed { reason })
+        })?;
+        // the proposal's chunk and when to read it back, until it is read back or another
+        // message takes its slot
+        let mut unverified_proposal = self
+            .coordinator_config
+            .proposal_readback_delay
+            .map(|delay| (proposal_chunk, delay));
         counters.bump_naka_proposed_blocks();
+        let round_id = SignRoundId {
+            signer_sighash: block.header.signer_signature_hash(),
+            attempt: block_attempt,
+        };
+        let trace = match self.trace_writer.as_ref() {
+            Some(writer) => writer.start_round(round_id),
+            None => SignRoundTrace::disabled(round_id),
+        };
+        trace.record(SignTraceEvent::ProposalSent {
+            block_id: block_id.clone(),
+            burn_height: burn_block_height,
+            reward_cycle: reward_cycle_id,
+        });
+        let mut timing = SignRoundTiming::start();
+        // every exit from here on logs the round's summary, traces its end and merges it into
+        //  the tenure's signing stats
+        let log_summary = |round: &SignRound,
+                           timing: &SignRoundTiming,
+                           completion: SignRoundCompletion,
+                           tenure_stats: &mut TenureSigningStats| {
+            round
+                .summary(block_id.clone(), block_attempt, timing, completion)
+                .log(&LOGGER);
+            trace.record_end(completion);
+            if completion.counts_toward_signer_stats() {
+                merge_tenure_signing_stats(tenure_stats, &block.header.consensus_hash, round);
+            }
+        };
         #[cfg(test)]
         {
             // In test mode, short-circuit waiting for the signers if the TEST_SIGNING
@@ -417,90 +2250,330 @@ impl SignCoordinator {
                 crate::tests::nakamoto_integrations::TestSigningChannel::get_signature()
             {
                 debug!("Short-circuiting waiting for signers, using test signature");
-                return Ok(signature);
+                counters.bump_naka_accepted_blocks();
+                log_summary(
+                    &round,
+                    &timing,
+                    SignRoundCompletion::TestSignature,
+                    &mut self.tenure_signing_stats,
+                );
+                return Ok(SignedBlockResult::from_test_channel(signature));
             }
         }
 
         let Some(ref mut receiver) = self.receiver else {
-            return Err(NakamotoNodeError::SigningCoordinatorFailure(
-                "Failed to obtain the StackerDB event receiver".into(),
+            log_summary(
+                &round,
+                &timing,
+                SignRoundCompletion::Error,
+                &mut self.tenure_signing_stats,
+            );
+            return Err(NakamotoNodeError::SigningFailure(
+                SigningError::ChannelDisconnected,
             ));
         };
 
-        let start_ts = Instant::now();
-        while start_ts.elapsed() <= self.signing_round_timeout {
-            let event = match receiver.recv_timeout(EVENT_RECEIVER_POLL) {
-                Ok(event) => event,
-                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
-                    continue;
+        // reused for every batch of signer messages in the round
+        let mut update = SignRoundUpdate::default();
+        // the block's signature, and until when late signers are still counted
+        let mut signed: Option<(ThresholdSignature, Instant)> = None;
+        while timing.elapsed() <= self.coordinator_config.signing_round_timeout {
+            if matches!(signed, Some((_, grace_end)) if Instant::now() >= grace_end) {
+                break;
+            }
+            if let Err(e) = check_registration(&STACKER_DB_CHANNEL, &self.channel_registration) {
+                if signed.is_some() {
+                    break;
                 }
-                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
-                    return Err(NakamotoNodeError::SigningCoordinatorFailure(
-                        "StackerDB event receiver disconnected".into(),
-                    ))
+                warn!(
+                    "Miner/Coordinator: A newer coordinator took over the StackerDB channel. Abandoning the signing round.";
+                    "generation" => self.channel_registration.generation(),
+                    "signer_sighash" => %block.header.signer_signature_hash(),
+                );
+                log_summary(
+                    &round,
+                    &timing,
+                    SignRoundCompletion::Error,
+                    &mut self.tenure_signing_stats,
+                );
+                return Err(e);
+            }
+            if let Err(e) = check_burn_tip(sortdb, &miner_burn_view) {
+                if signed.is_some() {
+                    break;
+                }
+                let completion = if let NakamotoNodeError::BurnchainTipChanged {
+                    old_consensus_hash,
+                    new_consensus_hash,
+                } = &e
+                {
+                    warn!(
+                        "Miner/Coordinator: Burnchain tip changed during the signing round. Abandoning it.";
+                        "old_consensus_hash" => %old_consensus_hash,
+                        "new_consensus_hash" => %new_consensus_hash,
+                        "signer_sighash" => %block.header.signer_signature_hash(),
+                    );
+                    SignRoundCompletion::BurnTipChanged
+                } else {
+                    SignRoundCompletion::Error
+                };
+                log_summary(&round, &timing, completion, &mut self.tenure_signing_stats);
+                return Err(e);
+            }
+            if let Some((proposal_chunk, readback_delay)) = unverified_proposal.as_ref() {
+                if timing.has_response() {
+                    unverified_proposal = None;
+                } else if timing.elapsed() >= *readback_delay {
+                    Self::verify_proposal_written(
+                        &self.miner_sk,
+                        stackerdbs,
+                        proposal_chunk,
+                        self.is_mainnet,
+                        &mut self.message_buf,
+                        &mut self.miners_session,
+                        counters,
+                    );
+                    unverified_proposal = None;
                 }
-            };
-
-            let is_signer_event =
-                event.contract_id.name.starts_with(SIGNERS_NAME) && event.contract_id.is_boot();
-            if !is_signer_event {
-                debug!("Ignoring StackerDB event for non-signer contract"; "contract" => %event.contract_id);
-                continue;
             }
-            let modified_slots = &event.modified_slots;
+            // verifying a large batch can take a while, so the work of each iteration is
+            //  capped at one poll interval, and at the round's timeout. Messages left over
+            //  are processed first on the next iteration.
+            let work_deadline = |timing: &SignRoundTiming| {
+                Instant::now()
+                    + EVENT_RECEIVER_POLL.min(
+                        self.coordinator_config
+                            .signing_round_timeout
+                            .saturating_sub(timing.elapsed()),
+                    )
+            };
+            if round.has_queued_messages() {
+                let work_deadline = work_deadline(&timing);
+                round
+                    .process_queued_messages_until(&mut update, || Instant::now() >= work_deadline);
+            } else {
+                let next_event = signer_events.next_event(receiver, stackerdbs);
+                counters.add_naka_duplicate_signer_chunks(signer_events.take_duplicate_chunks());
+                counters.add_naka_oversized_signer_chunks(signer_events.take_oversized_chunks());
+                if let Some(delay) = signer_events.take_dequeue_delay() {
+                    counters.record_naka_chunk_dequeue_delay(delay);
+                }
+                let event = match next_event {
+                    Ok(Some(event)) => event,
+                    Ok(None) => continue,
+                    Err(_) if signed.is_some() => break,
+                    Err(e) => {
+                        log_summary(
+                            &round,
+                            &timing,
+                            SignRoundCompletion::Error,
+                            &mut self.tenure_signing_stats,
+                        );
+                        // a superseded coordinator's receiver is disconnected
+                        check_registration(&STACKER_DB_CHANNEL, &self.channel_registration)?;
+                        return Err(e);
+                    }
+                };
 
-            // Update `next_signers_bitvec` with the slots that were modified in the event
-            modified_slots.iter().for_each(|chunk| {
-                if let Ok(slot_id) = chunk.slot_id.try_into() {
-                    match &self.next_signer_bitvec.set(slot_id, true) {
-                        Err(e) => {
-                            warn!("Failed to set bitvec for next signer: {e:?}");
-                        }
-                        _ => (),
-                    };
+                let is_signer_event =
+                    event.contract_id.name.starts_with(SIGNERS_NAME) && event.contract_id.is_boot();
+                if !is_signer_event {
+                    debug!("Ignoring StackerDB event for non-signer contract"; "contract" => %event.contract_id);
+                    continue;
+                }
+                let chunk_versions: Vec<_> = if trace.is_enabled() {
+                    event
+                        .modified_slots
+                        .iter()
+                        .map(|chunk| (chunk.slot_id, chunk.slot_version))
+                        .collect()
                 } else {
-                    error!("FATAL: slot_id greater than u16, which should never happen.");
+                    vec![]
+                };
+                let Ok(signer_event) = SignerEvent::try_from(event).map_err(|e| {
+                    warn!("Failure parsing StackerDB event into signer event. Ignoring message."; "err" => ?e);
+                }) else {
+                    continue;
+                };
+                let SignerEvent::SignerMessages(signer_set, mut messages) = signer_event else {
+                    debug!("Received signer event other than a signer message. Ignoring.");
+                    continue;
+                };
+                debug!("Miner/Coordinator: Received messages from signers"; "count" => messages.len(), "signer_set" => signer_set);
+                for (slot_id, slot_version) in chunk_versions {
+                    // chunks that do not hold a signer message are dropped while parsing
+                    let parsed = messages
+                        .iter()
+                        .any(|msg| msg.slot_id == slot_id && msg.slot_version == slot_version);
+                    trace.record(SignTraceEvent::ChunkReceived {
+                        signer_set,
+                        slot_id,
+                        slot_version,
+                        parsed,
+                    });
                 }
-            });
 
-            let Ok(signer_event) = SignerEvent::try_from(event).map_err(|e| {
-                warn!("Failure parsing StackerDB event into signer event. Ignoring message."; "err" => ?e);
-            }) else {
-                continue;
-            };
-            let SignerEvent::SignerMessages(signer_set, messages) = signer_event else {
-                debug!("Received signer event other than a signer message. Ignoring.");
-                continue;
-            };
-            if signer_set != u32::try_from(reward_cycle_id % 2).unwrap() {
-                debug!("Received signer event for other reward cycle. Ignoring.");
-                continue;
-            };
-            debug!("Miner/Coordinator: Received messages from signers"; "count" => messages.len());
-            let coordinator_pk = ecdsa::PublicKey::new(&self.message_key).map_err(|_e| {
-                NakamotoNodeError::MinerSignatureError("Bad signing key for the FIRE coordinator")
-            })?;
-            let packets: Vec<_> = messages
-                .into_iter()
-                .filter_map(|msg| match msg {
-                    SignerMessage::DkgResults { .. }
-                    | SignerMessage::BlockResponse(_)
-                    | SignerMessage::EncryptedSignerState(_)
-                    | SignerMessage::Transactions(_) => None,
-                    SignerMessage::Packet(packet) => {
-                        debug!("Received signers packet: {packet:?}");
-                        if !packet.verify(&self.wsts_public_keys, &coordinator_pk) {
-                            warn!("Failed to verify StackerDB packet: {packet:?}");
-                            None
-                        } else {
-                            Some(packet)
+                prioritize_signer_messages(&mut messages, &self.prioritized_signer_keys);
+                let work_deadline = work_deadline(&timing);
+                round.process_signer_messages_until(signer_set, messages, &mut update, || {
+                    Instant::now() >= work_deadline
+                });
+            }
+            if !update.responding_slots.is_empty() {
+                timing.record_response();
+            }
+            for round_event in update.events.iter() {
+                trace.record_round_event(round_event);
+                match round_event {
+                    SignRoundEvent::SignerSetMismatch { signer_set } => {
+                        debug!(
+                            "Received signer event for other reward cycle. Ignoring.";
+                            "reward_cycle" => reward_cycle_id,
+                            "expected_signer_set" => self.signer_sets.signer_set(),
+                            "signer_set" => signer_set,
+                        );
+                        counters.bump_naka_signer_set_mismatches();
+                    }
+                    SignRoundEvent::ElectionMismatch {
+                        slot_id,
+                        election_consensus_hash,
+                    } => {
+                        debug!(
+                            "Miner/Coordinator: Ignoring a rejection of the block for another election";
+                            "slot_id" => slot_id,
+                            "election_consensus_hash" => %block.header.consensus_hash,
+                            "rejection_election_consensus_hash" => %election_consensus_hash,
+                        );
+                        counters.bump_naka_election_mismatches();
+                    }
+                    SignRoundEvent::Ignored { slot_id } => {
+                        debug!(
+                            "Miner/Coordinator: Dropped a response from an ignored signer";
+                            "slot_id" => slot_id,
+                        );
+                        counters.bump_naka_ignored_signer_responses();
+                    }
+                    SignRoundEvent::SignatureReplayed { slot_id } => {
+                        // a signer that already signed the block answers its re-proposal with
+                        //  the signature it produced, rather than with a new signature share
+                        if signed.is_some() {
+                            continue;
                         }
+                        let Some(signature) = round.replayed_signature() else {
+                            continue;
+                        };
+                        let now = Instant::now();
+                        let grace = post_threshold_grace(
+                            now,
+                            broadcast_deadline,
+                            self.coordinator_config.broadcast_deadline_margin,
+                            self.coordinator_config.post_threshold_grace,
+                        );
+                        info!(
+                            "SignCoordinator: A signer published the block's signature from an earlier round";
+                            "slot_id" => slot_id,
+                            "signer_sighash" => %block.header.signer_signature_hash(),
+                            "grace_ms" => grace.as_millis(),
+                        );
+                        signed = Some((signature.clone(), now + grace));
                     }
-                })
-                .collect();
+                    SignRoundEvent::WeightUpdated {
+                        accepted_weight,
+                        rejected_weight,
+                    } => {
+                        debug!(
+                            "Miner/Coordinator: Signer weight updated";
+                            "accepted_weight" => accepted_weight,
+                            "rejected_weight" => rejected_weight,
+                            "threshold" => self.signer_set.threshold(),
+                        );
+                    }
+                    SignRoundEvent::ThresholdReached { accepted_weight } => {
+                        let latency = timing.record_threshold();
+                        debug!(
+                            "Miner/Coordinator: Signers holding the threshold weight sent signature shares";
+                            "accepted_weight" => accepted_weight,
+                            "threshold" => self.signer_set.threshold(),
+                            "block_size" => block_size,
+                            "latency_ms" => latency.as_millis(),
+                        );
+                        self.latency_estimate.record(block_size, latency);
+                    }
+                    SignRoundEvent::Equivocation { slot_id } => {
+                        warn!(
+                            "Miner/Coordinator: Signer both signed and rejected the block. Only its first response counts.";
+                            "slot_id" => slot_id,
+                            "signer_sighash" => %block.header.signer_signature_hash(),
+                        );
+                    }
+                    SignRoundEvent::Rejected { rejected_weight } => {
+                        warn!(
+                            "Miner/Coordinator: Too much signer weight rejected the block for it to be signed";
+                            "rejected_weight" => rejected_weight,
+                            "total_weight" => self.signer_set.total_weight(),
+                            "threshold" => self.signer_set.threshold(),
+                        );
+                        counters.bump_naka_rejected_blocks();
+                        log_summary(
+                            &round,
+                            &timing,
+                            SignRoundCompletion::Rejected,
+                            &mut self.tenure_signing_stats,
+                        );
+                        return Err(self.signing_failure(
+                            SigningError::Rejected {
+                                weight: *rejected_weight,
+                            },
+                            round.rejections(),
+                        ));
+                    }
+                }
+            }
+
+            // Update `next_signers_bitvec` with the slots of the messages in the event
+            for slot_id in update.responding_slots.iter() {
+                if let Ok(slot_id) = (*slot_id).try_into() {
+                    if let Err(e) = self.next_signer_bitvec.set(slot_id, true) {
+                        warn!("Failed to set bitvec for next signer: {e:?}");
+                    }
+                } else {
+                    error!("FATAL: slot_id greater than u16, which should never happen.");
+                }
+            }
+
+            let packets = &update.packets;
+            if let Some(proposal_db) = self.proposal_db.as_ref() {
+                let block_sighash = block.header.signer_signature_hash();
+                for packet in packets.iter() {
+                    let WstsMessage::SignatureShareResponse(ref response) = packet.msg else {
+                        continue;
+                    };
+                    if response.sign_id != sign_id || response.sign_iter_id != sign_iter_id {
+                        continue;
+                    }
+                    if let Err(e) =
+                        proposal_db.record_signature_share(&block_sighash, response.signer_id)
+                    {
+                        warn!("Failed to persist signature share"; "signer_id" => response.signer_id, "err" => ?e);
+                    }
+                }
+            }
+            Self::record_signer_equivocations(
+                self.equivocation_db.as_mut(),
+                &self.wsts_public_keys,
+                &mut round_votes,
+                block,
+                packets,
+                sign_id,
+                sign_iter_id,
+            );
+            if signed.is_some() {
+                // the FIRE coordinator is done with the round; late shares only count as responses
+                continue;
+            }
             let (outbound_msgs, op_results) = self
                 .coordinator
-                .process_inbound_messages(&packets)
+                .process_inbound_messages(packets)
                 .unwrap_or_else(|e| {
                     error!(
                         "Miner/Coordinator: Failed to process inbound message packets";
@@ -529,35 +2602,71 @@ impl SignCoordinator {
                                 "signature" => %signature,
                                 "block_signer_signature_hash" => %block_sighash
                             );
-                            return Err(NakamotoNodeError::SignerSignatureError(
-                                "Signature failed to validate over the expected block".into(),
+                            counters.bump_naka_rejected_blocks();
+                            log_summary(
+                                &round,
+                                &timing,
+                                SignRoundCompletion::InvalidSignature,
+                                &mut self.tenure_signing_stats,
+                            );
+                            return Err(NakamotoNodeError::SigningFailure(
+                                SigningError::InvalidSignature(
+                                    "Signature failed to validate over the expected block".into(),
+                                ),
                             ));
                         } else {
+                            let now = Instant::now();
+                            let grace = post_threshold_grace(
+                                now,
+                                broadcast_deadline,
+                                self.coordinator_config.broadcast_deadline_margin,
+                                self.coordinator_config.post_threshold_grace,
+                            );
+                            let responding_weight =
+                                Self::responding_weight(&self.signer_set, &self.next_signer_bitvec);
                             info!(
                                 "SignCoordinator: Generated a valid signature for the block";
                                 "next_signer_bitvec" => self.next_signer_bitvec.binary_str(),
+                                "responding_weight" => responding_weight,
+                                "accepted_weight" => round.accepted_weight(),
+                                "grace_ms" => grace.as_millis(),
                             );
-                            return Ok(signature);
+                            signed = Some((signature, now + grace));
                         }
                     }
                     wsts::state_machine::OperationResult::SignError(e) => {
-                        return Err(NakamotoNodeError::SignerSignatureError(format!(
-                            "Signing failed: {e:?}"
-                        )))
+                        counters.bump_naka_rejected_blocks();
+                        log_summary(
+                            &round,
+                            &timing,
+                            SignRoundCompletion::SignError,
+                            &mut self.tenure_signing_stats,
+                        );
+                        return Err(self.signing_failure(
+                            SigningError::SignError(format!("{e:?}")),
+                            round.rejections(),
+                        ));
                     }
                 }
             }
+            if signed.is_some() {
+                continue;
+            }
             for msg in outbound_msgs {
+                unverified_proposal = None;
                 match Self::send_signers_message(
-                    &self.message_key,
+                    &self.miner_sk,
                     sortdb,
                     burn_tip,
                     stackerdbs,
                     msg.into(),
                     self.is_mainnet,
+                    self.payload_envelope.as_ref(),
+                    self.message_tag,
+                    &mut self.message_buf,
                     &mut self.miners_session,
                 ) {
-                    Ok(()) => {
+                    Ok(_) => {
                         debug!("Miner/Coordinator: sent outbound message.");
                     }
                     Err(e) => {
@@ -569,8 +2678,1679 @@ impl SignCoordinator {
             }
         }
 
-        Err(NakamotoNodeError::SignerSignatureError(
-            "Timed out waiting for group signature".into(),
-        ))
+        if let Some((signature, _)) = signed {
+            let responding_weight =
+                Self::responding_weight(&self.signer_set, &self.next_signer_bitvec);
+            debug!(
+                "SignCoordinator: Returning the signed block";
+                "next_signer_bitvec" => self.next_signer_bitvec.binary_str(),
+                "responding_weight" => responding_weight,
+                "accepted_weight" => round.accepted_weight(),
+            );
+            counters.bump_naka_accepted_blocks();
+            log_summary(
+                &round,
+                &timing,
+                SignRoundCompletion::Signed,
+                &mut self.tenure_signing_stats,
+            );
+            return Ok(SignedBlockResult::from_signers(
+                signature,
+                round.signer_signature_data(&self.reward_set_signers),
+            ));
+        }
+        counters.bump_naka_signing_timeouts();
+        log_summary(
+            &round,
+            &timing,
+            SignRoundCompletion::TimedOut,
+            &mut self.tenure_signing_stats,
+        );
+        Err(self.signing_failure(SigningError::Timeout, round.rejections()))
+    }
+
+    /// The error to return when a signing round fails with `reason`. If the signers' rejections
+    /// call for waiting before proposing again, the error carries how long to wait.
+    fn signing_failure(
+        &self,
+        reason: SigningError,
+        rejections: &RejectionTally,
+    ) -> NakamotoNodeError {
+        let reproposal_backoff =
+            rejections.reproposal_backoff(self.coordinator_config.reproposal_backoff_max);
+        if reproposal_backoff.is_zero() {
+            return NakamotoNodeError::SigningFailure(reason);
+        }
+        NakamotoNodeError::SignersConnectivityIssues {
+            reason,
+            reproposal_backoff,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use clarity::vm::types::QualifiedContractIdentifier;
+    use libsigner::envelope::EnvelopeConfig;
+    use libsigner::protocol::{
+        untag_message, MinerWatermark, ProtocolCapabilities, ProtocolVersion,
+    };
+    use libsigner::v1::messages::{
+        BlockResponse, MessageSlotID, MinerSlotID, SignerMessage, MAX_BLOCK_RESPONSE_LEN,
+    };
+    use libsigner::{
+        signer_set_for_cycle, BlockProposal, SignerEntries, SignerEvent, SlotId, TenureView,
+    };
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use stacks::burnchains::PoxConstants;
+    use stacks::chainstate::burn::ConsensusHash;
+    use stacks::chainstate::nakamoto::signer_set::NakamotoSigners;
+    use stacks::chainstate::nakamoto::test_signers::TestSigners as BlockSigners;
+    use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
+    use stacks::chainstate::stacks::boot::{
+        signing_threshold_for, MINERS_NAME, SIGNER_BITVEC_MAX_SIZE,
+    };
+    use stacks::chainstate::stacks::events::StackerDBChunksEvent;
+    use stacks::chainstate::stacks::{
+        StacksTransaction, TenureChangeCause, TenureChangePayload, ThresholdSignature,
+        TransactionAuth, TransactionPayload, TransactionSmartContract, TransactionVersion,
+        MAX_BLOCK_LEN,
+    };
+    use stacks::core::CHAIN_ID_TESTNET;
+    use stacks::libstackerdb::StackerDBChunkData;
+    use stacks::net::stackerdb::{StackerDBConfig, StackerDBs};
+    use stacks::util_lib::boot::boot_code_id;
+    use stacks::util_lib::strings::StacksString;
+    use stacks_common::bitvec::BitVec;
+    use stacks_common::codec::{read_next, StacksMessageCodec};
+    use stacks_common::types::chainstate::{
+        StacksAddress, StacksBlockId, StacksPrivateKey, StacksPublicKey,
+    };
+    use stacks_common::types::{PublicKey, StacksEpochId};
+    use stacks_common::util::hash::{Hash160, MerkleTree, Sha512Trunc256Sum};
+    use wsts::curve::ecdsa;
+    use wsts::curve::scalar::Scalar;
+
+    use super::{
+        boundary_reward_cycle, broadcast_deadline, check_burn_tip, check_miner_slot_range,
+        check_proposal_readback, check_protocol_support, check_registration, miner_chunk_key,
+        miner_slot_id, missed_tenures, post_threshold_grace, preflight_checks,
+        register_signer_events, release_signer_events, sample_signers_by_weight,
+        seal_miner_message, signer_bitvec_len, signer_entries_hash, tenure_extend_of,
+        BurnTipWatcher, NakamotoSigningParams, ProposalPreflightError, ProposalReadback,
+        SignatureProvenance, SignedBlockResult, SignerEventSource, SigningParamsCache,
+        SigningSubsystemInfo, EXPECTED_BURN_BLOCK_INTERVAL,
+    };
+    use crate::config::SignCoordinatorConfig;
+    use crate::event_dispatcher::{StackerDBChannel, StackerDBChannelEvent};
+    use crate::nakamoto_node::sign_round::testing::TestSigners;
+    use crate::nakamoto_node::sign_round::{
+        SignRound, SignRoundEvent, SignRoundUpdate, SignerSetParity, SignerSetWeights,
+    };
+    use crate::nakamoto_node::{
+        Error as NakamotoNodeError, SigningError, SigningFailureAction,
+        COORDINATOR_REGISTRATION_RETRY_INTERVAL, NODE_SYNC_RETRY_INTERVAL,
+    };
+    use crate::neon::Counters;
+    use crate::Config;
+
+    #[test]
+    fn miner_chunks_verify_against_registered_key() {
+        for compress_public in [true, false] {
+            let mut miner_sk = StacksPrivateKey::new();
+            miner_sk.set_compress_public(compress_public);
+            // the `.miners` contract registers the hash of the compressed public key
+            let registered = StacksAddress {
+                version: 0,
+                bytes: Hash160::from_node_public_key(&StacksPublicKey::from_private(&miner_sk)),
+            };
+
+            let chunk_key = miner_chunk_key(&miner_sk);
+            let chunk_pubkey = StacksPublicKey::from_private(&chunk_key);
+            assert!(chunk_pubkey.compressed());
+            assert_eq!(
+                Hash160::from_data(&chunk_pubkey.to_bytes()),
+                registered.bytes
+            );
+
+            let mut chunk = StackerDBChunkData::new(0, 1, vec![1, 2, 3]);
+            chunk.sign(&chunk_key).unwrap();
+            assert!(chunk.verify(&registered).unwrap());
+            assert_eq!(
+                chunk.recover_pk().unwrap().to_bytes_compressed(),
+                chunk_pubkey.to_bytes()
+            );
+
+            // WSTS packets are signed with the same key
+            let message_key = Scalar::from(*chunk_key.as_slice());
+            assert_eq!(
+                ecdsa::PublicKey::new(&message_key).unwrap(),
+                ecdsa::PublicKey::try_from(chunk_pubkey.to_bytes().as_slice()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn short_miner_slot_range_is_misconfigured() {
+        let required = u32::try_from(MinerSlotID::ALL.len()).unwrap();
+        for slot_range in [5..5, 5..(5 + required - 1)] {
+            assert!(matches!(
+                check_miner_slot_range(&slot_range),
+                Err(NakamotoNodeError::MinersContractMisconfigured {
+                    slot_count,
+                    required_slots,
+                }) if slot_count == slot_range.len() as u32 && required_slots == required
+            ));
+            assert!(matches!(
+                miner_slot_id(&slot_range, MinerSlotID::BlockProposal),
+                Err(NakamotoNodeError::MinersContractMisconfigured { .. })
+            ));
+        }
+        // a backwards range is treated as empty rather than underflowing
+        #[allow(clippy::reversed_empty_ranges)]
+        let backwards = 6..5;
+        assert!(check_miner_slot_range(&backwards).is_err());
+    }
+
+    #[test]
+    fn miner_slot_ids_are_offset_from_the_range_start() {
+        let required = u32::try_from(MinerSlotID::ALL.len()).unwrap();
+        let slot_range = 7..(7 + required);
+        check_miner_slot_range(&slot_range).unwrap();
+        for msg_id in MinerSlotID::ALL {
+            let slot_id = miner_slot_id(&slot_range, *msg_id).unwrap();
+            assert!(slot_range.contains(&slot_id));
+        }
+        assert_eq!(
+            miner_slot_id(&slot_range, MinerSlotID::BlockProposal).unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn proposal_readback_detects_a_stalled_write() {
+        let miner_sk = StacksPrivateKey::new();
+        let written = |slot_version: u32, payload: &[u8]| {
+            let mut chunk = StackerDBChunkData::new(7, slot_version, payload.to_vec());
+            chunk.sign(&miner_sk).unwrap();
+            chunk.get_slot_metadata()
+        };
+        let proposal_chunk = written(5, b"proposal");
+
+        assert_eq!(
+            check_proposal_readback(&proposal_chunk, Ok(Some(proposal_chunk.clone()))),
+            ProposalReadback::Found
+        );
+        // the node accepted the write, but its own copy of the slot is still the old one
+        assert!(matches!(
+            check_proposal_readback(&proposal_chunk, Ok(Some(written(4, b"older message")))),
+            ProposalReadback::Missing(_)
+        ));
+        assert!(matches!(
+            check_proposal_readback(&proposal_chunk, Ok(Some(written(5, b"another payload")))),
+            ProposalReadback::Missing(_)
+        ));
+        assert!(matches!(
+            check_proposal_readback(&proposal_chunk, Ok(None)),
+            ProposalReadback::Missing(_)
+        ));
+        assert_eq!(
+            check_proposal_readback(&proposal_chunk, Err("DB locked".into())),
+            ProposalReadback::Missing("DB locked".into())
+        );
+        // a later message is not overwritten with the proposal
+        assert_eq!(
+            check_proposal_readback(&proposal_chunk, Ok(Some(written(6, b"next message")))),
+            ProposalReadback::Superseded { slot_version: 6 }
+        );
+    }
+
+    #[test]
+    fn signer_bitvec_len_is_capped() {
+        let cap = usize::from(SIGNER_BITVEC_MAX_SIZE);
+        for signer_count in [0, 1, cap] {
+            let len = signer_bitvec_len(signer_count).unwrap();
+            assert_eq!(usize::from(len), signer_count);
+            // a bitvec of that length can record every signer's participation
+            let bitvec = BitVec::<SIGNER_BITVEC_MAX_SIZE>::from_slot_set(0..len, len).unwrap();
+            assert_eq!(bitvec, BitVec::ones(len).unwrap());
+        }
+        for signer_count in [cap + 1, usize::from(u16::MAX) + 1] {
+            assert!(matches!(
+                signer_bitvec_len(signer_count),
+                Err(NakamotoNodeError::SigningFailure(
+                    SigningError::Coordinator(_)
+                ))
+            ));
+        }
+    }
+
+    #[test]
+    fn broadcast_deadline_follows_the_burn_tip() {
+        let now = Instant::now();
+        let now_secs = 1_700_000_000;
+        // a tip that just arrived leaves the whole interval
+        assert_eq!(
+            broadcast_deadline(now_secs, now, now_secs),
+            now + EXPECTED_BURN_BLOCK_INTERVAL
+        );
+        assert_eq!(
+            broadcast_deadline(now_secs + 60, now, now_secs),
+            now + EXPECTED_BURN_BLOCK_INTERVAL
+        );
+        assert_eq!(
+            broadcast_deadline(now_secs - 540, now, now_secs),
+            now + Duration::from_secs(60)
+        );
+        // an overdue burn block could arrive any moment
+        assert_eq!(broadcast_deadline(now_secs - 900, now, now_secs), now);
+    }
+
+    #[test]
+    fn post_threshold_grace_gives_way_to_the_broadcast_deadline() {
+        let signed_at = Instant::now();
+        let margin = Duration::from_secs(30);
+        let grace = Duration::from_millis(500);
+        let grace_at = |until_deadline| {
+            post_threshold_grace(signed_at, signed_at + until_deadline, margin, grace)
+        };
+
+        // plenty of time: wait out the whole grace period
+        assert_eq!(grace_at(Duration::from_secs(300)), grace);
+        // the grace period is cut short so as not to eat into the margin
+        assert_eq!(
+            grace_at(margin + Duration::from_millis(200)),
+            Duration::from_millis(200)
+        );
+        // the deadline is near or past: broadcast right away
+        assert_eq!(grace_at(margin), Duration::ZERO);
+        assert_eq!(grace_at(Duration::from_secs(1)), Duration::ZERO);
+        assert_eq!(
+            post_threshold_grace(signed_at + margin, signed_at, margin, grace),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn boundary_cycle_only_near_the_cycle_boundary() {
+        // reward cycle `n` starts at burn height `5n + 1`
+        let reward_cycles = PoxConstants::regtest_default().reward_cycle_info(0);
+        assert_eq!(reward_cycles.reward_cycle_length, 5);
+
+        // the end of cycle 2 borders on cycle 3
+        assert_eq!(boundary_reward_cycle(&reward_cycles, 2, 15, 2), Some(3));
+        assert_eq!(boundary_reward_cycle(&reward_cycles, 2, 14, 2), Some(3));
+        // the start of cycle 2 borders on cycle 1
+        assert_eq!(boundary_reward_cycle(&reward_cycles, 2, 11, 2), Some(1));
+        assert_eq!(boundary_reward_cycle(&reward_cycles, 2, 12, 2), Some(1));
+        // the middle of the cycle is outside the window
+        assert_eq!(boundary_reward_cycle(&reward_cycles, 2, 13, 2), None);
+        // a window of 0 disables it
+        assert_eq!(boundary_reward_cycle(&reward_cycles, 2, 15, 0), None);
+        // there is no cycle before the first one
+        assert_eq!(boundary_reward_cycle(&reward_cycles, 0, 1, 2), None);
+    }
+
+    struct TestBurnTip(RefCell<ConsensusHash>);
+
+    impl BurnTipWatcher for TestBurnTip {
+        fn canonical_tip(&self) -> Result<ConsensusHash, NakamotoNodeError> {
+            Ok(self.0.borrow().clone())
+        }
+    }
+
+    #[test]
+    fn burn_tip_change_mid_round_aborts() {
+        let round_burn_view = ConsensusHash([0x01; 20]);
+        let watcher = TestBurnTip(RefCell::new(round_burn_view.clone()));
+
+        // polls before the tip moves are fine
+        for _ in 0..3 {
+            check_burn_tip(&watcher, &round_burn_view).unwrap();
+        }
+
+        // a late burn block or a reorg moves the tip
+        let new_tip = ConsensusHash([0x02; 20]);
+        watcher.0.replace(new_tip.clone());
+        match check_burn_tip(&watcher, &round_burn_view) {
+            Err(NakamotoNodeError::BurnchainTipChanged {
+                old_consensus_hash,
+                new_consensus_hash,
+            }) => {
+                assert_eq!(old_consensus_hash, round_burn_view);
+                assert_eq!(new_consensus_hash, new_tip);
+            }
+            other => panic!("Expected BurnchainTipChanged, got {other:?}"),
+        }
+    }
+
+    /// `message`, signed by `signer_sk` into version `slot_version` of `slot_id`
+    fn signer_chunk(
+        signer_sk: &StacksPrivateKey,
+        slot_id: u32,
+        slot_version: u32,
+        message: &SignerMessage,
+    ) -> StackerDBChunkData {
+        let mut chunk = StackerDBChunkData::new(slot_id, slot_version, message.serialize_to_vec());
+        chunk.sign(signer_sk).unwrap();
+        chunk
+    }
+
+    fn write_chunk(
+        stackerdbs: &mut StackerDBs,
+        contract_id: &QualifiedContractIdentifier,
+        chunk: &StackerDBChunkData,
+    ) {
+        let tx = stackerdbs.tx_begin(StackerDBConfig::noop()).unwrap();
+        tx.try_replace_chunk(contract_id, &chunk.get_slot_metadata(), &chunk.data)
+            .unwrap();
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn dropping_a_replaced_coordinator_keeps_the_newest_receiver() {
+        let channel = StackerDBChannel::new();
+        let contract_id = MessageSlotID::BlockResponse.stacker_db_contract(false, 10);
+
+        // a miner thread crashes mid-tenure, and the next one's coordinator forces its way in
+        //  before the crashed one's is dropped
+        let (crashed_receiver, crashed_registration) =
+            channel.register_miner_coordinator(false).unwrap();
+        let (receiver, registration) = channel.register_miner_coordinator(true).unwrap();
+        assert!(registration.replaced_other);
+
+        release_signer_events(&channel, Some(crashed_receiver), crashed_registration);
+        assert!(channel.is_active(&contract_id).is_some());
+        // nor does a coordinator that lost its receiver panic, or release anything, on drop
+        release_signer_events(&channel, None, crashed_registration);
+        release_signer_events(&channel, None, registration);
+        assert!(channel.is_active(&contract_id).is_some());
+
+        release_signer_events(&channel, Some(receiver), registration);
+        assert!(channel.is_active(&contract_id).is_none());
+    }
+
+    #[test]
+    fn concurrent_coordinators_do_not_share_the_channel() {
+        let channel = StackerDBChannel::new();
+        let contract_id = NakamotoSigners::make_signers_db_contract_id(10, 0, false);
+
+        // two miner threads race to set up their coordinators: the first wins, and the second
+        //  is told to wait for it rather than silently taking its events
+        let (first_receiver, first) = register_signer_events(&channel, &[10], false, false)
+            .expect("The first coordinator could not register");
+        let refused = register_signer_events(&channel, &[10], false, false).unwrap_err();
+        assert!(matches!(
+            refused,
+            NakamotoNodeError::SigningFailure(SigningError::CoordinatorActive { generation: 0 })
+        ));
+        assert_eq!(
+            refused.signing_failure_action(),
+            SigningFailureAction::AwaitCoordinator(COORDINATOR_REGISTRATION_RETRY_INTERVAL)
+        );
+        assert!(check_registration(&channel, &first).is_ok());
+        assert!(channel.is_active(&contract_id).is_some());
+
+        // the second forces its way in once the first has held on for too long, and the first
+        //  stops on its next poll
+        let (receiver, second) = register_signer_events(&channel, &[10], false, true)
+            .expect("The forced coordinator could not register");
+        assert!(second.replaced_other);
+        let superseded = check_registration(&channel, &first).unwrap_err();
+        assert!(matches!(
+            superseded,
+            NakamotoNodeError::SigningFailure(SigningError::Superseded)
+        ));
+        assert_eq!(
+            superseded.signing_failure_action(),
+            SigningFailureAction::EndTenure
+        );
+        assert!(check_registration(&channel, &second).is_ok());
+
+        // the first one's release on drop leaves the second's receiver in place
+        release_signer_events(&channel, Some(first_receiver), first);
+        assert!(channel.is_active(&contract_id).is_some());
+        release_signer_events(&channel, Some(receiver), second);
+        assert!(channel.is_active(&contract_id).is_none());
+        assert!(register_signer_events(&channel, &[10], false, false).is_ok());
+    }
+
+    #[test]
+    fn signer_responses_are_read_back_when_events_stall() {
+        const REWARD_CYCLE: u64 = 10;
+        const SIGN_ID: u64 = 3;
+        const SIGN_ITER_ID: u64 = 7;
+
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id =
+            MessageSlotID::SignatureShareResponse.stacker_db_contract(false, REWARD_CYCLE);
+        let slots: Vec<_> = signers
+            .private_keys
+            .iter()
+            .map(|sk| {
+                let signer_addr = StacksAddress {
+                    version: 0,
+                    bytes: Hash160::from_node_public_key(&StacksPublicKey::from_private(sk)),
+                };
+                (signer_addr, 1)
+            })
+            .collect();
+        let mut stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        let tx = stackerdbs.tx_begin(StackerDBConfig::noop()).unwrap();
+        tx.create_stackerdb(&contract_id, &slots).unwrap();
+        tx.commit().unwrap();
+
+        // a response to an earlier proposal is already there when the round starts
+        let stale_share = signers.signature_share(0, SIGN_ID, SIGN_ITER_ID - 1);
+        let stale_chunk = signer_chunk(&signers.private_keys[0], 0, 1, &stale_share.message);
+        write_chunk(&mut stackerdbs, &contract_id, &stale_chunk);
+
+        // the channel stays open, but never delivers anything
+        let (sender, receiver) = channel();
+        let mut signer_events =
+            SignerEventSource::new(&[REWARD_CYCLE], false, &stackerdbs, Duration::ZERO);
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+
+        let coordinator_sk = StacksPrivateKey::new();
+        let coordinator_pk =
+            ecdsa::PublicKey::new(&Scalar::from(*coordinator_sk.as_slice())).unwrap();
+        let mut round = SignRound::new(
+            signers.weights(),
+            Sha512Trunc256Sum([0x01; 32]),
+            SIGN_ID,
+            SIGN_ITER_ID,
+            SignerSetParity::new(REWARD_CYCLE, None).unwrap(),
+            ConsensusHash([0x02; 20]),
+            None,
+            signers.wsts_public_keys(),
+            coordinator_pk,
+        );
+
+        let mut chunks = vec![];
+        for (signer, slot_version) in [(0, 2), (1, 1)] {
+            let share = signers.signature_share(signer, SIGN_ID, SIGN_ITER_ID);
+            let slot_id = u32::try_from(signer).unwrap();
+            let chunk = signer_chunk(
+                &signers.private_keys[signer],
+                slot_id,
+                slot_version,
+                &share.message,
+            );
+            write_chunk(&mut stackerdbs, &contract_id, &chunk);
+            chunks.push(chunk);
+        }
+
+        let event = signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .expect("Expected the signers' responses to be read from the StackerDB");
+        assert_eq!(event.contract_id, contract_id);
+        assert_eq!(event.modified_slots, chunks);
+        let Ok(SignerEvent::SignerMessages(signer_set, messages)) =
+            SignerEvent::<SignerMessage>::try_from(event)
+        else {
+            panic!("Expected signer messages");
+        };
+        assert_eq!(signer_set, signer_set_for_cycle(REWARD_CYCLE));
+        let update = round.process_signer_messages(signer_set, messages);
+        assert!(update
+            .events
+            .contains(&SignRoundEvent::ThresholdReached { accepted_weight: 7 }));
+
+        // nothing is read back twice
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+
+        // and chunks that were read back are dropped if the channel delivers them late
+        sender
+            .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                contract_id: contract_id.clone(),
+                modified_slots: chunks,
+            }))
+            .unwrap();
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn reproposed_signed_block_is_signed_by_the_replayed_signature() {
+        const REWARD_CYCLE: u64 = 10;
+        const SIGN_ID: u64 = 3;
+        // the round resumed after the one in which the signers signed the block
+        const SIGN_ITER_ID: u64 = 2;
+
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id = MessageSlotID::BlockResponse.stacker_db_contract(false, REWARD_CYCLE);
+        let slots: Vec<_> = signers
+            .private_keys
+            .iter()
+            .map(|sk| {
+                let signer_addr = StacksAddress {
+                    version: 0,
+                    bytes: Hash160::from_node_public_key(&StacksPublicKey::from_private(sk)),
+                };
+                (signer_addr, 1)
+            })
+            .collect();
+        let mut stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        let tx = stackerdbs.tx_begin(StackerDBConfig::noop()).unwrap();
+        tx.create_stackerdb(&contract_id, &slots).unwrap();
+        tx.commit().unwrap();
+
+        let mut block_signers = BlockSigners::default();
+        let mut block = NakamotoBlock {
+            header: NakamotoBlockHeader::empty(),
+            txs: vec![],
+        };
+        block_signers.sign_nakamoto_block(&mut block, 0);
+        let block_sighash = block.header.signer_signature_hash();
+        let signature = block.header.signer_signature.clone();
+        let mut other_block = NakamotoBlock {
+            header: NakamotoBlockHeader::empty(),
+            txs: vec![],
+        };
+        other_block.header.chain_length = 1;
+        block_signers.sign_nakamoto_block(&mut other_block, 0);
+
+        // the signers answer the re-proposal with the signature they cached, not with shares.
+        //  One of them publishes a signature that is not over the block.
+        let (_sender, receiver) = channel();
+        let mut signer_events =
+            SignerEventSource::new(&[REWARD_CYCLE], false, &stackerdbs, Duration::ZERO);
+        for (signer, signature) in [
+            (0, other_block.header.signer_signature.clone()),
+            (1, signature.clone()),
+        ] {
+            let response =
+                SignerMessage::BlockResponse(BlockResponse::Accepted((block_sighash, signature)));
+            let slot_id = u32::try_from(signer).unwrap();
+            let chunk = signer_chunk(&signers.private_keys[signer], slot_id, 1, &response);
+            write_chunk(&mut stackerdbs, &contract_id, &chunk);
+        }
+        let event = signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .expect("Expected the signers' responses to be read from the StackerDB");
+        let Ok(SignerEvent::SignerMessages(signer_set, messages)) =
+            SignerEvent::<SignerMessage>::try_from(event)
+        else {
+            panic!("Expected signer messages");
+        };
+
+        let coordinator_sk = StacksPrivateKey::new();
+        let coordinator_pk =
+            ecdsa::PublicKey::new(&Scalar::from(*coordinator_sk.as_slice())).unwrap();
+        let new_round = || {
+            SignRound::new(
+                signers.weights(),
+                block_sighash,
+                SIGN_ID,
+                SIGN_ITER_ID,
+                SignerSetParity::new(REWARD_CYCLE, None).unwrap(),
+                ConsensusHash([0x02; 20]),
+                None,
+                signers.wsts_public_keys(),
+                coordinator_pk,
+            )
+        };
+
+        // a coordinator that cannot verify the signature keeps waiting for shares
+        let mut round = new_round();
+        let update = round.process_signer_messages(signer_set, messages.clone());
+        assert!(update.events.is_empty());
+        assert!(round.replayed_signature().is_none());
+
+        // otherwise only the signature over the block signs it, without any shares
+        let mut round = new_round();
+        round.accept_replayed_signatures(block_signers.aggregate_public_key);
+        let update = round.process_signer_messages(signer_set, messages.clone());
+        assert_eq!(
+            update.events,
+            vec![SignRoundEvent::SignatureReplayed { slot_id: 1 }]
+        );
+        assert_eq!(round.replayed_signature(), Some(&signature));
+        assert_eq!(round.accepted_weight(), 0);
+
+        // and it is only reported once
+        let update = round.process_signer_messages(signer_set, messages);
+        assert!(update.events.is_empty());
+        assert_eq!(round.replayed_signature(), Some(&signature));
+    }
+
+    #[test]
+    fn redelivered_signer_chunks_keep_round_state_bounded() {
+        const REWARD_CYCLE: u64 = 10;
+        const SIGN_ID: u64 = 3;
+        const SIGN_ITER_ID: u64 = 7;
+        const EVENTS: usize = 10_000;
+
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id =
+            MessageSlotID::SignatureShareResponse.stacker_db_contract(false, REWARD_CYCLE);
+        let shares: Vec<_> = (0..signers.private_keys.len())
+            .map(|signer| {
+                let share = signers.signature_share(signer, SIGN_ID, SIGN_ITER_ID);
+                let slot_id = u32::try_from(signer).unwrap();
+                signer_chunk(&signers.private_keys[signer], slot_id, 1, &share.message)
+            })
+            .collect();
+
+        let stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        let (sender, receiver) = channel();
+        // the StackerDB is never read back; every chunk comes over the channel
+        let mut signer_events = SignerEventSource::new(
+            &[REWARD_CYCLE],
+            false,
+            &stackerdbs,
+            Duration::from_secs(3600),
+        );
+        let coordinator_sk = StacksPrivateKey::new();
+        let coordinator_pk =
+            ecdsa::PublicKey::new(&Scalar::from(*coordinator_sk.as_slice())).unwrap();
+        let mut round = SignRound::new(
+            signers.weights(),
+            Sha512Trunc256Sum([0x01; 32]),
+            SIGN_ID,
+            SIGN_ITER_ID,
+            SignerSetParity::new(REWARD_CYCLE, None).unwrap(),
+            ConsensusHash([0x02; 20]),
+            None,
+            signers.wsts_public_keys(),
+            coordinator_pk,
+        );
+
+        // each signer rewrites its slot over and over, and every version is delivered twice
+        let mut update = SignRoundUpdate::default();
+        let mut delivered = 0;
+        let mut thresholds_reached = 0;
+        for i in 0..EVENTS {
+            let mut chunk = shares[i % shares.len()].clone();
+            chunk.slot_version = u32::try_from(1 + i / (2 * shares.len())).unwrap();
+            sender
+                .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                    contract_id: contract_id.clone(),
+                    modified_slots: vec![chunk],
+                }))
+                .unwrap();
+            let Some(event) = signer_events.next_event(&receiver, &stackerdbs).unwrap() else {
+                continue;
+            };
+            delivered += 1;
+            let Ok(SignerEvent::SignerMessages(signer_set, messages)) =
+                SignerEvent::<SignerMessage>::try_from(event)
+            else {
+                panic!("Expected signer messages");
+            };
+            round.process_signer_messages_into(signer_set, messages, &mut update);
+            thresholds_reached += update
+                .events
+                .iter()
+                .filter(|event| matches!(event, SignRoundEvent::ThresholdReached { .. }))
+                .count();
+        }
+
+        // duplicates never reached deserialization
+        assert_eq!(delivered, EVENTS / 2);
+        // one watermark per slot, and nothing left queued
+        assert_eq!(signer_events.processed.len(), shares.len());
+        assert!(signer_events.pending.is_empty());
+        // the round saw the same thing as if each signer had responded once
+        assert_eq!(thresholds_reached, 1);
+        assert_eq!(round.accepted_weight(), 10);
+        assert_eq!(update.responding_slots.len(), 1);
+    }
+
+    #[test]
+    fn duplicate_signer_chunks_are_verified_once() {
+        const REWARD_CYCLE: u64 = 10;
+        const SIGN_ID: u64 = 3;
+        const SIGN_ITER_ID: u64 = 7;
+
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id =
+            MessageSlotID::SignatureShareResponse.stacker_db_contract(false, REWARD_CYCLE);
+        let share = signers.signature_share(0, SIGN_ID, SIGN_ITER_ID);
+        let chunk = signer_chunk(&signers.private_keys[0], 0, 1, &share.message);
+
+        let stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        let (sender, receiver) = channel();
+        let mut signer_events = SignerEventSource::new(
+            &[REWARD_CYCLE],
+            false,
+            &stackerdbs,
+            Duration::from_secs(3600),
+        );
+        let coordinator_sk = StacksPrivateKey::new();
+        let coordinator_pk =
+            ecdsa::PublicKey::new(&Scalar::from(*coordinator_sk.as_slice())).unwrap();
+        let mut round = SignRound::new(
+            signers.weights(),
+            Sha512Trunc256Sum([0x01; 32]),
+            SIGN_ID,
+            SIGN_ITER_ID,
+            SignerSetParity::new(REWARD_CYCLE, None).unwrap(),
+            ConsensusHash([0x02; 20]),
+            None,
+            signers.wsts_public_keys(),
+            coordinator_pk,
+        );
+
+        // two peers push the same version of the slot
+        for _ in 0..2 {
+            sender
+                .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                    contract_id: contract_id.clone(),
+                    modified_slots: vec![chunk.clone()],
+                }))
+                .unwrap();
+        }
+        let event = signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .expect("Expected the first delivery");
+        let Ok(SignerEvent::SignerMessages(signer_set, messages)) =
+            SignerEvent::<SignerMessage>::try_from(event)
+        else {
+            panic!("Expected signer messages");
+        };
+        round.process_signer_messages(signer_set, messages);
+        assert_eq!(signer_events.take_duplicate_chunks(), 0);
+
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+        assert_eq!(signer_events.take_duplicate_chunks(), 1);
+        assert_eq!(signer_events.take_duplicate_chunks(), 0);
+        assert_eq!(round.verified_packets(), 1);
+        assert_eq!(round.accepted_weight(), 4);
+    }
+
+    #[test]
+    fn oversized_block_responses_are_dropped_unread() {
+        const REWARD_CYCLE: u64 = 10;
+
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id = MessageSlotID::BlockResponse.stacker_db_contract(false, REWARD_CYCLE);
+        let accepted = SignerMessage::BlockResponse(BlockResponse::Accepted((
+            Sha512Trunc256Sum([0x01; 32]),
+            ThresholdSignature::empty(),
+        )));
+        let chunk = signer_chunk(&signers.private_keys[0], 0, 1, &accepted);
+        // the same acceptance, padded out to a whole chunk; it would otherwise parse
+        let mut padded_data = accepted.serialize_to_vec();
+        padded_data.resize(MAX_BLOCK_RESPONSE_LEN + 1, 0);
+        let mut padded = StackerDBChunkData::new(1, 1, padded_data);
+        padded.sign(&signers.private_keys[1]).unwrap();
+
+        let stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        let (sender, receiver) = channel();
+        let mut signer_events = SignerEventSource::new(
+            &[REWARD_CYCLE],
+            false,
+            &stackerdbs,
+            Duration::from_secs(3600),
+        );
+        sender
+            .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                contract_id: contract_id.clone(),
+                modified_slots: vec![padded.clone(), chunk.clone()],
+            }))
+            .unwrap();
+        let event = signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .expect("Expected the well-sized response");
+        assert_eq!(event.modified_slots, vec![chunk]);
+        assert_eq!(signer_events.take_oversized_chunks(), 1);
+        assert_eq!(signer_events.take_oversized_chunks(), 0);
+
+        // an event of nothing but oversized chunks yields nothing, and a redelivery of one is a
+        //  duplicate rather than parsed again
+        let mut padded_again = padded.clone();
+        padded_again.slot_id = 2;
+        padded_again.sign(&signers.private_keys[2]).unwrap();
+        for modified_slots in [vec![padded_again], vec![padded]] {
+            sender
+                .send(StackerDBChannelEvent::new(StackerDBChunksEvent {
+                    contract_id: contract_id.clone(),
+                    modified_slots,
+                }))
+                .unwrap();
+        }
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+        assert_eq!(signer_events.take_oversized_chunks(), 1);
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+        assert_eq!(signer_events.take_oversized_chunks(), 0);
+        assert_eq!(signer_events.take_duplicate_chunks(), 1);
+    }
+
+    #[test]
+    fn chunk_dequeue_delays_are_recorded() {
+        const REWARD_CYCLE: u64 = 10;
+
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id =
+            MessageSlotID::SignatureShareResponse.stacker_db_contract(false, REWARD_CYCLE);
+        let stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        let (sender, receiver) = channel();
+        let mut signer_events = SignerEventSource::new(
+            &[REWARD_CYCLE],
+            false,
+            &stackerdbs,
+            Duration::from_secs(3600),
+        );
+        let counters = Counters::new();
+        assert_eq!(counters.snapshot_naka().chunk_dequeue_delay_p50_us, None);
+        assert_eq!(counters.snapshot_naka().chunk_dequeue_delay_p95_us, None);
+
+        // nothing was dequeued, so nothing is recorded
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+        assert_eq!(signer_events.take_dequeue_delay(), None);
+
+        // the i-th event was dispatched i * 10ms before it is dequeued
+        for i in 1..=20u32 {
+            let share = signers.signature_share(0, 3, 7);
+            let chunk = signer_chunk(&signers.private_keys[0], 0, i, &share.message);
+            sender
+                .send(StackerDBChannelEvent {
+                    event: StackerDBChunksEvent {
+                        contract_id: contract_id.clone(),
+                        modified_slots: vec![chunk],
+                    },
+                    dispatched_at: Instant::now() - Duration::from_millis(u64::from(i) * 10),
+                })
+                .unwrap();
+            signer_events
+                .next_event(&receiver, &stackerdbs)
+                .unwrap()
+                .expect("Expected the signer's response");
+            let delay = signer_events
+                .take_dequeue_delay()
+                .expect("Expected the dequeue delay");
+            assert!(delay >= Duration::from_millis(u64::from(i) * 10));
+            assert_eq!(signer_events.take_dequeue_delay(), None);
+            counters.record_naka_chunk_dequeue_delay(delay);
+        }
+
+        let snapshot = counters.snapshot_naka();
+        let p50 = snapshot.chunk_dequeue_delay_p50_us.unwrap();
+        let p95 = snapshot.chunk_dequeue_delay_p95_us.unwrap();
+        assert!((100_000..110_000).contains(&p50), "p50 was {p50}us");
+        assert!((190_000..200_000).contains(&p95), "p95 was {p95}us");
+    }
+
+    #[test]
+    fn first_signer_db_read_is_limited_to_the_sampled_slots() {
+        const REWARD_CYCLE: u64 = 10;
+
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id =
+            MessageSlotID::SignatureShareResponse.stacker_db_contract(false, REWARD_CYCLE);
+        let slots: Vec<_> = signers
+            .private_keys
+            .iter()
+            .map(|sk| {
+                let signer_addr = StacksAddress {
+                    version: 0,
+                    bytes: Hash160::from_node_public_key(&StacksPublicKey::from_private(sk)),
+                };
+                (signer_addr, 1)
+            })
+            .collect();
+        let mut stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        let tx = stackerdbs.tx_begin(StackerDBConfig::noop()).unwrap();
+        tx.create_stackerdb(&contract_id, &slots).unwrap();
+        tx.commit().unwrap();
+
+        let (_sender, receiver) = channel();
+        let mut signer_events =
+            SignerEventSource::new(&[REWARD_CYCLE], false, &stackerdbs, Duration::ZERO)
+                .with_first_read_slots(&[SlotId(1)]);
+
+        let mut chunks = vec![];
+        for signer in [0, 1] {
+            let share = signers.signature_share(signer, 3, 7);
+            let slot_id = u32::try_from(signer).unwrap();
+            let chunk = signer_chunk(&signers.private_keys[signer], slot_id, 1, &share.message);
+            write_chunk(&mut stackerdbs, &contract_id, &chunk);
+            chunks.push(chunk);
+        }
+
+        // the sampled signer is polled first...
+        let event = signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .expect("Expected the sampled signer's response");
+        assert_eq!(event.modified_slots, vec![chunks[1].clone()]);
+        // ...and everyone else on the next read
+        let event = signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .expect("Expected the other signer's response");
+        assert_eq!(event.modified_slots, vec![chunks[0].clone()]);
+        assert!(signer_events
+            .next_event(&receiver, &stackerdbs)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn legacy_signer_sets_get_untagged_messages() {
+        const REWARD_CYCLE: u64 = 10;
+
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let contract_id = ProtocolCapabilities::stacker_db_contract(false, REWARD_CYCLE);
+        let slots: Vec<_> = signers
+            .private_keys
+            .iter()
+            .map(|sk| {
+                let signer_addr = StacksAddress {
+                    version: 0,
+                    bytes: Hash160::from_node_public_key(&StacksPublicKey::from_private(sk)),
+                };
+                (signer_addr, 1)
+            })
+            .collect();
+        let mut stackerdbs = StackerDBs::connect(":memory:", true).unwrap();
+        // without a replica of the capabilities contract, nobody has advertised
+        let message_tag =
+            check_protocol_support(false, &signers.entries, &stackerdbs, REWARD_CYCLE, 7).unwrap();
+        assert_eq!(message_tag, None);
+        let tx = stackerdbs.tx_begin(StackerDBConfig::noop()).unwrap();
+        tx.create_stackerdb(&contract_id, &slots).unwrap();
+        tx.commit().unwrap();
+
+        let message = SignerMessage::Transactions(vec![]);
+        let watermark = MinerWatermark::new(100, 0);
+        let seal = |message_tag: Option<ProtocolVersion>| {
+            let mut buf = vec![];
+            seal_miner_message(
+                &mut buf,
+                None,
+                message_tag.map(|version| (version, watermark)),
+                &message,
+            )
+            .unwrap();
+            buf
+        };
+        let advertise = |stackerdbs: &mut StackerDBs, signer: usize| {
+            let capabilities = ProtocolCapabilities::new(vec![ProtocolVersion::V1]);
+            let slot_id = u32::try_from(signer).unwrap();
+            let mut chunk = StackerDBChunkData::new(slot_id, 1, capabilities.serialize_to_vec());
+            chunk.sign(&signers.private_keys[signer]).unwrap();
+            write_chunk(stackerdbs, &contract_id, &chunk);
+        };
+
+        // nobody advertises, so every signer speaks the protocol, but may not read tags
+        let message_tag =
+            check_protocol_support(false, &signers.entries, &stackerdbs, REWARD_CYCLE, 7).unwrap();
+        assert_eq!(message_tag, None);
+        assert_eq!(seal(message_tag), message.serialize_to_vec());
+
+        // signers with 4 + 2 keys advertise, which is still short of the threshold
+        advertise(&mut stackerdbs, 0);
+        advertise(&mut stackerdbs, 2);
+        let message_tag =
+            check_protocol_support(false, &signers.entries, &stackerdbs, REWARD_CYCLE, 7).unwrap();
+        assert_eq!(message_tag, None);
+        assert_eq!(seal(message_tag), message.serialize_to_vec());
+
+        // with the signer holding 3 keys, signers holding the threshold read tagged messages
+        advertise(&mut stackerdbs, 1);
+        let message_tag =
+            check_protocol_support(false, &signers.entries, &stackerdbs, REWARD_CYCLE, 7).unwrap();
+        assert_eq!(message_tag, Some(ProtocolVersion::V1));
+        let sealed = seal(message_tag);
+        assert_eq!(
+            untag_message(&sealed).unwrap(),
+            (
+                ProtocolVersion::V1,
+                Some(watermark),
+                &message.serialize_to_vec()[..]
+            )
+        );
+    }
+
+    #[test]
+    fn sampled_signers_exceed_the_target_weight() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for _ in 0..100 {
+            let weights: Vec<u32> = (0..rng.gen_range(1..=30))
+                .map(|_| rng.gen_range(1..=20))
+                .collect();
+            let signer_set = TestSigners::new(&weights).weights();
+            let total_weight = signer_set.total_weight();
+            let target_weight = rng.gen_range(0..total_weight);
+            let seed = rng.gen();
+
+            let sample = sample_signers_by_weight(
+                &signer_set,
+                target_weight,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            let sampled_weight: u32 = sample.iter().map(|slot| signer_set.weight(*slot)).sum();
+            assert!(sampled_weight > target_weight);
+            // every slot is a signer's, and none repeats
+            assert!(sample.iter().all(|slot| signer_set.weight(*slot) > 0));
+            assert_eq!(sample.iter().collect::<HashSet<_>>().len(), sample.len());
+            // without the last signer drawn, the sample does not exceed the target
+            let last_weight = signer_set.weight(*sample.last().unwrap());
+            assert!(sampled_weight - last_weight <= target_weight);
+            // the same seed draws the same sample
+            assert_eq!(
+                sample,
+                sample_signers_by_weight(
+                    &signer_set,
+                    target_weight,
+                    &mut StdRng::seed_from_u64(seed)
+                )
+            );
+            // a target the whole set cannot exceed gets every signer
+            assert_eq!(
+                sample_signers_by_weight(&signer_set, total_weight, &mut rng).len(),
+                signer_set.num_signers()
+            );
+        }
+    }
+
+    #[test]
+    fn malformed_signing_key_is_left_out() {
+        // 20 keys in total, so the threshold is 14
+        let mut signers = TestSigners::new(&[3, 2, 4, 1, 2, 3, 2, 3]);
+        // not a point on the curve
+        signers.entries[2].signing_key = [0x02; 33];
+        signers.entries[2].signing_key[1..].copy_from_slice(&[0xff; 32]);
+
+        let params = NakamotoSigningParams::parse(false, &signers.reward_set()).unwrap();
+        assert_eq!(params.num_signers, 8);
+        assert_eq!(params.num_keys, 20);
+        assert_eq!(params.threshold, 14);
+        assert!(!params.signer_key_ids.contains_key(&2));
+        assert!(!params.signer_public_keys.contains_key(&2));
+        assert!(!params.wsts_public_keys.signers.contains_key(&2));
+        // the others keep their signer and key IDs
+        let key_ids: Vec<_> = params.signer_key_ids[&3].iter().copied().collect();
+        assert_eq!(key_ids, vec![10]);
+        assert_eq!(params.wsts_public_keys.key_ids.len(), 16);
+        assert!((6..10).all(|key_id| !params.wsts_public_keys.key_ids.contains_key(&key_id)));
+
+        let weights = SignerSetWeights::new(&signers.entries, params.threshold);
+        assert_eq!(weights.total_weight(), 16);
+        assert_eq!(weights.weight(SlotId(2)), 0);
+        assert_eq!(weights.weight(SlotId(3)), 1);
+        assert_eq!(
+            weights.slot_of_key(&StacksPublicKey::from_private(&signers.private_keys[7])),
+            Some(SlotId(7))
+        );
+
+        // without another 3 keys, the threshold is out of reach
+        signers.entries[7].signing_key = signers.entries[2].signing_key;
+        assert!(NakamotoSigningParams::parse(false, &signers.reward_set()).is_err());
+    }
+
+    #[test]
+    fn signing_params_cache_is_busted_by_changed_signer_entries() {
+        const REWARD_CYCLE: u64 = 10;
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let reward_set = signers.reward_set();
+        let mut cache = SigningParamsCache::new(Duration::from_secs(3600));
+
+        let params = cache
+            .get_or_parse(false, REWARD_CYCLE, &reward_set)
+            .unwrap();
+        let cached = cache
+            .get_or_parse(false, REWARD_CYCLE, &reward_set)
+            .unwrap();
+        assert!(Arc::ptr_eq(&params, &cached));
+        // other cycles and networks are parsed on their own
+        let next_cycle = cache
+            .get_or_parse(false, REWARD_CYCLE + 1, &reward_set)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&params, &next_cycle));
+        let mainnet = cache.get_or_parse(true, REWARD_CYCLE, &reward_set).unwrap();
+        assert!(!Arc::ptr_eq(&params, &mainnet));
+
+        // a changed weight busts the cycle's entry, and the new params replace it
+        let mut changed = signers.entries.clone();
+        changed[3].weight = 5;
+        let mut changed_reward_set = reward_set.clone();
+        changed_reward_set.signers = Some(changed);
+        let reparsed = cache
+            .get_or_parse(false, REWARD_CYCLE, &changed_reward_set)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&params, &reparsed));
+        assert_eq!(reparsed.num_keys, 14);
+        let cached = cache
+            .get_or_parse(false, REWARD_CYCLE, &changed_reward_set)
+            .unwrap();
+        assert!(Arc::ptr_eq(&reparsed, &cached));
+
+        // a reward set that fails to parse is not cached
+        let mut bad_reward_set = reward_set.clone();
+        bad_reward_set.signers = None;
+        assert!(cache
+            .get_or_parse(false, REWARD_CYCLE + 2, &bad_reward_set)
+            .is_err());
+
+        // expired entries are parsed again
+        let mut cache = SigningParamsCache::new(Duration::ZERO);
+        let params = cache
+            .get_or_parse(false, REWARD_CYCLE, &reward_set)
+            .unwrap();
+        let reparsed = cache
+            .get_or_parse(false, REWARD_CYCLE, &reward_set)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&params, &reparsed));
+    }
+
+    #[test]
+    fn signer_entries_hash_covers_every_field() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let hash = signer_entries_hash(&signers.entries);
+        assert_eq!(hash, signer_entries_hash(&signers.entries.clone()));
+
+        let mut changed = signers.entries.clone();
+        changed[0].signing_key[1] ^= 0x01;
+        assert_ne!(signer_entries_hash(&changed), hash);
+
+        let mut changed = signers.entries.clone();
+        changed[1].stacked_amt += 1;
+        assert_ne!(signer_entries_hash(&changed), hash);
+
+        let mut changed = signers.entries.clone();
+        changed[2].weight += 1;
+        assert_ne!(signer_entries_hash(&changed), hash);
+
+        // order matters, since it assigns the signer ids
+        let mut changed = signers.entries.clone();
+        changed.swap(0, 1);
+        assert_ne!(signer_entries_hash(&changed), hash);
+
+        assert_ne!(signer_entries_hash(&signers.entries[..3]), hash);
+    }
+
+    #[test]
+    fn miners_and_signers_agree_on_the_signing_threshold() {
+        for weights in [
+            vec![1; 10],
+            vec![2, 2, 2],
+            vec![100, 1, 1, 1],
+            vec![70, 30],
+            vec![70, 31],
+            vec![7, 3, 11, 1, 9],
+        ] {
+            let signers = TestSigners::new(&weights);
+            let reward_set = signers.reward_set();
+            let (threshold, total_weight) = signing_threshold_for(&reward_set).unwrap();
+            let params = NakamotoSigningParams::parse(false, &reward_set).unwrap();
+            assert_eq!(
+                (params.threshold, params.num_keys),
+                (threshold, total_weight)
+            );
+            // what the signers compute from the same reward set
+            let entries = SignerEntries::parse(false, &signers.entries).unwrap();
+            assert_eq!(entries.count_keys().unwrap(), total_weight, "{weights:?}");
+            assert_eq!(
+                entries.get_signing_threshold().unwrap(),
+                threshold,
+                "{weights:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reward_set_weights_above_u32_max_are_rejected() {
+        // checked before the keys are parsed, so WSTS never sees this many key IDs
+        let signers = TestSigners::new(&[u32::MAX - 1, 2]);
+        assert!(NakamotoSigningParams::parse(false, &signers.reward_set()).is_err());
+        let signers = TestSigners::new(&[u32::MAX / 2 + 1, u32::MAX / 2 + 1]);
+        assert!(NakamotoSigningParams::parse(false, &signers.reward_set()).is_err());
+    }
+
+    /// A block whose only transaction is a tenure change with `cause`
+    fn tenure_change_block(cause: TenureChangeCause) -> NakamotoBlock {
+        let mut header = NakamotoBlockHeader::empty();
+        header.consensus_hash = ConsensusHash([0x01; 20]);
+        header.parent_block_id = StacksBlockId([0x02; 32]);
+        let payload = TenureChangePayload {
+            tenure_consensus_hash: header.consensus_hash.clone(),
+            prev_tenure_consensus_hash: header.consensus_hash.clone(),
+            burn_view_consensus_hash: ConsensusHash([0x03; 20]),
+            previous_tenure_end: header.parent_block_id.clone(),
+            previous_tenure_blocks: 4,
+            cause,
+            pubkey_hash: Hash160([0x05; 20]),
+        };
+        let tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&StacksPrivateKey::new()).unwrap(),
+            TransactionPayload::TenureChange(payload),
+        );
+        NakamotoBlock {
+            header,
+            txs: vec![tx],
+        }
+    }
+
+    #[test]
+    fn tenure_extend_block_is_proposed_as_one() {
+        let block = tenure_change_block(TenureChangeCause::Extended);
+        let tenure_extend = tenure_extend_of(&block).expect("Expected a tenure extension");
+        assert_eq!(
+            tenure_extend.tenure_consensus_hash,
+            block.header.consensus_hash
+        );
+        assert_eq!(
+            tenure_extend.burn_view_consensus_hash,
+            ConsensusHash([0x03; 20])
+        );
+        assert_eq!(
+            tenure_extend.previous_tenure_end,
+            block.header.parent_block_id
+        );
+        assert_eq!(tenure_extend.previous_tenure_blocks, 4);
+
+        // the signers read the extension back out of the proposal
+        let proposal = BlockProposal {
+            block: block.clone(),
+            burn_height: 7,
+            reward_cycle: 1,
+            miner_view: Some(TenureView {
+                burn_view_consensus_hash: ConsensusHash([0x03; 20]),
+                parent_tenure_id: Some(StacksBlockId([0x06; 32])),
+                tenure_change_cause: Some(TenureChangeCause::Extended),
+            }),
+            tenure_extend: Some(tenure_extend.clone()),
+            attempt: Some(0),
+        };
+        let bytes = proposal.serialize_to_vec();
+        let decoded = read_next::<BlockProposal, _>(&mut &bytes[..]).unwrap();
+        assert_eq!(decoded.tenure_extend, Some(tenure_extend));
+        assert_eq!(decoded.block, block);
+
+        // a block that does not extend a tenure is not proposed as an extension
+        let block = tenure_change_block(TenureChangeCause::BlockFound);
+        assert_eq!(tenure_extend_of(&block), None);
+    }
+
+    #[test]
+    fn signed_block_results_record_their_provenance() {
+        let mut block = NakamotoBlock {
+            header: NakamotoBlockHeader::empty(),
+            txs: vec![],
+        };
+        BlockSigners::default().sign_nakamoto_block(&mut block, 0);
+        let signature = block.header.signer_signature.clone();
+
+        let signed = SignedBlockResult::from_signers(signature.clone(), vec![]);
+        assert_eq!(signed.provenance(), SignatureProvenance::RealSigners);
+        assert_eq!(signed.into_broadcast_signature().unwrap(), signature);
+
+        let signed = SignedBlockResult::from_staged_block(&block);
+        assert_eq!(signed.provenance(), SignatureProvenance::StagedBlock);
+        assert_eq!(signed.signature(), &signature);
+        assert_eq!(signed.into_broadcast_signature().unwrap(), signature);
+
+        // test builds broadcast whatever the test signing channel hands out
+        let signed = SignedBlockResult::from_test_channel(ThresholdSignature::empty());
+        assert_eq!(signed.provenance(), SignatureProvenance::TestChannel);
+        assert_eq!(
+            signed.into_broadcast_signature().unwrap(),
+            ThresholdSignature::empty()
+        );
+    }
+
+    #[test]
+    fn empty_signatures_are_never_broadcast() {
+        let unsigned_block = NakamotoBlock {
+            header: NakamotoBlockHeader::empty(),
+            txs: vec![],
+        };
+        for signed in [
+            SignedBlockResult::from_signers(ThresholdSignature::empty(), vec![]),
+            SignedBlockResult::from_staged_block(&unsigned_block),
+        ] {
+            assert!(matches!(
+                signed.into_broadcast_signature(),
+                Err(NakamotoNodeError::SigningFailure(
+                    SigningError::InvalidSignature(_)
+                ))
+            ));
+        }
+    }
+
+    /// A testnet tenure extension block, with `txs` after its tenure change and a header that
+    /// commits to all of them
+    fn preflight_block(txs: Vec<StacksTransaction>) -> NakamotoBlock {
+        let mut block = tenure_change_block(TenureChangeCause::Extended);
+        block.txs.extend(txs);
+        for tx in block.txs.iter_mut() {
+            tx.chain_id = CHAIN_ID_TESTNET;
+        }
+        let txids = block
+            .txs
+            .iter()
+            .map(|tx| tx.txid().as_bytes().to_vec())
+            .collect();
+        block.header.tx_merkle_root = MerkleTree::new(&txids).root();
+        block
+    }
+
+    #[test]
+    fn preflight_accepts_a_well_formed_block() {
+        let block = preflight_block(vec![]);
+        assert_eq!(
+            preflight_checks(&block, false, CHAIN_ID_TESTNET, StacksEpochId::Epoch30),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn preflight_rejects_oversized_blocks() {
+        let max_size = u64::from(MAX_BLOCK_LEN);
+        let code_body = "a".repeat(usize::try_from(MAX_BLOCK_LEN).unwrap());
+        let contract_tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&StacksPrivateKey::new()).unwrap(),
+            TransactionPayload::SmartContract(
+                TransactionSmartContract {
+                    name: "too-big".into(),
+                    code_body: StacksString::from_str(&code_body).unwrap(),
+                },
+                None,
+            ),
+        );
+        let block = preflight_block(vec![contract_tx]);
+        let size = u64::try_from(block.serialize_to_vec().len()).unwrap();
+        assert!(size > max_size);
+        assert_eq!(
+            preflight_checks(&block, false, CHAIN_ID_TESTNET, StacksEpochId::Epoch30),
+            Err(ProposalPreflightError::TooLarge { size, max_size })
+        );
+    }
+
+    #[test]
+    fn preflight_rejects_a_bad_tx_merkle_root() {
+        let mut block = preflight_block(vec![]);
+        let computed_root = block.header.tx_merkle_root.clone();
+        block.header.tx_merkle_root = Sha512Trunc256Sum([0x07; 32]);
+        assert_eq!(
+            preflight_checks(&block, false, CHAIN_ID_TESTNET, StacksEpochId::Epoch30),
+            Err(ProposalPreflightError::TxMerkleRootMismatch {
+                header_root: Sha512Trunc256Sum([0x07; 32]),
+                computed_root,
+            })
+        );
+    }
+
+    #[test]
+    fn preflight_rejects_invalid_transactions() {
+        let block = preflight_block(vec![]);
+        // tenure changes are not allowed before Stacks 3.0
+        assert_eq!(
+            preflight_checks(&block, false, CHAIN_ID_TESTNET, StacksEpochId::Epoch25),
+            Err(ProposalPreflightError::InvalidTransactions {
+                epoch_id: StacksEpochId::Epoch25
+            })
+        );
+        // testnet transactions cannot be mined on mainnet
+        assert_eq!(
+            preflight_checks(&block, true, CHAIN_ID_TESTNET, StacksEpochId::Epoch30),
+            Err(ProposalPreflightError::InvalidTransactions {
+                epoch_id: StacksEpochId::Epoch30
+            })
+        );
+        // nor for another chain
+        assert_eq!(
+            preflight_checks(&block, false, CHAIN_ID_TESTNET + 1, StacksEpochId::Epoch30),
+            Err(ProposalPreflightError::InvalidTransactions {
+                epoch_id: StacksEpochId::Epoch30
+            })
+        );
+    }
+
+    #[test]
+    fn tenures_missing_from_the_stacks_tip_are_counted() {
+        // the burn heights and winners of the sortitions before the tenure being mined, at 110
+        let ancestors =
+            |winners: &[(u64, bool)]| winners.to_vec().into_iter().map(Ok::<_, NakamotoNodeError>);
+        let chain = [
+            (109, false),
+            (108, true),
+            (107, false),
+            (106, true),
+            (105, true),
+            (104, true),
+        ];
+
+        // a stacks tip in the parent tenure, after empty sortitions, misses nothing
+        assert_eq!(missed_tenures(ancestors(&chain), 108, 1).unwrap(), 0);
+        assert_eq!(missed_tenures(ancestors(&chain), 109, 1).unwrap(), 0);
+        assert_eq!(missed_tenures(ancestors(&chain), 110, 1).unwrap(), 0);
+        // nor does one whose sortitions were empty since
+        assert_eq!(missed_tenures(ancestors(&chain[..1]), 100, 1).unwrap(), 0);
+        assert_eq!(missed_tenures(ancestors(&chain), 106, 1).unwrap(), 1);
+        // counting stops once the node is known to be lagging
+        assert_eq!(missed_tenures(ancestors(&chain), 100, 1).unwrap(), 2);
+        assert_eq!(missed_tenures(ancestors(&chain), 100, 10).unwrap(), 4);
+        assert_eq!(missed_tenures(ancestors(&chain), 104, 0).unwrap(), 1);
+
+        // a sortition that cannot be read fails the check
+        let failing = ancestors(&chain[..2]).chain(std::iter::once(Err(
+            NakamotoNodeError::UnexpectedChainState,
+        )));
+        assert!(missed_tenures(failing, 100, 1).is_err());
+    }
+
+    #[test]
+    fn signing_failures_map_to_miner_actions() {
+        let backoff = Duration::from_secs(5);
+        let cases = [
+            (
+                NakamotoNodeError::SigningFailure(SigningError::StackerDbWriteFailed {
+                    reason: "slot version is stale".into(),
+                }),
+                SigningFailureAction::RetryNow,
+            ),
+            (
+                NakamotoNodeError::SignersConnectivityIssues {
+                    reason: SigningError::Timeout,
+                    reproposal_backoff: backoff,
+                },
+                SigningFailureAction::ReproposeAfter(backoff),
+            ),
+            (
+                NakamotoNodeError::ProposalPreflightFailed(
+                    ProposalPreflightError::InvalidTransactions {
+                        epoch_id: StacksEpochId::Epoch30,
+                    },
+                ),
+                SigningFailureAction::MineAnother,
+            ),
+            (
+                NakamotoNodeError::NodeNotSynced {
+                    stacks_tip: ConsensusHash([0x01; 20]),
+                    burn_tip: ConsensusHash([0x02; 20]),
+                },
+                SigningFailureAction::AwaitSync(NODE_SYNC_RETRY_INTERVAL),
+            ),
+            (
+                NakamotoNodeError::SigningFailure(SigningError::Timeout),
+                SigningFailureAction::EndTenure,
+            ),
+            (
+                NakamotoNodeError::SigningFailure(SigningError::Rejected { weight: 3 }),
+                SigningFailureAction::EndTenure,
+            ),
+            (
+                NakamotoNodeError::SigningFailure(SigningError::ChannelDisconnected),
+                SigningFailureAction::EndTenure,
+            ),
+            (
+                NakamotoNodeError::SigningFailure(SigningError::InvalidSignature(
+                    "Signature failed to validate over the expected block".into(),
+                )),
+                SigningFailureAction::EndTenure,
+            ),
+            (
+                NakamotoNodeError::BurnchainTipChanged {
+                    old_consensus_hash: ConsensusHash([0x01; 20]),
+                    new_consensus_hash: ConsensusHash([0x02; 20]),
+                },
+                SigningFailureAction::EndTenure,
+            ),
+            (
+                NakamotoNodeError::NotWinningMiner {
+                    consensus_hash: ConsensusHash([0x01; 20]),
+                    winner_key_hash: None,
+                    miner_key_hash: Hash160([0x03; 20]),
+                },
+                SigningFailureAction::EndTenure,
+            ),
+        ];
+        for (error, action) in cases {
+            assert_eq!(error.signing_failure_action(), action, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn signing_errors_keep_their_log_messages() {
+        assert_eq!(
+            SigningError::Timeout.to_string(),
+            "Timed out waiting for group signature"
+        );
+        assert_eq!(
+            SigningError::Rejected { weight: 7 }.to_string(),
+            "Signers with weight 7 rejected the block"
+        );
+        assert_eq!(
+            SigningError::ChannelDisconnected.to_string(),
+            "StackerDB event receiver disconnected"
+        );
+        assert_eq!(
+            SigningError::CoordinatorActive { generation: 4 }.to_string(),
+            "Sign coordinator of generation 4 still holds the StackerDB channel"
+        );
+        assert_eq!(
+            SigningError::Superseded.to_string(),
+            "A newer sign coordinator took over the StackerDB channel"
+        );
+        assert_eq!(
+            SigningError::StackerDbWriteFailed {
+                reason: "slot version is stale".into()
+            }
+            .to_string(),
+            "slot version is stale"
+        );
+    }
+
+    #[test]
+    fn signing_subsystem_info_reports_the_signer_set() {
+        const REWARD_CYCLE: u64 = 10;
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let miners_contract_id = boot_code_id(MINERS_NAME, false);
+        let coordinator_config = SignCoordinatorConfig::from_config(&Config::default()).unwrap();
+        let info = SigningSubsystemInfo::new(
+            REWARD_CYCLE,
+            &signers.weights(),
+            &miners_contract_id,
+            Some(&(4..6)),
+            None,
+            None,
+            &coordinator_config,
+        );
+        assert_eq!(
+            info,
+            SigningSubsystemInfo {
+                message_version: "v1".into(),
+                protocol_version: "v1".into(),
+                reward_cycle: REWARD_CYCLE,
+                num_signers: 4,
+                total_weight: 10,
+                threshold: 7,
+                miners_contract_id: miners_contract_id.to_string(),
+                slot_start: Some(4),
+                slot_end: Some(6),
+                compatibility_shims: vec![],
+                coordinator_config: coordinator_config.clone(),
+            }
+        );
+
+        // event observers get every field
+        let payload = serde_json::to_value(&info).unwrap();
+        for key in [
+            "message_version",
+            "protocol_version",
+            "reward_cycle",
+            "num_signers",
+            "total_weight",
+            "threshold",
+            "miners_contract_id",
+            "slot_start",
+            "slot_end",
+            "compatibility_shims",
+            "coordinator_config",
+        ] {
+            assert!(
+                payload.get(key).is_some(),
+                "Payload is missing the {key} field"
+            );
+        }
+        assert_eq!(
+            payload["miners_contract_id"],
+            "ST000000000000000000002AMW42H.miners"
+        );
+        assert_eq!(
+            payload["coordinator_config"]["wait_on_signers_ms"],
+            coordinator_config.signing_round_timeout.as_millis() as u64
+        );
+        assert_eq!(
+            serde_json::from_value::<SigningSubsystemInfo>(payload).unwrap(),
+            info
+        );
+
+        // a miner without slots, enveloping its payloads for a set near the cycle boundary
+        let info = SigningSubsystemInfo::new(
+            REWARD_CYCLE,
+            &signers.weights(),
+            &miners_contract_id,
+            None,
+            Some(&EnvelopeConfig::default()),
+            Some(REWARD_CYCLE + 1),
+            &coordinator_config,
+        );
+        assert_eq!((info.slot_start, info.slot_end), (None, None));
+        assert_eq!(
+            info.compatibility_shims,
+            vec!["payload_envelope", "signer_set_boundary:11"]
+        );
     }
 }
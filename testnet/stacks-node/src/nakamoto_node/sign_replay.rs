@@ -0,0 +1,454 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline replay of a signing round. [`replay_sign_round`] feeds a dump of the StackerDB
+//! events a miner received through the same [`SignRound`] accounting the
+//! [`SignCoordinator`](super::sign_coordinator::SignCoordinator) uses, so that a round that went
+//! wrong can be stepped through after the fact. Backs the `replay-sign-round` subcommand.
+
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use libsigner::v1::messages::SignerMessage;
+use libsigner::SignerEvent;
+use stacks::chainstate::burn::ConsensusHash;
+use stacks::chainstate::stacks::boot::{RewardSet, SIGNERS_NAME};
+use stacks::chainstate::stacks::events::StackerDBChunksEvent;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use wsts::curve::ecdsa;
+
+use super::sign_coordinator::NakamotoSigningParams;
+use super::sign_round::{SignRound, SignRoundEvent, SignerSetParity, SignerSetWeights};
+
+/// One StackerDB event as captured by the miner, and when it arrived
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// When the event arrived, in milliseconds since the epoch
+    pub timestamp_ms: u64,
+    /// The event itself
+    pub event: StackerDBChunksEvent,
+}
+
+/// The stacker set to replay against, either as returned by `/v2/stacker_set/<cycle>` or as
+/// a bare reward set
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum StackerSetJson {
+    Response { stacker_set: RewardSet },
+    RewardSet(RewardSet),
+}
+
+/// The signing round to replay: what the miner proposed, and to whom
+#[derive(Debug, Clone)]
+pub struct ReplayParams {
+    pub is_mainnet: bool,
+    pub reward_cycle: u64,
+    /// The adjacent reward cycle whose signers' responses were also accepted, if any
+    pub boundary_reward_cycle: Option<u64>,
+    pub block_sighash: Sha512Trunc256Sum,
+    pub sign_id: u64,
+    pub sign_iter_id: u64,
+    /// The burnchain view the miner proposed the block with
+    pub miner_burn_view: ConsensusHash,
+    /// Consensus hash of the sortition that elected the block's tenure, if known
+    pub election_consensus_hash: Option<ConsensusHash>,
+    /// The public key of the miner's coordinator message key
+    pub coordinator_pk: ecdsa::PublicKey,
+}
+
+/// What one replayed event did to the round
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReplayStep {
+    pub timestamp_ms: u64,
+    /// The signer set that wrote the event, if it was a signer event at all
+    pub signer_set: Option<u32>,
+    /// Slots of the signers whose messages were in the event
+    pub responding_slots: Vec<u32>,
+    pub events: Vec<SignRoundEvent>,
+    pub accepted_weight: u32,
+    pub rejected_weight: u32,
+}
+
+/// How the replayed round ended
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ReplayOutcome {
+    /// Signers holding the threshold weight sent signature shares
+    ThresholdReached {
+        accepted_weight: u32,
+        timestamp_ms: u64,
+    },
+    /// Too much weight rejected the block for it to be signed
+    Rejected {
+        rejected_weight: u32,
+        timestamp_ms: u64,
+    },
+    /// The dump ran out before the round was decided
+    Undecided {
+        accepted_weight: u32,
+        rejected_weight: u32,
+    },
+}
+
+/// Read a dump of StackerDB events: either a JSONL file of [`ReplayEvent`]s, or a directory
+/// of them, read in file name order. Events are returned in the order they arrived.
+pub fn load_replay_events(path: &Path) -> Result<Vec<ReplayEvent>, String> {
+    let files = if path.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|file| file.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        files.sort();
+        files
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let mut events = vec![];
+    for file in files {
+        let reader = BufReader::new(
+            fs::File::open(&file).map_err(|e| format!("Failed to open {}: {e}", file.display()))?,
+        );
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event = serde_json::from_str(&line)
+                .map_err(|e| format!("{}:{}: malformed event: {e}", file.display(), line_no + 1))?;
+            events.push(event);
+        }
+    }
+    // stable, so events captured in the same millisecond keep their order
+    events.sort_by_key(|event: &ReplayEvent| event.timestamp_ms);
+    Ok(events)
+}
+
+/// Read the stacker set JSON at `path`
+pub fn load_reward_set(path: &Path) -> Result<RewardSet, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    match serde_json::from_str(&contents) {
+        Ok(StackerSetJson::Response { stacker_set }) => Ok(stacker_set),
+        Ok(StackerSetJson::RewardSet(reward_set)) => Ok(reward_set),
+        Err(e) => Err(format!("{}: malformed stacker set: {e}", path.display())),
+    }
+}
+
+/// Replay `events` through a signing round over `reward_set`, handing each step to
+/// `on_step`. Stops as soon as the round is decided, like the coordinator does.
+pub fn replay_sign_round(
+    params: &ReplayParams,
+    reward_set: &RewardSet,
+    events: Vec<ReplayEvent>,
+    mut on_step: impl FnMut(&ReplayStep),
+) -> Result<ReplayOutcome, String> {
+    let signers = reward_set
+        .signers
+        .as_ref()
+        .ok_or("The stacker set has no signers. It is not a Nakamoto reward set.")?;
+    let signing_params = NakamotoSigningParams::parse(params.is_mainnet, reward_set)
+        .map_err(|e| format!("Failed to parse the stacker set: {e:?}"))?;
+    let mut round = SignRound::new(
+        SignerSetWeights::new(signers, signing_params.threshold),
+        params.block_sighash.clone(),
+        params.sign_id,
+        params.sign_iter_id,
+        SignerSetParity::new(params.reward_cycle, params.boundary_reward_cycle)?,
+        params.miner_burn_view.clone(),
+        params.election_consensus_hash.clone(),
+        signing_params.wsts_public_keys,
+        params.coordinator_pk,
+    );
+
+    for ReplayEvent {
+        timestamp_ms,
+        event,
+    } in events
+    {
+        let mut step = ReplayStep {
+            timestamp_ms,
+            signer_set: None,
+            responding_slots: vec![],
+            events: vec![],
+            accepted_weight: round.accepted_weight(),
+            rejected_weight: round.rejections().rejected_weight(),
+        };
+        // the same filtering the coordinator applies to its StackerDB events
+        let is_signer_event =
+            event.contract_id.name.starts_with(SIGNERS_NAME) && event.contract_id.is_boot();
+        if is_signer_event {
+            if let Ok(SignerEvent::SignerMessages(signer_set, messages)) =
+                SignerEvent::<SignerMessage>::try_from(event)
+            {
+                let update = round.process_signer_messages(signer_set, messages);
+                step.signer_set = Some(signer_set);
+                step.responding_slots = update.responding_slots;
+                step.events = update.events;
+                step.accepted_weight = round.accepted_weight();
+                step.rejected_weight = round.rejections().rejected_weight();
+            }
+        }
+        on_step(&step);
+
+        for round_event in step.events.iter() {
+            match round_event {
+                SignRoundEvent::ThresholdReached { accepted_weight } => {
+                    return Ok(ReplayOutcome::ThresholdReached {
+                        accepted_weight: *accepted_weight,
+                        timestamp_ms,
+                    });
+                }
+                SignRoundEvent::Rejected { rejected_weight } => {
+                    return Ok(ReplayOutcome::Rejected {
+                        rejected_weight: *rejected_weight,
+                        timestamp_ms,
+                    });
+                }
+                SignRoundEvent::SignerSetMismatch { .. }
+                | SignRoundEvent::WeightUpdated { .. }
+                | SignRoundEvent::Equivocation { .. }
+                | SignRoundEvent::ElectionMismatch { .. }
+                | SignRoundEvent::Ignored { .. }
+                | SignRoundEvent::SignatureReplayed { .. } => {}
+            }
+        }
+    }
+    Ok(ReplayOutcome::Undecided {
+        accepted_weight: round.accepted_weight(),
+        rejected_weight: round.rejections().rejected_weight(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use libsigner::v1::messages::{MessageSlotID, RejectCode};
+    use libsigner::SignerSlotMessage;
+    use stacks::libstackerdb::StackerDBChunkData;
+    use stacks_common::codec::StacksMessageCodec;
+    use stacks_common::types::chainstate::StacksPrivateKey;
+    use wsts::curve::scalar::Scalar;
+
+    use super::*;
+    use crate::nakamoto_node::sign_round::testing::TestSigners;
+
+    const REWARD_CYCLE: u64 = 10;
+    const SIGN_ID: u64 = 3;
+    const SIGN_ITER_ID: u64 = 7;
+
+    fn replay_params() -> ReplayParams {
+        let coordinator_sk = StacksPrivateKey::new();
+        ReplayParams {
+            is_mainnet: false,
+            reward_cycle: REWARD_CYCLE,
+            boundary_reward_cycle: None,
+            block_sighash: Sha512Trunc256Sum([0x01; 32]),
+            sign_id: SIGN_ID,
+            sign_iter_id: SIGN_ITER_ID,
+            miner_burn_view: ConsensusHash([0x02; 20]),
+            election_consensus_hash: None,
+            coordinator_pk: ecdsa::PublicKey::new(&Scalar::from(*coordinator_sk.as_slice()))
+                .unwrap(),
+        }
+    }
+
+    /// The event the miner receives when `signer` writes `message` to the `msg_id` contract
+    fn replay_event(
+        signers: &TestSigners,
+        signer: usize,
+        msg_id: MessageSlotID,
+        message: SignerSlotMessage<SignerMessage>,
+        timestamp_ms: u64,
+    ) -> ReplayEvent {
+        let mut chunk = StackerDBChunkData::new(
+            message.slot_id,
+            message.slot_version,
+            message.message.serialize_to_vec(),
+        );
+        chunk.sign(&signers.private_keys[signer]).unwrap();
+        ReplayEvent {
+            timestamp_ms,
+            event: StackerDBChunksEvent {
+                contract_id: msg_id.stacker_db_contract(false, REWARD_CYCLE),
+                modified_slots: vec![chunk],
+            },
+        }
+    }
+
+    /// Capture `events` to a JSONL dump, as a miner would, and read them back
+    fn capture_and_load(events: &[ReplayEvent]) -> Vec<ReplayEvent> {
+        let path =
+            std::env::temp_dir().join(format!("sign-round-replay-{}.jsonl", rand::random::<u64>()));
+        let mut file = fs::File::create(&path).unwrap();
+        for event in events {
+            writeln!(file, "{}", serde_json::to_string(event).unwrap()).unwrap();
+        }
+        let loaded = load_replay_events(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn replay_events_round_trip_through_json() {
+        let signers = TestSigners::new(&[1]);
+        let event = replay_event(
+            &signers,
+            0,
+            MessageSlotID::SignatureShareResponse,
+            signers.signature_share(0, SIGN_ID, SIGN_ITER_ID),
+            1_700_000_000_000,
+        );
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<ReplayEvent>(&json).unwrap(), event);
+    }
+
+    #[test]
+    fn replay_reaches_the_threshold() {
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let params = replay_params();
+        // captured out of order: the dump is sorted by arrival time
+        let captured = vec![
+            replay_event(
+                &signers,
+                1,
+                MessageSlotID::SignatureShareResponse,
+                signers.signature_share(1, SIGN_ID, SIGN_ITER_ID),
+                300,
+            ),
+            replay_event(
+                &signers,
+                3,
+                MessageSlotID::BlockResponse,
+                signers.rejection(3, 3, &params.block_sighash, RejectCode::ConnectivityIssues),
+                100,
+            ),
+            replay_event(
+                &signers,
+                0,
+                MessageSlotID::SignatureShareResponse,
+                signers.signature_share(0, SIGN_ID, SIGN_ITER_ID),
+                200,
+            ),
+            // arrives after the round is decided, so it is never replayed
+            replay_event(
+                &signers,
+                2,
+                MessageSlotID::SignatureShareResponse,
+                signers.signature_share(2, SIGN_ID, SIGN_ITER_ID),
+                400,
+            ),
+        ];
+        let events = capture_and_load(&captured);
+
+        let mut steps = vec![];
+        let outcome = replay_sign_round(&params, &signers.reward_set(), events, |step| {
+            steps.push(step.clone())
+        })
+        .unwrap();
+        assert_eq!(
+            outcome,
+            ReplayOutcome::ThresholdReached {
+                accepted_weight: 7,
+                timestamp_ms: 300
+            }
+        );
+        let timeline: Vec<_> = steps
+            .iter()
+            .map(|step| {
+                (
+                    step.timestamp_ms,
+                    step.accepted_weight,
+                    step.rejected_weight,
+                )
+            })
+            .collect();
+        assert_eq!(timeline, [(100, 0, 1), (200, 4, 1), (300, 7, 1)]);
+        assert_eq!(steps[0].signer_set, Some(0));
+        assert_eq!(steps[0].responding_slots, [3]);
+    }
+
+    #[test]
+    fn replay_reports_rejected_and_undecided_rounds() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let params = replay_params();
+        let rejection = |signer: usize, timestamp_ms: u64| {
+            let slot_id = u32::try_from(signer).unwrap();
+            replay_event(
+                &signers,
+                signer,
+                MessageSlotID::BlockResponse,
+                signers.rejection(
+                    signer,
+                    slot_id,
+                    &params.block_sighash,
+                    RejectCode::ConnectivityIssues,
+                ),
+                timestamp_ms,
+            )
+        };
+
+        // more than 10 - 7 = 3 rejected keys put the threshold out of reach
+        let outcome = replay_sign_round(
+            &params,
+            &signers.reward_set(),
+            vec![rejection(0, 100)],
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(
+            outcome,
+            ReplayOutcome::Rejected {
+                rejected_weight: 4,
+                timestamp_ms: 100
+            }
+        );
+
+        let outcome = replay_sign_round(
+            &params,
+            &signers.reward_set(),
+            vec![rejection(3, 100)],
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(
+            outcome,
+            ReplayOutcome::Undecided {
+                accepted_weight: 0,
+                rejected_weight: 1
+            }
+        );
+    }
+
+    #[test]
+    fn stacker_set_is_read_in_either_shape() {
+        let signers = TestSigners::new(&[1, 2]);
+        let reward_set = signers.reward_set();
+        for json in [
+            serde_json::json!({ "stacker_set": reward_set }),
+            serde_json::to_value(&reward_set).unwrap(),
+        ] {
+            let path = std::env::temp_dir().join(format!(
+                "sign-round-replay-stacker-set-{}.json",
+                rand::random::<u64>()
+            ));
+            fs::write(&path, json.to_string()).unwrap();
+            assert_eq!(load_reward_set(&path).unwrap(), reward_set);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
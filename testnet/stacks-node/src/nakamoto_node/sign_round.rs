@@ -0,0 +1,2572 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The event-processing core of a signing round.
+//!
+//! [`SignRound`] takes the messages that signers write to their StackerDBs and turns them into
+//! the packets the WSTS coordinator needs and the [`SignRoundEvent`]s that tell the
+//! [`SignCoordinator`](super::sign_coordinator::SignCoordinator) how the round is going. It does
+//! no IO: reading StackerDB events, writing to `.miners` and persisting the round are left to
+//! the coordinator, so that the accounting here can be tested without a running node.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use hashbrown::{HashMap, HashSet};
+use libsigner::v1::messages::{BlockResponse, RejectCode, SignerMessage};
+use libsigner::{
+    signer_set_for_cycle, RewardSetIndex, SignerIndexMap, SignerSlotMessage, SlotId, WstsSignerId,
+};
+use stacks::chainstate::burn::ConsensusHash;
+use stacks::chainstate::stacks::boot::NakamotoSignerEntry;
+use stacks::chainstate::stacks::{ThresholdSignature, MAX_EPOCH_SIZE};
+use stacks_common::types::chainstate::{StacksBlockId, StacksPublicKey};
+use stacks_common::util::get_epoch_time_ms;
+use stacks_common::util::hash::{to_hex, Sha512Trunc256Sum};
+use stacks_common::util::log::get_loglevel;
+use wsts::curve::ecdsa;
+use wsts::curve::point::Point;
+use wsts::net::{Message as WstsMessage, Packet};
+use wsts::state_machine::PublicKeys;
+
+/// Weight of the most recent round in the signer latency estimate
+const LATENCY_ESTIMATE_ALPHA: f64 = 0.2;
+
+/// Smallest block size the signer latency estimate recommends, so that there is always room
+/// for the tenure change, coinbase and signer transactions
+const MIN_RECOMMENDED_BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Weight of the signers that rejected the block being signed, by kind of rejection
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RejectionTally {
+    /// Total weight of the signer set
+    total_weight: u32,
+    /// Weight of the signers that rejected the block for any reason
+    rejected_weight: u32,
+    /// Weight of the signers that rejected the block because they could not reach their
+    /// stacks-node
+    connectivity_weight: u32,
+    /// Slots that have already rejected the block
+    rejected_slots: HashSet<SlotId>,
+}
+
+impl RejectionTally {
+    pub fn new(total_weight: u32) -> Self {
+        Self {
+            total_weight,
+            ..Default::default()
+        }
+    }
+
+    /// Count a rejection with `reason_code` from the signer in `slot_id`, which carries
+    /// `weight`. Only the first rejection from each slot is counted.
+    /// Returns true if the rejection was counted.
+    pub fn add(&mut self, slot_id: SlotId, weight: u32, reason_code: &RejectCode) -> bool {
+        if !self.rejected_slots.insert(slot_id) {
+            return false;
+        }
+        // each slot is counted once, so this never exceeds the signer set's total weight
+        self.rejected_weight += weight;
+        debug_assert!(self.rejected_weight <= self.total_weight);
+        if matches!(reason_code, RejectCode::ConnectivityIssues) {
+            self.connectivity_weight += weight;
+        }
+        true
+    }
+
+    /// Has the signer in `slot_id` rejected the block?
+    pub fn has_rejected(&self, slot_id: SlotId) -> bool {
+        self.rejected_slots.contains(&slot_id)
+    }
+
+    /// Number of signers that rejected the block
+    pub fn num_rejected(&self) -> usize {
+        self.rejected_slots.len()
+    }
+
+    /// Weight of the signers that rejected the block for any reason
+    pub fn rejected_weight(&self) -> u32 {
+        self.rejected_weight
+    }
+
+    /// How long to wait before proposing another block. Proposing again right away just
+    /// repeats the failure when most of the rejecting weight could not reach its stacks-node,
+    /// so in that case the wait is `max` scaled by the share of the signer set's weight that
+    /// reported connectivity issues. Otherwise there is no reason to wait.
+    pub fn reproposal_backoff(&self, max: Duration) -> Duration {
+        if self.total_weight == 0
+            || u64::from(self.connectivity_weight) * 2 <= u64::from(self.rejected_weight)
+        {
+            return Duration::ZERO;
+        }
+        let backoff_ms =
+            max.as_millis() * u128::from(self.connectivity_weight) / u128::from(self.total_weight);
+        Duration::from_millis(u64::try_from(backoff_ms).unwrap_or(u64::MAX)).min(max)
+    }
+}
+
+/// The weights of a reward cycle's signers, and the threshold weight needed to sign a block.
+/// Signers are identified by the slot they write to in the signers StackerDBs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignerSetWeights {
+    /// How the signers' slots, reward set indices and WSTS signer IDs correspond
+    indices: SignerIndexMap,
+    /// Weight of each signer with a valid signing key, by slot
+    weights: HashMap<SlotId, u32>,
+    /// Hex-encoded compressed signing key of each signer with a valid signing key, by slot
+    signing_keys: HashMap<SlotId, String>,
+    /// Total weight of the signers with valid signing keys
+    total_weight: u32,
+    /// Weight needed to sign a block
+    threshold: u32,
+}
+
+impl SignerSetWeights {
+    /// `signers` must be from a reward set whose `total_signing_weight` fits in a u32, as
+    /// `NakamotoSigningParams::parse` checks
+    pub fn new(signers: &[NakamotoSignerEntry], threshold: u32) -> Self {
+        let indices = SignerIndexMap::new(signers);
+        let mut weights = HashMap::with_capacity(signers.len());
+        let mut signing_keys = HashMap::with_capacity(signers.len());
+        let mut total_weight = 0u32;
+        for (index, signer) in signers.iter().enumerate() {
+            let index =
+                RewardSetIndex(u32::try_from(index).expect("FATAL: more than u32::MAX signers"));
+            // a signer with a malformed signing key can never sign, so its weight is of no use
+            if StacksPublicKey::from_slice(signer.signing_key.as_slice()).is_err() {
+                continue;
+            }
+            let slot_id = indices
+                .slot_of(index)
+                .expect("FATAL: reward set signer has no slot");
+            weights.insert(slot_id, signer.weight);
+            signing_keys.insert(slot_id, to_hex(&signer.signing_key));
+            debug_assert!(
+                total_weight.checked_add(signer.weight).is_some(),
+                "signer weights exceed u32::MAX"
+            );
+            total_weight += signer.weight;
+        }
+        Self {
+            indices,
+            weights,
+            signing_keys,
+            total_weight,
+            threshold,
+        }
+    }
+
+    /// Weight of the signer in `slot_id`, or 0 if there is no such signer
+    pub fn weight(&self, slot_id: SlotId) -> u32 {
+        self.weights.get(&slot_id).copied().unwrap_or(0)
+    }
+
+    /// Hex-encoded compressed signing key of the signer in `slot_id`, if there is such a signer
+    pub fn signing_key(&self, slot_id: SlotId) -> Option<&str> {
+        self.signing_keys.get(&slot_id).map(String::as_str)
+    }
+
+    /// Slot of the signer whose signing key is `signer_pubkey`, if it is in the set
+    pub fn slot_of_key(&self, signer_pubkey: &StacksPublicKey) -> Option<SlotId> {
+        self.indices.slot_of_key(signer_pubkey)
+    }
+
+    /// Slot of the signer with WSTS signer ID `signer_id`, if it is in the set
+    pub fn slot_of_wsts_signer(&self, signer_id: WstsSignerId) -> Option<SlotId> {
+        self.indices.slot_of_wsts_signer(signer_id)
+    }
+
+    /// Total weight of the signers with valid signing keys
+    pub fn total_weight(&self) -> u32 {
+        self.total_weight
+    }
+
+    /// Number of signers with valid signing keys
+    pub fn num_signers(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Weight needed to sign a block
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// The slots of the signers with valid signing keys and their weights, in slot order
+    pub fn slot_weights(&self) -> Vec<(SlotId, u32)> {
+        let mut slot_weights: Vec<_> = self
+            .weights
+            .iter()
+            .map(|(slot_id, weight)| (*slot_id, *weight))
+            .collect();
+        slot_weights.sort_unstable();
+        slot_weights
+    }
+}
+
+/// How the signing weight of a reward set is spread over its signers, for monitoring how
+/// concentrated signing power is in a reward cycle
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RewardSetStats {
+    pub num_signers: u32,
+    pub min_weight: u32,
+    pub max_weight: u32,
+    /// The middle weight, or the mean of the two middle weights for an even number of signers
+    pub median_weight: f64,
+    /// Share of the total weight held by the 5 heaviest signers
+    pub top_5_share: f64,
+    /// Herfindahl-Hirschman index: the sum of the squares of the signers' shares of the total
+    /// weight. It ranges from `1 / num_signers` when the weight is evenly spread to 1 when a
+    /// single signer holds all of it.
+    pub hhi: f64,
+}
+
+impl RewardSetStats {
+    pub fn new(signers: &[NakamotoSignerEntry]) -> Self {
+        let mut weights: Vec<u32> = signers.iter().map(|signer| signer.weight).collect();
+        // heaviest first
+        weights.sort_unstable_by(|a, b| b.cmp(a));
+        let (Some(max_weight), Some(min_weight)) = (weights.first(), weights.last()) else {
+            return Self::default();
+        };
+        let mid = weights.len() / 2;
+        let median_weight = if weights.len() % 2 == 0 {
+            (f64::from(weights[mid - 1]) + f64::from(weights[mid])) / 2.0
+        } else {
+            f64::from(weights[mid])
+        };
+        // reward set entries always carry weight, but don't divide by zero if one doesn't
+        let total_weight = weights.iter().copied().map(u64::from).sum::<u64>().max(1) as f64;
+        let top_5_weight: u64 = weights.iter().take(5).copied().map(u64::from).sum();
+        let hhi = weights
+            .iter()
+            .map(|weight| (f64::from(*weight) / total_weight).powi(2))
+            .sum();
+        Self {
+            num_signers: u32::try_from(weights.len()).expect("FATAL: more than u32::MAX signers"),
+            min_weight: *min_weight,
+            max_weight: *max_weight,
+            median_weight,
+            top_5_share: top_5_weight as f64 / total_weight,
+            hhi,
+        }
+    }
+}
+
+/// Attribute `messages` from the adjacent reward cycle's signer set to the slots of the same
+/// signing keys in `signer_set`, whose weight is what counts towards the block being signed.
+/// Messages from signers that are not in `signer_set` are dropped.
+pub fn attribute_to_signer_set(
+    messages: Vec<SignerSlotMessage<SignerMessage>>,
+    signer_set: &SignerSetWeights,
+) -> Vec<SignerSlotMessage<SignerMessage>> {
+    messages
+        .into_iter()
+        .filter_map(|mut msg| {
+            msg.slot_id = signer_set.slot_of_key(&msg.signer_pubkey)?.0;
+            Some(msg)
+        })
+        .collect()
+}
+
+/// Move the messages from signers with `prioritized_keys` to the front of `messages`, keeping
+/// the order of the messages otherwise, so that their weight counts as early as possible
+pub fn prioritize_signer_messages(
+    messages: &mut [SignerSlotMessage<SignerMessage>],
+    prioritized_keys: &[StacksPublicKey],
+) {
+    if prioritized_keys.is_empty() {
+        return;
+    }
+    // stable, so the prioritized messages keep their order too
+    messages.sort_by_key(|msg| !prioritized_keys.contains(&msg.signer_pubkey));
+}
+
+/// Which of a signing round's signer sets a batch of signer messages came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerSetMatch {
+    /// The signer set of the round's reward cycle
+    RewardCycle,
+    /// The signer set of the adjacent reward cycle, near a boundary between the two
+    BoundaryCycle,
+}
+
+/// The signer sets whose messages a signing round accepts, worked out once when the round is
+/// set up rather than for every batch of messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignerSetParity {
+    reward_cycle: u64,
+    signer_set: u32,
+    /// The adjacent reward cycle whose signers' responses are also accepted, and its signer set
+    boundary: Option<(u64, u32)>,
+}
+
+impl SignerSetParity {
+    /// The signer sets of `reward_cycle` and, if given, of `boundary_reward_cycle`. Errors if
+    /// the boundary cycle is not adjacent to `reward_cycle`, since only adjacent cycles write
+    /// to different signer sets.
+    pub fn new(reward_cycle: u64, boundary_reward_cycle: Option<u64>) -> Result<Self, String> {
+        let boundary = match boundary_reward_cycle {
+            Some(cycle)
+                if reward_cycle.checked_add(1) == Some(cycle)
+                    || reward_cycle.checked_sub(1) == Some(cycle) =>
+            {
+                Some((cycle, signer_set_for_cycle(cycle)))
+            }
+            Some(cycle) => {
+                return Err(format!(
+                    "Boundary reward cycle {cycle} is not adjacent to reward cycle {reward_cycle}"
+                ));
+            }
+            None => None,
+        };
+        Ok(Self {
+            reward_cycle,
+            signer_set: signer_set_for_cycle(reward_cycle),
+            boundary,
+        })
+    }
+
+    /// The reward cycle of the round
+    pub fn reward_cycle(&self) -> u64 {
+        self.reward_cycle
+    }
+
+    /// The signer set of the round's reward cycle
+    pub fn signer_set(&self) -> u32 {
+        self.signer_set
+    }
+
+    /// The adjacent reward cycle whose signers' responses are also accepted, if any
+    pub fn boundary_reward_cycle(&self) -> Option<u64> {
+        self.boundary.map(|(cycle, _)| cycle)
+    }
+
+    /// The reward cycles whose signer sets are read, the round's first
+    pub fn signer_cycles(&self) -> Vec<u64> {
+        std::iter::once(self.reward_cycle)
+            .chain(self.boundary_reward_cycle())
+            .collect()
+    }
+
+    /// Which of the accepted signer sets `signer_set` is, if either
+    pub fn classify(&self, signer_set: u32) -> Option<SignerSetMatch> {
+        if signer_set == self.signer_set {
+            Some(SignerSetMatch::RewardCycle)
+        } else if matches!(self.boundary, Some((_, boundary_set)) if boundary_set == signer_set) {
+            Some(SignerSetMatch::BoundaryCycle)
+        } else {
+            None
+        }
+    }
+}
+
+/// Something that happened in a signing round as a result of signer messages
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum SignRoundEvent {
+    /// The messages came from a signer set that is not signing this block
+    SignerSetMismatch { signer_set: u32 },
+    /// A signer's response changed the weight accepting or rejecting the block
+    WeightUpdated {
+        accepted_weight: u32,
+        rejected_weight: u32,
+    },
+    /// Signers holding at least the threshold weight have sent signature shares
+    ThresholdReached { accepted_weight: u32 },
+    /// So much weight rejected the block that the threshold can no longer be reached
+    Rejected { rejected_weight: u32 },
+    /// The signer in `slot_id` both sent a signature share for the block and rejected it.
+    /// Only its first response counts.
+    Equivocation { slot_id: u32 },
+    /// The signer in `slot_id` rejected the block for a tenure elected in another sortition.
+    /// The rejection is ignored.
+    ElectionMismatch {
+        slot_id: u32,
+        election_consensus_hash: ConsensusHash,
+    },
+    /// The signer in `slot_id` is ignored by configuration, so its message was dropped unread
+    Ignored { slot_id: u32 },
+    /// The signer in `slot_id` published again the threshold signature of the block that it
+    /// produced in an earlier round, and the signature verified. The block is signed.
+    SignatureReplayed { slot_id: u32 },
+}
+
+/// The outcome of processing one batch of signer messages
+#[derive(Debug, Clone, Default)]
+pub struct SignRoundUpdate {
+    /// Verified WSTS packets, for the coordinator's state machine
+    pub packets: Vec<Packet>,
+    /// Slots of the signers that wrote the messages
+    pub responding_slots: Vec<u32>,
+    /// What happened in the round
+    pub events: Vec<SignRoundEvent>,
+}
+
+impl SignRoundUpdate {
+    /// Empty the update, keeping its allocations
+    pub fn clear(&mut self) {
+        self.packets.clear();
+        self.responding_slots.clear();
+        self.events.clear();
+    }
+}
+
+/// The state of one signing round: who has accepted or rejected the block so far
+pub struct SignRound {
+    signer_set: SignerSetWeights,
+    block_sighash: Sha512Trunc256Sum,
+    sign_id: u64,
+    sign_iter_id: u64,
+    /// The signer sets whose responses are accepted
+    signer_sets: SignerSetParity,
+    /// The burnchain view the miner proposed the block with
+    miner_burn_view: ConsensusHash,
+    /// Consensus hash of the sortition that elected the block's tenure. Rejections tagged
+    /// with another election are ignored.
+    election_consensus_hash: Option<ConsensusHash>,
+    wsts_public_keys: PublicKeys,
+    coordinator_pk: ecdsa::PublicKey,
+    rejections: RejectionTally,
+    /// Slots that have sent signature shares for the block
+    accepted_slots: HashSet<SlotId>,
+    accepted_weight: u32,
+    threshold_reached: bool,
+    rejected: bool,
+    /// Number of signer packets whose signatures have been verified
+    verified_packets: usize,
+    /// Messages attributed to the round's signer set that are not processed yet, because an
+    ///  earlier call ran out of time
+    backlog: VecDeque<SignerSlotMessage<SignerMessage>>,
+    /// Slots of the signers whose messages are dropped unread
+    ignored_slots: HashSet<SlotId>,
+    /// Number of messages dropped because their signer is ignored
+    ignored_responses: usize,
+    /// The group key that threshold signatures of the block published again by the signers
+    /// are verified against, if they are accepted
+    aggregate_public_key: Option<Point>,
+    /// The threshold signature of the block that a signer published again, if any
+    replayed_signature: Option<ThresholdSignature>,
+    /// How long each packet's verification is stretched by, to test slow verification
+    #[cfg(test)]
+    verify_delay: Option<Duration>,
+}
+
+impl SignRound {
+    pub fn new(
+        signer_set: SignerSetWeights,
+        block_sighash: Sha512Trunc256Sum,
+        sign_id: u64,
+        sign_iter_id: u64,
+        signer_sets: SignerSetParity,
+        miner_burn_view: ConsensusHash,
+        election_consensus_hash: Option<ConsensusHash>,
+        wsts_public_keys: PublicKeys,
+        coordinator_pk: ecdsa::PublicKey,
+    ) -> Self {
+        let rejections = RejectionTally::new(signer_set.total_weight());
+        Self {
+            signer_set,
+            block_sighash,
+            sign_id,
+            sign_iter_id,
+            signer_sets,
+            miner_burn_view,
+            election_consensus_hash,
+            wsts_public_keys,
+            coordinator_pk,
+            rejections,
+            accepted_slots: HashSet::new(),
+            accepted_weight: 0,
+            threshold_reached: false,
+            rejected: false,
+            verified_packets: 0,
+            backlog: VecDeque::new(),
+            ignored_slots: HashSet::new(),
+            ignored_responses: 0,
+            aggregate_public_key: None,
+            replayed_signature: None,
+            #[cfg(test)]
+            verify_delay: None,
+        }
+    }
+
+    /// The rejections received so far
+    pub fn rejections(&self) -> &RejectionTally {
+        &self.rejections
+    }
+
+    /// Weight of the signers that have sent signature shares for the block
+    pub fn accepted_weight(&self) -> u32 {
+        self.accepted_weight
+    }
+
+    /// Number of signer packets whose signatures have been verified
+    pub fn verified_packets(&self) -> usize {
+        self.verified_packets
+    }
+
+    /// Drop the messages of the signers with `signing_keys` unread, as if they never arrived.
+    /// Keys that are not in the signer set are skipped.
+    pub fn ignore_signers(&mut self, signing_keys: &[StacksPublicKey]) {
+        self.ignored_slots.extend(
+            signing_keys
+                .iter()
+                .filter_map(|signing_key| self.signer_set.slot_of_key(signing_key)),
+        );
+    }
+
+    /// Accept a threshold signature of the block that a signer publishes again in place of
+    /// its signature share, if it verifies against `aggregate_public_key`. Signers that have
+    /// already signed the block answer a repeat of its proposal that way.
+    pub fn accept_replayed_signatures(&mut self, aggregate_public_key: Point) {
+        self.aggregate_public_key = Some(aggregate_public_key);
+    }
+
+    /// The threshold signature of the block that a signer published again, if any
+    pub fn replayed_signature(&self) -> Option<&ThresholdSignature> {
+        self.replayed_signature.as_ref()
+    }
+
+    /// Number of messages dropped because their signer is ignored
+    pub fn ignored_responses(&self) -> usize {
+        self.ignored_responses
+    }
+
+    /// Process `messages` that signer set `signer_set` wrote to its StackerDBs
+    pub fn process_signer_messages(
+        &mut self,
+        signer_set: u32,
+        messages: Vec<SignerSlotMessage<SignerMessage>>,
+    ) -> SignRoundUpdate {
+        let mut update = SignRoundUpdate::default();
+        self.process_signer_messages_into(signer_set, messages, &mut update);
+        update
+    }
+
+    /// Like `process_signer_messages`, but writes the outcome into `update`, reusing its
+    /// buffers. Whatever `update` held before is cleared.
+    pub fn process_signer_messages_into(
+        &mut self,
+        signer_set: u32,
+        messages: Vec<SignerSlotMessage<SignerMessage>>,
+        update: &mut SignRoundUpdate,
+    ) {
+        self.process_signer_messages_until(signer_set, messages, update, || false);
+    }
+
+    /// Like `process_signer_messages_into`, but stops once `out_of_time` returns true, which
+    /// is checked after each message. The messages left over are queued for
+    /// `process_queued_messages_until`, and are processed before any later batch.
+    pub fn process_signer_messages_until(
+        &mut self,
+        signer_set: u32,
+        messages: Vec<SignerSlotMessage<SignerMessage>>,
+        update: &mut SignRoundUpdate,
+        out_of_time: impl FnMut() -> bool,
+    ) {
+        update.clear();
+        let messages = match self.signer_sets.classify(signer_set) {
+            Some(SignerSetMatch::RewardCycle) => messages,
+            Some(SignerSetMatch::BoundaryCycle) => {
+                attribute_to_signer_set(messages, &self.signer_set)
+            }
+            None => {
+                update
+                    .events
+                    .push(SignRoundEvent::SignerSetMismatch { signer_set });
+                return;
+            }
+        };
+        for msg in messages {
+            if self.ignored_slots.contains(&SlotId(msg.slot_id)) {
+                self.ignored_responses += 1;
+                update.events.push(SignRoundEvent::Ignored {
+                    slot_id: msg.slot_id,
+                });
+                continue;
+            }
+            self.backlog.push_back(msg);
+        }
+        self.process_backlog(update, out_of_time);
+    }
+
+    /// Whether an earlier call ran out of time before processing all of its messages
+    pub fn has_queued_messages(&self) -> bool {
+        !self.backlog.is_empty()
+    }
+
+    /// Process the messages queued by earlier calls that ran out of time, stopping once
+    /// `out_of_time` returns true. Whatever `update` held before is cleared.
+    pub fn process_queued_messages_until(
+        &mut self,
+        update: &mut SignRoundUpdate,
+        out_of_time: impl FnMut() -> bool,
+    ) {
+        update.clear();
+        self.process_backlog(update, out_of_time);
+    }
+
+    /// Stretch the verification of each packet by `delay`
+    #[cfg(test)]
+    pub fn set_verify_delay(&mut self, delay: Option<Duration>) {
+        self.verify_delay = delay;
+    }
+
+    /// Process queued messages in order until the queue is empty or `out_of_time` returns
+    /// true. At least one message is processed per call, so the queue always drains.
+    fn process_backlog(
+        &mut self,
+        update: &mut SignRoundUpdate,
+        mut out_of_time: impl FnMut() -> bool,
+    ) {
+        while let Some(msg) = self.backlog.pop_front() {
+            update.responding_slots.push(msg.slot_id);
+            self.process_signer_message(msg, update);
+            if out_of_time() {
+                break;
+            }
+        }
+    }
+
+    /// Process one message attributed to the round's signer set
+    fn process_signer_message(
+        &mut self,
+        msg: SignerSlotMessage<SignerMessage>,
+        update: &mut SignRoundUpdate,
+    ) {
+        match msg.message {
+            SignerMessage::BlockResponse(BlockResponse::Rejected(rejection)) => {
+                if rejection.signer_signature_hash != self.block_sighash {
+                    return;
+                }
+                if let Some(attempt) = rejection.attempt {
+                    if attempt < self.sign_iter_id {
+                        // the signer rejected an earlier proposal of this same block, which
+                        //  says nothing about the one in this round
+                        debug!(
+                            "Miner/Coordinator: Ignoring a rejection of an earlier signing attempt";
+                            "slot_id" => msg.slot_id,
+                            "attempt" => attempt,
+                            "sign_iter_id" => self.sign_iter_id,
+                        );
+                        return;
+                    }
+                }
+                if let (Some(expected), Some(election_consensus_hash)) = (
+                    self.election_consensus_hash.as_ref(),
+                    rejection.election_consensus_hash,
+                ) {
+                    if &election_consensus_hash != expected {
+                        update.events.push(SignRoundEvent::ElectionMismatch {
+                            slot_id: msg.slot_id,
+                            election_consensus_hash,
+                        });
+                        return;
+                    }
+                }
+                if let Some(signer_view) = rejection.signer_view.as_ref() {
+                    if signer_view.burn_view_consensus_hash != self.miner_burn_view {
+                        warn!(
+                            "Miner/Coordinator: Signer rejected block with a different burnchain view";
+                            "slot_id" => msg.slot_id,
+                            "reason" => %rejection.reason,
+                            "miner_burn_view" => %self.miner_burn_view,
+                            "signer_burn_view" => %signer_view.burn_view_consensus_hash,
+                        );
+                    }
+                }
+                self.add_rejection(
+                    SlotId(msg.slot_id),
+                    &rejection.reason_code,
+                    &mut update.events,
+                );
+            }
+            SignerMessage::BlockResponse(BlockResponse::Accepted((
+                signer_signature_hash,
+                signature,
+            ))) => {
+                if signer_signature_hash != self.block_sighash || self.replayed_signature.is_some()
+                {
+                    return;
+                }
+                let Some(aggregate_public_key) = self.aggregate_public_key.as_ref() else {
+                    return;
+                };
+                if !signature
+                    .0
+                    .verify(aggregate_public_key, &signer_signature_hash.0)
+                {
+                    warn!(
+                        "Miner/Coordinator: Signer published a signature of the block that does not verify";
+                        "slot_id" => msg.slot_id,
+                        "signer_sighash" => %signer_signature_hash,
+                    );
+                    return;
+                }
+                self.replayed_signature = Some(signature);
+                update.events.push(SignRoundEvent::SignatureReplayed {
+                    slot_id: msg.slot_id,
+                });
+            }
+            SignerMessage::DkgResults { .. }
+            | SignerMessage::EncryptedSignerState(_)
+            | SignerMessage::Transactions(_) => {}
+            SignerMessage::Packet(packet) => {
+                debug!("Received signers packet: {packet:?}");
+                self.verified_packets += 1;
+                #[cfg(test)]
+                if let Some(delay) = self.verify_delay {
+                    std::thread::sleep(delay);
+                }
+                if !packet.verify(&self.wsts_public_keys, &self.coordinator_pk) {
+                    warn!("Failed to verify StackerDB packet: {packet:?}");
+                    return;
+                }
+                if let WstsMessage::SignatureShareResponse(ref response) = packet.msg {
+                    if response.sign_id == self.sign_id
+                        && response.sign_iter_id == self.sign_iter_id
+                    {
+                        self.add_signature_share(
+                            WstsSignerId(response.signer_id),
+                            &mut update.events,
+                        );
+                    }
+                }
+                update.packets.push(packet);
+            }
+        }
+    }
+
+    /// Count a rejection from the signer in `slot_id`
+    fn add_rejection(
+        &mut self,
+        slot_id: SlotId,
+        reason_code: &RejectCode,
+        events: &mut Vec<SignRoundEvent>,
+    ) {
+        if self.accepted_slots.contains(&slot_id) {
+            events.push(SignRoundEvent::Equivocation { slot_id: slot_id.0 });
+            return;
+        }
+        let weight = self.signer_set.weight(slot_id);
+        if !self.rejections.add(slot_id, weight, reason_code) {
+            return;
+        }
+        events.push(self.weight_updated());
+        // the threshold is out of reach once more than the rest of the weight has rejected
+        let max_rejected_weight = self
+            .signer_set
+            .total_weight()
+            .saturating_sub(self.signer_set.threshold());
+        if !self.rejected && self.rejections.rejected_weight() > max_rejected_weight {
+            self.rejected = true;
+            events.push(SignRoundEvent::Rejected {
+                rejected_weight: self.rejections.rejected_weight(),
+            });
+        }
+    }
+
+    /// Count a signature share from the signer with `signer_id`
+    fn add_signature_share(&mut self, signer_id: WstsSignerId, events: &mut Vec<SignRoundEvent>) {
+        let Some(slot_id) = self.signer_set.slot_of_wsts_signer(signer_id) else {
+            return;
+        };
+        if self.rejections.has_rejected(slot_id) {
+            events.push(SignRoundEvent::Equivocation { slot_id: slot_id.0 });
+            return;
+        }
+        if !self.accepted_slots.insert(slot_id) {
+            return;
+        }
+        // each slot is counted once, so this never exceeds the signer set's total weight
+        self.accepted_weight += self.signer_set.weight(slot_id);
+        debug_assert!(self.accepted_weight <= self.signer_set.total_weight());
+        events.push(self.weight_updated());
+        if !self.threshold_reached && self.accepted_weight >= self.signer_set.threshold() {
+            self.threshold_reached = true;
+            events.push(SignRoundEvent::ThresholdReached {
+                accepted_weight: self.accepted_weight,
+            });
+        }
+    }
+
+    /// Who has accepted or rejected the block so far, and who has not responded
+    pub fn tally(&self) -> SignRoundTally {
+        let responders = self.accepted_slots.len();
+        let rejectors = self.rejections.num_rejected();
+        let silent = self
+            .signer_set
+            .num_signers()
+            .saturating_sub(responders)
+            .saturating_sub(rejectors);
+        SignRoundTally {
+            responders,
+            responder_weight: self.accepted_weight,
+            rejectors,
+            rejector_weight: self.rejections.rejected_weight(),
+            silent,
+            silent_weight: self
+                .signer_set
+                .total_weight()
+                .saturating_sub(self.accepted_weight)
+                .saturating_sub(self.rejections.rejected_weight()),
+        }
+    }
+
+    /// Whether each of `signers`, the reward set's signers in reward set order, sent a
+    /// signature share for the block
+    pub fn signer_signature_data(
+        &self,
+        signers: &[NakamotoSignerEntry],
+    ) -> Vec<SignerSignatureData> {
+        signers
+            .iter()
+            .map(|signer| {
+                let present = StacksPublicKey::from_slice(signer.signing_key.as_slice())
+                    .ok()
+                    .and_then(|signer_pubkey| self.signer_set.slot_of_key(&signer_pubkey))
+                    .map_or(false, |slot_id| self.accepted_slots.contains(&slot_id));
+                SignerSignatureData {
+                    signer_pubkey: to_hex(&signer.signing_key),
+                    weight: signer.weight,
+                    present,
+                }
+            })
+            .collect()
+    }
+
+    /// How each signer with a valid signing key responded to the block so far, in slot order.
+    /// Ignored signers are left out, since their responses were never read.
+    pub fn signer_report(&self) -> Vec<SignerRoundReport> {
+        self.signer_set
+            .slot_weights()
+            .into_iter()
+            .filter(|(slot_id, _)| !self.ignored_slots.contains(slot_id))
+            .filter_map(|(slot_id, weight)| {
+                let response = if self.accepted_slots.contains(&slot_id) {
+                    SignerResponse::Accepted
+                } else if self.rejections.has_rejected(slot_id) {
+                    SignerResponse::Rejected
+                } else {
+                    SignerResponse::Missed
+                };
+                Some(SignerRoundReport {
+                    signer_pubkey: self.signer_set.signing_key(slot_id)?.to_string(),
+                    weight,
+                    response,
+                })
+            })
+            .collect()
+    }
+
+    /// Summarize the round for proposing block `block_id` in signing attempt `attempt`,
+    /// which ended with `completion`
+    pub fn summary(
+        &self,
+        block_id: StacksBlockId,
+        attempt: u64,
+        timing: &SignRoundTiming,
+        completion: SignRoundCompletion,
+    ) -> SignRoundSummary {
+        SignRoundSummary {
+            block_id,
+            signer_sighash: self.block_sighash.clone(),
+            attempt,
+            proposal_sent_ms: timing.proposal_sent_ms,
+            first_response_latency: timing.first_response_latency,
+            threshold_latency: timing.threshold_latency,
+            tally: self.tally(),
+            ignored_responses: self.ignored_responses,
+            threshold: self.signer_set.threshold(),
+            total_weight: self.signer_set.total_weight(),
+            completion,
+        }
+    }
+
+    fn weight_updated(&self) -> SignRoundEvent {
+        SignRoundEvent::WeightUpdated {
+            accepted_weight: self.accepted_weight,
+            rejected_weight: self.rejections.rejected_weight(),
+        }
+    }
+}
+
+/// Who has accepted or rejected the block being signed, and who has not responded
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignRoundTally {
+    /// Number of signers that sent signature shares
+    pub responders: usize,
+    pub responder_weight: u32,
+    /// Number of signers that rejected the block
+    pub rejectors: usize,
+    pub rejector_weight: u32,
+    /// Number of signers that did neither
+    pub silent: usize,
+    pub silent_weight: u32,
+}
+
+/// Whether a reward set signer took part in signing a block, as reported to the event
+/// observers with the block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignerSignatureData {
+    /// The hex-encoded compressed signing key of the signer
+    pub signer_pubkey: String,
+    pub weight: u32,
+    /// Whether the signer sent a signature share for the block
+    pub present: bool,
+}
+
+/// How a signing round ended
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignRoundCompletion {
+    /// The signers produced a threshold signature over the block
+    Signed,
+    /// The test signing channel supplied the signature
+    TestSignature,
+    /// Too much weight rejected the block for it to be signed
+    Rejected,
+    /// The threshold signature did not verify over the block
+    InvalidSignature,
+    /// The WSTS coordinator failed to produce a signature
+    SignError,
+    /// The signers did not reach a decision before the round timed out
+    TimedOut,
+    /// The burnchain tip changed, so the round was abandoned
+    BurnTipChanged,
+    /// The round could not go on, e.g. because StackerDB events could not be read
+    Error,
+}
+
+impl SignRoundCompletion {
+    /// Did the round produce a signature for the block?
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Self::Signed | Self::TestSignature)
+    }
+
+    /// The broad outcome of the round
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            Self::Signed | Self::TestSignature => "signed",
+            Self::Rejected | Self::InvalidSignature | Self::SignError => "rejected",
+            Self::TimedOut => "timed_out",
+            Self::BurnTipChanged => "abandoned",
+            Self::Error => "failed",
+        }
+    }
+
+    /// Did the round end on the signers' responses or their silence, so that it counts
+    /// toward the tenure's signing stats? Rounds cut short by the miner do not.
+    pub fn counts_toward_signer_stats(&self) -> bool {
+        match self {
+            Self::Signed
+            | Self::Rejected
+            | Self::InvalidSignature
+            | Self::SignError
+            | Self::TimedOut => true,
+            Self::TestSignature | Self::BurnTipChanged | Self::Error => false,
+        }
+    }
+
+    /// The way the round reached its outcome
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::Signed => "threshold_signature",
+            Self::TestSignature => "test_signature",
+            Self::Rejected => "rejected_weight",
+            Self::InvalidSignature => "invalid_signature",
+            Self::SignError => "sign_error",
+            Self::TimedOut => "timeout",
+            Self::BurnTipChanged => "burn_tip_changed",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// How a signer responded to the block of a signing round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignerResponse {
+    /// The signer sent a signature share for the block
+    Accepted,
+    /// The signer rejected the block
+    Rejected,
+    /// The signer did neither before the round ended
+    Missed,
+}
+
+/// How one signer responded in a signing round, to be merged into the tenure's signing stats
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignerRoundReport {
+    /// The hex-encoded compressed signing key of the signer
+    pub signer_pubkey: String,
+    pub weight: u32,
+    pub response: SignerResponse,
+}
+
+/// How often a signer accepted, rejected or missed the miner's proposals in a tenure
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignerTenureStats {
+    /// The signer's weight in the signer set of the tenure's latest round
+    pub weight: u32,
+    pub accepted: u64,
+    pub rejected: u64,
+    pub missed: u64,
+}
+
+/// How each signer responded to the proposals of a tenure, over all of the tenure's signing
+/// rounds. It is kept across the rounds of a tenure like the [`SignerLatencyEstimate`], so
+/// that the miner can tell which signers hold up its blocks, and starts over when a round of
+/// another tenure is merged into it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TenureSigningStats {
+    /// Consensus hash of the sortition that elected the tenure, once a round is merged
+    pub election_consensus_hash: Option<ConsensusHash>,
+    /// Number of rounds merged
+    pub rounds: u64,
+    /// The stats of each signer, by hex-encoded compressed signing key
+    pub signers: BTreeMap<String, SignerTenureStats>,
+}
+
+impl TenureSigningStats {
+    /// Merge `report`, from a round of the tenure elected in `election_consensus_hash`.
+    /// Returns whether the stats started over because the round is of another tenure.
+    pub fn merge_round(
+        &mut self,
+        election_consensus_hash: &ConsensusHash,
+        report: &[SignerRoundReport],
+    ) -> bool {
+        let new_tenure = self.election_consensus_hash.as_ref() != Some(election_consensus_hash);
+        if new_tenure {
+            *self = Self {
+                election_consensus_hash: Some(election_consensus_hash.clone()),
+                ..Self::default()
+            };
+        }
+        self.rounds = self.rounds.saturating_add(1);
+        for signer in report.iter() {
+            let stats = self
+                .signers
+                .entry(signer.signer_pubkey.clone())
+                .or_default();
+            stats.weight = signer.weight;
+            let count = match signer.response {
+                SignerResponse::Accepted => &mut stats.accepted,
+                SignerResponse::Rejected => &mut stats.rejected,
+                SignerResponse::Missed => &mut stats.missed,
+            };
+            *count = count.saturating_add(1);
+        }
+        new_tenure
+    }
+}
+
+/// When a signing round's proposal went out, and how long the signers took to respond to it
+#[derive(Debug, Clone)]
+pub struct SignRoundTiming {
+    sent_at: Instant,
+    /// When the proposal was sent, in milliseconds since the epoch
+    proposal_sent_ms: u64,
+    /// How long after the proposal the first signer response arrived
+    first_response_latency: Option<Duration>,
+    /// How long after the proposal the threshold weight was reached
+    threshold_latency: Option<Duration>,
+}
+
+impl SignRoundTiming {
+    /// Start timing a round whose proposal was just sent
+    pub fn start() -> Self {
+        Self {
+            sent_at: Instant::now(),
+            proposal_sent_ms: u64::try_from(get_epoch_time_ms()).unwrap_or(u64::MAX),
+            first_response_latency: None,
+            threshold_latency: None,
+        }
+    }
+
+    /// How long ago the proposal was sent
+    pub fn elapsed(&self) -> Duration {
+        self.sent_at.elapsed()
+    }
+
+    /// Whether any signer responded to the proposal yet
+    pub fn has_response(&self) -> bool {
+        self.first_response_latency.is_some()
+    }
+
+    /// Record that signers responded to the proposal
+    pub fn record_response(&mut self) {
+        if self.first_response_latency.is_none() {
+            self.first_response_latency = Some(self.elapsed());
+        }
+    }
+
+    /// Record that the threshold weight was reached, returning how long that took
+    pub fn record_threshold(&mut self) -> Duration {
+        let latency = self.threshold_latency.unwrap_or_else(|| self.elapsed());
+        self.threshold_latency = Some(latency);
+        latency
+    }
+}
+
+/// Everything about a finished signing round, logged as one line so that operators can
+/// follow each round without piecing it together from the rest of the coordinator's logs
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignRoundSummary {
+    pub block_id: StacksBlockId,
+    pub signer_sighash: Sha512Trunc256Sum,
+    pub attempt: u64,
+    /// When the proposal was sent, in milliseconds since the epoch
+    pub proposal_sent_ms: u64,
+    pub first_response_latency: Option<Duration>,
+    pub threshold_latency: Option<Duration>,
+    pub tally: SignRoundTally,
+    /// Number of signer messages dropped because their signer is ignored
+    pub ignored_responses: usize,
+    /// Weight needed to sign the block
+    pub threshold: u32,
+    /// Total weight of the signers with valid signing keys
+    pub total_weight: u32,
+    pub completion: SignRoundCompletion,
+}
+
+impl SignRoundSummary {
+    /// Log the summary to `logger`, at info level if the block was signed and at warn level
+    /// otherwise, subject to the configured log level
+    pub fn log(&self, logger: &slog::Logger) {
+        let level = if self.completion.is_signed() {
+            slog::Level::Info
+        } else {
+            slog::Level::Warning
+        };
+        if !level.is_at_least(get_loglevel()) {
+            return;
+        }
+        let millis = |latency: Duration| u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+        let tally = &self.tally;
+        // slog needs the level as a literal, so the summary is written out once per level
+        macro_rules! log_summary {
+            ($log:ident) => {
+                $log!(
+                    logger,
+                    "Miner/Coordinator: Signing round summary";
+                    "block_id" => %self.block_id,
+                    "signer_sighash" => %self.signer_sighash,
+                    "attempt" => self.attempt,
+                    "proposal_sent_ms" => self.proposal_sent_ms,
+                    "first_response_ms" => self.first_response_latency.map(millis),
+                    "threshold_ms" => self.threshold_latency.map(millis),
+                    "responders" => tally.responders,
+                    "responder_weight" => tally.responder_weight,
+                    "rejectors" => tally.rejectors,
+                    "rejector_weight" => tally.rejector_weight,
+                    "silent" => tally.silent,
+                    "silent_weight" => tally.silent_weight,
+                    "ignored_responses" => self.ignored_responses,
+                    "threshold" => self.threshold,
+                    "total_weight" => self.total_weight,
+                    "outcome" => self.completion.outcome(),
+                    "completion" => self.completion.path(),
+                );
+            };
+        }
+        if self.completion.is_signed() {
+            log_summary!(slog_info);
+        } else {
+            log_summary!(slog_warn);
+        }
+    }
+}
+
+/// Exponentially weighted estimate of how long the signers take to reach the signing threshold
+/// for a block, modelled as a fixed overhead plus a validation cost per serialized byte. It
+/// is kept across the rounds of a tenure, so that the miner can size its blocks to what the
+/// signers can sign within a target latency.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SignerLatencyEstimate {
+    /// Number of rounds recorded
+    rounds: u64,
+    /// Weighted mean block size, in bytes
+    mean_size: f64,
+    /// Weighted mean time to threshold, in seconds
+    mean_secs: f64,
+    /// Weighted variance of the block size
+    size_variance: f64,
+    /// Weighted covariance of the block size and the time to threshold
+    covariance: f64,
+}
+
+impl SignerLatencyEstimate {
+    /// Record a round in which the signers took `latency` to reach the threshold for a block
+    /// of `block_size` bytes
+    pub fn record(&mut self, block_size: u64, latency: Duration) {
+        let size = block_size as f64;
+        let secs = latency.as_secs_f64();
+        if self.rounds == 0 {
+            self.mean_size = size;
+            self.mean_secs = secs;
+        } else {
+            let alpha = LATENCY_ESTIMATE_ALPHA;
+            let size_delta = size - self.mean_size;
+            let secs_delta = secs - self.mean_secs;
+            self.mean_size += alpha * size_delta;
+            self.mean_secs += alpha * secs_delta;
+            self.size_variance =
+                (1.0 - alpha) * (self.size_variance + alpha * size_delta * size_delta);
+            self.covariance = (1.0 - alpha) * (self.covariance + alpha * size_delta * secs_delta);
+        }
+        self.rounds = self.rounds.saturating_add(1);
+    }
+
+    /// The estimated overhead of a round, in seconds, and signing cost per byte, in seconds.
+    /// `None` if no round has been recorded.
+    fn latency_model(&self) -> Option<(f64, f64)> {
+        if self.rounds == 0 || self.mean_size <= 0.0 {
+            return None;
+        }
+        if self.size_variance > 0.0 {
+            let secs_per_byte = self.covariance / self.size_variance;
+            if secs_per_byte > 0.0 {
+                let overhead = (self.mean_secs - secs_per_byte * self.mean_size).max(0.0);
+                return Some((overhead, secs_per_byte));
+            }
+        }
+        // without a spread of block sizes to tell the overhead from the per-byte cost, all of
+        //  the latency is attributed to the block's size
+        Some((0.0, self.mean_secs / self.mean_size))
+    }
+
+    /// The largest block that the signers are expected to sign within `target_latency`, or
+    /// `None` if no round has been recorded yet
+    pub fn recommended_max_block_size(&self, target_latency: Duration) -> Option<u64> {
+        let (overhead, secs_per_byte) = self.latency_model()?;
+        let max_size = if secs_per_byte > 0.0 {
+            // float-to-int `as` saturates, and maps negative sizes to 0
+            ((target_latency.as_secs_f64() - overhead) / secs_per_byte) as u64
+        } else {
+            u64::MAX
+        };
+        Some(max_size.clamp(MIN_RECOMMENDED_BLOCK_SIZE, MAX_EPOCH_SIZE.into()))
+    }
+}
+
+/// Builders for synthetic signer sets and their responses, for testing signing rounds
+/// without a running node
+#[cfg(test)]
+pub mod testing {
+    use libsigner::v1::messages::{BlockRejection, BlockResponse, RejectCode, SignerMessage};
+    use libsigner::{SignerEntries, SignerSlotMessage};
+    use stacks::chainstate::nakamoto::NakamotoBlockVote;
+    use stacks::chainstate::stacks::boot::{NakamotoSignerEntry, RewardSet};
+    use stacks_common::codec::StacksMessageCodec;
+    use stacks_common::types::chainstate::{StacksPrivateKey, StacksPublicKey};
+    use stacks_common::util::hash::Sha512Trunc256Sum;
+    use wsts::curve::scalar::Scalar;
+    use wsts::net::{
+        Message as WstsMessage, NonceResponse, Packet, Signable, SignatureShareResponse,
+    };
+    use wsts::state_machine::PublicKeys;
+
+    use super::SignerSetWeights;
+
+    /// A signer set with freshly generated keys
+    pub struct TestSigners {
+        /// Each signer's private key, in reward set order
+        pub private_keys: Vec<StacksPrivateKey>,
+        /// The reward set entries of the signers
+        pub entries: Vec<NakamotoSignerEntry>,
+    }
+
+    impl TestSigners {
+        /// Generate a signer for each of `weights`
+        pub fn new(weights: &[u32]) -> Self {
+            let private_keys: Vec<_> = weights.iter().map(|_| StacksPrivateKey::new()).collect();
+            let entries = private_keys
+                .iter()
+                .zip(weights)
+                .map(|(sk, weight)| {
+                    let signing_key = StacksPublicKey::from_private(sk)
+                        .to_bytes_compressed()
+                        .try_into()
+                        .expect("FATAL: compressed public key is not 33 bytes");
+                    NakamotoSignerEntry {
+                        signing_key,
+                        stacked_amt: u128::from(*weight),
+                        weight: *weight,
+                    }
+                })
+                .collect();
+            Self {
+                private_keys,
+                entries,
+            }
+        }
+
+        /// A reward set with these signers
+        pub fn reward_set(&self) -> RewardSet {
+            let mut reward_set = RewardSet::empty();
+            reward_set.signers = Some(self.entries.clone());
+            reward_set
+        }
+
+        /// The WSTS public keys of these signers, as the coordinator parses them
+        pub fn wsts_public_keys(&self) -> PublicKeys {
+            SignerEntries::parse(false, &self.entries)
+                .expect("FATAL: failed to parse test signer entries")
+                .public_keys
+        }
+
+        /// The signing threshold of these signers, as the coordinator computes it
+        pub fn threshold(&self) -> u32 {
+            SignerEntries::parse(false, &self.entries)
+                .expect("FATAL: failed to parse test signer entries")
+                .get_signing_threshold()
+                .expect("FATAL: failed to compute signing threshold")
+        }
+
+        /// The weights of these signers, with their signing threshold
+        pub fn weights(&self) -> SignerSetWeights {
+            SignerSetWeights::new(&self.entries, self.threshold())
+        }
+
+        /// `signer`'s message, as read from its chunk in `slot_id`
+        pub fn slot_message(
+            &self,
+            signer: usize,
+            slot_id: u32,
+            message: SignerMessage,
+        ) -> SignerSlotMessage<SignerMessage> {
+            SignerSlotMessage {
+                slot_id,
+                slot_version: 1,
+                signer_pubkey: StacksPublicKey::from_private(&self.private_keys[signer]),
+                message,
+            }
+        }
+
+        /// `signer`'s rejection of the block with `block_sighash`, written to `slot_id`
+        pub fn rejection(
+            &self,
+            signer: usize,
+            slot_id: u32,
+            block_sighash: &Sha512Trunc256Sum,
+            reason_code: RejectCode,
+        ) -> SignerSlotMessage<SignerMessage> {
+            let rejection = BlockRejection::new(block_sighash.clone(), reason_code);
+            self.slot_message(
+                signer,
+                slot_id,
+                SignerMessage::BlockResponse(BlockResponse::Rejected(rejection)),
+            )
+        }
+
+        /// `signer`'s signed (empty) signature share response for the given signing round,
+        /// written to its own slot
+        pub fn signature_share(
+            &self,
+            signer: usize,
+            sign_id: u64,
+            sign_iter_id: u64,
+        ) -> SignerSlotMessage<SignerMessage> {
+            let signer_id = u32::try_from(signer).expect("FATAL: more than u32::MAX signers");
+            let response = SignatureShareResponse {
+                dkg_id: 0,
+                sign_id,
+                sign_iter_id,
+                signer_id,
+                signature_shares: vec![],
+            };
+            let signer_key = Scalar::from(*self.private_keys[signer].as_slice());
+            let sig = response
+                .sign(&signer_key)
+                .expect("FATAL: failed to sign signature share response");
+            let packet = Packet {
+                msg: WstsMessage::SignatureShareResponse(response),
+                sig,
+            };
+            self.slot_message(signer, signer_id, SignerMessage::Packet(packet))
+        }
+
+        /// `signer`'s signed (nonce-less) nonce response for the given signing round, carrying
+        /// its `vote`, written to its own slot
+        pub fn nonce_response(
+            &self,
+            signer: usize,
+            sign_id: u64,
+            sign_iter_id: u64,
+            vote: &NakamotoBlockVote,
+        ) -> SignerSlotMessage<SignerMessage> {
+            let signer_id = u32::try_from(signer).expect("FATAL: more than u32::MAX signers");
+            let response = NonceResponse {
+                dkg_id: 0,
+                sign_id,
+                sign_iter_id,
+                signer_id,
+                key_ids: vec![],
+                nonces: vec![],
+                message: vote.serialize_to_vec(),
+            };
+            let signer_key = Scalar::from(*self.private_keys[signer].as_slice());
+            let sig = response
+                .sign(&signer_key)
+                .expect("FATAL: failed to sign nonce response");
+            let packet = Packet {
+                msg: WstsMessage::NonceResponse(response),
+                sig,
+            };
+            self.slot_message(signer, signer_id, SignerMessage::Packet(packet))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    use libsigner::v1::messages::{BlockResponse, RejectCode, SignerMessage};
+    use libsigner::SlotId;
+    use stacks::chainstate::burn::ConsensusHash;
+    use stacks::chainstate::stacks::boot::signing_threshold_for;
+    use stacks::chainstate::stacks::MAX_EPOCH_SIZE;
+    use stacks::net::api::postblock_proposal::ValidateRejectCode;
+    use stacks_common::types::chainstate::{StacksBlockId, StacksPrivateKey, StacksPublicKey};
+    use stacks_common::util::hash::{to_hex, Sha512Trunc256Sum};
+    use stacks_common::util::log::get_loglevel;
+    use wsts::curve::ecdsa;
+    use wsts::curve::scalar::Scalar;
+    use wsts::state_machine::PublicKeys;
+
+    use super::testing::TestSigners;
+    use super::{
+        attribute_to_signer_set, prioritize_signer_messages, RejectionTally, RewardSetStats,
+        SignRound, SignRoundCompletion, SignRoundEvent, SignRoundTiming, SignRoundUpdate,
+        SignerLatencyEstimate, SignerResponse, SignerSetMatch, SignerSetParity, SignerSetWeights,
+        SignerTenureStats, TenureSigningStats, MIN_RECOMMENDED_BLOCK_SIZE,
+    };
+
+    /// A logged record: its level, message and key-value pairs
+    type CapturedRecord = (slog::Level, String, Vec<(String, String)>);
+
+    /// A drain that keeps every record logged to it
+    #[derive(Clone, Default)]
+    struct CaptureDrain(Arc<Mutex<Vec<CapturedRecord>>>);
+
+    impl CaptureDrain {
+        fn logger(&self) -> slog::Logger {
+            slog::Logger::root(self.clone(), o!())
+        }
+
+        fn take(&self) -> Vec<CapturedRecord> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    impl slog::Drain for CaptureDrain {
+        type Ok = ();
+        type Err = slog::Never;
+
+        fn log(
+            &self,
+            record: &slog::Record,
+            _values: &slog::OwnedKVList,
+        ) -> Result<(), slog::Never> {
+            let mut fields = CapturedFields::default();
+            slog::KV::serialize(&record.kv(), record, &mut fields).unwrap();
+            self.0
+                .lock()
+                .unwrap()
+                .push((record.level(), record.msg().to_string(), fields.0));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct CapturedFields(Vec<(String, String)>);
+
+    impl slog::Serializer for CapturedFields {
+        fn emit_arguments(&mut self, key: slog::Key, val: &fmt::Arguments) -> slog::Result {
+            self.0.push((key.to_string(), val.to_string()));
+            Ok(())
+        }
+    }
+
+    /// Log `round`'s summary with `completion`, and return the one record it logged
+    fn logged_summary(
+        round: &SignRound,
+        timing: &SignRoundTiming,
+        completion: SignRoundCompletion,
+    ) -> CapturedRecord {
+        let drain = CaptureDrain::default();
+        round
+            .summary(StacksBlockId([0x03; 32]), 2, timing, completion)
+            .log(&drain.logger());
+        let mut records = drain.take();
+        assert_eq!(records.len(), 1, "Expected exactly one summary line");
+        records.pop().unwrap()
+    }
+
+    fn field<'a>(record: &'a CapturedRecord, key: &str) -> &'a str {
+        record
+            .2
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or_else(|| panic!("Summary is missing the {key} field"))
+    }
+
+    fn assert_summary_fields(record: &CapturedRecord) {
+        for key in [
+            "block_id",
+            "signer_sighash",
+            "attempt",
+            "proposal_sent_ms",
+            "first_response_ms",
+            "threshold_ms",
+            "responders",
+            "responder_weight",
+            "rejectors",
+            "rejector_weight",
+            "silent",
+            "silent_weight",
+            "ignored_responses",
+            "threshold",
+            "total_weight",
+            "outcome",
+            "completion",
+        ] {
+            field(record, key);
+        }
+        assert_eq!(
+            field(record, "block_id"),
+            StacksBlockId([0x03; 32]).to_string()
+        );
+        assert_eq!(field(record, "signer_sighash"), block_sighash().to_string());
+        assert_eq!(field(record, "attempt"), "2");
+        assert_eq!(field(record, "threshold"), "7");
+        assert_eq!(field(record, "total_weight"), "10");
+    }
+
+    const SIGN_ID: u64 = 3;
+    const SIGN_ITER_ID: u64 = 7;
+    const REWARD_CYCLE: u64 = 10;
+
+    fn block_sighash() -> Sha512Trunc256Sum {
+        Sha512Trunc256Sum([0x01; 32])
+    }
+
+    fn election_consensus_hash() -> ConsensusHash {
+        ConsensusHash([0x04; 20])
+    }
+
+    fn sign_round(signers: &TestSigners, boundary_reward_cycle: Option<u64>) -> SignRound {
+        let coordinator_sk = StacksPrivateKey::new();
+        let coordinator_pk = ecdsa::PublicKey::new(&Scalar::from(*coordinator_sk.as_slice()))
+            .expect("Failed to make coordinator key");
+        SignRound::new(
+            signers.weights(),
+            block_sighash(),
+            SIGN_ID,
+            SIGN_ITER_ID,
+            SignerSetParity::new(REWARD_CYCLE, boundary_reward_cycle).unwrap(),
+            ConsensusHash([0x02; 20]),
+            Some(election_consensus_hash()),
+            signers.wsts_public_keys(),
+            coordinator_pk,
+        )
+    }
+
+    #[test]
+    fn connectivity_backoff_scales_with_rejecting_weight() {
+        let max = Duration::from_secs(10);
+        let mut rejections = RejectionTally::new(100);
+        assert_eq!(rejections.reproposal_backoff(max), Duration::ZERO);
+
+        rejections.add(SlotId(0), 10, &RejectCode::ConnectivityIssues);
+        assert_eq!(rejections.reproposal_backoff(max), Duration::from_secs(1));
+
+        rejections.add(SlotId(1), 30, &RejectCode::ConnectivityIssues);
+        assert_eq!(rejections.reproposal_backoff(max), Duration::from_secs(4));
+
+        // a repeated rejection from the same slot is not counted again
+        assert!(!rejections.add(SlotId(1), 30, &RejectCode::ConnectivityIssues));
+        assert_eq!(rejections.reproposal_backoff(max), Duration::from_secs(4));
+
+        rejections.add(SlotId(2), 60, &RejectCode::ConnectivityIssues);
+        assert_eq!(rejections.reproposal_backoff(max), max);
+    }
+
+    #[test]
+    fn no_connectivity_backoff_without_connectivity_majority() {
+        let max = Duration::from_secs(10);
+        let mut rejections = RejectionTally::new(100);
+        rejections.add(SlotId(0), 20, &RejectCode::ConnectivityIssues);
+        rejections.add(
+            SlotId(1),
+            20,
+            &RejectCode::ValidationFailed(ValidateRejectCode::InvalidBlock),
+        );
+        // exactly half of the rejecting weight is not a majority
+        assert_eq!(rejections.reproposal_backoff(max), Duration::ZERO);
+
+        rejections.add(SlotId(2), 5, &RejectCode::ConnectivityIssues);
+        assert_eq!(
+            rejections.reproposal_backoff(max),
+            Duration::from_millis(2_500)
+        );
+
+        // an empty signer set never backs off
+        let mut rejections = RejectionTally::new(0);
+        rejections.add(SlotId(0), 0, &RejectCode::ConnectivityIssues);
+        assert_eq!(rejections.reproposal_backoff(max), Duration::ZERO);
+    }
+
+    #[test]
+    fn rejected_weight_reaches_u32_max_without_overflowing() {
+        let signers = TestSigners::new(&[u32::MAX - 2, 1, 1]);
+        let (threshold, total_weight) = signing_threshold_for(&signers.reward_set()).unwrap();
+        assert_eq!(total_weight, u32::MAX);
+        let weights = SignerSetWeights::new(&signers.entries, threshold);
+        assert_eq!(weights.total_weight(), u32::MAX);
+        let coordinator_sk = StacksPrivateKey::new();
+        // too many keys for WSTS to parse, but rejections are not WSTS packets
+        let mut round = SignRound::new(
+            weights,
+            block_sighash(),
+            SIGN_ID,
+            SIGN_ITER_ID,
+            SignerSetParity::new(REWARD_CYCLE, None).unwrap(),
+            ConsensusHash([0x02; 20]),
+            None,
+            PublicKeys::default(),
+            ecdsa::PublicKey::new(&Scalar::from(*coordinator_sk.as_slice())).unwrap(),
+        );
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let rejections: Vec<_> = (0..3)
+            .map(|signer| {
+                let slot_id = u32::try_from(signer).unwrap();
+                signers.rejection(
+                    signer,
+                    slot_id,
+                    &block_sighash(),
+                    RejectCode::ConnectivityIssues,
+                )
+            })
+            .collect();
+        let update = round.process_signer_messages(signer_set, rejections);
+        assert!(update.events.contains(&SignRoundEvent::Rejected {
+            rejected_weight: u32::MAX - 2
+        }));
+        assert_eq!(round.rejections().rejected_weight(), u32::MAX);
+        assert_eq!(
+            round
+                .rejections()
+                .reproposal_backoff(Duration::from_secs(10)),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn signer_signature_data_marks_the_signers_that_sent_shares() {
+        let signers = TestSigners::new(&[1, 2, 3, 4]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let shares = [1, 3]
+            .into_iter()
+            .map(|signer| signers.signature_share(signer, SIGN_ID, SIGN_ITER_ID))
+            .collect();
+        round.process_signer_messages(signer_set, shares);
+
+        let data = round.signer_signature_data(&signers.entries);
+        assert_eq!(
+            data.iter()
+                .map(|signer| (signer.weight, signer.present))
+                .collect::<Vec<_>>(),
+            vec![(1, false), (2, true), (3, false), (4, true)]
+        );
+        assert_eq!(
+            data[1].signer_pubkey,
+            to_hex(&signers.entries[1].signing_key)
+        );
+    }
+
+    #[test]
+    fn prioritized_signers_count_first() {
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[1, 1, 1, 7]);
+        assert_eq!(signers.threshold(), 7);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let shares = || -> Vec<_> {
+            (0..4)
+                .map(|signer| signers.signature_share(signer, SIGN_ID, SIGN_ITER_ID))
+                .collect()
+        };
+        let heavy_signer = StacksPublicKey::from_private(&signers.private_keys[3]);
+
+        // without prioritized signers, the batch is processed in the order it arrived
+        let mut messages = shares();
+        prioritize_signer_messages(&mut messages, &[]);
+        let mut round = sign_round(&signers, None);
+        let update = round.process_signer_messages(signer_set, messages);
+        assert_eq!(update.responding_slots, vec![0, 1, 2, 3]);
+        assert_eq!(
+            update.events[..2],
+            [
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 1,
+                    rejected_weight: 0
+                },
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 2,
+                    rejected_weight: 0
+                },
+            ]
+        );
+
+        // the low-weight shares arrive first, but the prioritized signer's share counts first
+        let mut messages = shares();
+        prioritize_signer_messages(&mut messages, &[heavy_signer]);
+        let mut round = sign_round(&signers, None);
+        let update = round.process_signer_messages(signer_set, messages);
+        assert_eq!(update.responding_slots, vec![3, 0, 1, 2]);
+        assert_eq!(
+            update.events[..2],
+            [
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 7,
+                    rejected_weight: 0
+                },
+                SignRoundEvent::ThresholdReached { accepted_weight: 7 },
+            ]
+        );
+        assert_eq!(round.accepted_weight(), 10);
+    }
+
+    #[test]
+    fn slow_verification_honors_the_round_timeout() {
+        let signers = TestSigners::new(&[1; 10]);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let verify_delay = Duration::from_millis(20);
+        let poll_interval = Duration::from_millis(50);
+        let round_timeout = Duration::from_millis(100);
+        let mut round = sign_round(&signers, None);
+        round.set_verify_delay(Some(verify_delay));
+        let shares = (0..10)
+            .map(|signer| signers.signature_share(signer, SIGN_ID, SIGN_ITER_ID))
+            .collect();
+
+        // verifying the whole batch would take 200ms, so it is spread over the round's
+        //  iterations, each of which stops after the packet that crosses its deadline
+        let mut update = SignRoundUpdate::default();
+        let mut responding_slots = vec![];
+        let started = Instant::now();
+        let mut batch = Some(shares);
+        while started.elapsed() <= round_timeout {
+            let work_deadline =
+                Instant::now() + poll_interval.min(round_timeout.saturating_sub(started.elapsed()));
+            let iteration_start = Instant::now();
+            let out_of_time = || Instant::now() >= work_deadline;
+            match batch.take() {
+                Some(messages) => round.process_signer_messages_until(
+                    signer_set,
+                    messages,
+                    &mut update,
+                    out_of_time,
+                ),
+                None => round.process_queued_messages_until(&mut update, out_of_time),
+            }
+            assert!(!update.responding_slots.is_empty());
+            assert!(iteration_start.elapsed() < poll_interval + 2 * verify_delay);
+            responding_slots.extend(update.responding_slots.iter().copied());
+        }
+        assert!(started.elapsed() < round_timeout + poll_interval);
+        assert!(round.has_queued_messages());
+        assert!(round.verified_packets() < 10);
+        assert_eq!(round.accepted_weight(), round.verified_packets() as u32);
+
+        // the packets left over are still counted once there is time for them
+        round.set_verify_delay(None);
+        round.process_queued_messages_until(&mut update, || false);
+        responding_slots.extend(update.responding_slots.iter().copied());
+        assert!(!round.has_queued_messages());
+        assert_eq!(responding_slots, (0..10).collect::<Vec<_>>());
+        assert_eq!(round.verified_packets(), 10);
+        assert_eq!(round.accepted_weight(), 10);
+    }
+
+    #[test]
+    fn ignored_signers_are_dropped_unread() {
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[1, 1, 1, 7]);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let heavy_signer = StacksPublicKey::from_private(&signers.private_keys[3]);
+        let outsider = StacksPublicKey::from_private(&StacksPrivateKey::new());
+        let mut round = sign_round(&signers, None);
+        round.ignore_signers(&[heavy_signer, outsider]);
+
+        let update = round.process_signer_messages(
+            signer_set,
+            (0..4)
+                .map(|signer| signers.signature_share(signer, SIGN_ID, SIGN_ITER_ID))
+                .collect(),
+        );
+        // the ignored signer's share is not verified, and does not count as a response
+        assert_eq!(update.responding_slots, vec![0, 1, 2]);
+        assert_eq!(update.events[0], SignRoundEvent::Ignored { slot_id: 3 });
+        assert!(!update
+            .events
+            .iter()
+            .any(|event| matches!(event, SignRoundEvent::ThresholdReached { .. })));
+        assert_eq!(round.verified_packets(), 3);
+        assert_eq!(round.accepted_weight(), 3);
+        assert_eq!(round.ignored_responses(), 1);
+
+        let record = logged_summary(
+            &round,
+            &SignRoundTiming::start(),
+            SignRoundCompletion::TimedOut,
+        );
+        assert_eq!(field(&record, "ignored_responses"), "1");
+        assert_eq!(field(&record, "silent_weight"), "7");
+    }
+
+    #[test]
+    fn tenure_signing_stats_accumulate_over_rounds() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let signer_key = |signer: usize| to_hex(&signers.entries[signer].signing_key);
+        let sighash = block_sighash();
+        let mut stats = TenureSigningStats::default();
+
+        // signers 0 and 1 sign, signer 2 rejects, and signer 3 is silent
+        let mut round = sign_round(&signers, None);
+        round.process_signer_messages(
+            signer_set,
+            vec![
+                signers.signature_share(0, SIGN_ID, SIGN_ITER_ID),
+                signers.signature_share(1, SIGN_ID, SIGN_ITER_ID),
+                signers.rejection(2, 2, &sighash, RejectCode::ConnectivityIssues),
+            ],
+        );
+        let report = round.signer_report();
+        assert_eq!(
+            report
+                .iter()
+                .map(|signer| (signer.signer_pubkey.clone(), signer.response))
+                .collect::<Vec<_>>(),
+            vec![
+                (signer_key(0), SignerResponse::Accepted),
+                (signer_key(1), SignerResponse::Accepted),
+                (signer_key(2), SignerResponse::Rejected),
+                (signer_key(3), SignerResponse::Missed),
+            ]
+        );
+        // the first round of the miner's tenure starts the stats
+        assert!(stats.merge_round(&election_consensus_hash(), &report));
+
+        // signer 0 signs, signer 1 rejects, and signers 2 and 3 are silent
+        let mut round = sign_round(&signers, None);
+        round.process_signer_messages(
+            signer_set,
+            vec![
+                signers.signature_share(0, SIGN_ID, SIGN_ITER_ID),
+                signers.rejection(1, 1, &sighash, RejectCode::ConnectivityIssues),
+            ],
+        );
+        assert!(!stats.merge_round(&election_consensus_hash(), &round.signer_report()));
+
+        // signer 3's responses are ignored, so it is left out of the round's report
+        let mut round = sign_round(&signers, None);
+        round.ignore_signers(&[StacksPublicKey::from_private(&signers.private_keys[3])]);
+        round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(0, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert_eq!(round.signer_report().len(), 3);
+        assert!(!stats.merge_round(&election_consensus_hash(), &round.signer_report()));
+
+        assert_eq!(
+            stats.election_consensus_hash,
+            Some(election_consensus_hash())
+        );
+        assert_eq!(stats.rounds, 3);
+        let stats_of = |stats: &TenureSigningStats, signer: usize| {
+            stats.signers.get(&signer_key(signer)).cloned().unwrap()
+        };
+        let tenure_stats = |weight, accepted, rejected, missed| SignerTenureStats {
+            weight,
+            accepted,
+            rejected,
+            missed,
+        };
+        assert_eq!(stats_of(&stats, 0), tenure_stats(4, 3, 0, 0));
+        assert_eq!(stats_of(&stats, 1), tenure_stats(3, 1, 1, 1));
+        assert_eq!(stats_of(&stats, 2), tenure_stats(2, 0, 1, 2));
+        assert_eq!(stats_of(&stats, 3), tenure_stats(1, 0, 0, 2));
+
+        // a round of the next tenure starts the stats over
+        let next_election = ConsensusHash([0x05; 20]);
+        let round = sign_round(&signers, None);
+        assert!(stats.merge_round(&next_election, &round.signer_report()));
+        assert_eq!(stats.election_consensus_hash, Some(next_election));
+        assert_eq!(stats.rounds, 1);
+        assert_eq!(stats_of(&stats, 0), tenure_stats(4, 0, 0, 1));
+        assert_eq!(stats_of(&stats, 3), tenure_stats(1, 0, 0, 1));
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["signers"][signer_key(0)]["missed"], 1);
+        assert_eq!(
+            serde_json::from_value::<TenureSigningStats>(json).unwrap(),
+            stats
+        );
+    }
+
+    #[test]
+    fn only_rounds_the_signers_ended_count_toward_their_stats() {
+        for (completion, counts) in [
+            (SignRoundCompletion::Signed, true),
+            (SignRoundCompletion::Rejected, true),
+            (SignRoundCompletion::InvalidSignature, true),
+            (SignRoundCompletion::SignError, true),
+            (SignRoundCompletion::TimedOut, true),
+            (SignRoundCompletion::TestSignature, false),
+            (SignRoundCompletion::BurnTipChanged, false),
+            (SignRoundCompletion::Error, false),
+        ] {
+            assert_eq!(
+                completion.counts_toward_signer_stats(),
+                counts,
+                "{completion:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn signature_shares_reach_the_weight_threshold() {
+        // 10 keys in total, so the threshold is 7
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        assert_eq!(signers.threshold(), 7);
+        let reward_set = signers.reward_set();
+        assert_eq!(
+            SignerSetWeights::new(reward_set.signers.as_ref().unwrap(), 7),
+            signers.weights()
+        );
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(0, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert_eq!(update.packets.len(), 1);
+        assert_eq!(update.responding_slots, vec![0]);
+        assert_eq!(
+            update.events,
+            vec![SignRoundEvent::WeightUpdated {
+                accepted_weight: 4,
+                rejected_weight: 0
+            }]
+        );
+
+        // a share for another signing round does not count, but is still handed to WSTS
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(2, SIGN_ID, SIGN_ITER_ID + 1)],
+        );
+        assert_eq!(update.packets.len(), 1);
+        assert!(update.events.is_empty());
+
+        // a duplicate share does not count twice
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(0, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert!(update.events.is_empty());
+        assert_eq!(round.accepted_weight(), 4);
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(1, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert_eq!(
+            update.events,
+            vec![
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 7,
+                    rejected_weight: 0
+                },
+                SignRoundEvent::ThresholdReached { accepted_weight: 7 },
+            ]
+        );
+
+        // the threshold is only reported once
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(3, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert_eq!(
+            update.events,
+            vec![SignRoundEvent::WeightUpdated {
+                accepted_weight: 8,
+                rejected_weight: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn packets_from_unknown_signers_are_dropped() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let impostors = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![impostors.signature_share(0, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert!(update.packets.is_empty());
+        assert!(update.events.is_empty());
+        assert_eq!(round.accepted_weight(), 0);
+    }
+
+    #[test]
+    fn rejections_end_the_round_once_the_threshold_is_out_of_reach() {
+        // 10 keys in total, so the threshold is 7 and more than 3 rejecting ends the round
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let sighash = block_sighash();
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![
+                signers.rejection(3, 3, &sighash, RejectCode::ConnectivityIssues),
+                signers.rejection(2, 2, &sighash, RejectCode::ConnectivityIssues),
+                // a rejection of another block is ignored
+                signers.rejection(
+                    1,
+                    1,
+                    &Sha512Trunc256Sum([0x03; 32]),
+                    RejectCode::ConnectivityIssues,
+                ),
+            ],
+        );
+        assert_eq!(
+            update.events,
+            vec![
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 0,
+                    rejected_weight: 1
+                },
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 0,
+                    rejected_weight: 3
+                },
+            ]
+        );
+
+        // a repeated rejection does not count twice
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.rejection(2, 2, &sighash, RejectCode::ConnectivityIssues)],
+        );
+        assert!(update.events.is_empty());
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.rejection(
+                1,
+                1,
+                &sighash,
+                RejectCode::ValidationFailed(ValidateRejectCode::InvalidBlock),
+            )],
+        );
+        assert_eq!(
+            update.events,
+            vec![
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 0,
+                    rejected_weight: 6
+                },
+                SignRoundEvent::Rejected { rejected_weight: 6 },
+            ]
+        );
+        assert_eq!(round.rejections().rejected_weight(), 6);
+        assert_eq!(
+            round
+                .rejections()
+                .reproposal_backoff(Duration::from_secs(10)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn conflicting_responses_are_equivocations() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let sighash = block_sighash();
+
+        round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(0, SIGN_ID, SIGN_ITER_ID)],
+        );
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.rejection(0, 0, &sighash, RejectCode::ConnectivityIssues)],
+        );
+        assert_eq!(
+            update.events,
+            vec![SignRoundEvent::Equivocation { slot_id: 0 }]
+        );
+        assert_eq!(round.rejections().rejected_weight(), 0);
+
+        round.process_signer_messages(
+            signer_set,
+            vec![signers.rejection(1, 1, &sighash, RejectCode::ConnectivityIssues)],
+        );
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![signers.signature_share(1, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert_eq!(
+            update.events,
+            vec![SignRoundEvent::Equivocation { slot_id: 1 }]
+        );
+        assert_eq!(round.accepted_weight(), 4);
+    }
+
+    #[test]
+    fn other_signer_sets_are_mismatches() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let other_set = libsigner::signer_set_for_cycle(REWARD_CYCLE + 1);
+
+        let update = round.process_signer_messages(
+            other_set,
+            vec![signers.signature_share(0, SIGN_ID, SIGN_ITER_ID)],
+        );
+        assert!(update.packets.is_empty());
+        assert_eq!(
+            update.events,
+            vec![SignRoundEvent::SignerSetMismatch {
+                signer_set: other_set
+            }]
+        );
+    }
+
+    #[test]
+    fn rejections_from_another_election_are_ignored() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let sighash = block_sighash();
+        let tagged_rejection = |signer: usize, election_consensus_hash: ConsensusHash| {
+            let mut msg = signers.rejection(
+                signer,
+                u32::try_from(signer).unwrap(),
+                &sighash,
+                RejectCode::ConnectivityIssues,
+            );
+            if let SignerMessage::BlockResponse(BlockResponse::Rejected(rejection)) =
+                &mut msg.message
+            {
+                rejection.election_consensus_hash = Some(election_consensus_hash);
+            }
+            msg
+        };
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![
+                // a late rejection from a flash block's election
+                tagged_rejection(1, ConsensusHash([0x05; 20])),
+                tagged_rejection(2, election_consensus_hash()),
+                // rejections from signers that do not tag them still count
+                signers.rejection(3, 3, &sighash, RejectCode::ConnectivityIssues),
+            ],
+        );
+        assert_eq!(
+            update.events,
+            vec![
+                SignRoundEvent::ElectionMismatch {
+                    slot_id: 1,
+                    election_consensus_hash: ConsensusHash([0x05; 20]),
+                },
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 0,
+                    rejected_weight: 2
+                },
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 0,
+                    rejected_weight: 3
+                },
+            ]
+        );
+        assert_eq!(round.rejections().rejected_weight(), 3);
+    }
+
+    #[test]
+    fn rejections_of_earlier_attempts_are_ignored() {
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let sighash = block_sighash();
+        let attempt_rejection = |signer: usize, attempt: u64| {
+            let mut msg = signers.rejection(
+                signer,
+                u32::try_from(signer).unwrap(),
+                &sighash,
+                RejectCode::RejectedInPriorRound,
+            );
+            if let SignerMessage::BlockResponse(BlockResponse::Rejected(rejection)) =
+                &mut msg.message
+            {
+                rejection.attempt = Some(attempt);
+            }
+            msg
+        };
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![
+                // a late rejection of the previous proposal of this block
+                attempt_rejection(0, SIGN_ITER_ID - 1),
+                attempt_rejection(2, SIGN_ITER_ID),
+                // rejections from signers that do not tag them still count
+                signers.rejection(3, 3, &sighash, RejectCode::ConnectivityIssues),
+            ],
+        );
+        assert_eq!(
+            update.events,
+            vec![
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 0,
+                    rejected_weight: 2
+                },
+                SignRoundEvent::WeightUpdated {
+                    accepted_weight: 0,
+                    rejected_weight: 3
+                },
+            ]
+        );
+        assert!(!round.rejections().has_rejected(SlotId(0)));
+        assert_eq!(round.rejections().rejected_weight(), 3);
+    }
+
+    #[test]
+    fn signer_set_parity_near_u32_bounds() {
+        let u32_max = u64::from(u32::MAX);
+        for reward_cycle in [u32_max - 1, u32_max, u32_max + 1, u32_max + 2] {
+            let this_set = libsigner::signer_set_for_cycle(reward_cycle);
+            for boundary_cycle in [reward_cycle - 1, reward_cycle + 1] {
+                let boundary_set = libsigner::signer_set_for_cycle(boundary_cycle);
+                assert_ne!(this_set, boundary_set);
+                let parity = SignerSetParity::new(reward_cycle, Some(boundary_cycle)).unwrap();
+                assert_eq!(parity.reward_cycle(), reward_cycle);
+                assert_eq!(parity.signer_set(), this_set);
+                assert_eq!(parity.signer_cycles(), vec![reward_cycle, boundary_cycle]);
+                assert_eq!(parity.classify(this_set), Some(SignerSetMatch::RewardCycle));
+                assert_eq!(
+                    parity.classify(boundary_set),
+                    Some(SignerSetMatch::BoundaryCycle)
+                );
+                assert_eq!(parity.classify(2), None);
+                assert_eq!(parity.classify(u32::MAX), None);
+            }
+            let parity = SignerSetParity::new(reward_cycle, None).unwrap();
+            assert_eq!(parity.classify(this_set ^ 1), None);
+        }
+        // the parity of the cycle, not its truncation to a u32, picks the set
+        assert_eq!(SignerSetParity::new(u32_max, None).unwrap().signer_set(), 1);
+        assert_eq!(
+            SignerSetParity::new(u32_max + 1, None)
+                .unwrap()
+                .signer_set(),
+            0
+        );
+        assert_eq!(
+            SignerSetParity::new(u64::MAX, None).unwrap().signer_set(),
+            1
+        );
+        assert!(SignerSetParity::new(u64::MAX, Some(u64::MAX - 1)).is_ok());
+    }
+
+    #[test]
+    fn signer_set_parity_needs_an_adjacent_boundary_cycle() {
+        for (reward_cycle, boundary_cycle) in [
+            (REWARD_CYCLE, REWARD_CYCLE),
+            (REWARD_CYCLE, REWARD_CYCLE + 2),
+            (REWARD_CYCLE, REWARD_CYCLE - 2),
+            // the cycles do not wrap around
+            (u64::MAX, 0),
+            (0, u64::MAX),
+            (u64::from(u32::MAX), 0),
+        ] {
+            assert!(
+                SignerSetParity::new(reward_cycle, Some(boundary_cycle)).is_err(),
+                "Accepted boundary cycle {boundary_cycle} of reward cycle {reward_cycle}"
+            );
+        }
+    }
+
+    #[test]
+    fn boundary_set_responses_count_with_this_sets_weight() {
+        // the first three signers are in this cycle's set
+        let all_signers = TestSigners::new(&[1, 2, 3, 4]);
+        let signers = TestSigners {
+            private_keys: all_signers.private_keys[..3].to_vec(),
+            entries: all_signers.entries[..3].to_vec(),
+        };
+        let mut round = sign_round(&signers, Some(REWARD_CYCLE + 1));
+        let this_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let boundary_set = libsigner::signer_set_for_cycle(REWARD_CYCLE + 1);
+        let sighash = block_sighash();
+
+        // signer 0 still writes to this cycle's set; signers 1 and 2 already rolled over to
+        //  the adjacent set, where they hold other slots, as does signer 3, which is not in
+        //  this cycle's set at all
+        let boundary_messages = vec![
+            all_signers.rejection(1, 5, &sighash, RejectCode::ConnectivityIssues),
+            all_signers.rejection(2, 0, &sighash, RejectCode::ConnectivityIssues),
+            all_signers.rejection(3, 1, &sighash, RejectCode::ConnectivityIssues),
+        ];
+        let attributed = attribute_to_signer_set(boundary_messages.clone(), &signers.weights());
+        let attributed_slots: Vec<_> = attributed.iter().map(|msg| msg.slot_id).collect();
+        assert_eq!(attributed_slots, vec![1, 2]);
+
+        round.process_signer_messages(
+            this_set,
+            vec![all_signers.rejection(0, 0, &sighash, RejectCode::ConnectivityIssues)],
+        );
+        let update = round.process_signer_messages(boundary_set, boundary_messages);
+        assert_eq!(update.responding_slots, vec![1, 2]);
+        assert_eq!(round.rejections().rejected_weight(), 6);
+        assert_eq!(
+            round
+                .rejections()
+                .reproposal_backoff(Duration::from_secs(10)),
+            Duration::from_secs(10)
+        );
+    }
+
+    /// Time to threshold of signers that spend `overhead_ms` on every round and take
+    /// `ns_per_byte` to validate each byte of the block
+    fn synthetic_latency(overhead_ms: u64, ns_per_byte: u64, block_size: u64) -> Duration {
+        Duration::from_millis(overhead_ms) + Duration::from_nanos(ns_per_byte * block_size)
+    }
+
+    #[test]
+    fn latency_estimate_converges() {
+        let mut estimate = SignerLatencyEstimate::default();
+        let target_latency = Duration::from_millis(2_500);
+        assert_eq!(estimate.recommended_max_block_size(target_latency), None);
+
+        // 500ms of overhead and 2µs per byte: a 1MB block takes 2.5s
+        let sizes = [100_000, 1_500_000, 400_000, 800_000, 250_000];
+        for (i, size) in sizes.iter().cycle().take(100).enumerate() {
+            // +/- 50ms of jitter
+            let jitter = Duration::from_millis(50);
+            let latency = synthetic_latency(500, 2_000, *size);
+            let latency = if i % 2 == 0 {
+                latency + jitter
+            } else {
+                latency - jitter
+            };
+            estimate.record(*size, latency);
+        }
+        let recommended = estimate.recommended_max_block_size(target_latency).unwrap();
+        assert!(
+            recommended.abs_diff(1_000_000) < 50_000,
+            "Recommended {recommended} bytes"
+        );
+
+        // the signers slow down to 4µs per byte: a 500KB block now takes 2.5s
+        for size in sizes.iter().cycle().take(100) {
+            estimate.record(*size, synthetic_latency(500, 4_000, *size));
+        }
+        let recommended = estimate.recommended_max_block_size(target_latency).unwrap();
+        assert!(
+            recommended.abs_diff(500_000) < 25_000,
+            "Recommended {recommended} bytes"
+        );
+    }
+
+    #[test]
+    fn latency_estimate_without_size_spread() {
+        // every block the same size: the latency is all attributed to the size
+        let mut estimate = SignerLatencyEstimate::default();
+        for _ in 0..10 {
+            estimate.record(500_000, Duration::from_secs(1));
+        }
+        assert_eq!(
+            estimate.recommended_max_block_size(Duration::from_secs(2)),
+            Some(1_000_000)
+        );
+
+        // the recommendation never leaves room for less than the tenure's own transactions,
+        //  nor more than the epoch allows
+        assert_eq!(
+            estimate.recommended_max_block_size(Duration::ZERO),
+            Some(MIN_RECOMMENDED_BLOCK_SIZE)
+        );
+        assert_eq!(
+            estimate.recommended_max_block_size(Duration::from_secs(3_600)),
+            Some(MAX_EPOCH_SIZE.into())
+        );
+
+        // signers that take no time at all can sign anything
+        let mut estimate = SignerLatencyEstimate::default();
+        estimate.record(500_000, Duration::ZERO);
+        assert_eq!(
+            estimate.recommended_max_block_size(Duration::from_secs(1)),
+            Some(MAX_EPOCH_SIZE.into())
+        );
+    }
+
+    #[test]
+    fn reward_set_stats_of_uneven_weights() {
+        let signers = TestSigners::new(&[1, 7, 2, 2, 4, 1, 3]);
+        let stats = RewardSetStats::new(&signers.entries);
+        assert_eq!(stats.num_signers, 7);
+        assert_eq!(stats.min_weight, 1);
+        assert_eq!(stats.max_weight, 7);
+        assert_eq!(stats.median_weight, 2.0);
+        // 7 + 4 + 3 + 2 + 2 of 20
+        assert_eq!(stats.top_5_share, 0.9);
+        // (49 + 16 + 9 + 4 + 4 + 1 + 1) / 400
+        assert!((stats.hhi - 0.21).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reward_set_stats_of_even_weights() {
+        let signers = TestSigners::new(&[5, 5, 5, 5]);
+        let stats = RewardSetStats::new(&signers.entries);
+        assert_eq!(stats.num_signers, 4);
+        assert_eq!(stats.min_weight, 5);
+        assert_eq!(stats.max_weight, 5);
+        assert_eq!(stats.median_weight, 5.0);
+        assert_eq!(stats.top_5_share, 1.0);
+        // evenly spread weight has the lowest possible index
+        assert!((stats.hhi - 0.25).abs() < 1e-12);
+
+        // an even number of signers takes the mean of the middle two weights
+        let signers = TestSigners::new(&[1, 3, 8, 2]);
+        assert_eq!(RewardSetStats::new(&signers.entries).median_weight, 2.5);
+
+        // a single signer holds everything
+        let signers = TestSigners::new(&[6]);
+        let stats = RewardSetStats::new(&signers.entries);
+        assert_eq!(stats.hhi, 1.0);
+        assert_eq!(stats.top_5_share, 1.0);
+
+        assert_eq!(RewardSetStats::new(&[]), RewardSetStats::default());
+    }
+
+    #[test]
+    fn summary_of_a_signed_round() {
+        if !slog::Level::Info.is_at_least(get_loglevel()) {
+            return;
+        }
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let mut timing = SignRoundTiming::start();
+
+        let update = round.process_signer_messages(
+            signer_set,
+            vec![
+                signers.signature_share(0, SIGN_ID, SIGN_ITER_ID),
+                signers.signature_share(1, SIGN_ID, SIGN_ITER_ID),
+            ],
+        );
+        timing.record_response();
+        assert!(update
+            .events
+            .contains(&SignRoundEvent::ThresholdReached { accepted_weight: 7 }));
+        timing.record_threshold();
+
+        let record = logged_summary(&round, &timing, SignRoundCompletion::Signed);
+        assert_eq!(record.0, slog::Level::Info);
+        assert_summary_fields(&record);
+        assert_ne!(field(&record, "first_response_ms"), "None");
+        assert_ne!(field(&record, "threshold_ms"), "None");
+        assert_eq!(field(&record, "responders"), "2");
+        assert_eq!(field(&record, "responder_weight"), "7");
+        assert_eq!(field(&record, "rejectors"), "0");
+        assert_eq!(field(&record, "silent"), "2");
+        assert_eq!(field(&record, "silent_weight"), "3");
+        assert_eq!(field(&record, "outcome"), "signed");
+        assert_eq!(field(&record, "completion"), "threshold_signature");
+    }
+
+    #[test]
+    fn summary_of_a_rejected_round() {
+        if !slog::Level::Warning.is_at_least(get_loglevel()) {
+            return;
+        }
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let mut round = sign_round(&signers, None);
+        let signer_set = libsigner::signer_set_for_cycle(REWARD_CYCLE);
+        let mut timing = SignRoundTiming::start();
+
+        let rejections = (0..2)
+            .map(|signer| {
+                let slot_id = u32::try_from(signer).unwrap();
+                signers.rejection(
+                    signer,
+                    slot_id,
+                    &block_sighash(),
+                    RejectCode::ConnectivityIssues,
+                )
+            })
+            .collect();
+        round.process_signer_messages(signer_set, rejections);
+        timing.record_response();
+
+        let record = logged_summary(&round, &timing, SignRoundCompletion::Rejected);
+        assert_eq!(record.0, slog::Level::Warning);
+        assert_summary_fields(&record);
+        assert_eq!(field(&record, "threshold_ms"), "None");
+        assert_eq!(field(&record, "responders"), "0");
+        assert_eq!(field(&record, "rejectors"), "2");
+        assert_eq!(field(&record, "rejector_weight"), "7");
+        assert_eq!(field(&record, "silent"), "2");
+        assert_eq!(field(&record, "silent_weight"), "3");
+        assert_eq!(field(&record, "outcome"), "rejected");
+        assert_eq!(field(&record, "completion"), "rejected_weight");
+    }
+
+    #[test]
+    fn summary_of_a_timed_out_round() {
+        if !slog::Level::Warning.is_at_least(get_loglevel()) {
+            return;
+        }
+        let signers = TestSigners::new(&[4, 3, 2, 1]);
+        let round = sign_round(&signers, None);
+        let timing = SignRoundTiming::start();
+
+        let record = logged_summary(&round, &timing, SignRoundCompletion::TimedOut);
+        assert_eq!(record.0, slog::Level::Warning);
+        assert_summary_fields(&record);
+        assert_eq!(field(&record, "first_response_ms"), "None");
+        assert_eq!(field(&record, "threshold_ms"), "None");
+        assert_eq!(field(&record, "responders"), "0");
+        assert_eq!(field(&record, "rejectors"), "0");
+        assert_eq!(field(&record, "silent"), "4");
+        assert_eq!(field(&record, "silent_weight"), "10");
+        assert_eq!(field(&record, "outcome"), "timed_out");
+        assert_eq!(field(&record, "completion"), "timeout");
+    }
+}
@@ -0,0 +1,412 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Opt-in trace of the signing rounds a miner runs. [`SignTraceWriter`] appends one JSON record
+//! per significant event of a round to a JSONL file, so that what the miner saw can be set
+//! against what the signers saw when the two disagree about how a round went.
+
+use std::cell::Cell;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use super::sign_round::{SignRoundCompletion, SignRoundEvent};
+
+/// The signing round a trace record belongs to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignRoundId {
+    pub signer_sighash: Sha512Trunc256Sum,
+    pub attempt: u64,
+}
+
+/// Something that happened in a signing round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignTraceEvent {
+    /// The block proposal was written to `.miners`
+    ProposalSent {
+        block_id: StacksBlockId,
+        burn_height: u64,
+        reward_cycle: u64,
+    },
+    /// A chunk was read from signer set `signer_set`'s StackerDB. `parsed` is false if it did
+    /// not hold a signer message.
+    ChunkReceived {
+        signer_set: u32,
+        slot_id: u32,
+        slot_version: u32,
+        parsed: bool,
+    },
+    /// A signer's signature share counted towards the block, bringing the weights to these
+    Accepted {
+        accepted_weight: u32,
+        rejected_weight: u32,
+    },
+    /// A signer's rejection counted against the block, bringing the weights to these
+    Rejected {
+        accepted_weight: u32,
+        rejected_weight: u32,
+    },
+    /// Signers holding at least the threshold weight have sent signature shares
+    ThresholdReached { accepted_weight: u32 },
+    /// The round ended, e.g. because it was signed or timed out
+    Ended {
+        completion: SignRoundCompletion,
+        accepted_weight: u32,
+        rejected_weight: u32,
+    },
+}
+
+/// One line of a signing round trace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignTraceRecord {
+    pub round: SignRoundId,
+    /// Microseconds since the round's trace started, by the monotonic clock
+    pub elapsed_us: u64,
+    pub event: SignTraceEvent,
+}
+
+/// Appends signing round traces to a JSONL file. Once the file would grow past `max_bytes`,
+/// it is moved to [`SignTraceWriter::rotated_path`] and a new one is started.
+#[derive(Debug, Clone)]
+pub struct SignTraceWriter {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl SignTraceWriter {
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+        }
+    }
+
+    /// The file the trace is written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where the trace is moved once it is full, replacing whatever was moved there before
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        rotated.into()
+    }
+
+    /// Start tracing the round `round`
+    pub fn start_round(&self, round: SignRoundId) -> SignRoundTrace {
+        SignRoundTrace {
+            writer: Some(self.clone()),
+            round,
+            started_at: Instant::now(),
+            weights: Cell::new((0, 0)),
+        }
+    }
+
+    /// Append `record` to the trace, rotating it first if it is full
+    pub fn append(&self, record: &SignTraceRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let size = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e),
+        };
+        let line_len = u64::try_from(line.len()).unwrap_or(u64::MAX);
+        if size > 0 && size.saturating_add(line_len) > self.max_bytes {
+            fs::rename(&self.path, self.rotated_path())?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)
+    }
+}
+
+/// The trace of one signing round. Does nothing if tracing is disabled.
+pub struct SignRoundTrace {
+    writer: Option<SignTraceWriter>,
+    round: SignRoundId,
+    started_at: Instant,
+    /// The accepted and rejected weights last traced, to tell acceptances from rejections
+    weights: Cell<(u32, u32)>,
+}
+
+impl SignRoundTrace {
+    /// The trace of a round that is not traced
+    pub fn disabled(round: SignRoundId) -> Self {
+        Self {
+            writer: None,
+            round,
+            started_at: Instant::now(),
+            weights: Cell::new((0, 0)),
+        }
+    }
+
+    /// Is this round traced at all?
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Trace `event`. A failure to write the trace is logged, but does not affect the round.
+    pub fn record(&self, event: SignTraceEvent) {
+        let Some(writer) = self.writer.as_ref() else {
+            return;
+        };
+        let record = SignTraceRecord {
+            round: self.round.clone(),
+            elapsed_us: u64::try_from(self.started_at.elapsed().as_micros()).unwrap_or(u64::MAX),
+            event,
+        };
+        if let Err(e) = writer.append(&record) {
+            warn!(
+                "Failed to write the signing round trace";
+                "path" => %writer.path().display(),
+                "err" => ?e,
+            );
+        }
+    }
+
+    /// Trace the signers' responses and the threshold from `event`
+    pub fn record_round_event(&self, event: &SignRoundEvent) {
+        match event {
+            SignRoundEvent::WeightUpdated {
+                accepted_weight,
+                rejected_weight,
+            } => {
+                let (accepted_weight, rejected_weight) = (*accepted_weight, *rejected_weight);
+                let (prior_accepted_weight, _) =
+                    self.weights.replace((accepted_weight, rejected_weight));
+                self.record(if accepted_weight > prior_accepted_weight {
+                    SignTraceEvent::Accepted {
+                        accepted_weight,
+                        rejected_weight,
+                    }
+                } else {
+                    SignTraceEvent::Rejected {
+                        accepted_weight,
+                        rejected_weight,
+                    }
+                });
+            }
+            SignRoundEvent::ThresholdReached { accepted_weight } => {
+                self.record(SignTraceEvent::ThresholdReached {
+                    accepted_weight: *accepted_weight,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Trace the end of the round
+    pub fn record_end(&self, completion: SignRoundCompletion) {
+        let (accepted_weight, rejected_weight) = self.weights.get();
+        self.record(SignTraceEvent::Ended {
+            completion,
+            accepted_weight,
+            rejected_weight,
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::io::{BufRead, BufReader};
+    use std::path::{Path, PathBuf};
+
+    use stacks_common::types::chainstate::StacksBlockId;
+    use stacks_common::util::hash::Sha512Trunc256Sum;
+
+    use super::{SignRoundId, SignTraceEvent, SignTraceRecord, SignTraceWriter};
+    use crate::nakamoto_node::sign_round::{SignRoundCompletion, SignRoundEvent};
+
+    fn trace_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sign-round-trace-{}", rand::random::<u64>()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read_trace(path: &Path) -> Vec<SignTraceRecord> {
+        BufReader::new(fs::File::open(path).unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect()
+    }
+
+    fn round_id(attempt: u64) -> SignRoundId {
+        SignRoundId {
+            signer_sighash: Sha512Trunc256Sum([0x01; 32]),
+            attempt,
+        }
+    }
+
+    #[test]
+    fn round_trace_reads_back() {
+        let dir = trace_dir();
+        let writer = SignTraceWriter::new(dir.join("trace.jsonl"), u64::MAX);
+        let trace = writer.start_round(round_id(2));
+        assert!(trace.is_enabled());
+
+        trace.record(SignTraceEvent::ProposalSent {
+            block_id: StacksBlockId([0x02; 32]),
+            burn_height: 100,
+            reward_cycle: 10,
+        });
+        trace.record(SignTraceEvent::ChunkReceived {
+            signer_set: 0,
+            slot_id: 1,
+            slot_version: 3,
+            parsed: true,
+        });
+        trace.record(SignTraceEvent::ChunkReceived {
+            signer_set: 0,
+            slot_id: 2,
+            slot_version: 1,
+            parsed: false,
+        });
+        for event in [
+            SignRoundEvent::WeightUpdated {
+                accepted_weight: 4,
+                rejected_weight: 0,
+            },
+            SignRoundEvent::WeightUpdated {
+                accepted_weight: 4,
+                rejected_weight: 1,
+            },
+            SignRoundEvent::WeightUpdated {
+                accepted_weight: 7,
+                rejected_weight: 1,
+            },
+            SignRoundEvent::ThresholdReached { accepted_weight: 7 },
+        ] {
+            trace.record_round_event(&event);
+        }
+        // signer set mismatches and the like are not traced
+        trace.record_round_event(&SignRoundEvent::SignerSetMismatch { signer_set: 1 });
+        trace.record_end(SignRoundCompletion::Signed);
+
+        let records = read_trace(writer.path());
+        let events: Vec<_> = records.iter().map(|record| record.event.clone()).collect();
+        assert_eq!(
+            events,
+            vec![
+                SignTraceEvent::ProposalSent {
+                    block_id: StacksBlockId([0x02; 32]),
+                    burn_height: 100,
+                    reward_cycle: 10,
+                },
+                SignTraceEvent::ChunkReceived {
+                    signer_set: 0,
+                    slot_id: 1,
+                    slot_version: 3,
+                    parsed: true,
+                },
+                SignTraceEvent::ChunkReceived {
+                    signer_set: 0,
+                    slot_id: 2,
+                    slot_version: 1,
+                    parsed: false,
+                },
+                SignTraceEvent::Accepted {
+                    accepted_weight: 4,
+                    rejected_weight: 0,
+                },
+                SignTraceEvent::Rejected {
+                    accepted_weight: 4,
+                    rejected_weight: 1,
+                },
+                SignTraceEvent::Accepted {
+                    accepted_weight: 7,
+                    rejected_weight: 1,
+                },
+                SignTraceEvent::ThresholdReached { accepted_weight: 7 },
+                SignTraceEvent::Ended {
+                    completion: SignRoundCompletion::Signed,
+                    accepted_weight: 7,
+                    rejected_weight: 1,
+                },
+            ]
+        );
+        assert!(records.iter().all(|record| record.round == round_id(2)));
+        assert!(records
+            .windows(2)
+            .all(|pair| pair[0].elapsed_us <= pair[1].elapsed_us));
+
+        // a later round is appended to the same trace
+        let trace = writer.start_round(round_id(3));
+        trace.record_end(SignRoundCompletion::TimedOut);
+        let records = read_trace(writer.path());
+        assert_eq!(records.len(), 9);
+        assert_eq!(records[8].round, round_id(3));
+        assert_eq!(
+            records[8].event,
+            SignTraceEvent::Ended {
+                completion: SignRoundCompletion::TimedOut,
+                accepted_weight: 0,
+                rejected_weight: 0,
+            }
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn full_trace_is_rotated() {
+        let dir = trace_dir();
+        let record = |attempt| SignTraceRecord {
+            round: round_id(attempt),
+            elapsed_us: 0,
+            event: SignTraceEvent::ThresholdReached { accepted_weight: 7 },
+        };
+        // every record is as long as this one, with its newline
+        let record_len = u64::try_from(serde_json::to_vec(&record(1)).unwrap().len() + 1).unwrap();
+        // room for two records
+        let writer = SignTraceWriter::new(dir.join("trace.jsonl"), 2 * record_len + 1);
+        for attempt in 1..=3 {
+            writer.append(&record(attempt)).unwrap();
+        }
+        assert_eq!(
+            read_trace(&writer.rotated_path()),
+            vec![record(1), record(2)]
+        );
+        assert_eq!(read_trace(writer.path()), vec![record(3)]);
+
+        // the next rotation replaces the last one
+        for attempt in 4..=5 {
+            writer.append(&record(attempt)).unwrap();
+        }
+        assert_eq!(
+            read_trace(&writer.rotated_path()),
+            vec![record(3), record(4)]
+        );
+        assert_eq!(read_trace(writer.path()), vec![record(5)]);
+
+        // a record bigger than the limit still goes into a file of its own
+        let writer = SignTraceWriter::new(dir.join("tiny.jsonl"), 1);
+        writer.append(&record(1)).unwrap();
+        writer.append(&record(2)).unwrap();
+        assert_eq!(read_trace(&writer.rotated_path()), vec![record(1)]);
+        assert_eq!(read_trace(writer.path()), vec![record(2)]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
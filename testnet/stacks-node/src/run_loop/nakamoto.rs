@@ -46,6 +46,7 @@ use crate::node::{
 };
 use crate::run_loop::neon;
 use crate::run_loop::neon::Counters;
+use crate::stackerdb_stream::start_serving_stackerdb_stream;
 use crate::syncctl::{PoxSyncWatchdog, PoxSyncWatchdogComms};
 use crate::{
     run_loop, BitcoinRegtestController, BurnchainController, Config, EventDispatcher, Keychain,
@@ -456,6 +457,9 @@ impl RunLoop {
             globals.get_miner_status(),
         );
         self.start_prometheus();
+        if let Some(stackerdb_stream_bind) = self.config.node.stackerdb_stream_bind.clone() {
+            start_serving_stackerdb_stream(stackerdb_stream_bind);
+        }
 
         // We announce a new burn block so that the chains coordinator
         // can resume prior work and handle eventual unprocessed sortitions
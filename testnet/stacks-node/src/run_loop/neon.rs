@@ -1,9 +1,9 @@
-#[cfg(test)]
-use std::sync::atomic::AtomicU64;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::sync_channel;
 use std::sync::{Arc, Mutex};
 use std::thread::JoinHandle;
+use std::time::Duration;
 use std::{cmp, thread};
 
 use libc;
@@ -39,6 +39,7 @@ use crate::node::{
     get_account_balances, get_account_lockups, get_names, get_namespaces,
     use_test_genesis_chainstate,
 };
+use crate::stackerdb_stream::start_serving_stackerdb_stream;
 use crate::syncctl::{PoxSyncWatchdog, PoxSyncWatchdogComms};
 use crate::{
     run_loop, BitcoinRegtestController, BurnchainController, Config, EventDispatcher, Keychain,
@@ -46,14 +47,9 @@ use crate::{
 
 pub const STDERR: i32 = 2;
 
-#[cfg(test)]
 #[derive(Clone)]
 pub struct RunLoopCounter(pub Arc<AtomicU64>);
 
-#[cfg(not(test))]
-#[derive(Clone)]
-pub struct RunLoopCounter();
-
 #[cfg(test)]
 const UNCONDITIONAL_CHAIN_LIVENESS_CHECK: u64 = 30;
 
@@ -61,13 +57,14 @@ const UNCONDITIONAL_CHAIN_LIVENESS_CHECK: u64 = 30;
 const UNCONDITIONAL_CHAIN_LIVENESS_CHECK: u64 = 300;
 
 impl Default for RunLoopCounter {
-    #[cfg(test)]
     fn default() -> Self {
         RunLoopCounter(Arc::new(AtomicU64::new(0)))
     }
-    #[cfg(not(test))]
-    fn default() -> Self {
-        Self()
+}
+
+impl RunLoopCounter {
+    fn get(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
     }
 }
 
@@ -80,6 +77,46 @@ impl std::ops::Deref for RunLoopCounter {
     }
 }
 
+/// How many of the most recent samples a `RunLoopLatencies` keeps
+const LATENCY_SAMPLE_WINDOW: usize = 1024;
+
+/// The most recent latencies of some run loop operation, in microseconds
+#[derive(Clone, Default)]
+pub struct RunLoopLatencies(Arc<Mutex<VecDeque<u64>>>);
+
+impl RunLoopLatencies {
+    fn record(&self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let mut samples = self
+            .0
+            .lock()
+            .expect("FATAL: poisoned run loop latencies lock");
+        if samples.len() >= LATENCY_SAMPLE_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(micros);
+    }
+
+    /// The recorded latencies, smallest first
+    fn sorted(&self) -> Vec<u64> {
+        let mut samples: Vec<_> = self
+            .0
+            .lock()
+            .expect("FATAL: poisoned run loop latencies lock")
+            .iter()
+            .copied()
+            .collect();
+        samples.sort_unstable();
+        samples
+    }
+
+    /// The nearest-rank `pct`th percentile of `sorted`, or `None` if it is empty
+    fn percentile(sorted: &[u64], pct: usize) -> Option<u64> {
+        let rank = (sorted.len() * pct).div_ceil(100).max(1);
+        sorted.get(rank - 1).copied()
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct Counters {
     pub blocks_processed: RunLoopCounter,
@@ -92,7 +129,41 @@ pub struct Counters {
     pub naka_submitted_commits: RunLoopCounter,
     pub naka_mined_blocks: RunLoopCounter,
     pub naka_proposed_blocks: RunLoopCounter,
+    pub naka_accepted_blocks: RunLoopCounter,
+    pub naka_rejected_blocks: RunLoopCounter,
+    pub naka_signing_timeouts: RunLoopCounter,
+    pub naka_signer_set_mismatches: RunLoopCounter,
+    pub naka_election_mismatches: RunLoopCounter,
+    pub naka_duplicate_signer_chunks: RunLoopCounter,
+    pub naka_oversized_signer_chunks: RunLoopCounter,
+    /// Proposals the miner wrote again because its own StackerDB did not hold them
+    pub naka_proposal_rewrites: RunLoopCounter,
+    /// Signer responses the miner dropped because the signer's key is ignored
+    pub naka_ignored_signer_responses: RunLoopCounter,
     pub naka_mined_tenures: RunLoopCounter,
+    /// How long signer chunk events waited in the StackerDB channel before the miner
+    /// dequeued them
+    pub naka_chunk_dequeue_delays: RunLoopLatencies,
+}
+
+/// A point-in-time view of the Nakamoto block signing counters.
+/// Each count is read separately, so a round that finishes while the snapshot is taken may
+/// be counted as proposed but not yet as accepted, rejected or timed out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct NakaSigningCountersSnapshot {
+    /// Blocks proposed to the signers
+    pub proposed_blocks: u64,
+    /// Proposed blocks the signers produced a valid signature for
+    pub accepted_blocks: u64,
+    /// Proposed blocks whose signing round failed
+    pub rejected_blocks: u64,
+    /// Proposed blocks whose signing round timed out
+    pub signing_timeouts: u64,
+    /// The median time signer chunk events waited in the StackerDB channel, in microseconds,
+    /// over the most recent events. `None` if none were dequeued yet.
+    pub chunk_dequeue_delay_p50_us: Option<u64>,
+    /// The 95th percentile of the same
+    pub chunk_dequeue_delay_p95_us: Option<u64>,
 }
 
 impl Counters {
@@ -100,22 +171,18 @@ impl Counters {
         Self::default()
     }
 
-    #[cfg(test)]
     fn inc(ctr: &RunLoopCounter) {
         ctr.0.fetch_add(1, Ordering::SeqCst);
     }
 
-    #[cfg(not(test))]
-    fn inc(_ctr: &RunLoopCounter) {}
+    fn add(ctr: &RunLoopCounter, value: u64) {
+        ctr.0.fetch_add(value, Ordering::SeqCst);
+    }
 
-    #[cfg(test)]
     fn set(ctr: &RunLoopCounter, value: u64) {
         ctr.0.store(value, Ordering::SeqCst);
     }
 
-    #[cfg(not(test))]
-    fn set(_ctr: &RunLoopCounter, _value: u64) {}
-
     pub fn bump_blocks_processed(&self) {
         Counters::inc(&self.blocks_processed);
     }
@@ -150,15 +217,82 @@ impl Counters {
 
     pub fn bump_naka_proposed_blocks(&self) {
         Counters::inc(&self.naka_proposed_blocks);
+        stacks::monitoring::increment_naka_blocks_proposed_counter();
+    }
+
+    pub fn bump_naka_accepted_blocks(&self) {
+        Counters::inc(&self.naka_accepted_blocks);
+        stacks::monitoring::increment_naka_blocks_accepted_counter();
+    }
+
+    pub fn bump_naka_rejected_blocks(&self) {
+        Counters::inc(&self.naka_rejected_blocks);
+        stacks::monitoring::increment_naka_blocks_rejected_counter();
+    }
+
+    pub fn bump_naka_signing_timeouts(&self) {
+        Counters::inc(&self.naka_signing_timeouts);
+        stacks::monitoring::increment_naka_signing_rounds_timed_out_counter();
+    }
+
+    pub fn bump_naka_signer_set_mismatches(&self) {
+        Counters::inc(&self.naka_signer_set_mismatches);
+    }
+
+    pub fn bump_naka_election_mismatches(&self) {
+        Counters::inc(&self.naka_election_mismatches);
+    }
+
+    pub fn add_naka_duplicate_signer_chunks(&self, count: u64) {
+        if count == 0 {
+            return;
+        }
+        Counters::add(&self.naka_duplicate_signer_chunks, count);
+        stacks::monitoring::increment_naka_duplicate_signer_chunks_counter(count);
+    }
+
+    pub fn add_naka_oversized_signer_chunks(&self, count: u64) {
+        if count == 0 {
+            return;
+        }
+        Counters::add(&self.naka_oversized_signer_chunks, count);
+        stacks::monitoring::increment_naka_oversized_signer_chunks_counter(count);
+    }
+
+    pub fn bump_naka_proposal_rewrites(&self) {
+        Counters::inc(&self.naka_proposal_rewrites);
+        stacks::monitoring::increment_naka_proposal_rewrites_counter();
+    }
+
+    pub fn bump_naka_ignored_signer_responses(&self) {
+        Counters::inc(&self.naka_ignored_signer_responses);
+        stacks::monitoring::increment_naka_ignored_signer_responses_counter();
     }
 
     pub fn bump_naka_mined_tenures(&self) {
         Counters::inc(&self.naka_mined_tenures);
     }
 
+    pub fn record_naka_chunk_dequeue_delay(&self, delay: Duration) {
+        self.naka_chunk_dequeue_delays.record(delay);
+    }
+
     pub fn set_microblocks_processed(&self, value: u64) {
         Counters::set(&self.microblocks_processed, value)
     }
+
+    /// Read the Nakamoto block signing counters
+    pub fn snapshot_naka(&self) -> NakaSigningCountersSnapshot {
+        let dequeue_delays = self.naka_chunk_dequeue_delays.sorted();
+        NakaSigningCountersSnapshot {
+            proposed_blocks: self.naka_proposed_blocks.get(),
+            accepted_blocks: self.naka_accepted_blocks.get(),
+            rejected_blocks: self.naka_rejected_blocks.get(),
+            signing_timeouts: self.naka_signing_timeouts.get(),
+            chunk_dequeue_delay_p50_us: RunLoopLatencies::percentile(&dequeue_delays, 50),
+            chunk_dequeue_delay_p95_us: RunLoopLatencies::percentile(&dequeue_delays, 95),
+        }
+    }
 }
 
 /// Coordinating a node running in neon mode.
@@ -1062,6 +1196,9 @@ impl RunLoop {
         );
         self.instantiate_pox_watchdog();
         self.start_prometheus();
+        if let Some(stackerdb_stream_bind) = self.config.node.stackerdb_stream_bind.clone() {
+            start_serving_stackerdb_stream(stackerdb_stream_bind);
+        }
 
         // We announce a new burn block so that the chains coordinator
         // can resume prior work and handle eventual unprocessed sortitions
@@ -0,0 +1,883 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Streams the StackerDB chunk events of the `.signers` and `.miners` boot contracts to
+//! connected clients as server-sent events, so that tooling can follow signer and miner
+//! messages as they arrive without implementing the event observer interface.
+//!
+//! The stream is fed from the same `EventDispatcher` call that feeds `STACKER_DB_CHANNEL`.
+//! Each client gets a bounded queue of events, and a client that falls that far behind is
+//! dropped rather than allowed to hold up the dispatcher. The number of connections, and the
+//! size of and time taken by their requests, are bounded too, so that whoever can reach the
+//! port cannot use up the node's threads or memory.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use stacks::chainstate::stacks::boot::{MINERS_NAME, SIGNERS_NAME};
+use stacks::chainstate::stacks::events::StackerDBChunksEvent;
+use stacks::libstackerdb::SlotMetadata;
+use stacks_common::util::hash::to_hex;
+
+/// The path clients request the stream from
+pub const STACKER_DB_STREAM_PATH: &str = "/v2/stackerdb/stream";
+
+/// How many events a client may fall behind by before it is dropped
+pub const STREAM_CLIENT_BUFFER: usize = 256;
+
+/// How long an idle connection goes without a keepalive comment, so that closed connections
+/// are noticed even when no chunks are written
+const STREAM_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long writing to a client may block before the client is considered gone
+const STREAM_WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a client has to send its whole request
+pub const STREAM_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many connections, streaming or still sending their request, are served at once
+pub const STREAM_MAX_CLIENTS: usize = 64;
+
+/// The longest request line a client may send, in bytes
+const STREAM_MAX_REQUEST_LINE_LEN: usize = 8192;
+
+/// The longest header line a client may send, in bytes
+const STREAM_MAX_HEADER_LEN: usize = 8192;
+
+/// How many headers a client may send
+const STREAM_MAX_HEADERS: usize = 64;
+
+/// How long, and for how many bytes, the rest of a refused request is read and discarded
+/// before the connection is closed, so that the unread request does not reset the connection
+/// before the client has read the refusal
+const STREAM_REFUSAL_LINGER: Duration = Duration::from_secs(1);
+const STREAM_REFUSAL_LINGER_BYTES: u64 = 65536;
+
+pub static STACKER_DB_STREAM: StackerDBStream = StackerDBStream::new();
+
+/// Is `contract_id` one of the boot contracts whose chunks are streamed?
+pub fn is_streamed_contract(contract_id: &QualifiedContractIdentifier) -> bool {
+    contract_id.is_boot()
+        && (contract_id.name.as_str() == MINERS_NAME || contract_id.name.starts_with(SIGNERS_NAME))
+}
+
+/// Which contracts' chunks a client asked for
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamContractFilter {
+    /// Any of the `.signers-*` boot contracts
+    Signers,
+    /// The `.miners` boot contract
+    Miners,
+    /// A single contract, by its full identifier
+    Contract(QualifiedContractIdentifier),
+}
+
+impl StreamContractFilter {
+    fn matches(&self, contract_id: &QualifiedContractIdentifier) -> bool {
+        match self {
+            Self::Signers => contract_id.name.starts_with(SIGNERS_NAME),
+            Self::Miners => contract_id.name.as_str() == MINERS_NAME,
+            Self::Contract(id) => id == contract_id,
+        }
+    }
+}
+
+/// What a client asked to be streamed
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamSubscription {
+    /// The contracts to stream chunks from. Empty means all the streamed contracts.
+    pub contracts: Vec<StreamContractFilter>,
+    /// Whether to include each chunk's hex-encoded data, rather than just its metadata
+    pub include_data: bool,
+}
+
+impl StreamSubscription {
+    /// Parse a request's query string. Recognizes any number of `contract` parameters, each
+    /// `signers`, `miners`, or a contract identifier, and an `include_data` flag.
+    pub fn from_query(query: &str) -> Result<Self, String> {
+        let mut subscription = Self::default();
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            match key {
+                "contract" => {
+                    let filter = match value {
+                        "signers" => StreamContractFilter::Signers,
+                        "miners" => StreamContractFilter::Miners,
+                        _ => {
+                            let contract_id = QualifiedContractIdentifier::parse(value)
+                                .map_err(|e| format!("Invalid contract {value:?}: {e}"))?;
+                            if !is_streamed_contract(&contract_id) {
+                                return Err(format!("Contract {contract_id} is not streamed"));
+                            }
+                            StreamContractFilter::Contract(contract_id)
+                        }
+                    };
+                    subscription.contracts.push(filter);
+                }
+                "include_data" => {
+                    subscription.include_data = match value {
+                        "" | "1" | "true" => true,
+                        "0" | "false" => false,
+                        _ => return Err(format!("Invalid include_data value {value:?}")),
+                    };
+                }
+                _ => return Err(format!("Unknown query parameter {key:?}")),
+            }
+        }
+        Ok(subscription)
+    }
+
+    /// Does the client want chunks from `contract_id`?
+    pub fn wants(&self, contract_id: &QualifiedContractIdentifier) -> bool {
+        is_streamed_contract(contract_id)
+            && (self.contracts.is_empty()
+                || self
+                    .contracts
+                    .iter()
+                    .any(|filter| filter.matches(contract_id)))
+    }
+}
+
+/// A chunk as it is streamed: its slot metadata, and its data if the client asked for it
+#[derive(Serialize)]
+struct StreamedChunk {
+    #[serde(flatten)]
+    metadata: SlotMetadata,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+}
+
+/// A chunks event as it is streamed
+#[derive(Serialize)]
+struct StreamedChunksEvent {
+    contract_id: String,
+    modified_slots: Vec<StreamedChunk>,
+}
+
+/// Encode `event` as the JSON data of a server-sent event
+fn stream_event_json(event: &StackerDBChunksEvent, include_data: bool) -> String {
+    let streamed = StreamedChunksEvent {
+        contract_id: event.contract_id.to_string(),
+        modified_slots: event
+            .modified_slots
+            .iter()
+            .map(|chunk| StreamedChunk {
+                metadata: chunk.get_slot_metadata(),
+                data: include_data.then(|| to_hex(&chunk.data)),
+            })
+            .collect(),
+    };
+    serde_json::to_string(&streamed).expect("FATAL: failed to serialize StackerDB stream event")
+}
+
+/// Reads from a client's connection until `deadline`, after which reads time out, however
+/// slowly the client trickles in its bytes
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Timed out reading from the client",
+            ));
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Read a line of at most `max_len` bytes, including its line ending. Returns `None` if the
+/// line is longer, without reading the rest of it.
+fn read_bounded_line(reader: &mut impl BufRead, max_len: usize) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    let limit = u64::try_from(max_len).expect("FATAL: line limit exceeds u64");
+    reader.take(limit + 1).read_line(&mut line)?;
+    Ok((line.len() <= max_len).then_some(line))
+}
+
+/// A connection's place in the count of connections being served, given up when dropped
+struct ConnectionSlot<'a>(usize, &'a AtomicUsize);
+
+impl<'a> ConnectionSlot<'a> {
+    /// Count a new connection in `connections`. The slot holds how many connections are
+    /// being served with it.
+    fn take(connections: &'a AtomicUsize) -> Self {
+        Self(connections.fetch_add(1, Ordering::SeqCst) + 1, connections)
+    }
+}
+
+impl Drop for ConnectionSlot<'_> {
+    fn drop(&mut self) {
+        self.1.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct StreamClient {
+    peer: SocketAddr,
+    subscription: StreamSubscription,
+    sender: SyncSender<String>,
+}
+
+/// The clients connected to the StackerDB event stream
+pub struct StackerDBStream {
+    clients: Mutex<Vec<StreamClient>>,
+    /// How many events each client may fall behind by
+    client_buffer: usize,
+    /// How many connections are served at once
+    max_clients: usize,
+    /// How long a client has to send its request
+    request_timeout: Duration,
+    /// How many connections are being served
+    connections: AtomicUsize,
+    /// Whether a thread is already serving the stream
+    serving: AtomicBool,
+}
+
+impl StackerDBStream {
+    pub const fn new() -> Self {
+        Self::with_client_buffer(STREAM_CLIENT_BUFFER)
+    }
+
+    /// A stream whose clients are dropped once they fall `client_buffer` events behind
+    pub const fn with_client_buffer(client_buffer: usize) -> Self {
+        Self::with_limits(client_buffer, STREAM_MAX_CLIENTS, STREAM_REQUEST_TIMEOUT)
+    }
+
+    /// A stream whose clients are dropped once they fall `client_buffer` events behind, which
+    /// serves at most `max_clients` connections at once and gives each `request_timeout` to
+    /// send its request
+    pub const fn with_limits(
+        client_buffer: usize,
+        max_clients: usize,
+        request_timeout: Duration,
+    ) -> Self {
+        Self {
+            clients: Mutex::new(vec![]),
+            client_buffer,
+            max_clients,
+            request_timeout,
+            connections: AtomicUsize::new(0),
+            serving: AtomicBool::new(false),
+        }
+    }
+
+    /// Is any connected client interested in chunks events from `contract_id`?
+    pub fn is_active(&self, contract_id: &QualifiedContractIdentifier) -> bool {
+        self.clients
+            .lock()
+            .expect("FATAL: poisoned StackerDBStream lock")
+            .iter()
+            .any(|client| client.subscription.wants(contract_id))
+    }
+
+    /// Add a client to the stream. The receiver disconnects once the client is dropped.
+    pub fn subscribe(
+        &self,
+        peer: SocketAddr,
+        subscription: StreamSubscription,
+    ) -> Receiver<String> {
+        let (sender, receiver) = sync_channel(self.client_buffer);
+        self.clients
+            .lock()
+            .expect("FATAL: poisoned StackerDBStream lock")
+            .push(StreamClient {
+                peer,
+                subscription,
+                sender,
+            });
+        receiver
+    }
+
+    /// Queue `event` for every client interested in it, dropping the clients that have gone
+    /// away or fallen too far behind. Never blocks on a client.
+    pub fn broadcast(&self, event: &StackerDBChunksEvent) {
+        if !is_streamed_contract(&event.contract_id) {
+            return;
+        }
+        let mut clients = self
+            .clients
+            .lock()
+            .expect("FATAL: poisoned StackerDBStream lock");
+        let mut with_data = None;
+        let mut without_data = None;
+        clients.retain(|client| {
+            if !client.subscription.wants(&event.contract_id) {
+                return true;
+            }
+            let json = if client.subscription.include_data {
+                with_data.get_or_insert_with(|| stream_event_json(event, true))
+            } else {
+                without_data.get_or_insert_with(|| stream_event_json(event, false))
+            };
+            match client.sender.try_send(json.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!(
+                        "StackerDB stream: dropping a client that fell behind";
+                        "peer" => %client.peer,
+                        "buffered_events" => self.client_buffer,
+                    );
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    debug!("StackerDB stream: client disconnected"; "peer" => %client.peer);
+                    false
+                }
+            }
+        });
+    }
+
+    /// Accept stream clients on `listener`, each on its own thread, refusing connections
+    /// beyond the client limit. Does not return unless `listener` fails.
+    pub fn serve(&'static self, listener: TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("StackerDB stream: failed to accept a connection: {e:?}");
+                    continue;
+                }
+            };
+            let slot = ConnectionSlot::take(&self.connections);
+            if slot.0 > self.max_clients {
+                debug!(
+                    "StackerDB stream: refusing a connection, too many clients";
+                    "max_clients" => self.max_clients,
+                );
+                // never block the accepting thread on the refused client
+                let _ = stream.set_nonblocking(true).and_then(|_| {
+                    write_refusal(&mut stream, "503 Service Unavailable", "Too many clients\n")
+                });
+                continue;
+            }
+            let spawned = thread::Builder::new()
+                .name("stackerdb-stream-client".into())
+                .spawn(move || {
+                    let _slot = slot;
+                    if let Err(e) = self.handle_client(stream) {
+                        debug!("StackerDB stream: client connection closed: {e:?}");
+                    }
+                });
+            if let Err(e) = spawned {
+                warn!("StackerDB stream: failed to spawn a client thread: {e:?}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a client's request within the request timeout, and parse what it subscribes to.
+    /// Returns the HTTP status and message to refuse it with if it is malformed or over the
+    /// size limits.
+    fn read_request(
+        &self,
+        stream: &TcpStream,
+    ) -> io::Result<Result<StreamSubscription, (&'static str, String)>> {
+        let mut reader = BufReader::new(DeadlineReader {
+            stream,
+            deadline: Instant::now() + self.request_timeout,
+        });
+        let Some(request_line) = read_bounded_line(&mut reader, STREAM_MAX_REQUEST_LINE_LEN)?
+        else {
+            return Ok(Err(("414 URI Too Long", "Request line too long\n".into())));
+        };
+        // the headers are not needed, but have to be read before responding
+        for _ in 0..=STREAM_MAX_HEADERS {
+            let Some(header) = read_bounded_line(&mut reader, STREAM_MAX_HEADER_LEN)? else {
+                return Ok(Err((
+                    "431 Request Header Fields Too Large",
+                    "Header too long\n".into(),
+                )));
+            };
+            if header.trim().is_empty() {
+                return Ok(parse_request_line(&request_line));
+            }
+        }
+        Ok(Err((
+            "431 Request Header Fields Too Large",
+            "Too many headers\n".into(),
+        )))
+    }
+
+    /// Read a client's request, then stream the events it asked for until it goes away or
+    /// is dropped
+    fn handle_client(&self, mut stream: TcpStream) -> io::Result<()> {
+        let peer = stream.peer_addr()?;
+        stream.set_write_timeout(Some(STREAM_WRITE_TIMEOUT))?;
+
+        let request = match self.read_request(&stream) {
+            Ok(request) => request,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+                ) =>
+            {
+                Err((
+                    "408 Request Timeout",
+                    "Timed out reading the request\n".into(),
+                ))
+            }
+            Err(e) => return Err(e),
+        };
+        let subscription = match request {
+            Ok(subscription) => subscription,
+            Err((status, message)) => {
+                debug!(
+                    "StackerDB stream: refusing a request";
+                    "peer" => %peer,
+                    "status" => status,
+                );
+                write_refusal(&mut stream, status, &message)?;
+                return linger(&stream);
+            }
+        };
+        write!(stream, "HTTP/1.1 200 OK\r\n")?;
+        write!(stream, "Content-Type: text/event-stream\r\n")?;
+        write!(stream, "Cache-Control: no-cache\r\n")?;
+        write!(stream, "Connection: close\r\n\r\n")?;
+        stream.flush()?;
+
+        info!("StackerDB stream: client connected"; "peer" => %peer, "subscription" => ?subscription);
+        let receiver = self.subscribe(peer, subscription);
+        loop {
+            match receiver.recv_timeout(STREAM_KEEPALIVE_INTERVAL) {
+                Ok(json) => write!(stream, "event: stackerdb_chunks\ndata: {json}\n\n")?,
+                Err(RecvTimeoutError::Timeout) => stream.write_all(b": keepalive\n\n")?,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+            stream.flush()?;
+        }
+    }
+}
+
+/// Parse a stream request's request line into what it subscribes to, or the HTTP status and
+/// message to refuse it with
+fn parse_request_line(request_line: &str) -> Result<StreamSubscription, (&'static str, String)> {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(("400 Bad Request", "Malformed request\n".into()));
+    };
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != STACKER_DB_STREAM_PATH {
+        return Err(("404 Not Found", format!("No such path {path}\n")));
+    }
+    if method != "GET" {
+        return Err((
+            "405 Method Not Allowed",
+            format!("Unsupported method {method}\n"),
+        ));
+    }
+    StreamSubscription::from_query(query).map_err(|e| ("400 Bad Request", format!("{e}\n")))
+}
+
+/// Write the response refusing a request with `status` and `message`
+fn write_refusal(stream: &mut TcpStream, status: &str, message: &str) -> io::Result<()> {
+    write!(stream, "HTTP/1.1 {status}\r\n")?;
+    write!(stream, "Content-Type: text/plain\r\n")?;
+    write!(stream, "Content-Length: {}\r\n", message.len())?;
+    write!(stream, "Connection: close\r\n\r\n{message}")?;
+    stream.flush()
+}
+
+/// Stop writing to a refused client, and read and discard what is left of its request for a
+/// moment, so that closing the connection with the request unread does not reset it before
+/// the client has read the refusal
+fn linger(stream: &TcpStream) -> io::Result<()> {
+    stream.shutdown(Shutdown::Write)?;
+    let reader = DeadlineReader {
+        stream,
+        deadline: Instant::now() + STREAM_REFUSAL_LINGER,
+    };
+    let _ = io::copy(
+        &mut reader.take(STREAM_REFUSAL_LINGER_BYTES),
+        &mut io::sink(),
+    );
+    Ok(())
+}
+
+/// Serve the StackerDB event stream on `bind_address` from a new thread, unless it is already
+/// being served
+pub fn start_serving_stackerdb_stream(bind_address: String) {
+    if STACKER_DB_STREAM.serving.swap(true, Ordering::SeqCst) {
+        info!("StackerDB stream is already being served, will not serve it again");
+        return;
+    }
+    thread::Builder::new()
+        .name("stackerdb-stream".to_string())
+        .spawn(move || {
+            let listener = match TcpListener::bind(&bind_address) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    warn!(
+                        "StackerDB stream: unable to bind address, will not serve the stream";
+                        "bind_address" => &bind_address,
+                        "err" => ?e,
+                    );
+                    return;
+                }
+            };
+            info!("StackerDB stream: listening on http://{bind_address}{STACKER_DB_STREAM_PATH}");
+            if let Err(e) = STACKER_DB_STREAM.serve(listener) {
+                error!("StackerDB stream: stopped serving: {e:?}");
+            }
+        })
+        .expect("FATAL: failed to start StackerDB stream thread");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::time::Instant;
+
+    use stacks::libstackerdb::StackerDBChunkData;
+    use stacks::util_lib::boot::boot_code_id;
+
+    use super::*;
+
+    fn chunks_event(contract_id: QualifiedContractIdentifier, data: &[u8]) -> StackerDBChunksEvent {
+        StackerDBChunksEvent {
+            contract_id,
+            modified_slots: vec![StackerDBChunkData::new(1, 2, data.to_vec())],
+        }
+    }
+
+    /// Serve `stream` on a local port, and return its address
+    fn serve_locally(stream: &'static StackerDBStream) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || stream.serve(listener));
+        addr
+    }
+
+    /// Connect to the stream at `addr` with `query`, and read the response headers
+    fn connect(addr: SocketAddr, query: &str) -> (String, BufReader<TcpStream>) {
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        write!(
+            client,
+            "GET {STACKER_DB_STREAM_PATH}{query} HTTP/1.1\r\nHost: {addr}\r\n\r\n"
+        )
+        .unwrap();
+        let mut reader = BufReader::new(client);
+        let mut headers = String::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            headers.push_str(&line);
+        }
+        (headers, reader)
+    }
+
+    /// Read the next event's JSON data from a connected client
+    fn next_event(reader: &mut BufReader<TcpStream>) -> serde_json::Value {
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(json) = line.strip_prefix("data: ") {
+                return serde_json::from_str(json).unwrap();
+            }
+        }
+    }
+
+    /// Wait until `stream` has a client interested in `contract_id`
+    fn wait_for_client(stream: &StackerDBStream, contract_id: &QualifiedContractIdentifier) {
+        let start = Instant::now();
+        while !stream.is_active(contract_id) {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Client never subscribed"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn clients_receive_the_contracts_they_asked_for() {
+        static STREAM: StackerDBStream = StackerDBStream::new();
+        let addr = serve_locally(&STREAM);
+        let miners_id = boot_code_id(MINERS_NAME, false);
+        let signers_id = boot_code_id(&format!("{SIGNERS_NAME}-1-0"), false);
+
+        let (headers, mut miners_client) = connect(addr, "?contract=miners");
+        assert!(headers.starts_with("HTTP/1.1 200 OK"));
+        assert!(headers.contains("Content-Type: text/event-stream"));
+        wait_for_client(&STREAM, &miners_id);
+        let (_, mut all_client) = connect(addr, "?include_data=true");
+        wait_for_client(&STREAM, &signers_id);
+
+        STREAM.broadcast(&chunks_event(signers_id.clone(), b"signer message"));
+        STREAM.broadcast(&chunks_event(miners_id.clone(), b"miner message"));
+
+        // the miners client skips the signers chunk, and only gets metadata
+        let event = next_event(&mut miners_client);
+        assert_eq!(event["contract_id"], miners_id.to_string());
+        let slot = &event["modified_slots"][0];
+        assert_eq!(slot["slot_id"], 1);
+        assert_eq!(slot["slot_version"], 2);
+        assert_eq!(
+            slot["data_hash"],
+            StackerDBChunkData::new(1, 2, b"miner message".to_vec())
+                .data_hash()
+                .to_string()
+        );
+        assert!(slot.get("data").is_none());
+
+        // the unfiltered client gets both, with their data
+        let event = next_event(&mut all_client);
+        assert_eq!(event["contract_id"], signers_id.to_string());
+        assert_eq!(
+            event["modified_slots"][0]["data"],
+            to_hex(b"signer message")
+        );
+        let event = next_event(&mut all_client);
+        assert_eq!(event["contract_id"], miners_id.to_string());
+        assert_eq!(event["modified_slots"][0]["data"], to_hex(b"miner message"));
+    }
+
+    #[test]
+    fn only_boot_signer_and_miner_contracts_are_streamed() {
+        static STREAM: StackerDBStream = StackerDBStream::new();
+        let receiver = STREAM.subscribe(
+            "127.0.0.1:1".parse().unwrap(),
+            StreamSubscription::default(),
+        );
+        let other_id =
+            QualifiedContractIdentifier::parse("ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.miners")
+                .unwrap();
+        assert!(!STREAM.is_active(&other_id));
+        assert!(STREAM.is_active(&boot_code_id(MINERS_NAME, true)));
+
+        STREAM.broadcast(&chunks_event(other_id.clone(), b"not a miner"));
+        assert!(receiver.try_recv().is_err());
+
+        assert!(StreamSubscription::from_query(&format!("contract={other_id}")).is_err());
+        assert!(StreamSubscription::from_query("contract=not-a-contract").is_err());
+        assert!(StreamSubscription::from_query("include_data=maybe").is_err());
+        assert!(StreamSubscription::from_query("unknown=1").is_err());
+        assert_eq!(
+            StreamSubscription::from_query(&format!(
+                "contract=signers&contract={}&include_data",
+                boot_code_id(MINERS_NAME, false)
+            ))
+            .unwrap(),
+            StreamSubscription {
+                contracts: vec![
+                    StreamContractFilter::Signers,
+                    StreamContractFilter::Contract(boot_code_id(MINERS_NAME, false)),
+                ],
+                include_data: true,
+            }
+        );
+    }
+
+    #[test]
+    fn slow_clients_are_dropped() {
+        static STREAM: StackerDBStream = StackerDBStream::with_client_buffer(2);
+        let miners_id = boot_code_id(MINERS_NAME, false);
+        let slow = STREAM.subscribe(
+            "127.0.0.1:1".parse().unwrap(),
+            StreamSubscription::default(),
+        );
+        let fast = STREAM.subscribe(
+            "127.0.0.1:2".parse().unwrap(),
+            StreamSubscription::default(),
+        );
+
+        for i in 0..3u8 {
+            STREAM.broadcast(&chunks_event(miners_id.clone(), &[i]));
+            fast.recv().unwrap();
+        }
+
+        // the slow client gets what was buffered before it fell behind, and nothing after
+        assert!(slow.recv().is_ok());
+        assert!(slow.recv().is_ok());
+        assert!(slow.recv().is_err());
+
+        // while the other client keeps receiving
+        STREAM.broadcast(&chunks_event(miners_id.clone(), &[3]));
+        assert!(fast.recv().is_ok());
+        assert_eq!(STREAM.clients.lock().unwrap().len(), 1);
+
+        // and a client that goes away is dropped at the next event
+        drop(fast);
+        STREAM.broadcast(&chunks_event(miners_id, &[4]));
+        assert!(STREAM.clients.lock().unwrap().is_empty());
+    }
+
+    /// Send `request` to the stream at `addr`, and return the status line of the response
+    fn refusal(addr: SocketAddr, request: &str) -> String {
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        response.lines().next().unwrap().to_string()
+    }
+
+    /// Wait until `stream` has no connections left
+    fn wait_for_no_connections(stream: &StackerDBStream) {
+        let start = Instant::now();
+        while stream.connections.load(Ordering::SeqCst) > 0 {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Connections never closed"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn bad_requests_are_refused() {
+        static STREAM: StackerDBStream = StackerDBStream::new();
+        let addr = serve_locally(&STREAM);
+
+        assert_eq!(
+            refusal(addr, "GET /v2/info HTTP/1.1\r\n\r\n"),
+            "HTTP/1.1 404 Not Found"
+        );
+        assert_eq!(
+            refusal(
+                addr,
+                &format!("POST {STACKER_DB_STREAM_PATH} HTTP/1.1\r\n\r\n")
+            ),
+            "HTTP/1.1 405 Method Not Allowed"
+        );
+        assert_eq!(
+            refusal(
+                addr,
+                &format!("GET {STACKER_DB_STREAM_PATH}?contract=nope HTTP/1.1\r\n\r\n")
+            ),
+            "HTTP/1.1 400 Bad Request"
+        );
+        assert!(STREAM.clients.lock().unwrap().is_empty());
+    }
+    #[test]
+    fn oversized_requests_are_refused() {
+        static STREAM: StackerDBStream = StackerDBStream::new();
+        let addr = serve_locally(&STREAM);
+
+        let long_query = "a".repeat(STREAM_MAX_REQUEST_LINE_LEN);
+        assert_eq!(
+            refusal(
+                addr,
+                &format!("GET {STACKER_DB_STREAM_PATH}?{long_query} HTTP/1.1\r\n\r\n")
+            ),
+            "HTTP/1.1 414 URI Too Long"
+        );
+
+        let long_header = format!("X-Long: {}\r\n", "a".repeat(STREAM_MAX_HEADER_LEN));
+        assert_eq!(
+            refusal(
+                addr,
+                &format!("GET {STACKER_DB_STREAM_PATH} HTTP/1.1\r\n{long_header}\r\n")
+            ),
+            "HTTP/1.1 431 Request Header Fields Too Large"
+        );
+
+        let many_headers = "X-Header: a\r\n".repeat(STREAM_MAX_HEADERS + 1);
+        assert_eq!(
+            refusal(
+                addr,
+                &format!("GET {STACKER_DB_STREAM_PATH} HTTP/1.1\r\n{many_headers}\r\n")
+            ),
+            "HTTP/1.1 431 Request Header Fields Too Large"
+        );
+
+        // while a request at the limits is served
+        let headers = "X-Header: a\r\n".repeat(STREAM_MAX_HEADERS);
+        let mut client = TcpStream::connect(addr).unwrap();
+        write!(
+            client,
+            "GET {STACKER_DB_STREAM_PATH} HTTP/1.1\r\n{headers}\r\n"
+        )
+        .unwrap();
+        wait_for_client(&STREAM, &boot_code_id(MINERS_NAME, false));
+    }
+
+    #[test]
+    fn slow_requests_time_out() {
+        static STREAM: StackerDBStream =
+            StackerDBStream::with_limits(STREAM_CLIENT_BUFFER, 1, Duration::from_millis(200));
+        let addr = serve_locally(&STREAM);
+
+        // a client trickling in its headers does not get more time than one that sends
+        // nothing
+        let start = Instant::now();
+        let mut client = TcpStream::connect(addr).unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        write!(client, "GET {STACKER_DB_STREAM_PATH} HTTP/1.1\r\n").unwrap();
+        for _ in 0..10 {
+            let _ = client.write_all(b"X");
+            thread::sleep(Duration::from_millis(50));
+        }
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout"));
+        assert!(start.elapsed() < STREAM_REQUEST_TIMEOUT);
+
+        // and the timed out connection no longer counts against the client limit
+        wait_for_no_connections(&STREAM);
+        let (headers, _client) = connect(addr, "");
+        assert!(headers.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[test]
+    fn connections_beyond_the_limit_are_refused() {
+        static STREAM: StackerDBStream =
+            StackerDBStream::with_limits(STREAM_CLIENT_BUFFER, 2, STREAM_REQUEST_TIMEOUT);
+        let addr = serve_locally(&STREAM);
+
+        // one streaming client, and one that has not sent its request yet
+        let (_, _streaming) = connect(addr, "");
+        let _idle = TcpStream::connect(addr).unwrap();
+        let start = Instant::now();
+        while STREAM.connections.load(Ordering::SeqCst) < 2 {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Connections never counted"
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let mut refused = TcpStream::connect(addr).unwrap();
+        refused
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        let mut response = String::new();
+        refused.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable"));
+        assert_eq!(STREAM.connections.load(Ordering::SeqCst), 2);
+    }
+}
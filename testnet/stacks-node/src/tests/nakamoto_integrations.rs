@@ -27,6 +27,7 @@ use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
 use clarity::vm::ClarityVersion;
 use http_types::headers::AUTHORIZATION;
 use lazy_static::lazy_static;
+use libsigner::protocol::untag_message;
 use libsigner::v1::messages::SignerMessage;
 use libsigner::{BlockProposal, SignerSession, StackerDBSession};
 use rand::RngCore;
@@ -41,7 +42,7 @@ use stacks::chainstate::nakamoto::test_signers::TestSigners;
 use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
 use stacks::chainstate::stacks::address::{PoxAddress, StacksAddressExtensions};
 use stacks::chainstate::stacks::boot::{
-    MINERS_NAME, SIGNERS_VOTING_FUNCTION_NAME, SIGNERS_VOTING_NAME,
+    MINERS_NAME, SIGNERS_VOTING_FUNCTION_NAME, SIGNERS_VOTING_NAME, SIGNER_BITVEC_MAX_SIZE,
 };
 use stacks::chainstate::stacks::db::StacksChainState;
 use stacks::chainstate::stacks::miner::{BlockBuilder, BlockLimitFunction, TransactionResult};
@@ -313,10 +314,14 @@ pub fn get_latest_block_proposal(
     let proposed_block = {
         let miner_contract_id = boot_code_id(MINERS_NAME, false);
         let mut miners_stackerdb = StackerDBSession::new(&conf.node.rpc_bind, miner_contract_id);
-        let message: SignerMessage = miners_stackerdb
-            .get_latest(miner_slot_id.start)
+        let chunk = miners_stackerdb
+            .get_latest_chunk(miner_slot_id.start)
             .expect("Failed to get latest chunk from the miner slot ID")
             .expect("No chunk found");
+        let (_, _, mut message_bytes) =
+            untag_message(&chunk).expect("Failed to read miner message protocol version");
+        let message = SignerMessage::consensus_deserialize(&mut message_bytes)
+            .expect("Failed to deserialize miner message");
         let SignerMessage::Packet(packet) = message else {
             panic!("Expected a signer message packet. Got {message:?}");
         };
@@ -1016,6 +1021,7 @@ fn simple_neon_integration() {
 
     let mut run_loop = boot_nakamoto::BootRunLoop::new(naka_conf.clone()).unwrap();
     let run_loop_stopper = run_loop.get_termination_switch();
+    let counters = run_loop.counters();
     let Counters {
         blocks_processed,
         naka_submitted_vrfs: vrfs_submitted,
@@ -1178,6 +1184,16 @@ fn simple_neon_integration() {
     assert!(tip.anchored_header.as_stacks_nakamoto().is_some());
     assert!(tip.stacks_block_height >= block_height_pre_3_0 + 30);
 
+    // every proposal was signed through the test signing channel
+    let signing_counters = counters.snapshot_naka();
+    assert!(signing_counters.proposed_blocks >= 30);
+    assert_eq!(
+        signing_counters.accepted_blocks,
+        signing_counters.proposed_blocks
+    );
+    assert_eq!(signing_counters.rejected_blocks, 0);
+    assert_eq!(signing_counters.signing_timeouts, 0);
+
     // make sure prometheus returns an updated height
     #[cfg(feature = "monitoring_prom")]
     {
@@ -1191,6 +1207,11 @@ fn simple_neon_integration() {
             .unwrap();
         let expected_result = format!("stacks_node_stacks_tip_height {}", tip.stacks_block_height);
         assert!(res.contains(&expected_result));
+        let expected_result = format!(
+            "stacks_node_naka_blocks_accepted_total {}",
+            signing_counters.accepted_blocks
+        );
+        assert!(res.contains(&expected_result));
     }
 
     coord_channel
@@ -2243,8 +2264,10 @@ fn miner_writes_proposed_block_to_stackerdb() {
 
     let signer_bitvec_str = observed_block.signer_bitvec.clone();
     let signer_bitvec_bytes = hex_bytes(&signer_bitvec_str).unwrap();
-    let signer_bitvec = BitVec::<4000>::consensus_deserialize(&mut signer_bitvec_bytes.as_slice())
-        .expect("Failed to deserialize signer bitvec");
+    let signer_bitvec = BitVec::<SIGNER_BITVEC_MAX_SIZE>::consensus_deserialize(
+        &mut signer_bitvec_bytes.as_slice(),
+    )
+    .expect("Failed to deserialize signer bitvec");
 
     assert_eq!(signer_bitvec.len(), 1);
 
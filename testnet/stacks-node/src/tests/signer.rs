@@ -16,7 +16,7 @@ use stacks::chainstate::coordinator::comm::CoordinatorChannels;
 use stacks::chainstate::nakamoto::signer_set::NakamotoSigners;
 use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
 use stacks::chainstate::stacks::boot::{
-    SIGNERS_NAME, SIGNERS_VOTING_FUNCTION_NAME, SIGNERS_VOTING_NAME,
+    SIGNERS_NAME, SIGNERS_VOTING_FUNCTION_NAME, SIGNERS_VOTING_NAME, SIGNER_BITVEC_MAX_SIZE,
 };
 use stacks::chainstate::stacks::events::StackerDBChunksEvent;
 use stacks::chainstate::stacks::miner::TransactionEvent;
@@ -127,7 +127,7 @@ impl SignerTest {
                 info!("spawning signer");
                 let signer_config =
                     SignerConfig::load_from_str(&signer_configs[i as usize]).unwrap();
-                SpawnedSigner::from(signer_config)
+                SpawnedSigner::try_from(signer_config).unwrap()
             })
             .collect();
 
@@ -713,7 +713,7 @@ impl SignerTest {
             .into_iter()
             .map(|unsigned| {
                 invalid_stacks_client
-                    .sign_transaction(unsigned)
+                    .sign_transaction(unsigned, reward_cycle)
                     .expect("Failed to sign transaction")
             })
             .collect()
@@ -751,7 +751,7 @@ impl SignerTest {
 
         info!("Restarting signer");
         let config = SignerConfig::load_from_str(&signer_config).unwrap();
-        let signer = SpawnedSigner::from(config);
+        let signer = SpawnedSigner::try_from(config).unwrap();
         self.spawned_signers.insert(signer_idx, signer);
     }
 
@@ -1035,11 +1035,17 @@ fn stackerdb_sign_request_rejected() {
         block: block1.clone(),
         burn_height: 0,
         reward_cycle,
+        miner_view: None,
+        tenure_extend: None,
+        attempt: None,
     };
     let block_proposal_2 = BlockProposal {
         block: block2.clone(),
         burn_height: 0,
         reward_cycle,
+        miner_view: None,
+        tenure_extend: None,
+        attempt: None,
     };
     // Determine the coordinator of the current node height
     info!("signer_runloop: spawn send commands to do sign");
@@ -1571,7 +1577,7 @@ fn stackerdb_sign_after_signer_reboot() {
     // Check that the latest block's bitvec is all 1's
     assert_eq!(
         last_block.signer_bitvec,
-        serde_json::to_value(BitVec::<4000>::ones(num_signers as u16).unwrap())
+        serde_json::to_value(BitVec::<SIGNER_BITVEC_MAX_SIZE>::ones(num_signers as u16).unwrap())
             .expect("Failed to serialize BitVec")
             .as_str()
             .expect("Failed to serialize BitVec")